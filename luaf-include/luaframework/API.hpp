@@ -6,6 +6,7 @@
 #include <format>
 #include <memory>
 #include <stdexcept>
+#include <utility>
 
 #define LUAF_API __declspec(dllexport)
 
@@ -14,6 +15,65 @@ namespace luaf
 
 	typedef void (*OnLuaStateCreatedCb)(void*);
 	typedef void (*OnLuaStateDestroyedCb)(void*);
+	// (ImGuiContext*, user_data)
+	typedef void (*RenderCallback)(void*, void*);
+	// (args, arg_count, user_data), called before the original function
+	typedef void (*BridgeHookCallback)(const uint64_t*, uint32_t, void*);
+	// (callback, user_data) -> success
+	typedef bool (*BridgeSubscribeFn)(BridgeHookCallback, void*);
+
+	// (phase, user_data)
+	typedef void (*InitPhaseCallback)(uint32_t, void*);
+
+	// (hwnd, msg, wparam, lparam, result, user_data)
+	// Return true to consume the message, the value written to result is returned from the window procedure.
+	typedef bool (*WndProcFilter)(void*, uint32_t, uintptr_t, intptr_t, intptr_t*, void*);
+
+	// (address, user_data) -> valid
+	// Validates an address resolved from a candidate pattern, return false to try the next pattern.
+	typedef bool (*AddressValidator)(void*, void*);
+
+	// Core function "RenderCore::CreateTexture", registered by the render backend extension via add_core_function.
+	// (rgba, width, height) -> ImTextureID, 0 on failure
+	// Called on the render thread before the frame is rendered. rgba is tightly packed RGBA8 pixel data of
+	// width * height * 4 bytes, only valid during the call. Width and height are between 1 and 4096.
+	typedef uint64_t (*RenderCoreCreateTextureFn)(const uint8_t*, uint32_t, uint32_t);
+
+	// Core function "RenderCore::DestroyTexture", registered together with "RenderCore::CreateTexture".
+	// (texture_id)
+	// Called on the render thread before the frame is rendered, only with ids returned by CreateTexture,
+	// at most once per id. Textures are only used if both functions are registered.
+	typedef void (*RenderCoreDestroyTextureFn)(uint64_t);
+
+	// Candidate pattern of a managed address, see Api::set_managed_address_ex.
+	typedef struct ManagedPattern {
+		const char* pattern;
+		uint32_t pattern_len;
+		int32_t offset;
+	} ManagedPattern;
+
+	// Initialization phases, entered in order.
+	enum class InitPhase : uint32_t
+	{
+		// Extensions are being loaded, ExtInitialize is called in this phase.
+		Extensions = 0,
+		// All extensions are loaded, addresses provided by the compatibility bridge are available.
+		Addresses = 1,
+		// Scripts are being loaded.
+		Scripts = 2,
+		// Initialization is finished.
+		Ready = 3,
+		NotStarted = UINT32_MAX,
+	};
+
+	enum class MouseButton : uint32_t
+	{
+		Left = 0,
+		Right = 1,
+		Middle = 2,
+		X1 = 3,
+		X2 = 4,
+	};
 
 	typedef struct CoreAPIFunctions {
 		void (*add_core_function)(const char*, uint32_t, const void*);
@@ -34,6 +94,11 @@ namespace luaf
 		bool (*is_key_down)(uint32_t);
 		bool (*is_controller_pressed)(uint32_t);
 		bool (*is_controller_down)(uint32_t);
+		bool (*is_mouse_pressed)(uint32_t);
+		bool (*is_mouse_down)(uint32_t);
+		void (*get_mouse_position)(int32_t* x, int32_t* y);
+		float (*get_mouse_wheel)();
+		bool (*get_mouse_drag)(uint32_t button, int32_t* dx, int32_t* dy);
 	} CoreAPIInput;
 
 	typedef struct CoreAPIParam {
@@ -121,6 +186,118 @@ namespace luaf
 			m_param->functions->set_managed_address(name.data(), 0, pattern.data(), 0, offset);
 		}
 
+		bool is_mouse_pressed(MouseButton button) {
+			return m_param->input->is_mouse_pressed(static_cast<uint32_t>(button));
+		}
+
+		bool is_mouse_down(MouseButton button) {
+			return m_param->input->is_mouse_down(static_cast<uint32_t>(button));
+		}
+
+		// Cursor position in the client area of the game window.
+		std::pair<int32_t, int32_t> mouse_position() {
+			int32_t x = 0, y = 0;
+			m_param->input->get_mouse_position(&x, &y);
+			return { x, y };
+		}
+
+		// Wheel movement in notches since the last frame, positive when scrolled up.
+		float mouse_wheel() {
+			return m_param->input->get_mouse_wheel();
+		}
+
+		// Movement since the button was pressed, false if the button is not held.
+		bool mouse_drag(MouseButton button, int32_t& dx, int32_t& dy) {
+			return m_param->input->get_mouse_drag(static_cast<uint32_t>(button), &dx, &dy);
+		}
+
+		// Set a managed address with candidate patterns, tried in order until one is found.
+		// The first pattern is the primary pattern. `validator` is called with each resolved address
+		// and may reject it to try the next pattern.
+		// Returns false if the framework does not support candidate patterns.
+		bool set_managed_address_ex(std::string_view name, const std::vector<ManagedPattern>& patterns, AddressValidator validator = nullptr, void* user_data = nullptr) {
+			auto fun = reinterpret_cast<bool(*)(const char*, uint32_t, const ManagedPattern*, uint32_t, AddressValidator, void*)>(m_param->functions->get_core_function("Address::set_managed_address_ex", 0));
+			return fun != nullptr && fun(name.data(), static_cast<uint32_t>(name.size()), patterns.data(), static_cast<uint32_t>(patterns.size()), validator, user_data);
+		}
+
+		// Register a render callback invoked while the overlay is shown. Returns 0 on failure.
+		uint64_t add_on_imgui_render(RenderCallback callback, void* user_data) {
+			auto fun = reinterpret_cast<uint64_t(*)(RenderCallback, void*)>(m_param->functions->get_core_function("Render::add_on_imgui_render", 0));
+			return fun != nullptr ? fun(callback, user_data) : 0;
+		}
+
+		// Register a render callback invoked every frame. Returns 0 on failure.
+		uint64_t add_on_draw_render(RenderCallback callback, void* user_data) {
+			auto fun = reinterpret_cast<uint64_t(*)(RenderCallback, void*)>(m_param->functions->get_core_function("Render::add_on_draw_render", 0));
+			return fun != nullptr ? fun(callback, user_data) : 0;
+		}
+
+		bool remove_render_callback(uint64_t handle) {
+			auto fun = reinterpret_cast<bool(*)(uint64_t)>(m_param->functions->get_core_function("Render::remove_render_callback", 0));
+			return fun != nullptr && fun(handle);
+		}
+
+		// Dispatch a Lua event to callbacks registered by `core.on_event(name, fn)`.
+		// Can be called from any thread, the event is dispatched on the game thread in the next frame.
+		// Pass an empty payload to call the callback without argument.
+		bool dispatch_lua_event(std::string_view name, std::string_view payload = {}) {
+			auto fun = reinterpret_cast<void(*)(const char*, uint32_t, const char*, uint32_t)>(m_param->functions->get_core_function("Lua::dispatch_event", 0));
+			if (fun == nullptr) {
+				return false;
+			}
+			fun(name.data(), static_cast<uint32_t>(name.size()), payload.empty() ? nullptr : payload.data(), static_cast<uint32_t>(payload.size()));
+			return true;
+		}
+
+		// Register a callback invoked when LuaFramework enters an initialization phase.
+		// The callback is called immediately with the current phase if initialization has started.
+		bool on_init_phase(InitPhaseCallback callback, void* user_data) {
+			auto fun = reinterpret_cast<void(*)(InitPhaseCallback, void*)>(m_param->functions->get_core_function("Core::on_init_phase", 0));
+			if (fun == nullptr) {
+				return false;
+			}
+			fun(callback, user_data);
+			return true;
+		}
+
+		InitPhase init_phase() {
+			auto fun = reinterpret_cast<uint32_t(*)()>(m_param->functions->get_core_function("Core::get_init_phase", 0));
+			return fun != nullptr ? static_cast<InitPhase>(fun()) : InitPhase::NotStarted;
+		}
+
+		// Handle (HWND) of the game window, nullptr if the window is not created yet.
+		void* game_window() {
+			auto fun = reinterpret_cast<void*(*)()>(m_param->functions->get_core_function("Window::get_handle", 0));
+			return fun != nullptr ? fun() : nullptr;
+		}
+
+		// Register a filter for messages sent to the game window.
+		// Filters are called in registration order before the original window procedure.
+		// Returns the filter id, 0 if the game window is unavailable.
+		uint32_t add_message_filter(WndProcFilter filter, void* user_data) {
+			auto fun = reinterpret_cast<uint32_t(*)(WndProcFilter, void*)>(m_param->functions->get_core_function("Window::add_message_filter", 0));
+			return fun != nullptr ? fun(filter, user_data) : 0;
+		}
+
+		bool remove_message_filter(uint32_t id) {
+			auto fun = reinterpret_cast<bool(*)(uint32_t)>(m_param->functions->get_core_function("Window::remove_message_filter", 0));
+			return fun != nullptr && fun(id);
+		}
+
+		// Compatibility bridge: provide an address already resolved by another framework.
+		// Should be called in ExtInitialize.
+		bool bridge_provide_address(std::string_view name, void* address) {
+			auto fun = reinterpret_cast<bool(*)(const char*, uint32_t, void*)>(m_param->functions->get_core_function("Bridge::provide_address", 0));
+			return fun != nullptr && fun(name.data(), static_cast<uint32_t>(name.size()), address);
+		}
+
+		// Compatibility bridge: register a hook owned by another framework.
+		// LuaFramework subscribes through `subscribe` instead of hooking `target` again.
+		bool bridge_register_hook(std::string_view name, void* target, BridgeSubscribeFn subscribe) {
+			auto fun = reinterpret_cast<bool(*)(const char*, uint32_t, void*, BridgeSubscribeFn)>(m_param->functions->get_core_function("Bridge::register_hook", 0));
+			return fun != nullptr && fun(name.data(), static_cast<uint32_t>(name.size()), target, subscribe);
+		}
+
 		template<typename T>
 		T* get_or_set_managed_address(std::string_view name, std::string_view pattern, int offset) {
 			T* result = get_managed_address<T>(name);
@@ -3,6 +3,76 @@ use std::ffi::c_void;
 
 pub type OnLuaStateCreatedCb = unsafe extern "C" fn(lua_state: *mut c_void);
 pub type OnLuaStateDestroyedCb = unsafe extern "C" fn(lua_state: *mut c_void);
+/// Render callback, `imgui_ctx` is the current `ImGuiContext`.
+pub type RenderCallback = unsafe extern "C" fn(imgui_ctx: *mut c_void, user_data: *mut c_void);
+/// Compatibility bridge hook callback, called before the original function.
+///
+/// `args` points to the first `arg_count` integer arguments of the hooked function.
+pub type BridgeHookCallback =
+    unsafe extern "C" fn(args: *const u64, arg_count: u32, user_data: *mut c_void);
+/// Provided by the adapter extension, subscribes a callback to a hook it owns.
+pub type BridgeSubscribeFn =
+    extern "C" fn(callback: BridgeHookCallback, user_data: *mut c_void) -> bool;
+
+/// Initialization phase callback, `phase` is the value of [InitPhase].
+pub type InitPhaseCallback = unsafe extern "C" fn(phase: u32, user_data: *mut c_void);
+
+/// Window message filter.
+///
+/// Return `true` to consume the message, the value written to `result` is returned from the
+/// window procedure. Return `false` to pass the message to the next filter.
+pub type WndProcFilter = unsafe extern "C" fn(
+    hwnd: *mut c_void,
+    msg: u32,
+    wparam: usize,
+    lparam: isize,
+    result: *mut isize,
+    user_data: *mut c_void,
+) -> bool;
+
+/// Validates an address resolved from a candidate pattern.
+///
+/// Return `false` to reject the address and try the next candidate pattern.
+pub type AddressValidator =
+    unsafe extern "C" fn(address: *mut c_void, user_data: *mut c_void) -> bool;
+
+/// Candidate pattern of a managed address, see [`CoreFunctions::set_managed_address_ex`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ManagedPattern {
+    pub pattern: *const u8,
+    pub pattern_len: u32,
+    pub offset: i32,
+}
+
+/// Initialization phases, entered in order.
+///
+/// - `Extensions`: extensions are being loaded, `ExtInitialize` is called in this phase.
+///   Singletons may be unavailable if extensions are loaded on the init thread.
+/// - `Addresses`: all extensions are loaded, addresses provided by the compatibility bridge
+///   are available. Patch profiles and framework hooks are resolved in this phase.
+/// - `Scripts`: scripts are being loaded.
+/// - `Ready`: initialization is finished.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum InitPhase {
+    Extensions = 0,
+    Addresses = 1,
+    Scripts = 2,
+    Ready = 3,
+}
+
+impl InitPhase {
+    pub fn from_repr(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(InitPhase::Extensions),
+            1 => Some(InitPhase::Addresses),
+            2 => Some(InitPhase::Scripts),
+            3 => Some(InitPhase::Ready),
+            _ => None,
+        }
+    }
+}
 
 #[repr(C)]
 pub struct CoreAPIParam {
@@ -45,6 +115,11 @@ pub struct CoreAPIInput {
     pub is_key_down: extern "C" fn(key: u32) -> bool,
     pub is_controller_pressed: extern "C" fn(button: u32) -> bool,
     pub is_controller_down: extern "C" fn(button: u32) -> bool,
+    pub is_mouse_pressed: extern "C" fn(button: u32) -> bool,
+    pub is_mouse_down: extern "C" fn(button: u32) -> bool,
+    pub get_mouse_position: extern "C" fn(x: *mut i32, y: *mut i32),
+    pub get_mouse_wheel: extern "C" fn() -> f32,
+    pub get_mouse_drag: extern "C" fn(button: u32, dx: *mut i32, dy: *mut i32) -> bool,
 }
 
 #[repr(i32)]
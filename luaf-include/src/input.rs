@@ -4,7 +4,19 @@ use strum::{EnumIter, FromRepr, IntoStaticStr};
 use crate::CoreAPIInput;
 
 #[repr(u32)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, FromRepr)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    FromRepr,
+    EnumIter,
+    IntoStaticStr,
+)]
 pub enum ControllerButton {
     Share = 1 << 0,
     L3 = 1 << 1,
@@ -202,6 +214,28 @@ pub enum KeyCode {
     MediaSelect = 237, // 0x000000ED
 }
 
+#[repr(u32)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    FromRepr,
+    EnumIter,
+    IntoStaticStr,
+)]
+pub enum MouseButton {
+    Left = 0,
+    Right = 1,
+    Middle = 2,
+    X1 = 3,
+    X2 = 4,
+}
+
 #[repr(transparent)]
 pub struct Input<'a>(pub &'a CoreAPIInput);
 
@@ -213,6 +247,10 @@ impl Input<'_> {
     pub fn controller(&self) -> InputController<'_> {
         InputController(self.0)
     }
+
+    pub fn mouse(&self) -> InputMouse<'_> {
+        InputMouse(self.0)
+    }
 }
 
 #[repr(transparent)]
@@ -240,3 +278,34 @@ impl InputController<'_> {
         (self.0.is_controller_down)(button as u32)
     }
 }
+
+#[repr(transparent)]
+pub struct InputMouse<'a>(&'a CoreAPIInput);
+
+impl InputMouse<'_> {
+    /// Cursor position in game window client coordinates.
+    pub fn position(&self) -> (i32, i32) {
+        let (mut x, mut y) = (0, 0);
+        (self.0.get_mouse_position)(&mut x, &mut y);
+        (x, y)
+    }
+
+    pub fn is_pressed(&self, button: MouseButton) -> bool {
+        (self.0.is_mouse_pressed)(button as u32)
+    }
+
+    pub fn is_down(&self, button: MouseButton) -> bool {
+        (self.0.is_mouse_down)(button as u32)
+    }
+
+    /// Wheel movement in notches during the last frame, positive when scrolling up.
+    pub fn wheel_delta(&self) -> f32 {
+        (self.0.get_mouse_wheel)()
+    }
+
+    /// Offset from the position where the button was pressed, if it is being dragged.
+    pub fn drag_delta(&self, button: MouseButton) -> Option<(i32, i32)> {
+        let (mut dx, mut dy) = (0, 0);
+        (self.0.get_mouse_drag)(button as u32, &mut dx, &mut dy).then_some((dx, dy))
+    }
+}
@@ -5,7 +5,7 @@ pub mod input;
 
 use std::{ffi::c_void, ptr::addr_of_mut};
 
-pub use input::{ControllerButton, KeyCode};
+pub use input::{ControllerButton, KeyCode, MouseButton};
 
 mod ext;
 pub use ext::*;
@@ -111,6 +111,186 @@ impl CoreFunctions<'_> {
         );
     }
 
+    /// Set a managed address with candidate patterns, tried in order until one is found.
+    ///
+    /// `patterns` are `(pattern, offset)` pairs, the first one is the primary pattern.
+    /// `validator` is called with each resolved address, and may reject it to try the next
+    /// pattern. Returns `false` if the framework does not support candidate patterns.
+    pub fn set_managed_address_ex(
+        &self,
+        name: &str,
+        patterns: &[(&str, i32)],
+        validator: Option<AddressValidator>,
+        user_data: *mut c_void,
+    ) -> bool {
+        let Some(fun) = self.get_core_function("Address::set_managed_address_ex") else {
+            return false;
+        };
+        let fun: extern "C" fn(
+            *const u8,
+            u32,
+            *const ManagedPattern,
+            u32,
+            Option<AddressValidator>,
+            *mut c_void,
+        ) -> bool = unsafe { std::mem::transmute(fun) };
+        let candidates = patterns
+            .iter()
+            .map(|(pattern, offset)| ManagedPattern {
+                pattern: pattern.as_ptr(),
+                pattern_len: pattern.len() as u32,
+                offset: *offset,
+            })
+            .collect::<Vec<_>>();
+        let name_bytes = name.as_bytes();
+        fun(
+            name_bytes.as_ptr(),
+            name_bytes.len() as u32,
+            candidates.as_ptr(),
+            candidates.len() as u32,
+            validator,
+            user_data,
+        )
+    }
+
+    /// Register a render callback invoked while the overlay is shown.
+    ///
+    /// Returns a handle for [`Self::remove_render_callback`].
+    pub fn add_on_imgui_render(&self, cb: RenderCallback, user_data: *mut c_void) -> Option<u64> {
+        let fun = self.get_core_function("Render::add_on_imgui_render")?;
+        let fun: extern "C" fn(RenderCallback, *mut c_void) -> u64 =
+            unsafe { std::mem::transmute(fun) };
+        Some(fun(cb, user_data))
+    }
+
+    /// Register a render callback invoked every frame.
+    ///
+    /// Returns a handle for [`Self::remove_render_callback`].
+    pub fn add_on_draw_render(&self, cb: RenderCallback, user_data: *mut c_void) -> Option<u64> {
+        let fun = self.get_core_function("Render::add_on_draw_render")?;
+        let fun: extern "C" fn(RenderCallback, *mut c_void) -> u64 =
+            unsafe { std::mem::transmute(fun) };
+        Some(fun(cb, user_data))
+    }
+
+    pub fn remove_render_callback(&self, handle: u64) -> bool {
+        let Some(fun) = self.get_core_function("Render::remove_render_callback") else {
+            return false;
+        };
+        let fun: extern "C" fn(u64) -> bool = unsafe { std::mem::transmute(fun) };
+        fun(handle)
+    }
+
+    /// Dispatch a Lua event to callbacks registered by `core.on_event(name, fn)`.
+    ///
+    /// Can be called from any thread, the event is dispatched on the game thread in the next frame.
+    pub fn dispatch_lua_event(&self, name: &str, payload: Option<&str>) -> bool {
+        let Some(fun) = self.get_core_function("Lua::dispatch_event") else {
+            return false;
+        };
+        let fun: extern "C" fn(*const u8, u32, *const u8, u32) =
+            unsafe { std::mem::transmute(fun) };
+        let name_bytes = name.as_bytes();
+        // length 0 means a C string
+        let (payload_ptr, payload_len) = match payload {
+            Some("") => (c"".as_ptr() as *const u8, 0),
+            Some(payload) => (payload.as_ptr(), payload.len() as u32),
+            None => (std::ptr::null(), 0),
+        };
+        fun(
+            name_bytes.as_ptr(),
+            name_bytes.len() as u32,
+            payload_ptr,
+            payload_len,
+        );
+        true
+    }
+
+    /// Register a callback invoked when LuaFramework enters an initialization phase.
+    ///
+    /// The callback is called immediately with the current phase if initialization has started.
+    pub fn on_init_phase(&self, cb: InitPhaseCallback, user_data: *mut c_void) -> bool {
+        let Some(fun) = self.get_core_function("Core::on_init_phase") else {
+            return false;
+        };
+        let fun: extern "C" fn(InitPhaseCallback, *mut c_void) =
+            unsafe { std::mem::transmute(fun) };
+        fun(cb, user_data);
+        true
+    }
+
+    /// Current initialization phase, `None` if initialization has not started.
+    pub fn init_phase(&self) -> Option<InitPhase> {
+        let fun = self.get_core_function("Core::get_init_phase")?;
+        let fun: extern "C" fn() -> u32 = unsafe { std::mem::transmute(fun) };
+        InitPhase::from_repr(fun())
+    }
+
+    /// Handle (HWND) of the game window, `None` if the window is not created yet.
+    pub fn game_window(&self) -> Option<*mut c_void> {
+        let fun = self.get_core_function("Window::get_handle")?;
+        let fun: extern "C" fn() -> *mut c_void = unsafe { std::mem::transmute(fun) };
+        let hwnd = fun();
+        (!hwnd.is_null()).then_some(hwnd)
+    }
+
+    /// Register a filter for messages sent to the game window.
+    ///
+    /// Filters are called in registration order before the original window procedure.
+    /// Returns the filter id used to remove it, `None` if the game window is unavailable.
+    pub fn add_message_filter(&self, filter: WndProcFilter, user_data: *mut c_void) -> Option<u32> {
+        let fun = self.get_core_function("Window::add_message_filter")?;
+        let fun: extern "C" fn(WndProcFilter, *mut c_void) -> u32 =
+            unsafe { std::mem::transmute(fun) };
+        let id = fun(filter, user_data);
+        (id != 0).then_some(id)
+    }
+
+    pub fn remove_message_filter(&self, id: u32) -> bool {
+        let Some(fun) = self.get_core_function("Window::remove_message_filter") else {
+            return false;
+        };
+        let fun: extern "C" fn(u32) -> bool = unsafe { std::mem::transmute(fun) };
+        fun(id)
+    }
+
+    /// Compatibility bridge: provide an address already resolved by another framework.
+    ///
+    /// Overrides the managed address with the same name. Should be called in `ExtInitialize`.
+    pub fn bridge_provide_address(&self, name: &str, address: *mut c_void) -> bool {
+        let Some(fun) = self.get_core_function("Bridge::provide_address") else {
+            return false;
+        };
+        let fun: extern "C" fn(*const u8, u32, *mut c_void) -> bool =
+            unsafe { std::mem::transmute(fun) };
+        let name_bytes = name.as_bytes();
+        fun(name_bytes.as_ptr(), name_bytes.len() as u32, address)
+    }
+
+    /// Compatibility bridge: register a hook owned by another framework.
+    ///
+    /// LuaFramework subscribes through `subscribe` instead of hooking `target` again.
+    /// Should be called in `ExtInitialize`.
+    pub fn bridge_register_hook(
+        &self,
+        name: &str,
+        target: *mut c_void,
+        subscribe: BridgeSubscribeFn,
+    ) -> bool {
+        let Some(fun) = self.get_core_function("Bridge::register_hook") else {
+            return false;
+        };
+        let fun: extern "C" fn(*const u8, u32, *mut c_void, BridgeSubscribeFn) -> bool =
+            unsafe { std::mem::transmute(fun) };
+        let name_bytes = name.as_bytes();
+        fun(
+            name_bytes.as_ptr(),
+            name_bytes.len() as u32,
+            target,
+            subscribe,
+        )
+    }
+
     pub fn get_or_set_managed_address(
         &self,
         name: &str,
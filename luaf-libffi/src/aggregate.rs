@@ -0,0 +1,187 @@
+//! 结构体类型
+//!
+//! 结构体按值传递时需要 libffi 的聚合类型。类型句柄由框架创建并持有，
+//! 调用时与标量类型一起传入：小于 256 的值为 [`ArgType`]，否则为结构体类型句柄。
+
+use libffi::high::FfiAbi;
+use std::ffi::c_void;
+
+use crate::call::{AnyVar, ArgType, CallError};
+
+pub struct StructType {
+    ffi_type: libffi::raw::ffi_type,
+    /// 以空指针结尾的成员类型，ffi_type 引用该数组
+    elements: Vec<*mut libffi::raw::ffi_type>,
+}
+
+/// 创建结构体类型，`field_types` 为展开后的成员类型，失败时返回空指针
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn NewStructType(field_types: *const i32, len: usize) -> *mut c_void {
+    if len == 0 {
+        return std::ptr::null_mut();
+    }
+    let mut elements = vec![];
+    for i in 0..len {
+        let field_type_int = field_types.add(i).read();
+        match ArgType::from_repr(field_type_int) {
+            Some(ArgType::Void) | None => {
+                CallError::InvalidFFIArgType(field_type_int).write_last_error();
+                return std::ptr::null_mut();
+            }
+            Some(field_type) => elements.push(field_type.as_ffi_type()),
+        }
+    }
+    elements.push(std::ptr::null_mut());
+
+    let mut struct_type = Box::new(StructType {
+        ffi_type: Default::default(),
+        elements,
+    });
+    // size 和 alignment 由 prep_cif 计算
+    struct_type.ffi_type.type_ = libffi::raw::FFI_TYPE_STRUCT as u16;
+    struct_type.ffi_type.elements = struct_type.elements.as_mut_ptr();
+
+    Box::into_raw(struct_type) as *mut c_void
+}
+
+/// 释放结构体类型，使用该类型的调用结束后才能释放
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn FreeStructType(handle: *mut c_void) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle as *mut StructType));
+    }
+}
+
+unsafe fn resolve_type(raw: usize) -> Result<*mut libffi::raw::ffi_type, CallError> {
+    if raw < 256 {
+        return ArgType::from_repr(raw as i32)
+            .map(|arg_type| arg_type.as_ffi_type())
+            .ok_or(CallError::InvalidFFIArgType(raw as i32));
+    }
+    Ok(&mut (*(raw as *mut StructType)).ffi_type)
+}
+
+/// 调用函数，支持按值传递和返回结构体
+///
+/// `args` 为各参数值的指针，结构体参数指向结构体数据。
+/// `ret_val` 指向的缓冲区不小于返回值大小，且至少 8 字节。
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn CallNativeFunctionEx(
+    ptr: *mut c_void,
+    arg_types: *const usize,
+    args: *mut *mut c_void,
+    args_len: usize,
+    ret_type: usize,
+    ret_val: *mut c_void,
+    abi: FfiAbi,
+) -> i32 {
+    let mut ffi_arg_types = vec![];
+    for i in 0..args_len {
+        match resolve_type(arg_types.add(i).read()) {
+            Ok(ffi_type) => ffi_arg_types.push(ffi_type),
+            Err(err) => {
+                err.write_last_error();
+                return err.as_code();
+            }
+        }
+    }
+    let ffi_ret_type = match resolve_type(ret_type) {
+        Ok(ffi_type) => ffi_type,
+        Err(err) => {
+            err.write_last_error();
+            return err.as_code();
+        }
+    };
+
+    let mut cif: libffi::raw::ffi_cif = Default::default();
+    let result = libffi::low::prep_cif(
+        &mut cif,
+        abi,
+        ffi_arg_types.len(),
+        ffi_ret_type,
+        ffi_arg_types.as_mut_ptr(),
+    );
+    if let Err(e) = result {
+        let err = CallError::LibFFI(format!("{:?}", e));
+        err.write_last_error();
+        return err.as_code();
+    }
+
+    let fn_ = Some(std::mem::transmute::<AnyVar, unsafe extern "C" fn()>(ptr));
+    libffi::raw::ffi_call(&mut cif, fn_, ret_val, args);
+
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libffi::raw::ffi_abi_FFI_WIN64;
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Vec4 {
+        x: f32,
+        y: f32,
+        z: f32,
+        w: f32,
+    }
+
+    #[inline(never)]
+    extern "system" fn scale(v: Vec4, factor: f32) -> Vec4 {
+        Vec4 {
+            x: v.x * factor,
+            y: v.y * factor,
+            z: v.z * factor,
+            w: v.w * factor,
+        }
+    }
+
+    #[test]
+    fn test_struct_by_value() {
+        unsafe {
+            let field_types = [ArgType::Float as i32; 4];
+            let vec4 = NewStructType(field_types.as_ptr(), field_types.len());
+            assert!(!vec4.is_null());
+
+            let mut value = Vec4 {
+                x: 1.0,
+                y: 2.0,
+                z: 3.0,
+                w: 4.0,
+            };
+            let mut factor = 2.0f32;
+            let arg_types = [vec4 as usize, ArgType::Float as usize];
+            let mut args = [
+                &mut value as *mut Vec4 as *mut c_void,
+                &mut factor as *mut f32 as *mut c_void,
+            ];
+            let mut ret = std::mem::MaybeUninit::<Vec4>::uninit();
+
+            let code = CallNativeFunctionEx(
+                scale as *mut c_void,
+                arg_types.as_ptr(),
+                args.as_mut_ptr(),
+                args.len(),
+                vec4 as usize,
+                ret.as_mut_ptr() as *mut c_void,
+                ffi_abi_FFI_WIN64,
+            );
+            assert_eq!(code, 0);
+            assert_eq!(
+                ret.assume_init(),
+                Vec4 {
+                    x: 2.0,
+                    y: 4.0,
+                    z: 6.0,
+                    w: 8.0
+                }
+            );
+
+            FreeStructType(vec4);
+        }
+    }
+}
@@ -2,7 +2,7 @@ use libffi::high::FfiAbi;
 use std::{ffi::c_void, ptr::addr_of_mut};
 use strum::FromRepr;
 
-type AnyVar = *mut c_void;
+pub(crate) type AnyVar = *mut c_void;
 
 static mut LAST_ERROR_MESSAGE: [u8; 512] = [0; 512];
 
@@ -17,7 +17,7 @@ pub enum CallError {
 }
 
 impl CallError {
-    fn as_code(&self) -> i32 {
+    pub(crate) fn as_code(&self) -> i32 {
         match self {
             CallError::UnmatchingArgCount(_, _) => 1,
             CallError::InvalidFFIArgType(_) => 2,
@@ -25,7 +25,7 @@ impl CallError {
         }
     }
 
-    fn write_last_error(&self) {
+    pub(crate) fn write_last_error(&self) {
         let msg = self.to_string();
         let msg_bytes = msg.as_bytes();
         if msg_bytes.len() >= 512 {
@@ -61,7 +61,7 @@ pub enum ArgType {
 }
 
 impl ArgType {
-    fn as_ffi_type(&self) -> *mut libffi::raw::ffi_type {
+    pub(crate) fn as_ffi_type(&self) -> *mut libffi::raw::ffi_type {
         match self {
             ArgType::Void => addr_of_mut!(libffi::raw::ffi_type_void),
             ArgType::UInt8 => addr_of_mut!(libffi::raw::ffi_type_uint8),
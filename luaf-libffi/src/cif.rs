@@ -0,0 +1,166 @@
+//! 预先构建的调用接口
+//!
+//! 多次调用同一函数时复用 CIF，避免每次调用重新解析参数类型。
+
+use libffi::high::FfiAbi;
+use std::ffi::c_void;
+
+use crate::call::{AnyVar, ArgType, CallError};
+
+pub struct PreparedCif {
+    cif: libffi::raw::ffi_cif,
+    /// CIF 引用该数组，需要与 CIF 同生命周期
+    arg_types: Vec<*mut libffi::raw::ffi_type>,
+    ret_type: ArgType,
+}
+
+impl PreparedCif {
+    /// 解析参数类型并构建 CIF
+    pub(crate) unsafe fn new(
+        arg_types: *const i32,
+        arg_types_len: usize,
+        ret_type: i32,
+        abi: FfiAbi,
+    ) -> Result<Box<Self>, CallError> {
+        let mut ffi_arg_types = vec![];
+        for i in 0..arg_types_len {
+            let arg_type_int = arg_types.add(i).read();
+            let arg_type = ArgType::from_repr(arg_type_int)
+                .ok_or(CallError::InvalidFFIArgType(arg_type_int))?;
+            ffi_arg_types.push(arg_type.as_ffi_type());
+        }
+        let ret_type = ArgType::from_repr(ret_type).unwrap_or(ArgType::Void);
+
+        let mut prepared = Box::new(PreparedCif {
+            cif: Default::default(),
+            arg_types: ffi_arg_types,
+            ret_type,
+        });
+        libffi::low::prep_cif(
+            &mut prepared.cif,
+            abi,
+            prepared.arg_types.len(),
+            ret_type.as_ffi_type(),
+            prepared.arg_types.as_mut_ptr(),
+        )
+        .map_err(|e| CallError::LibFFI(format!("{:?}", e)))?;
+
+        Ok(prepared)
+    }
+
+    pub(crate) fn as_raw(&mut self) -> *mut libffi::raw::ffi_cif {
+        &mut self.cif
+    }
+}
+
+/// 构建 CIF，成功时将句柄写入 `out`
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn PrepareCif(
+    arg_types: *const i32,
+    arg_types_len: usize,
+    ret_type: i32,
+    abi: FfiAbi,
+    out: *mut *mut c_void,
+) -> i32 {
+    match PreparedCif::new(arg_types, arg_types_len, ret_type, abi) {
+        Ok(prepared) => {
+            out.write(Box::into_raw(prepared) as *mut c_void);
+            0
+        }
+        Err(err) => {
+            err.write_last_error();
+            err.as_code()
+        }
+    }
+}
+
+/// 使用已构建的 CIF 调用函数
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn CallCif(
+    cif: *mut c_void,
+    ptr: *mut c_void,
+    args: *mut AnyVar,
+    args_len: usize,
+    ret_val: *mut AnyVar,
+) -> i32 {
+    let prepared = &mut *(cif as *mut PreparedCif);
+    if prepared.arg_types.len() != args_len {
+        let err = CallError::UnmatchingArgCount(prepared.arg_types.len(), args_len);
+        err.write_last_error();
+        return err.as_code();
+    }
+
+    let mut ffi_args = vec![];
+    for i in 0..args_len {
+        ffi_args.push(args.add(i) as *mut c_void);
+    }
+
+    let fn_ = Some(std::mem::transmute::<AnyVar, unsafe extern "C" fn()>(ptr));
+    let mut ret_raw = std::mem::MaybeUninit::<AnyVar>::uninit();
+    libffi::raw::ffi_call(
+        &mut prepared.cif,
+        fn_,
+        ret_raw.as_mut_ptr() as *mut c_void,
+        ffi_args.as_mut_ptr(),
+    );
+
+    if prepared.ret_type != ArgType::Void {
+        ret_val.write(ret_raw.assume_init());
+    }
+
+    0
+}
+
+/// 释放 CIF
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn FreeCif(cif: *mut c_void) {
+    if !cif.is_null() {
+        drop(Box::from_raw(cif as *mut PreparedCif));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libffi::raw::ffi_abi_FFI_WIN64;
+
+    #[inline(never)]
+    extern "system" fn test_mul(a: i32, b: i32) -> i32 {
+        a * b
+    }
+
+    #[test]
+    fn test_prepared_cif_call() {
+        unsafe {
+            let arg_types = [ArgType::Sint32 as i32, ArgType::Sint32 as i32];
+            let mut cif = std::ptr::null_mut::<c_void>();
+            let code = PrepareCif(
+                arg_types.as_ptr(),
+                arg_types.len(),
+                ArgType::Sint32 as i32,
+                ffi_abi_FFI_WIN64,
+                &mut cif,
+            );
+            assert_eq!(code, 0);
+
+            for i in 0..3 {
+                let mut args = vec![i as AnyVar, 3i32 as AnyVar];
+                let mut ret_val = std::ptr::null_mut::<c_void>();
+                let code = CallCif(
+                    cif,
+                    test_mul as *mut c_void,
+                    args.as_mut_ptr(),
+                    args.len(),
+                    &mut ret_val,
+                );
+                assert_eq!(code, 0);
+                assert_eq!(ret_val as i32, i * 3);
+            }
+
+            FreeCif(cif);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! 原生回调
+//!
+//! 创建 libffi 闭包，得到可以传给原生代码的函数指针，调用时转发给框架的回调。
+
+use libffi::high::FfiAbi;
+use std::ffi::c_void;
+
+use crate::{call::CallError, cif::PreparedCif};
+
+/// 闭包被调用时的回调，`args` 为各参数值的指针，返回值写入 `ret`
+pub type ClosureCallback =
+    unsafe extern "C" fn(user_data: *mut c_void, args: *mut *mut c_void, ret: *mut c_void);
+
+struct ClosureData {
+    closure: *mut libffi::raw::ffi_closure,
+    /// 闭包引用该 CIF，需要与闭包同生命周期
+    cif: Box<PreparedCif>,
+    callback: ClosureCallback,
+    user_data: *mut c_void,
+}
+
+unsafe extern "C" fn closure_trampoline(
+    _cif: *mut libffi::raw::ffi_cif,
+    ret: *mut c_void,
+    args: *mut *mut c_void,
+    data: *mut c_void,
+) {
+    let data = &*(data as *const ClosureData);
+    (data.callback)(data.user_data, args, ret);
+}
+
+/// 创建闭包，成功时将函数指针写入 `out_code`，句柄写入 `out_handle`
+#[no_mangle]
+#[allow(non_snake_case, clippy::too_many_arguments)]
+pub unsafe extern "C" fn CreateClosure(
+    arg_types: *const i32,
+    arg_types_len: usize,
+    ret_type: i32,
+    abi: FfiAbi,
+    callback: ClosureCallback,
+    user_data: *mut c_void,
+    out_code: *mut *mut c_void,
+    out_handle: *mut *mut c_void,
+) -> i32 {
+    let cif = match PreparedCif::new(arg_types, arg_types_len, ret_type, abi) {
+        Ok(cif) => cif,
+        Err(err) => {
+            err.write_last_error();
+            return err.as_code();
+        }
+    };
+
+    let mut code = std::ptr::null_mut::<c_void>();
+    let closure =
+        libffi::raw::ffi_closure_alloc(std::mem::size_of::<libffi::raw::ffi_closure>(), &mut code)
+            as *mut libffi::raw::ffi_closure;
+    if closure.is_null() {
+        let err = CallError::LibFFI("failed to allocate closure".to_string());
+        err.write_last_error();
+        return err.as_code();
+    }
+
+    let data = Box::into_raw(Box::new(ClosureData {
+        closure,
+        cif,
+        callback,
+        user_data,
+    }));
+    let status = libffi::raw::ffi_prep_closure_loc(
+        closure,
+        (*data).cif.as_raw(),
+        Some(closure_trampoline),
+        data as *mut c_void,
+        code,
+    );
+    if status != libffi::raw::ffi_status_FFI_OK {
+        FreeClosure(data as *mut c_void);
+        let err = CallError::LibFFI(format!("ffi_prep_closure_loc failed: {}", status));
+        err.write_last_error();
+        return err.as_code();
+    }
+
+    out_code.write(code);
+    out_handle.write(data as *mut c_void);
+    0
+}
+
+/// 释放闭包，释放后不能再调用其函数指针
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn FreeClosure(handle: *mut c_void) {
+    if handle.is_null() {
+        return;
+    }
+    let data = Box::from_raw(handle as *mut ClosureData);
+    libffi::raw::ffi_closure_free(data.closure as *mut c_void);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::call::ArgType;
+    use libffi::raw::ffi_abi_FFI_WIN64;
+
+    unsafe extern "C" fn add_callback(
+        user_data: *mut c_void,
+        args: *mut *mut c_void,
+        ret: *mut c_void,
+    ) {
+        let offset = user_data as i64;
+        let a = *(*args.add(0) as *const i32);
+        let b = *(*args.add(1) as *const i32);
+        (ret as *mut i64).write(a as i64 + b as i64 + offset);
+    }
+
+    #[test]
+    fn test_closure_call() {
+        unsafe {
+            let arg_types = [ArgType::Sint32 as i32, ArgType::Sint32 as i32];
+            let mut code = std::ptr::null_mut::<c_void>();
+            let mut handle = std::ptr::null_mut::<c_void>();
+            let result = CreateClosure(
+                arg_types.as_ptr(),
+                arg_types.len(),
+                ArgType::Sint32 as i32,
+                ffi_abi_FFI_WIN64,
+                add_callback,
+                10 as *mut c_void,
+                &mut code,
+                &mut handle,
+            );
+            assert_eq!(result, 0);
+
+            let fun = std::mem::transmute::<*mut c_void, extern "system" fn(i32, i32) -> i32>(code);
+            assert_eq!(fun(1, 2), 13);
+
+            FreeClosure(handle);
+        }
+    }
+}
@@ -3,9 +3,15 @@
 
 use luaf_include::{CoreAPIParam, API};
 
+mod aggregate;
 mod call;
+mod cif;
+mod closure;
 
+pub use aggregate::{CallNativeFunctionEx, FreeStructType, NewStructType};
 pub use call::CallNativeFunction;
+pub use cif::{CallCif, FreeCif, PrepareCif};
+pub use closure::{CreateClosure, FreeClosure};
 
 #[no_mangle]
 #[allow(non_snake_case)]
@@ -16,6 +22,33 @@ pub extern "C" fn ExtInitialize(param: &'static CoreAPIParam) -> i32 {
         "libffi::call_c_function",
         call::CallNativeFunction as *const _,
     );
+    API::get().functions().add_core_function(
+        "libffi::call_c_function_ex",
+        aggregate::CallNativeFunctionEx as *const _,
+    );
+    API::get().functions().add_core_function(
+        "libffi::new_struct_type",
+        aggregate::NewStructType as *const _,
+    );
+    API::get().functions().add_core_function(
+        "libffi::free_struct_type",
+        aggregate::FreeStructType as *const _,
+    );
+    API::get()
+        .functions()
+        .add_core_function("libffi::prepare_cif", cif::PrepareCif as *const _);
+    API::get()
+        .functions()
+        .add_core_function("libffi::call_cif", cif::CallCif as *const _);
+    API::get()
+        .functions()
+        .add_core_function("libffi::free_cif", cif::FreeCif as *const _);
+    API::get()
+        .functions()
+        .add_core_function("libffi::create_closure", closure::CreateClosure as *const _);
+    API::get()
+        .functions()
+        .add_core_function("libffi::free_closure", closure::FreeClosure as *const _);
 
     0
 }
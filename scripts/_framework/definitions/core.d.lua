@@ -1,8 +1,104 @@
 ---@class core
----@field unsafe_mode fun(enable: boolean)
+---@field console CoreConsole
+---@field log CoreLog
+---@field unsafe_mode fun(enable: boolean) @ 授予或撤销全部不安全权限，建议改用 with_unsafe。
+---@field with_unsafe fun(capability: UnsafeCapability, fn: fun(...): ..., ...): ... @ 在回调执行期间持有指定的不安全权限，返回回调的返回值。
 ---@field msg fun(message: string)
 ---@field version fun(): integer, integer, integer
 ---@field require_version fun(semver: string)
 ---@field on_update fun(callback: fun())
 ---@field on_imgui fun(callback: fun())
----@field on_draw fun(callback: fun())
+---@field on_draw fun(callback: fun()) @ 设置绘制回调，位于 hud 绘制层，z 值为 0。
+---@field on_draw_layer fun(layer: DrawLayer, callback: fun()|nil, z?: integer) @ 设置指定绘制层的回调，层之间从下到上为 background、world、hud、debug，同层按 z 从小到大绘制。传入 nil 取消。
+---@field set_draw_layer_enabled fun(layer: DrawLayer, enabled: boolean) @ 启用或禁用绘制层，影响所有脚本并保存到配置。
+---@field is_draw_layer_enabled fun(layer: DrawLayer): boolean
+---@field on_menu fun(callback: fun(), title?: string) @ 设置主菜单栏回调，在以 title（默认为脚本名）命名的菜单中绘制菜单项，脚本卸载后自动移除。
+---@field on_event fun(name: string, callback: fun(payload: string|nil)|nil) @ 设置扩展发布的事件回调，传入 nil 取消。
+---@field on_monster_spawn fun(callback: fun(ptr: LuaPtr, species: integer, sub_id: integer, id: integer)|nil) @ 设置怪物生成回调，在生成后的下一帧调用，传入 nil 取消。id 为实例 ID。同一帧内生成又析构的怪物不会触发回调。
+---@field on_monster_death fun(callback: fun(ptr: LuaPtr, species: integer, sub_id: integer, id: integer)|nil) @ 设置怪物析构回调，在下一帧调用，此时对象已不可访问。id 为实例 ID。
+---@field on_quest_start fun(callback: fun(quest_id: integer)|nil) @ 设置任务开始回调，在下一帧调用，传入 nil 取消。
+---@field on_quest_complete fun(callback: fun(quest_id: integer, success: boolean)|nil) @ 设置任务结束回调，任务失败时 success 为 false。
+---@field on_quest_return fun(callback: fun(quest_id: integer)|nil) @ 设置任务结束后返回据点的回调。
+---@field on_damage fun(callback: fun(record: DamageRecord)|nil) @ 设置伤害事件回调，在下一帧调用，传入 nil 取消。
+---@field on_before_reload fun(callback: fun(script_name: string|nil)) @ 重载前回调，重载全部脚本时参数为 nil。
+---@field on_after_reload fun(callback: fun(script_name: string|nil)) @ 重载后回调，由重载后的虚拟机接收。
+---@field on_save fun(callback: fun()) @ 设置保存回调，定期自动保存和场景切换时调用，脚本应在其中保存设置。
+---@field reload_all fun(): boolean @ 请求在下一帧重载全部脚本，距离上次重载过近时忽略并返回 false。
+---@field reload_script fun(name: string): boolean @ 请求在下一帧重载指定脚本。
+---@field reload_self fun(): boolean @ 请求在下一帧重载当前脚本。
+---@field async fun(fn: fun(...), ...): thread @ 创建协程并立即执行到第一次等待，之后由每帧更新恢复。
+---@field wait_frames fun(n?: integer) @ 在 core.async 协程中等待 n 帧，默认 1 帧。
+---@field wait_seconds fun(seconds: number) @ 在 core.async 协程中等待指定秒数。
+---@field is_cutscene fun(): boolean
+---@field is_loading fun(): boolean
+---@field thread_info fun(): ThreadInfo @ 获取当前线程信息。
+---@field asset_path fun(path: string): string
+---@field dump_state fun(path?: string): string @ 将脚本列表、全局变量快照、Hook 与补丁信息导出为 data 目录下的 JSON 文件，返回文件路径。
+---@field stats fun(): table<string, table<string, CallbackStats>> @ 各脚本的回调统计，按脚本名和回调名（如 on_update、hook、timers）索引。单次回调超出预算时发布 script_over_budget 事件，内容为 JSON。
+---@field init_phase fun(): InitPhase|nil @ 当前初始化阶段。初始化完成时发布 init_phase 事件，内容为 "ready"。
+---@field dofile_isolated fun(path: string, options?: DofileIsolatedOptions): ... @ 在独立环境中执行脚本目录下的 Lua 文件。
+
+---@alias InitPhase
+---| "extensions" # 加载扩展
+---| "addresses" # 解析地址，应用补丁方案
+---| "scripts" # 加载脚本
+---| "ready" # 初始化完成
+
+---@alias DrawLayer
+---| "background" # 背景，位于所有内容下方
+---| "world" # 世界空间标记，如怪物血条
+---| "hud" # 界面叠加显示
+---| "debug" # 调试信息，位于最上方
+
+---@alias UnsafeCapability
+---| "memory_read" # 跳过读取内存时的权限检查
+---| "memory_write" # 跳过写入内存时的权限检查
+---| "game_control" # 控制怪物、生成物品等
+
+---@class ThreadInfo
+---@field id integer @ 当前线程 ID。
+---@field game_thread_id integer? @ 游戏主线程 ID，初始化前为 nil。
+---@field is_game_thread boolean
+
+---@class CallbackStats
+---@field calls integer
+---@field errors integer
+---@field over_budget integer @ 超出耗时预算的次数。
+---@field avg_us number @ 平均耗时（微秒）。
+---@field max_us integer @ 最大耗时（微秒）。
+
+---@class DamageRecord
+---@field version integer @ 记录结构版本，当前为 1。
+---@field attacker LuaPtr|nil @ 攻击者，环境伤害等没有攻击者时为 nil。
+---@field victim LuaPtr @ 受击者，回调时对象可能已析构。
+---@field raw_damage number @ 原始伤害值。
+---@field damage number @ 计算后的最终伤害。
+---@field element "none"|"fire"|"water"|"thunder"|"ice"|"dragon"|"unknown"
+---@field part_id integer @ 命中部位 ID。
+---@field critical boolean @ 是否会心。
+
+---@class DofileIsolatedOptions
+---@field share? string[] @ 共享到新环境的全局变量名。
+---@field inherit? boolean @ 未定义的变量从调用方全局表读取。
+
+---@class CoreLog
+---@field set_level fun(level: LogLevel) @ 设置日志等级，立即生效并保存到配置。
+---@field get_level fun(): LogLevel
+---@field set_console_enabled fun(enabled: boolean) @ 设置是否输出到控制台。
+---@field set_file_enabled fun(enabled: boolean) @ 设置是否写入日志文件，启用时以追加方式打开日志文件。
+---@field set_file_path fun(path: string) @ 更改日志文件路径，路径相对于 lua_framework/data 且必须以 .log 结尾。启用文件日志时立即重新打开，失败时保留原文件。
+---@field settings fun(): LogSettings
+
+---@class LogSettings
+---@field level LogLevel
+---@field console boolean
+---@field file boolean
+---@field file_path string
+
+---@alias LogLevel "trace"|"debug"|"info"|"warn"|"error"
+
+---@class CoreConsole
+---@field show fun() @ 显示日志控制台。
+---@field hide fun() @ 隐藏并释放日志控制台，之后的日志不会自动弹出控制台。
+---@field toggle fun(): boolean @ 切换日志控制台，返回切换后是否显示。
+---@field is_visible fun(): boolean
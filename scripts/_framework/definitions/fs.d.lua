@@ -12,3 +12,8 @@ local _ = _
 ---@field dump fun(path:string, data:any)
 ---@field dump_pretty fun(path:string, data:any)
 ---@field load fun(path:string): any
+local _ = _
+
+---@class fs
+---@field read_asset fun(path:string): string
+---@field asset_exists fun(path:string): boolean
@@ -5,16 +5,56 @@
 ---@field Memory Memory
 ---@field AddressRepository AddressRepository
 ---@field Interceptor Interceptor
+---@field Hook Hook
 ---@field Monster Monster
----@field call_native_function fun()
+---@field Player Player
+---@field Quest Quest
+---@field Chat Chat
+---@field ClassDef ClassDef
+---@field Struct Struct
+---@field Spawn Spawn
+---@field Network Network
+---@field DamageNumber DamageNumber
+---@field CodeWriter CodeWriter
+---@field PatchProfile PatchProfile
+---@field Singletons Singletons
+---@field GameObject _TGameObjectConstructor
+---@field DTI DTI
+---@field Timer Timer
+---@field Hotkey Hotkey
+---@field get_singleton fun(name:string): LuaPtr @ 获取单例，不存在时报错
+---@field singletons fun(): table<string, LuaPtr> @ 所有已解析的单例，键为单例名
+---@field call_native_function fun(fun:AsLuaPtr, args:table, ret_type?:string|StructType, use_system_abi?:boolean, retains_args?:boolean): any @ retains_args 表示函数会保存参数指针，传入临时字符串时输出警告。结构体参数为 {type=StructType, value={...}}，结构体返回值为表
+---@field struct fun(fields:{[1]:string, [2]:integer|nil}[]): StructType @ 描述按值传递的结构体，如 sdk.struct{ {"float", 4} }，值为按顺序列出所有成员的表
+---@field bind_function fun(params:BindFunctionParams): NativeFunction @ 绑定原生函数，返回可直接调用的对象，参数按签名转换
+---@field new_native_callback fun(fn:function, args?:string[], ret?:string, use_system_abi?:boolean): LuaPtr @ 将 Lua 函数包装为原生函数指针，回调按签名接收参数。脚本卸载时释放，之后不能再调用
+---@field free_native_callback fun(ptr:AsLuaPtr): boolean @ 释放本脚本创建的原生回调，不是本脚本创建的返回 false。回调正在执行时在执行结束后释放
 local _ = _
 
+---@class BindFunctionParams
+---@field address AsLuaPtr
+---@field args string[]|nil @ 参数类型名列表，与 call_native_function 相同
+---@field ret string|nil @ 返回值类型名，默认无返回值
+---@field name string|nil @ 用于错误信息
+---@field system_abi boolean|nil
+
+---@class StructType
+---@field size integer
+---@field align integer
+
+---@class NativeFunction
+---@field name string|nil
+---@field address LuaPtr
+---@operator call(...): any
+
 local sdk = {
     ---@class _TStringConstructor
     ---@field new_utf8 fun(str:string): ManagedString
     ---@field new_utf16 fun(str:string): ManagedString
     ---@field from_ptr fun(ptr:AsLuaPtr): ManagedString
-    ---@field from_utf8_bytes fun(bytes:Bytes): ManagedString
+    ---@field from_utf8_bytes fun(bytes:BytesLike): ManagedString
+    ---@field pin fun(str:string|ManagedString, encoding?:"utf8"|"utf16"): PinnedString @ 固定字符串，地址在释放或脚本卸载前有效，用于会保存指针的原生函数
+    ---@field release fun(str:PinnedString|AsLuaPtr): boolean @ 释放固定字符串
     String = {},
     ---@class _TLuaPtrConstructor
     ---@field __call fun(address:integer): LuaPtr
@@ -23,23 +63,56 @@ local sdk = {
 
 -- typedef
 ---@alias Bytes table<integer, integer>
+---@alias BytesLike Bytes|string @ 字节数组或二进制字符串。数组长度受设置 scripts.max_bytes_len 限制，字符串不受限制，大块数据应使用字符串。
 ---@alias UInt64 table<string, integer> @ 暂时未被广泛使用，仅占位。LuaTable实现的长整型，适用于FFI安全调用。
 ---@alias AsLuaPtr LuaPtr @ 指示该值能够被转换为LuaPtr的类型。具体参考LuaPtr创建函数。
 
 ---@class Input
 ---@field keyboard _Tkey
 ---@field controller _Tcontroller
+---@field mouse _Tmouse
+---@field on_key fun(callback:fun(key:string, down:boolean)) @ 设置键盘按键状态变化回调，按下和松开时各触发一次。
+---@field on_key_repeat fun(callback:fun(key:string)) @ 设置按住按键时的重复触发回调，延迟和间隔由设置决定。
+---@field on_button fun(callback:fun(button:string, down:boolean)) @ 设置手柄按键状态变化回调，按下和松开时各触发一次。
+---@field settings fun(): InputSettings @ 获取死区和按键重复设置。
+---@field send_key fun(key:string|integer, down:boolean) @ 向游戏键盘写入按键状态，按下的按键保持按下直到释放。需要在设置中允许输入注入。
+---@field send_text fun(text:string): integer @ 按当前键盘布局依次输入字符，每帧一步，返回加入队列的字符数。需要在设置中允许输入注入。
 local Input = {
     ---@class _Tkey
     ---@field is_down fun():boolean
     ---@field is_pressed fun():boolean
+    ---@field is_repeated fun(key:string|integer):boolean @ 按键本帧被点击，或按住时重复触发。
+    ---@field display_name fun(key:string|integer): string @ 获取按键在当前键盘布局下的显示名称。
+    ---@field find_by_display_name fun(name:string): string|nil @ 通过显示名称查找按键，返回 KeyCode 名称。
+    ---@field to_virtual_key fun(key:string|integer): integer|nil @ 获取按键在当前键盘布局下的虚拟键码。
+    ---@field from_virtual_key fun(vk:integer): string|nil @ 通过虚拟键码获取按键，返回 KeyCode 名称。
     keyboard = {},
     ---@class _Tcontroller
-    ---@field is_down fun():boolean
-    ---@field is_pressed fun():boolean
-    controller = {}
+    ---@field is_down fun(button:string|integer):boolean @ 支持逻辑按键名称，如 "confirm"。
+    ---@field is_pressed fun(button:string|integer):boolean @ 支持逻辑按键名称，如 "confirm"。
+    ---@field mapping fun(name:string): string[]|nil @ 获取逻辑按键对应的物理按键。
+    ---@field logical_names fun(): string[] @ 获取所有逻辑按键名称。
+    ---@field stick fun(side:"left"|"right", index?:integer): number|nil, number|nil @ 读取摇杆位置，范围 -1~1，已应用死区。仅支持 XInput 手柄，未连接时返回 nil。
+    ---@field trigger fun(side:"left"|"right", index?:integer): number|nil @ 读取扳机位置，范围 0~1，已应用死区。仅支持 XInput 手柄，未连接时返回 nil。
+    controller = {},
+    ---@class _Tmouse
+    ---@field position fun(): integer, integer @ 光标在游戏窗口客户区中的位置。
+    ---@field is_down fun(button:MouseButton|integer):boolean @ 游戏窗口不在前台时视为松开。
+    ---@field is_pressed fun(button:MouseButton|integer):boolean
+    ---@field is_released fun(button:MouseButton|integer):boolean
+    ---@field wheel fun(): number @ 上一帧的滚轮滚动格数，向上为正。
+    ---@field drag_delta fun(button:MouseButton|integer): integer|nil, integer|nil @ 按住拖动时返回相对按下位置的偏移，未拖动时返回 nil。
+    mouse = {}
 }
 
+---@alias MouseButton "Left"|"Right"|"Middle"|"X1"|"X2"
+
+---@class InputSettings
+---@field stick_dead_zone number
+---@field trigger_dead_zone number
+---@field repeat_delay_ms integer
+---@field repeat_interval_ms integer @ 为 0 时不重复触发
+
 ---@class ManagedString
 ---@field encoding string "utf8" | "utf16"
 ---@field len fun():integer
@@ -48,13 +121,20 @@ local Input = {
 ---@field to_bytes fun():Bytes
 ---@field as_ptr fun():AsLuaPtr
 
+---@class PinnedString
+---@field encoding string "utf8" | "utf16"
+---@field released boolean
+---@field as_ptr fun():LuaPtr
+---@field release fun():boolean
+
 ---@class LuaPtr
 ---@field to_integer fun():integer
 ---@field to_uint64 fun():UInt64
----@field read_integer fun(size:integer): integer
+---@field read_integer fun(size:integer): integer @ 读取 size 字节的整数，不进行符号扩展。
+---@field read_signed fun(size:integer): integer @ 读取 size 字节的有符号整数，进行符号扩展。
 ---@field read_bytes fun(size:integer): Bytes
 ---@field write_integer fun(value:integer, size:integer)
----@field write_bytes fun(value:Bytes, size:integer|nil)
+---@field write_bytes fun(value:BytesLike, size:integer|nil)
 ---@field read_u8 fun(): integer
 ---@field read_i8 fun(): integer
 ---@field read_u16 fun(): integer
@@ -76,27 +156,315 @@ local Input = {
 ---@field write_f32 fun(value:number)
 ---@field write_f64 fun(value:number)
 ---@field read_ptr fun(): LuaPtr @ 读取当前指针的值，并将新的值作为 LuaPtr 返回。
+---@field read_string fun(max_len:integer|nil, encoding:"utf8"|"utf16"|nil): string @ 读取以 \0 结尾的字符串，默认最多读取 1024 字节，编码默认 utf8。
+---@field write_string fun(value:string, encoding:"utf8"|"utf16"|nil) @ 写入字符串，包含结尾的 \0。
 ---@field offset fun(...): LuaPtr @ 偏移指针。支持传入多个变量进行多级偏移。返回新的LuaPtr，可链式调用。
 ---@field offset_ce fun(...): LuaPtr @ CE方法偏移指针。支持传入多个变量进行多级偏移。与默认方法相比，该方法会先对基址进行取值操作。等效于 `:read_ptr():offset()`。返回新的LuaPtr，可链式调用。
 
 ---@class Memory
----@field scan fun(address:integer, size:integer, pattern:string, offset:integer|nil): LuaPtr
+---@field scan fun(address:integer, size:integer, pattern:string, offset:integer|nil): LuaPtr @ 特征码支持 ?? 通配、4? 半字节通配和 E8&FE 掩码。
 ---@field scan_all fun(address:integer, size:integer, pattern:string, offset:integer|nil): table<integer, LuaPtr>
----@field patch fun(ptr:AsLuaPtr, bytes:Bytes): LuaPtr
+---@field modules fun(): ModuleInfo[] @ 列出所有已加载的模块，第一个为主模块。
+---@field scan_module fun(module:string, pattern:string, offset:integer|nil): LuaPtr @ 在指定模块（如 "somePlugin.dll"，不区分大小写）中扫描，查找匹配的第一个地址。
+---@field disasm fun(ptr:AsLuaPtr, count:integer|nil): Instruction[] @ 反汇编 count 条指令（默认 10，最多 4096），遇到无效指令或不可读内存时提前停止。
+---@field find_next_call fun(ptr:AsLuaPtr, max_count:integer|nil): Instruction|nil @ 在 max_count 条指令内（默认 64，最多 4096）查找下一条 call 指令。
+---@field find_next_jump fun(ptr:AsLuaPtr, max_count:integer|nil): Instruction|nil @ 查找下一条跳转指令，包括条件跳转。
+---@field find_next_instruction fun(ptr:AsLuaPtr, mnemonic:string, max_count:integer|nil): Instruction|nil @ 查找下一条指定助记符（如 "lea"）的指令。
+---@field scan_all_async fun(address:integer, size:integer, pattern:string, callback:fun(results:LuaPtr[]|nil, err:string|nil), offset:integer|nil): TaskHandle @ 在后台线程扫描，完成后在游戏主线程调用回调，未找到时 results 为空表。取消或脚本卸载后不再调用回调。
+---@field patch fun(ptr:AsLuaPtr, bytes:BytesLike, revisions:integer[]|nil): LuaPtr|nil @ revisions 为适用的游戏版本，不匹配时跳过补丁并返回 nil。
 ---@field patch_nop fun(ptr:AsLuaPtr, size:integer): LuaPtr
+---@field nop_instructions fun(ptr:AsLuaPtr, count:integer): integer @ 以完整指令为单位填充 nop，不会截断指令，返回填充的字节数。可通过 Memory.restore_patch 还原。
 ---@field restore_patch fun(ptr:AsLuaPtr): boolean
+---@field malloc fun(size:integer): LuaPtr @ 分配一段填充为 0 的堆内存。脚本卸载时自动释放。
+---@field alloc fun(size:integer, protection:"rw"|"rwx"|nil): LuaPtr @ 分配页内存，默认可读写。脚本卸载时自动释放。
+---@field alloc_near fun(address:AsLuaPtr, size:integer, protection:"rw"|"rwx"|nil): LuaPtr @ 在地址前后 ±2GB 范围内分配页内存，可用 rel32 跳转到达，默认可读写执行。
+---@field free fun(ptr:AsLuaPtr): boolean @ 释放当前脚本通过 malloc、alloc 或 alloc_near 分配的内存。
+---@field compare fun(ptr_a:AsLuaPtr, ptr_b:AsLuaPtr, len:integer): boolean, integer|nil @ 返回是否相同以及第一个不同字节的偏移。
+---@field crc32 fun(ptr:AsLuaPtr, len:integer): integer
+---@field read_u8 fun(ptr:AsLuaPtr): integer
+---@field read_i8 fun(ptr:AsLuaPtr): integer @ 与 LuaPtr 的 read_i* 方法相同，不进行符号扩展，有符号整数使用 read_signed。
+---@field read_u16 fun(ptr:AsLuaPtr): integer
+---@field read_i16 fun(ptr:AsLuaPtr): integer
+---@field read_u32 fun(ptr:AsLuaPtr): integer
+---@field read_i32 fun(ptr:AsLuaPtr): integer
+---@field read_u64 fun(ptr:AsLuaPtr): integer
+---@field read_i64 fun(ptr:AsLuaPtr): integer
+---@field write_u8 fun(ptr:AsLuaPtr, value:integer)
+---@field write_i8 fun(ptr:AsLuaPtr, value:integer)
+---@field write_u16 fun(ptr:AsLuaPtr, value:integer)
+---@field write_i16 fun(ptr:AsLuaPtr, value:integer)
+---@field write_u32 fun(ptr:AsLuaPtr, value:integer)
+---@field write_i32 fun(ptr:AsLuaPtr, value:integer)
+---@field write_u64 fun(ptr:AsLuaPtr, value:integer)
+---@field write_i64 fun(ptr:AsLuaPtr, value:integer)
+---@field read_signed fun(ptr:AsLuaPtr, size:integer): integer @ 读取 size 字节的有符号整数，进行符号扩展。
+---@field resolve_chain fun(base:AsLuaPtr, offsets:integer[]): LuaPtr|nil @ 多级指针偏移，与 LuaPtr:offset 相同，最后一级不取值。取值失败或遇到空指针时返回 nil。
+---@field resolve_chain_ce fun(base:AsLuaPtr, offsets:integer[]): LuaPtr|nil @ 按 CheatEngine 算法解析指针路径，每一级先取值再偏移，与 LuaPtr:offset_ce 相同。
+---@field read_f32 fun(ptr:AsLuaPtr): number
+---@field read_f64 fun(ptr:AsLuaPtr): number
+---@field write_f32 fun(ptr:AsLuaPtr, value:number)
+---@field write_f64 fun(ptr:AsLuaPtr, value:number)
+---@field read_ptr fun(ptr:AsLuaPtr): LuaPtr
+---@field read_bytes fun(ptr:AsLuaPtr, size:integer): Bytes
+---@field write_bytes fun(ptr:AsLuaPtr, bytes:BytesLike)
+---@field read_string fun(ptr:AsLuaPtr, max_len:integer|nil, encoding:"utf8"|"utf16"|nil): string @ 读取以 \0 结尾的字符串，默认最多读取 1024 字节。读写函数与 LuaPtr 的同名方法相同，遵循内存读写权限。
+---@field write_string fun(ptr:AsLuaPtr, value:string, encoding:"utf8"|"utf16"|nil)
+---@field watch fun(ptr:AsLuaPtr, len:integer, callback:fun(ptr:LuaPtr, old_crc:integer, new_crc:integer|nil), interval_ms:number|nil): integer @ 定期检查内存区域（默认 500ms），内容变化时调用回调，内存不可读时 new_crc 为 nil 并移除监视。返回监视 ID。
+---@field unwatch fun(id:integer): boolean
+
+---@alias TaskStatus "running"|"completed"|"failed"|"cancelled"
+
+---@class TaskHandle
+---@field id integer
+---@field cancel fun(self:TaskHandle): boolean @ 取消任务，任务已结束时返回 false。
+---@field status fun(self:TaskHandle): TaskStatus
+
+---@class CodeWriter
+---@field alloc fun(size:integer): LuaPtr @ 分配可执行代码岛，脚本卸载时自动释放。
+---@field free fun(ptr:AsLuaPtr): boolean
+---@field write fun(ptr:AsLuaPtr, writer:fun(w:X86Writer)): integer @ 向代码岛写入代码，返回写入的字节数。
+---@field patch fun(ptr:AsLuaPtr, size:integer, writer:fun(w:X86Writer)): LuaPtr @ 原地修改代码，剩余空间填充 nop。可通过 Memory.restore_patch 还原。
+
+---@class X86Writer
+---@field offset fun(self:X86Writer): integer
+---@field pc fun(self:X86Writer): LuaPtr
+---@field put_bytes fun(self:X86Writer, bytes:BytesLike)
+---@field put_nop fun(self:X86Writer, count:integer|nil)
+---@field put_ret fun(self:X86Writer)
+---@field put_jmp fun(self:X86Writer, target:AsLuaPtr)
+---@field put_call fun(self:X86Writer, target:AsLuaPtr)
+---@field relocate fun(self:X86Writer, src:AsLuaPtr, min_bytes:integer): integer @ 重定位至少 min_bytes 字节的完整指令，返回读取的源字节数。
+
+---@class ModuleInfo
+---@field name string
+---@field base LuaPtr
+---@field size integer
+
+---@class Instruction
+---@field address LuaPtr
+---@field size integer
+---@field bytes integer[]
+---@field mnemonic string
+---@field operands string
+---@field text string @ 完整的指令文本，Intel 语法。
+---@field flow "next"|"call"|"jump"|"conditional_jump"|"return"|"other"
+---@field target LuaPtr|nil @ 直接调用或跳转的目标地址。
+---@field memory_target LuaPtr|nil @ RIP 相对寻址的内存操作数地址。
+
+---@class AddressRecord
+---@field name string
+---@field pattern string
+---@field offset integer
+---@field skip_bytes integer|nil @ 匹配时忽略特征码的前 N 个字节
+---@field tolerate_hooks boolean|nil @ 精确匹配失败时忽略函数头和相对调用/跳转指令重新扫描，容忍其他工具的 Hook
+---@field policy "required"|"optional"|"deferred"|nil @ 解析失败时的处理方式，默认 required。optional 不在错误面板中报告，deferred 在下次获取时重试
+---@field fallbacks PatternCandidate[]|nil @ 主特征码未找到或未通过校验时，依次尝试的候选特征码
+---@field validator (fun(address:LuaPtr): boolean)|nil @ 校验扫描得到的地址，返回 false 时尝试下一个特征码。脚本卸载后拒绝所有地址
+
+---@class PatternCandidate
+---@field pattern string
+---@field offset integer|nil
+---@field skip_bytes integer|nil
 
 ---@class AddressRepository
 ---@field get fun(name:string): LuaPtr
 ---@field try_get fun(name:string): table<nil, nil> @ return: (ok: boolean, ptr_or_error: LuaPtr|string)
 ---@field set_record fun() @ 接受 AddressRecord 或 (name:string, pattern:string, offset:integer|nil)
 ---@field get_or_insert fun(): LuaPtr @ 接受 AddressRecord 或 (name:string, pattern:string, offset:integer|nil)。尝试获取已记录的特征码地址，若不存在则插入新记录并获取值。
+---@field list fun(): AddressRecordStatus[] @ 列出所有地址记录及解析状态，按名称排序。
+---@field get_deferred fun(name:string): LuaPtr|nil @ 获取地址，解析失败时返回 nil，之后获取时重试（有最短间隔）。
+---@field rescan fun(name:string): LuaPtr @ 丢弃缓存的地址并重新扫描，用于游戏更新后检查特征码。
+
+---@class AddressRecordStatus
+---@field name string
+---@field pattern string|nil @ 由其他扩展直接提供的地址没有特征码
+---@field offset integer|nil
+---@field policy "required"|"optional"|"deferred"|nil
+---@field status "resolved"|"failed"|"unresolved"
+---@field address LuaPtr|nil
+---@field error string|nil @ 最近一次解析失败的原因
 
 ---@class Interceptor
----@field attach fun()
----@field attach_instruction fun()
----@field detach fun()
+---@field attach fun(ptr:AsLuaPtr, params:InterceptorParams): integer
+---@field attach_instruction fun(ptr:AsLuaPtr, params:InterceptorParams): integer
+---@field attach_vtable fun(object:AsLuaPtr, index:integer, params:InterceptorParams): integer @ 替换对象虚表中第 index 项，只有使用该虚表的对象触发回调。脚本卸载或 detach 后还原虚表项，不支持 persistent。
+---@field replace fun(ptr:AsLuaPtr, signature:ReplaceSignature, fn:fun(original:fun(...):any, ...):any): integer @ 替换函数，回调按签名接收参数，返回值作为函数返回值，可调用 original(...) 执行原函数。回调出错时调用原函数。需要 luaf_libffi 扩展。
+---@field detach fun(handle:integer): boolean
+
+---@class ReplaceSignature
+---@field ret string|nil @ 返回值类型名，与 call_native_function 相同，默认 "void"
+---@field args string[]|nil @ 参数类型名列表
+
+---@alias RegisterValue AsLuaPtr|boolean|number|{type:string, value:any} @ 设置 Hook 参数、返回值或寄存器时接受的值，浮点数默认按 double 转换，可通过 {type="float", value=1.5} 指定类型。
+
+---@class Hook
+---@field on_method fun(class_name:string, method:integer|string, callback:fun(this:any, args)): integer @ Hook DTI 类的虚函数，method 为虚函数索引或 ClassDef 中的方法名。类存在 ClassDef 定义时 this 为类对象，否则为 LuaPtr。虚函数表从已有实例读取（单例或通过 DTI.of 访问过的对象），尚无实例时报错。
+
+---@class InterceptorParams
+---@field on_enter fun(args)|nil
+---@field on_leave fun(retval)|nil
+---@field on_hit fun(context)|nil
+---@field persistent boolean|nil @ 脚本重载时保留 Hook，由重载后的脚本以相同 key 重新绑定回调。重载后未重新绑定的 Hook 会被移除。
+---@field key string|nil @ 持久化 Hook 的标识，默认为 Hook 地址。
+---@field game_thread_only boolean|nil @ 仅在游戏主线程触发时调用回调。
+---@field signature string|nil @ 函数签名，如 "void(cPlayer* this, float dmg, int part)"，on_enter 中可通过 args.this、args.part 按名称访问参数并自动解码。前 4 个浮点参数从 XMM 寄存器读取，浮点返回值从 XMM0 读取。
+
+---@class _TGameObjectConstructor
+---@field track fun(ptr:AsLuaPtr): GameObject @ 追踪游戏对象，返回在对象析构时失效的弱引用。
+
+---@class GameObject
+---@field is_valid fun(self:GameObject): boolean
+---@field ptr fun(self:GameObject): LuaPtr|nil @ 对象已析构时返回 nil。
+---@field class_name fun(self:GameObject): string|nil
+---@field as_class fun(self:GameObject, class_name:string|nil): GameClassObject @ 转换为 ClassDef 类对象，对象已析构时报错。
+
+---@class Singletons
+---@field wait fun(name:string, callback:fun(ptr:LuaPtr)): boolean @ 单例可用时调用回调，已可用时立即调用并返回 true。
+---@field on_registered fun(callback:fun(name:string, ptr:LuaPtr)) @ 设置新单例解析回调，游戏运行中新构造的单例会在下一帧解析。单个回调出错不影响其他回调。
+
+---@class Timer
+---@field after fun(seconds:number, callback:fun(...), ...): integer @ seconds 秒后执行一次，额外参数传给回调，返回任务句柄。
+---@field every fun(seconds:number, callback:fun(...), ...): integer @ 每 seconds 秒执行一次，直到取消。
+---@field cancel fun(handle:integer): boolean @ 取消任务，返回任务是否存在。脚本卸载时任务自动清理。
+
+---@class Hotkey
+---@field register fun(name:string, keys:(string|integer)[], callback:fun(name:string)): string[] @ 注册组合键，如 {"LeftControl", "H"}。用户在界面中修改过的绑定优先，返回实际生效的按键。
+---@field unregister fun(name:string): boolean
+---@field get fun(name:string): string[]|nil @ 获取当前绑定的按键。
+
+---@class PatchProfile
+---@field define fun(name:string, patches:PatchEntry[], revisions:integer[]|nil) @ 定义补丁方案并保存到配置，已存在时替换补丁内容。revisions 为适用的游戏版本，不匹配时方案不会被应用。
+---@field enable fun(name:string) @ 应用方案中的全部补丁，任一失败时整体还原。
+---@field disable fun(name:string) @ 还原方案。
+---@field is_applied fun(name:string): boolean
+---@field list fun(): string[]
+
+---@class PatchEntry
+---@field record string|nil @ AddressRepository 记录名。
+---@field pattern string|nil @ 特征码，未指定 record 时使用。
+---@field offset integer|nil
+---@field bytes string @ 十六进制字节，如 "90 90 EB"。
 
 ---@class Monster
 ---@field list fun(): table<integer, integer>
+---@field all fun(): MonsterInfo[] @ 所有已生成怪物的状态，尚未初始化的怪物不包含在内。
 ---@field contains fun(ptr:AsLuaPtr): boolean
+---@field set_rage fun(ptr:AsLuaPtr, value:number) @ 设置怒气累计值。需要 game_control 权限。
+---@field set_stamina fun(ptr:AsLuaPtr, value:number) @ 设置体力值。需要 game_control 权限。
+---@field set_target fun(ptr:AsLuaPtr, target:AsLuaPtr|nil) @ 设置仇恨目标，传入 nil 清除目标。需要 game_control 权限。
+---@field enqueue_action fun(ptr:AsLuaPtr, action_id:integer): boolean @ 令怪物执行指定行为。需要 game_control 权限。
+
+---@class MonsterInfo
+---@field ptr LuaPtr
+---@field id integer @ 实例 ID，对象地址被复用时也不会重复，与生成/析构回调中的 id 一致。
+---@field species integer @ 怪物种类 ID。
+---@field sub_id integer
+---@field hp number
+---@field max_hp number
+---@field rage number
+---@field position {x:number, y:number, z:number}
+
+---@alias Position {x:number, y:number, z:number}|number[]
+
+---@class DamageNumber
+---@field active fun(): DamageNumberInfo[] @ 正在显示的伤害数字，界面未加载时为空表。可配合 draw.world_to_screen 和 draw.text 用框架字体重新绘制。
+---@field format fun(value:number, decimals:integer|nil, separator:string|nil): string @ 格式化数字，默认不保留小数，千位分隔符为 ","。
+
+---@class DamageNumberInfo
+---@field value integer
+---@field critical boolean @ 会心伤害。
+---@field weak_point boolean @ 弱点伤害。
+---@field elapsed number @ 已显示时间（秒）。
+---@field color integer @ 文字颜色，RGBA8。
+---@field position {x:number, y:number, z:number} @ 世界坐标。
+
+---@class Spawn
+---@field item fun(item_id:integer, position:Position, count:integer|nil): LuaPtr @ 在指定位置掉落物品。需要 game_control 权限，调用频率受限。
+---@field endemic_life fun(em_id:integer, position:Position): LuaPtr @ 在指定位置生成环境生物。需要 game_control 权限，调用频率受限。
+
+---@class Network
+---@field info fun(): SessionInfo|nil @ 获取当前会话信息，未进入会话时返回 nil。
+---@field is_online fun(): boolean @ 是否处于多人联机会话。
+
+---@class SessionInfo
+---@field online boolean
+---@field player_count integer
+---@field members string[]
+---@field session_id string
+
+---@class ClassDef
+---@field load fun(path:string): table<integer, string> @ 从 lua_framework/defs 目录加载定义文件（toml/json），返回加载的类名列表。
+---@field define fun(def:table): string @ 使用 Lua table 定义类，结构与定义文件中的 class 相同。
+---@field list fun(): table<integer, string>
+---@field new fun(name:string, ptr:AsLuaPtr): GameClassObject @ 将指针包装为指定类的对象。
+---@field singleton fun(name:string): GameClassObject @ 获取类定义中声明的单例对象。
+
+---@class GameClassObject
+---@field _ptr LuaPtr
+---@field _class string
+
+---@class Struct
+---@field define fun(name:string, fields:table<string, table>, size:integer|nil): integer @ 定义结构体布局，字段格式为 `name = {offset, type, count?}`，返回结构体大小。type 为基础类型（如 "f32"、"pointer"）、已定义的结构体名（内嵌）或 "*结构体名"（结构体指针）。指定 count 时字段为数组。指定 size 时检查字段是否超出结构体大小。结构体只在定义它的脚本中可见。
+---@field wrap fun(name:string, ptr:AsLuaPtr): StructObject @ 将指针包装为结构体对象，通过字段名读写内存。
+---@field sizeof fun(name:string): integer
+---@field list fun(): table<integer, string>
+
+---@class StructObject
+---@field _ptr LuaPtr
+---@field _struct string
+---@field _size integer
+
+---@class StructArray @ 结构体中的定长数组，索引从 1 开始，越界访问报错。
+---@field _ptr LuaPtr
+---@field to_table fun(self:StructArray): table
+
+---@class DTI
+---@field list fun(): table<integer, string> @ 列出所有已注册的 DTI 类名。
+---@field find fun(name:string): DtiInfo|nil
+---@field of fun(ptr:AsLuaPtr): DtiInfo|nil @ 获取对象的类型信息，不是 MtObject 时返回 nil。
+---@field parents fun(name:string): table<integer, string> @ 类及其父类名列表，从自身开始。
+---@field is_a fun(ptr:AsLuaPtr, name:string): boolean @ 对象是否为指定类或其子类的实例。
+---@field new fun(name:string): LuaPtr @ 通过 DTI 的分配器创建新实例，不再使用时通过 DTI.destroy 释放，脚本卸载时自动释放。
+---@field destroy fun(ptr:AsLuaPtr) @ 释放 DTI.new 创建的实例，只能释放当前脚本创建的实例。
+---@field properties fun(ptr:AsLuaPtr): DtiProperty[] @ 读取对象的属性列表。
+
+---@class DtiInfo
+---@field name string
+---@field address LuaPtr
+---@field size integer @ 实例大小（字节）。
+---@field id integer
+---@field parent string|nil
+
+---@class DtiProperty
+---@field name string
+---@field type integer @ MT Framework 属性类型编号。
+---@field attr integer
+---@field offset integer|nil @ 字段相对对象的偏移，通过 getter/setter 访问的属性为 nil。
+
+---@class Player @ 当前玩家（猎人），未进入游戏时读取接口返回 nil。
+---@field get fun(): LuaPtr|nil
+---@field position fun(): {x:number, y:number, z:number}|nil
+---@field health fun(): number|nil, number|nil @ 返回当前值和最大值。
+---@field stamina fun(): number|nil, number|nil @ 返回当前值和最大值。
+---@field weapon fun(): {type:integer, id:integer}|nil
+---@field action_id fun(): integer|nil
+---@field set_position fun(position:{x:number, y:number, z:number}|number[]) @ 需要 game_control 权限。
+---@field set_health fun(value:number) @ 截断到 [0, 最大值]，需要 game_control 权限。
+---@field set_stamina fun(value:number) @ 截断到 [0, 最大值]，需要 game_control 权限。
+
+---@class Quest
+---@field id fun(): integer|nil @ 当前任务 ID，不在任务中时返回 nil。
+---@field state fun(): "none"|"active"|"complete"|"failed"|"returning"|"unknown"
+---@field remaining_time fun(): number|nil @ 剩余时间（秒），不在任务中时返回 nil。
+
+---@class Chat
+---@field send fun(text:string) @ 以自己的身份发送聊天消息，超出输入框长度的部分被截断。发送的消息不会作为聊天命令处理。需要 game_control 权限。
+---@field system_message fun(text:string) @ 在聊天记录中显示只有自己可见的系统消息。
+---@field on_message fun(callback:fun(text:string, kind:"sent"|"received", sender:string|nil)|nil) @ 设置聊天消息回调，在下一帧调用，传入 nil 取消。
+---@field register_command fun(name:string, handler:fun(args:string[], raw:string): string|nil, options:ChatCommandOptions|nil) @ 注册聊天命令，如 "!speed"。参数按空白分割，双引号包围的部分作为一个参数。处理函数返回的字符串作为系统消息显示，出错时显示命令用法。输入 "/lf help" 列出所有命令，"/lf" 列出已加载的脚本。
+---@field unregister_command fun(name:string): boolean
+
+---@class ChatCommandOptions
+---@field usage string|nil @ 参数说明，如 "<value>"。
+---@field help string|nil @ 命令说明，显示在 "/lf help" 中。
+---@field swallow boolean|nil @ 是否拦截命令消息，不发送给其他玩家，默认为 true。
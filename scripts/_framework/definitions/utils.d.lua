@@ -1,13 +1,45 @@
 ---@class utils
 ---@field Instant _TInstantConstructor
+---@field deep_copy fun(value:any): any @ 深拷贝。保留元表与循环引用，函数和 userdata 按引用复制。嵌套超过 200 层时报错，table_merge 和 table_diff 同样如此。
+---@field table_merge fun(dst:table, src:table, deep:boolean|nil): table @ 将 src 合并到 dst 中并返回 dst。deep 为 true 时递归合并嵌套 table。
+---@field uuid fun(): string @ 生成 UUID v4 字符串。
+---@field hash32 fun(data:string, seed:integer|nil): integer @ xxHash32。
+---@field hash64 fun(data:string, seed:integer|nil): integer @ xxHash64，结果按 64 位有符号整数返回。
+---@field crc32 fun(data:string): integer @ CRC-32 (IEEE)。
+---@field md5 fun(data:string): string @ 返回小写十六进制字符串。
+---@field sha256 fun(data:string): string @ 返回小写十六进制字符串。
+---@field Random _TRandomConstructor
+---@field time TimeUtils
+---@field debounce fun(fn:function, ms:number): function @ 防抖，连续调用时只在最后一次调用 ms 毫秒后执行。
+---@field throttle fun(fn:function, ms:number): function @ 节流，每 ms 毫秒最多执行一次，间隔内的最后一次调用会在间隔结束后补发。
+---@field assert_game_thread fun(message?: string) @ 断言当前处于游戏主线程，否则抛出错误。
+---@field table_diff fun(a:table, b:table): table<any, {old:any, new:any}> @ 比较两个 table，返回值不同的字段。
+---@field process_info fun(): ProcessInfo @ 进程模块信息与版本指纹。
+
+---@class ProcessInfo
+---@field exe_path string
+---@field base_address integer
+---@field image_size integer
+---@field timestamp integer @ PE 文件头中的链接时间戳
+---@field revision integer|nil @ 游戏版本号
+---@field version table<string, string> @ 版本资源字符串：file_version、product_version、product_name、company_name、file_description
+---@field fingerprint string @ 版本指纹，格式为 "版本号-时间戳-映像大小"，可用于按构建启用功能
 local _ = _
 
 local utils = {
     ---@class _TInstantConstructor
     ---@field now fun(): Instant
-    Instant = {}
+    Instant = {},
+    ---@class _TRandomConstructor
+    ---@field new fun(seed:integer|nil): Random @ 创建随机数生成器，未指定种子时使用系统随机源。
+    Random = {}
 }
 
+---@class Random
+---@field integer fun(self:Random, min:integer, max:integer): integer @ 生成 [min, max] 范围内的整数。
+---@field number fun(self:Random): number @ 生成 [0, 1) 范围内的浮点数。
+---@field bool fun(self:Random, p:number|nil): boolean @ 以概率 p 返回 true，默认 0.5。
+
 ---@class Instant
 ---@field elapsed fun(): Duration
 
@@ -17,3 +49,25 @@ local utils = {
 ---@field as_millis fun(): integer
 ---@field as_micros fun(): integer
 ---@field as_nanos fun(): integer
+
+---@class TimeUtils
+---@field now fun(): number @ Unix 时间戳（秒，含小数部分）。
+---@field now_millis fun(): integer @ Unix 时间戳（毫秒）。
+---@field monotonic fun(): number @ 高精度单调时钟（秒），适用于计时。
+---@field format fun(timestamp:number|nil, format:string|nil, utc:boolean|nil): string @ 格式化时间戳，默认为当前时间，格式默认为 "%Y-%m-%d %H:%M:%S"。
+---@field local_time fun(timestamp:number|nil): LocalTime @ 获取本地时间的各个字段。
+---@field game_time fun(): number @ 框架启动后累计的游戏内地图时钟。
+---@field game_time_scale fun(): number @ 游戏时钟与现实时间（秒）的比例。
+---@field real_to_game fun(seconds:number): number @ 现实时间（秒）转换为游戏时间。
+---@field game_to_real fun(game_time:number): number|nil @ 游戏时间转换为现实时间（秒），时钟未运行时返回 nil。
+
+---@class LocalTime
+---@field year integer
+---@field month integer
+---@field day integer
+---@field hour integer
+---@field minute integer
+---@field second integer
+---@field millis integer
+---@field weekday integer @ 1-7，周一为 1
+---@field utc_offset integer @ 与 UTC 的偏移（秒）
@@ -1,23 +1,126 @@
-use std::{collections::HashMap, sync::LazyLock};
+mod cache;
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, LazyLock},
+    time::{Duration, Instant},
+};
 
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 
-use crate::memory::MemoryUtils;
+use crate::memory::{MemoryError, MemoryUtils};
+
+use cache::AddressCache;
 
 use crate::error::{Error, Result};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 容忍 Hook 时忽略的函数头长度（jmp rel32）
+const HOOKED_PROLOGUE_SIZE: usize = 5;
+/// 延迟解析失败后，再次扫描前的最短间隔
+const DEFERRED_RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 地址解析失败时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResolutionPolicy {
+    /// 解析失败视为错误
+    #[default]
+    Required,
+    /// 相关功能可以缺失，解析失败时不报告
+    Optional,
+    /// 解析失败时禁用相关功能，下次使用时重试
+    Deferred,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AddressRecord {
     pub name: String,
     pub pattern: String,
     pub offset: isize,
+    /// 匹配时忽略特征码的前 N 个字节
+    #[serde(default)]
+    pub skip_bytes: usize,
+    /// 精确匹配失败时，忽略函数头和相对调用/跳转指令重新扫描，
+    /// 用于容忍其他工具对目标函数的 Hook
+    #[serde(default)]
+    pub tolerate_hooks: bool,
+    #[serde(default)]
+    pub policy: ResolutionPolicy,
+    /// 主特征码未找到或未通过校验时，依次尝试的候选特征码
+    #[serde(default)]
+    pub fallbacks: Vec<PatternCandidate>,
+    /// 校验扫描得到的地址，返回 false 时尝试下一个特征码
+    #[serde(skip)]
+    pub validator: Option<AddressValidator>,
+}
+
+impl AddressRecord {
+    /// 按尝试顺序排列的特征码，第一个为主特征码
+    fn candidates(&self) -> Vec<PatternCandidate> {
+        std::iter::once(PatternCandidate {
+            pattern: self.pattern.clone(),
+            offset: self.offset,
+            skip_bytes: self.skip_bytes,
+        })
+        .chain(self.fallbacks.iter().cloned())
+        .collect()
+    }
+
+    fn validate(&self, address: usize) -> bool {
+        self.validator
+            .as_ref()
+            .is_none_or(|validator| (validator.0)(address))
+    }
+}
+
+/// 候选特征码
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PatternCandidate {
+    pub pattern: String,
+    #[serde(default)]
+    pub offset: isize,
+    #[serde(default)]
+    pub skip_bytes: usize,
+}
+
+/// 地址校验回调，参数为加上偏移后的地址
+#[derive(Clone)]
+pub struct AddressValidator(pub Arc<dyn Fn(usize) -> bool + Send + Sync>);
+
+impl std::fmt::Debug for AddressValidator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("AddressValidator")
+    }
+}
+
+/// 地址解析状态
+#[derive(Debug, Clone)]
+pub enum AddressStatus {
+    Resolved(usize),
+    Failed(String),
+    Unresolved,
+}
+
+/// 地址记录及其解析状态，由外部提供的地址没有记录
+#[derive(Debug, Clone)]
+pub struct AddressRecordStatus {
+    pub name: String,
+    pub record: Option<AddressRecord>,
+    pub status: AddressStatus,
 }
 
 #[derive(Default)]
 struct RepositoryInner {
     records: HashMap<String, AddressRecord>,
     data: HashMap<String, usize>,
+    /// 最近一次解析失败的原因
+    errors: HashMap<String, String>,
+    /// 延迟解析失败的记录，在此时间之前不再重新扫描
+    retry_after: HashMap<String, Instant>,
+    /// 随其他记录扫描但未找到的记录，不再参与批量扫描，单独请求时仍会扫描
+    missed: HashSet<String>,
+    cache: AddressCache,
 }
 
 #[derive(Default)]
@@ -34,23 +137,330 @@ impl AddressRepository {
 
     /// 获取指定名称的地址
     pub fn get_address(&self, name: &str) -> Result<usize> {
-        let mut inner = self.inner.lock();
+        let cached = {
+            let inner = self.inner.lock();
+
+            // 直接返回缓存
+            if let Some(address) = inner.data.get(name) {
+                return Ok(*address);
+            }
 
-        // 直接返回缓存
-        if let Some(address) = inner.data.get(name) {
-            return Ok(*address);
+            let Some(record) = inner.records.get(name) else {
+                return Err(Error::AddressRecordNotFound(name.to_string()));
+            };
+            inner
+                .cache
+                .get(record)
+                .map(|address| (address, record.clone()))
+        };
+
+        // 使用上次启动保存的地址，同样需要通过校验
+        if let Some((address, record)) = cached {
+            if record.validate(address) {
+                self.inner.lock().data.insert(name.to_string(), address);
+                return Ok(address);
+            }
+            log::debug!(
+                "Cached address 0x{:x} of '{}' rejected by validator",
+                address,
+                name
+            );
+            self.inner.lock().cache.remove(name);
         }
 
-        // 扫描地址
-        let Some(record) = inner.records.get(name) else {
-            return Err(Error::AddressRecordNotFound(name.to_string()));
+        let pending = Self::pending_records(&self.inner.lock(), name);
+
+        // 扫描时不持有锁，校验回调中可以获取其他地址
+        let mut results = match Self::scan_candidates(&pending) {
+            Ok(results) => results,
+            Err(e) => {
+                self.inner
+                    .lock()
+                    .errors
+                    .insert(name.to_string(), e.to_string());
+                return Err(e);
+            }
+        };
+        let result = match results[0] {
+            Some(address) => Ok(address),
+            None => Self::scan_last_resort(&pending[0]),
         };
+        if let Ok(address) = result {
+            results[0] = Some(address);
+        }
+        self.store_results(&pending, &results, result.as_ref().err());
+        self.save_cache();
+
+        result
+    }
 
-        let addr = MemoryUtils::auto_scan_first(&record.pattern)?;
-        let addr = ((addr as isize) + record.offset) as usize;
-        inner.data.insert(name.to_string(), addr);
+    /// 获取地址，解析失败时返回 None，下次使用时重试
+    ///
+    /// 失败后至少间隔 [`DEFERRED_RETRY_INTERVAL`] 才重新扫描，避免每次使用都扫描主模块。
+    pub fn get_address_deferred(&self, name: &str) -> Option<usize> {
+        {
+            let mut inner = self.inner.lock();
+            if let Some(address) = inner.data.get(name) {
+                return Some(*address);
+            }
+            let now = Instant::now();
+            if inner.retry_after.get(name).is_some_and(|time| now < *time) {
+                return None;
+            }
+            inner
+                .retry_after
+                .insert(name.to_string(), now + DEFERRED_RETRY_INTERVAL);
+        }
 
-        Ok(addr)
+        match self.get_address(name) {
+            Ok(address) => {
+                self.inner.lock().retry_after.remove(name);
+                Some(address)
+            }
+            Err(e) => {
+                log::debug!("Deferred address '{}' is unresolved: {}", name, e);
+                None
+            }
+        }
+    }
+
+    /// 汇总解析失败的地址，输出一条警告并显示在错误面板中，可选地址不报告
+    ///
+    /// 返回报告的地址数量。
+    pub fn report_unresolved(&self) -> usize {
+        let unresolved = self
+            .list()
+            .into_iter()
+            .filter_map(|item| {
+                let AddressStatus::Failed(error) = item.status else {
+                    return None;
+                };
+                let policy = item.record.map(|record| record.policy).unwrap_or_default();
+                (policy != ResolutionPolicy::Optional)
+                    .then(|| format!("- {} ({:?}): {}", item.name, policy, error))
+            })
+            .collect::<Vec<_>>();
+        if unresolved.is_empty() {
+            return 0;
+        }
+
+        let message = format!(
+            "{} address(es) failed to resolve, related features are disabled:\n{}",
+            unresolved.len(),
+            unresolved.join("\n")
+        );
+        log::warn!("{}", message);
+        crate::error::set_last_error(message);
+        unresolved.len()
+    }
+
+    /// 保存地址缓存，没有修改或游戏版本号未知时跳过
+    pub fn save_cache(&self) {
+        if !self.inner.lock().cache.is_dirty() {
+            return;
+        }
+        let Some(revision) = crate::utility::get_game_revision() else {
+            return;
+        };
+        if let Err(e) = self.inner.lock().cache.save(revision) {
+            log::warn!("Failed to save address cache: {}", e.log());
+        }
+    }
+
+    /// 需要扫描的记录，`requested` 在第一个
+    ///
+    /// 同时扫描其他尚未解析的记录，避免每条记录各扫描一遍主模块。
+    fn pending_records(inner: &RepositoryInner, requested: &str) -> Vec<AddressRecord> {
+        let mut pending = vec![inner.records[requested].clone()];
+        pending.extend(
+            inner
+                .records
+                .values()
+                .filter(|record| {
+                    record.name != requested
+                        && !inner.data.contains_key(&record.name)
+                        && !inner.errors.contains_key(&record.name)
+                        && !inner.missed.contains(&record.name)
+                        && inner.cache.get(record).is_none()
+                })
+                .cloned(),
+        );
+        pending
+    }
+
+    /// 扫描记录，返回各记录加上偏移后的地址，未找到时为 None
+    ///
+    /// 每轮批量扫描所有未解析记录的下一个候选特征码，直到全部解析或候选特征码用尽。
+    fn scan_candidates(records: &[AddressRecord]) -> Result<Vec<Option<usize>>> {
+        let candidates = records
+            .iter()
+            .map(AddressRecord::candidates)
+            .collect::<Vec<_>>();
+        let rounds = candidates.iter().map(Vec::len).max().unwrap_or(0);
+        let mut results = vec![None; records.len()];
+
+        for round in 0..rounds {
+            let mut indices = Vec::new();
+            let mut patterns = Vec::new();
+            for (index, record_candidates) in candidates.iter().enumerate() {
+                if results[index].is_some() {
+                    continue;
+                }
+                let Some(candidate) = record_candidates.get(round) else {
+                    continue;
+                };
+                match MemoryUtils::masked_pattern(&candidate.pattern, candidate.skip_bytes, false) {
+                    Ok(pattern) => {
+                        indices.push(index);
+                        patterns.push(pattern);
+                    }
+                    Err(e) => log::warn!(
+                        "Invalid pattern #{} of address record '{}': {}",
+                        round,
+                        records[index].name,
+                        e
+                    ),
+                }
+            }
+            if patterns.is_empty() {
+                continue;
+            }
+
+            let found = MemoryUtils::scan_batch(&patterns)?;
+            for (index, address) in indices.into_iter().zip(found) {
+                let Some(address) = address else {
+                    continue;
+                };
+                let record = &records[index];
+                let address = ((address as isize) + candidates[index][round].offset) as usize;
+                if !record.validate(address) {
+                    log::debug!(
+                        "Address 0x{:x} of '{}' rejected by validator",
+                        address,
+                        record.name
+                    );
+                    continue;
+                }
+                if round != 0 {
+                    log::info!(
+                        "Address '{}' resolved by fallback pattern #{}",
+                        record.name,
+                        round
+                    );
+                }
+                results[index] = Some(address);
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// 所有候选特征码均未找到时的最后尝试，返回加上偏移后的地址
+    fn scan_last_resort(record: &AddressRecord) -> Result<usize> {
+        // 报告主特征码本身的错误
+        MemoryUtils::masked_pattern(&record.pattern, record.skip_bytes, false)?;
+
+        let address = Self::scan_tolerating_hooks(record)?;
+        let address = ((address as isize) + record.offset) as usize;
+        if !record.validate(address) {
+            return Err(MemoryError::NotFound(record.pattern.clone()).into());
+        }
+        Ok(address)
+    }
+
+    /// 保存扫描结果，第一条以外未找到的记录保持未解析状态，在单独请求时再报告错误
+    ///
+    /// 第一条以外未找到的记录记为未命中，之后不再随其他记录扫描。
+    fn store_results(
+        &self,
+        records: &[AddressRecord],
+        results: &[Option<usize>],
+        requested_error: Option<&Error>,
+    ) {
+        let mut inner = self.inner.lock();
+        if let Some(e) = requested_error {
+            inner.errors.insert(records[0].name.clone(), e.to_string());
+        }
+        for (index, (record, address)) in records.iter().zip(results).enumerate() {
+            let Some(address) = address else {
+                if index != 0 {
+                    inner.missed.insert(record.name.clone());
+                }
+                continue;
+            };
+            inner.missed.remove(&record.name);
+            inner.errors.remove(&record.name);
+            inner.data.insert(record.name.clone(), *address);
+            inner.cache.insert(record, *address);
+        }
+    }
+
+    /// 丢弃缓存的地址并重新扫描
+    pub fn rescan(&self, name: &str) -> Result<usize> {
+        {
+            let mut inner = self.inner.lock();
+            if !inner.records.contains_key(name) {
+                return Err(Error::AddressRecordNotFound(name.to_string()));
+            }
+            inner.data.remove(name);
+            inner.errors.remove(name);
+            inner.retry_after.remove(name);
+            inner.missed.remove(name);
+            inner.cache.remove(name);
+        }
+        self.get_address(name)
+    }
+
+    /// 列出所有地址记录及解析状态，按名称排序
+    pub fn list(&self) -> Vec<AddressRecordStatus> {
+        let inner = self.inner.lock();
+        let mut names = inner
+            .records
+            .keys()
+            .chain(inner.data.keys())
+            .cloned()
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+
+        names
+            .into_iter()
+            .map(|name| {
+                let status = if let Some(addr) = inner.data.get(&name) {
+                    AddressStatus::Resolved(*addr)
+                } else if let Some(error) = inner.errors.get(&name) {
+                    AddressStatus::Failed(error.clone())
+                } else {
+                    AddressStatus::Unresolved
+                };
+                AddressRecordStatus {
+                    record: inner.records.get(&name).cloned(),
+                    name,
+                    status,
+                }
+            })
+            .collect()
+    }
+
+    /// 精确匹配失败后的重试，仅用于允许容忍 Hook 的记录
+    fn scan_tolerating_hooks(record: &AddressRecord) -> Result<usize> {
+        if !record.tolerate_hooks {
+            return Err(MemoryError::NotFound(record.pattern.clone()).into());
+        }
+
+        log::warn!(
+            "Pattern of '{}' not found, retrying with hooked prologue and call sites ignored",
+            record.name
+        );
+        // Hook 写入的跳转位于函数入口，即匹配位置加上 offset 处
+        let hook_start = record.offset.max(0) as usize;
+        let hook_end = (record.offset + HOOKED_PROLOGUE_SIZE as isize).max(0) as usize;
+        Ok(MemoryUtils::auto_scan_first_masked(
+            &record.pattern,
+            record.skip_bytes,
+            hook_start..hook_end,
+            true,
+        )?)
     }
 
     /// 获取指定名称的地址（指针形式）
@@ -58,25 +468,63 @@ impl AddressRepository {
         self.get_address(name).map(|addr| addr as *mut T)
     }
 
-    /// 设置地址记录
+    /// 获取延迟解析的地址（指针形式），见 [`Self::get_address_deferred`]
+    pub fn get_ptr_deferred<T>(&self, name: &str) -> Result<*mut T> {
+        self.get_address_deferred(name)
+            .map(|addr| addr as *mut T)
+            .ok_or_else(|| Error::AddressUnresolved(name.to_string()))
+    }
+
+    /// 设置由外部提供的已解析地址，覆盖扫描结果
+    pub fn provide_address(&self, name: &str, address: usize) {
+        let mut inner = self.inner.lock();
+        inner.errors.remove(name);
+        if let Some(old) = inner.data.insert(name.to_string(), address)
+            && old != address
+        {
+            log::warn!(
+                "Address '{}' replaced by provided address: 0x{:x} -> 0x{:x}",
+                name,
+                old,
+                address
+            );
+        }
+    }
+
+    /// 设置地址记录，清除旧记录的解析结果
     pub fn set_record(&self, record: AddressRecord) {
         let mut inner = self.inner.lock();
-        inner.records.insert(record.name.clone(), record);
+        let name = record.name.clone();
+        inner.data.remove(&name);
+        inner.errors.remove(&name);
+        inner.retry_after.remove(&name);
+        inner.missed.remove(&name);
+        inner.records.insert(name, record);
     }
 
-    fn set_record_inner(inner: &mut RepositoryInner, name: &str, pattern: &str, offset: isize) {
+    fn set_record_inner<'a>(
+        inner: &'a mut RepositoryInner,
+        name: &str,
+        pattern: &str,
+        offset: isize,
+    ) -> &'a mut AddressRecord {
         inner.records.insert(
             name.to_string(),
             AddressRecord {
                 name: name.to_string(),
                 pattern: pattern.to_string(),
                 offset,
+                ..Default::default()
             },
         );
+        inner.records.get_mut(name).unwrap()
     }
 
     fn new_with_internal() -> Self {
-        let mut inner = RepositoryInner::default();
+        let mut inner = RepositoryInner {
+            cache: AddressCache::load(),
+            ..Default::default()
+        };
         Self::set_record_inner(
             &mut inner,
             Self::CORE_POST_MH_MAIN_CTOR,
@@ -95,11 +543,25 @@ impl AddressRepository {
             "E8 ?? ?? ?? ?? 48 8B 4B 08 0F 57 FF 48 8B",
             -32,
         );
+        // 该函数常被其他框架 Hook
         Self::set_record_inner(
             &mut inner,
             Self::CHAT_MESSAGE_SENT,
             "44 89 ?? ?? ?? ?? ?? 44 88 00 4C 89 ?? ?? ?? ?? ?? 4C 89 ?? ?? ?? ?? ?? 44 89",
             -26,
+        )
+        .tolerate_hooks = true;
+        Self::set_record_inner(
+            &mut inner,
+            Self::CHAT_MESSAGE_RECEIVED,
+            "48 89 5C 24 10 48 89 74 24 18 57 48 83 EC 30 48 8B 7A 70 48 8B F2",
+            0,
+        );
+        Self::set_record_inner(
+            &mut inner,
+            Self::CHAT_SYSTEM_MESSAGE,
+            "48 89 5C 24 08 48 89 74 24 10 57 48 83 EC 30 41 0F B6 F9 8B F2",
+            0,
         );
         Self::set_record_inner(&mut inner, Self::MONSTER_CTOR, "4C 89 B3 10 76 00 00", -60);
         Self::set_record_inner(
@@ -108,6 +570,61 @@ impl AddressRepository {
             "48 83 EC 20 48 8B B9 A0 09 00 00",
             -20,
         );
+        Self::set_record_inner(
+            &mut inner,
+            Self::MONSTER_DO_ACTION,
+            "48 89 5C 24 08 57 48 83 EC 20 48 63 42 04 48 8B F9 48 8B DA",
+            0,
+        );
+        Self::set_record_inner(
+            &mut inner,
+            Self::MONSTER_SET_ANGER,
+            "40 53 48 83 EC 20 0F 28 C1 48 8B D9 F3 0F 11 41 24",
+            0,
+        );
+        Self::set_record_inner(
+            &mut inner,
+            Self::MONSTER_SET_STAMINA,
+            "48 83 EC 28 F3 0F 10 91 FC C0 01 00 0F 2F CA",
+            0,
+        );
+        Self::set_record_inner(
+            &mut inner,
+            Self::MONSTER_SET_TARGET,
+            "48 89 5C 24 08 57 48 83 EC 20 48 8B FA 48 8B D9 48 3B 91 A8 75 00 00",
+            0,
+        );
+        Self::set_record_inner(
+            &mut inner,
+            Self::SPAWN_DROP_ITEM,
+            "48 89 5C 24 10 48 89 74 24 18 57 48 83 EC 50 0F 28 02 41 8B F0 8B FA",
+            0,
+        );
+        Self::set_record_inner(
+            &mut inner,
+            Self::SPAWN_ENDEMIC_LIFE,
+            "40 53 48 83 EC 40 0F 28 02 8B DA 48 8D 54 24 20 0F 29 44 24 20",
+            0,
+        );
+        Self::set_record_inner(
+            &mut inner,
+            Self::QUEST_SET_STATE,
+            "48 89 5C 24 08 57 48 83 EC 20 8B 41 38 8B FA 48 8B D9 3B C2 74",
+            0,
+        );
+        Self::set_record_inner(
+            &mut inner,
+            Self::DAMAGE_CALCULATE,
+            "48 89 5C 24 10 48 89 6C 24 18 56 57 41 56 48 83 EC 40 48 8B F2 48 8B E9",
+            0,
+        );
+        // 加载相机全局指针的指令
+        Self::set_record_inner(
+            &mut inner,
+            Self::CAMERA_INSTANCE,
+            "48 8B 0D ?? ?? ?? ?? 48 85 C9 74 ?? 48 8B 01 F3 0F 10 89 ?? ?? ?? ?? FF 50",
+            0,
+        );
         Self::set_record_inner(
             &mut inner,
             "GUITitle:Play",
@@ -127,6 +644,33 @@ impl AddressRepository {
             0,
         );
 
+        // 以下地址只在调用相关功能时使用，解析失败时不影响框架运行。
+        // 启动时创建 Hook 的地址只解析一次，不使用延迟解析
+        for name in [
+            Self::CHAT_SYSTEM_MESSAGE,
+            Self::MONSTER_DO_ACTION,
+            Self::MONSTER_SET_ANGER,
+            Self::MONSTER_SET_STAMINA,
+            Self::MONSTER_SET_TARGET,
+            Self::SPAWN_DROP_ITEM,
+            Self::SPAWN_ENDEMIC_LIFE,
+            Self::CAMERA_INSTANCE,
+            "GUITitle:Play",
+        ] {
+            if let Some(record) = inner.records.get_mut(name) {
+                record.policy = ResolutionPolicy::Deferred;
+            }
+        }
+        // 只存在与当前图形 API 对应的一个
+        for name in [
+            "D3DRender12:SwapChainPresentCall",
+            "D3DRender11:SwapChainPresentCall",
+        ] {
+            if let Some(record) = inner.records.get_mut(name) {
+                record.policy = ResolutionPolicy::Optional;
+            }
+        }
+
         Self {
             inner: Mutex::new(inner),
         }
@@ -136,6 +680,17 @@ impl AddressRepository {
     pub const CORE_MAP_CLOCK_LOCAL: &str = "Core::MapClockLocal";
     pub const C_SYSTEM_CTOR: &str = "cSystem:Ctor";
     pub const CHAT_MESSAGE_SENT: &str = "Chat:MessageSent";
+    pub const CHAT_MESSAGE_RECEIVED: &str = "Chat:MessageReceived";
+    pub const CHAT_SYSTEM_MESSAGE: &str = "Chat:SystemMessage";
     pub const MONSTER_CTOR: &str = "Monster:Ctor";
     pub const MONSTER_DTOR: &str = "Monster:Dtor";
+    pub const MONSTER_DO_ACTION: &str = "Monster:DoAction";
+    pub const MONSTER_SET_ANGER: &str = "Monster:SetAnger";
+    pub const MONSTER_SET_STAMINA: &str = "Monster:SetStamina";
+    pub const MONSTER_SET_TARGET: &str = "Monster:SetTarget";
+    pub const SPAWN_DROP_ITEM: &str = "Spawn:DropItem";
+    pub const SPAWN_ENDEMIC_LIFE: &str = "Spawn:EndemicLife";
+    pub const QUEST_SET_STATE: &str = "Quest:SetState";
+    pub const DAMAGE_CALCULATE: &str = "Damage:Calculate";
+    pub const CAMERA_INSTANCE: &str = "Camera:Instance";
 }
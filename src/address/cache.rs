@@ -0,0 +1,169 @@
+//! 地址缓存
+//!
+//! 解析成功的地址以相对主模块的偏移保存到 `lua_framework/cache/addresses_<版本号>.json`，
+//! 之后启动时直接使用，只有缓存未命中或游戏更新后才重新扫描。
+//!
+//! 游戏版本号需要扫描特征码才能获取，因此启动时按文件中保存的 PE 时间戳和映像大小查找缓存，
+//! 有修改时再获取版本号并保存。版本号只获取一次。
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::AddressRecord;
+use crate::{error::Result, memory::MemoryUtils};
+
+const CACHE_DIR: &str = "lua_framework/cache";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    /// 相对主模块基地址的偏移
+    rva: usize,
+    pattern: String,
+    offset: isize,
+    skip_bytes: usize,
+}
+
+impl CacheEntry {
+    fn matches(&self, record: &AddressRecord) -> bool {
+        self.pattern == record.pattern
+            && self.offset == record.offset
+            && self.skip_bytes == record.skip_bytes
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheFile {
+    revision: u32,
+    timestamp: u32,
+    image_size: usize,
+    entries: HashMap<String, CacheEntry>,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct AddressCache {
+    base: usize,
+    image_size: usize,
+    timestamp: u32,
+    entries: HashMap<String, CacheEntry>,
+    /// 是否有未保存的修改
+    dirty: bool,
+}
+
+impl AddressCache {
+    /// 加载与当前主模块匹配的缓存文件
+    pub fn load() -> Self {
+        let Ok((base, image_size)) = MemoryUtils::base_module_space() else {
+            return Self::default();
+        };
+        let timestamp = unsafe { crate::utility::process::read_pe_timestamp(base) };
+        let mut cache = Self {
+            base,
+            image_size,
+            timestamp,
+            ..Default::default()
+        };
+
+        let Ok(dir) = std::fs::read_dir(CACHE_DIR) else {
+            return cache;
+        };
+        for entry in dir.flatten() {
+            let path = entry.path();
+            let is_cache_file = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("addresses_") && name.ends_with(".json"));
+            if !is_cache_file {
+                continue;
+            }
+            let Some(file) = read_cache_file(&path) else {
+                continue;
+            };
+            if file.timestamp == timestamp && file.image_size == image_size {
+                log::debug!(
+                    "Loaded {} cached addresses from {}",
+                    file.entries.len(),
+                    path.display()
+                );
+                cache.entries = file.entries;
+                break;
+            }
+        }
+
+        cache
+    }
+
+    /// 获取记录的缓存地址，记录的特征码或偏移变化时视为未命中
+    pub fn get(&self, record: &AddressRecord) -> Option<usize> {
+        let entry = self.entries.get(&record.name)?;
+        if !entry.matches(record) || entry.rva >= self.image_size {
+            return None;
+        }
+        Some(self.base + entry.rva)
+    }
+
+    /// 缓存扫描得到的地址，不在主模块内的地址不缓存
+    pub fn insert(&mut self, record: &AddressRecord, address: usize) {
+        if self.base == 0 || !(self.base..self.base + self.image_size).contains(&address) {
+            return;
+        }
+        self.entries.insert(
+            record.name.clone(),
+            CacheEntry {
+                rva: address - self.base,
+                pattern: record.pattern.clone(),
+                offset: record.offset,
+                skip_bytes: record.skip_bytes,
+            },
+        );
+        self.dirty = true;
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        if self.entries.remove(name).is_some() {
+            self.dirty = true;
+        }
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// 保存到指定游戏版本的缓存文件，没有修改时跳过
+    pub fn save(&mut self, revision: u32) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        let file = CacheFile {
+            revision,
+            timestamp: self.timestamp,
+            image_size: self.image_size,
+            entries: self.entries.clone(),
+        };
+        let content = serde_json::to_string_pretty(&file).map_err(std::io::Error::from)?;
+        std::fs::create_dir_all(CACHE_DIR)?;
+        std::fs::write(cache_path(revision), content)?;
+        self.dirty = false;
+
+        Ok(())
+    }
+}
+
+fn cache_path(revision: u32) -> PathBuf {
+    Path::new(CACHE_DIR).join(format!("addresses_{}.json", revision))
+}
+
+fn read_cache_file(path: &Path) -> Option<CacheFile> {
+    let content = std::fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(file) => Some(file),
+        Err(e) => {
+            log::warn!("Ignoring invalid address cache {}: {}", path.display(), e);
+            None
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! 定期自动保存
+//!
+//! 配置默认只在修改时保存。自动保存每隔一段时间以及场景切换（如任务结束返回据点）时
+//! 保存框架配置，并调用脚本的 `on_save` 回调由脚本保存各自的设置，
+//! 避免游戏崩溃时丢失修改。
+
+use std::{
+    sync::{
+        LazyLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::{config::Config, luavm::LuaVMManager};
+
+pub struct Autosave {
+    last_save: Mutex<Instant>,
+    /// 上一帧是否处于场景加载中
+    was_loading: AtomicBool,
+}
+
+impl Autosave {
+    pub fn instance() -> &'static Autosave {
+        static INSTANCE: LazyLock<Autosave> = LazyLock::new(|| Autosave {
+            last_save: Mutex::new(Instant::now()),
+            was_loading: AtomicBool::new(false),
+        });
+        &INSTANCE
+    }
+
+    /// 每帧调用，到达保存间隔或开始切换场景时保存
+    pub fn tick(&self) {
+        let (interval_minutes, on_scene_change) = {
+            let config = Config::global();
+            (
+                config.autosave.interval_minutes,
+                config.autosave.on_scene_change,
+            )
+        };
+
+        let loading = crate::game::scene::is_loading();
+        let was_loading = self.was_loading.swap(loading, Ordering::Relaxed);
+        let entered_loading = loading && !was_loading;
+
+        if on_scene_change && entered_loading {
+            self.save_now("scene change");
+            return;
+        }
+
+        let interval = Duration::from_secs(interval_minutes as u64 * 60);
+        if interval_minutes != 0 && self.last_save.lock().elapsed() >= interval {
+            self.save_now("interval");
+        }
+    }
+
+    /// 立即保存配置并通知脚本
+    pub fn save_now(&self, reason: &str) {
+        *self.last_save.lock() = Instant::now();
+        log::debug!("Autosave triggered by {}", reason);
+
+        if let Err(e) = Config::global().try_save_global() {
+            log::error!("Failed to save global config: {}", e.log());
+        }
+        LuaVMManager::instance().invoke_fn("on_save");
+    }
+}
@@ -1,21 +1,44 @@
+use std::thread::JoinHandle;
+
+use luaf_include::InitPhase;
+use mlua::prelude::*;
+use parking_lot::Mutex;
+
 use crate::address::AddressRepository;
 
 use crate::error::Error;
-use crate::luavm::LuaVMManager;
+use crate::game::damage::{DAMAGE_EVENT_VERSION, DamageEvent};
+use crate::game::monster::MonsterEvent;
+use crate::game::quest::QuestEvent;
+use crate::input::InputEvent;
+use crate::luavm::library::sdk::luaptr::LuaPtr;
+use crate::luavm::{LuaVMManager, ReloadRequest};
+use crate::render_core::splash;
 use crate::{static_mut, static_ref};
 
+pub mod phase;
+
+use phase::InitPhases;
+
 static mut MH_MAIN_CTOR_HOOK: Option<safetyhook::MidHook> = None;
 
+/// 初始化线程中的扩展加载任务
+static EXTENSION_LOADER: Mutex<Option<JoinHandle<Result<(usize, usize), Error>>>> =
+    Mutex::new(None);
+
 static mut ON_POST_MH_MAIN_CTOR_CALLBACK: Option<
     Box<dyn FnOnce() -> Result<(), Error> + Send + 'static>,
 > = None;
 
 unsafe extern "C" fn mh_main_ctor_hooked(_ctx: &mut safetyhook::mid_hook::Context) {
     unsafe {
-        if let Some(on_post) = static_mut!(ON_POST_MH_MAIN_CTOR_CALLBACK).take()
-            && let Err(e) = on_post()
-        {
-            log::error!("Failed to run bootstrap callback: {}", e)
+        if let Some(on_post) = static_mut!(ON_POST_MH_MAIN_CTOR_CALLBACK).take() {
+            splash::mark_started();
+            let result = on_post();
+            if let Err(e) = &result {
+                log::error!("Failed to run bootstrap callback: {}", e.log())
+            }
+            splash::mark_finished(result.err().map(|e| e.to_string()));
         };
     }
 }
@@ -32,37 +55,119 @@ fn create_mh_main_ctor_hook() -> Result<(), Error> {
     Ok(())
 }
 
+/// 加载扩展，进入 `Extensions` 阶段
+fn load_extensions() -> Result<(usize, usize), Error> {
+    InitPhases::instance().enter(InitPhase::Extensions);
+    let (total, success) = crate::extension::CoreAPI::instance().load_core_exts()?;
+    log::info!(
+        "Loaded {} extensions successfully, {} failed.",
+        success,
+        total - success
+    );
+    Ok((total, success))
+}
+
+/// 等待初始化线程加载扩展，未启用初始化线程时在当前线程加载
+fn wait_for_extensions() -> Result<(usize, usize), Error> {
+    let Some(loader) = EXTENSION_LOADER.lock().take() else {
+        return load_extensions();
+    };
+    loader.join().unwrap_or_else(|_| {
+        log::error!("Extension loader thread panicked");
+        Ok((0, 0))
+    })
+}
+
+/// 初始化框架
+///
+/// 初始化按 [phase] 中描述的阶段顺序进行：扩展 → 地址解析 → 脚本。
+/// 核心函数在加载扩展前注册，扩展可在 `ExtInitialize` 中注册阶段回调。
 pub fn setup() -> Result<(), Error> {
+    // 注册核心函数
+    crate::render_core::RenderManager::register_core_functions();
+    crate::extension::bridge::CompatBridge::register_core_functions();
+    crate::extension::window::WindowHook::register_core_functions();
+    crate::extension::CoreAPI::instance().register_core_functions();
+    InitPhases::register_core_functions();
+
+    if crate::config::Config::global()
+        .extensions
+        .load_on_init_thread
+    {
+        log::info!("Loading extensions on init thread...");
+        let handle = std::thread::Builder::new()
+            .name("luaf-init".to_string())
+            .spawn(load_extensions)?;
+        *EXTENSION_LOADER.lock() = Some(handle);
+    }
+
     unsafe {
         if static_ref!(MH_MAIN_CTOR_HOOK).is_none() {
             create_mh_main_ctor_hook()?;
         }
 
         ON_POST_MH_MAIN_CTOR_CALLBACK = Some(Box::new(|| {
+            // 标记游戏主线程
+            crate::game::thread::mark_game_thread();
             // 处理单例
             crate::game::singleton::SingletonManager::instance().parse_singletons();
+            match crate::utility::process::process_info() {
+                Ok(info) => log::info!("Game build: {}", info.fingerprint),
+                Err(e) => log::warn!("Failed to read process info: {}", e.log()),
+            }
             // 初始化输入
             crate::input::Input::initialize()?;
-            // 注册Render函数
-            crate::render_core::RenderManager::register_core_functions();
-
-            // 注册扩展
-            let (total, success) = crate::extension::CoreAPI::instance().load_core_exts()?;
-            log::info!(
-                "Loaded {} extensions successfully, {} failed.",
-                success,
-                total - success
-            );
-
-            // 初始加载 LuaVM
+            // 回调耗时预算
+            if let Some(budget_ms) = crate::config::Config::global().scripts.callback_budget_ms {
+                crate::profiler::Profiler::instance().set_callback_budget(budget_ms);
+            }
+
+            // 阶段一：加载扩展
+            let (total, success) = wait_for_extensions()?;
+            splash::set_extensions(total, success);
+
+            // 阶段二：地址解析，复用兼容桥接提供的地址和 Hook
+            InitPhases::instance().enter(InitPhase::Addresses);
+            crate::luavm::PatchProfileManager::instance().apply_enabled_profiles();
+            if let Err(e) = crate::game::command::init_game_command() {
+                log::error!("Failed to initialize game command: {}", e.log());
+            }
+
+            // 阶段三：初始加载 LuaVM
+            InitPhases::instance().enter(InitPhase::Scripts);
             log::info!("Loading scripts...");
-            LuaVMManager::instance().auto_load_vms(LuaVMManager::LUA_SCRIPTS_DIR)?;
+            let vms = LuaVMManager::instance().auto_load_script_dirs()?;
+            splash::set_scripts(vms.len());
+            crate::luavm::watcher::ScriptWatcher::instance().start();
 
             // 设置 on_update 回调
             crate::game::on_update::on_map_clock_local(|| {
+                crate::game::network::refresh_online_state();
+                handle_reload_key();
+                LuaVMManager::instance().process_pending_reload();
+                LuaVMManager::instance().process_script_changes();
+                dispatch_new_singletons();
+                dispatch_monster_events();
+                dispatch_quest_events();
+                dispatch_chat_messages();
+                dispatch_damage_events();
+                dispatch_extension_events();
+                dispatch_budget_events();
+                crate::input::Input::instance().apply_injected();
+                dispatch_input_events();
+                dispatch_hotkeys();
+                LuaVMManager::instance().tick_timers();
+                crate::autosave::Autosave::instance().tick();
                 LuaVMManager::instance().invoke_fn("on_update")
             })?;
 
+            // 初始化用到的地址已全部解析，游戏版本号此时可用
+            AddressRepository::instance().save_cache();
+            AddressRepository::instance().report_unresolved();
+
+            InitPhases::instance().enter(InitPhase::Ready);
+            LuaVMManager::instance()
+                .invoke_fn_with_args("on_event:init_phase", phase::phase_name(InitPhase::Ready));
             log::info!("LuaFramework initialized.");
 
             // 隐藏前台控制台窗口，防止分辨率问题
@@ -76,6 +181,150 @@ pub fn setup() -> Result<(), Error> {
     Ok(())
 }
 
+/// 处理重载快捷键
+fn handle_reload_key() {
+    let Some(reload_key) = crate::config::Config::global().scripts.reload_key else {
+        return;
+    };
+    if crate::input::Input::instance()
+        .keyboard()
+        .is_pressed(reload_key)
+    {
+        log::info!("Reload hotkey pressed");
+        LuaVMManager::instance().request_reload(ReloadRequest::All);
+    }
+}
+
+/// 解析新构造的单例并通知脚本
+fn dispatch_new_singletons() {
+    let singletons = crate::game::singleton::SingletonManager::instance().parse_singletons();
+    if !singletons.is_empty() {
+        LuaVMManager::instance().dispatch_singletons_registered(&singletons);
+    }
+}
+
+/// 分发怪物生成和析构事件，参数为 (怪物指针, 种类 ID, 子种类 ID, 实例 ID)
+fn dispatch_monster_events() {
+    for event in crate::game::monster::take_events() {
+        let (fn_name, entry) = match event {
+            MonsterEvent::Spawn(entry) => ("on_monster_spawn", entry),
+            MonsterEvent::Death(entry) => ("on_monster_death", entry),
+        };
+        LuaVMManager::instance().invoke_fn_with_args(
+            fn_name,
+            (
+                LuaPtr::new(entry.address as u64),
+                entry.type_id,
+                entry.type_sub_id,
+                entry.id,
+            ),
+        );
+    }
+}
+
+/// 分发任务开始、结束和返回据点事件
+fn dispatch_quest_events() {
+    let manager = LuaVMManager::instance();
+    for event in crate::game::quest::take_events() {
+        match event {
+            QuestEvent::Start(id) => manager.invoke_fn_with_args("on_quest_start", id),
+            QuestEvent::Complete(id, success) => {
+                manager.invoke_fn_with_args("on_quest_complete", (id, success))
+            }
+            QuestEvent::Return(id) => manager.invoke_fn_with_args("on_quest_return", id),
+        }
+    }
+}
+
+/// 分发聊天消息，参数为 (消息, 类型, 发送者)
+fn dispatch_chat_messages() {
+    for message in crate::game::chat::take_events() {
+        LuaVMManager::instance().invoke_fn_with_args(
+            "on_chat_message",
+            (
+                message.text,
+                message.kind.as_ref().to_string(),
+                message.sender,
+            ),
+        );
+    }
+}
+
+/// 分发伤害事件，参数为伤害记录表
+fn dispatch_damage_events() {
+    for event in crate::game::damage::take_events() {
+        LuaVMManager::instance().invoke_fn_with_args("on_damage", DamageRecord(event));
+    }
+}
+
+/// 传入脚本的伤害记录
+#[derive(Clone)]
+struct DamageRecord(DamageEvent);
+
+impl IntoLua for DamageRecord {
+    fn into_lua(self, lua: &Lua) -> LuaResult<LuaValue> {
+        let event = self.0;
+        let table = lua.create_table()?;
+        table.set("version", DAMAGE_EVENT_VERSION)?;
+        table.set(
+            "attacker",
+            (event.attacker != 0).then(|| LuaPtr::new(event.attacker as u64)),
+        )?;
+        table.set("victim", LuaPtr::new(event.victim as u64))?;
+        table.set("raw_damage", event.raw_damage)?;
+        table.set("damage", event.damage)?;
+        table.set("element", event.element.as_ref())?;
+        table.set("part_id", event.part_id)?;
+        table.set("critical", event.critical)?;
+        Ok(LuaValue::Table(table))
+    }
+}
+
+/// 分发扩展发布的 Lua 事件
+fn dispatch_extension_events() {
+    for (name, payload) in crate::extension::CoreAPI::instance().take_lua_events() {
+        LuaVMManager::instance().invoke_fn_with_args(&format!("on_event:{}", name), payload);
+    }
+}
+
+/// 发布回调超出预算事件，事件内容为 JSON
+fn dispatch_budget_events() {
+    for event in crate::profiler::Profiler::instance().take_budget_events() {
+        let Ok(payload) = serde_json::to_string(&event) else {
+            continue;
+        };
+        LuaVMManager::instance().invoke_fn_with_args("on_event:script_over_budget", payload);
+    }
+}
+
+/// 分发按键状态变化事件
+fn dispatch_input_events() {
+    for event in crate::input::Input::instance().poll_events() {
+        match event {
+            InputEvent::Key { key, down } => {
+                let key_name: &'static str = key.into();
+                LuaVMManager::instance().invoke_fn_with_args("on_key", (key_name, down));
+            }
+            InputEvent::KeyRepeat { key } => {
+                let key_name: &'static str = key.into();
+                LuaVMManager::instance().invoke_fn_with_args("on_key_repeat", key_name);
+            }
+            InputEvent::Button { button, down } => {
+                let button_name: &'static str = button.into();
+                LuaVMManager::instance().invoke_fn_with_args("on_button", (button_name, down));
+            }
+        }
+    }
+}
+
+fn dispatch_hotkeys() {
+    let triggered = crate::input::hotkey::HotkeyManager::instance()
+        .poll_triggered(crate::input::Input::instance().keyboard());
+    if !triggered.is_empty() {
+        LuaVMManager::instance().dispatch_hotkeys(&triggered);
+    }
+}
+
 /// 隐藏前台控制台窗口
 fn hide_console_window() -> Result<(), String> {
     let is_foreground = crate::utility::is_game_foreground().map_err(|e| e.to_string())?;
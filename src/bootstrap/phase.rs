@@ -0,0 +1,84 @@
+//! 初始化阶段
+//!
+//! 初始化按以下顺序进行，进入每个阶段时通知扩展注册的回调：
+//!
+//! 1. `Extensions`：加载扩展并调用 `ExtInitialize`。核心函数在此之前已全部注册。
+//!    扩展在初始化线程中加载时，此阶段中单例尚未解析。
+//! 2. `Addresses`：扩展已全部加载，兼容桥接提供的地址和 Hook 可用。
+//!    补丁方案和聊天命令在此阶段解析地址。
+//! 3. `Scripts`：加载脚本。
+//! 4. `Ready`：初始化完成，脚本收到 `init_phase` 事件。
+
+use std::sync::{
+    LazyLock,
+    atomic::{AtomicU32, Ordering},
+};
+
+use luaf_include::{InitPhase, InitPhaseCallback};
+use parking_lot::Mutex;
+
+use crate::extension::CoreAPI;
+
+const NOT_STARTED: u32 = u32::MAX;
+
+pub struct InitPhases {
+    current: AtomicU32,
+    /// 扩展注册的回调：(回调, user_data)
+    callbacks: Mutex<Vec<(InitPhaseCallback, usize)>>,
+}
+
+impl InitPhases {
+    pub fn instance() -> &'static InitPhases {
+        static INSTANCE: LazyLock<InitPhases> = LazyLock::new(|| InitPhases {
+            current: AtomicU32::new(NOT_STARTED),
+            callbacks: Mutex::new(Vec::new()),
+        });
+        &INSTANCE
+    }
+
+    pub fn register_core_functions() {
+        let core_api = CoreAPI::instance();
+        core_api.register_function("Core::on_init_phase", on_init_phase as _);
+        core_api.register_function("Core::get_init_phase", get_init_phase as _);
+    }
+
+    /// 当前阶段，初始化开始前返回 None
+    pub fn current(&self) -> Option<InitPhase> {
+        InitPhase::from_repr(self.current.load(Ordering::Acquire))
+    }
+
+    /// 进入阶段并通知扩展
+    pub fn enter(&self, phase: InitPhase) {
+        self.current.store(phase as u32, Ordering::Release);
+        log::debug!("Entering init phase: {}", phase_name(phase));
+
+        // 复制一份，允许回调中注册新回调
+        let callbacks = self.callbacks.lock().clone();
+        for (callback, user_data) in callbacks {
+            unsafe { callback(phase as u32, user_data as *mut _) };
+        }
+    }
+}
+
+pub fn phase_name(phase: InitPhase) -> &'static str {
+    match phase {
+        InitPhase::Extensions => "extensions",
+        InitPhase::Addresses => "addresses",
+        InitPhase::Scripts => "scripts",
+        InitPhase::Ready => "ready",
+    }
+}
+
+/// 注册阶段回调，初始化已开始时立即以当前阶段调用一次
+extern "C" fn on_init_phase(callback: InitPhaseCallback, user_data: *mut std::ffi::c_void) {
+    let phases = InitPhases::instance();
+    phases.callbacks.lock().push((callback, user_data as usize));
+
+    if let Some(phase) = phases.current() {
+        unsafe { callback(phase as u32, user_data) };
+    }
+}
+
+extern "C" fn get_init_phase() -> u32 {
+    InitPhases::instance().current.load(Ordering::Acquire)
+}
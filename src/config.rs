@@ -1,8 +1,11 @@
-use std::{path::Path, sync::LazyLock};
+use std::{collections::BTreeMap, path::Path, sync::LazyLock};
 
 use parking_lot::{Mutex, MutexGuard};
 use serde::{Deserialize, Serialize};
 
+use crate::luavm::capability::UnsafeCapability;
+use crate::render_core::layer::DrawLayer;
+
 const CONFIG_FILE_PATH: &str = "lua_framework/config.toml";
 
 static GLOBAL_CONFIG: LazyLock<Mutex<Config>> = LazyLock::new(|| Mutex::new(Config::default()));
@@ -30,6 +33,21 @@ pub struct LogConfig {
     pub log_to_file: bool,
     #[serde(default = "default_log_file_path")]
     pub log_file_path: String,
+    /// 控制台窗口标题
+    #[serde(default = "default_console_title")]
+    pub console_title: String,
+    /// 控制台窗口位置
+    #[serde(default)]
+    pub console_position: Option<[i32; 2]>,
+    /// 禁用的日志频道
+    #[serde(default)]
+    pub disabled_channels: Vec<String>,
+    /// 不写入日志文件的日志频道
+    #[serde(default)]
+    pub file_excluded_channels: Vec<String>,
+    /// 日志中框架错误信息的语言
+    #[serde(default)]
+    pub language: crate::error::Language,
 }
 
 impl Default for LogConfig {
@@ -39,6 +57,11 @@ impl Default for LogConfig {
             log_to_console: true,
             log_to_file: true,
             log_file_path: default_log_file_path(),
+            console_title: default_console_title(),
+            console_position: None,
+            disabled_channels: Vec::new(),
+            file_excluded_channels: Vec::new(),
+            language: Default::default(),
         }
     }
 }
@@ -59,12 +82,30 @@ fn default_log_file_path() -> String {
     "lua_framework/lua_framework.log".to_string()
 }
 
+fn default_console_title() -> String {
+    "LuaFramework Console".to_string()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UIConfig {
     #[serde(default)]
     pub font_size: f32,
     #[serde(default = "default_menu_key")]
     pub menu_key: luaf_include::KeyCode,
+    #[serde(default)]
+    pub layout: WindowLayout,
+    /// 显示性能统计浮窗
+    #[serde(default)]
+    pub show_stats_overlay: bool,
+    /// 过场动画和加载时自动隐藏界面
+    #[serde(default = "default_true")]
+    pub hide_in_cutscene: bool,
+    /// 启动时显示加载状态浮窗
+    #[serde(default = "default_true")]
+    pub show_startup_status: bool,
+    /// 禁用的绘制层
+    #[serde(default)]
+    pub disabled_draw_layers: Vec<DrawLayer>,
 }
 
 impl Default for UIConfig {
@@ -72,14 +113,208 @@ impl Default for UIConfig {
         Self {
             font_size: 0.0,
             menu_key: default_menu_key(),
+            layout: WindowLayout::default(),
+            show_stats_overlay: false,
+            hide_in_cutscene: true,
+            show_startup_status: true,
+            disabled_draw_layers: Vec::new(),
         }
     }
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// 框架主窗口布局
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WindowLayout {
+    #[serde(default)]
+    pub position: Option<[f32; 2]>,
+    #[serde(default)]
+    pub size: Option<[f32; 2]>,
+    #[serde(default)]
+    pub collapsed: bool,
+    /// 展开的折叠标题
+    #[serde(default)]
+    pub open_headers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScriptsConfig {
     #[serde(default)]
     pub disabled_scripts: Vec<String>,
+    /// 处于联机会话时禁用不安全模式，并阻止脚本修改内存、打补丁和调用原生函数
+    #[serde(default = "default_true")]
+    pub disable_unsafe_online: bool,
+    /// 重载全部脚本的快捷键
+    #[serde(default)]
+    pub reload_key: Option<luaf_include::KeyCode>,
+    /// 额外的脚本目录，与默认目录合并加载
+    #[serde(default)]
+    pub extra_dirs: Vec<String>,
+    /// 脚本名 -> 被用户禁用的不安全权限
+    #[serde(default)]
+    pub denied_capabilities: BTreeMap<String, Vec<UnsafeCapability>>,
+    /// 单次回调耗时预算（毫秒），超出时发布 script_over_budget 事件。
+    /// 未设置时使用默认值，设为 0 时不检查
+    #[serde(default)]
+    pub callback_budget_ms: Option<f32>,
+    /// 从 Lua 传入字节数组的最大长度，设为 0 时不限制
+    #[serde(default = "default_max_bytes_len")]
+    pub max_bytes_len: usize,
+    /// 脚本文件变化时自动重载对应的脚本
+    #[serde(default)]
+    pub hot_reload: bool,
+}
+
+impl Default for ScriptsConfig {
+    fn default() -> Self {
+        Self {
+            disabled_scripts: Vec::new(),
+            disable_unsafe_online: true,
+            reload_key: None,
+            extra_dirs: Vec::new(),
+            denied_capabilities: BTreeMap::new(),
+            callback_budget_ms: None,
+            max_bytes_len: default_max_bytes_len(),
+            hot_reload: false,
+        }
+    }
+}
+
+fn default_max_bytes_len() -> usize {
+    1024 * 1024
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SafetyConfig {
+    /// 允许在联机会话中调用受限接口的脚本
+    #[serde(default)]
+    pub allowed_scripts: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputConfig {
+    /// 手柄逻辑按键名称到物理按键的映射
+    #[serde(default = "default_controller_map")]
+    pub controller_map: BTreeMap<String, Vec<luaf_include::ControllerButton>>,
+    /// 允许脚本向游戏键盘写入按键状态
+    #[serde(default)]
+    pub allow_injection: bool,
+    /// 摇杆死区，范围 0~1
+    #[serde(default = "default_stick_dead_zone")]
+    pub stick_dead_zone: f32,
+    /// 扳机死区，范围 0~1
+    #[serde(default = "default_trigger_dead_zone")]
+    pub trigger_dead_zone: f32,
+    /// 按住按键后开始重复触发的延迟（毫秒）
+    #[serde(default = "default_repeat_delay")]
+    pub repeat_delay_ms: u32,
+    /// 重复触发间隔（毫秒），设为 0 时不重复触发
+    #[serde(default = "default_repeat_interval")]
+    pub repeat_interval_ms: u32,
+    /// 用户修改的快捷键：脚本名 -> 绑定名称 -> 组合键
+    #[serde(default)]
+    pub hotkeys: BTreeMap<String, BTreeMap<String, Vec<luaf_include::KeyCode>>>,
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        Self {
+            controller_map: default_controller_map(),
+            allow_injection: false,
+            stick_dead_zone: default_stick_dead_zone(),
+            trigger_dead_zone: default_trigger_dead_zone(),
+            repeat_delay_ms: default_repeat_delay(),
+            repeat_interval_ms: default_repeat_interval(),
+            hotkeys: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_stick_dead_zone() -> f32 {
+    0.24
+}
+
+fn default_trigger_dead_zone() -> f32 {
+    0.12
+}
+
+fn default_repeat_delay() -> u32 {
+    400
+}
+
+fn default_repeat_interval() -> u32 {
+    50
+}
+
+fn default_controller_map() -> BTreeMap<String, Vec<luaf_include::ControllerButton>> {
+    use luaf_include::ControllerButton;
+
+    BTreeMap::from([
+        ("confirm".to_string(), vec![ControllerButton::Cross]),
+        ("cancel".to_string(), vec![ControllerButton::Circle]),
+        ("menu".to_string(), vec![ControllerButton::Options]),
+        ("modifier".to_string(), vec![ControllerButton::R1]),
+    ])
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExtensionsConfig {
+    /// 在独立的初始化线程中加载扩展，与游戏初始化并行
+    ///
+    /// 扩展的 `ExtInitialize` 将不在游戏主线程调用，此时单例尚未解析。
+    #[serde(default)]
+    pub load_on_init_thread: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutosaveConfig {
+    /// 自动保存间隔（分钟），设为 0 时不定期保存
+    #[serde(default = "default_autosave_interval")]
+    pub interval_minutes: u32,
+    /// 场景切换（如任务结束返回据点）时保存
+    #[serde(default = "default_true")]
+    pub on_scene_change: bool,
+}
+
+impl Default for AutosaveConfig {
+    fn default() -> Self {
+        Self {
+            interval_minutes: default_autosave_interval(),
+            on_scene_change: true,
+        }
+    }
+}
+
+fn default_autosave_interval() -> u32 {
+    5
+}
+
+/// 补丁方案，一组可以整体启用和还原的内存补丁
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatchProfile {
+    pub name: String,
+    /// 启动时自动应用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 适用的游戏版本，为空时不限制
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub revisions: Vec<u32>,
+    #[serde(default)]
+    pub patches: Vec<PatchEntry>,
+}
+
+/// 补丁方案中的单个补丁，地址由 `record` 或 `pattern` 给出
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatchEntry {
+    /// AddressRepository 记录名
+    #[serde(default)]
+    pub record: Option<String>,
+    /// 特征码
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub offset: isize,
+    /// 十六进制字节，如 "90 90 EB"
+    pub bytes: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -91,6 +326,16 @@ pub struct Config {
     pub ui: UIConfig,
     #[serde(default)]
     pub scripts: ScriptsConfig,
+    #[serde(default)]
+    pub safety: SafetyConfig,
+    #[serde(default)]
+    pub input: InputConfig,
+    #[serde(default)]
+    pub extensions: ExtensionsConfig,
+    #[serde(default)]
+    pub autosave: AutosaveConfig,
+    #[serde(default)]
+    pub patch_profiles: Vec<PatchProfile>,
 }
 
 impl Default for Config {
@@ -100,6 +345,11 @@ impl Default for Config {
             log: LogConfig::default(),
             ui: UIConfig::default(),
             scripts: ScriptsConfig::default(),
+            safety: SafetyConfig::default(),
+            input: InputConfig::default(),
+            extensions: ExtensionsConfig::default(),
+            autosave: AutosaveConfig::default(),
+            patch_profiles: Vec::new(),
         }
     }
 }
@@ -0,0 +1,131 @@
+//! 嵌入接口
+//!
+//! 启用 `embed` 特性后，可作为 Rust 库在自己的注入器或工具中使用虚拟机与扩展子系统，
+//! 不经过 DllMain 和游戏初始化流程。
+//!
+//! ```ignore
+//! use lua_framework::embed;
+//!
+//! embed::initialize(embed::EmbedOptions::default())?;
+//! embed::register_module(|lua, globals| {
+//!     globals.set("answer", lua.create_function(|_, ()| Ok(42))?)
+//! });
+//! let vm = embed::run_script("scripts/main.lua")?;
+//! vm.load_script("print(answer())")?;
+//!
+//! // 交互式执行，临时虚拟机释放后放回池中复用
+//! let scratch = embed::run_scratch("x = 1")?;
+//! scratch.load_script("print(x)")?;
+//! ```
+//!
+//! 游戏相关的接口（如 Input、单例、怪物）依赖游戏进程中的数据，在嵌入环境中不可用。
+
+use std::path::Path;
+
+use mlua::prelude::*;
+use parking_lot::Mutex;
+
+pub use crate::error::{Error, Result};
+pub use crate::extension::CoreAPI;
+pub use crate::luavm::{LuaVM, LuaVMManager, ScratchVM, SharedLuaVM};
+pub use mlua;
+
+/// 自定义模块注册函数，参数为虚拟机和全局表
+pub type ModuleRegisterFn = fn(&Lua, &LuaTable) -> LuaResult<()>;
+
+static CUSTOM_MODULES: Mutex<Vec<ModuleRegisterFn>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Clone)]
+pub struct EmbedOptions {
+    /// 从配置文件加载配置，否则使用默认配置
+    pub load_config: bool,
+    /// 安装框架的日志记录器，宿主已有记录器时应关闭
+    pub init_logger: bool,
+    /// 加载 `lua_framework/extensions` 中的扩展
+    pub load_extensions: bool,
+}
+
+impl Default for EmbedOptions {
+    fn default() -> Self {
+        Self {
+            load_config: true,
+            init_logger: true,
+            load_extensions: false,
+        }
+    }
+}
+
+/// 初始化框架，代替 DllMain 中的初始化流程，只需调用一次
+pub fn initialize(options: EmbedOptions) -> Result<()> {
+    if options.load_config {
+        crate::config::Config::initialize()?;
+    }
+    if options.init_logger {
+        crate::logger::init_logger();
+    }
+
+    let core_api = CoreAPI::instance();
+    core_api.register_core_functions();
+    if options.load_extensions {
+        let (total, success) = core_api.load_core_exts()?;
+        log::info!(
+            "Loaded {} extensions successfully, {} failed.",
+            success,
+            total - success
+        );
+    }
+
+    Ok(())
+}
+
+/// 在游戏进程中执行完整的初始化流程，与 DllMain 中的流程相同
+///
+/// 用于自行注入后手动启动框架，不应与 [initialize] 同时使用。
+pub fn initialize_in_game() -> anyhow::Result<()> {
+    crate::main_entry()
+}
+
+/// 注册自定义模块，之后创建的虚拟机都会调用注册函数
+pub fn register_module(register: ModuleRegisterFn) {
+    CUSTOM_MODULES.lock().push(register);
+}
+
+/// 加载脚本文件，虚拟机由 [LuaVMManager] 管理
+pub fn run_script<P: AsRef<Path>>(path: P) -> Result<SharedLuaVM> {
+    LuaVMManager::instance().create_vm_with_file(path)
+}
+
+/// 在新的虚拟虚拟机中执行代码，虚拟机由 [LuaVMManager] 管理
+///
+/// name 不含 `virtual:` 前缀，移除时使用 [LuaVMManager::remove_virtual_vm]。
+pub fn run_string(name: &str, code: &str) -> Result<SharedLuaVM> {
+    let luavm = LuaVMManager::instance().create_virtual_vm(name);
+    luavm.load_script(code)?;
+    Ok(luavm)
+}
+
+/// 在池中的临时虚拟机中执行代码，用于 REPL 等交互式执行
+///
+/// 返回的 [ScratchVM] 释放时清空状态并放回池中，执行出错时虚拟机同样被回收。
+pub fn run_scratch(code: &str) -> Result<ScratchVM> {
+    let luavm = LuaVMManager::instance().acquire_scratch_vm()?;
+    luavm.load_script(code)?;
+    Ok(luavm)
+}
+
+/// 调用所有虚拟机的 on_update 回调并执行到期的定时任务，宿主应每帧调用
+pub fn update() {
+    let manager = LuaVMManager::instance();
+    manager.process_pending_reload();
+    manager.tick_timers();
+    manager.invoke_fn("on_update");
+}
+
+/// 向虚拟机注册自定义模块
+pub(crate) fn register_custom_modules(lua: &Lua, globals: &LuaTable) -> LuaResult<()> {
+    let modules = CUSTOM_MODULES.lock().clone();
+    for register in modules {
+        register(lua, globals)?;
+    }
+    Ok(())
+}
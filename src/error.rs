@@ -1,5 +1,9 @@
 use parking_lot::Mutex;
 
+mod catalog;
+
+pub use catalog::{Language, set_log_language};
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug, thiserror::Error)]
@@ -26,6 +30,8 @@ pub enum Error {
     Frida(String),
     #[error("Lua VM not found")]
     LuaVMNotFound,
+    #[error("LuaVM '{0}' not found")]
+    LuaVMNameNotFound(String),
     #[error("Invalid argument: expected {0}, got {1}")]
     InvalidValue(&'static str, String),
     #[error(
@@ -48,6 +54,52 @@ pub enum Error {
     ProcAddressNotFound(String),
     #[error("Game window not found")]
     GameWindowNotFound,
+    #[error("Native call is unavailable, luaf_libffi extension is not loaded")]
+    FFIUnavailable,
+    #[error("Class definition '{0}' not found")]
+    ClassDefNotFound(String),
+    #[error("Class '{0}' has no member '{1}'")]
+    ClassMemberNotFound(String, String),
+    #[error("'{0}' requires unsafe capability '{1}', call it inside core.with_unsafe(\"{1}\", fn)")]
+    UnsafeModeRequired(&'static str, &'static str),
+    #[error("Unsafe capability '{0}' is denied for script '{1}'")]
+    CapabilityDenied(&'static str, String),
+    #[error("'{0}' is called too frequently, try again later")]
+    RateLimited(&'static str),
+    #[error("'{0}' is blocked by online session safety policy")]
+    BlockedByOnlinePolicy(&'static str),
+    #[error("Script '{0}' has no asset directory")]
+    AssetDirUnavailable(String),
+    #[error("Script '{0}' is not loaded from a file")]
+    ScriptDirUnavailable(String),
+    #[error("Thread {0} is not the game thread")]
+    NotGameThread(u32),
+    #[error("DTI class '{0}' not found")]
+    DtiNotFound(String),
+    #[error("Cannot resolve vtable of class '{0}'")]
+    VtableUnavailable(String),
+    #[error("Patch profile '{0}' not found")]
+    PatchProfileNotFound(String),
+    #[error("'{0}' requires game revision {1}, but current revision is {2}")]
+    GameRevisionMismatch(String, String, String),
+    #[error("Input injection is disabled, enable it in the framework settings")]
+    InputInjectionDisabled,
+    #[error(
+        "Byte array of length {0} exceeds the limit of {1}, pass a binary string instead or raise scripts.max_bytes_len"
+    )]
+    BytesTooLarge(usize, usize),
+    #[error("Index {0} is out of bounds, length is {1}")]
+    IndexOutOfBounds(usize, usize),
+    #[error("Struct '{0}' not found")]
+    StructNotFound(String),
+    #[error("Failed to decode image '{0}': {1}")]
+    ImageDecode(String, String),
+    #[error("Texture loading is unavailable, the render backend does not support it")]
+    TextureUnavailable,
+    #[error("Address '{0}' is unresolved, it will be retried later")]
+    AddressUnresolved(String),
+    #[error("Table nesting exceeds {0} levels")]
+    TableTooDeep(usize),
 }
 
 #[derive(Debug, Clone)]
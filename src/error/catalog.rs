@@ -0,0 +1,227 @@
+//! 错误信息目录
+//!
+//! 框架错误在写入日志时按日志语言输出，与界面语言无关，
+//! 并附带固定的错误码，便于在不同语言的日志中搜索同一错误。
+//! 错误码一经发布不再修改，新增错误使用新的错误码。
+
+use std::{
+    fmt,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::Error;
+
+/// 日志语言
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    strum::EnumIter,
+    strum::IntoStaticStr,
+)]
+pub enum Language {
+    #[default]
+    #[serde(rename = "en")]
+    #[strum(serialize = "English")]
+    En,
+    #[serde(rename = "zh-CN")]
+    #[strum(serialize = "简体中文")]
+    ZhCn,
+}
+
+impl Language {
+    pub fn display_name(self) -> &'static str {
+        self.into()
+    }
+}
+
+/// 当前日志语言，缓存配置值，避免记录日志时锁定配置
+static LOG_LANGUAGE: AtomicU8 = AtomicU8::new(Language::En as u8);
+
+pub fn log_language() -> Language {
+    match LOG_LANGUAGE.load(Ordering::Relaxed) {
+        x if x == Language::ZhCn as u8 => Language::ZhCn,
+        _ => Language::En,
+    }
+}
+
+pub fn set_log_language(language: Language) {
+    LOG_LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+
+impl Error {
+    /// 错误码
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::Io(_) => "LF-E0001",
+            Error::IoWithContext(..) => "LF-E0002",
+            Error::Lua(_) => "LF-E0003",
+            Error::Windows(_) => "LF-E0004",
+            Error::InlineHook(_) => "LF-E0005",
+            Error::MidHook(_) => "LF-E0006",
+            Error::Config(_) => "LF-E0007",
+            Error::Memory(_) => "LF-E0008",
+            Error::Frida(_) => "LF-E0009",
+
+            Error::LuaVMNotFound => "LF-E0100",
+            Error::LuaVMNameNotFound(_) => "LF-E0101",
+            Error::InvalidValue(..) => "LF-E0102",
+            Error::LuaFVersionMismatch(..) => "LF-E0103",
+            Error::InitCoreExtension(_) => "LF-E0104",
+            Error::ParseInt(_) => "LF-E0105",
+            Error::FFIUnavailable => "LF-E0106",
+            Error::BytesTooLarge(..) => "LF-E0107",
+            Error::IndexOutOfBounds(..) => "LF-E0108",
+            Error::ImageDecode(..) => "LF-E0109",
+            Error::TableTooDeep(_) => "LF-E0110",
+
+            Error::AddressRecordNotFound(_) => "LF-E0200",
+            Error::SingletonNotFound(_) => "LF-E0201",
+            Error::PatchAlreadyExists(_) => "LF-E0202",
+            Error::ProcAddressNotFound(_) => "LF-E0203",
+            Error::GameWindowNotFound => "LF-E0204",
+            Error::ClassDefNotFound(_) => "LF-E0205",
+            Error::ClassMemberNotFound(..) => "LF-E0206",
+            Error::DtiNotFound(_) => "LF-E0207",
+            Error::VtableUnavailable(_) => "LF-E0208",
+            Error::PatchProfileNotFound(_) => "LF-E0209",
+            Error::GameRevisionMismatch(..) => "LF-E0210",
+            Error::StructNotFound(_) => "LF-E0211",
+            Error::TextureUnavailable => "LF-E0212",
+            Error::AddressUnresolved(_) => "LF-E0213",
+
+            Error::PathNotAllowed(_) => "LF-E0300",
+            Error::UnsafeModeRequired(..) => "LF-E0301",
+            Error::CapabilityDenied(..) => "LF-E0302",
+            Error::RateLimited(_) => "LF-E0303",
+            Error::BlockedByOnlinePolicy(_) => "LF-E0304",
+            Error::AssetDirUnavailable(_) => "LF-E0305",
+            Error::ScriptDirUnavailable(_) => "LF-E0306",
+            Error::NotGameThread(_) => "LF-E0307",
+            Error::InputInjectionDisabled => "LF-E0308",
+        }
+    }
+
+    /// 指定语言的错误信息，不含错误码
+    pub fn message(&self, language: Language) -> String {
+        match language {
+            Language::En => self.to_string(),
+            Language::ZhCn => self.message_zh_cn(),
+        }
+    }
+
+    /// 按日志语言输出的错误信息，带错误码
+    pub fn log(&self) -> LogMessage<'_> {
+        LogMessage(self)
+    }
+
+    fn message_zh_cn(&self) -> String {
+        match self {
+            Error::Io(e) => format!("IO 错误：{}", e),
+            Error::IoWithContext(e, cause) => format!("IO 错误：{}。原因：{}", e, cause),
+            Error::Lua(e) => format!("Lua 错误：{}", e),
+            Error::Windows(e) => format!("Windows 错误：{}", e),
+            Error::InlineHook(e) => format!("Inline Hook 错误：{}", e),
+            Error::MidHook(e) => format!("Mid Hook 错误：{}", e),
+            Error::Config(e) => format!("配置错误：{}", e),
+            Error::Memory(e) => format!("内存模块错误：{}", e),
+            Error::Frida(e) => format!("Frida 错误：{}", e),
+
+            Error::LuaVMNotFound => "未找到 Lua 虚拟机".to_string(),
+            Error::LuaVMNameNotFound(name) => format!("未找到 Lua 虚拟机 '{}'", name),
+            Error::InvalidValue(expected, got) => {
+                format!("无效参数：期望 {}，实际为 {}", expected, got)
+            }
+            Error::LuaFVersionMismatch(current, required) => format!(
+                "脚本需要 LuaFramework 版本 {}，当前版本为 {}，请更新 LuaFramework 或脚本。",
+                required, current
+            ),
+            Error::InitCoreExtension(code) => format!("初始化核心扩展失败：错误码 {}", code),
+            Error::ParseInt(s) => format!("无法从 '{}' 解析整数", s),
+            Error::FFIUnavailable => "原生调用不可用，未加载 luaf_libffi 扩展".to_string(),
+
+            Error::AddressRecordNotFound(name) => format!("获取地址记录 '{}' 失败", name),
+            Error::SingletonNotFound(name) => format!("获取单例 '{}' 失败", name),
+            Error::PatchAlreadyExists(addr) => format!("地址 0x{:x} 处已存在内存补丁", addr),
+            Error::ProcAddressNotFound(name) => format!("未找到导出函数地址 '{}'", name),
+            Error::GameWindowNotFound => "未找到游戏窗口".to_string(),
+            Error::ClassDefNotFound(name) => format!("未找到类定义 '{}'", name),
+            Error::ClassMemberNotFound(class, member) => {
+                format!("类 '{}' 没有成员 '{}'", class, member)
+            }
+            Error::DtiNotFound(name) => format!("未找到 DTI 类 '{}'", name),
+            Error::VtableUnavailable(name) => format!("无法解析类 '{}' 的虚函数表", name),
+            Error::PatchProfileNotFound(name) => format!("未找到补丁方案 '{}'", name),
+            Error::AddressUnresolved(name) => format!("地址 '{}' 尚未解析，稍后将重试", name),
+            Error::GameRevisionMismatch(target, required, current) => format!(
+                "'{}' 需要游戏版本 {}，当前版本为 {}",
+                target, required, current
+            ),
+
+            Error::PathNotAllowed(path) => format!("不允许访问路径：{}", path),
+            Error::UnsafeModeRequired(name, capability) => format!(
+                "'{}' 需要不安全权限 '{}'，请在 core.with_unsafe(\"{}\", fn) 中调用",
+                name, capability, capability
+            ),
+            Error::CapabilityDenied(capability, script) => {
+                format!("脚本 '{}' 的不安全权限 '{}' 已被拒绝", script, capability)
+            }
+            Error::RateLimited(name) => format!("'{}' 调用过于频繁，请稍后重试", name),
+            Error::BlockedByOnlinePolicy(name) => format!("'{}' 已被联机安全策略阻止", name),
+            Error::AssetDirUnavailable(name) => format!("脚本 '{}' 没有资源目录", name),
+            Error::ScriptDirUnavailable(name) => format!("脚本 '{}' 不是从文件加载的", name),
+            Error::NotGameThread(id) => format!("线程 {} 不是游戏主线程", id),
+            Error::InputInjectionDisabled => "输入注入已禁用，请在框架设置中启用".to_string(),
+            Error::BytesTooLarge(len, max) => format!(
+                "字节数组长度 {} 超出限制 {}，请改为传入二进制字符串或调大 scripts.max_bytes_len",
+                len, max
+            ),
+            Error::IndexOutOfBounds(index, len) => {
+                format!("索引 {} 越界，长度为 {}", index, len)
+            }
+            Error::StructNotFound(name) => format!("未找到结构体 '{}'", name),
+            Error::ImageDecode(path, e) => format!("解码图片 '{}' 失败：{}", path, e),
+            Error::TextureUnavailable => "纹理加载不可用，渲染后端不支持上传纹理".to_string(),
+            Error::TableTooDeep(max) => format!("table 嵌套超过 {} 层", max),
+        }
+    }
+}
+
+/// 日志中的错误信息
+pub struct LogMessage<'a>(&'a Error);
+
+impl fmt::Display for LogMessage<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.0.code(), self.0.message(log_language()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_catalog() {
+        let err = Error::PatchProfileNotFound("unlock_fps".to_string());
+        assert_eq!(err.code(), "LF-E0209");
+        assert_eq!(
+            err.message(Language::En),
+            "Patch profile 'unlock_fps' not found"
+        );
+        assert_eq!(err.message(Language::ZhCn), "未找到补丁方案 'unlock_fps'");
+
+        set_log_language(Language::ZhCn);
+        assert_eq!(
+            err.log().to_string(),
+            "[LF-E0209] 未找到补丁方案 'unlock_fps'"
+        );
+        set_log_language(Language::En);
+    }
+}
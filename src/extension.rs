@@ -1,8 +1,13 @@
-use std::{collections::HashMap, ffi::c_void, path::Path, sync::LazyLock};
+use std::{
+    collections::HashMap,
+    ffi::c_void,
+    path::Path,
+    sync::{Arc, LazyLock},
+};
 
 use luaf_include::{
     ControllerButton, CoreAPIFunctions, CoreAPIInput, CoreAPILua, CoreAPIParam, KeyCode, LogLevel,
-    OnLuaStateCreatedCb, OnLuaStateDestroyedCb,
+    ManagedPattern, MouseButton, OnLuaStateCreatedCb, OnLuaStateDestroyedCb,
 };
 use parking_lot::Mutex;
 use windows::{
@@ -14,13 +19,16 @@ use windows::{
 };
 
 use crate::{
-    address::{AddressRecord, AddressRepository},
+    address::{AddressRecord, AddressRepository, AddressValidator, PatternCandidate},
     error::{Error, Result},
     game::singleton::SingletonManager,
     input::Input,
     luavm::LuaVMManager,
 };
 
+pub mod bridge;
+pub mod window;
+
 /// 核心扩展API，加载扩展，动态加载函数，事件分发等。
 #[derive(Debug, Default)]
 pub struct CoreAPI {
@@ -75,6 +83,20 @@ impl CoreAPI {
         }
     }
 
+    /// 取出扩展发布的 Lua 事件
+    pub fn take_lua_events(&self) -> Vec<(String, Option<String>)> {
+        std::mem::take(&mut self.inner.lock().pending_lua_events)
+    }
+
+    /// 注册 Lua 相关的扩展函数
+    pub fn register_core_functions(&self) {
+        self.register_function("Lua::dispatch_event", dispatch_lua_event as _);
+        self.register_function(
+            "Address::set_managed_address_ex",
+            set_managed_address_ex as _,
+        );
+    }
+
     /// 从扩展目录中扫描并加载扩展
     ///
     /// 返回：总数量，成功数量
@@ -169,6 +191,8 @@ pub struct CoreAPIInner {
     functions: HashMap<String, *const c_void>,
     on_lua_state_created: Vec<OnLuaStateCreatedCb>,
     on_lua_state_destroyed: Vec<OnLuaStateDestroyedCb>,
+    /// 扩展发布的 Lua 事件：(事件名, 参数)，下一帧分发
+    pending_lua_events: Vec<(String, Option<String>)>,
 }
 
 unsafe impl Send for CoreAPIInner {}
@@ -206,6 +230,11 @@ const CORE_API_KEY: CoreAPIInput = CoreAPIInput {
     is_key_down,
     is_controller_pressed,
     is_controller_down,
+    is_mouse_pressed,
+    is_mouse_down,
+    get_mouse_position,
+    get_mouse_wheel,
+    get_mouse_drag,
 };
 
 fn get_core_api_param() -> &'static CoreAPIParam {
@@ -252,9 +281,10 @@ extern "C" fn get_singleton(name: *const u8, len: u32) -> *mut c_void {
 extern "C" fn get_managed_address(name: *const u8, len: u32) -> *mut c_void {
     let name = from_ffi_str(name, len);
 
+    // 解析失败时返回空指针，下次获取时重试
     let result = AddressRepository::instance()
-        .get_ptr(name)
-        .unwrap_or(std::ptr::null_mut());
+        .get_address_deferred(name)
+        .map_or(std::ptr::null_mut(), |address| address as *mut c_void);
     log::debug!("Get managed address: {} -> {:p}", name, result);
     result
 }
@@ -279,9 +309,57 @@ extern "C" fn set_managed_address(
         name: name.to_string(),
         pattern: pattern.to_string(),
         offset: offset as isize,
+        ..Default::default()
     });
 }
 
+/// 设置带候选特征码的地址记录，第一个特征码为主特征码
+extern "C" fn set_managed_address_ex(
+    name: *const u8,
+    name_len: u32,
+    patterns: *const ManagedPattern,
+    pattern_count: u32,
+    validator: Option<luaf_include::AddressValidator>,
+    user_data: *mut c_void,
+) -> bool {
+    let name = from_ffi_str(name, name_len);
+    if patterns.is_null() || pattern_count == 0 {
+        log::error!("Set managed address '{}' without pattern", name);
+        return false;
+    }
+    let mut candidates = unsafe { std::slice::from_raw_parts(patterns, pattern_count as usize) }
+        .iter()
+        .map(|p| PatternCandidate {
+            pattern: from_ffi_str(p.pattern, p.pattern_len).to_string(),
+            offset: p.offset as isize,
+            skip_bytes: 0,
+        })
+        .collect::<Vec<_>>();
+    let primary = candidates.remove(0);
+    log::debug!(
+        "Set managed address: {} -> {} (offset: {}, fallbacks: {})",
+        name,
+        primary.pattern,
+        primary.offset,
+        candidates.len()
+    );
+
+    let user_data = user_data as usize;
+    AddressRepository::instance().set_record(AddressRecord {
+        name: name.to_string(),
+        pattern: primary.pattern,
+        offset: primary.offset,
+        fallbacks: candidates,
+        validator: validator.map(|validator| {
+            AddressValidator(Arc::new(move |address| unsafe {
+                validator(address as *mut c_void, user_data as *mut c_void)
+            }))
+        }),
+        ..Default::default()
+    });
+    true
+}
+
 extern "C" fn on_lua_state_created(callback: OnLuaStateCreatedCb) {
     CoreAPI::instance()
         .inner
@@ -298,6 +376,23 @@ extern "C" fn on_lua_state_destroyed(callback: OnLuaStateDestroyedCb) {
         .push(callback);
 }
 
+/// 发布 Lua 事件，可在任意线程调用，事件在下一帧由游戏线程分发给 `core.on_event` 注册的回调
+extern "C" fn dispatch_lua_event(
+    name: *const u8,
+    name_len: u32,
+    payload: *const u8,
+    payload_len: u32,
+) {
+    let name = from_ffi_str(name, name_len);
+    let payload = (!payload.is_null()).then(|| from_ffi_str(payload, payload_len).to_string());
+
+    CoreAPI::instance()
+        .inner
+        .lock()
+        .pending_lua_events
+        .push((name.to_string(), payload));
+}
+
 extern "C" fn with_lua_lock(fun: extern "C" fn(*mut c_void), user_data: *mut c_void) {
     let _ = LuaVMManager::instance().run_with_lock(|_| {
         fun(user_data);
@@ -348,3 +443,44 @@ extern "C" fn is_controller_down(button: u32) -> bool {
     };
     Input::instance().controller().is_down(button)
 }
+
+extern "C" fn is_mouse_pressed(button: u32) -> bool {
+    let Some(button) = MouseButton::from_repr(button) else {
+        return false;
+    };
+    Input::instance().mouse().is_pressed(button)
+}
+
+extern "C" fn is_mouse_down(button: u32) -> bool {
+    let Some(button) = MouseButton::from_repr(button) else {
+        return false;
+    };
+    Input::instance().mouse().is_down(button)
+}
+
+extern "C" fn get_mouse_position(x: *mut i32, y: *mut i32) {
+    let (px, py) = Input::instance().mouse().position();
+    unsafe {
+        *x = px;
+        *y = py;
+    }
+}
+
+extern "C" fn get_mouse_wheel() -> f32 {
+    Input::instance().mouse().wheel_delta()
+}
+
+/// 按键正在拖动时写入相对按下位置的偏移并返回 true
+extern "C" fn get_mouse_drag(button: u32, dx: *mut i32, dy: *mut i32) -> bool {
+    let Some(button) = MouseButton::from_repr(button) else {
+        return false;
+    };
+    let Some((x, y)) = Input::instance().mouse().drag_delta(button) else {
+        return false;
+    };
+    unsafe {
+        *dx = x;
+        *dy = y;
+    }
+    true
+}
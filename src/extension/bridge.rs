@@ -0,0 +1,93 @@
+//! 兼容桥接
+//!
+//! 其他框架（如 SharpPluginLoader）的适配扩展可以通过桥接接口提供已解析的地址和已持有的 Hook，
+//! LuaFramework 优先复用这些结果，避免扫描被其他加载器修改过的特征码。
+//!
+//! 桥接接口应在扩展的 `ExtInitialize` 中调用。
+
+use std::{collections::HashMap, ffi::c_void, sync::LazyLock};
+
+use luaf_include::{BridgeHookCallback, BridgeSubscribeFn};
+use parking_lot::Mutex;
+
+use super::{CoreAPI, from_ffi_str};
+use crate::address::AddressRepository;
+
+/// 扩展持有的 Hook
+#[derive(Debug, Clone, Copy)]
+pub struct BridgeHook {
+    pub target: usize,
+    subscribe: BridgeSubscribeFn,
+}
+
+impl BridgeHook {
+    /// 订阅 Hook，回调在原函数执行前调用
+    pub fn subscribe(&self, callback: BridgeHookCallback, user_data: *mut c_void) -> bool {
+        (self.subscribe)(callback, user_data)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CompatBridge {
+    hooks: Mutex<HashMap<String, BridgeHook>>,
+}
+
+impl CompatBridge {
+    pub fn instance() -> &'static CompatBridge {
+        static INSTANCE: LazyLock<CompatBridge> = LazyLock::new(CompatBridge::default);
+        &INSTANCE
+    }
+
+    pub fn register_core_functions() {
+        let core_api = CoreAPI::instance();
+        core_api.register_function("Bridge::provide_address", provide_address as _);
+        core_api.register_function("Bridge::register_hook", register_hook as _);
+    }
+
+    /// 获取扩展持有的 Hook
+    pub fn hook(&self, name: &str) -> Option<BridgeHook> {
+        self.hooks.lock().get(name).copied()
+    }
+
+    fn register_hook(&self, name: &str, hook: BridgeHook) {
+        AddressRepository::instance().provide_address(name, hook.target);
+        self.hooks.lock().insert(name.to_string(), hook);
+    }
+}
+
+/// 提供已解析的地址，覆盖同名地址记录
+extern "C" fn provide_address(name: *const u8, len: u32, address: *mut c_void) -> bool {
+    let name = from_ffi_str(name, len);
+    if address.is_null() {
+        log::warn!("Bridge: null address provided for '{}', ignored", name);
+        return false;
+    }
+
+    log::debug!("Bridge: address provided: {} -> {:p}", name, address);
+    AddressRepository::instance().provide_address(name, address as usize);
+    true
+}
+
+/// 注册扩展持有的 Hook，LuaFramework 将通过 `subscribe` 订阅而不是重复 Hook 目标函数
+extern "C" fn register_hook(
+    name: *const u8,
+    len: u32,
+    target: *mut c_void,
+    subscribe: BridgeSubscribeFn,
+) -> bool {
+    let name = from_ffi_str(name, len);
+    if target.is_null() {
+        log::warn!("Bridge: null target for hook '{}', ignored", name);
+        return false;
+    }
+
+    log::debug!("Bridge: hook registered: {} -> {:p}", name, target);
+    CompatBridge::instance().register_hook(
+        name,
+        BridgeHook {
+            target: target as usize,
+            subscribe,
+        },
+    );
+    true
+}
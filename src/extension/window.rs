@@ -0,0 +1,148 @@
+//! 游戏窗口消息过滤
+//!
+//! 扩展（如自定义输入或渲染后端）通过框架订阅游戏窗口消息，而不是各自替换窗口过程，
+//! 避免多个子类化 Hook 之间相互覆盖。
+//!
+//! 第一个过滤器注册时替换窗口过程，之后不再还原，其他工具可能已在此基础上继续子类化。
+
+use std::{
+    ffi::c_void,
+    sync::{
+        LazyLock,
+        atomic::{AtomicIsize, AtomicU32, Ordering},
+    },
+};
+
+use luaf_include::WndProcFilter;
+use parking_lot::Mutex;
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+    UI::WindowsAndMessaging::{CallWindowProcW, GWLP_WNDPROC, SetWindowLongPtrW, WNDPROC},
+};
+
+use super::CoreAPI;
+use crate::error::{Error, Result};
+
+#[derive(Clone, Copy)]
+struct MessageFilter {
+    id: u32,
+    filter: WndProcFilter,
+    user_data: usize,
+}
+
+pub struct WindowHook {
+    filters: Mutex<Vec<MessageFilter>>,
+    next_id: AtomicU32,
+    /// 原窗口过程，0 表示尚未替换
+    original_proc: AtomicIsize,
+}
+
+impl WindowHook {
+    pub fn instance() -> &'static WindowHook {
+        static INSTANCE: LazyLock<WindowHook> = LazyLock::new(|| WindowHook {
+            filters: Mutex::new(Vec::new()),
+            next_id: AtomicU32::new(1),
+            original_proc: AtomicIsize::new(0),
+        });
+        &INSTANCE
+    }
+
+    pub fn register_core_functions() {
+        let core_api = CoreAPI::instance();
+        core_api.register_function("Window::get_handle", get_handle as _);
+        core_api.register_function("Window::add_message_filter", add_message_filter as _);
+        core_api.register_function("Window::remove_message_filter", remove_message_filter as _);
+    }
+
+    /// 添加消息过滤器，返回过滤器 ID
+    pub(crate) fn add_filter(&self, filter: WndProcFilter, user_data: usize) -> Result<u32> {
+        self.install()?;
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.filters.lock().push(MessageFilter {
+            id,
+            filter,
+            user_data,
+        });
+        Ok(id)
+    }
+
+    fn remove_filter(&self, id: u32) -> bool {
+        let mut filters = self.filters.lock();
+        let len = filters.len();
+        filters.retain(|f| f.id != id);
+        filters.len() != len
+    }
+
+    /// 替换游戏窗口过程
+    fn install(&self) -> Result<()> {
+        // 持有锁，防止并发注册时重复替换
+        let _filters = self.filters.lock();
+        if self.original_proc.load(Ordering::Acquire) != 0 {
+            return Ok(());
+        }
+
+        let hwnd = crate::utility::get_game_window_handle()?;
+        let original = unsafe { SetWindowLongPtrW(hwnd, GWLP_WNDPROC, hooked_wnd_proc as isize) };
+        if original == 0 {
+            return Err(Error::Windows(windows::core::Error::from_thread()));
+        }
+        self.original_proc.store(original, Ordering::Release);
+        log::debug!("Game window procedure replaced");
+
+        Ok(())
+    }
+}
+
+unsafe extern "system" fn hooked_wnd_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    let hook = WindowHook::instance();
+    // 复制一份，允许过滤器中注册或移除过滤器，以及消息重入
+    let filters = hook.filters.lock().clone();
+    for filter in filters {
+        let mut result = 0isize;
+        let consumed = unsafe {
+            (filter.filter)(
+                hwnd.0,
+                msg,
+                wparam.0,
+                lparam.0,
+                &mut result,
+                filter.user_data as *mut c_void,
+            )
+        };
+        if consumed {
+            return LRESULT(result);
+        }
+    }
+
+    let original: WNDPROC =
+        unsafe { std::mem::transmute(hook.original_proc.load(Ordering::Acquire)) };
+    unsafe { CallWindowProcW(original, hwnd, msg, wparam, lparam) }
+}
+
+/// 获取游戏窗口句柄，窗口不存在时返回空指针
+extern "C" fn get_handle() -> *mut c_void {
+    crate::utility::get_game_window_handle()
+        .map(|hwnd| hwnd.0)
+        .unwrap_or(std::ptr::null_mut())
+}
+
+/// 添加消息过滤器，失败时返回 0
+extern "C" fn add_message_filter(filter: WndProcFilter, user_data: *mut c_void) -> u32 {
+    match WindowHook::instance().add_filter(filter, user_data as usize) {
+        Ok(id) => id,
+        Err(e) => {
+            log::error!("Failed to add window message filter: {}", e.log());
+            0
+        }
+    }
+}
+
+extern "C" fn remove_message_filter(id: u32) -> bool {
+    WindowHook::instance().remove_filter(id)
+}
@@ -0,0 +1,227 @@
+//! 游戏聊天
+//!
+//! 发送和接收的消息在 Hook 中记录，在下一帧游戏主线程中分发。
+//! 游戏内部使用以 `\0` 结尾的 UTF-8 字符串，写入前截断到缓冲区大小，且不会截断多字节字符。
+//!
+//! 脚本发送的消息同样经过发送消息的 Hook，发送期间设置线程局部标记，
+//! Hook 只记录消息而不再作为命令处理，避免命令处理函数发送消息时重入。
+
+use std::{
+    cell::Cell,
+    ffi::{CString, c_void},
+    sync::LazyLock,
+};
+
+use parking_lot::Mutex;
+use safetyhook::InlineHook;
+
+use crate::{
+    address::AddressRepository,
+    error::{Error, Result},
+    game::singleton::SingletonManager,
+    memory::{MAX_C_STRING_LEN, MemoryUtils},
+    static_mut, static_ref,
+};
+
+const CHAT_SINGLETON: &str = "sChat";
+
+static mut RECEIVED_HOOK: Option<InlineHook> = None;
+static EVENTS: LazyLock<Mutex<Vec<ChatMessage>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+thread_local! {
+    /// 当前线程正在通过 [`send`] 发送消息
+    static SENDING: Cell<bool> = const { Cell::new(false) };
+}
+
+type SendMessageFn = extern "C" fn(*const i8) -> i8;
+type SystemMessageFn = unsafe extern "C" fn(*mut c_void, *const i8, f32, u32, u8);
+type MessageReceivedFn = unsafe extern "C" fn(*mut c_void, *const c_void);
+
+mod offsets {
+    /// sChat：输入框文本
+    pub const INPUT_BUFFER: isize = 0x1008;
+    /// 输入框缓冲区大小，包含结尾的 `\0`
+    pub const INPUT_BUFFER_SIZE: usize = 0x100;
+    /// 接收的消息：发送者名称
+    pub const MESSAGE_SENDER: isize = 0x30;
+    /// 接收的消息：消息文本指针
+    pub const MESSAGE_TEXT: isize = 0x70;
+}
+
+/// 系统消息的默认显示时间，负数表示使用游戏默认值
+const SYSTEM_MESSAGE_DURATION: f32 = -1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::AsRefStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum ChatMessageKind {
+    Sent,
+    Received,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChatMessage {
+    pub kind: ChatMessageKind,
+    pub text: String,
+    /// 发送者名称，自己发送的消息为 None
+    pub sender: Option<String>,
+}
+
+unsafe extern "C" fn message_received_hook(chat: *mut c_void, message: *const c_void) {
+    if !message.is_null() {
+        let message = message as usize;
+        let text = unsafe { *((message as isize + offsets::MESSAGE_TEXT) as *const usize) };
+        if let Some(text) = read_game_string(text) {
+            let sender = read_game_string((message as isize + offsets::MESSAGE_SENDER) as usize);
+            EVENTS.lock().push(ChatMessage {
+                kind: ChatMessageKind::Received,
+                text,
+                sender,
+            });
+        }
+    }
+
+    unsafe {
+        let original: MessageReceivedFn =
+            std::mem::transmute(static_ref!(RECEIVED_HOOK).as_ref().unwrap().original());
+        original(chat, message);
+    }
+}
+
+pub fn init_hooks() -> Result<()> {
+    let received =
+        AddressRepository::instance().get_ptr(AddressRepository::CHAT_MESSAGE_RECEIVED)?;
+    unsafe {
+        static_mut!(RECEIVED_HOOK).replace(safetyhook::create_inline(
+            received,
+            message_received_hook as _,
+        )?);
+    }
+
+    Ok(())
+}
+
+/// 记录自己发送的消息，由发送消息的 Hook 调用
+pub(super) fn record_sent(text: &str) {
+    EVENTS.lock().push(ChatMessage {
+        kind: ChatMessageKind::Sent,
+        text: text.to_string(),
+        sender: None,
+    });
+}
+
+/// 当前线程是否正在通过 [`send`] 发送消息，此时发送消息的 Hook 不处理命令
+pub(super) fn is_sending() -> bool {
+    SENDING.get()
+}
+
+/// 取出等待分发的消息
+pub fn take_events() -> Vec<ChatMessage> {
+    std::mem::take(&mut *EVENTS.lock())
+}
+
+/// 以自己的身份发送聊天消息，需在游戏主线程调用
+pub fn send(text: &str) -> Result<()> {
+    let chat = get_chat()?;
+    let text = to_game_string(text, offsets::INPUT_BUFFER_SIZE);
+    let send_message: SendMessageFn = unsafe {
+        std::mem::transmute(
+            AddressRepository::instance()
+                .get_ptr_deferred::<c_void>(AddressRepository::CHAT_MESSAGE_SENT)?,
+        )
+    };
+
+    let buffer = (chat as isize + offsets::INPUT_BUFFER) as usize;
+    let bytes = text.as_bytes_with_nul();
+    MemoryUtils::write(buffer, bytes, true)?;
+    SENDING.set(true);
+    send_message(chat as *const i8);
+    SENDING.set(false);
+
+    Ok(())
+}
+
+/// 在聊天记录中显示系统消息，只有自己可见
+pub fn system_message(text: &str) -> Result<()> {
+    let chat = get_chat()?;
+    let text = to_game_string(text, usize::MAX);
+    let system_message: SystemMessageFn = unsafe {
+        std::mem::transmute(
+            AddressRepository::instance()
+                .get_ptr_deferred::<c_void>(AddressRepository::CHAT_SYSTEM_MESSAGE)?,
+        )
+    };
+
+    unsafe {
+        system_message(
+            chat as *mut c_void,
+            text.as_ptr(),
+            SYSTEM_MESSAGE_DURATION,
+            u32::MAX,
+            0,
+        )
+    };
+
+    Ok(())
+}
+
+fn get_chat() -> Result<usize> {
+    let chat = SingletonManager::instance()
+        .get_address(CHAT_SINGLETON)
+        .ok_or_else(|| Error::SingletonNotFound(CHAT_SINGLETON.to_string()))?;
+    MemoryUtils::check_permission_read(chat)?;
+    Ok(chat)
+}
+
+/// 转换为游戏字符串：移除 `\0`，并在字符边界处截断到 `max_len`（包含结尾的 `\0`）
+fn to_game_string(text: &str, max_len: usize) -> CString {
+    let mut text = text.replace('\0', "");
+    if text.len() >= max_len {
+        let mut end = max_len.saturating_sub(1);
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    // 已移除所有 `\0`
+    CString::new(text).unwrap_or_default()
+}
+
+/// 读取游戏字符串，地址不可读或字符串为空时返回 None，最多读取 [`MAX_C_STRING_LEN`] 字节
+fn read_game_string(address: usize) -> Option<String> {
+    if address == 0 {
+        return None;
+    }
+    let bytes = MemoryUtils::read_c_string(address, MAX_C_STRING_LEN).ok()?;
+    (!bytes.is_empty()).then(|| String::from_utf8_lossy(&bytes).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_game_string_truncates_at_char_boundary() {
+        // "猎" 占 3 字节，缓冲区只能容纳 4 字节文本
+        let text = to_game_string("ab猎人", 5);
+        assert_eq!(text.as_bytes(), "ab".as_bytes());
+
+        let text = to_game_string("a\0b", 16);
+        assert_eq!(text.as_bytes(), b"ab");
+    }
+
+    #[test]
+    fn test_read_game_string() {
+        let text = b"hello\0world";
+        assert_eq!(
+            read_game_string(text.as_ptr() as usize).as_deref(),
+            Some("hello")
+        );
+        assert_eq!(read_game_string(b"\0".as_ptr() as usize), None);
+        assert_eq!(read_game_string(0), None);
+
+        // 没有结尾 `\0` 时截断到上限
+        let unterminated = vec![b'a'; MAX_C_STRING_LEN * 2];
+        let text = read_game_string(unterminated.as_ptr() as usize).unwrap();
+        assert_eq!(text.len(), MAX_C_STRING_LEN);
+    }
+}
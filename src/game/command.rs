@@ -1,17 +1,33 @@
-use std::ffi::CStr;
+//! 游戏内聊天命令
+//!
+//! 脚本注册的命令由 `CommandRegistry` 统一管理，处理函数保存在虚拟机的 `_chat_commands` 表中，
+//! 虚拟机销毁时移除该脚本的所有命令。命令消息默认不发送给其他玩家，
+//! 通过兼容桥接订阅 Hook 时无法拦截消息。
 
-use crate::{address::AddressRepository, error::Result, luavm::LuaVMManager, static_ref};
+use std::{
+    ffi::{CStr, c_void},
+    sync::LazyLock,
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    address::AddressRepository, error::Result, extension::bridge::CompatBridge,
+    luavm::LuaVMManager, static_ref,
+};
 
 static mut HOOK: Option<safetyhook::InlineHook> = None;
 
 type Func = extern "C" fn(*const i8) -> i8;
 
-unsafe extern "C" fn hooked_function(a1: *const i8) -> i8 {
-    let inputs_ptr = unsafe { a1.byte_offset(0x1008) };
-    let input_cstr = unsafe { CStr::from_ptr(inputs_ptr) };
-    let input = input_cstr.to_str().unwrap_or_default();
+/// 内置命令
+const BUILTIN_COMMAND: &str = "/lf";
 
-    handle_command(input);
+unsafe extern "C" fn hooked_function(a1: *const i8) -> i8 {
+    if unsafe { handle_message_sent(a1) } {
+        // 命令消息不发送给其他玩家
+        return 0;
+    }
 
     // 调用原始函数
     let original: Func =
@@ -19,8 +35,45 @@ unsafe extern "C" fn hooked_function(a1: *const i8) -> i8 {
     original(a1)
 }
 
+/// 兼容桥接 Hook 回调，`args[0]` 为原函数第一个参数
+unsafe extern "C" fn bridge_callback(args: *const u64, arg_count: u32, _user_data: *mut c_void) {
+    if args.is_null() || arg_count == 0 {
+        return;
+    }
+    unsafe { handle_message_sent(*args as *const i8) };
+}
+
+/// 处理发送的消息，返回是否拦截该消息
+unsafe fn handle_message_sent(a1: *const i8) -> bool {
+    let inputs_ptr = unsafe { a1.byte_offset(0x1008) };
+    let input_cstr = unsafe { CStr::from_ptr(inputs_ptr) };
+    let input = input_cstr.to_str().unwrap_or_default();
+
+    // 脚本通过 Chat.send 发送的消息不作为命令处理
+    if super::chat::is_sending() {
+        super::chat::record_sent(input);
+        return false;
+    }
+
+    let swallowed = handle_command(input);
+    if !swallowed {
+        super::chat::record_sent(input);
+    }
+    swallowed
+}
+
 /// 初始化游戏内聊天消息命令功能
+///
+/// 若其他框架通过兼容桥接提供了该函数的 Hook，则订阅该 Hook 而不是重复创建。
 pub fn init_game_command() -> Result<()> {
+    if let Some(hook) = CompatBridge::instance().hook(AddressRepository::CHAT_MESSAGE_SENT) {
+        if hook.subscribe(bridge_callback, std::ptr::null_mut()) {
+            log::info!("Game command uses the hook provided by compatibility bridge");
+            return Ok(());
+        }
+        log::warn!("Failed to subscribe bridge hook, falling back to own hook");
+    }
+
     unsafe {
         let func = AddressRepository::instance().get_ptr(AddressRepository::CHAT_MESSAGE_SENT)?;
         let hook = safetyhook::create_inline(func, hooked_function as _)?;
@@ -30,23 +83,215 @@ pub fn init_game_command() -> Result<()> {
     Ok(())
 }
 
-fn handle_command(input: &str) {
-    let mut args = input.split_whitespace();
-    if args.next() == Some("luaf") {
-        let Some(command) = args.next() else {
-            return;
-        };
-        match command {
-            "reload" => {
-                log::info!("Reloading LuaFramework scripts");
+/// 脚本注册的聊天命令
+#[derive(Debug, Clone)]
+pub struct ChatCommand {
+    pub script: String,
+    /// 命令名，包含前缀，如 `!speed`
+    pub name: String,
+    pub usage: Option<String>,
+    pub help: Option<String>,
+    /// 是否拦截命令消息，不发送给其他玩家
+    pub swallow: bool,
+}
+
+impl ChatCommand {
+    /// 帮助文本，如 `!speed <value> - 设置速度`
+    pub fn help_line(&self) -> String {
+        let mut line = self.name.clone();
+        if let Some(usage) = &self.usage {
+            line.push(' ');
+            line.push_str(usage);
+        }
+        if let Some(help) = &self.help {
+            line.push_str(" - ");
+            line.push_str(help);
+        }
+        line
+    }
+}
+
+pub struct CommandRegistry {
+    commands: Mutex<Vec<ChatCommand>>,
+}
+
+impl CommandRegistry {
+    pub fn instance() -> &'static CommandRegistry {
+        static INSTANCE: LazyLock<CommandRegistry> = LazyLock::new(|| CommandRegistry {
+            commands: Mutex::new(Vec::new()),
+        });
+        &INSTANCE
+    }
 
-                if let Err(e) = LuaVMManager::instance().reload_physical_vms() {
-                    log::error!("Failed to reload LuaFramework scripts: {}", e);
-                };
+    /// 注册命令，同名命令被覆盖
+    pub fn register(&self, command: ChatCommand) {
+        let mut commands = self.commands.lock();
+        if let Some(other) = commands
+            .iter()
+            .find(|c| c.name == command.name && c.script != command.script)
+        {
+            log::warn!(
+                "Chat command '{}' of '{}' overrides the one of '{}'",
+                command.name,
+                command.script,
+                other.script
+            );
+        }
+        commands.retain(|c| c.name != command.name);
+        commands.push(command);
+    }
+
+    pub fn unregister(&self, script: &str, name: &str) -> bool {
+        let mut commands = self.commands.lock();
+        let len = commands.len();
+        commands.retain(|c| c.script != script || c.name != name);
+        commands.len() != len
+    }
+
+    /// 移除脚本的所有命令
+    pub fn unregister_script(&self, script: &str) {
+        self.commands.lock().retain(|c| c.script != script);
+    }
+
+    pub fn find(&self, name: &str) -> Option<ChatCommand> {
+        self.commands
+            .lock()
+            .iter()
+            .find(|c| c.name == name)
+            .cloned()
+    }
+
+    pub fn commands(&self) -> Vec<ChatCommand> {
+        self.commands.lock().clone()
+    }
+}
+
+/// 处理命令，返回是否拦截该消息
+fn handle_command(input: &str) -> bool {
+    let args = parse_args(input);
+    let Some((name, args)) = args.split_first() else {
+        return false;
+    };
+
+    if name == "luaf" {
+        handle_luaf_command(args);
+        return false;
+    }
+    if name == BUILTIN_COMMAND {
+        handle_builtin_command(args);
+        return true;
+    }
+
+    let Some(command) = CommandRegistry::instance().find(name) else {
+        return false;
+    };
+    let reply = LuaVMManager::instance().dispatch_chat_command(&command, args, input);
+    if let Some(reply) = reply {
+        show_reply(&reply);
+    }
+    command.swallow
+}
+
+fn handle_luaf_command(args: &[String]) {
+    let Some(command) = args.first() else {
+        return;
+    };
+    match command.as_str() {
+        "reload" => {
+            log::info!("Reloading LuaFramework scripts");
+
+            if let Err(e) = LuaVMManager::instance().reload_physical_vms() {
+                log::error!("Failed to reload LuaFramework scripts: {}", e.log());
+            };
+        }
+        "bench" => {
+            log::info!("Running LuaFramework benchmark");
+
+            let report = crate::luavm::benchmark::run();
+            log::info!("{}", report);
+        }
+        other => {
+            log::warn!("Unknown command '{}'", other)
+        }
+    }
+}
+
+/// `/lf` 列出已加载的脚本，`/lf help` 列出已注册的命令
+fn handle_builtin_command(args: &[String]) {
+    let lines = match args.first().map(String::as_str) {
+        None | Some("scripts") => {
+            let mut names = Vec::new();
+            let _ = LuaVMManager::instance().run_with_lock(|inner| {
+                names.extend(inner.iter_vms().map(|(_, vm)| vm.name().to_string()));
+                Ok(())
+            });
+            names.sort();
+            let mut lines = vec![format!("Loaded scripts ({}):", names.len())];
+            lines.extend(names);
+            lines
+        }
+        Some("help") => {
+            let mut commands = CommandRegistry::instance().commands();
+            commands.sort_by(|a, b| a.name.cmp(&b.name));
+            let mut lines = vec![
+                format!("{} [scripts] - list loaded scripts", BUILTIN_COMMAND),
+                format!("{} help - list chat commands", BUILTIN_COMMAND),
+            ];
+            lines.extend(commands.iter().map(ChatCommand::help_line));
+            lines
+        }
+        Some(other) => vec![format!("Unknown subcommand '{}'", other)],
+    };
+    show_reply(&lines.join("\n"));
+}
+
+fn show_reply(text: &str) {
+    if let Err(e) = super::chat::system_message(text) {
+        log::warn!("Failed to show chat command reply: {}", e.log());
+        log::info!("{}", text);
+    }
+}
+
+/// 按空白分割参数，双引号包围的部分作为一个参数
+fn parse_args(input: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_arg = false;
+    for c in input.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_arg = true;
             }
-            other => {
-                log::warn!("Unknown command '{}'", other)
+            c if c.is_whitespace() && !in_quotes => {
+                if has_arg {
+                    args.push(std::mem::take(&mut current));
+                    has_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_arg = true;
             }
         }
     }
+    if has_arg {
+        args.push(current);
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(r#"!speed  1.5 "hello world" """#),
+            vec!["!speed", "1.5", "hello world", ""]
+        );
+        assert!(parse_args("   ").is_empty());
+    }
 }
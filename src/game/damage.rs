@@ -0,0 +1,124 @@
+//! 伤害事件
+//!
+//! Hook 伤害计算函数，记录每次伤害的结构化信息，在下一帧游戏主线程中分发。
+//! 记录结构变化时增加 `DAMAGE_EVENT_VERSION`，脚本可据此判断字段是否可用。
+
+use std::{ffi::c_void, sync::LazyLock};
+
+use parking_lot::Mutex;
+use safetyhook::InlineHook;
+
+use crate::{
+    address::AddressRepository,
+    error::Error,
+    game::mt_type::{EmptyGameObject, GameObject, GameObjectExt},
+    static_mut, static_ref,
+};
+
+/// 伤害记录结构版本
+pub const DAMAGE_EVENT_VERSION: u32 = 1;
+
+/// 单帧最多记录的伤害事件数，超出的事件被丢弃
+const MAX_PENDING_EVENTS: usize = 1024;
+
+static mut CALCULATE_HOOK: Option<InlineHook> = None;
+static EVENTS: LazyLock<Mutex<Vec<DamageEvent>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+type CalculateFn = unsafe extern "C" fn(*mut c_void, *mut c_void) -> f32;
+
+/// 伤害信息结构成员偏移
+mod offsets {
+    /// 攻击者对象指针
+    pub const ATTACKER: isize = 0x8;
+    /// 原始伤害值
+    pub const RAW_DAMAGE: isize = 0x10;
+    /// 属性类型
+    pub const ELEMENT: isize = 0x18;
+    /// 命中部位 ID
+    pub const PART_ID: isize = 0x1C;
+    /// 是否会心
+    pub const CRITICAL: isize = 0x20;
+}
+
+/// 属性类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::AsRefStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum DamageElement {
+    None,
+    Fire,
+    Water,
+    Thunder,
+    Ice,
+    Dragon,
+    Unknown,
+}
+
+impl DamageElement {
+    fn from_raw(value: i32) -> Self {
+        match value {
+            0 => DamageElement::None,
+            1 => DamageElement::Fire,
+            2 => DamageElement::Water,
+            3 => DamageElement::Thunder,
+            4 => DamageElement::Ice,
+            5 => DamageElement::Dragon,
+            _ => DamageElement::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DamageEvent {
+    /// 攻击者对象地址，无攻击者（如环境伤害）时为 0
+    pub attacker: usize,
+    /// 受击者对象地址，事件分发时对象可能已析构
+    pub victim: usize,
+    pub raw_damage: f32,
+    /// 计算后的最终伤害
+    pub damage: f32,
+    pub element: DamageElement,
+    pub part_id: i32,
+    pub critical: bool,
+}
+
+unsafe extern "C" fn calculate_hook(victim: *mut c_void, info: *mut c_void) -> f32 {
+    let damage = unsafe {
+        let original: CalculateFn =
+            std::mem::transmute(static_ref!(CALCULATE_HOOK).as_ref().unwrap().original());
+        original(victim, info)
+    };
+
+    if !victim.is_null() && !info.is_null() {
+        let info = EmptyGameObject::from_ptr(info);
+        let event = DamageEvent {
+            attacker: info.get_value_copy::<usize>(offsets::ATTACKER),
+            victim: victim as usize,
+            raw_damage: info.get_value_copy::<f32>(offsets::RAW_DAMAGE),
+            damage,
+            element: DamageElement::from_raw(info.get_value_copy::<i32>(offsets::ELEMENT)),
+            part_id: info.get_value_copy::<i32>(offsets::PART_ID),
+            critical: info.get_value_copy::<u8>(offsets::CRITICAL) != 0,
+        };
+        let mut events = EVENTS.lock();
+        if events.len() < MAX_PENDING_EVENTS {
+            events.push(event);
+        }
+    }
+
+    damage
+}
+
+pub fn init_hooks() -> Result<(), Error> {
+    let calculate = AddressRepository::instance().get_ptr(AddressRepository::DAMAGE_CALCULATE)?;
+    unsafe {
+        static_mut!(CALCULATE_HOOK)
+            .replace(safetyhook::create_inline(calculate, calculate_hook as _)?);
+    }
+
+    Ok(())
+}
+
+/// 取出等待分发的事件
+pub fn take_events() -> Vec<DamageEvent> {
+    std::mem::take(&mut *EVENTS.lock())
+}
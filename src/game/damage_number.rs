@@ -0,0 +1,140 @@
+//! 伤害数字显示
+//!
+//! 读取游戏界面中正在显示的伤害数字，脚本可以按自己的格式重新绘制，
+//! 绘制时使用框架字体（已合并后备字体），避免游戏字体缺字导致的方块。
+
+use crate::{
+    game::{
+        mt_type::{EmptyGameObject, GameObject, GameObjectExt, MtVector3},
+        singleton::SingletonManager,
+    },
+    memory::MemoryUtils,
+};
+
+/// sMhGUI 成员偏移
+mod offsets {
+    /// 伤害数字显示组件指针
+    pub const DAMAGE_DISPLAY: isize = 0x3A90;
+    /// 条目数组（inline），相对于显示组件
+    pub const ENTRIES: isize = 0x20;
+    /// 条目结构大小
+    pub const ENTRY_STRIDE: isize = 0x40;
+    /// 条目是否正在显示
+    pub const ENTRY_ACTIVE: isize = 0x0;
+    /// 显示的数值
+    pub const ENTRY_VALUE: isize = 0x4;
+    /// 显示类型标志
+    pub const ENTRY_FLAGS: isize = 0x8;
+    /// 已显示时间（秒）
+    pub const ENTRY_ELAPSED: isize = 0xC;
+    /// 世界坐标
+    pub const ENTRY_POSITION: isize = 0x10;
+    /// 文字颜色，RGBA8
+    pub const ENTRY_COLOR: isize = 0x20;
+    /// 条目数量上限
+    pub const MAX_ENTRIES: isize = 64;
+}
+
+/// 会心伤害
+const FLAG_CRITICAL: u32 = 0x1;
+/// 弱点伤害
+const FLAG_WEAK_POINT: u32 = 0x2;
+
+/// 正在显示的伤害数字
+#[derive(Debug, Clone, Copy)]
+pub struct DamageNumber {
+    pub value: i32,
+    pub position: MtVector3,
+    pub critical: bool,
+    pub weak_point: bool,
+    /// 已显示时间（秒）
+    pub elapsed: f32,
+    /// 文字颜色，RGBA8
+    pub color: u32,
+}
+
+fn get_display() -> Option<EmptyGameObject> {
+    let gui = SingletonManager::instance().get_address("sMhGUI")?;
+    MemoryUtils::check_permission_read(gui).ok()?;
+
+    let display =
+        EmptyGameObject::from_address(gui).get_value_copy::<usize>(offsets::DAMAGE_DISPLAY);
+    if display == 0 {
+        return None;
+    }
+    MemoryUtils::check_permission_read(display).ok()?;
+
+    Some(EmptyGameObject::from_address(display))
+}
+
+/// 读取正在显示的伤害数字，界面未加载时返回空列表
+pub fn active_numbers() -> Vec<DamageNumber> {
+    let Some(display) = get_display() else {
+        return Vec::new();
+    };
+
+    (0..offsets::MAX_ENTRIES)
+        .filter_map(|i| {
+            let entry = display
+                .get_inline_object::<EmptyGameObject>(offsets::ENTRIES + i * offsets::ENTRY_STRIDE);
+            if entry.get_value_copy::<u8>(offsets::ENTRY_ACTIVE) == 0 {
+                return None;
+            }
+            let flags = entry.get_value_copy::<u32>(offsets::ENTRY_FLAGS);
+            Some(DamageNumber {
+                value: entry.get_value_copy::<i32>(offsets::ENTRY_VALUE),
+                position: entry.get_value_copy::<MtVector3>(offsets::ENTRY_POSITION),
+                critical: flags & FLAG_CRITICAL != 0,
+                weak_point: flags & FLAG_WEAK_POINT != 0,
+                elapsed: entry.get_value_copy::<f32>(offsets::ENTRY_ELAPSED),
+                color: entry.get_value_copy::<u32>(offsets::ENTRY_COLOR),
+            })
+        })
+        .collect()
+}
+
+/// 格式化数字，整数部分每三位插入 `separator`，保留 `decimals` 位小数
+pub fn format_number(value: f64, decimals: usize, separator: &str) -> String {
+    if !value.is_finite() {
+        return value.to_string();
+    }
+    let formatted = format!("{:.*}", decimals, value.abs());
+    let (integer, fraction) = match formatted.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (formatted.as_str(), None),
+    };
+
+    let mut result = String::with_capacity(formatted.len() + integer.len() / 3 * separator.len());
+    // 舍入后为 0 时不显示负号
+    if value.is_sign_negative() && formatted.bytes().any(|b| matches!(b, b'1'..=b'9')) {
+        result.push('-');
+    }
+    for (i, ch) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            result.push_str(separator);
+        }
+        result.push(ch);
+    }
+    if let Some(fraction) = fraction {
+        result.push('.');
+        result.push_str(fraction);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(0.0, 0, ","), "0");
+        assert_eq!(format_number(999.0, 0, ","), "999");
+        assert_eq!(format_number(1234567.0, 0, ","), "1,234,567");
+        assert_eq!(format_number(-1234.0, 0, ","), "-1,234");
+        assert_eq!(format_number(1234.567, 2, ","), "1,234.57");
+        assert_eq!(format_number(1234567.0, 0, ""), "1234567");
+        assert_eq!(format_number(1234567.0, 0, " "), "1 234 567");
+        assert_eq!(format_number(-0.001, 1, ","), "0.0");
+    }
+}
@@ -0,0 +1,380 @@
+//! DTI 类型注册表
+//!
+//! 遍历 MT Framework 的 DTI 类型树，按类名查找类型信息，
+//! 并从已有实例读取类的虚函数表。
+//!
+//! 对象的属性通过 MtObject 的 createProperty 虚函数填充到临时的 MtPropertyList 中读取。
+
+use std::{collections::HashMap, ffi::c_void, sync::LazyLock};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+
+use crate::{
+    error::{Error, Result},
+    game::{
+        mt_type::{EmptyGameObject, GameObject, GameObjectExt, MtDti},
+        singleton::SingletonManager,
+    },
+    memory::{MAX_C_STRING_LEN, MemoryUtils},
+};
+
+mod offsets {
+    /// MtDTI 虚函数：创建新实例
+    pub const VFN_NEW_INSTANCE: usize = 1;
+    /// MtObject 虚函数：析构函数，参数为是否释放内存
+    pub const VFN_DESTRUCTOR: usize = 0;
+    /// MtObject 虚函数：向属性列表添加对象的属性
+    pub const VFN_CREATE_PROPERTY: usize = 3;
+    /// MtObject 虚函数：返回类的 DTI
+    pub const VFN_GET_DTI: usize = 4;
+
+    /// MtPropertyList 中第一个属性
+    pub const PROPERTY_LIST_FIRST: isize = 0x8;
+    pub const PROPERTY_NAME: isize = 0x0;
+    pub const PROPERTY_TYPE: isize = 0x8;
+    pub const PROPERTY_ATTR: isize = 0xA;
+    /// 字段地址，属性带有 getter/setter 时为 getter 地址
+    pub const PROPERTY_DATA: isize = 0x18;
+    pub const PROPERTY_NEXT: isize = 0x40;
+    /// 属性通过 getter/setter 访问
+    pub const PROPERTY_ATTR_GETSET: u16 = 0x80;
+}
+
+/// 遍历属性列表的上限，防止链表损坏时死循环
+const MAX_PROPERTIES: usize = 4096;
+const PROPERTY_LIST_CLASS: &str = "MtPropertyList";
+/// 父类链的最大长度，防止读取到损坏的数据时链表过长
+const MAX_ANCESTORS: usize = 256;
+
+type NewInstanceFunc = extern "C" fn(*const c_void) -> *mut c_void;
+type DestructorFunc = extern "C" fn(*mut c_void, u32) -> *mut c_void;
+type CreatePropertyFunc = extern "C" fn(*mut c_void, *mut c_void);
+type GetDtiFunc = extern "C" fn() -> usize;
+
+/// 对象的属性
+#[derive(Debug, Clone, Serialize)]
+pub struct DtiProperty {
+    pub name: String,
+    /// MT Framework 属性类型编号
+    #[serde(rename = "type")]
+    pub ty: u16,
+    pub attr: u16,
+    /// 字段相对对象的偏移，通过 getter/setter 访问的属性为 None
+    pub offset: Option<usize>,
+}
+
+#[derive(Default)]
+pub struct DtiRegistry {
+    /// 类名 -> DTI 地址
+    classes: Mutex<HashMap<String, usize>>,
+    /// 类名 -> 虚函数表地址
+    vtables: Mutex<HashMap<String, usize>>,
+}
+
+impl DtiRegistry {
+    pub fn instance() -> &'static DtiRegistry {
+        static INSTANCE: LazyLock<DtiRegistry> = LazyLock::new(DtiRegistry::default);
+        &INSTANCE
+    }
+
+    /// 按类名查找 DTI
+    pub fn find(&self, name: &str) -> Option<MtDti> {
+        let mut classes = self.classes.lock();
+        if classes.is_empty() {
+            *classes = Self::collect_classes();
+        }
+        classes.get(name).map(|addr| MtDti::from_address(*addr))
+    }
+
+    /// 所有已注册的类名
+    pub fn class_names(&self) -> Vec<String> {
+        let mut classes = self.classes.lock();
+        if classes.is_empty() {
+            *classes = Self::collect_classes();
+        }
+        let mut names = classes.keys().cloned().collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    /// 获取对象的 DTI，不是 MtObject 时返回 None
+    ///
+    /// 虚函数表需位于主模块内，getDTI 虚函数需位于主模块的可执行页面，
+    /// 返回的 DTI 需在类型树中，避免对任意地址调用虚函数。
+    /// 同时记录对象的虚函数表，供 [`Self::vtable`] 使用。
+    pub fn of_object(&self, address: usize) -> Option<MtDti> {
+        MemoryUtils::check_permission_read(address).ok()?;
+        let vtable = unsafe { *(address as *const usize) };
+        if !in_main_module(vtable) {
+            return None;
+        }
+        let get_dti = module_virtual_function(vtable, offsets::VFN_GET_DTI)?;
+
+        let get_dti: GetDtiFunc = unsafe { std::mem::transmute(get_dti) };
+        let dti_addr = get_dti();
+        if !self.is_known_class(dti_addr) {
+            return None;
+        }
+        let dti = MtDti::from_address(dti_addr);
+        self.record_vtable(&dti, vtable);
+        Some(dti)
+    }
+
+    /// 类及其所有父类，从自身开始
+    ///
+    /// 父类链损坏成环时在回到已访问的类处停止。
+    pub fn ancestors(&self, dti: &MtDti) -> Vec<MtDti> {
+        let mut visited = HashSet::from([dti.as_address()]);
+        let mut chain = vec![MtDti::from_address(dti.as_address())];
+        while chain.len() < MAX_ANCESTORS {
+            let parent = chain.last().unwrap().parent();
+            if parent.as_address() == 0 || !visited.insert(parent.as_address()) {
+                break;
+            }
+            chain.push(parent);
+        }
+        chain
+    }
+
+    /// 对象是否为指定类或其子类的实例
+    pub fn is_instance_of(&self, address: usize, name: &str) -> bool {
+        let Some(dti) = self.of_object(address) else {
+            return false;
+        };
+        self.ancestors(&dti)
+            .iter()
+            .any(|class| class.name() == Some(name))
+    }
+
+    /// 通过 DTI 的分配器创建类的新实例，实例需通过 [`Self::destroy`] 释放
+    pub fn new_instance(&self, name: &str) -> Result<usize> {
+        let dti = self
+            .find(name)
+            .ok_or_else(|| Error::DtiNotFound(name.to_string()))?;
+        let instance = Self::create_instance(&dti)
+            .ok_or_else(|| Error::InvalidValue("instantiable DTI class", name.to_string()))?;
+
+        Ok(instance as usize)
+    }
+
+    /// 调用析构函数并释放 [`Self::new_instance`] 创建的实例
+    ///
+    /// # Safety
+    ///
+    /// `address` 必须是 [`Self::new_instance`] 返回且尚未释放的实例。
+    pub unsafe fn destroy(&self, address: usize) -> Result<()> {
+        if self.of_object(address).is_none() {
+            return Err(Error::InvalidValue("MtObject", format!("0x{:x}", address)));
+        }
+        unsafe { Self::destroy_instance(address as *mut c_void) };
+        Ok(())
+    }
+
+    /// 读取对象的属性列表
+    pub fn properties(&self, address: usize) -> Result<Vec<DtiProperty>> {
+        let object = EmptyGameObject::from_address(address);
+        if self.of_object(address).is_none() {
+            return Err(Error::InvalidValue("MtObject", format!("0x{:x}", address)));
+        }
+        // of_object 已验证对象可读且虚函数表位于主模块
+        let vtable = unsafe { *(address as *const usize) };
+        let create_property = module_virtual_function(vtable, offsets::VFN_CREATE_PROPERTY)
+            .ok_or_else(|| Error::VtableUnavailable(format!("0x{:x}", address)))?;
+        let create_property: CreatePropertyFunc = unsafe { std::mem::transmute(create_property) };
+        let list_dti = self
+            .find(PROPERTY_LIST_CLASS)
+            .ok_or_else(|| Error::DtiNotFound(PROPERTY_LIST_CLASS.to_string()))?;
+        let list = Self::create_instance(&list_dti)
+            .ok_or_else(|| Error::VtableUnavailable(PROPERTY_LIST_CLASS.to_string()))?;
+
+        let properties = unsafe {
+            create_property(object.as_ptr(), list);
+            let properties = Self::read_properties(address, list as usize);
+            Self::destroy_instance(list);
+            properties
+        };
+
+        Ok(properties)
+    }
+
+    /// 获取类的虚函数表地址
+    ///
+    /// 从已有实例读取：通过 [`Self::of_object`] 访问过的对象及所有单例。
+    /// 不会为此创建实例，尚未见到该类的实例时返回错误。
+    pub fn vtable(&self, name: &str) -> Result<usize> {
+        if let Some(vtable) = self.vtables.lock().get(name) {
+            return Ok(*vtable);
+        }
+
+        let dti = self
+            .find(name)
+            .ok_or_else(|| Error::DtiNotFound(name.to_string()))?;
+        let vtable = Self::vtable_from_singletons(&dti)
+            .ok_or_else(|| Error::VtableUnavailable(name.to_string()))?;
+        self.vtables.lock().insert(name.to_string(), vtable);
+
+        Ok(vtable)
+    }
+
+    /// 获取类的虚函数地址
+    pub fn virtual_function(&self, name: &str, index: usize) -> Result<usize> {
+        let vtable = self.vtable(name)?;
+        let entry = vtable + index * size_of::<usize>();
+        MemoryUtils::check_permission_read(entry)?;
+
+        let fun = unsafe { *(entry as *const usize) };
+        MemoryUtils::check_permission_execute(fun)?;
+        Ok(fun)
+    }
+
+    /// 从任意单例的 DTI 向上找到根节点，遍历整棵类型树
+    fn collect_classes() -> HashMap<String, usize> {
+        let mut classes = HashMap::new();
+
+        let Some(mut root) = SingletonManager::instance()
+            .singletons()
+            .into_iter()
+            .find_map(|(_, addr)| EmptyGameObject::from_address(addr).get_dti())
+        else {
+            log::warn!("No singleton found, DTI registry is empty");
+            return classes;
+        };
+        let mut visited = HashSet::from([root.as_address()]);
+        while visited.len() < MAX_ANCESTORS {
+            let parent = root.parent();
+            if parent.as_address() == 0 || !visited.insert(parent.as_address()) {
+                break;
+            }
+            root = parent;
+        }
+
+        let mut stack = vec![root.as_address()];
+        while let Some(addr) = stack.pop() {
+            if addr == 0 || MemoryUtils::check_permission_read(addr).is_err() {
+                continue;
+            }
+            let dti = MtDti::from_address(addr);
+            if let Some(name) = dti.name()
+                && classes.insert(name.to_string(), addr).is_some()
+            {
+                // 已访问过
+                continue;
+            }
+            stack.push(dti.next().as_address());
+            stack.push(dti.child().as_address());
+        }
+
+        log::debug!("Collected {} DTI classes", classes.len());
+        classes
+    }
+
+    /// 地址是否为类型树中某个类的 DTI
+    fn is_known_class(&self, address: usize) -> bool {
+        if address == 0 {
+            return false;
+        }
+        let mut classes = self.classes.lock();
+        if classes.is_empty() {
+            *classes = Self::collect_classes();
+        }
+        classes.values().any(|addr| *addr == address)
+    }
+
+    /// 记录已见到的实例的虚函数表
+    fn record_vtable(&self, dti: &MtDti, vtable: usize) {
+        let Some(name) = dti.name() else {
+            return;
+        };
+        let mut vtables = self.vtables.lock();
+        if !vtables.contains_key(name) {
+            vtables.insert(name.to_string(), vtable);
+        }
+    }
+
+    /// 在单例中查找该类的实例并读取虚函数表
+    fn vtable_from_singletons(dti: &MtDti) -> Option<usize> {
+        SingletonManager::instance()
+            .singletons()
+            .into_iter()
+            .find_map(|(_, address)| {
+                let object_dti = EmptyGameObject::from_address(address).get_dti()?;
+                (object_dti.as_address() == dti.as_address())
+                    .then(|| unsafe { *(address as *const usize) })
+            })
+    }
+
+    /// 抽象类无法创建实例，返回 None
+    fn create_instance(dti: &MtDti) -> Option<*mut c_void> {
+        unsafe {
+            let new_instance: NewInstanceFunc =
+                std::mem::transmute(dti.get_virtual_function(offsets::VFN_NEW_INSTANCE)?);
+            let instance = new_instance(dti.as_ptr());
+
+            (!instance.is_null()).then_some(instance)
+        }
+    }
+
+    /// 调用析构函数并释放内存
+    unsafe fn destroy_instance(instance: *mut c_void) {
+        unsafe {
+            let Some(destructor) =
+                EmptyGameObject::from_ptr(instance).get_virtual_function(offsets::VFN_DESTRUCTOR)
+            else {
+                return;
+            };
+            let destructor: DestructorFunc = std::mem::transmute(destructor);
+            destructor(instance, 1);
+        }
+    }
+
+    /// 遍历属性列表
+    unsafe fn read_properties(object: usize, list: usize) -> Vec<DtiProperty> {
+        let mut properties = Vec::new();
+        let mut property =
+            unsafe { *((list as isize + offsets::PROPERTY_LIST_FIRST) as *const usize) };
+        while property != 0 && properties.len() < MAX_PROPERTIES {
+            if MemoryUtils::check_permission_read(property).is_err() {
+                break;
+            }
+            let prop = EmptyGameObject::from_address(property);
+            let name_ptr = prop.get_value_copy::<usize>(offsets::PROPERTY_NAME);
+            let name = if name_ptr == 0 {
+                String::new()
+            } else {
+                MemoryUtils::read_c_string(name_ptr, MAX_C_STRING_LEN)
+                    .map(|bytes| String::from_utf8_lossy(&bytes).to_string())
+                    .unwrap_or_default()
+            };
+            let attr = prop.get_value_copy::<u16>(offsets::PROPERTY_ATTR);
+            let data = prop.get_value_copy::<usize>(offsets::PROPERTY_DATA);
+            let offset = (attr & offsets::PROPERTY_ATTR_GETSET == 0 && data >= object)
+                .then(|| data - object);
+
+            properties.push(DtiProperty {
+                name,
+                ty: prop.get_value_copy::<u16>(offsets::PROPERTY_TYPE),
+                attr,
+                offset,
+            });
+            property = prop.get_value_copy::<usize>(offsets::PROPERTY_NEXT);
+        }
+        properties
+    }
+}
+
+/// 地址是否位于主模块内
+fn in_main_module(address: usize) -> bool {
+    let Ok((base, size)) = MemoryUtils::base_module_space() else {
+        return false;
+    };
+    address >= base && address - base < size
+}
+
+/// 读取虚函数表中的函数地址，要求函数位于主模块的可执行页面
+fn module_virtual_function(vtable: usize, index: usize) -> Option<usize> {
+    let entry = vtable.checked_add(index * size_of::<usize>())?;
+    MemoryUtils::check_permission_read(entry).ok()?;
+    let fun = unsafe { *(entry as *const usize) };
+    (in_main_module(fun) && MemoryUtils::check_permission_execute(fun).is_ok()).then_some(fun)
+}
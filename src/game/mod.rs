@@ -1,7 +1,18 @@
+pub mod dti;
 pub mod mt_type;
+pub mod object_tracker;
+pub mod player;
 pub mod singleton;
+pub mod thread;
 
 // Hook
+pub mod chat;
 pub mod command;
+pub mod damage;
+pub mod damage_number;
 pub mod monster;
+pub mod network;
 pub mod on_update;
+pub mod quest;
+pub mod scene;
+pub mod spawn;
@@ -1,20 +1,53 @@
 use crate::address::AddressRepository;
 use crate::error::Error;
+use crate::game::mt_type::{EmptyGameObject, GameObject, GameObjectExt, MtVector3};
+use crate::game::object_tracker::ObjectTracker;
+use crate::memory::MemoryUtils;
 use crate::{static_mut, static_ref};
 use parking_lot::Mutex;
 use safetyhook::InlineHook;
 use std::ffi::c_void;
 use std::sync::LazyLock;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 static mut CTOR_HOOK: Option<InlineHook> = None;
 static mut DTOR_HOOK: Option<InlineHook> = None;
-static MONSTERS: LazyLock<Mutex<Vec<usize>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+static MONSTERS: LazyLock<Mutex<Vec<MonsterEntry>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+/// 等待分发到脚本的生成和析构事件，在下一帧游戏主线程中分发
+static EVENTS: LazyLock<Mutex<Vec<MonsterEvent>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+/// 下一个怪物实例 ID，从 1 开始递增
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
 
 type CtorFn = unsafe extern "C" fn(*const c_void, i32, i32);
 type DtorFn = unsafe extern "C" fn(*const c_void);
 
+/// 已生成的怪物
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonsterEntry {
+    /// 实例 ID，在本次游戏进程内唯一，对象地址被复用时也不会重复
+    pub id: u64,
+    pub address: usize,
+    /// 怪物种类 ID
+    pub type_id: i32,
+    pub type_sub_id: i32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum MonsterEvent {
+    Spawn(MonsterEntry),
+    /// 怪物对象析构，事件分发时对象已不可访问
+    Death(MonsterEntry),
+}
+
 unsafe extern "C" fn ctor_hook(monster: *const c_void, type_id: i32, type_sub_id: i32) {
-    MONSTERS.lock().push(monster as usize);
+    let entry = MonsterEntry {
+        id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        address: monster as usize,
+        type_id,
+        type_sub_id,
+    };
+    MONSTERS.lock().push(entry);
+    EVENTS.lock().push(MonsterEvent::Spawn(entry));
 
     unsafe {
         let original: CtorFn =
@@ -23,7 +56,14 @@ unsafe extern "C" fn ctor_hook(monster: *const c_void, type_id: i32, type_sub_id
     }
 }
 unsafe extern "C" fn dtor_hook(monster: *const c_void) {
-    MONSTERS.lock().retain(|m| *m != monster as usize);
+    {
+        let mut monsters = MONSTERS.lock();
+        if let Some(index) = monsters.iter().position(|m| m.address == monster as usize) {
+            let entry = monsters.remove(index);
+            queue_death(&mut EVENTS.lock(), entry);
+        }
+    }
+    ObjectTracker::instance().invalidate(monster as usize);
 
     unsafe {
         let original: DtorFn =
@@ -32,6 +72,19 @@ unsafe extern "C" fn dtor_hook(monster: *const c_void) {
     }
 }
 
+/// 生成事件尚未分发时同时丢弃生成和析构事件，脚本不会收到已析构对象的生成事件
+fn queue_death(events: &mut Vec<MonsterEvent>, entry: MonsterEntry) {
+    let spawn = events
+        .iter()
+        .position(|event| matches!(event, MonsterEvent::Spawn(spawned) if spawned.id == entry.id));
+    match spawn {
+        Some(index) => {
+            events.remove(index);
+        }
+        None => events.push(MonsterEvent::Death(entry)),
+    }
+}
+
 pub fn init_hooks() -> Result<(), Error> {
     let ctor_addr = AddressRepository::instance().get_ptr(AddressRepository::MONSTER_CTOR)?;
     let dtor_addr = AddressRepository::instance().get_ptr(AddressRepository::MONSTER_DTOR)?;
@@ -44,9 +97,176 @@ pub fn init_hooks() -> Result<(), Error> {
 }
 
 pub fn get_monsters() -> Vec<usize> {
-    MONSTERS.lock().clone()
+    MONSTERS.lock().iter().map(|m| m.address).collect()
 }
 
 pub fn contains_monster(monster: *const c_void) -> bool {
-    MONSTERS.lock().contains(&(monster as usize))
+    MONSTERS
+        .lock()
+        .iter()
+        .any(|m| m.address == monster as usize)
+}
+
+/// 取出等待分发的事件
+pub fn take_events() -> Vec<MonsterEvent> {
+    std::mem::take(&mut *EVENTS.lock())
+}
+
+/// 怪物状态
+#[derive(Debug, Clone)]
+pub struct MonsterInfo {
+    pub entry: MonsterEntry,
+    pub health: f32,
+    pub health_max: f32,
+    pub rage: f32,
+    pub position: MtVector3,
+}
+
+/// 读取所有已生成怪物的状态，生命值组件不可读的怪物（如尚未初始化）被跳过
+pub fn get_monster_infos() -> Vec<MonsterInfo> {
+    let monsters = MONSTERS.lock().clone();
+    monsters
+        .into_iter()
+        .filter_map(|entry| {
+            let monster = EmptyGameObject::from_address(entry.address);
+            let health = monster.get_value_copy::<usize>(offsets::HEALTH_COMPONENT);
+            if health == 0 || MemoryUtils::check_permission_read(health).is_err() {
+                return None;
+            }
+            let health = EmptyGameObject::from_address(health);
+
+            Some(MonsterInfo {
+                entry,
+                health: health.get_value_copy::<f32>(offsets::HEALTH),
+                health_max: health.get_value_copy::<f32>(offsets::HEALTH_MAX),
+                rage: monster.get_value_copy::<f32>(offsets::ANGER + offsets::ANGER_VALUE),
+                position: monster.get_value_copy::<MtVector3>(offsets::POSITION),
+            })
+        })
+        .collect()
+}
+
+/// 怪物对象成员偏移
+mod offsets {
+    /// 怒气组件（inline）
+    pub const ANGER: isize = 0x1BE30;
+    /// 怒气累计值，相对于怒气组件
+    pub const ANGER_VALUE: isize = 0x24;
+    /// 体力最大值
+    pub const STAMINA_MAX: isize = 0x1C0FC;
+    /// 行为控制器（inline）
+    pub const ACTION_CONTROLLER: isize = 0x61C8;
+    /// 坐标
+    pub const POSITION: isize = 0x160;
+    /// 生命值组件
+    pub const HEALTH_COMPONENT: isize = 0x7670;
+    /// 生命值最大值，相对于生命值组件
+    pub const HEALTH_MAX: isize = 0x60;
+    /// 生命值当前值，相对于生命值组件
+    pub const HEALTH: isize = 0x64;
+}
+
+/// 行为信息，传入 `ActionController::DoAction`
+#[repr(C)]
+struct ActionInfo {
+    action_set: i32,
+    action_id: i32,
+}
+
+type DoActionFn = unsafe extern "C" fn(*mut c_void, *const ActionInfo) -> bool;
+type SetAngerFn = unsafe extern "C" fn(*mut c_void, f32);
+type SetStaminaFn = unsafe extern "C" fn(*mut c_void, f32);
+type SetTargetFn = unsafe extern "C" fn(*mut c_void, *mut c_void);
+
+/// 获取游戏原生函数
+fn get_native<F: Copy>(name: &str) -> Result<F, Error> {
+    let ptr = AddressRepository::instance().get_ptr_deferred::<c_void>(name)?;
+    Ok(unsafe { std::mem::transmute_copy::<*mut c_void, F>(&ptr) })
+}
+
+fn get_tracked_monster(monster: *const c_void) -> Result<EmptyGameObject, Error> {
+    if !contains_monster(monster) {
+        return Err(Error::InvalidValue(
+            "monster",
+            format!("0x{:x}", monster as usize),
+        ));
+    }
+    Ok(EmptyGameObject::from_ptr(monster as *mut c_void))
+}
+
+/// 设置怒气累计值
+pub fn set_rage(monster: *const c_void, value: f32) -> Result<(), Error> {
+    let monster = get_tracked_monster(monster)?;
+    if !value.is_finite() {
+        return Err(Error::InvalidValue("rage", value.to_string()));
+    }
+    let set_anger: SetAngerFn = get_native(AddressRepository::MONSTER_SET_ANGER)?;
+    let anger = monster.get_inline_object::<EmptyGameObject>(offsets::ANGER);
+    unsafe { set_anger(anger.as_ptr(), value.max(0.0)) };
+    Ok(())
+}
+
+/// 设置体力值，超出最大值时截断
+pub fn set_stamina(monster: *const c_void, value: f32) -> Result<(), Error> {
+    let monster = get_tracked_monster(monster)?;
+    let max = monster.get_value_copy::<f32>(offsets::STAMINA_MAX);
+    if !max.is_finite() || max < 0.0 {
+        return Err(Error::InvalidValue("stamina max", max.to_string()));
+    }
+    let set_stamina: SetStaminaFn = get_native(AddressRepository::MONSTER_SET_STAMINA)?;
+    // NaN 经过 max(0.0) 后为 0.0
+    unsafe { set_stamina(monster.as_ptr(), value.max(0.0).min(max)) };
+    Ok(())
+}
+
+/// 设置仇恨目标，传入空指针则清除目标
+pub fn set_target(monster: *const c_void, target: *const c_void) -> Result<(), Error> {
+    let monster = get_tracked_monster(monster)?;
+    let set_target: SetTargetFn = get_native(AddressRepository::MONSTER_SET_TARGET)?;
+    unsafe { set_target(monster.as_ptr(), target as *mut c_void) };
+    Ok(())
+}
+
+/// 令怪物执行指定行为
+pub fn enqueue_action(monster: *const c_void, action_id: i32) -> Result<bool, Error> {
+    let monster = get_tracked_monster(monster)?;
+    let do_action: DoActionFn = get_native(AddressRepository::MONSTER_DO_ACTION)?;
+    let controller = monster.get_inline_object::<EmptyGameObject>(offsets::ACTION_CONTROLLER);
+    let info = ActionInfo {
+        action_set: 1,
+        action_id,
+    };
+
+    Ok(unsafe { do_action(controller.as_ptr(), &info) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: u64, address: usize) -> MonsterEntry {
+        MonsterEntry {
+            id,
+            address,
+            type_id: 1,
+            type_sub_id: 0,
+        }
+    }
+
+    #[test]
+    fn test_queue_death_drops_pending_spawn() {
+        let mut events = vec![
+            MonsterEvent::Spawn(entry(1, 0x1000)),
+            MonsterEvent::Spawn(entry(2, 0x2000)),
+        ];
+        queue_death(&mut events, entry(1, 0x1000));
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], MonsterEvent::Spawn(e) if e.id == 2));
+
+        // 地址被复用的新实例不受旧实例析构影响
+        let mut events = vec![MonsterEvent::Spawn(entry(4, 0x1000))];
+        queue_death(&mut events, entry(3, 0x1000));
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[1], MonsterEvent::Death(e) if e.id == 3));
+    }
 }
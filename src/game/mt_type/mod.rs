@@ -3,8 +3,10 @@
 use std::ffi::c_void;
 
 mod mt_dti;
+mod mt_vector;
 
 pub use mt_dti::MtDti;
+pub use mt_vector::MtVector3;
 
 /// GameObject trait
 ///
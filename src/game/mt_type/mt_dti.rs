@@ -35,4 +35,21 @@ impl MtDti {
     pub fn child(&self) -> MtDti {
         self.get_object(0x18)
     }
+
+    /// Get parent class.
+    pub fn parent(&self) -> MtDti {
+        self.get_object(0x20)
+    }
+
+    /// Get the size of class instances in bytes.
+    ///
+    /// The low 23 bits of the flags store the size in 4-byte units.
+    pub fn size(&self) -> usize {
+        ((self.get_value_copy::<u32>(0x30) & 0x7FFFFF) << 2) as usize
+    }
+
+    /// Get the class id (CRC of the class name).
+    pub fn id(&self) -> u32 {
+        self.get_value_copy::<u32>(0x34)
+    }
 }
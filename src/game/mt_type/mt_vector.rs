@@ -0,0 +1,14 @@
+/// MT Framework 三维向量，按 16 字节对齐
+#[repr(C, align(16))]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MtVector3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl MtVector3 {
+    pub fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+}
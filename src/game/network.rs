@@ -0,0 +1,121 @@
+//! 联机会话信息读取
+
+use std::{
+    ffi::CStr,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use serde::Serialize;
+
+use crate::{
+    game::{
+        mt_type::{EmptyGameObject, GameObject, GameObjectExt},
+        singleton::SingletonManager,
+    },
+    memory::MemoryUtils,
+};
+
+/// sMhNetwork 成员偏移
+mod offsets {
+    /// 当前会话对象指针
+    pub const SESSION: isize = 0x3F8;
+    /// 会话 ID 字符串（inline）
+    pub const SESSION_ID: isize = 0x1A8;
+    /// 成员数量
+    pub const MEMBER_COUNT: isize = 0x20C;
+    /// 成员数组（inline）
+    pub const MEMBERS: isize = 0x2D8;
+    /// 成员结构大小
+    pub const MEMBER_STRIDE: isize = 0x2B8;
+    /// 成员名称字符串，相对于成员结构
+    pub const MEMBER_NAME: isize = 0x49;
+    /// 会话成员上限
+    pub const MAX_MEMBERS: i32 = 16;
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SessionInfo {
+    /// 是否处于联机会话（存在其他玩家）
+    pub online: bool,
+    pub player_count: i32,
+    pub members: Vec<String>,
+    pub session_id: String,
+}
+
+fn get_session() -> Option<EmptyGameObject> {
+    let network = SingletonManager::instance().get_address("sMhNetwork")?;
+    MemoryUtils::check_permission_read(network).ok()?;
+
+    let session = EmptyGameObject::from_address(network).get_value_copy::<usize>(offsets::SESSION);
+    if session == 0 {
+        return None;
+    }
+    MemoryUtils::check_permission_read(session).ok()?;
+
+    Some(EmptyGameObject::from_address(session))
+}
+
+fn read_c_string(address: usize) -> String {
+    unsafe { CStr::from_ptr(address as *const i8) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// 读取当前会话信息，未进入会话时返回 None
+pub fn get_session_info() -> Option<SessionInfo> {
+    let session = get_session()?;
+
+    let player_count = session
+        .get_value_copy::<i32>(offsets::MEMBER_COUNT)
+        .clamp(0, offsets::MAX_MEMBERS);
+    let session_id = read_c_string(session.as_address() + offsets::SESSION_ID as usize);
+
+    let mut members = Vec::with_capacity(player_count as usize);
+    for i in 0..player_count as isize {
+        let member = session
+            .get_inline_object::<EmptyGameObject>(offsets::MEMBERS + i * offsets::MEMBER_STRIDE);
+        let name = read_c_string(member.as_address() + offsets::MEMBER_NAME as usize);
+        if !name.is_empty() {
+            members.push(name);
+        }
+    }
+
+    Some(SessionInfo {
+        online: player_count > 1,
+        player_count,
+        members,
+        session_id,
+    })
+}
+
+/// 每帧缓存的联机状态
+static ONLINE_STATE: AtomicU8 = AtomicU8::new(STATE_UNKNOWN);
+
+const STATE_UNKNOWN: u8 = 0;
+const STATE_OFFLINE: u8 = 1;
+const STATE_ONLINE: u8 = 2;
+
+fn read_online() -> bool {
+    get_session().is_some_and(|session| session.get_value_copy::<i32>(offsets::MEMBER_COUNT) > 1)
+}
+
+/// 刷新联机状态缓存，每帧开始时调用一次
+pub fn refresh_online_state() {
+    let state = if read_online() {
+        STATE_ONLINE
+    } else {
+        STATE_OFFLINE
+    };
+    ONLINE_STATE.store(state, Ordering::Relaxed);
+}
+
+/// 是否处于多人联机会话
+///
+/// 返回本帧缓存的结果，首次调用前尚未缓存时直接读取。
+pub fn is_online() -> bool {
+    match ONLINE_STATE.load(Ordering::Relaxed) {
+        STATE_ONLINE => true,
+        STATE_OFFLINE => false,
+        _ => read_online(),
+    }
+}
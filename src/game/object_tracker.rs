@@ -0,0 +1,46 @@
+//! 游戏对象存活追踪
+//!
+//! 为被追踪的对象分配代数，对象析构时移除记录。
+//! 弱引用保存地址与代数，地址被复用时代数不同，旧引用仍视为失效。
+
+use std::{
+    collections::HashMap,
+    sync::{
+        LazyLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use parking_lot::Mutex;
+
+#[derive(Default)]
+pub struct ObjectTracker {
+    /// 对象地址 -> 代数
+    objects: Mutex<HashMap<usize, u64>>,
+    next_generation: AtomicU64,
+}
+
+impl ObjectTracker {
+    pub fn instance() -> &'static ObjectTracker {
+        static INSTANCE: LazyLock<ObjectTracker> = LazyLock::new(ObjectTracker::default);
+        &INSTANCE
+    }
+
+    /// 追踪对象，返回对象当前的代数
+    pub fn track(&self, address: usize) -> u64 {
+        *self
+            .objects
+            .lock()
+            .entry(address)
+            .or_insert_with(|| self.next_generation.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    /// 对象析构时调用
+    pub fn invalidate(&self, address: usize) {
+        self.objects.lock().remove(&address);
+    }
+
+    pub fn is_valid(&self, address: usize, generation: u64) -> bool {
+        self.objects.lock().get(&address) == Some(&generation)
+    }
+}
@@ -1,4 +1,7 @@
 use std::ffi::c_void;
+use std::time::Instant;
+
+use parking_lot::Mutex;
 
 use crate::address::AddressRepository;
 
@@ -7,10 +10,58 @@ use crate::static_ref;
 
 static mut HOOK: Option<safetyhook::InlineHook> = None;
 static mut CALLBACK: Option<Box<dyn Fn() + Send + 'static>> = None;
+static MAP_CLOCK: Mutex<MapClock> = Mutex::new(MapClock::new());
+
+/// 游戏内地图时钟统计
+///
+/// 由 Core::MapClockLocal 每次调用的增量累计得到。
+#[derive(Debug, Clone, Copy)]
+pub struct MapClock {
+    /// 累计的游戏时间
+    pub game_time: f64,
+    /// 游戏时间与现实时间（秒）的比例
+    pub scale: f64,
+    last_tick: Option<Instant>,
+}
+
+impl MapClock {
+    const fn new() -> Self {
+        Self {
+            game_time: 0.0,
+            scale: 0.0,
+            last_tick: None,
+        }
+    }
+
+    fn tick(&mut self, delta: f32) {
+        let now = Instant::now();
+        self.game_time += delta as f64;
+
+        if let Some(last_tick) = self.last_tick.replace(now) {
+            let real_delta = now.duration_since(last_tick).as_secs_f64();
+            if real_delta > 0.0 {
+                // 指数平滑，避免单帧波动
+                let ratio = delta as f64 / real_delta;
+                self.scale = if self.scale == 0.0 {
+                    ratio
+                } else {
+                    self.scale * 0.95 + ratio * 0.05
+                };
+            }
+        }
+    }
+}
+
+/// 获取当前地图时钟统计
+pub fn map_clock() -> MapClock {
+    *MAP_CLOCK.lock()
+}
 
 type MapClockLocalFn = unsafe extern "C" fn(*const c_void, f32);
 
 unsafe extern "C" fn map_clock_local_hooked(a1: *const c_void, a2: f32) {
+    MAP_CLOCK.lock().tick(a2);
+
     unsafe {
         if let Some(callback) = static_ref!(CALLBACK).as_ref() {
             callback();
@@ -0,0 +1,162 @@
+//! 玩家（猎人）数据
+//!
+//! 当前玩家从单例 `sPlayer` 获取，未进入游戏时不存在，此时读取接口返回 None。
+
+use crate::{
+    error::{Error, Result},
+    game::{
+        mt_type::{EmptyGameObject, GameObject, GameObjectExt, MtVector3},
+        singleton::SingletonManager,
+    },
+    memory::MemoryUtils,
+};
+
+const PLAYER_SINGLETON: &str = "sPlayer";
+
+/// 玩家对象成员偏移
+mod offsets {
+    /// 当前玩家，相对于 sPlayer
+    pub const CURRENT_PLAYER: isize = 0x80;
+    /// 坐标
+    pub const POSITION: isize = 0x160;
+    /// 状态组件
+    pub const STATUS: isize = 0x7630;
+    /// 生命值最大值，相对于状态组件
+    pub const HEALTH_MAX: isize = 0x60;
+    /// 生命值当前值，相对于状态组件
+    pub const HEALTH: isize = 0x64;
+    /// 耐力当前值，相对于状态组件
+    pub const STAMINA: isize = 0x13C;
+    /// 耐力最大值，相对于状态组件
+    pub const STAMINA_MAX: isize = 0x144;
+    /// 武器组件
+    pub const WEAPON: isize = 0x76B0;
+    /// 武器类型，相对于武器组件
+    pub const WEAPON_TYPE: isize = 0x2E8;
+    /// 武器 ID，相对于武器组件
+    pub const WEAPON_ID: isize = 0x2EC;
+    /// 行为控制器（inline）
+    pub const ACTION_CONTROLLER: isize = 0x468;
+    /// 当前行为 ID，相对于行为控制器
+    pub const ACTION_ID: isize = 0xAC;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WeaponInfo {
+    pub weapon_type: i32,
+    pub weapon_id: i32,
+}
+
+/// 获取当前玩家对象
+pub fn get_player() -> Option<EmptyGameObject> {
+    let singleton = SingletonManager::instance().get_address(PLAYER_SINGLETON)?;
+    let player = read_pointer(singleton, offsets::CURRENT_PLAYER)?;
+    Some(EmptyGameObject::from_address(player))
+}
+
+pub fn position() -> Option<MtVector3> {
+    let player = get_player()?;
+    Some(player.get_value_copy::<MtVector3>(offsets::POSITION))
+}
+
+/// 返回当前值和最大值
+pub fn health() -> Option<(f32, f32)> {
+    let status = status()?;
+    Some((
+        status.get_value_copy::<f32>(offsets::HEALTH),
+        status.get_value_copy::<f32>(offsets::HEALTH_MAX),
+    ))
+}
+
+/// 返回当前值和最大值
+pub fn stamina() -> Option<(f32, f32)> {
+    let status = status()?;
+    Some((
+        status.get_value_copy::<f32>(offsets::STAMINA),
+        status.get_value_copy::<f32>(offsets::STAMINA_MAX),
+    ))
+}
+
+pub fn weapon() -> Option<WeaponInfo> {
+    let player = get_player()?;
+    let weapon = read_pointer(player.as_address(), offsets::WEAPON)?;
+    let weapon = EmptyGameObject::from_address(weapon);
+    Some(WeaponInfo {
+        weapon_type: weapon.get_value_copy::<i32>(offsets::WEAPON_TYPE),
+        weapon_id: weapon.get_value_copy::<i32>(offsets::WEAPON_ID),
+    })
+}
+
+pub fn action_id() -> Option<i32> {
+    let player = get_player()?;
+    Some(player.get_value_copy::<i32>(offsets::ACTION_CONTROLLER + offsets::ACTION_ID))
+}
+
+/// 设置坐标
+pub fn set_position(position: MtVector3) -> Result<()> {
+    let player = get_player().ok_or(Error::SingletonNotFound(PLAYER_SINGLETON.to_string()))?;
+    *player.get_value_mut::<MtVector3>(offsets::POSITION) = position;
+    Ok(())
+}
+
+/// 设置生命值，截断到 [0, 最大值]
+pub fn set_health(value: f32) -> Result<()> {
+    let status = status().ok_or(Error::SingletonNotFound(PLAYER_SINGLETON.to_string()))?;
+    let max = status.get_value_copy::<f32>(offsets::HEALTH_MAX);
+    *status.get_value_mut::<f32>(offsets::HEALTH) = clamp_to_max(value, max)?;
+    Ok(())
+}
+
+/// 设置耐力，截断到 [0, 最大值]
+pub fn set_stamina(value: f32) -> Result<()> {
+    let status = status().ok_or(Error::SingletonNotFound(PLAYER_SINGLETON.to_string()))?;
+    let max = status.get_value_copy::<f32>(offsets::STAMINA_MAX);
+    *status.get_value_mut::<f32>(offsets::STAMINA) = clamp_to_max(value, max)?;
+    Ok(())
+}
+
+/// 截断到 [0, max]，NaN 视为 0
+///
+/// 最大值从游戏内存读取，状态组件未初始化时可能不是有效值，此时返回错误而不是写入。
+fn clamp_to_max(value: f32, max: f32) -> Result<f32> {
+    if !max.is_finite() || max < 0.0 {
+        return Err(Error::InvalidValue(
+            "finite non-negative maximum",
+            max.to_string(),
+        ));
+    }
+    Ok(value.max(0.0).min(max))
+}
+
+fn status() -> Option<EmptyGameObject> {
+    let player = get_player()?;
+    let status = read_pointer(player.as_address(), offsets::STATUS)?;
+    Some(EmptyGameObject::from_address(status))
+}
+
+/// 读取对象中的指针成员，对象不可读或指针为空时返回 None
+fn read_pointer(object: usize, offset: isize) -> Option<usize> {
+    let field = (object as isize + offset) as usize;
+    MemoryUtils::check_permission_read(field).ok()?;
+    let pointer = unsafe { *(field as *const usize) };
+    if pointer == 0 || MemoryUtils::check_permission_read(pointer).is_err() {
+        return None;
+    }
+    Some(pointer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamp_to_max() {
+        assert_eq!(clamp_to_max(50.0, 100.0).unwrap(), 50.0);
+        assert_eq!(clamp_to_max(150.0, 100.0).unwrap(), 100.0);
+        assert_eq!(clamp_to_max(-1.0, 100.0).unwrap(), 0.0);
+        assert_eq!(clamp_to_max(f32::NAN, 100.0).unwrap(), 0.0);
+        assert!(clamp_to_max(50.0, f32::NAN).is_err());
+        assert!(clamp_to_max(50.0, -1.0).is_err());
+        assert!(clamp_to_max(50.0, f32::INFINITY).is_err());
+    }
+}
@@ -0,0 +1,142 @@
+//! 任务状态
+//!
+//! Hook 任务状态切换函数，将开始、结束和返回据点转换为事件，在下一帧游戏主线程中分发。
+
+use std::{ffi::c_void, sync::LazyLock};
+
+use parking_lot::Mutex;
+use safetyhook::InlineHook;
+
+use crate::{
+    address::AddressRepository,
+    error::Error,
+    game::{
+        mt_type::{EmptyGameObject, GameObject, GameObjectExt},
+        singleton::SingletonManager,
+    },
+    memory::MemoryUtils,
+    static_mut, static_ref,
+};
+
+const QUEST_SINGLETON: &str = "sQuest";
+
+static mut STATE_HOOK: Option<InlineHook> = None;
+static EVENTS: LazyLock<Mutex<Vec<QuestEvent>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+type SetStateFn = unsafe extern "C" fn(*mut c_void, u32);
+
+/// sQuest 成员偏移
+mod offsets {
+    /// 任务状态
+    pub const STATE: isize = 0x38;
+    /// 当前任务 ID，不在任务中时为 -1
+    pub const QUEST_ID: isize = 0x4C;
+    /// 任务已进行的时间（秒）
+    pub const ELAPSED_TIME: isize = 0x13198;
+    /// 任务时间限制（秒）
+    pub const TIME_LIMIT: isize = 0x1319C;
+}
+
+/// 任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::AsRefStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum QuestState {
+    None,
+    Active,
+    Complete,
+    Failed,
+    Returning,
+    Unknown,
+}
+
+impl QuestState {
+    fn from_raw(value: u32) -> Self {
+        match value {
+            0 => QuestState::None,
+            2 => QuestState::Active,
+            3 => QuestState::Complete,
+            5 => QuestState::Failed,
+            6 | 7 => QuestState::Returning,
+            _ => QuestState::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum QuestEvent {
+    Start(i32),
+    /// 任务结束，参数为任务 ID 和是否成功
+    Complete(i32, bool),
+    /// 返回据点
+    Return(i32),
+}
+
+unsafe extern "C" fn set_state_hook(quest: *mut c_void, state: u32) {
+    let object = EmptyGameObject::from_ptr(quest);
+    let previous = QuestState::from_raw(object.get_value_copy::<u32>(offsets::STATE));
+    let quest_id = object.get_value_copy::<i32>(offsets::QUEST_ID);
+
+    unsafe {
+        let original: SetStateFn =
+            std::mem::transmute(static_ref!(STATE_HOOK).as_ref().unwrap().original());
+        original(quest, state);
+    }
+
+    let event = match (previous, QuestState::from_raw(state)) {
+        (previous, current) if previous == current => None,
+        (_, QuestState::Active) => Some(QuestEvent::Start(quest_id)),
+        (_, QuestState::Complete) => Some(QuestEvent::Complete(quest_id, true)),
+        (_, QuestState::Failed) => Some(QuestEvent::Complete(quest_id, false)),
+        (QuestState::None, QuestState::Returning) => None,
+        (_, QuestState::Returning) => Some(QuestEvent::Return(quest_id)),
+        _ => None,
+    };
+    if let Some(event) = event {
+        EVENTS.lock().push(event);
+    }
+}
+
+pub fn init_hooks() -> Result<(), Error> {
+    let set_state = AddressRepository::instance().get_ptr(AddressRepository::QUEST_SET_STATE)?;
+    unsafe {
+        static_mut!(STATE_HOOK).replace(safetyhook::create_inline(set_state, set_state_hook as _)?);
+    }
+
+    Ok(())
+}
+
+/// 取出等待分发的事件
+pub fn take_events() -> Vec<QuestEvent> {
+    std::mem::take(&mut *EVENTS.lock())
+}
+
+fn get_quest() -> Option<EmptyGameObject> {
+    let address = SingletonManager::instance().get_address(QUEST_SINGLETON)?;
+    MemoryUtils::check_permission_read(address).ok()?;
+
+    Some(EmptyGameObject::from_address(address))
+}
+
+/// 当前任务 ID，不在任务中时返回 None
+pub fn quest_id() -> Option<i32> {
+    let quest = get_quest()?;
+    let id = quest.get_value_copy::<i32>(offsets::QUEST_ID);
+    (id >= 0).then_some(id)
+}
+
+pub fn state() -> QuestState {
+    get_quest().map_or(QuestState::None, |quest| {
+        QuestState::from_raw(quest.get_value_copy::<u32>(offsets::STATE))
+    })
+}
+
+/// 剩余时间（秒），不在任务中时返回 None
+pub fn remaining_time() -> Option<f32> {
+    if state() != QuestState::Active {
+        return None;
+    }
+    let quest = get_quest()?;
+    let elapsed = quest.get_value_copy::<f32>(offsets::ELAPSED_TIME);
+    let limit = quest.get_value_copy::<f32>(offsets::TIME_LIMIT);
+    Some((limit - elapsed).max(0.0))
+}
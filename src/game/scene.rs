@@ -0,0 +1,35 @@
+//! 过场动画与加载状态检测
+
+use crate::{
+    game::{
+        mt_type::{EmptyGameObject, GameObject, GameObjectExt},
+        singleton::SingletonManager,
+    },
+    memory::MemoryUtils,
+};
+
+mod offsets {
+    /// sMhDemo：当前播放的过场动画对象指针
+    pub const DEMO_PLAYING: isize = 0x60;
+    /// sMhScene：场景切换（加载）状态
+    pub const SCENE_LOADING: isize = 0x1A4;
+}
+
+fn get_singleton(name: &str) -> Option<EmptyGameObject> {
+    let address = SingletonManager::instance().get_address(name)?;
+    MemoryUtils::check_permission_read(address).ok()?;
+
+    Some(EmptyGameObject::from_address(address))
+}
+
+/// 是否正在播放过场动画
+pub fn is_cutscene() -> bool {
+    get_singleton("sMhDemo")
+        .is_some_and(|demo| demo.get_value_copy::<usize>(offsets::DEMO_PLAYING) != 0)
+}
+
+/// 是否正在加载场景
+pub fn is_loading() -> bool {
+    get_singleton("sMhScene")
+        .is_some_and(|scene| scene.get_value_copy::<u32>(offsets::SCENE_LOADING) != 0)
+}
@@ -1,5 +1,4 @@
 use std::{
-    cell::{LazyCell, RefCell},
     collections::{HashMap, HashSet},
     ffi::c_void,
     ptr::addr_of_mut,
@@ -13,26 +12,27 @@ use crate::{
     address::AddressRepository,
     game::mt_type::{EmptyGameObject, GameObjectExt},
     memory::MemoryUtils,
-    static_mut, static_ref,
+    static_mut,
 };
 use crate::{error::Result, game::mt_type::GameObject};
 
 static mut HOOK: Option<InlineHook> = None;
-static mut SINGLETONS_TEMP: LazyCell<RefCell<HashSet<usize>>> =
-    LazyCell::new(|| RefCell::new(HashSet::new()));
+/// 已构造、尚未解析的单例。构造函数可能在任意线程调用
+static SINGLETONS_TEMP: LazyLock<Mutex<HashSet<usize>>> =
+    LazyLock::new(|| Mutex::new(HashSet::new()));
 
 type FuncType = extern "C" fn(*const c_void) -> *const c_void;
 
 unsafe extern "C" fn csystem_ctor_hooked(instance: *const c_void) -> *const c_void {
-    unsafe {
-        static_ref!(SINGLETONS_TEMP)
-            .borrow_mut()
-            .insert(instance as usize);
-
+    let result = unsafe {
         let hook = &mut *addr_of_mut!(HOOK);
         let original: FuncType = std::mem::transmute(hook.as_ref().unwrap().original());
         original(instance)
-    }
+    };
+    // 构造完成后才能读取 DTI
+    SINGLETONS_TEMP.lock().insert(instance as usize);
+
+    result
 }
 
 pub struct SingletonManager {
@@ -59,14 +59,18 @@ impl SingletonManager {
         Ok(())
     }
 
-    /// Parse all singletons registered before.
+    /// Parse all singletons registered before, returns newly parsed singletons.
     ///
-    /// Run it after mhMain ctor.
-    pub fn parse_singletons(&self) {
+    /// Run it after mhMain ctor, and every frame for singletons constructed later.
+    pub fn parse_singletons(&self) -> Vec<(String, usize)> {
+        let temp_singletons = std::mem::take(&mut *SINGLETONS_TEMP.lock());
+        if temp_singletons.is_empty() {
+            return Vec::new();
+        }
         let mut singletons = self.singletons.lock();
-        let mut temp_singletons = unsafe { static_ref!(SINGLETONS_TEMP).borrow_mut() };
+        let mut parsed = Vec::new();
 
-        for addr in temp_singletons.iter().cloned() {
+        for addr in temp_singletons {
             let mt_obj = EmptyGameObject::from_ptr(addr as *mut _);
 
             let Some(dti) = mt_obj.get_dti() else {
@@ -81,11 +85,12 @@ impl SingletonManager {
 
             log::debug!("Found singleton: {} at 0x{:x}", name, addr);
 
-            singletons.insert(name.to_string(), addr);
+            if singletons.insert(name.to_string(), addr) != Some(addr) {
+                parsed.push((name.to_string(), addr));
+            }
         }
 
-        temp_singletons.clear();
-        temp_singletons.shrink_to_fit();
+        parsed
     }
 
     /// 获取单例地址
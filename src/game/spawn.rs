@@ -0,0 +1,79 @@
+//! 物品与环境生物生成
+
+use std::{
+    ffi::c_void,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    address::AddressRepository,
+    error::{Error, Result},
+    game::mt_type::MtVector3,
+};
+
+/// 每个时间窗口内允许的最大生成次数
+const SPAWN_LIMIT_PER_WINDOW: u32 = 20;
+const SPAWN_LIMIT_WINDOW: Duration = Duration::from_secs(1);
+
+static SPAWN_LIMITER: Mutex<Option<(Instant, u32)>> = Mutex::new(None);
+
+type DropItemFn = unsafe extern "C" fn(*const MtVector3, i32, i32) -> *mut c_void;
+type SpawnEndemicLifeFn = unsafe extern "C" fn(*const MtVector3, i32) -> *mut c_void;
+
+/// 检查生成频率限制
+fn acquire_spawn_quota(api_name: &'static str) -> Result<()> {
+    let mut limiter = SPAWN_LIMITER.lock();
+    let now = Instant::now();
+
+    match limiter.as_mut() {
+        Some((start, count)) if now.duration_since(*start) < SPAWN_LIMIT_WINDOW => {
+            if *count >= SPAWN_LIMIT_PER_WINDOW {
+                return Err(Error::RateLimited(api_name));
+            }
+            *count += 1;
+        }
+        _ => {
+            limiter.replace((now, 1));
+        }
+    }
+
+    Ok(())
+}
+
+/// 在指定位置掉落物品，返回生成的对象地址
+pub fn spawn_item(item_id: i32, count: i32, position: MtVector3) -> Result<usize> {
+    if count <= 0 {
+        return Err(Error::InvalidValue(
+            "positive item count",
+            count.to_string(),
+        ));
+    }
+    acquire_spawn_quota("Spawn.item")?;
+
+    let drop_item: DropItemFn = unsafe {
+        std::mem::transmute(
+            AddressRepository::instance()
+                .get_ptr_deferred::<c_void>(AddressRepository::SPAWN_DROP_ITEM)?,
+        )
+    };
+    let object = unsafe { drop_item(&position, item_id, count) };
+
+    Ok(object as usize)
+}
+
+/// 在指定位置生成环境生物，返回生成的对象地址
+pub fn spawn_endemic_life(em_id: i32, position: MtVector3) -> Result<usize> {
+    acquire_spawn_quota("Spawn.endemic_life")?;
+
+    let spawn: SpawnEndemicLifeFn = unsafe {
+        std::mem::transmute(
+            AddressRepository::instance()
+                .get_ptr_deferred::<c_void>(AddressRepository::SPAWN_ENDEMIC_LIFE)?,
+        )
+    };
+    let object = unsafe { spawn(&position, em_id) };
+
+    Ok(object as usize)
+}
@@ -0,0 +1,35 @@
+//! 游戏主线程标记
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use windows::Win32::System::Threading::GetCurrentThreadId;
+
+/// 0 表示尚未标记
+static GAME_THREAD_ID: AtomicU32 = AtomicU32::new(0);
+
+/// 将当前线程标记为游戏主线程
+pub fn mark_game_thread() {
+    let id = current_thread_id();
+    let old = GAME_THREAD_ID.swap(id, Ordering::Relaxed);
+    if old != 0 && old != id {
+        log::warn!("Game thread id changed: {} -> {}", old, id);
+    }
+}
+
+/// 当前线程 ID
+pub fn current_thread_id() -> u32 {
+    unsafe { GetCurrentThreadId() }
+}
+
+/// 游戏主线程 ID，未初始化时返回 None
+pub fn game_thread_id() -> Option<u32> {
+    match GAME_THREAD_ID.load(Ordering::Relaxed) {
+        0 => None,
+        id => Some(id),
+    }
+}
+
+/// 当前线程是否为游戏主线程
+pub fn is_game_thread() -> bool {
+    game_thread_id() == Some(current_thread_id())
+}
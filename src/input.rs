@@ -1,8 +1,10 @@
 //! 键盘，鼠标，手柄等输入设备按键管理
 
-use std::{ffi::c_void, mem::MaybeUninit};
+use std::{ffi::c_void, mem::MaybeUninit, time::Duration};
 
 pub use luaf_include::{ControllerButton, KeyCode};
+use parking_lot::Mutex;
+use strum::IntoEnumIterator;
 
 use crate::error::{Error, Result};
 use crate::game::{
@@ -10,6 +12,15 @@ use crate::game::{
     singleton::SingletonManager,
 };
 use crate::static_ref;
+use mouse::Mouse;
+
+pub mod axis;
+pub mod hotkey;
+mod inject;
+pub mod layout;
+pub mod mouse;
+pub mod remap;
+mod repeat;
 
 static mut INPUT: Option<Input> = None;
 
@@ -17,6 +28,9 @@ static mut INPUT: Option<Input> = None;
 pub struct Input {
     keyboard: Keyboard,
     controller: Controller,
+    mouse: Mouse,
+    injector: Mutex<inject::Injector>,
+    repeat: Mutex<repeat::KeyRepeat>,
 }
 
 impl Input {
@@ -33,6 +47,9 @@ impl Input {
             INPUT = Some(Self {
                 keyboard: Keyboard::from_ptr(keyboard),
                 controller: Controller::from_ptr(controller),
+                mouse: Mouse::default(),
+                injector: Mutex::new(inject::Injector::default()),
+                repeat: Mutex::new(repeat::KeyRepeat::default()),
             });
         }
 
@@ -49,6 +66,11 @@ impl Input {
         }
     }
 
+    /// 未初始化时返回 None
+    pub fn try_instance() -> Option<&'static Input> {
+        unsafe { static_ref!(INPUT).as_ref() }
+    }
+
     pub fn keyboard(&self) -> &Keyboard {
         &self.keyboard
     }
@@ -56,6 +78,109 @@ impl Input {
     pub fn controller(&self) -> &Controller {
         &self.controller
     }
+
+    pub fn mouse(&self) -> &Mouse {
+        &self.mouse
+    }
+
+    /// 注入键盘按键状态，按下的按键保持按下直到释放或脚本卸载
+    pub fn send_key(&self, key: KeyCode, down: bool, script_name: &str) -> Result<()> {
+        Self::check_injection_allowed()?;
+        self.injector.lock().set_key(key, down, script_name);
+        Ok(())
+    }
+
+    /// 注入文本输入，每帧输入一步，返回加入队列的字符数
+    pub fn send_text(&self, text: &str, script_name: &str) -> Result<usize> {
+        Self::check_injection_allowed()?;
+        Ok(self.injector.lock().push_text(text, script_name))
+    }
+
+    /// 释放脚本注入的按键，在脚本卸载时调用
+    pub fn release_injected(&self, script_name: &str) {
+        self.injector.lock().release_script(script_name);
+    }
+
+    /// 写入本帧注入的按键状态，需要在游戏主线程调用
+    pub fn apply_injected(&self) {
+        self.injector.lock().apply(&self.keyboard);
+    }
+
+    fn check_injection_allowed() -> Result<()> {
+        if crate::config::Config::global().input.allow_injection {
+            Ok(())
+        } else {
+            Err(Error::InputInjectionDisabled)
+        }
+    }
+
+    /// 按键本帧是否被点击，或按住时重复触发
+    pub fn is_repeated(&self, key: KeyCode) -> bool {
+        self.keyboard.is_pressed(key) || self.repeat.lock().is_repeated(key)
+    }
+
+    /// 收集本帧状态发生变化的按键，以及按住时重复触发的按键
+    ///
+    /// 每帧调用一次，同时更新鼠标状态。
+    pub fn poll_events(&self) -> Vec<InputEvent> {
+        let mut events = Vec::new();
+        self.mouse.update();
+
+        let (delay, interval) = {
+            let config = crate::config::Config::global();
+            (
+                Duration::from_millis(config.input.repeat_delay_ms as u64),
+                Duration::from_millis(config.input.repeat_interval_ms as u64),
+            )
+        };
+        let repeated = self.repeat.lock().update(
+            std::time::Instant::now(),
+            |key| self.keyboard.is_down(key),
+            delay,
+            interval,
+        );
+        events.extend(
+            repeated
+                .into_iter()
+                .map(|key| InputEvent::KeyRepeat { key }),
+        );
+
+        for key in KeyCode::iter() {
+            if self.keyboard.is_changed(key) {
+                events.push(InputEvent::Key {
+                    key,
+                    down: self.keyboard.is_down(key),
+                });
+            }
+        }
+        for button in ControllerButton::iter() {
+            if self.controller.is_changed(button) {
+                events.push(InputEvent::Button {
+                    button,
+                    down: self.controller.is_down(button),
+                });
+            }
+        }
+
+        events
+    }
+}
+
+/// 按键状态变化事件
+#[derive(Debug, Clone, Copy)]
+pub enum InputEvent {
+    Key {
+        key: KeyCode,
+        down: bool,
+    },
+    /// 按住按键时重复触发
+    KeyRepeat {
+        key: KeyCode,
+    },
+    Button {
+        button: ControllerButton,
+        down: bool,
+    },
 }
 
 /// sMhSteamController singleton
@@ -124,9 +249,18 @@ impl Controller {
 }
 
 /// sMhKeyboard singleton
+/// sMhKeyboard 成员偏移
+mod keyboard_offsets {
+    /// 虚拟键码表
+    pub const VK_TABLE: isize = 0x38;
+    /// 按键状态
+    pub const STATE: isize = 0x138;
+}
+
 pub struct Keyboard {
     ptr: *mut c_void,
-    state: &'static KeyboardState,
+    /// 游戏每帧写入，注入时由框架写入，因此只通过裸指针访问
+    state: *mut KeyboardState,
     vk_table: &'static [u8; 256],
 }
 
@@ -135,21 +269,16 @@ unsafe impl Sync for Keyboard {}
 
 impl GameObject for Keyboard {
     fn from_ptr(ptr: *mut c_void) -> Self {
-        let dummy_state: *const KeyboardState = std::ptr::null();
         let dummy_vk: *const [u8; 256] = std::ptr::null();
 
         let mut this = Self {
             ptr,
-            state: unsafe { &*dummy_state },
+            state: (ptr as *mut u8).wrapping_offset(keyboard_offsets::STATE) as *mut KeyboardState,
             vk_table: unsafe { &*dummy_vk },
         };
 
         // cache pointers
-        let state = this.get_value_ref::<KeyboardState>(0x138);
-        let vk_table = this.get_value_ref::<[u8; 256]>(0x38);
-
-        this.state = state;
-        this.vk_table = vk_table;
+        this.vk_table = this.get_value_ref::<[u8; 256]>(keyboard_offsets::VK_TABLE);
 
         this
     }
@@ -161,23 +290,56 @@ impl GameObject for Keyboard {
 
 impl Keyboard {
     pub fn is_down(&self, key: KeyCode) -> bool {
-        let vk = self.vk_table[key as usize];
-        self.state.on[(vk >> 5) as usize] & (1u32 << (vk & 0x1F)) != 0
+        self.state_bit(key, unsafe { &raw const (*self.state).on })
     }
 
     pub fn is_pressed(&self, key: KeyCode) -> bool {
-        let vk = self.vk_table[key as usize];
-        self.state.trg[(vk >> 5) as usize] & (1u32 << (vk & 0x1F)) != 0
+        self.state_bit(key, unsafe { &raw const (*self.state).trg })
     }
 
     pub fn is_released(&self, key: KeyCode) -> bool {
-        let vk = self.vk_table[key as usize];
-        self.state.rel[(vk >> 5) as usize] & (1u32 << (vk & 0x1F)) != 0
+        self.state_bit(key, unsafe { &raw const (*self.state).rel })
     }
 
     pub fn is_changed(&self, key: KeyCode) -> bool {
+        self.state_bit(key, unsafe { &raw const (*self.state).chg })
+    }
+
+    fn state_bit(&self, key: KeyCode, bits: *const [u32; 8]) -> bool {
+        let vk = self.vk_table[key as usize];
+        let word = unsafe {
+            (bits as *const u32)
+                .add((vk >> 5) as usize)
+                .read_unaligned()
+        };
+        word & (1u32 << (vk & 0x1F)) != 0
+    }
+
+    /// 写入按键状态，与当前状态相同时不做修改
+    pub fn inject(&self, key: KeyCode, down: bool) {
+        if self.is_down(key) == down {
+            return;
+        }
         let vk = self.vk_table[key as usize];
-        self.state.chg[(vk >> 5) as usize] & (1u32 << (vk & 0x1F)) != 0
+        let index = (vk >> 5) as usize;
+        let mask = 1u32 << (vk & 0x1F);
+
+        let state = self.state;
+        unsafe {
+            set_state_bit(&raw mut (*state).on, index, mask, down);
+            set_state_bit(&raw mut (*state).trg, index, mask, down);
+            set_state_bit(&raw mut (*state).rel, index, mask, !down);
+            set_state_bit(&raw mut (*state).chg, index, mask, true);
+        }
+    }
+}
+
+/// KeyboardState 为紧凑布局，按非对齐方式读写
+unsafe fn set_state_bit(bits: *mut [u32; 8], index: usize, mask: u32, value: bool) {
+    unsafe {
+        let word = (bits as *mut u32).add(index);
+        let old = word.read_unaligned();
+        word.write_unaligned(if value { old | mask } else { old & !mask });
     }
 }
 
@@ -0,0 +1,72 @@
+//! 手柄摇杆与扳机
+//!
+//! 游戏手柄单例中只有数字按键状态，模拟量通过 XInput 读取，因此仅支持 XInput 手柄。
+//! 读取的值已应用设置中的死区，并重新缩放到 0~1，死区边缘不会产生跳变。
+
+use windows::Win32::UI::Input::XboxController::{XINPUT_STATE, XInputGetState};
+
+use crate::config::Config;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum Side {
+    Left,
+    Right,
+}
+
+fn get_state(user_index: u32) -> Option<XINPUT_STATE> {
+    let mut state = XINPUT_STATE::default();
+    // ERROR_SUCCESS
+    if unsafe { XInputGetState(user_index, &mut state) } != 0 {
+        return None;
+    }
+    Some(state)
+}
+
+/// 读取摇杆位置，范围 -1~1，手柄未连接时返回 `None`
+pub fn stick(user_index: u32, side: Side) -> Option<(f32, f32)> {
+    let gamepad = get_state(user_index)?.Gamepad;
+    let (x, y) = match side {
+        Side::Left => (gamepad.sThumbLX, gamepad.sThumbLY),
+        Side::Right => (gamepad.sThumbRX, gamepad.sThumbRY),
+    };
+    let dead_zone = Config::global().input.stick_dead_zone;
+    Some(apply_radial_dead_zone(
+        normalize_thumb(x),
+        normalize_thumb(y),
+        dead_zone,
+    ))
+}
+
+/// 读取扳机位置，范围 0~1，手柄未连接时返回 `None`
+pub fn trigger(user_index: u32, side: Side) -> Option<f32> {
+    let gamepad = get_state(user_index)?.Gamepad;
+    let value = match side {
+        Side::Left => gamepad.bLeftTrigger,
+        Side::Right => gamepad.bRightTrigger,
+    };
+    let dead_zone = Config::global().input.trigger_dead_zone;
+    Some(apply_dead_zone(value as f32 / u8::MAX as f32, dead_zone))
+}
+
+fn normalize_thumb(value: i16) -> f32 {
+    (value as f32 / i16::MAX as f32).clamp(-1.0, 1.0)
+}
+
+/// 径向死区，按摇杆偏移距离判断，保持方向不变
+pub fn apply_radial_dead_zone(x: f32, y: f32, dead_zone: f32) -> (f32, f32) {
+    let magnitude = (x * x + y * y).sqrt();
+    if magnitude <= dead_zone || dead_zone >= 1.0 {
+        return (0.0, 0.0);
+    }
+    let scaled = ((magnitude - dead_zone) / (1.0 - dead_zone)).min(1.0);
+    (x / magnitude * scaled, y / magnitude * scaled)
+}
+
+/// 单轴死区
+pub fn apply_dead_zone(value: f32, dead_zone: f32) -> f32 {
+    if value.abs() <= dead_zone || dead_zone >= 1.0 {
+        return 0.0;
+    }
+    ((value.abs() - dead_zone) / (1.0 - dead_zone)).min(1.0) * value.signum()
+}
@@ -0,0 +1,229 @@
+//! 脚本快捷键绑定
+//!
+//! 脚本注册命名的组合键，由框架统一判定触发和检测冲突。组合键中的修饰键必须按住，
+//! 其他按键本帧按下时触发；按住了组合键之外的修饰键时不触发，避免 Ctrl+H 与 H 同时触发。
+//! 用户在界面中修改的绑定按脚本名保存到配置，脚本重新注册时使用已保存的按键。
+
+use std::sync::{
+    LazyLock,
+    atomic::{AtomicBool, Ordering},
+};
+
+use parking_lot::Mutex;
+
+use super::{KeyCode, Keyboard};
+use crate::config::Config;
+
+const MODIFIERS: [KeyCode; 6] = [
+    KeyCode::LeftControl,
+    KeyCode::RightControl,
+    KeyCode::LeftShift,
+    KeyCode::RightShift,
+    KeyCode::LeftAlt,
+    KeyCode::RightAlt,
+];
+
+pub fn is_modifier(key: KeyCode) -> bool {
+    MODIFIERS.contains(&key)
+}
+
+#[derive(Debug, Clone)]
+pub struct Binding {
+    pub script: String,
+    pub name: String,
+    pub default_keys: Vec<KeyCode>,
+    pub keys: Vec<KeyCode>,
+}
+
+pub struct HotkeyManager {
+    bindings: Mutex<Vec<Binding>>,
+    /// 界面正在录制按键时不触发快捷键
+    capturing: AtomicBool,
+}
+
+impl HotkeyManager {
+    pub fn instance() -> &'static HotkeyManager {
+        static INSTANCE: LazyLock<HotkeyManager> = LazyLock::new(|| HotkeyManager {
+            bindings: Mutex::new(Vec::new()),
+            capturing: AtomicBool::new(false),
+        });
+        &INSTANCE
+    }
+
+    /// 注册快捷键，已保存用户绑定时使用保存的按键，返回实际生效的按键
+    pub fn register(&self, script: &str, name: &str, default_keys: Vec<KeyCode>) -> Vec<KeyCode> {
+        let keys = Config::global()
+            .input
+            .hotkeys
+            .get(script)
+            .and_then(|bindings| bindings.get(name))
+            .cloned()
+            .unwrap_or_else(|| default_keys.clone());
+
+        let mut bindings = self.bindings.lock();
+        bindings.retain(|b| b.script != script || b.name != name);
+        for other in bindings.iter().filter(|b| is_same_chord(&b.keys, &keys)) {
+            log::warn!(
+                "Hotkey '{}' of '{}' conflicts with '{}' of '{}'",
+                name,
+                script,
+                other.name,
+                other.script
+            );
+        }
+        bindings.push(Binding {
+            script: script.to_string(),
+            name: name.to_string(),
+            default_keys,
+            keys: keys.clone(),
+        });
+
+        keys
+    }
+
+    pub fn unregister(&self, script: &str, name: &str) -> bool {
+        let mut bindings = self.bindings.lock();
+        let len = bindings.len();
+        bindings.retain(|b| b.script != script || b.name != name);
+        bindings.len() != len
+    }
+
+    /// 移除脚本的所有快捷键，已保存的用户绑定保留
+    pub fn unregister_script(&self, script: &str) {
+        self.bindings.lock().retain(|b| b.script != script);
+    }
+
+    pub fn keys(&self, script: &str, name: &str) -> Option<Vec<KeyCode>> {
+        self.bindings
+            .lock()
+            .iter()
+            .find(|b| b.script == script && b.name == name)
+            .map(|b| b.keys.clone())
+    }
+
+    pub fn bindings(&self) -> Vec<Binding> {
+        self.bindings.lock().clone()
+    }
+
+    /// 修改绑定并保存到配置
+    pub fn rebind(&self, script: &str, name: &str, keys: Vec<KeyCode>) {
+        let mut bindings = self.bindings.lock();
+        let Some(binding) = bindings
+            .iter_mut()
+            .find(|b| b.script == script && b.name == name)
+        else {
+            return;
+        };
+        binding.keys = keys.clone();
+
+        let mut config = Config::global_mut();
+        let script_bindings = config.input.hotkeys.entry(script.to_string()).or_default();
+        if keys == binding.default_keys {
+            script_bindings.remove(name);
+        } else {
+            script_bindings.insert(name.to_string(), keys);
+        }
+        if script_bindings.is_empty() {
+            config.input.hotkeys.remove(script);
+        }
+    }
+
+    /// 与指定绑定使用相同组合键的其他绑定
+    pub fn conflicts(&self, script: &str, name: &str) -> Vec<(String, String)> {
+        let bindings = self.bindings.lock();
+        let Some(binding) = bindings
+            .iter()
+            .find(|b| b.script == script && b.name == name)
+        else {
+            return Vec::new();
+        };
+        bindings
+            .iter()
+            .filter(|b| !(b.script == script && b.name == name))
+            .filter(|b| is_same_chord(&b.keys, &binding.keys))
+            .map(|b| (b.script.clone(), b.name.clone()))
+            .collect()
+    }
+
+    pub fn set_capturing(&self, capturing: bool) {
+        self.capturing.store(capturing, Ordering::Relaxed);
+    }
+
+    /// 本帧触发的快捷键：(脚本名, 绑定名称)
+    pub fn poll_triggered(&self, keyboard: &Keyboard) -> Vec<(String, String)> {
+        if self.capturing.load(Ordering::Relaxed) {
+            return Vec::new();
+        }
+        self.bindings
+            .lock()
+            .iter()
+            .filter(|b| {
+                is_chord_triggered(&b.keys, |k| keyboard.is_down(k), |k| keyboard.is_pressed(k))
+            })
+            .map(|b| (b.script.clone(), b.name.clone()))
+            .collect()
+    }
+}
+
+fn is_same_chord(a: &[KeyCode], b: &[KeyCode]) -> bool {
+    !a.is_empty() && a.len() == b.len() && a.iter().all(|k| b.contains(k))
+}
+
+/// 组合键按键全部按下，其中非修饰键本帧按下，且没有按住组合键之外的修饰键
+fn is_chord_triggered(
+    chord: &[KeyCode],
+    is_down: impl Fn(KeyCode) -> bool,
+    is_pressed: impl Fn(KeyCode) -> bool,
+) -> bool {
+    if chord.is_empty() || !chord.iter().all(|&k| is_down(k)) {
+        return false;
+    }
+    if MODIFIERS.iter().any(|m| !chord.contains(m) && is_down(*m)) {
+        return false;
+    }
+    // 只有修饰键的组合在任一按键按下时触发
+    let mut triggers = chord.iter().filter(|k| !is_modifier(**k)).peekable();
+    if triggers.peek().is_none() {
+        return chord.iter().any(|&k| is_pressed(k));
+    }
+    triggers.any(|&k| is_pressed(k))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chord_modifiers() {
+        let chord = [KeyCode::LeftControl, KeyCode::H];
+
+        let down = |keys: &'static [KeyCode]| move |k| keys.contains(&k);
+        let pressed_h = |k| k == KeyCode::H;
+
+        assert!(is_chord_triggered(
+            &chord,
+            down(&[KeyCode::LeftControl, KeyCode::H]),
+            pressed_h
+        ));
+        // 缺少修饰键
+        assert!(!is_chord_triggered(&chord, down(&[KeyCode::H]), pressed_h));
+        // 多余的修饰键
+        assert!(!is_chord_triggered(
+            &chord,
+            down(&[KeyCode::LeftControl, KeyCode::LeftShift, KeyCode::H]),
+            pressed_h
+        ));
+        // 单独的 H 不会在按住 Ctrl 时触发
+        assert!(!is_chord_triggered(
+            &[KeyCode::H],
+            down(&[KeyCode::LeftControl, KeyCode::H]),
+            pressed_h
+        ));
+        // 修饰键先按住，按键不是本帧按下
+        assert!(!is_chord_triggered(
+            &chord,
+            down(&[KeyCode::LeftControl, KeyCode::H]),
+            |_| false
+        ));
+    }
+}
@@ -0,0 +1,83 @@
+//! 键盘输入注入
+//!
+//! 直接写入游戏键盘单例的按键状态，不经过系统输入，游戏窗口不在前台时同样有效。
+//! 注入的状态在每帧 on_update 前写入：按住的按键每帧重新写入，直到脚本释放；
+//! 文本按字符拆分为按下和释放，每帧处理一步。
+//!
+//! 按键和文本记录发起的脚本，脚本卸载时释放其仍按住的按键。
+
+use std::collections::{HashMap, VecDeque};
+
+use super::{KeyCode, Keyboard, layout};
+
+#[derive(Debug, Default)]
+pub struct Injector {
+    /// 脚本按住的按键：按键 -> 脚本名称
+    held: HashMap<KeyCode, String>,
+    /// 待释放的按键
+    released: Vec<KeyCode>,
+    /// 文本输入队列：(按键, 是否按下, 脚本名称)
+    queue: VecDeque<(KeyCode, bool, String)>,
+}
+
+impl Injector {
+    pub fn set_key(&mut self, key: KeyCode, down: bool, owner: &str) {
+        if down {
+            self.held.insert(key, owner.to_string());
+        } else {
+            self.held.remove(&key);
+            self.released.push(key);
+        }
+    }
+
+    /// 将文本加入输入队列，返回加入的字符数，无法输入的字符被跳过
+    pub fn push_text(&mut self, text: &str, owner: &str) -> usize {
+        let mut count = 0;
+        for ch in text.chars() {
+            let Some((key, shift)) = layout::from_char(ch) else {
+                log::debug!("Cannot inject character {:?} with current layout", ch);
+                continue;
+            };
+            if shift {
+                self.queue
+                    .push_back((KeyCode::LeftShift, true, owner.to_string()));
+            }
+            self.queue.push_back((key, true, owner.to_string()));
+            self.queue.push_back((key, false, owner.to_string()));
+            if shift {
+                self.queue
+                    .push_back((KeyCode::LeftShift, false, owner.to_string()));
+            }
+            count += 1;
+        }
+        count
+    }
+
+    /// 释放脚本按住的按键，并丢弃其未输入的文本
+    pub fn release_script(&mut self, owner: &str) {
+        self.queue.retain(|(_, _, name)| name != owner);
+        let keys = self
+            .held
+            .iter()
+            .filter(|(_, name)| *name == owner)
+            .map(|(key, _)| *key)
+            .collect::<Vec<_>>();
+        for key in keys {
+            self.held.remove(&key);
+            self.released.push(key);
+        }
+    }
+
+    /// 写入本帧的注入状态
+    pub fn apply(&mut self, keyboard: &Keyboard) {
+        if let Some((key, down, owner)) = self.queue.pop_front() {
+            self.set_key(key, down, &owner);
+        }
+        for key in self.released.drain(..) {
+            keyboard.inject(key, false);
+        }
+        for key in self.held.keys() {
+            keyboard.inject(*key, true);
+        }
+    }
+}
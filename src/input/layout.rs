@@ -0,0 +1,88 @@
+//! 键盘布局映射
+//!
+//! KeyCode 基于扫描码，与物理按键位置对应。
+//! 这里通过当前键盘布局，将扫描码转换为虚拟键码和显示名称。
+
+use strum::IntoEnumIterator;
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    GetKeyNameTextW, MAPVK_VK_TO_VSC_EX, MAPVK_VSC_TO_VK_EX, MapVirtualKeyW, VkKeyScanW,
+};
+
+use super::KeyCode;
+
+/// 扩展键扫描码标记（E0 前缀）
+const EXTENDED_FLAG: u32 = 0x80;
+
+fn split_scancode(key: KeyCode) -> (u32, bool) {
+    let code = key as u32;
+    (code & 0x7F, code & EXTENDED_FLAG != 0)
+}
+
+/// 获取按键在当前布局下的虚拟键码，无法映射时返回 None
+pub fn to_virtual_key(key: KeyCode) -> Option<u32> {
+    let (scancode, extended) = split_scancode(key);
+    let scancode = if extended {
+        0xE000 | scancode
+    } else {
+        scancode
+    };
+    let vk = unsafe { MapVirtualKeyW(scancode, MAPVK_VSC_TO_VK_EX) };
+
+    (vk != 0).then_some(vk)
+}
+
+/// 从虚拟键码获取按键
+pub fn from_virtual_key(vk: u32) -> Option<KeyCode> {
+    let scancode = unsafe { MapVirtualKeyW(vk, MAPVK_VK_TO_VSC_EX) };
+    if scancode == 0 {
+        return None;
+    }
+    let extended = scancode & 0xFF00 == 0xE000;
+    let code = (scancode & 0x7F) | if extended { EXTENDED_FLAG } else { 0 };
+
+    KeyCode::from_repr(code)
+}
+
+/// 获取在当前布局下输入字符所需的按键，返回 (按键, 是否需要 Shift)
+///
+/// 需要 Ctrl、Alt 组合或无法输入的字符返回 None。
+pub fn from_char(ch: char) -> Option<(KeyCode, bool)> {
+    let mut units = [0u16; 2];
+    let [unit] = ch.encode_utf16(&mut units) else {
+        return None;
+    };
+    let scan = unsafe { VkKeyScanW(*unit) };
+    if scan == -1 {
+        return None;
+    }
+
+    let vk = (scan & 0xFF) as u32;
+    let modifiers = (scan >> 8) & 0xFF;
+    // 1: Shift，2: Ctrl，4: Alt
+    if modifiers & !1 != 0 {
+        return None;
+    }
+    Some((from_virtual_key(vk)?, modifiers & 1 != 0))
+}
+
+/// 获取按键在当前布局下的显示名称
+///
+/// 无法获取时返回 KeyCode 枚举名称。
+pub fn display_name(key: KeyCode) -> String {
+    let (scancode, extended) = split_scancode(key);
+    let lparam = (scancode << 16) | if extended { 1 << 24 } else { 0 };
+
+    let mut buf = [0u16; 64];
+    let len = unsafe { GetKeyNameTextW(lparam as i32, &mut buf) };
+    if len <= 0 {
+        let name: &'static str = key.into();
+        return name.to_string();
+    }
+
+    String::from_utf16_lossy(&buf[..len as usize])
+}
+
+/// 通过显示名称查找按键，忽略大小写
+pub fn find_by_display_name(name: &str) -> Option<KeyCode> {
+    KeyCode::iter().find(|key| display_name(*key).eq_ignore_ascii_case(name))
+}
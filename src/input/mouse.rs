@@ -0,0 +1,205 @@
+//! 鼠标输入
+//!
+//! 游戏鼠标单例的布局未知，按键和光标位置通过 Win32 读取，每帧更新一次，
+//! 同一帧内读取到的状态一致。滚轮通过窗口消息过滤器累计，游戏窗口不在前台时按键视为松开。
+//!
+//! 首次读取鼠标状态后才开始每帧更新，首次读取滚轮后才注册消息过滤器。
+
+use std::{
+    ffi::c_void,
+    sync::atomic::{AtomicBool, AtomicI32, AtomicIsize, Ordering},
+};
+
+use parking_lot::Mutex;
+use windows::Win32::{
+    Foundation::{HWND, POINT},
+    Graphics::Gdi::ScreenToClient,
+    UI::{
+        Input::KeyboardAndMouse::{
+            GetAsyncKeyState, VIRTUAL_KEY, VK_LBUTTON, VK_MBUTTON, VK_RBUTTON, VK_XBUTTON1,
+            VK_XBUTTON2,
+        },
+        WindowsAndMessaging::{GetCursorPos, GetForegroundWindow, WHEEL_DELTA, WM_MOUSEWHEEL},
+    },
+};
+
+pub use luaf_include::MouseButton;
+
+use crate::extension::window::WindowHook;
+
+/// 按下后移动超过该距离（像素）视为拖动
+const DRAG_THRESHOLD: i32 = 4;
+
+#[derive(Debug, Default)]
+struct MouseState {
+    position: (i32, i32),
+    /// 按键状态，按 `MouseButton` 的值索引位
+    down: u32,
+    previous_down: u32,
+    wheel_delta: f32,
+    /// 按键按下时的光标位置
+    drag_origin: [Option<(i32, i32)>; 5],
+}
+
+#[derive(Debug, Default)]
+pub struct Mouse {
+    state: Mutex<MouseState>,
+    /// 窗口消息中累计的滚轮值，每帧取出
+    pending_wheel: AtomicI32,
+    filter_installed: AtomicBool,
+    /// 是否读取过鼠标状态，未读取时不更新
+    used: AtomicBool,
+    /// 是否读取过滚轮，未读取时不注册消息过滤器
+    wheel_used: AtomicBool,
+    /// 游戏窗口句柄，找到后不再查找
+    hwnd: AtomicIsize,
+}
+
+impl Mouse {
+    /// 读取本帧的鼠标状态，需要在游戏主线程每帧调用一次
+    pub fn update(&self) {
+        if !self.used.load(Ordering::Relaxed) {
+            return;
+        }
+        if self.wheel_used.load(Ordering::Relaxed) {
+            self.install_wheel_filter();
+        }
+
+        let hwnd = self.window();
+        let foreground = hwnd.is_some_and(|hwnd| unsafe { GetForegroundWindow() } == hwnd);
+        let wheel = self.pending_wheel.swap(0, Ordering::Relaxed);
+
+        let mut state = self.state.lock();
+        if let Some(hwnd) = hwnd {
+            let mut point = POINT::default();
+            if unsafe { GetCursorPos(&mut point) }.is_ok()
+                && unsafe { ScreenToClient(hwnd, &mut point) }.as_bool()
+            {
+                state.position = (point.x, point.y);
+            }
+        }
+
+        state.previous_down = state.down;
+        state.down = 0;
+        if foreground {
+            for button in <MouseButton as strum::IntoEnumIterator>::iter() {
+                if is_async_key_down(virtual_key(button)) {
+                    state.down |= 1 << button as u32;
+                }
+            }
+        }
+        state.wheel_delta = if foreground {
+            wheel as f32 / WHEEL_DELTA as f32
+        } else {
+            0.0
+        };
+
+        let position = state.position;
+        for (index, origin) in state.drag_origin.iter_mut().enumerate() {
+            let down = state.down & (1 << index) != 0;
+            let was_down = state.previous_down & (1 << index) != 0;
+            if down && !was_down {
+                *origin = Some(position);
+            } else if !down {
+                *origin = None;
+            }
+        }
+    }
+
+    /// 光标在游戏窗口客户区中的位置
+    pub fn position(&self) -> (i32, i32) {
+        self.state().position
+    }
+
+    pub fn is_down(&self, button: MouseButton) -> bool {
+        self.state().down & (1 << button as u32) != 0
+    }
+
+    pub fn is_pressed(&self, button: MouseButton) -> bool {
+        let state = self.state();
+        let mask = 1 << button as u32;
+        state.down & mask != 0 && state.previous_down & mask == 0
+    }
+
+    pub fn is_released(&self, button: MouseButton) -> bool {
+        let state = self.state();
+        let mask = 1 << button as u32;
+        state.down & mask == 0 && state.previous_down & mask != 0
+    }
+
+    /// 上一帧的滚轮滚动格数，向上为正
+    pub fn wheel_delta(&self) -> f32 {
+        self.wheel_used.store(true, Ordering::Relaxed);
+        self.state().wheel_delta
+    }
+
+    /// 按住按键拖动时，返回相对按下位置的偏移
+    pub fn drag_delta(&self, button: MouseButton) -> Option<(i32, i32)> {
+        let state = self.state();
+        let (x, y) = state.drag_origin[button as usize]?;
+        let (dx, dy) = (state.position.0 - x, state.position.1 - y);
+        (dx.abs() > DRAG_THRESHOLD || dy.abs() > DRAG_THRESHOLD).then_some((dx, dy))
+    }
+
+    /// 读取状态，并标记需要每帧更新
+    fn state(&self) -> parking_lot::MutexGuard<'_, MouseState> {
+        self.used.store(true, Ordering::Relaxed);
+        self.state.lock()
+    }
+
+    /// 游戏窗口句柄，窗口创建前返回 None
+    fn window(&self) -> Option<HWND> {
+        let cached = self.hwnd.load(Ordering::Relaxed);
+        if cached != 0 {
+            return Some(HWND(cached as *mut c_void));
+        }
+        let hwnd = crate::utility::get_game_window_handle().ok()?;
+        self.hwnd.store(hwnd.0 as isize, Ordering::Relaxed);
+        Some(hwnd)
+    }
+
+    /// 注册滚轮消息过滤器，游戏窗口创建前失败时在下一帧重试
+    fn install_wheel_filter(&self) {
+        if self.filter_installed.load(Ordering::Relaxed) {
+            return;
+        }
+        let user_data = self as *const Mouse as usize;
+        match WindowHook::instance().add_filter(wheel_filter, user_data) {
+            Ok(_) => self.filter_installed.store(true, Ordering::Relaxed),
+            Err(e) => log::trace!("Mouse wheel filter not installed yet: {}", e),
+        }
+    }
+}
+
+fn virtual_key(button: MouseButton) -> VIRTUAL_KEY {
+    match button {
+        MouseButton::Left => VK_LBUTTON,
+        MouseButton::Right => VK_RBUTTON,
+        MouseButton::Middle => VK_MBUTTON,
+        MouseButton::X1 => VK_XBUTTON1,
+        MouseButton::X2 => VK_XBUTTON2,
+    }
+}
+
+fn is_async_key_down(vk: VIRTUAL_KEY) -> bool {
+    unsafe { GetAsyncKeyState(vk.0 as i32) as u16 & 0x8000 != 0 }
+}
+
+/// 累计滚轮值，不拦截消息
+unsafe extern "C" fn wheel_filter(
+    _hwnd: *mut c_void,
+    msg: u32,
+    wparam: usize,
+    _lparam: isize,
+    _result: *mut isize,
+    user_data: *mut c_void,
+) -> bool {
+    if msg == WM_MOUSEWHEEL {
+        let mouse = unsafe { &*(user_data as *const Mouse) };
+        let delta = (wparam >> 16) as u16 as i16;
+        mouse
+            .pending_wheel
+            .fetch_add(delta as i32, Ordering::Relaxed);
+    }
+    false
+}
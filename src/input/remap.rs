@@ -0,0 +1,40 @@
+//! 手柄按键重映射
+//!
+//! 将逻辑按键名称（如 `confirm`）映射到物理按键，
+//! 使脚本在不同手柄布局下表现一致。
+
+use luaf_include::ControllerButton;
+
+use crate::config::Config;
+
+pub const CONFIRM: &str = "confirm";
+pub const CANCEL: &str = "cancel";
+
+/// 获取逻辑按键对应的物理按键
+pub fn resolve(name: &str) -> Option<Vec<ControllerButton>> {
+    Config::global().input.controller_map.get(name).cloned()
+}
+
+/// 获取所有逻辑按键映射
+pub fn mappings() -> Vec<(String, Vec<ControllerButton>)> {
+    Config::global()
+        .input
+        .controller_map
+        .iter()
+        .map(|(name, buttons)| (name.clone(), buttons.clone()))
+        .collect()
+}
+
+/// 交换确认键与取消键
+pub fn swap_confirm_cancel() {
+    let mut config = Config::global_mut();
+    let map = &mut config.input.controller_map;
+    let confirm = map.remove(CONFIRM).unwrap_or_default();
+    let cancel = map.remove(CANCEL).unwrap_or_default();
+    if !cancel.is_empty() {
+        map.insert(CONFIRM.to_string(), cancel);
+    }
+    if !confirm.is_empty() {
+        map.insert(CANCEL.to_string(), confirm);
+    }
+}
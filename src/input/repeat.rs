@@ -0,0 +1,86 @@
+//! 按住按键时的重复触发
+//!
+//! 按住按键超过延迟后，每隔固定间隔触发一次，类似文本输入的按键重复。
+//! 延迟和间隔由设置统一配置，脚本无需各自计时。
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use strum::IntoEnumIterator;
+
+use super::KeyCode;
+
+#[derive(Debug, Default)]
+pub struct KeyRepeat {
+    /// 按住的按键及下次触发时间
+    held: HashMap<KeyCode, Instant>,
+    /// 本帧重复触发的按键
+    repeated: HashSet<KeyCode>,
+}
+
+impl KeyRepeat {
+    /// 更新本帧状态，返回本帧重复触发的按键
+    ///
+    /// `interval` 为 0 时不重复触发。
+    pub fn update(
+        &mut self,
+        now: Instant,
+        is_down: impl Fn(KeyCode) -> bool,
+        delay: Duration,
+        interval: Duration,
+    ) -> Vec<KeyCode> {
+        self.repeated.clear();
+
+        for key in KeyCode::iter() {
+            if !is_down(key) {
+                self.held.remove(&key);
+                continue;
+            }
+            let Some(next) = self.held.get_mut(&key) else {
+                self.held.insert(key, now + delay);
+                continue;
+            };
+            if !interval.is_zero() && now >= *next {
+                *next = now + interval;
+                self.repeated.insert(key);
+            }
+        }
+
+        self.repeated.iter().copied().collect()
+    }
+
+    pub fn is_repeated(&self, key: KeyCode) -> bool {
+        self.repeated.contains(&key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeat_after_delay() {
+        let mut repeat = KeyRepeat::default();
+        let delay = Duration::from_millis(400);
+        let interval = Duration::from_millis(50);
+        let start = Instant::now();
+        let is_down = |key| key == KeyCode::A;
+
+        assert!(repeat.update(start, is_down, delay, interval).is_empty());
+        let t = start + Duration::from_millis(399);
+        assert!(repeat.update(t, is_down, delay, interval).is_empty());
+        let t = start + Duration::from_millis(400);
+        assert_eq!(repeat.update(t, is_down, delay, interval), vec![KeyCode::A]);
+        let t = start + Duration::from_millis(420);
+        assert!(repeat.update(t, is_down, delay, interval).is_empty());
+        let t = start + Duration::from_millis(450);
+        assert!(!repeat.update(t, is_down, delay, interval).is_empty());
+
+        // 松开后重新计算延迟
+        repeat.update(t, |_| false, delay, interval);
+        assert!(repeat.update(t, is_down, delay, interval).is_empty());
+        assert!(!repeat.is_repeated(KeyCode::A));
+    }
+}
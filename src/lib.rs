@@ -1,5 +1,7 @@
+#[cfg(not(feature = "embed"))]
 use std::sync::Once;
 
+#[cfg(not(feature = "embed"))]
 use windows::{
     Win32::{
         Foundation::TRUE,
@@ -8,11 +10,15 @@ use windows::{
     core::BOOL,
 };
 
+#[cfg(not(feature = "embed"))]
 static MAIN_THREAD_ONCE: Once = Once::new();
 
 mod address;
+mod autosave;
 mod bootstrap;
 mod config;
+#[cfg(feature = "embed")]
+pub mod embed;
 mod error;
 mod extension;
 mod game;
@@ -20,6 +26,7 @@ mod input;
 mod logger;
 mod luavm;
 mod memory;
+mod profiler;
 mod render_core;
 mod utility;
 
@@ -27,7 +34,10 @@ mod utility;
 mod tests;
 
 fn panic_hook(info: &std::panic::PanicHookInfo) {
-    let msg = format!("LuaFramework panic: {}", info);
+    let msg = match utility::process::cached_fingerprint() {
+        Some(fingerprint) => format!("LuaFramework panic (game build {}): {}", fingerprint, info),
+        None => format!("LuaFramework panic: {}", info),
+    };
     log::error!("{:#}", msg);
     utility::show_error_msgbox(&msg, "LuaFramework Panic");
 }
@@ -41,10 +51,18 @@ fn main_entry() -> anyhow::Result<()> {
     logger::init_logger();
 
     // 初始化hook等资源
-    game::command::init_game_command()?;
     if let Err(e) = game::monster::init_hooks() {
         log::error!("Failed to initialize monster hooks: {:#}", e);
     };
+    if let Err(e) = game::quest::init_hooks() {
+        log::error!("Failed to initialize quest hooks: {:#}", e);
+    };
+    if let Err(e) = game::chat::init_hooks() {
+        log::error!("Failed to initialize chat hooks: {:#}", e);
+    };
+    if let Err(e) = game::damage::init_hooks() {
+        log::error!("Failed to initialize damage hooks: {:#}", e);
+    };
     game::singleton::SingletonManager::instance().initialize()?;
 
     bootstrap::setup()?;
@@ -52,6 +70,7 @@ fn main_entry() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[cfg(not(feature = "embed"))]
 #[unsafe(no_mangle)]
 #[allow(non_snake_case)]
 extern "system" fn DllMain(_: usize, call_reason: u32, _: usize) -> BOOL {
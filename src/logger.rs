@@ -1,3 +1,4 @@
+use std::collections::BTreeSet;
 use std::fs;
 use std::io::Write;
 use std::sync::LazyLock;
@@ -11,17 +12,54 @@ use windows::Win32::{
     Foundation::HANDLE,
     System::Console::{
         AllocConsole, ENABLE_PROCESSED_OUTPUT, ENABLE_VIRTUAL_TERMINAL_PROCESSING,
-        ENABLE_WRAP_AT_EOL_OUTPUT, GetConsoleWindow, GetStdHandle, STD_OUTPUT_HANDLE,
-        SetConsoleMode, WriteConsoleW,
+        ENABLE_WRAP_AT_EOL_OUTPUT, FreeConsole, GetConsoleWindow, GetStdHandle, STD_OUTPUT_HANDLE,
+        SetConsoleMode, SetConsoleTitleW, WriteConsoleW,
     },
+    UI::WindowsAndMessaging::{SWP_NOSIZE, SWP_NOZORDER, SetWindowPos},
 };
+use windows::core::HSTRING;
 
 use crate::config::Config;
 
 static LOG_CONSOLE_SPAWNED: AtomicBool = AtomicBool::new(false);
+/// 用户主动隐藏控制台后，不再因日志输出自动弹出
+static LOG_CONSOLE_SUPPRESSED: AtomicBool = AtomicBool::new(false);
 
 static LOGGER: LazyLock<Logger> = LazyLock::new(Logger::new);
 
+/// 日志频道使用的 target 前缀
+const CHANNEL_TARGET_PREFIX: &str = "luaf_channel::";
+
+static CHANNELS: Mutex<ChannelState> = Mutex::new(ChannelState::new());
+
+/// 日志频道状态
+struct ChannelState {
+    /// 已创建的频道
+    known: BTreeSet<String>,
+    /// 禁用的频道，不输出到任何位置
+    disabled: BTreeSet<String>,
+    /// 不写入日志文件的频道
+    file_excluded: BTreeSet<String>,
+}
+
+impl ChannelState {
+    const fn new() -> Self {
+        Self {
+            known: BTreeSet::new(),
+            disabled: BTreeSet::new(),
+            file_excluded: BTreeSet::new(),
+        }
+    }
+}
+
+/// 日志频道信息
+#[derive(Debug, Clone)]
+pub struct ChannelInfo {
+    pub name: String,
+    pub enabled: bool,
+    pub to_file: bool,
+}
+
 struct LoggerOutput {
     stdout: Option<HANDLE>,
     file: Option<fs::File>,
@@ -29,7 +67,7 @@ struct LoggerOutput {
 
 struct Logger {
     output: Mutex<LoggerOutput>,
-    log_config: crate::config::LogConfig,
+    log_config: Mutex<crate::config::LogConfig>,
 }
 
 unsafe impl Send for Logger {}
@@ -40,12 +78,7 @@ impl Logger {
         let config = Config::global().log.clone();
         let file = if config.log_to_file {
             // try to open log file
-            match fs::OpenOptions::new()
-                .create(true)
-                .truncate(true)
-                .write(true)
-                .open(&config.log_file_path)
-            {
+            match open_log_file(&config.log_file_path, true) {
                 Ok(file) => Some(file),
                 Err(e) => {
                     crate::utility::show_error_msgbox(
@@ -64,12 +97,12 @@ impl Logger {
                 stdout: None, // lazy init
                 file,
             }),
-            log_config: config,
+            log_config: Mutex::new(config),
         }
     }
 
-    pub fn set_stdout_handle(&self, handle: HANDLE) {
-        self.output.lock().stdout = Some(handle);
+    pub fn set_stdout_handle(&self, handle: Option<HANDLE>) {
+        self.output.lock().stdout = handle;
     }
 }
 
@@ -79,12 +112,29 @@ impl log::Log for Logger {
     }
 
     fn log(&self, record: &Record) {
-        if !self.log_config.log_to_console && !self.log_config.log_to_file {
+        let (level, log_to_console, log_to_file) = {
+            let config = self.log_config.lock();
+            (config.level, config.log_to_console, config.log_to_file)
+        };
+        if !log_to_console && !log_to_file {
             return;
         }
 
+        // 频道过滤
+        let mut to_file = log_to_file;
+        if let Some(channel) = record.target().strip_prefix(CHANNEL_TARGET_PREFIX) {
+            let channels = CHANNELS.lock();
+            if channels.disabled.contains(channel) {
+                return;
+            }
+            to_file &= !channels.file_excluded.contains(channel);
+        }
+
         let cur_level: luaf_include::LogLevel = record.level().to_level_filter().into();
-        if self.log_config.log_to_console && cur_level >= self.log_config.level {
+        if log_to_console
+            && cur_level >= level
+            && !LOG_CONSOLE_SUPPRESSED.load(atomic::Ordering::Relaxed)
+        {
             spawn_logger_console();
         }
 
@@ -92,9 +142,7 @@ impl log::Log for Logger {
         let now = chrono::Local::now();
         let time_str = format!("[ {} ]", now.format("%Y-%m-%d %H:%M:%S"));
 
-        if self.log_config.log_to_console
-            && let Some(stdout) = self.output.lock().stdout
-        {
+        if log_to_console && let Some(stdout) = self.output.lock().stdout {
             // colored
             let msg_str_colored = match record.level() {
                 log::Level::Error => msg_str.red().bold(),
@@ -115,16 +163,14 @@ impl log::Log for Logger {
             }
         }
 
-        if self.log_config.log_to_file
-            && let Some(file) = self.output.lock().file.as_mut()
-        {
+        if to_file && let Some(file) = self.output.lock().file.as_mut() {
             let msg = format!("{} {}", time_str, msg_str);
             let _ = writeln!(file, "{}", msg);
         }
     }
 
     fn flush(&self) {
-        if self.log_config.log_to_file
+        if self.log_config.lock().log_to_file
             && let Some(file) = self.output.lock().file.as_mut()
         {
             let _ = file.sync_all();
@@ -135,10 +181,132 @@ impl log::Log for Logger {
 /// Initialize logger.
 /// Should be called by plugin entry point once.
 pub fn init_logger() {
+    {
+        let config = Config::global();
+        let mut channels = CHANNELS.lock();
+        channels.disabled = config.log.disabled_channels.iter().cloned().collect();
+        channels.file_excluded = config.log.file_excluded_channels.iter().cloned().collect();
+        crate::error::set_log_language(config.log.language);
+    }
+
     log::set_logger(&*LOGGER).unwrap();
     log::set_max_level(Config::global().log.level.into());
 }
 
+fn open_log_file(path: &str, truncate: bool) -> std::io::Result<fs::File> {
+    fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(truncate)
+        .append(!truncate)
+        .open(path)
+}
+
+/// 当前日志等级
+pub fn level() -> luaf_include::LogLevel {
+    LOGGER.log_config.lock().level
+}
+
+/// 设置日志等级，立即生效并保存到配置
+pub fn set_level(level: luaf_include::LogLevel) {
+    LOGGER.log_config.lock().level = level;
+    log::set_max_level(level.into());
+    Config::global_mut().log.level = level;
+}
+
+/// 设置是否输出到控制台
+pub fn set_log_to_console(enabled: bool) {
+    LOGGER.log_config.lock().log_to_console = enabled;
+    Config::global_mut().log.log_to_console = enabled;
+}
+
+/// 设置是否写入日志文件，启用时以追加方式打开日志文件
+pub fn set_log_to_file(enabled: bool) -> std::io::Result<()> {
+    let path = LOGGER.log_config.lock().log_file_path.clone();
+    {
+        let mut output = LOGGER.output.lock();
+        if !enabled {
+            output.file = None;
+        } else if output.file.is_none() {
+            output.file = Some(open_log_file(&path, false)?);
+        }
+    }
+    LOGGER.log_config.lock().log_to_file = enabled;
+    Config::global_mut().log.log_to_file = enabled;
+    Ok(())
+}
+
+/// 更改日志文件路径并重新打开日志文件，打开失败时保留原文件
+pub fn set_log_file_path(path: &str) -> std::io::Result<()> {
+    if LOGGER.log_config.lock().log_to_file {
+        let file = open_log_file(path, true)?;
+        LOGGER.output.lock().file = Some(file);
+    }
+    LOGGER.log_config.lock().log_file_path = path.to_string();
+    Config::global_mut().log.log_file_path = path.to_string();
+    Ok(())
+}
+
+/// 当前生效的日志设置
+pub fn settings() -> crate::config::LogConfig {
+    LOGGER.log_config.lock().clone()
+}
+
+/// 获取日志频道对应的 target，并登记频道
+pub fn channel_target(name: &str) -> String {
+    let mut channels = CHANNELS.lock();
+    if !channels.known.contains(name) {
+        channels.known.insert(name.to_string());
+    }
+    format!("{}{}", CHANNEL_TARGET_PREFIX, name)
+}
+
+/// 列出所有已创建和已配置的日志频道
+pub fn channels() -> Vec<ChannelInfo> {
+    let channels = CHANNELS.lock();
+    channels
+        .known
+        .iter()
+        .chain(channels.disabled.iter())
+        .chain(channels.file_excluded.iter())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|name| ChannelInfo {
+            name: name.clone(),
+            enabled: !channels.disabled.contains(name),
+            to_file: !channels.file_excluded.contains(name),
+        })
+        .collect()
+}
+
+/// 启用或禁用日志频道
+pub fn set_channel_enabled(name: &str, enabled: bool) {
+    let disabled = {
+        let mut channels = CHANNELS.lock();
+        if enabled {
+            channels.disabled.remove(name);
+        } else {
+            channels.disabled.insert(name.to_string());
+        }
+        channels.disabled.iter().cloned().collect()
+    };
+    Config::global_mut().log.disabled_channels = disabled;
+}
+
+/// 设置日志频道是否写入日志文件
+pub fn set_channel_to_file(name: &str, to_file: bool) {
+    let file_excluded = {
+        let mut channels = CHANNELS.lock();
+        if to_file {
+            channels.file_excluded.remove(name);
+        } else {
+            channels.file_excluded.insert(name.to_string());
+        }
+        channels.file_excluded.iter().cloned().collect()
+    };
+    Config::global_mut().log.file_excluded_channels = file_excluded;
+}
+
 pub fn spawn_logger_console() {
     if LOG_CONSOLE_SPAWNED
         .compare_exchange(
@@ -185,5 +353,62 @@ pub fn spawn_logger_console() {
         );
     };
 
-    LOGGER.set_stdout_handle(stdout_handle);
+    LOGGER.set_stdout_handle(Some(stdout_handle));
+    apply_console_settings(&LOGGER.log_config.lock().clone());
+}
+
+/// 设置控制台窗口标题和位置
+fn apply_console_settings(config: &crate::config::LogConfig) {
+    unsafe {
+        let _ = SetConsoleTitleW(&HSTRING::from(config.console_title.as_str()));
+
+        let hwnd = GetConsoleWindow();
+        if let Some([x, y]) = config.console_position
+            && !hwnd.0.is_null()
+        {
+            let _ = SetWindowPos(hwnd, None, x, y, 0, 0, SWP_NOSIZE | SWP_NOZORDER);
+        }
+    }
+}
+
+/// 显示控制台
+pub fn show_logger_console() {
+    LOG_CONSOLE_SUPPRESSED.store(false, atomic::Ordering::Relaxed);
+    spawn_logger_console();
+}
+
+/// 隐藏并释放控制台，之后的日志不会自动弹出控制台
+pub fn hide_logger_console() {
+    LOG_CONSOLE_SUPPRESSED.store(true, atomic::Ordering::Relaxed);
+    if LOG_CONSOLE_SPAWNED
+        .compare_exchange(
+            true,
+            false,
+            atomic::Ordering::Acquire,
+            atomic::Ordering::Relaxed,
+        )
+        .is_err()
+    {
+        return;
+    }
+
+    LOGGER.set_stdout_handle(None);
+    unsafe {
+        let _ = FreeConsole();
+    }
+}
+
+/// 切换控制台显示状态，返回切换后是否显示
+pub fn toggle_logger_console() -> bool {
+    if is_logger_console_visible() {
+        hide_logger_console();
+        false
+    } else {
+        show_logger_console();
+        true
+    }
+}
+
+pub fn is_logger_console_visible() -> bool {
+    LOG_CONSOLE_SPAWNED.load(atomic::Ordering::Relaxed)
 }
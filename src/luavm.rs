@@ -1,8 +1,12 @@
 use std::{
     cell::RefCell,
     collections::{HashMap, HashSet},
-    path::Path,
-    sync::{Arc, LazyLock, Weak},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, LazyLock, Weak,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
 };
 
 use library::LuaModule;
@@ -12,8 +16,20 @@ use rand::RngCore;
 
 use crate::config::Config;
 use crate::error::{Error, Result};
+use crate::profiler::Profiler;
+use crate::render_core::layer::DrawLayer;
 
+pub mod benchmark;
+pub mod capability;
 mod library;
+pub mod safety;
+mod scratch;
+pub mod watcher;
+
+pub use library::sdk::patch_profile::PatchProfileManager;
+#[cfg(feature = "embed")]
+pub use scratch::ScratchVM;
+use watcher::ScriptChange;
 
 pub type SharedLuaVM = Arc<LuaVM>;
 pub type WeakLuaVM = Weak<LuaVM>;
@@ -42,19 +58,36 @@ impl LuaVMId {
 
 #[derive(Debug, Clone)]
 pub struct LastLoadInfo {
-    pub path: String,
+    pub paths: Vec<PathBuf>,
     #[allow(dead_code)]
     pub time: std::time::Instant,
 }
 
+/// 等待执行的重载请求
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReloadRequest {
+    All,
+    Script(String),
+}
+
 #[derive(Default)]
 pub struct LuaVMManager {
     inner: ReentrantMutex<RefCell<LuaVMManagerInner>>,
     last_load_info: Mutex<Option<LastLoadInfo>>,
+    /// 正在重载，防止重入
+    reloading: AtomicBool,
+    /// 上次重载完成的时间，用于防抖
+    last_reload: Mutex<Option<Instant>>,
+    /// 下一帧执行的重载请求
+    pending_reload: Mutex<Option<ReloadRequest>>,
+    /// 可复用的临时虚拟机
+    scratch_pool: Mutex<Vec<SharedLuaVM>>,
 }
 
 impl LuaVMManager {
     pub const LUA_SCRIPTS_DIR: &str = "./lua_framework/scripts";
+    /// 两次重载之间的最小间隔
+    const RELOAD_DEBOUNCE: Duration = Duration::from_millis(500);
 
     pub fn instance() -> &'static LuaVMManager {
         static INSTANCE: LazyLock<LuaVMManager> = LazyLock::new(LuaVMManager::default);
@@ -66,7 +99,6 @@ impl LuaVMManager {
     /// 此操作会加载默认的库。
     ///
     /// name: 虚拟名称，用于标识虚拟机。会自动在前面加上 `virtual:`
-    #[allow(dead_code)]
     pub fn create_virtual_vm(&self, name: &str) -> SharedLuaVM {
         let virtual_name = format!("virtual:{}", name);
         let luavm = LuaVM::new_with_libs(&virtual_name).unwrap();
@@ -83,6 +115,18 @@ impl LuaVMManager {
         luavm_shared
     }
 
+    /// 移除虚拟虚拟机，name 不含 `virtual:` 前缀
+    pub fn remove_virtual_vm(&self, name: &str) {
+        {
+            let inner = self.inner.lock();
+            inner
+                .borrow_mut()
+                .remove_vm_by_name(&format!("virtual:{}", name));
+        }
+        // 不会再有虚拟机重新绑定其持久化 Hook
+        library::sdk::frida::FridaModule::release_orphaned_hooks();
+    }
+
     /// 创建一个新的虚拟机并加载库和脚本，返回副本
     pub fn create_vm_with_file<P>(&self, script_path: P) -> Result<SharedLuaVM>
     where
@@ -113,6 +157,14 @@ impl LuaVMManager {
         {
             // 加载自定义库
             luavm_shared.load_luaf_libs()?;
+            // 记录脚本所在目录，用于定位资源文件
+            if let Some(script_dir) = script_path.as_ref().parent() {
+                let script_dir = script_dir.to_string_lossy().replace('\\', "/");
+                luavm_shared
+                    .lua()
+                    .globals()
+                    .set("_script_dir", script_dir)?;
+            }
             // 加载脚本
             let script_data = std::fs::read_to_string(&script_path).map_err(|e| {
                 Error::IoWithContext(
@@ -136,20 +188,36 @@ impl LuaVMManager {
         let inner_b = inner.borrow();
         inner_b.vms.get(&luaid).cloned()
     }
-    ///
+    /// 所有脚本目录：默认目录与配置中的额外目录
+    pub fn script_dirs() -> Vec<PathBuf> {
+        let mut dirs = vec![PathBuf::from(Self::LUA_SCRIPTS_DIR)];
+        dirs.extend(
+            Config::global()
+                .scripts
+                .extra_dirs
+                .iter()
+                .map(PathBuf::from),
+        );
+        dirs
+    }
+
+    /// 扫描所有脚本目录并加载虚拟机
+    pub fn auto_load_script_dirs(&self) -> Result<Vec<LuaVMId>> {
+        self.auto_load_vms_from(&Self::script_dirs())
+    }
+
     /// 扫描路径并加载所有虚拟机
     pub fn auto_load_vms<P>(&self, dir_path: P) -> Result<Vec<LuaVMId>>
     where
         P: AsRef<Path>,
     {
-        if !dir_path.as_ref().exists() {
-            log::warn!(
-                "Script directory '{}' not exists",
-                dir_path.as_ref().display(),
-            );
-            return Ok(Vec::new());
-        }
+        self.auto_load_vms_from(&[dir_path.as_ref().to_path_buf()])
+    }
 
+    /// 扫描多个路径并加载所有虚拟机
+    ///
+    /// 虚拟机以文件名标识，不同目录中的同名脚本只加载先出现的一个。
+    pub fn auto_load_vms_from(&self, dir_paths: &[PathBuf]) -> Result<Vec<LuaVMId>> {
         // update disabled vms
         {
             let inner = self.inner.lock();
@@ -159,28 +227,38 @@ impl LuaVMManager {
             }
         }
 
-        {
-            let abs_path = std::fs::canonicalize(&dir_path).unwrap_or_default();
-            log::info!("Scanning script directory '{}'", abs_path.display());
+        // 文件名 -> 脚本路径
+        let mut script_files: Vec<(String, PathBuf)> = Vec::new();
+        for dir_path in dir_paths {
+            for path in Self::scan_script_dir(dir_path)? {
+                let file_name = path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string();
+                if let Some((_, existing)) =
+                    script_files.iter().find(|(name, _)| *name == file_name)
+                {
+                    let err_msg = format!(
+                        "Script '{}' in '{}' conflicts with '{}'. Skipping.",
+                        file_name,
+                        dir_path.display(),
+                        existing.display()
+                    );
+                    crate::error::set_last_error(err_msg.clone());
+                    log::warn!("{}", err_msg);
+                    continue;
+                }
+                script_files.push((file_name, path));
+            }
         }
 
         let mut vms = Vec::new();
-        for entry in std::fs::read_dir(&dir_path)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_dir() {
-                continue;
-            }
-
-            if path.extension() != Some("lua".as_ref()) {
-                continue;
-            }
-
+        for (file_name, path) in script_files {
             // 检查是否被禁用
-            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
             {
                 let inner = self.inner.lock();
-                if !inner.borrow().is_vm_name_enabled(file_name.as_ref()) {
+                if !inner.borrow().is_vm_name_enabled(&file_name) {
                     log::debug!("Script file '{}' is disabled. Skipping.", file_name);
                     continue;
                 }
@@ -197,15 +275,164 @@ impl LuaVMManager {
         }
 
         self.last_load_info.lock().replace(LastLoadInfo {
-            path: dir_path.as_ref().to_string_lossy().to_string(),
+            paths: dir_paths.to_vec(),
             time: std::time::Instant::now(),
         });
 
         Ok(vms)
     }
 
+    /// 列出目录中的脚本文件，按文件名排序
+    fn scan_script_dir(dir_path: &Path) -> Result<Vec<PathBuf>> {
+        if !dir_path.exists() {
+            log::warn!("Script directory '{}' not exists", dir_path.display());
+            return Ok(Vec::new());
+        }
+
+        {
+            let abs_path = std::fs::canonicalize(dir_path).unwrap_or_default();
+            log::info!("Scanning script directory '{}'", abs_path.display());
+        }
+
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(dir_path)? {
+            let entry = entry?;
+            let path = entry.path();
+            if path.is_dir() {
+                continue;
+            }
+
+            if path.extension() != Some("lua".as_ref()) {
+                continue;
+            }
+
+            paths.push(path);
+        }
+        paths.sort();
+
+        Ok(paths)
+    }
+
+    /// 请求在下一帧执行重载
+    ///
+    /// 脚本回调中不能直接销毁虚拟机，因此重载请求会延迟到 on_update 之前执行。
+    /// 距离上次重载过近的请求会被忽略。
+    pub fn request_reload(&self, request: ReloadRequest) -> bool {
+        if self
+            .last_reload
+            .lock()
+            .is_some_and(|last| last.elapsed() < Self::RELOAD_DEBOUNCE)
+        {
+            log::debug!("Reload request {:?} ignored by debounce", request);
+            return false;
+        }
+
+        let mut pending = self.pending_reload.lock();
+        // 重载全部优先于重载单个脚本
+        if *pending != Some(ReloadRequest::All) {
+            *pending = Some(request);
+        }
+        true
+    }
+
+    /// 执行等待中的重载请求
+    pub fn process_pending_reload(&self) {
+        let Some(request) = self.pending_reload.lock().take() else {
+            return;
+        };
+
+        let result = match &request {
+            ReloadRequest::All => self.reload_physical_vms(),
+            ReloadRequest::Script(name) => self.reload_vm_by_name(name),
+        };
+        if let Err(e) = result {
+            log::error!(
+                "Failed to process reload request {:?}: {}",
+                request,
+                e.log()
+            );
+        }
+    }
+
+    /// 应用脚本文件监视发现的变更，只重载受影响的虚拟机
+    pub fn process_script_changes(&self) {
+        for change in watcher::ScriptWatcher::instance().take_changes() {
+            let path = match &change {
+                ScriptChange::Created(path)
+                | ScriptChange::Modified(path)
+                | ScriptChange::Removed(path) => path.clone(),
+            };
+            let name = path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            let (loaded_path, enabled) = {
+                let inner = self.inner.lock();
+                let inner_b = inner.borrow();
+                (
+                    inner_b
+                        .vm_names
+                        .get(&name)
+                        .and_then(|id| inner_b.vms.get(id))
+                        .and_then(|vm| vm.script_path()),
+                    inner_b.is_vm_name_enabled(&name),
+                )
+            };
+            let loaded = loaded_path.is_some();
+            // 其他目录中的同名脚本不影响已加载的脚本
+            if let Some(loaded_path) = loaded_path
+                && canonical_script_path(&loaded_path) != canonical_script_path(&path)
+            {
+                log::debug!(
+                    "Ignoring change of '{}', script '{}' is loaded from '{}'",
+                    path.display(),
+                    name,
+                    loaded_path.display()
+                );
+                continue;
+            }
+
+            let result = match change {
+                ScriptChange::Removed(_) if loaded => {
+                    log::info!("Script '{}' removed, unloading", name);
+                    {
+                        let inner = self.inner.lock();
+                        inner.borrow_mut().remove_vm_by_name(&name);
+                    }
+                    // 脚本已删除，不会再有虚拟机重新绑定其持久化 Hook
+                    library::sdk::frida::FridaModule::release_orphaned_hooks();
+                    Ok(())
+                }
+                ScriptChange::Modified(_) if loaded => {
+                    log::info!("Script '{}' changed, reloading", name);
+                    // 加载失败的信息已由 reload_vm_by_name 记录
+                    let _ = self.reload_vm_by_name(&name);
+                    Ok(())
+                }
+                // 新建的脚本，或之前加载失败的脚本
+                ScriptChange::Created(_) | ScriptChange::Modified(_) if !loaded && enabled => {
+                    log::info!("Script '{}' added, loading", name);
+                    self.create_vm_with_file(&path).map(|_| ())
+                }
+                _ => Ok(()),
+            };
+            if let Err(e) = result {
+                let err_msg = format!("Failed to load script '{}':\n{}", path.display(), e);
+                crate::error::set_last_error(err_msg.clone());
+                log::error!("{}", err_msg);
+            }
+        }
+    }
+
     /// 重新加载所有虚拟机
     pub fn reload_physical_vms(&self) -> Result<()> {
+        let Some(_guard) = ReloadGuard::acquire(self) else {
+            log::warn!("Reload is already in progress");
+            return Ok(());
+        };
+        self.invoke_fn_with_args("on_before_reload", LuaNil);
+
         {
             let inner = self.inner.lock();
             let mut inner_b = inner.borrow_mut();
@@ -219,17 +446,68 @@ impl LuaVMManager {
         library::sdk::shared_state::SharedState::instance().clear_states();
         // 加载
         let info = self.last_load_info.lock().clone();
-        if let Some(info) = info.as_ref() {
-            self.auto_load_vms(&info.path)?;
+        let result = if let Some(info) = info.as_ref() {
+            self.auto_load_vms_from(&info.paths)
         } else {
-            self.auto_load_vms(Self::LUA_SCRIPTS_DIR)?;
+            self.auto_load_script_dirs()
+        };
+        // 移除未被重新绑定的持久化 Hook
+        library::sdk::frida::FridaModule::release_orphaned_hooks();
+        result?;
+
+        self.invoke_fn_with_args("on_after_reload", LuaNil);
+        Ok(())
+    }
+
+    /// 重新加载单个虚拟机
+    ///
+    /// 销毁时执行脚本的 on_destroy 并移除其 Hook 和补丁，其他虚拟机不受影响。
+    pub fn reload_vm_by_name(&self, name: &str) -> Result<()> {
+        let Some(_guard) = ReloadGuard::acquire(self) else {
+            log::warn!("Reload is already in progress");
+            return Ok(());
+        };
+
+        let script_path = {
+            let inner = self.inner.lock();
+            let inner_b = inner.borrow();
+            inner_b
+                .vm_names
+                .get(name)
+                .and_then(|id| inner_b.vms.get(id))
+                .filter(|vm| !vm.is_virtual())
+                .and_then(|vm| vm.script_path())
+                .ok_or_else(|| Error::LuaVMNameNotFound(name.to_string()))?
+        };
+
+        self.invoke_fn_with_args("on_before_reload", name);
+        {
+            let inner = self.inner.lock();
+            inner.borrow_mut().remove_vm_by_name(name);
+        }
+        let result = self.create_vm_with_file(&script_path);
+        library::sdk::frida::FridaModule::release_orphaned_hooks();
+        if let Err(e) = result {
+            let err_msg = format!("Failed to load script '{}':\n{}", script_path.display(), e);
+            crate::error::set_last_error(err_msg.clone());
+            log::error!("{}", err_msg);
+            return Err(e);
         }
 
+        self.invoke_fn_with_args("on_after_reload", name);
         Ok(())
     }
 
     /// 调用已设置的回调函数，无参数。
     pub fn invoke_fn(&self, fn_name: &str) {
+        self.invoke_fn_with_args(fn_name, ());
+    }
+
+    /// 调用所有虚拟机中的回调函数，并传入参数
+    pub fn invoke_fn_with_args<A>(&self, fn_name: &str, args: A)
+    where
+        A: IntoLuaMulti + Clone,
+    {
         let inner = self.inner.lock();
         let inner_b = inner.borrow();
         for (_, luavm) in inner_b.iter_vms() {
@@ -237,7 +515,17 @@ impl LuaVMManager {
             let Ok(fun) = globals.get::<LuaFunction>(format!("_{fn_name}")) else {
                 continue;
             };
-            if let Err(e) = fun.call::<()>(()) {
+            let start = Instant::now();
+            let result = fun.call::<()>(args.clone());
+            Profiler::instance().record_callback(
+                luavm.name(),
+                fn_name,
+                start.elapsed(),
+                result.is_err(),
+            );
+            // 每帧至少调用一次 on_update，未换行的输出不会一直留在缓冲区中
+            library::runtime::RuntimeModule::flush_stdio(luavm.lua());
+            if let Err(e) = result {
                 let err_msg = format!("`{fn_name}` in LuaVM({}) error:\n{}", luavm.name(), e);
                 crate::error::set_last_error(err_msg.clone());
                 log::error!("{}", err_msg);
@@ -245,6 +533,209 @@ impl LuaVMManager {
         }
     }
 
+    /// 绘制脚本通过 `core.on_menu` 注册的主菜单栏，没有脚本注册时不显示菜单栏
+    pub fn render_menu_bar(&self) {
+        let inner = self.inner.lock();
+        let inner_b = inner.borrow();
+        let mut menus = inner_b
+            .iter_vms()
+            .filter_map(|(_, luavm)| {
+                let globals = luavm.lua().globals();
+                let fun = globals.get::<LuaFunction>("_on_menu").ok()?;
+                let title = globals
+                    .get::<Option<String>>("_menu_title")
+                    .ok()
+                    .flatten()
+                    .unwrap_or_else(|| luavm.name().trim_end_matches(".lua").to_string());
+                Some((title, luavm.name().to_string(), fun))
+            })
+            .collect::<Vec<_>>();
+        if menus.is_empty() {
+            return;
+        }
+        menus.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let start = Instant::now();
+        library::render::RenderModule::draw_main_menu_bar(&menus);
+        Profiler::instance().add_lua_time(start.elapsed());
+    }
+
+    /// 按绘制层和层内 z 值调用所有虚拟机的绘制回调
+    ///
+    /// `core.on_draw` 注册的回调位于 hud 层，z 值为 0。同层同 z 值的回调按虚拟机顺序调用。
+    pub fn render_draw_layers(&self) {
+        let inner = self.inner.lock();
+        let inner_b = inner.borrow();
+        let mut entries = Vec::new();
+        for (_, luavm) in inner_b.iter_vms() {
+            let globals = luavm.lua().globals();
+            if let Ok(fun) = globals.get::<LuaFunction>("_on_draw") {
+                entries.push((DrawLayer::Hud, 0, luavm.name(), "on_draw".to_string(), fun));
+            }
+            let Ok(layers) = globals.get::<LuaTable>("_draw_layers") else {
+                continue;
+            };
+            for pair in layers.pairs::<String, LuaTable>() {
+                let Ok((name, entry)) = pair else {
+                    continue;
+                };
+                let Ok(layer) = name.parse::<DrawLayer>() else {
+                    continue;
+                };
+                let (Ok(fun), Ok(z)) = (entry.get::<LuaFunction>("fn"), entry.get::<i32>("z"))
+                else {
+                    continue;
+                };
+                entries.push((layer, z, luavm.name(), format!("on_draw:{}", name), fun));
+            }
+        }
+        entries.retain(|(layer, ..)| crate::render_core::layer::is_enabled(*layer));
+        entries.sort_by_key(|(layer, z, ..)| (*layer, *z));
+
+        for (_, _, script_name, fn_name, fun) in entries {
+            let start = Instant::now();
+            let result = fun.call::<()>(());
+            Profiler::instance().record_callback(
+                script_name,
+                &fn_name,
+                start.elapsed(),
+                result.is_err(),
+            );
+            if let Err(e) = result {
+                let err_msg = format!("`{fn_name}` in LuaVM({}) error:\n{}", script_name, e);
+                crate::error::set_last_error(err_msg.clone());
+                log::error!("{}", err_msg);
+            };
+        }
+    }
+
+    /// 执行所有虚拟机中到期的定时任务，恢复等待到期的协程
+    pub fn tick_timers(&self) {
+        let inner = self.inner.lock();
+        let inner_b = inner.borrow();
+        for (_, luavm) in inner_b.iter_vms() {
+            let start = Instant::now();
+            let result = library::utility::UtilityModule::tick_timers(luavm.lua())
+                .and_then(|_| library::sdk::memory::MemoryModule::tick_watches(luavm.lua()))
+                .and_then(|_| library::runtime::RuntimeModule::tick_coroutines(luavm.lua()));
+            Profiler::instance().record_callback(
+                luavm.name(),
+                "timers",
+                start.elapsed(),
+                result.is_err(),
+            );
+            if let Err(e) = result {
+                log::error!("Failed to tick timers in LuaVM({}): {}", luavm.name(), e);
+            }
+        }
+    }
+
+    /// 调用本帧触发的快捷键回调
+    pub fn dispatch_hotkeys(&self, triggered: &[(String, String)]) {
+        let inner = self.inner.lock();
+        let inner_b = inner.borrow();
+        for (script, name) in triggered {
+            let Some(luavm) = inner_b
+                .vm_names
+                .get(script)
+                .and_then(|id| inner_b.vms.get(id))
+            else {
+                continue;
+            };
+            let start = Instant::now();
+            let result = library::sdk::hotkey::HotkeyModule::dispatch(luavm.lua(), name);
+            Profiler::instance().record_callback(
+                luavm.name(),
+                "hotkey",
+                start.elapsed(),
+                result.is_err(),
+            );
+            if let Err(e) = result {
+                let err_msg = format!(
+                    "hotkey '{}' callback in LuaVM({}) error:\n{}",
+                    name,
+                    luavm.name(),
+                    e
+                );
+                crate::error::set_last_error(err_msg.clone());
+                log::error!("{}", err_msg);
+            }
+        }
+    }
+
+    /// 调用聊天命令的处理函数，返回要显示的回复
+    ///
+    /// 处理函数出错时回复命令用法。
+    pub fn dispatch_chat_command(
+        &self,
+        command: &crate::game::command::ChatCommand,
+        args: &[String],
+        raw: &str,
+    ) -> Option<String> {
+        let inner = self.inner.lock();
+        let inner_b = inner.borrow();
+        let luavm = inner_b
+            .vm_names
+            .get(&command.script)
+            .and_then(|id| inner_b.vms.get(id))?;
+
+        let start = Instant::now();
+        let result =
+            library::sdk::chat::ChatModule::dispatch_command(luavm.lua(), &command.name, args, raw);
+        Profiler::instance().record_callback(
+            luavm.name(),
+            "chat_command",
+            start.elapsed(),
+            result.is_err(),
+        );
+        match result {
+            Ok(reply) => reply,
+            Err(e) => {
+                let err_msg = format!(
+                    "chat command '{}' in LuaVM({}) error:\n{}",
+                    command.name,
+                    luavm.name(),
+                    e
+                );
+                crate::error::set_last_error(err_msg.clone());
+                log::error!("{}", err_msg);
+                Some(format!("Usage: {}", command.help_line()))
+            }
+        }
+    }
+
+    /// 通知所有虚拟机新解析的单例
+    pub fn dispatch_singletons_registered(&self, singletons: &[(String, usize)]) {
+        let inner = self.inner.lock();
+        let inner_b = inner.borrow();
+        for (_, luavm) in inner_b.iter_vms() {
+            for (name, address) in singletons {
+                let start = Instant::now();
+                let result = library::sdk::singletons::SingletonsModule::dispatch_registered(
+                    luavm.lua(),
+                    name,
+                    *address,
+                );
+                Profiler::instance().record_callback(
+                    luavm.name(),
+                    "on_singleton_registered",
+                    start.elapsed(),
+                    result.is_err(),
+                );
+                if let Err(e) = result {
+                    let err_msg = format!(
+                        "singleton '{}' callback in LuaVM({}) error:\n{}",
+                        name,
+                        luavm.name(),
+                        e
+                    );
+                    crate::error::set_last_error(err_msg.clone());
+                    log::error!("{}", err_msg);
+                }
+            }
+        }
+    }
+
     pub fn run_with_lock<F>(&self, f: F) -> LuaResult<()>
     where
         F: FnOnce(&LuaVMManagerInner) -> LuaResult<()>,
@@ -312,6 +803,12 @@ impl LuaVMManagerInner {
         self.vm_names.insert(name.to_string(), id);
     }
 
+    fn remove_vm_by_name(&mut self, name: &str) {
+        if let Some(id) = self.vm_names.remove(name) {
+            self.vms.remove(&id);
+        }
+    }
+
     fn remove_pyhsical_vms(&mut self) {
         // 清除错误信息
         crate::error::clear_last_error();
@@ -321,6 +818,60 @@ impl LuaVMManagerInner {
     }
 }
 
+/// 重载期间持有，结束时记录完成时间
+struct ReloadGuard<'a> {
+    manager: &'a LuaVMManager,
+}
+
+impl<'a> ReloadGuard<'a> {
+    fn acquire(manager: &'a LuaVMManager) -> Option<Self> {
+        manager
+            .reloading
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()?;
+        Some(Self { manager })
+    }
+}
+
+impl Drop for ReloadGuard<'_> {
+    fn drop(&mut self) {
+        self.manager.last_reload.lock().replace(Instant::now());
+        self.manager.reloading.store(false, Ordering::Release);
+    }
+}
+
+/// 规范化脚本路径，用于比较不同写法的同一文件
+///
+/// 文件可能已被删除，因此只规范化所在目录。
+fn canonical_script_path(path: &Path) -> PathBuf {
+    let (Some(dir), Some(file_name)) = (path.parent(), path.file_name()) else {
+        return path.to_path_buf();
+    };
+    std::fs::canonicalize(dir)
+        .map(|dir| dir.join(file_name))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// 虚拟机身份，保存在 app data 中
+///
+/// 全局变量 `_name` 可被脚本修改，权限和安全策略检查使用此处的脚本名。
+pub struct ScriptIdentity {
+    pub id: LuaVMId,
+    pub name: String,
+}
+
+impl ScriptIdentity {
+    /// 未加载框架库的虚拟机使用的脚本名
+    pub const UNKNOWN_NAME: &'static str = "Script";
+
+    /// 虚拟机的脚本名
+    pub fn name_of(lua: &Lua) -> String {
+        lua.app_data_ref::<ScriptIdentity>()
+            .map(|identity| identity.name.clone())
+            .unwrap_or_else(|| Self::UNKNOWN_NAME.to_string())
+    }
+}
+
 pub struct LuaVM {
     id: LuaVMId,
     lua: Lua,
@@ -333,6 +884,17 @@ impl Drop for LuaVM {
         // 发布移除事件
         let lua_state_ptr = library::runtime::RuntimeModule::get_state_ptr(&self.lua).unwrap();
         crate::extension::CoreAPI::instance().dispatch_lua_state_destroyed(lua_state_ptr);
+        self.release_resources();
+
+        log::debug!("LuaVM({}) removed", self.name());
+    }
+}
+
+impl LuaVM {
+    /// 执行 on_destroy 回调，并释放脚本创建的 Hook、补丁、后台任务等资源
+    fn release_resources(&self) {
+        // 移除回调统计
+        Profiler::instance().remove_script(self.name());
         // 执行 on_destroy 回调
         if let Err(e) = library::runtime::RuntimeModule::invoke_on_destroy(&self.lua) {
             log::error!(
@@ -346,15 +908,60 @@ impl Drop for LuaVM {
         if let Err(e) = result {
             log::error!("Failed to remove LuaVM({}) frida hooks: {}", self.name(), e);
         }
+        // 移除快捷键
+        crate::input::hotkey::HotkeyManager::instance().unregister_script(self.name());
+        // 释放注入的按键
+        if let Some(input) = crate::input::Input::try_instance() {
+            input.release_injected(self.name());
+        }
+        // 移除聊天命令
+        crate::game::command::CommandRegistry::instance().unregister_script(self.name());
+        // 取消后台任务
+        if let Err(e) = library::utility::task::cancel_all(&self.lua) {
+            log::error!("Failed to cancel LuaVM({}) tasks: {}", self.name(), e);
+        }
         // 移除patches
         let result = library::sdk::memory::MemoryModule::restore_all_patches(&self.lua);
         if let Err(e) = result {
             log::error!("Failed to restore LuaVM({}) patches: {}", self.name(), e);
         }
-        // 移除内存分配
-        library::sdk::memory::MemoryModule::free_all_allocations();
-
-        log::debug!("LuaVM({}) removed", self.name());
+        // 释放内存分配
+        let result = library::sdk::memory::MemoryModule::free_all_allocations(&self.lua);
+        if let Err(e) = result {
+            log::error!("Failed to free LuaVM({}) allocations: {}", self.name(), e);
+        }
+        // 释放 DTI 实例
+        let result = library::sdk::dti::DtiModule::destroy_all_instances(&self.lua);
+        if let Err(e) = result {
+            log::error!(
+                "Failed to destroy LuaVM({}) DTI instances: {}",
+                self.name(),
+                e
+            );
+        }
+        // 释放原生回调
+        let result = library::sdk::ffi_call::FFICallModule::free_all_callbacks(&self.lua);
+        if let Err(e) = result {
+            log::error!(
+                "Failed to free LuaVM({}) native callbacks: {}",
+                self.name(),
+                e
+            );
+        }
+        // 释放代码岛
+        let result = library::sdk::code_writer::CodeWriterModule::free_all_islands(&self.lua);
+        if let Err(e) = result {
+            log::error!("Failed to free LuaVM({}) code islands: {}", self.name(), e);
+        }
+        // 释放固定字符串
+        let result = library::sdk::string::StringModule::release_all_pinned(&self.lua);
+        if let Err(e) = result {
+            log::error!(
+                "Failed to release LuaVM({}) pinned strings: {}",
+                self.name(),
+                e
+            );
+        }
     }
 }
 
@@ -390,6 +997,15 @@ impl LuaVM {
         &self.lua
     }
 
+    /// 脚本文件路径，虚拟虚拟机返回 None
+    pub fn script_path(&self) -> Option<PathBuf> {
+        if self.is_virtual() {
+            return None;
+        }
+        let script_dir = self.lua.globals().get::<String>("_script_dir").ok()?;
+        Some(Path::new(&script_dir).join(&self.name))
+    }
+
     /// 获取虚拟机脚本名称
     pub fn name(&self) -> &str {
         &self.name
@@ -401,6 +1017,10 @@ impl LuaVM {
 
         globals.set("_id", self.id)?;
         globals.set("_name", self.name())?;
+        self.lua.set_app_data(ScriptIdentity {
+            id: self.id,
+            name: self.name().to_string(),
+        });
         // 设置模块搜索路径
         self.lua
             .load(r#"package.path = package.path .. ";lua_framework/scripts/?.lua""#)
@@ -411,6 +1031,8 @@ impl LuaVM {
         library::sdk::SdkModule::register_library(&self.lua, &globals)?;
         library::render::RenderModule::register_library(&self.lua, &globals)?;
         library::fs::FSModule::register_library(&self.lua, &globals)?;
+        #[cfg(feature = "embed")]
+        crate::embed::register_custom_modules(&self.lua, &globals)?;
 
         Ok(())
     }
@@ -433,6 +1055,7 @@ impl LuaVM {
 mod tests {
     use super::*;
 
+    use crate::luavm::capability::UnsafeCapability;
     use crate::tests::init_logging;
 
     #[test]
@@ -448,6 +1071,299 @@ mod tests {
         assert_eq!(globals.get::<String>("_name").unwrap(), "virtual:test.lua");
     }
 
+    #[test]
+    fn test_userdata_json_roundtrip() {
+        let vm = LuaVM::new_with_libs("virtual:test_userdata_serde.lua").unwrap();
+
+        let script = r#"
+            local encoded = json.encode({ ptr = sdk.LuaPtr(0x1234), name = sdk.String.new_utf16("abc") })
+            local decoded = json.decode(encoded)
+            assert(decoded.ptr:to_integer() == 0x1234)
+            assert(decoded.name.encoding == "utf16")
+            assert(decoded.name:to_string() == "abc")
+        "#;
+        vm.load_script(script).unwrap();
+    }
+
+    #[test]
+    fn test_table_utils() {
+        let vm = LuaVM::new_with_libs("virtual:test_table_utils.lua").unwrap();
+
+        let script = r#"
+            local t = { a = 1, nested = { b = 2 } }
+            t.self = t
+            local copy = utils.deep_copy(t)
+            assert(copy ~= t and copy.self == copy)
+            assert(copy.nested ~= t.nested and copy.nested.b == 2)
+
+            utils.table_merge(copy, { nested = { c = 3 } }, true)
+            assert(copy.nested.b == 2 and copy.nested.c == 3)
+
+            local diff = utils.table_diff({ a = 1, b = 2 }, { a = 1, b = 3, c = 4 })
+            assert(diff.a == nil and diff.b.new == 3 and diff.c.old == nil)
+
+            -- 元表被保留，元方法不影响复制
+            local mt = { __index = function() return "meta" end, __newindex = error }
+            local with_mt = setmetatable({ x = 1 }, mt)
+            local copy_mt = utils.deep_copy(with_mt)
+            assert(getmetatable(copy_mt) == mt and copy_mt.x == 1 and copy_mt.y == "meta")
+
+            -- 嵌套过深时返回错误而不是耗尽原生栈
+            local function nest(depth)
+                local root = {}
+                local node = root
+                for _ = 1, depth do
+                    node.child = {}
+                    node = node.child
+                end
+                return root
+            end
+            local ok, err = pcall(utils.deep_copy, nest(100000))
+            assert(not ok and tostring(err):find("nesting"))
+            assert(not pcall(utils.table_merge, nest(100000), nest(100000), true))
+            assert(not pcall(utils.table_diff, { a = nest(100000) }, { a = nest(100000) }))
+            assert(pcall(utils.deep_copy, nest(100)))
+        "#;
+        vm.load_script(script).unwrap();
+    }
+
+    #[test]
+    fn test_stdio_redirect() {
+        init_logging();
+
+        let vm = LuaVM::new_with_libs("virtual:test_stdio_redirect.lua").unwrap();
+
+        let script = r#"
+            io.write("Hello, ", 42, "\n")
+            assert(io.stdout:write("partial"):flush())
+            io.stderr:write("error line\n")
+            assert(not pcall(io.write, {}))
+        "#;
+        vm.load_script(script).unwrap();
+    }
+
+    #[test]
+    fn test_callback_stats() {
+        let manager = LuaVMManager::instance();
+        let vm = manager.create_virtual_vm("test_callback_stats.lua");
+        vm.load_script("_on_test_stats = function() end").unwrap();
+        manager.invoke_fn("test_stats");
+
+        let script = r#"
+            local stats = core.stats()["virtual:test_callback_stats.lua"].test_stats
+            assert(stats.calls == 1 and stats.errors == 0)
+            assert(stats.max_us >= 0 and stats.avg_us >= 0)
+        "#;
+        vm.load_script(script).unwrap();
+        manager.remove_virtual_vm("test_callback_stats.lua");
+    }
+
+    #[test]
+    fn test_scratch_vm_reset() {
+        let manager = LuaVMManager::instance();
+        let id = {
+            let vm = manager.acquire_scratch_vm().unwrap();
+            vm.load_script("scratch_value = 1; core.on_update(function() end)")
+                .unwrap();
+            vm.id()
+        };
+
+        let vm = manager.acquire_scratch_vm().unwrap();
+        assert_eq!(vm.id(), id);
+        vm.load_script("assert(scratch_value == nil and _on_update == nil and core ~= nil)")
+            .unwrap();
+    }
+
+    #[test]
+    fn test_with_unsafe() {
+        let vm = LuaVM::new_with_libs("virtual:test_with_unsafe.lua").unwrap();
+
+        let script = r#"
+            local a, b = core.with_unsafe("memory_write", function(x) return x, x + 1 end, 1)
+            assert(a == 1 and b == 2)
+            assert(not pcall(core.with_unsafe, "unknown", function() end))
+            assert(not pcall(core.with_unsafe, "memory_read", error, "inner"))
+        "#;
+        vm.load_script(script).unwrap();
+
+        let requested = capability::requested_capabilities(vm.lua());
+        assert_eq!(
+            requested,
+            vec![UnsafeCapability::MemoryRead, UnsafeCapability::MemoryWrite]
+        );
+        assert!(!capability::has_capability(
+            vm.lua(),
+            UnsafeCapability::MemoryWrite
+        ));
+    }
+
+    #[test]
+    fn test_capability_ignores_script_globals() {
+        let name = "virtual:test_capability_globals.lua";
+        let vm = LuaVM::new_with_libs(name).unwrap();
+        capability::set_allowed(name, UnsafeCapability::MemoryWrite, false);
+
+        let script = r#"
+            _name = "other.lua"
+            _unsafe_grants = { memory_write = 1, memory_read = 1 }
+            _unsafe_mode = true
+            denied = not pcall(core.with_unsafe, "memory_write", function() end)
+        "#;
+        let result = vm.load_script(script);
+        capability::set_allowed(name, UnsafeCapability::MemoryWrite, true);
+        result.unwrap();
+
+        assert!(vm.lua().globals().get::<bool>("denied").unwrap());
+        assert!(!capability::has_capability(
+            vm.lua(),
+            UnsafeCapability::MemoryWrite
+        ));
+        assert!(!capability::has_capability(
+            vm.lua(),
+            UnsafeCapability::MemoryRead
+        ));
+        // 只有通过 with_unsafe 申请的权限会显示在界面中
+        assert_eq!(
+            capability::requested_capabilities(vm.lua()),
+            vec![UnsafeCapability::MemoryWrite]
+        );
+    }
+
+    #[test]
+    fn test_debounce_throttle() {
+        let vm = LuaVM::new_with_libs("virtual:test_debounce_throttle.lua").unwrap();
+
+        let script = r#"
+            debounced_count, throttled_count = 0, 0
+            local debounced = utils.debounce(function(n) debounced_count = n end, 10)
+            local throttled = utils.throttle(function() throttled_count = throttled_count + 1 end, 10)
+            for i = 1, 3 do
+                debounced(i)
+                throttled()
+            end
+            assert(debounced_count == 0 and throttled_count == 1)
+        "#;
+        vm.load_script(script).unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        library::utility::UtilityModule::tick_timers(vm.lua()).unwrap();
+
+        vm.load_script("assert(debounced_count == 3 and throttled_count == 2)")
+            .unwrap();
+    }
+
+    #[test]
+    fn test_timer_every_and_cancel() {
+        let vm = LuaVM::new_with_libs("virtual:test_timer.lua").unwrap();
+        let script = r#"
+            once_count, every_count = 0, 0
+            sdk.Timer.after(0, function() once_count = once_count + 1 end)
+            every_handle = sdk.Timer.every(0.001, function() every_count = every_count + 1 end)
+        "#;
+        vm.load_script(script).unwrap();
+
+        for _ in 0..2 {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            library::utility::UtilityModule::tick_timers(vm.lua()).unwrap();
+        }
+        vm.load_script("assert(once_count == 1 and every_count == 2)")
+            .unwrap();
+        vm.load_script("assert(sdk.Timer.cancel(every_handle))")
+            .unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        library::utility::UtilityModule::tick_timers(vm.lua()).unwrap();
+        vm.load_script("assert(every_count == 2)").unwrap();
+    }
+
+    #[test]
+    fn test_async_wait_frames() {
+        let vm = LuaVM::new_with_libs("virtual:test_async.lua").unwrap();
+        let script = r#"
+            steps = 0
+            core.async(function()
+                steps = 1
+                core.wait_frames(2)
+                steps = 2
+            end)
+            assert(steps == 1)
+            assert(not pcall(core.wait_frames, 1))
+        "#;
+        vm.load_script(script).unwrap();
+
+        library::runtime::RuntimeModule::tick_coroutines(vm.lua()).unwrap();
+        vm.load_script("assert(steps == 1)").unwrap();
+        library::runtime::RuntimeModule::tick_coroutines(vm.lua()).unwrap();
+        vm.load_script("assert(steps == 2)").unwrap();
+    }
+
+    #[test]
+    fn test_memory_compare_crc32() {
+        let vm = LuaVM::new_with_libs("virtual:test_memory_compare.lua").unwrap();
+
+        let data_a = b"hello world".to_vec();
+        let data_b = b"hello_world".to_vec();
+        let globals = vm.lua().globals();
+        globals
+            .set(
+                "ptr_a",
+                library::sdk::luaptr::LuaPtr::new(data_a.as_ptr() as u64),
+            )
+            .unwrap();
+        globals
+            .set(
+                "ptr_b",
+                library::sdk::luaptr::LuaPtr::new(data_b.as_ptr() as u64),
+            )
+            .unwrap();
+
+        let script = r#"
+            local equal, offset = sdk.Memory.compare(ptr_a, ptr_b, 11)
+            assert(not equal and offset == 5)
+            assert(sdk.Memory.compare(ptr_a, ptr_b, 5))
+            assert(sdk.Memory.crc32(ptr_a, 11) == 0x0D4A1185)
+            assert(utils.crc32("hello world") == 0x0D4A1185)
+        "#;
+        vm.load_script(script).unwrap();
+    }
+
+    #[test]
+    fn test_memory_read_string() {
+        let vm = LuaVM::new_with_libs("virtual:test_memory_read_string.lua").unwrap();
+
+        let utf8 = b"hello\0world".to_vec();
+        let utf16 = "\u{4f60}\u{597d}\0x"
+            .encode_utf16()
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect::<Vec<_>>();
+        let integers = [0xFEu8, 0xFF, 0xFF, 0xFF];
+        let globals = vm.lua().globals();
+        for (name, ptr) in [
+            ("ptr_utf8", utf8.as_ptr()),
+            ("ptr_utf16", utf16.as_ptr()),
+            ("ptr_int", integers.as_ptr()),
+        ] {
+            globals
+                .set(name, library::sdk::luaptr::LuaPtr::new(ptr as u64))
+                .unwrap();
+        }
+
+        let script = r#"
+            assert(ptr_utf8:read_string() == "hello")
+            assert(ptr_utf8:read_string(3) == "hel")
+            assert(sdk.Memory.read_string(ptr_utf8, 0) == "")
+            assert(ptr_utf16:read_string(nil, "utf16") == "\u{4f60}\u{597d}")
+            assert(ptr_utf16:read_string(3, "utf16") == "\u{4f60}")
+
+            assert(ptr_int:read_i8() == 0xFE)
+            assert(ptr_int:read_i32() == 0xFFFFFFFE)
+            assert(sdk.Memory.read_i16(ptr_int) == 0xFFFE)
+            assert(ptr_int:read_signed(1) == -2)
+            assert(sdk.Memory.read_signed(ptr_int, 4) == -2)
+        "#;
+        vm.load_script(script).unwrap();
+    }
+
     #[test]
     fn test_manager_auto_load() {
         init_logging();
@@ -464,4 +1380,15 @@ mod tests {
         manager.auto_load_vms("./test_files").unwrap();
         manager.reload_physical_vms().unwrap();
     }
+
+    #[test]
+    fn test_canonical_script_path() {
+        let a = canonical_script_path(Path::new("./src/missing.lua"));
+        let b = canonical_script_path(Path::new("./src/../src/missing.lua"));
+        assert_eq!(a, b);
+        assert!(a.is_absolute());
+
+        let other = canonical_script_path(Path::new("./src/luavm/missing.lua"));
+        assert_ne!(a, other);
+    }
 }
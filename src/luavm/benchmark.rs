@@ -0,0 +1,221 @@
+//! 性能基准测试
+//!
+//! 在当前机器上测量特征码扫描吞吐量、Hook 分发延迟、FFI 调用开销和 Lua 回调开销，
+//! 为不同版本之间的性能比较提供可参照的数据。
+//! 通过聊天命令 `luaf bench` 运行，结果输出到日志。
+
+use std::{
+    fmt,
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use mlua::prelude::*;
+use rand::RngCore;
+
+use super::{
+    LuaVMManager,
+    library::sdk::ffi_call::{self, Argument, ArgumentType},
+};
+use crate::memory::MemoryUtils;
+
+/// 特征码扫描的数据大小
+const SCAN_BUFFER_SIZE: usize = 16 * 1024 * 1024;
+const SCAN_ITERATIONS: u32 = 4;
+const SCAN_PATTERN: &str = "48 8B 05 ?? ?? ?? ?? 48 85 C0 74 ?? E8";
+const SCAN_PATTERN_BYTES: &[u8] = &[
+    0x48, 0x8B, 0x05, 0x11, 0x22, 0x33, 0x44, 0x48, 0x85, 0xC0, 0x74, 0x55, 0xE8,
+];
+
+const CALL_ITERATIONS: u32 = 100_000;
+const FFI_ITERATIONS: u32 = 10_000;
+
+const BENCHMARK_VM_NAME: &str = "benchmark";
+
+/// 单项测试结果
+pub struct BenchmarkResult {
+    pub name: &'static str,
+    pub outcome: Result<Measurement, String>,
+}
+
+pub struct Measurement {
+    pub iterations: u32,
+    pub elapsed: Duration,
+    /// 附加说明，如吞吐量
+    pub detail: Option<String>,
+}
+
+impl Measurement {
+    fn new(iterations: u32, elapsed: Duration) -> Self {
+        Self {
+            iterations,
+            elapsed,
+            detail: None,
+        }
+    }
+
+    pub fn nanos_per_iter(&self) -> f64 {
+        self.elapsed.as_nanos() as f64 / self.iterations.max(1) as f64
+    }
+}
+
+pub struct BenchmarkReport {
+    pub results: Vec<BenchmarkResult>,
+}
+
+impl fmt::Display for BenchmarkReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "LuaFramework v{} benchmark", env!("CARGO_PKG_VERSION"))?;
+        for result in self.results.iter() {
+            match &result.outcome {
+                Ok(m) => {
+                    write!(
+                        f,
+                        "  {:<16} {:>12.1} ns/iter ({} iters, {:.2} ms)",
+                        result.name,
+                        m.nanos_per_iter(),
+                        m.iterations,
+                        m.elapsed.as_secs_f64() * 1000.0
+                    )?;
+                    if let Some(detail) = &m.detail {
+                        write!(f, ", {}", detail)?;
+                    }
+                    writeln!(f)?;
+                }
+                Err(e) => writeln!(f, "  {:<16} skipped: {}", result.name, e)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 运行所有测试
+///
+/// 需要在游戏主线程调用，Hook 分发测试会在临时虚拟机中安装 Hook。
+pub fn run() -> BenchmarkReport {
+    let results = vec![
+        BenchmarkResult {
+            name: "pattern_scan",
+            outcome: bench_pattern_scan(),
+        },
+        BenchmarkResult {
+            name: "lua_callback",
+            outcome: bench_lua_callback().map_err(|e| e.to_string()),
+        },
+        BenchmarkResult {
+            name: "hook_dispatch",
+            outcome: bench_hook_dispatch().map_err(|e| e.to_string()),
+        },
+        BenchmarkResult {
+            name: "ffi_call",
+            outcome: bench_ffi_call(),
+        },
+    ];
+
+    BenchmarkReport { results }
+}
+
+/// Hook 和原生调用的目标函数
+///
+/// 使用栈上数组，保证函数有完整的序言，长度足够写入跳转指令而不覆盖相邻函数。
+#[inline(never)]
+extern "C" fn bench_target(a: i32) -> i32 {
+    let mut values = [a; 8];
+    black_box(&mut values);
+    values.iter().fold(0, |acc: i32, v| acc.wrapping_add(*v))
+}
+
+fn bench_pattern_scan() -> Result<Measurement, String> {
+    // 随机数据，特征码位于末尾，扫描整个缓冲区
+    let mut buffer = vec![0u8; SCAN_BUFFER_SIZE];
+    rand::rng().fill_bytes(&mut buffer);
+    let pattern_offset = SCAN_BUFFER_SIZE - SCAN_PATTERN_BYTES.len();
+    buffer[pattern_offset..].copy_from_slice(SCAN_PATTERN_BYTES);
+
+    let start = Instant::now();
+    for _ in 0..SCAN_ITERATIONS {
+        MemoryUtils::scan_first(
+            black_box(buffer.as_ptr() as usize),
+            buffer.len(),
+            SCAN_PATTERN,
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    let elapsed = start.elapsed();
+
+    let total_mib = (SCAN_BUFFER_SIZE as f64 * SCAN_ITERATIONS as f64) / (1024.0 * 1024.0);
+    let mut measurement = Measurement::new(SCAN_ITERATIONS, elapsed);
+    measurement.detail = Some(format!(
+        "{:.1} MiB/s",
+        total_mib / elapsed.as_secs_f64().max(f64::EPSILON)
+    ));
+    Ok(measurement)
+}
+
+fn bench_lua_callback() -> LuaResult<Measurement> {
+    let lua = Lua::new();
+    let callback = lua
+        .load("return function(a) return a end")
+        .eval::<LuaFunction>()?;
+
+    let start = Instant::now();
+    for i in 0..CALL_ITERATIONS {
+        black_box(callback.call::<i32>(i as i32)?);
+    }
+    Ok(Measurement::new(CALL_ITERATIONS, start.elapsed()))
+}
+
+fn bench_hook_dispatch() -> LuaResult<Measurement> {
+    let target: extern "C" fn(i32) -> i32 = black_box(bench_target);
+
+    // 未 Hook 时的调用耗时
+    let start = Instant::now();
+    for i in 0..CALL_ITERATIONS {
+        black_box(target(i as i32));
+    }
+    let baseline = start.elapsed();
+
+    let manager = LuaVMManager::instance();
+    let luavm = manager.create_virtual_vm(BENCHMARK_VM_NAME);
+    let result = (|| -> LuaResult<Duration> {
+        luavm
+            .lua()
+            .load(format!(
+                "sdk.Interceptor.attach(sdk.LuaPtr(0x{:x}), {{ on_enter = function(args) end }})",
+                target as usize
+            ))
+            .exec()?;
+
+        let start = Instant::now();
+        for i in 0..CALL_ITERATIONS {
+            black_box(target(i as i32));
+        }
+        Ok(start.elapsed())
+    })();
+    // 移除虚拟机时同时移除 Hook
+    drop(luavm);
+    manager.remove_virtual_vm(BENCHMARK_VM_NAME);
+
+    let hooked = result?;
+    let mut measurement = Measurement::new(CALL_ITERATIONS, hooked.saturating_sub(baseline));
+    measurement.detail = Some("excluding unhooked call".to_string());
+    Ok(measurement)
+}
+
+fn bench_ffi_call() -> Result<Measurement, String> {
+    if !ffi_call::is_ffi_available() {
+        return Err("luaf_libffi extension is not loaded".to_string());
+    }
+
+    let start = Instant::now();
+    for i in 0..FFI_ITERATIONS {
+        ffi_call::call_native_function(
+            bench_target as usize as u64,
+            vec![Argument::SInt32(i as i32)],
+            Some(ArgumentType::SInt32),
+            false,
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(Measurement::new(FFI_ITERATIONS, start.elapsed()))
+}
@@ -0,0 +1,188 @@
+//! 不安全模式权限
+//!
+//! 脚本通过 `core.with_unsafe(capability, fn)` 在回调期间临时获取权限，
+//! 用户可在 Script Manager 中为每个脚本单独禁用某项权限。
+//!
+//! 授权状态和脚本名保存在虚拟机的 app data 中，脚本无法通过修改全局变量绕过检查。
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use mlua::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Config, error::Error, luavm::ScriptIdentity};
+
+/// 虚拟机的授权状态
+#[derive(Default)]
+struct CapabilityState {
+    /// 当前持有的权限 -> 嵌套授权次数
+    grants: BTreeMap<UnsafeCapability, u32>,
+    /// 脚本申请过的权限，用于界面展示
+    requested: BTreeSet<UnsafeCapability>,
+    /// 通过 core.unsafe_mode(true) 授予全部权限
+    unsafe_mode: bool,
+}
+
+impl CapabilityState {
+    /// 修改虚拟机的授权状态，闭包中不能调用 Lua
+    fn update<R>(lua: &Lua, f: impl FnOnce(&mut CapabilityState) -> R) -> R {
+        if lua.app_data_ref::<CapabilityState>().is_none() {
+            lua.set_app_data(CapabilityState::default());
+        }
+        let mut state = lua.app_data_mut::<CapabilityState>().unwrap();
+        f(&mut state)
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    strum::EnumIter,
+    strum::EnumString,
+    strum::IntoStaticStr,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum UnsafeCapability {
+    /// 跳过读取内存时的权限检查
+    MemoryRead,
+    /// 跳过写入内存时的权限检查
+    MemoryWrite,
+    /// 直接控制游戏对象，如怪物、生成物品
+    GameControl,
+}
+
+impl UnsafeCapability {
+    pub fn name(self) -> &'static str {
+        self.into()
+    }
+
+    fn parse(name: &str) -> LuaResult<Self> {
+        name.parse::<Self>()
+            .map_err(|_| Error::InvalidValue("unsafe capability", name.to_string()).into_lua_err())
+    }
+}
+
+/// 用户是否允许脚本使用该权限
+pub fn is_allowed(script_name: &str, capability: UnsafeCapability) -> bool {
+    !Config::global()
+        .scripts
+        .denied_capabilities
+        .get(script_name)
+        .is_some_and(|denied| denied.contains(&capability))
+}
+
+/// 设置用户是否允许脚本使用该权限
+pub fn set_allowed(script_name: &str, capability: UnsafeCapability, allowed: bool) {
+    let mut config = Config::global_mut();
+    let denied_map = &mut config.scripts.denied_capabilities;
+    let denied = denied_map.entry(script_name.to_string()).or_default();
+    if allowed {
+        denied.retain(|c| *c != capability);
+    } else if !denied.contains(&capability) {
+        denied.push(capability);
+        denied.sort();
+    }
+    if denied.is_empty() {
+        denied_map.remove(script_name);
+    }
+}
+
+/// 脚本当前是否持有该权限
+pub fn has_capability(lua: &Lua, capability: UnsafeCapability) -> bool {
+    let allowed = match lua.app_data_ref::<ScriptIdentity>() {
+        Some(identity) => is_allowed(&identity.name, capability),
+        None => is_allowed(ScriptIdentity::UNKNOWN_NAME, capability),
+    };
+    if !allowed {
+        return false;
+    }
+    if Config::global().scripts.disable_unsafe_online && crate::game::network::is_online() {
+        return false;
+    }
+
+    let Some(state) = lua.app_data_ref::<CapabilityState>() else {
+        return false;
+    };
+    // 兼容 core.unsafe_mode(true)，授予全部权限
+    state.unsafe_mode
+        || state
+            .grants
+            .get(&capability)
+            .is_some_and(|count| *count > 0)
+}
+
+/// 检查是否持有权限，未持有时返回错误
+pub fn ensure_capability(
+    lua: &Lua,
+    capability: UnsafeCapability,
+    api_name: &'static str,
+) -> LuaResult<()> {
+    if !has_capability(lua, capability) {
+        return Err(Error::UnsafeModeRequired(api_name, capability.name()).into_lua_err());
+    }
+    Ok(())
+}
+
+/// 脚本申请过的权限
+pub fn requested_capabilities(lua: &Lua) -> Vec<UnsafeCapability> {
+    lua.app_data_ref::<CapabilityState>()
+        .map(|state| state.requested.iter().copied().collect())
+        .unwrap_or_default()
+}
+
+/// `core.with_unsafe(capability, fn, ...)`：在回调执行期间持有权限
+pub fn with_unsafe(
+    lua: &Lua,
+    (name, fun, args): (String, LuaFunction, LuaMultiValue),
+) -> LuaResult<LuaMultiValue> {
+    let capability = UnsafeCapability::parse(&name)?;
+    CapabilityState::update(lua, |state| state.requested.insert(capability));
+
+    let script_name = ScriptIdentity::name_of(lua);
+    if !is_allowed(&script_name, capability) {
+        return Err(Error::CapabilityDenied(capability.name(), script_name).into_lua_err());
+    }
+
+    CapabilityState::update(lua, |state| {
+        *state.grants.entry(capability).or_default() += 1
+    });
+
+    let result = fun.call::<LuaMultiValue>(args);
+
+    CapabilityState::update(lua, |state| {
+        if let Some(count) = state.grants.get_mut(&capability) {
+            *count -= 1;
+            if *count == 0 {
+                state.grants.remove(&capability);
+            }
+        }
+    });
+
+    result
+}
+
+/// 清空虚拟机的授权状态，复用虚拟机时调用
+pub(crate) fn reset(lua: &Lua) {
+    lua.remove_app_data::<CapabilityState>();
+}
+
+/// `core.unsafe_mode(enable)`：授予或撤销全部权限
+pub fn set_unsafe_mode(lua: &Lua, enable: bool) -> LuaResult<()> {
+    CapabilityState::update(lua, |state| {
+        if enable {
+            state
+                .requested
+                .extend(<UnsafeCapability as strum::IntoEnumIterator>::iter());
+        }
+        state.unsafe_mode = enable;
+    });
+    Ok(())
+}
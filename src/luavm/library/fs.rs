@@ -4,9 +4,13 @@ use mlua::prelude::*;
 
 use crate::error::Error;
 
-use super::LuaModule;
+use super::{
+    LuaModule,
+    userdata_serde::{decode_userdata, encode_userdata},
+};
 
 const FS_BASE_PATH: &str = "lua_framework/data";
+const ASSETS_DIR_NAME: &str = "assets";
 
 pub struct FSModule;
 
@@ -120,12 +124,43 @@ impl LuaModule for FSModule {
 
         registry.set("toml", toml_table)?;
 
+        // 脚本资源文件
+        let fs_table = lua.create_table()?;
+        fs_table.set(
+            "read_asset",
+            lua.create_function(|lua, path: String| {
+                let full_path = create_asset_path(lua, &path)?;
+                let content = std::fs::read(&full_path).map_err(|e| {
+                    Error::IoWithContext(
+                        e,
+                        format!("fs.read_asset: open file {}", full_path.display()),
+                    )
+                    .into_lua_err()
+                })?;
+
+                lua.create_string(content)
+            })?,
+        )?;
+        fs_table.set(
+            "asset_exists",
+            lua.create_function(|lua, path: String| Ok(create_asset_path(lua, &path)?.is_file()))?,
+        )?;
+
+        registry.set("fs", fs_table)?;
+
         Ok(())
     }
 }
 
 /// Check and create valid absolute path.
-fn create_abs_path(path: impl AsRef<Path>) -> LuaResult<PathBuf> {
+pub fn create_abs_path(path: impl AsRef<Path>) -> LuaResult<PathBuf> {
+    create_abs_path_in(FS_BASE_PATH, path)
+}
+
+/// Check and create valid path under `base`.
+///
+/// Absolute paths and parent dir components are rejected.
+pub fn create_abs_path_in(base: impl AsRef<Path>, path: impl AsRef<Path>) -> LuaResult<PathBuf> {
     if path.as_ref().is_absolute() {
         return Err(Error::PathNotAllowed("path is absolute".to_string()).into_lua_err());
     }
@@ -137,11 +172,41 @@ fn create_abs_path(path: impl AsRef<Path>) -> LuaResult<PathBuf> {
         }
     }
 
-    let abs_path = Path::new(FS_BASE_PATH).join(path.as_ref());
+    let abs_path = base.as_ref().join(path.as_ref());
     Ok(abs_path)
 }
 
-fn create_dirs(path: &Path) -> LuaResult<()> {
+/// 获取脚本资源目录 `<脚本目录>/<脚本名>/assets`
+pub fn script_asset_dir(lua: &Lua) -> LuaResult<PathBuf> {
+    let globals = lua.globals();
+    let name = globals.get::<String>("_name")?;
+    let Ok(script_dir) = globals.get::<String>("_script_dir") else {
+        return Err(Error::AssetDirUnavailable(name).into_lua_err());
+    };
+
+    let stem = Path::new(&name)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or(name);
+    Ok(Path::new(&script_dir).join(stem).join(ASSETS_DIR_NAME))
+}
+
+/// Check and create valid path under the script directory.
+pub fn create_script_path(lua: &Lua, path: impl AsRef<Path>) -> LuaResult<PathBuf> {
+    let globals = lua.globals();
+    let Ok(script_dir) = globals.get::<String>("_script_dir") else {
+        let name = globals.get::<String>("_name")?;
+        return Err(Error::ScriptDirUnavailable(name).into_lua_err());
+    };
+    create_abs_path_in(script_dir, path)
+}
+
+/// Check and create valid path under the script asset directory.
+pub fn create_asset_path(lua: &Lua, path: impl AsRef<Path>) -> LuaResult<PathBuf> {
+    create_abs_path_in(script_asset_dir(lua)?, path)
+}
+
+pub fn create_dirs(path: &Path) -> LuaResult<()> {
     let Some(parent) = path.parent() else {
         return Ok(());
     };
@@ -154,13 +219,13 @@ fn create_dirs(path: &Path) -> LuaResult<()> {
 }
 
 fn value_to_json_string(lua: &Lua, value: LuaValue) -> LuaResult<String> {
-    let json_value: serde_json::Value = lua.from_value(value)?;
+    let json_value: serde_json::Value = lua.from_value(encode_userdata(lua, value)?)?;
     let json_string = serde_json::to_string(&json_value).map_err(|e| e.into_lua_err())?;
     Ok(json_string)
 }
 
 fn value_to_json_string_pretty(lua: &Lua, value: LuaValue) -> LuaResult<String> {
-    let json_value: serde_json::Value = lua.from_value(value)?;
+    let json_value: serde_json::Value = lua.from_value(encode_userdata(lua, value)?)?;
     let json_string = serde_json::to_string_pretty(&json_value).map_err(|e| e.into_lua_err())?;
     Ok(json_string)
 }
@@ -169,17 +234,17 @@ fn json_string_to_value(lua: &Lua, json_string: String) -> LuaResult<LuaValue> {
     let json_value: serde_json::Value =
         serde_json::from_str(&json_string).map_err(|e| e.into_lua_err())?;
     let lua_value = lua.to_value(&json_value)?;
-    Ok(lua_value)
+    decode_userdata(lua, lua_value)
 }
 
 fn value_to_toml_string(lua: &Lua, value: LuaValue) -> LuaResult<String> {
-    let toml_value: serde_json::Value = lua.from_value(value)?;
+    let toml_value: serde_json::Value = lua.from_value(encode_userdata(lua, value)?)?;
     let toml_string = toml::to_string(&toml_value).map_err(|e| e.into_lua_err())?;
     Ok(toml_string)
 }
 
 fn value_to_toml_string_pretty(lua: &Lua, value: LuaValue) -> LuaResult<String> {
-    let toml_value: serde_json::Value = lua.from_value(value)?;
+    let toml_value: serde_json::Value = lua.from_value(encode_userdata(lua, value)?)?;
     let toml_string = toml::to_string_pretty(&toml_value).map_err(|e| e.into_lua_err())?;
     Ok(toml_string)
 }
@@ -188,5 +253,5 @@ fn toml_string_to_value(lua: &Lua, toml_string: String) -> LuaResult<LuaValue> {
     let toml_value: serde_json::Value =
         toml::from_str(&toml_string).map_err(|e| e.into_lua_err())?;
     let lua_value = lua.to_value(&toml_value)?;
-    Ok(lua_value)
+    decode_userdata(lua, lua_value)
 }
@@ -2,6 +2,8 @@ pub mod fs;
 pub mod render;
 pub mod runtime;
 pub mod sdk;
+pub mod state_dump;
+pub mod userdata_serde;
 pub mod utility;
 
 pub trait LuaModule {
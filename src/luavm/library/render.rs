@@ -1,20 +1,71 @@
-use std::ffi::CString;
+use std::{collections::HashSet, ffi::CString, sync::LazyLock};
 
 use super::LuaModule;
+use input_text::InputTextCallbacks;
+use plot::{PlotBuffer, PlotKind};
+use texture::LuaTexture;
 
 use crate::config::Config;
+use crate::error::Error;
+use crate::render_core::{RenderManager, texture::TextureManager};
+use cimgui::FontGlyphRanges;
 use cimgui::sys::traits::Zero;
 use mlua::prelude::*;
+use parking_lot::Mutex;
+
+mod draw;
+mod glyph;
+mod input_text;
+mod plot;
+mod texture;
+
+/// 脚本字体目录
+const FONT_BASE_PATH: &str = "lua_framework/fonts";
+
+/// 已分配的自定义字形范围，相同内容复用同一份数据
+static GLYPH_RANGES: LazyLock<Mutex<HashSet<&'static [u32]>>> =
+    LazyLock::new(|| Mutex::new(HashSet::new()));
 
 pub struct RenderModule;
 
 impl LuaModule for RenderModule {
-    fn register_library(_lua: &mlua::Lua, registry: &mlua::Table) -> mlua::Result<()> {
+    fn register_library(lua: &mlua::Lua, registry: &mlua::Table) -> mlua::Result<()> {
         registry.set("imgui", LuaImgui)?;
+        registry.set("draw", draw::create_draw_table(lua)?)?;
         Ok(())
     }
 }
 
+impl RenderModule {
+    /// 绘制框架管理的主菜单栏
+    ///
+    /// menus: (菜单标题, 脚本名, 回调)，每个脚本的菜单项在各自的菜单中绘制。
+    pub fn draw_main_menu_bar(menus: &[(String, String, LuaFunction)]) {
+        unsafe {
+            if !cimgui::sys::igBeginMainMenuBar() {
+                return;
+            }
+
+            for (title, script_name, fun) in menus {
+                let Ok(title) = CString::new(title.as_str()) else {
+                    continue;
+                };
+                if !cimgui::sys::igBeginMenu(title.as_ptr(), true) {
+                    continue;
+                }
+                if let Err(e) = fun.call::<()>(()) {
+                    let err_msg = format!("`on_menu` in LuaVM({}) error:\n{}", script_name, e);
+                    crate::error::set_last_error(err_msg.clone());
+                    log::error!("{}", err_msg);
+                }
+                cimgui::sys::igEndMenu();
+            }
+
+            cimgui::sys::igEndMainMenuBar();
+        }
+    }
+}
+
 pub struct LuaImgui;
 
 impl LuaUserData for LuaImgui {
@@ -75,36 +126,318 @@ impl LuaUserData for LuaImgui {
                 Ok(())
             },
         );
+        // 返回 (是否修改, 新文本)，与 checkbox、combo 一致
         methods.add_function(
             "input_text",
-            |_, (label, value, flags): (CString, CString, Option<i32>)| unsafe {
-                let flags = flags.unwrap_or(0);
-                const BUF_SIZE: usize = 1024;
-                let mut buf: [u8; BUF_SIZE] = [0; BUF_SIZE];
-                let value_bytes = value.as_bytes();
-                buf[..value_bytes.len()].copy_from_slice(value_bytes);
-
-                cimgui::sys::igInputText(
-                    label.as_ptr(),
-                    buf.as_mut_ptr() as *mut i8,
-                    BUF_SIZE,
-                    flags,
+            |lua,
+             (label, value, flags, callbacks): (
+                CString,
+                LuaString,
+                Option<i32>,
+                Option<InputTextCallbacks>,
+            )| {
+                input_text::input_text(
+                    lua,
+                    &label,
+                    &value.as_bytes(),
+                    flags.unwrap_or(0),
                     None,
-                    std::ptr::null_mut(),
+                    callbacks.as_ref(),
+                )
+            },
+        );
+        // 多行输入框，size 为 {0, 0} 时使用默认尺寸
+        methods.add_function(
+            "input_text_multiline",
+            |lua,
+             (label, value, size, flags, callbacks): (
+                CString,
+                LuaString,
+                Option<ImVec2>,
+                Option<i32>,
+                Option<InputTextCallbacks>,
+            )| {
+                input_text::input_text(
+                    lua,
+                    &label,
+                    &value.as_bytes(),
+                    flags.unwrap_or(0),
+                    Some(size.unwrap_or_default()),
+                    callbacks.as_ref(),
+                )
+            },
+        );
+        // 以下控件返回 (是否修改, 新值)，format 为显示格式，flags 为 ImGuiSliderFlags
+        methods.add_function(
+            "slider_float",
+            |_,
+             (label, value, min, max, format, flags): (
+                CString,
+                f32,
+                f32,
+                f32,
+                Option<CString>,
+                Option<i32>,
+            )| unsafe {
+                let mut value = value;
+                let changed = cimgui::sys::igSliderFloat(
+                    label.as_ptr(),
+                    &mut value,
+                    min,
+                    max,
+                    format.as_deref().unwrap_or(c"%.3f").as_ptr(),
+                    flags.unwrap_or(0),
                 );
-
-                let new_value = CString::new(
-                    buf.iter()
-                        .take_while(|&x| *x != 0)
-                        .copied()
-                        .collect::<Vec<u8>>(),
+                Ok((changed, value))
+            },
+        );
+        methods.add_function(
+            "slider_int",
+            |_,
+             (label, value, min, max, format, flags): (
+                CString,
+                i32,
+                i32,
+                i32,
+                Option<CString>,
+                Option<i32>,
+            )| unsafe {
+                let mut value = value;
+                let changed = cimgui::sys::igSliderInt(
+                    label.as_ptr(),
+                    &mut value,
+                    min,
+                    max,
+                    format.as_deref().unwrap_or(c"%d").as_ptr(),
+                    flags.unwrap_or(0),
+                );
+                Ok((changed, value))
+            },
+        );
+        // min 和 max 均为 0 时不限制范围
+        methods.add_function(
+            "drag_float",
+            |_,
+             (label, value, speed, min, max, format, flags): (
+                CString,
+                f32,
+                Option<f32>,
+                Option<f32>,
+                Option<f32>,
+                Option<CString>,
+                Option<i32>,
+            )| unsafe {
+                let mut value = value;
+                let changed = cimgui::sys::igDragFloat(
+                    label.as_ptr(),
+                    &mut value,
+                    speed.unwrap_or(1.0),
+                    min.unwrap_or(0.0),
+                    max.unwrap_or(0.0),
+                    format.as_deref().unwrap_or(c"%.3f").as_ptr(),
+                    flags.unwrap_or(0),
+                );
+                Ok((changed, value))
+            },
+        );
+        methods.add_function(
+            "drag_int",
+            |_,
+             (label, value, speed, min, max, format, flags): (
+                CString,
+                i32,
+                Option<f32>,
+                Option<i32>,
+                Option<i32>,
+                Option<CString>,
+                Option<i32>,
+            )| unsafe {
+                let mut value = value;
+                let changed = cimgui::sys::igDragInt(
+                    label.as_ptr(),
+                    &mut value,
+                    speed.unwrap_or(1.0),
+                    min.unwrap_or(0),
+                    max.unwrap_or(0),
+                    format.as_deref().unwrap_or(c"%d").as_ptr(),
+                    flags.unwrap_or(0),
+                );
+                Ok((changed, value))
+            },
+        );
+        // 颜色分量范围为 0~1，flags 为 ImGuiColorEditFlags
+        methods.add_function(
+            "color_edit3",
+            |_, (label, color, flags): (CString, [f32; 3], Option<i32>)| unsafe {
+                let mut color = color;
+                let changed = cimgui::sys::igColorEdit3(
+                    label.as_ptr(),
+                    color.as_mut_ptr(),
+                    flags.unwrap_or(0),
+                );
+                Ok((changed, color))
+            },
+        );
+        methods.add_function(
+            "color_edit4",
+            |_, (label, color, flags): (CString, ImVec4, Option<i32>)| unsafe {
+                let mut color = color;
+                let changed =
+                    cimgui::sys::igColorEdit4(label.as_ptr(), &mut color.0.x, flags.unwrap_or(0));
+                Ok((changed, color))
+            },
+        );
+        methods.add_function(
+            "color_picker",
+            |_, (label, color, flags): (CString, ImVec4, Option<i32>)| unsafe {
+                let mut color = color;
+                let changed = cimgui::sys::igColorPicker4(
+                    label.as_ptr(),
+                    &mut color.0.x,
+                    flags.unwrap_or(0),
+                    std::ptr::null(),
+                );
+                Ok((changed, color))
+            },
+        );
+        // 创建容量为 capacity 的环形缓冲区，用于 plot_lines 和 plot_histogram，容量上限为 65536
+        methods.add_function("new_plot_buffer", |_, capacity: usize| {
+            Ok(PlotBuffer::new(capacity))
+        });
+        // values 为 PlotBuffer 或数字数组，scale 为 nil 时根据数据自动计算
+        methods.add_function(
+            "plot_lines",
+            |_,
+             (label, values, overlay, scale_min, scale_max, size): (
+                CString,
+                LuaValue,
+                Option<CString>,
+                Option<f32>,
+                Option<f32>,
+                Option<ImVec2>,
+            )| {
+                plot::plot(
+                    PlotKind::Lines,
+                    &label,
+                    &values,
+                    overlay.as_deref(),
+                    scale_min,
+                    scale_max,
+                    size,
                 )
-                .unwrap();
-
-                Ok(new_value)
             },
         );
-
+        methods.add_function(
+            "plot_histogram",
+            |_,
+             (label, values, overlay, scale_min, scale_max, size): (
+                CString,
+                LuaValue,
+                Option<CString>,
+                Option<f32>,
+                Option<f32>,
+                Option<ImVec2>,
+            )| {
+                plot::plot(
+                    PlotKind::Histogram,
+                    &label,
+                    &values,
+                    overlay.as_deref(),
+                    scale_min,
+                    scale_max,
+                    size,
+                )
+            },
+        );
+        // fraction 范围为 0~1，size 默认占满可用宽度
+        methods.add_function(
+            "progress_bar",
+            |_, (fraction, size, overlay): (f32, Option<ImVec2>, Option<CString>)| unsafe {
+                let size = size.unwrap_or(ImVec2(cimgui::sys::ImVec2 {
+                    x: -f32::MIN_POSITIVE,
+                    y: 0.0,
+                }));
+                cimgui::sys::igProgressBar(
+                    fraction,
+                    *size,
+                    overlay
+                        .as_ref()
+                        .map(|s| s.as_ptr())
+                        .unwrap_or(std::ptr::null()),
+                );
+                Ok(())
+            },
+        );
+        // 加载 lua_framework/data 下的 PNG 或 DDS 图片，纹理在下一帧上传后可用
+        methods.add_function("load_texture", |_, path: String| {
+            let full_path = super::fs::create_abs_path(&path)?;
+            let info = TextureManager::instance().load(&full_path).into_lua_err()?;
+            Ok(LuaTexture::new(info))
+        });
+        // size 默认为纹理尺寸，纹理未上传时只占位
+        methods.add_function(
+            "image",
+            |_,
+             (texture, size, uv0, uv1, tint): (
+                LuaUserDataRef<LuaTexture>,
+                Option<ImVec2>,
+                Option<ImVec2>,
+                Option<ImVec2>,
+                Option<ImVec4>,
+            )| unsafe {
+                let size = size.unwrap_or_else(|| texture.size());
+                let Some(texture_id) = texture.texture_id() else {
+                    cimgui::sys::igDummy(*size);
+                    return Ok(());
+                };
+                cimgui::sys::igImage(
+                    texture_id,
+                    *size,
+                    *uv0.unwrap_or_default(),
+                    *uv1.unwrap_or(ImVec2(cimgui::sys::ImVec2 { x: 1.0, y: 1.0 })),
+                    *tint.unwrap_or(ImVec4(cimgui::sys::ImVec4 {
+                        x: 1.0,
+                        y: 1.0,
+                        z: 1.0,
+                        w: 1.0,
+                    })),
+                    *ImVec4::default(),
+                );
+                Ok(())
+            },
+        );
+        // 返回是否点击，纹理未上传时显示为普通按钮
+        methods.add_function(
+            "image_button",
+            |_,
+             (str_id, texture, size, bg_color, tint): (
+                CString,
+                LuaUserDataRef<LuaTexture>,
+                Option<ImVec2>,
+                Option<ImVec4>,
+                Option<ImVec4>,
+            )| unsafe {
+                let size = size.unwrap_or_else(|| texture.size());
+                let Some(texture_id) = texture.texture_id() else {
+                    return Ok(cimgui::sys::igButton(str_id.as_ptr(), *size));
+                };
+                let pressed = cimgui::sys::igImageButton(
+                    str_id.as_ptr(),
+                    texture_id,
+                    *size,
+                    *ImVec2::default(),
+                    *ImVec2(cimgui::sys::ImVec2 { x: 1.0, y: 1.0 }),
+                    *bg_color.unwrap_or_default(),
+                    *tint.unwrap_or(ImVec4(cimgui::sys::ImVec4 {
+                        x: 1.0,
+                        y: 1.0,
+                        z: 1.0,
+                        w: 1.0,
+                    })),
+                );
+                Ok(pressed)
+            },
+        );
         methods.add_function(
             "same_line",
             |_, (offset_from_start_x, spacing): (Option<f32>, Option<f32>)| unsafe {
@@ -148,9 +481,53 @@ impl LuaUserData for LuaImgui {
         methods.add_function("calc_current_item_width", |_, ()| unsafe {
             Ok(cimgui::sys::igCalcItemWidth())
         });
+        // 注册 lua_framework/fonts 下的字体，size 默认为界面字体大小，必须大于 0，
+        // glyph_ranges 为预设名称或 {{起始, 结束}, ...}，字体在下一帧加载后可用。
+        // 重复注册相同名称、路径和大小的字体不会重新加载
+        methods.add_function(
+            "register_font",
+            |_, (name, path, size, glyph_ranges): (String, String, Option<f32>, Option<LuaValue>)| {
+                let full_path = super::fs::create_abs_path_in(FONT_BASE_PATH, &path)?;
+                let size = size.unwrap_or_else(|| Config::global().ui.font_size);
+                let glyph_ranges = parse_glyph_ranges(glyph_ranges)?;
+                RenderManager::get_mut()
+                    .add_font(&name, full_path, size, glyph_ranges)
+                    .into_lua_err()
+            },
+        );
+        // 字体尚未加载时返回 false，此时不应调用 pop_font
+        methods.add_function("push_font", |_, name: String| unsafe {
+            let Some(font_id) = RenderManager::get().get_font(&name) else {
+                return Ok(false);
+            };
+            cimgui::sys::igPushFont(font_id.0 as *mut cimgui::sys::ImFont);
+            Ok(true)
+        });
+        methods.add_function("pop_font", |_, ()| unsafe {
+            cimgui::sys::igPopFont();
+            Ok(())
+        });
         methods.add_function("get_default_font_size", |_, ()| {
             Ok(Config::global().ui.font_size)
         });
+        // 检查当前字体是否包含文本的所有字形，返回 (是否完整, 缺少的字符)
+        methods.add_function("has_glyphs", |_, text: String| {
+            let missing = glyph::missing_glyphs(&text)
+                .into_iter()
+                .map(String::from)
+                .collect::<Vec<_>>();
+            Ok((missing.is_empty(), missing))
+        });
+        // 将当前字体缺少的字符替换为 replacement（默认为 "?"），避免显示为方块
+        methods.add_function(
+            "sanitize_text",
+            |_, (text, replacement): (String, Option<String>)| {
+                Ok(glyph::sanitize(
+                    &text,
+                    replacement.as_deref().unwrap_or("?"),
+                ))
+            },
+        );
 
         methods.add_function(
             "begin_window",
@@ -216,6 +593,149 @@ impl LuaUserData for LuaImgui {
             Ok(())
         });
 
+        // menu apis
+        methods.add_function("begin_main_menu_bar", |_, ()| unsafe {
+            Ok(cimgui::sys::igBeginMainMenuBar())
+        });
+        methods.add_function("end_main_menu_bar", |_, ()| unsafe {
+            cimgui::sys::igEndMainMenuBar();
+            Ok(())
+        });
+        methods.add_function("begin_menu_bar", |_, ()| unsafe {
+            Ok(cimgui::sys::igBeginMenuBar())
+        });
+        methods.add_function("end_menu_bar", |_, ()| unsafe {
+            cimgui::sys::igEndMenuBar();
+            Ok(())
+        });
+        methods.add_function(
+            "begin_menu",
+            |_, (label, enabled): (CString, Option<bool>)| unsafe {
+                Ok(cimgui::sys::igBeginMenu(
+                    label.as_ptr(),
+                    enabled.unwrap_or(true),
+                ))
+            },
+        );
+        methods.add_function("end_menu", |_, ()| unsafe {
+            cimgui::sys::igEndMenu();
+            Ok(())
+        });
+        // 返回是否点击和点击后的选中状态
+        methods.add_function(
+            "menu_item",
+            |_,
+             (label, shortcut, selected, enabled): (
+                CString,
+                Option<CString>,
+                Option<bool>,
+                Option<bool>,
+            )| unsafe {
+                let mut selected = selected.unwrap_or(false);
+                let clicked = cimgui::sys::igMenuItem_BoolPtr(
+                    label.as_ptr(),
+                    shortcut
+                        .as_ref()
+                        .map(|s| s.as_ptr())
+                        .unwrap_or(std::ptr::null()),
+                    &mut selected,
+                    enabled.unwrap_or(true),
+                );
+                Ok((clicked, selected))
+            },
+        );
+
+        // tab bar apis
+        methods.add_function(
+            "begin_tab_bar",
+            |_, (str_id, flags): (CString, Option<i32>)| unsafe {
+                Ok(cimgui::sys::igBeginTabBar(
+                    str_id.as_ptr(),
+                    flags.unwrap_or(0),
+                ))
+            },
+        );
+        methods.add_function("end_tab_bar", |_, ()| unsafe {
+            cimgui::sys::igEndTabBar();
+            Ok(())
+        });
+        // 传入 open 时显示关闭按钮，返回 (是否选中, 关闭按钮点击后的 open)
+        methods.add_function(
+            "begin_tab_item",
+            |_, (label, open, flags): (CString, Option<bool>, Option<i32>)| unsafe {
+                let mut open = open;
+                let selected = cimgui::sys::igBeginTabItem(
+                    label.as_ptr(),
+                    open.as_mut()
+                        .map(|open| open as *mut bool)
+                        .unwrap_or(std::ptr::null_mut()),
+                    flags.unwrap_or(0),
+                );
+                Ok((selected, open))
+            },
+        );
+        methods.add_function("end_tab_item", |_, ()| unsafe {
+            cimgui::sys::igEndTabItem();
+            Ok(())
+        });
+
+        // popup apis
+        methods.add_function(
+            "open_popup",
+            |_, (str_id, flags): (CString, Option<i32>)| unsafe {
+                cimgui::sys::igOpenPopup_Str(str_id.as_ptr(), flags.unwrap_or(0));
+                Ok(())
+            },
+        );
+        methods.add_function(
+            "begin_popup",
+            |_, (str_id, flags): (CString, Option<i32>)| unsafe {
+                Ok(cimgui::sys::igBeginPopup(
+                    str_id.as_ptr(),
+                    flags.unwrap_or(0),
+                ))
+            },
+        );
+        // 与 begin_tab_item 相同，传入 open 时显示关闭按钮
+        methods.add_function(
+            "begin_popup_modal",
+            |_, (name, open, flags): (CString, Option<bool>, Option<i32>)| unsafe {
+                let mut open = open;
+                let visible = cimgui::sys::igBeginPopupModal(
+                    name.as_ptr(),
+                    open.as_mut()
+                        .map(|open| open as *mut bool)
+                        .unwrap_or(std::ptr::null_mut()),
+                    flags.unwrap_or(0),
+                );
+                Ok((visible, open))
+            },
+        );
+        methods.add_function("close_current_popup", |_, ()| unsafe {
+            cimgui::sys::igCloseCurrentPopup();
+            Ok(())
+        });
+        methods.add_function("end_popup", |_, ()| unsafe {
+            cimgui::sys::igEndPopup();
+            Ok(())
+        });
+
+        // tooltip apis
+        methods.add_function("is_item_hovered", |_, flags: Option<i32>| unsafe {
+            Ok(cimgui::sys::igIsItemHovered(flags.unwrap_or(0)))
+        });
+        methods.add_function("begin_tooltip", |_, ()| unsafe {
+            Ok(cimgui::sys::igBeginTooltip())
+        });
+        methods.add_function("end_tooltip", |_, ()| unsafe {
+            cimgui::sys::igEndTooltip();
+            Ok(())
+        });
+        methods.add_function("set_tooltip", |_, text: CString| unsafe {
+            cimgui::sys::igSetTooltip(c"%s".as_ptr(), text.as_ptr());
+            Ok(())
+        });
+
         // table apis
         methods.add_function(
             "begin_table",
@@ -285,6 +805,64 @@ impl LuaUserData for LuaImgui {
     }
 }
 
+/// 解析字形范围
+///
+/// 自定义范围在字体图集中一直使用，因此不释放；按内容缓存，
+/// 重复注册字体或重载脚本时不会再次分配。
+fn parse_glyph_ranges(value: Option<LuaValue>) -> LuaResult<FontGlyphRanges> {
+    let ranges = match value {
+        None | Some(LuaValue::Nil) => return Ok(FontGlyphRanges::default()),
+        Some(LuaValue::String(preset)) => {
+            return match &*preset.to_str()? {
+                "default" => Ok(FontGlyphRanges::default()),
+                "chinese_full" => Ok(FontGlyphRanges::chinese_full()),
+                "chinese_simplified_common" => Ok(FontGlyphRanges::chinese_simplified_common()),
+                "japanese" => Ok(FontGlyphRanges::japanese()),
+                "korean" => Ok(FontGlyphRanges::korean()),
+                "cyrillic" => Ok(FontGlyphRanges::cyrillic()),
+                "thai" => Ok(FontGlyphRanges::thai()),
+                "vietnamese" => Ok(FontGlyphRanges::vietnamese()),
+                other => {
+                    Err(Error::InvalidValue("glyph range preset", other.to_string()).into_lua_err())
+                }
+            };
+        }
+        Some(LuaValue::Table(table)) => table,
+        Some(other) => {
+            return Err(Error::InvalidValue(
+                "glyph range preset or table",
+                other.type_name().to_string(),
+            )
+            .into_lua_err());
+        }
+    };
+
+    let mut values = Vec::new();
+    for range in ranges.sequence_values::<[u32; 2]>() {
+        let [start, end] = range?;
+        if start == 0 || start > end {
+            return Err(Error::InvalidValue(
+                "glyph range {start, end}",
+                format!("{{{start}, {end}}}"),
+            )
+            .into_lua_err());
+        }
+        values.extend([start, end]);
+    }
+    values.push(0);
+
+    let mut cache = GLYPH_RANGES.lock();
+    let ranges = match cache.get(values.as_slice()) {
+        Some(ranges) => *ranges,
+        None => {
+            let ranges: &'static [u32] = Box::leak(values.into_boxed_slice());
+            cache.insert(ranges);
+            ranges
+        }
+    };
+    Ok(FontGlyphRanges::from_slice(ranges))
+}
+
 #[derive(Clone)]
 pub struct ImVec2(pub cimgui::sys::ImVec2);
 
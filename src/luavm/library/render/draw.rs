@@ -0,0 +1,221 @@
+//! 绘制图元
+//!
+//! 直接在 ImGui 的背景或前景绘制列表上绘制，不需要创建窗口，适用于准星、标记等叠加显示。
+//! 坐标为屏幕坐标，颜色为 `0xAABBGGRR` 格式的整数，可通过 `draw.color` 生成。
+
+use std::ffi::CString;
+
+use cimgui::sys;
+use mlua::prelude::*;
+
+use super::ImVec2;
+use crate::{game::mt_type::MtVector3, render_core::camera};
+
+#[derive(Debug, Clone, Copy)]
+enum DrawTarget {
+    /// 位于所有窗口下方
+    Background,
+    /// 位于所有窗口上方
+    Foreground,
+}
+
+impl DrawTarget {
+    fn draw_list(self) -> *mut sys::ImDrawList {
+        unsafe {
+            match self {
+                DrawTarget::Background => sys::igGetBackgroundDrawList_Nil(),
+                DrawTarget::Foreground => sys::igGetForegroundDrawList_Nil(),
+            }
+        }
+    }
+}
+
+/// 创建 `draw` 表，默认绘制到背景，`draw.foreground` 绘制到前景
+pub fn create_draw_table(lua: &Lua) -> LuaResult<LuaTable> {
+    let background = create_target_table(lua, DrawTarget::Background)?;
+    let foreground = create_target_table(lua, DrawTarget::Foreground)?;
+    background.set("background", &background)?;
+    background.set("foreground", foreground)?;
+    Ok(background)
+}
+
+fn create_target_table(lua: &Lua, target: DrawTarget) -> LuaResult<LuaTable> {
+    let table = lua.create_table()?;
+
+    // 将 0~255 的颜色分量打包为颜色值，alpha 默认为 255
+    table.set(
+        "color",
+        lua.create_function(|_, (r, g, b, a): (u8, u8, u8, Option<u8>)| {
+            Ok(pack_color(r, g, b, a.unwrap_or(u8::MAX)))
+        })?,
+    )?;
+    // 将世界坐标投影到屏幕坐标，返回 {x, y}，位于相机后方时返回 nil
+    table.set(
+        "world_to_screen",
+        lua.create_function(|_, (x, y, z): (f32, f32, f32)| unsafe {
+            let display_size = (*sys::igGetIO()).DisplaySize;
+            Ok(
+                camera::world_to_screen(MtVector3::new(x, y, z), [display_size.x, display_size.y])
+                    .map(|[x, y]| ImVec2(sys::ImVec2 { x, y })),
+            )
+        })?,
+    )?;
+    table.set(
+        "line",
+        lua.create_function(
+            move |_, (p1, p2, color, thickness): (ImVec2, ImVec2, u32, Option<f32>)| unsafe {
+                sys::ImDrawList_AddLine(
+                    target.draw_list(),
+                    *p1,
+                    *p2,
+                    color,
+                    thickness.unwrap_or(1.0),
+                );
+                Ok(())
+            },
+        )?,
+    )?;
+    table.set(
+        "rect",
+        lua.create_function(
+            move |_,
+                  (min, max, color, rounding, thickness): (
+                ImVec2,
+                ImVec2,
+                u32,
+                Option<f32>,
+                Option<f32>,
+            )| unsafe {
+                sys::ImDrawList_AddRect(
+                    target.draw_list(),
+                    *min,
+                    *max,
+                    color,
+                    rounding.unwrap_or(0.0),
+                    0,
+                    thickness.unwrap_or(1.0),
+                );
+                Ok(())
+            },
+        )?,
+    )?;
+    table.set(
+        "rect_filled",
+        lua.create_function(
+            move |_, (min, max, color, rounding): (ImVec2, ImVec2, u32, Option<f32>)| unsafe {
+                sys::ImDrawList_AddRectFilled(
+                    target.draw_list(),
+                    *min,
+                    *max,
+                    color,
+                    rounding.unwrap_or(0.0),
+                    0,
+                );
+                Ok(())
+            },
+        )?,
+    )?;
+    // segments 为 0 时根据半径自动计算
+    table.set(
+        "circle",
+        lua.create_function(
+            move |_,
+                  (center, radius, color, thickness, segments): (
+                ImVec2,
+                f32,
+                u32,
+                Option<f32>,
+                Option<i32>,
+            )| unsafe {
+                sys::ImDrawList_AddCircle(
+                    target.draw_list(),
+                    *center,
+                    radius,
+                    color,
+                    segments.unwrap_or(0),
+                    thickness.unwrap_or(1.0),
+                );
+                Ok(())
+            },
+        )?,
+    )?;
+    table.set(
+        "circle_filled",
+        lua.create_function(
+            move |_, (center, radius, color, segments): (ImVec2, f32, u32, Option<i32>)| unsafe {
+                sys::ImDrawList_AddCircleFilled(
+                    target.draw_list(),
+                    *center,
+                    radius,
+                    color,
+                    segments.unwrap_or(0),
+                );
+                Ok(())
+            },
+        )?,
+    )?;
+    table.set(
+        "text",
+        lua.create_function(
+            move |_, (pos, color, text): (ImVec2, u32, CString)| unsafe {
+                sys::ImDrawList_AddText_Vec2(
+                    target.draw_list(),
+                    *pos,
+                    color,
+                    text.as_ptr(),
+                    std::ptr::null(),
+                );
+                Ok(())
+            },
+        )?,
+    )?;
+    // closed 为 true 时连接首尾，filled 为 true 时填充（仅支持凸多边形）
+    table.set(
+        "poly",
+        lua.create_function(
+            move |_,
+                  (points, color, thickness, closed, filled): (
+                Vec<ImVec2>,
+                u32,
+                Option<f32>,
+                Option<bool>,
+                Option<bool>,
+            )| unsafe {
+                let points = points.into_iter().map(|p| p.0).collect::<Vec<_>>();
+                if points.len() < 2 {
+                    return Ok(());
+                }
+                if filled.unwrap_or(false) {
+                    sys::ImDrawList_AddConvexPolyFilled(
+                        target.draw_list(),
+                        points.as_ptr(),
+                        points.len() as i32,
+                        color,
+                    );
+                } else {
+                    let flags = if closed.unwrap_or(false) {
+                        sys::ImDrawFlags_Closed as i32
+                    } else {
+                        0
+                    };
+                    sys::ImDrawList_AddPolyline(
+                        target.draw_list(),
+                        points.as_ptr(),
+                        points.len() as i32,
+                        color,
+                        flags,
+                        thickness.unwrap_or(1.0),
+                    );
+                }
+                Ok(())
+            },
+        )?,
+    )?;
+
+    Ok(table)
+}
+
+/// 与 ImGui 的 `IM_COL32` 相同
+fn pack_color(r: u8, g: u8, b: u8, a: u8) -> u32 {
+    u32::from_le_bytes([r, g, b, a])
+}
@@ -0,0 +1,45 @@
+//! 字形检查
+//!
+//! 游戏文本（如伤害数字、怪物名称）可能包含当前字体没有的字符，直接绘制会显示为方块。
+//! 绘制前可检查缺失的字符，或将其替换为指定文本。
+
+use cimgui::sys;
+
+/// 当前字体是否包含字符的字形，不使用字体的后备字形
+pub fn has_glyph(ch: char) -> bool {
+    // 空白和控制字符不需要字形
+    if ch.is_control() {
+        return true;
+    }
+    let Ok(wchar) = sys::ImWchar::try_from(ch as u32) else {
+        return false;
+    };
+    unsafe {
+        let font = sys::igGetFont();
+        !font.is_null() && !sys::ImFont_FindGlyphNoFallback(font, wchar).is_null()
+    }
+}
+
+/// 文本中当前字体缺少的字符，去重并保持出现顺序
+pub fn missing_glyphs(text: &str) -> Vec<char> {
+    let mut missing = Vec::new();
+    for ch in text.chars() {
+        if !has_glyph(ch) && !missing.contains(&ch) {
+            missing.push(ch);
+        }
+    }
+    missing
+}
+
+/// 将当前字体缺少的字符替换为 `replacement`
+pub fn sanitize(text: &str, replacement: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if has_glyph(ch) {
+            result.push(ch);
+        } else {
+            result.push_str(replacement);
+        }
+    }
+    result
+}
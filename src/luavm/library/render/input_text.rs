@@ -0,0 +1,225 @@
+//! 文本输入控件
+//!
+//! 缓冲区通过 `ImGuiInputTextFlags_CallbackResize` 随输入增长，不限制文本长度。
+//! 可选的 Lua 回调用于过滤字符、补全、历史记录和编辑后处理，
+//! 回调返回字符串时替换整个文本，可用于格式化或语法检查。
+
+use std::ffi::{CStr, c_char, c_void};
+
+use cimgui::sys;
+use mlua::prelude::*;
+
+use super::ImVec2;
+
+/// 文本输入回调
+pub struct InputTextCallbacks {
+    /// fun(char: string): boolean，返回 false 时丢弃输入的字符
+    char_filter: Option<LuaFunction>,
+    /// fun(text: string, cursor: integer): string|nil，按下 Tab 时调用
+    on_complete: Option<LuaFunction>,
+    /// fun(text: string, direction: "up"|"down"): string|nil，按下上下方向键时调用
+    on_history: Option<LuaFunction>,
+    /// fun(text: string, cursor: integer): string|nil，文本被修改时调用
+    on_edit: Option<LuaFunction>,
+}
+
+impl FromLua for InputTextCallbacks {
+    fn from_lua(value: LuaValue, _: &Lua) -> LuaResult<Self> {
+        let LuaValue::Table(table) = value else {
+            return Err(LuaError::FromLuaConversionError {
+                from: value.type_name(),
+                to: "InputTextCallbacks".to_string(),
+                message: None,
+            });
+        };
+
+        Ok(Self {
+            char_filter: table.get("char_filter")?,
+            on_complete: table.get("on_complete")?,
+            on_history: table.get("on_history")?,
+            on_edit: table.get("on_edit")?,
+        })
+    }
+}
+
+impl InputTextCallbacks {
+    fn flags(&self) -> i32 {
+        let mut flags = 0;
+        if self.char_filter.is_some() {
+            flags |= sys::ImGuiInputTextFlags_CallbackCharFilter as i32;
+        }
+        if self.on_complete.is_some() {
+            flags |= sys::ImGuiInputTextFlags_CallbackCompletion as i32;
+        }
+        if self.on_history.is_some() {
+            flags |= sys::ImGuiInputTextFlags_CallbackHistory as i32;
+        }
+        if self.on_edit.is_some() {
+            flags |= sys::ImGuiInputTextFlags_CallbackEdit as i32;
+        }
+        flags
+    }
+}
+
+/// 多行输入框的尺寸，`None` 表示单行输入框
+pub type MultilineSize = Option<ImVec2>;
+
+/// 绘制文本输入框，返回 (是否修改, 新文本)
+///
+/// 初始文本在第一个 NUL 字节处截断。缓冲区按需扩容，多字节字符不会被截断。
+pub fn input_text(
+    lua: &Lua,
+    label: &CStr,
+    value: &[u8],
+    flags: i32,
+    multiline: MultilineSize,
+    callbacks: Option<&InputTextCallbacks>,
+) -> LuaResult<(bool, LuaString)> {
+    let text_len = value.iter().position(|&b| b == 0).unwrap_or(value.len());
+    // 无效的 UTF-8 序列替换为 U+FFFD，保证返回的文本始终是有效的 UTF-8
+    let text = String::from_utf8_lossy(&value[..text_len]);
+    let mut buf = Vec::with_capacity(text.len() + 1);
+    buf.extend_from_slice(text.as_bytes());
+    buf.push(0);
+
+    let mut state = InputTextState {
+        buf,
+        callbacks,
+        error: None,
+    };
+    let flags = flags
+        | sys::ImGuiInputTextFlags_CallbackResize as i32
+        | callbacks.map(|c| c.flags()).unwrap_or(0);
+
+    let changed = unsafe {
+        let buf_ptr = state.buf.as_mut_ptr() as *mut c_char;
+        let buf_size = state.buf.len();
+        let user_data = &mut state as *mut InputTextState as *mut c_void;
+        match multiline {
+            Some(size) => sys::igInputTextMultiline(
+                label.as_ptr(),
+                buf_ptr,
+                buf_size,
+                *size,
+                flags,
+                Some(input_text_callback),
+                user_data,
+            ),
+            None => sys::igInputText(
+                label.as_ptr(),
+                buf_ptr,
+                buf_size,
+                flags,
+                Some(input_text_callback),
+                user_data,
+            ),
+        }
+    };
+
+    if let Some(e) = state.error {
+        return Err(e);
+    }
+
+    let text_len = state
+        .buf
+        .iter()
+        .position(|&b| b == 0)
+        .unwrap_or(state.buf.len());
+    Ok((changed, lua.create_string(&state.buf[..text_len])?))
+}
+
+struct InputTextState<'a> {
+    buf: Vec<u8>,
+    callbacks: Option<&'a InputTextCallbacks>,
+    /// 回调中的第一个错误，输入框绘制结束后返回
+    error: Option<LuaError>,
+}
+
+impl InputTextState<'_> {
+    unsafe fn handle(&mut self, data: &mut sys::ImGuiInputTextCallbackData) -> LuaResult<i32> {
+        let event = data.EventFlag as i32;
+        if event == sys::ImGuiInputTextFlags_CallbackResize as i32 {
+            self.buf.resize(data.BufSize as usize, 0);
+            data.Buf = self.buf.as_mut_ptr() as *mut c_char;
+            return Ok(0);
+        }
+
+        let Some(callbacks) = self.callbacks else {
+            return Ok(0);
+        };
+
+        if event == sys::ImGuiInputTextFlags_CallbackCharFilter as i32 {
+            let Some(filter) = &callbacks.char_filter else {
+                return Ok(0);
+            };
+            let Some(ch) = char::from_u32(data.EventChar as u32) else {
+                return Ok(1);
+            };
+            let accepted = filter.call::<bool>(ch.to_string())?;
+            return Ok(if accepted { 0 } else { 1 });
+        }
+
+        let text =
+            unsafe { std::slice::from_raw_parts(data.Buf as *const u8, data.BufTextLen as usize) };
+        let text = String::from_utf8_lossy(text).to_string();
+        let replacement = if event == sys::ImGuiInputTextFlags_CallbackCompletion as i32 {
+            call_optional(&callbacks.on_complete, (text, data.CursorPos))?
+        } else if event == sys::ImGuiInputTextFlags_CallbackHistory as i32 {
+            let direction = if data.EventKey == sys::ImGuiKey_UpArrow {
+                "up"
+            } else {
+                "down"
+            };
+            call_optional(&callbacks.on_history, (text, direction))?
+        } else if event == sys::ImGuiInputTextFlags_CallbackEdit as i32 {
+            call_optional(&callbacks.on_edit, (text, data.CursorPos))?
+        } else {
+            None
+        };
+
+        if let Some(replacement) = replacement {
+            let bytes = replacement.as_bytes();
+            unsafe {
+                sys::ImGuiInputTextCallbackData_DeleteChars(data, 0, data.BufTextLen);
+                sys::ImGuiInputTextCallbackData_InsertChars(
+                    data,
+                    0,
+                    bytes.as_ptr() as *const c_char,
+                    bytes.as_ptr().add(bytes.len()) as *const c_char,
+                );
+            }
+        }
+
+        Ok(0)
+    }
+}
+
+fn call_optional(
+    fun: &Option<LuaFunction>,
+    args: impl IntoLuaMulti,
+) -> LuaResult<Option<LuaString>> {
+    match fun {
+        Some(fun) => fun.call(args),
+        None => Ok(None),
+    }
+}
+
+unsafe extern "C" fn input_text_callback(data: *mut sys::ImGuiInputTextCallbackData) -> i32 {
+    unsafe {
+        let data = &mut *data;
+        let state = &mut *(data.UserData as *mut InputTextState);
+        // 出错后不再调用 Lua 回调，但仍需处理缓冲区扩容
+        if state.error.is_some()
+            && data.EventFlag as i32 != sys::ImGuiInputTextFlags_CallbackResize as i32
+        {
+            return 0;
+        }
+        match state.handle(data) {
+            Ok(ret) => ret,
+            Err(e) => {
+                state.error = Some(e);
+                0
+            }
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! 图表数据
+//!
+//! `PlotBuffer` 是固定容量的环形缓冲区，写满后覆盖最旧的值。
+//! 绘制时直接将内部数组和起始偏移传给 ImGui，不需要每帧构建 Lua 表。
+
+use std::ffi::CStr;
+
+use mlua::prelude::*;
+
+use super::ImVec2;
+
+/// 缓冲区容量上限，超出时截断
+pub const MAX_PLOT_CAPACITY: usize = 65536;
+
+pub struct PlotBuffer {
+    values: Vec<f32>,
+    capacity: usize,
+    /// 写满后下一个写入位置，也是最旧值的位置
+    head: usize,
+}
+
+impl PlotBuffer {
+    /// 容量截断到 [`MAX_PLOT_CAPACITY`]，内部数组随写入增长
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            values: Vec::new(),
+            capacity: capacity.min(MAX_PLOT_CAPACITY),
+            head: 0,
+        }
+    }
+
+    pub fn push(&mut self, value: f32) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.values.len() < self.capacity {
+            self.values.push(value);
+        } else {
+            self.values[self.head] = value;
+            self.head = (self.head + 1) % self.capacity;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.head = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// 从旧到新迭代
+    pub fn iter(&self) -> impl Iterator<Item = f32> + '_ {
+        let (newer, older) = self.values.split_at(self.head);
+        older.iter().chain(newer).copied()
+    }
+
+    /// 内部数组和最旧值的偏移，对应 ImGui 的 `values` 和 `values_offset`
+    fn raw(&self) -> (&[f32], usize) {
+        (&self.values, self.head)
+    }
+}
+
+impl LuaUserData for PlotBuffer {
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        methods.add_method_mut("push", |_, this, value: f32| {
+            this.push(value);
+            Ok(())
+        });
+        methods.add_method_mut("clear", |_, this, ()| {
+            this.clear();
+            Ok(())
+        });
+        methods.add_method("capacity", |_, this, ()| Ok(this.capacity));
+        methods.add_method("to_table", |_, this, ()| {
+            Ok(this.iter().collect::<Vec<_>>())
+        });
+        methods.add_method("max", |_, this, ()| Ok(this.iter().reduce(f32::max)));
+        methods.add_method("average", |_, this, ()| {
+            if this.len() == 0 {
+                return Ok(None);
+            }
+            Ok(Some(this.iter().sum::<f32>() / this.len() as f32))
+        });
+        methods.add_meta_method(LuaMetaMethod::Len, |_, this, ()| Ok(this.len()));
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum PlotKind {
+    Lines,
+    Histogram,
+}
+
+/// 绘制图表，values 为 `PlotBuffer` 或数字数组，scale 为 nil 时自动计算
+pub fn plot(
+    kind: PlotKind,
+    label: &CStr,
+    values: &LuaValue,
+    overlay: Option<&CStr>,
+    scale_min: Option<f32>,
+    scale_max: Option<f32>,
+    size: Option<ImVec2>,
+) -> LuaResult<()> {
+    let overlay = overlay.map(CStr::as_ptr).unwrap_or(std::ptr::null());
+    let scale_min = scale_min.unwrap_or(f32::MAX);
+    let scale_max = scale_max.unwrap_or(f32::MAX);
+    let size = *size.unwrap_or_default();
+
+    let draw = |values: &[f32], offset: usize| unsafe {
+        let count = values.len() as i32;
+        let stride = size_of::<f32>() as i32;
+        match kind {
+            PlotKind::Lines => cimgui::sys::igPlotLines_FloatPtr(
+                label.as_ptr(),
+                values.as_ptr(),
+                count,
+                offset as i32,
+                overlay,
+                scale_min,
+                scale_max,
+                size,
+                stride,
+            ),
+            PlotKind::Histogram => cimgui::sys::igPlotHistogram_FloatPtr(
+                label.as_ptr(),
+                values.as_ptr(),
+                count,
+                offset as i32,
+                overlay,
+                scale_min,
+                scale_max,
+                size,
+                stride,
+            ),
+        }
+    };
+
+    match values {
+        LuaValue::UserData(ud) => {
+            let buffer = ud.borrow::<PlotBuffer>()?;
+            let (values, offset) = buffer.raw();
+            draw(values, offset);
+        }
+        LuaValue::Table(table) => {
+            let values = table
+                .sequence_values::<f32>()
+                .collect::<LuaResult<Vec<_>>>()?;
+            draw(&values, 0);
+        }
+        other => {
+            return Err(LuaError::FromLuaConversionError {
+                from: other.type_name(),
+                to: "PlotBuffer".to_string(),
+                message: Some("expected PlotBuffer or number array".to_string()),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plot_buffer_wraps() {
+        let mut buffer = PlotBuffer::new(3);
+        for value in [1.0, 2.0, 3.0, 4.0] {
+            buffer.push(value);
+        }
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.iter().collect::<Vec<_>>(), vec![2.0, 3.0, 4.0]);
+        assert_eq!(buffer.raw(), (&[4.0, 2.0, 3.0][..], 1));
+    }
+
+    #[test]
+    fn test_plot_buffer_capacity_capped() {
+        let buffer = PlotBuffer::new(usize::MAX);
+        assert_eq!(buffer.capacity, MAX_PLOT_CAPACITY);
+        assert_eq!(buffer.values.capacity(), 0);
+    }
+}
@@ -0,0 +1,43 @@
+use mlua::prelude::*;
+
+use crate::render_core::texture::{TextureInfo, TextureManager};
+
+use super::ImVec2;
+
+/// 脚本持有的纹理，被回收时释放
+pub struct LuaTexture(TextureInfo);
+
+impl LuaTexture {
+    pub fn new(info: TextureInfo) -> Self {
+        Self(info)
+    }
+
+    /// 已上传时返回 ImTextureID
+    pub fn texture_id(&self) -> Option<cimgui::sys::ImTextureID> {
+        TextureManager::instance()
+            .texture_id(self.0.handle)
+            .map(|id| id as usize as cimgui::sys::ImTextureID)
+    }
+
+    pub fn size(&self) -> ImVec2 {
+        ImVec2(cimgui::sys::ImVec2 {
+            x: self.0.width as f32,
+            y: self.0.height as f32,
+        })
+    }
+}
+
+impl Drop for LuaTexture {
+    fn drop(&mut self) {
+        TextureManager::instance().release(self.0.handle);
+    }
+}
+
+impl LuaUserData for LuaTexture {
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        methods.add_method("width", |_, this, ()| Ok(this.0.width));
+        methods.add_method("height", |_, this, ()| Ok(this.0.height));
+        methods.add_method("size", |_, this, ()| Ok(this.size()));
+        methods.add_method("is_ready", |_, this, ()| Ok(this.texture_id().is_some()));
+    }
+}
@@ -1,9 +1,16 @@
+use luaf_include::LogLevel;
 use mlua::{lua_State, prelude::*};
 
-use crate::error::Error;
+use crate::{
+    error::Error,
+    luavm::{LuaVMManager, ReloadRequest, capability},
+    render_core::layer::{self, DrawLayer},
+};
 
 use super::LuaModule;
 
+mod scheduler;
+
 const RUNTIME_LUA_MODULE: &str = include_str!("runtime.lua");
 
 pub struct RuntimeModule;
@@ -18,6 +25,11 @@ impl LuaModule for RuntimeModule {
         log_table.set("error", lua.create_function(error)?)?;
         log_table.set("debug", lua.create_function(debug)?)?;
         log_table.set("trace", lua.create_function(trace)?)?;
+        // 创建命名日志频道
+        log_table.set(
+            "channel",
+            lua.create_function(|_, name: String| Ok(LogChannel::new(name)))?,
+        )?;
 
         registry.set("log", log_table)?;
 
@@ -26,11 +38,10 @@ impl LuaModule for RuntimeModule {
             .or_else(|_| lua.create_table())?;
         core_table.set(
             "unsafe_mode",
-            lua.create_function(|lua, enable: bool| {
-                lua.globals().set("_unsafe_mode", enable)?;
-                Ok(())
-            })?,
+            lua.create_function(capability::set_unsafe_mode)?,
         )?;
+        // 在回调执行期间获取指定的不安全权限
+        core_table.set("with_unsafe", lua.create_function(capability::with_unsafe)?)?;
         unsafe {
             core_table.set("get_state_ptr", lua.create_c_function(lua_get_state_ptr)?)?;
         }
@@ -73,6 +84,93 @@ impl LuaModule for RuntimeModule {
                 Ok(())
             })?,
         )?;
+        // 设置指定绘制层的回调，同层按 z 从小到大绘制，传入 nil 取消
+        core_table.set(
+            "on_draw_layer",
+            lua.create_function(
+                |lua, (layer, fun, z): (String, Option<LuaFunction>, Option<i32>)| {
+                    parse_draw_layer(&layer)?;
+                    let layers = lua.globals().get::<LuaTable>("_draw_layers")?;
+                    let Some(fun) = fun else {
+                        return layers.set(layer, LuaNil);
+                    };
+                    let entry = lua.create_table()?;
+                    entry.set("fn", fun)?;
+                    entry.set("z", z.unwrap_or(0))?;
+                    layers.set(layer, entry)
+                },
+            )?,
+        )?;
+        // 启用或禁用绘制层，影响所有脚本
+        core_table.set(
+            "set_draw_layer_enabled",
+            lua.create_function(|_, (layer, enabled): (String, bool)| {
+                layer::set_enabled(parse_draw_layer(&layer)?, enabled);
+                Ok(())
+            })?,
+        )?;
+        core_table.set(
+            "is_draw_layer_enabled",
+            lua.create_function(|_, layer: String| {
+                Ok(layer::is_enabled(parse_draw_layer(&layer)?))
+            })?,
+        )?;
+        lua.globals().set("_draw_layers", lua.create_table()?)?;
+        // 设置主菜单栏回调，菜单项显示在以脚本名（或 title）命名的菜单中
+        core_table.set(
+            "on_menu",
+            lua.create_function(|lua, (fun, title): (LuaFunction, Option<String>)| {
+                let globals = lua.globals();
+                globals.set("_on_menu", fun)?;
+                globals.set("_menu_title", title)?;
+                Ok(())
+            })?,
+        )?;
+        // 设置怪物生成和析构回调，在下一帧调用，参数为 (怪物指针, 种类 ID, 子种类 ID)
+        core_table.set(
+            "on_monster_spawn",
+            lua.create_function(|lua, fun: Option<LuaFunction>| {
+                lua.globals().set("_on_monster_spawn", fun)?;
+                Ok(())
+            })?,
+        )?;
+        core_table.set(
+            "on_monster_death",
+            lua.create_function(|lua, fun: Option<LuaFunction>| {
+                lua.globals().set("_on_monster_death", fun)?;
+                Ok(())
+            })?,
+        )?;
+        // 设置任务事件回调，在下一帧调用，参数为任务 ID，结束回调额外传入是否成功
+        core_table.set(
+            "on_quest_start",
+            lua.create_function(|lua, fun: Option<LuaFunction>| {
+                lua.globals().set("_on_quest_start", fun)?;
+                Ok(())
+            })?,
+        )?;
+        core_table.set(
+            "on_quest_complete",
+            lua.create_function(|lua, fun: Option<LuaFunction>| {
+                lua.globals().set("_on_quest_complete", fun)?;
+                Ok(())
+            })?,
+        )?;
+        core_table.set(
+            "on_quest_return",
+            lua.create_function(|lua, fun: Option<LuaFunction>| {
+                lua.globals().set("_on_quest_return", fun)?;
+                Ok(())
+            })?,
+        )?;
+        // 设置伤害事件回调，在下一帧调用，参数为伤害记录表
+        core_table.set(
+            "on_damage",
+            lua.create_function(|lua, fun: Option<LuaFunction>| {
+                lua.globals().set("_on_damage", fun)?;
+                Ok(())
+            })?,
+        )?;
         // 设置on_destroy回调
         core_table.set(
             "on_destroy",
@@ -82,6 +180,184 @@ impl LuaModule for RuntimeModule {
             })?,
         )?;
 
+        // 设置扩展事件回调
+        core_table.set(
+            "on_event",
+            lua.create_function(|lua, (name, fun): (String, Option<LuaFunction>)| {
+                if name.is_empty() {
+                    return Err(Error::InvalidValue("non-empty event name", name).into_lua_err());
+                }
+                lua.globals().set(format!("_on_event:{}", name), fun)?;
+                Ok(())
+            })?,
+        )?;
+        // 设置重载前后回调，重载全部时参数为 nil，重载单个脚本时为脚本名
+        core_table.set(
+            "on_before_reload",
+            lua.create_function(|lua, fun: LuaFunction| {
+                lua.globals().set("_on_before_reload", fun)?;
+                Ok(())
+            })?,
+        )?;
+        core_table.set(
+            "on_after_reload",
+            lua.create_function(|lua, fun: LuaFunction| {
+                lua.globals().set("_on_after_reload", fun)?;
+                Ok(())
+            })?,
+        )?;
+        // 设置保存回调，自动保存时调用，脚本应在其中保存设置
+        core_table.set(
+            "on_save",
+            lua.create_function(|lua, fun: LuaFunction| {
+                lua.globals().set("_on_save", fun)?;
+                Ok(())
+            })?,
+        )?;
+        // 请求重载，在下一帧执行
+        core_table.set(
+            "reload_all",
+            lua.create_function(|_, ()| {
+                Ok(LuaVMManager::instance().request_reload(ReloadRequest::All))
+            })?,
+        )?;
+        core_table.set(
+            "reload_script",
+            lua.create_function(|_, name: String| {
+                Ok(LuaVMManager::instance().request_reload(ReloadRequest::Script(name)))
+            })?,
+        )?;
+        core_table.set(
+            "reload_self",
+            lua.create_function(|lua, ()| {
+                let name = lua.globals().get::<String>("_name")?;
+                Ok(LuaVMManager::instance().request_reload(ReloadRequest::Script(name)))
+            })?,
+        )?;
+
+        // 日志控制台
+        let console_table = lua.create_table()?;
+        console_table.set(
+            "show",
+            lua.create_function(|_, ()| {
+                crate::logger::show_logger_console();
+                Ok(())
+            })?,
+        )?;
+        console_table.set(
+            "hide",
+            lua.create_function(|_, ()| {
+                crate::logger::hide_logger_console();
+                Ok(())
+            })?,
+        )?;
+        console_table.set(
+            "toggle",
+            lua.create_function(|_, ()| Ok(crate::logger::toggle_logger_console()))?,
+        )?;
+        console_table.set(
+            "is_visible",
+            lua.create_function(|_, ()| Ok(crate::logger::is_logger_console_visible()))?,
+        )?;
+        core_table.set("console", console_table)?;
+
+        // 运行时日志设置，修改立即生效并保存到配置
+        let core_log_table = lua.create_table()?;
+        core_log_table.set(
+            "set_level",
+            lua.create_function(|_, level: String| {
+                crate::logger::set_level(parse_log_level(&level)?);
+                Ok(())
+            })?,
+        )?;
+        core_log_table.set(
+            "get_level",
+            lua.create_function(|_, ()| Ok(log_level_name(crate::logger::level())))?,
+        )?;
+        core_log_table.set(
+            "set_console_enabled",
+            lua.create_function(|_, enabled: bool| {
+                crate::logger::set_log_to_console(enabled);
+                Ok(())
+            })?,
+        )?;
+        core_log_table.set(
+            "set_file_enabled",
+            lua.create_function(|_, enabled: bool| {
+                crate::logger::set_log_to_file(enabled).map_err(|e| Error::Io(e).into_lua_err())
+            })?,
+        )?;
+        // 更改日志文件路径，启用文件日志时立即重新打开
+        // 路径相对于数据目录，且必须为 .log 文件，避免脚本覆盖任意文件
+        core_log_table.set(
+            "set_file_path",
+            lua.create_function(|_, path: String| {
+                let full_path = super::fs::create_abs_path(&path)?;
+                let is_log = full_path
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("log"));
+                if !is_log {
+                    return Err(Error::PathNotAllowed(
+                        "log file must have .log extension".to_string(),
+                    )
+                    .into_lua_err());
+                }
+                if let Some(parent) = full_path.parent() {
+                    std::fs::create_dir_all(parent).map_err(|e| Error::Io(e).into_lua_err())?;
+                }
+                crate::logger::set_log_file_path(&full_path.to_string_lossy())
+                    .map_err(|e| Error::Io(e).into_lua_err())
+            })?,
+        )?;
+        core_log_table.set(
+            "settings",
+            lua.create_function(|lua, ()| {
+                let settings = crate::logger::settings();
+                let table = lua.create_table()?;
+                table.set("level", log_level_name(settings.level))?;
+                table.set("console", settings.log_to_console)?;
+                table.set("file", settings.log_to_file)?;
+                table.set("file_path", settings.log_file_path)?;
+                Ok(table)
+            })?,
+        )?;
+        core_table.set("log", core_log_table)?;
+
+        core_table.set(
+            "asset_path",
+            lua.create_function(|lua, path: String| {
+                let full_path = super::fs::create_asset_path(lua, &path)?;
+                Ok(full_path.to_string_lossy().replace('\\', "/"))
+            })?,
+        )?;
+        core_table.set("dofile_isolated", lua.create_function(dofile_isolated)?)?;
+        // 导出脚本状态，用于问题报告
+        core_table.set(
+            "dump_state",
+            lua.create_function(|lua, path: Option<String>| {
+                super::state_dump::dump_state(lua, path)
+            })?,
+        )?;
+        // 当前线程信息
+        core_table.set(
+            "thread_info",
+            lua.create_function(|lua, ()| {
+                let info = lua.create_table()?;
+                info.set("id", crate::game::thread::current_thread_id())?;
+                info.set("game_thread_id", crate::game::thread::game_thread_id())?;
+                info.set("is_game_thread", crate::game::thread::is_game_thread())?;
+                Ok(info)
+            })?,
+        )?;
+        core_table.set(
+            "is_cutscene",
+            lua.create_function(|_, ()| Ok(crate::game::scene::is_cutscene()))?,
+        )?;
+        core_table.set(
+            "is_loading",
+            lua.create_function(|_, ()| Ok(crate::game::scene::is_loading()))?,
+        )?;
+
         core_table.set(
             "get_last_error",
             lua.create_function(|lua, ()| {
@@ -91,8 +367,45 @@ impl LuaModule for RuntimeModule {
             })?,
         )?;
 
+        // 各脚本的回调统计：脚本名 -> 回调名 -> { calls, errors, over_budget, avg_us, max_us }
+        core_table.set(
+            "stats",
+            lua.create_function(|lua, ()| {
+                lua.to_value(&crate::profiler::Profiler::instance().script_stats())
+            })?,
+        )?;
+
+        // 当前初始化阶段：extensions、addresses、scripts、ready
+        core_table.set(
+            "init_phase",
+            lua.create_function(|_, ()| {
+                Ok(crate::bootstrap::phase::InitPhases::instance()
+                    .current()
+                    .map(crate::bootstrap::phase::phase_name))
+            })?,
+        )?;
+
+        // 协程调度，在协程中等待指定帧数或秒数
+        core_table.set(
+            "async",
+            lua.create_function(|lua, (fun, args): (LuaFunction, LuaMultiValue)| {
+                scheduler::spawn(lua, fun, args)
+            })?,
+        )?;
+        core_table.set(
+            "wait_frames",
+            scheduler::create_wait_function(lua, "frames")?,
+        )?;
+        core_table.set(
+            "wait_seconds",
+            scheduler::create_wait_function(lua, "seconds")?,
+        )?;
+
         registry.set("core", core_table)?;
 
+        // 重定向 io.write / io.stdout / io.stderr 到日志
+        Self::redirect_stdio(lua)?;
+
         // 加载 Lua 文件扩展
         lua.load(RUNTIME_LUA_MODULE).exec()?;
 
@@ -101,11 +414,6 @@ impl LuaModule for RuntimeModule {
 }
 
 impl RuntimeModule {
-    /// 是否启用不安全内存访问模式
-    pub fn is_unsafe_mode(lua: &Lua) -> bool {
-        lua.globals().get::<bool>("_unsafe_mode").unwrap_or(false)
-    }
-
     /// 获取 lua_State 指针
     pub fn get_state_ptr(lua: &Lua) -> LuaResult<usize> {
         let core_table = lua.globals().get::<LuaTable>("core")?;
@@ -115,14 +423,188 @@ impl RuntimeModule {
         Ok(result as usize)
     }
 
+    /// 恢复等待到期的协程
+    pub fn tick_coroutines(lua: &Lua) -> LuaResult<()> {
+        scheduler::tick(lua)
+    }
+
+    /// 将标准输出重定向到脚本日志，按行输出
+    fn redirect_stdio(lua: &Lua) -> LuaResult<()> {
+        let Ok(io_table) = lua.globals().get::<LuaTable>("io") else {
+            return Ok(());
+        };
+
+        let stdout = lua.create_userdata(OutputStream::new(log::Level::Info))?;
+        let stderr = lua.create_userdata(OutputStream::new(log::Level::Error))?;
+        io_table.set(
+            "write",
+            lua.create_function({
+                let stdout = stdout.clone();
+                move |lua, args: mlua::Variadic<LuaValue>| {
+                    OutputStream::write(lua, &stdout, args)?;
+                    Ok(stdout.clone())
+                }
+            })?,
+        )?;
+        // 脚本可能替换 io.stdout，刷新时从注册表中取原始的流
+        lua.set_named_registry_value(STDOUT_KEY, &stdout)?;
+        lua.set_named_registry_value(STDERR_KEY, &stderr)?;
+        io_table.set("stdout", stdout)?;
+        io_table.set("stderr", stderr)?;
+
+        Ok(())
+    }
+
     pub fn invoke_on_destroy(lua: &Lua) -> LuaResult<()> {
-        if let Ok(on_destroy) = lua.globals().get::<LuaFunction>("_on_destroy") {
-            on_destroy.call::<()>(())?;
+        let result = match lua.globals().get::<LuaFunction>("_on_destroy") {
+            Ok(on_destroy) => on_destroy.call::<()>(()),
+            Err(_) => Ok(()),
+        };
+        // 输出未换行的剩余内容
+        Self::flush_stdio(lua);
+
+        result
+    }
+
+    /// 输出未换行的剩余内容，每次回调结束后调用
+    pub fn flush_stdio(lua: &Lua) {
+        for key in [STDOUT_KEY, STDERR_KEY] {
+            if let Ok(ud) = lua.named_registry_value::<LuaAnyUserData>(key)
+                && let Ok(mut stream) = ud.borrow_mut::<OutputStream>()
+            {
+                stream.flush(lua);
+            }
         }
+    }
+}
+
+/// 命名日志频道，可在界面中单独开关
+struct LogChannel {
+    name: String,
+    target: String,
+}
+
+impl LogChannel {
+    fn new(name: String) -> Self {
+        let target = crate::logger::channel_target(&name);
+        Self { name, target }
+    }
+
+    fn log(&self, lua: &Lua, level: log::Level, msgs: mlua::Variadic<LuaValue>) -> LuaResult<()> {
+        let args = format_args(lua, msgs)?;
+        log::log!(
+            target: &self.target,
+            level,
+            "{}[{}] {}",
+            get_prefix(lua),
+            self.name,
+            args.join(" ")
+        );
         Ok(())
     }
 }
 
+impl LuaUserData for LogChannel {
+    fn add_fields<F: LuaUserDataFields<Self>>(fields: &mut F) {
+        fields.add_field_method_get("name", |_, this| Ok(this.name.clone()));
+    }
+
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        methods.add_method("info", |lua, this, msgs| {
+            this.log(lua, log::Level::Info, msgs)
+        });
+        methods.add_method("warn", |lua, this, msgs| {
+            this.log(lua, log::Level::Warn, msgs)
+        });
+        methods.add_method("error", |lua, this, msgs| {
+            this.log(lua, log::Level::Error, msgs)
+        });
+        methods.add_method("debug", |lua, this, msgs| {
+            this.log(lua, log::Level::Debug, msgs)
+        });
+        methods.add_method("trace", |lua, this, msgs| {
+            this.log(lua, log::Level::Trace, msgs)
+        });
+    }
+}
+
+/// 标准输出流在注册表中的键
+const STDOUT_KEY: &str = "_luaf_stdout";
+const STDERR_KEY: &str = "_luaf_stderr";
+/// 未换行内容超过此长度时直接输出
+const MAX_LINE_BUFFER: usize = 4096;
+
+/// 替代 io.stdout / io.stderr 的输出流，缓冲到换行后写入日志
+///
+/// 未换行的内容在超过 [`MAX_LINE_BUFFER`] 或回调结束时输出。
+struct OutputStream {
+    level: log::Level,
+    buffer: String,
+}
+
+impl OutputStream {
+    fn new(level: log::Level) -> Self {
+        Self {
+            level,
+            buffer: String::new(),
+        }
+    }
+
+    fn write(lua: &Lua, ud: &LuaAnyUserData, args: mlua::Variadic<LuaValue>) -> LuaResult<()> {
+        let mut this = ud.borrow_mut::<Self>()?;
+        for (i, arg) in args.into_iter().enumerate() {
+            let type_name = arg.type_name();
+            let Some(s) = lua.coerce_string(arg)? else {
+                return Err(LuaError::external(format!(
+                    "bad argument #{} to 'write' (string expected, got {})",
+                    i + 1,
+                    type_name
+                )));
+            };
+            this.buffer.push_str(&s.to_string_lossy());
+        }
+
+        // 输出完整的行
+        while let Some(pos) = this.buffer.find('\n') {
+            let line = this.buffer[..pos].trim_end_matches('\r').to_string();
+            this.buffer.drain(..=pos);
+            log::log!(this.level, "{} {}", get_prefix(lua), line);
+        }
+        if this.buffer.len() >= MAX_LINE_BUFFER {
+            this.flush(lua);
+        }
+
+        Ok(())
+    }
+
+    fn flush(&mut self, lua: &Lua) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let line = std::mem::take(&mut self.buffer);
+        log::log!(self.level, "{} {}", get_prefix(lua), line);
+    }
+}
+
+impl LuaUserData for OutputStream {
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        methods.add_function(
+            "write",
+            |lua, (ud, args): (LuaAnyUserData, mlua::Variadic<LuaValue>)| {
+                OutputStream::write(lua, &ud, args)?;
+                Ok(ud)
+            },
+        );
+        methods.add_method_mut("flush", |lua, this, ()| {
+            this.flush(lua);
+            Ok(true)
+        });
+        // 以下为兼容 file 接口的空实现
+        methods.add_method("setvbuf", |_, _, _: mlua::Variadic<LuaValue>| Ok(true));
+        methods.add_method("close", |_, _, ()| Ok(true));
+    }
+}
+
 fn format_args(lua: &Lua, args: mlua::Variadic<LuaValue>) -> LuaResult<Vec<String>> {
     let mut outs = vec![];
 
@@ -192,6 +674,42 @@ unsafe extern "C-unwind" fn lua_get_state_ptr(L: *mut lua_State) -> std::ffi::c_
     }
 }
 
+/// 在独立的环境表中执行脚本目录下的 Lua 文件，返回文件的返回值
+///
+/// options:
+/// - share: 需要共享到新环境的全局变量名列表
+/// - inherit: 为 true 时，新环境中未定义的变量从调用方全局表读取
+fn dofile_isolated(
+    lua: &Lua,
+    (path, options): (String, Option<LuaTable>),
+) -> LuaResult<LuaMultiValue> {
+    let full_path = super::fs::create_script_path(lua, &path)?;
+    let code = std::fs::read_to_string(&full_path).map_err(|e| {
+        Error::IoWithContext(e, format!("Failed to read script file '{}'", path)).into_lua_err()
+    })?;
+
+    let globals = lua.globals();
+    let env = lua.create_table()?;
+    env.set("_G", &env)?;
+    if let Some(options) = options {
+        if let Some(share) = options.get::<Option<Vec<String>>>("share")? {
+            for name in share {
+                env.set(name.as_str(), globals.get::<LuaValue>(name.as_str())?)?;
+            }
+        }
+        if options.get::<Option<bool>>("inherit")?.unwrap_or(false) {
+            let metatable = lua.create_table()?;
+            metatable.set("__index", &globals)?;
+            env.set_metatable(Some(metatable))?;
+        }
+    }
+
+    lua.load(code)
+        .set_name(format!("@{}", path))
+        .set_environment(env)
+        .call(())
+}
+
 fn require_version(_lua: &Lua, require_version: String) -> LuaResult<()> {
     let req = semver::VersionReq::parse(&require_version).map_err(|e| e.into_lua_err())?;
 
@@ -204,3 +722,30 @@ fn require_version(_lua: &Lua, require_version: String) -> LuaResult<()> {
 
     Ok(())
 }
+
+fn parse_draw_layer(layer: &str) -> LuaResult<DrawLayer> {
+    layer
+        .parse::<DrawLayer>()
+        .map_err(|_| Error::InvalidValue("draw layer", layer.to_string()).into_lua_err())
+}
+
+fn parse_log_level(level: &str) -> LuaResult<LogLevel> {
+    match level.to_ascii_lowercase().as_str() {
+        "trace" => Ok(LogLevel::Trace),
+        "debug" => Ok(LogLevel::Debug),
+        "info" => Ok(LogLevel::Info),
+        "warn" => Ok(LogLevel::Warn),
+        "error" => Ok(LogLevel::Error),
+        _ => Err(Error::InvalidValue("log level", level.to_string()).into_lua_err()),
+    }
+}
+
+fn log_level_name(level: LogLevel) -> &'static str {
+    match level {
+        LogLevel::Trace => "trace",
+        LogLevel::Debug => "debug",
+        LogLevel::Info => "info",
+        LogLevel::Warn => "warn",
+        LogLevel::Error => "error",
+    }
+}
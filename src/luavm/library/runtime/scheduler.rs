@@ -0,0 +1,130 @@
+//! 协程调度
+//!
+//! `core.async` 创建的协程保存在虚拟机的 `_coroutines` 表中，通过 `core.wait_frames`
+//! 或 `core.wait_seconds` 让出，每帧 on_update 之前恢复到期的协程。
+//! 由原生函数回调进入的 Lua 代码（如 Hook 回调）不能让出。
+
+use mlua::prelude::*;
+
+use crate::luavm::library::utility::time;
+
+const COROUTINES_KEY: &str = "_coroutines";
+const NEXT_ID_KEY: &str = "_coroutine_next_id";
+/// 调度帧计数，每次 tick 递增
+const FRAME_KEY: &str = "_coroutine_frame";
+
+/// 让出函数，在 Lua 中定义以便从协程内部调用
+const WAIT_CHUNK: &str = r#"
+local kind = ...
+return function(value)
+    local co, is_main = coroutine.running()
+    if co == nil or is_main then
+        error("core.wait_" .. kind .. " must be called inside core.async", 2)
+    end
+    return coroutine.yield(kind, value)
+end
+"#;
+
+fn coroutines_table(lua: &Lua) -> LuaResult<LuaTable> {
+    let globals = lua.globals();
+    if let Ok(coroutines) = globals.get::<LuaTable>(COROUTINES_KEY) {
+        return Ok(coroutines);
+    }
+    let coroutines = lua.create_table()?;
+    globals.set(COROUTINES_KEY, &coroutines)?;
+    Ok(coroutines)
+}
+
+fn now_millis() -> f64 {
+    time::monotonic() * 1000.0
+}
+
+/// 创建 `wait_frames` 或 `wait_seconds` 函数
+pub fn create_wait_function(lua: &Lua, kind: &str) -> LuaResult<LuaFunction> {
+    lua.load(WAIT_CHUNK).set_name("=wait").call(kind)
+}
+
+/// 创建协程并立即执行到第一次让出
+pub fn spawn(lua: &Lua, fun: LuaFunction, args: LuaMultiValue) -> LuaResult<LuaThread> {
+    let globals = lua.globals();
+    let id = globals.get::<Option<i64>>(NEXT_ID_KEY)?.unwrap_or(1);
+    globals.set(NEXT_ID_KEY, id + 1)?;
+
+    let thread = lua.create_thread(fun)?;
+    let entry = lua.create_table()?;
+    entry.set("thread", &thread)?;
+    coroutines_table(lua)?.set(id, &entry)?;
+
+    resume(lua, id, &entry, args)?;
+    Ok(thread)
+}
+
+/// 恢复到期的协程
+pub fn tick(lua: &Lua) -> LuaResult<()> {
+    let Ok(coroutines) = lua.globals().get::<LuaTable>(COROUTINES_KEY) else {
+        return Ok(());
+    };
+    let frame = lua.globals().get::<Option<i64>>(FRAME_KEY)?.unwrap_or(0) + 1;
+    lua.globals().set(FRAME_KEY, frame)?;
+    let now = now_millis();
+
+    let mut ready = Vec::new();
+    for pair in coroutines.pairs::<i64, LuaTable>() {
+        let (id, entry) = pair?;
+        let frame_ready = entry
+            .get::<Option<i64>>("frame")?
+            .is_none_or(|target| target <= frame);
+        let time_ready = entry
+            .get::<Option<f64>>("due")?
+            .is_none_or(|due| due <= now);
+        if frame_ready && time_ready {
+            ready.push((id, entry));
+        }
+    }
+    ready.sort_by_key(|(id, _)| *id);
+
+    for (id, entry) in ready {
+        resume(lua, id, &entry, LuaMultiValue::new())?;
+    }
+
+    Ok(())
+}
+
+fn resume(lua: &Lua, id: i64, entry: &LuaTable, args: LuaMultiValue) -> LuaResult<()> {
+    let thread = entry.get::<LuaThread>("thread")?;
+    entry.set("frame", LuaNil)?;
+    entry.set("due", LuaNil)?;
+
+    let yielded = match thread.resume::<LuaMultiValue>(args) {
+        Ok(values) => values,
+        Err(e) => {
+            let name = lua.globals().get::<String>("_name").unwrap_or_default();
+            let err_msg = format!("coroutine in LuaVM({}) error:\n{}", name, e);
+            crate::error::set_last_error(err_msg.clone());
+            log::error!("{}", err_msg);
+            return coroutines_table(lua)?.set(id, LuaNil);
+        }
+    };
+    if thread.status() != LuaThreadStatus::Resumable {
+        return coroutines_table(lua)?.set(id, LuaNil);
+    }
+
+    // 其他方式让出的协程在下一帧恢复
+    let mut yielded = yielded.into_iter();
+    let kind = yielded.next();
+    let value = yielded.next().and_then(|v| v.as_f64()).unwrap_or(1.0);
+    match kind
+        .as_ref()
+        .and_then(|k| k.as_string())
+        .map(|k| k.to_string_lossy())
+    {
+        Some(kind) if kind == "seconds" => {
+            entry.set("due", now_millis() + value.max(0.0) * 1000.0)?;
+        }
+        _ => {
+            let frame = lua.globals().get::<Option<i64>>(FRAME_KEY)?.unwrap_or(0);
+            entry.set("frame", frame + (value as i64).max(1))?;
+        }
+    }
+    Ok(())
+}
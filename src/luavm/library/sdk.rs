@@ -4,15 +4,31 @@ use crate::{error::Error, game::singleton::SingletonManager};
 
 use super::LuaModule;
 
+pub mod buffer;
+pub mod chat;
+pub mod class_def;
+pub mod code_writer;
+pub mod damage_number;
+pub mod dti;
 pub mod ffi_call;
 pub mod frida;
+pub mod game_object;
+pub mod hotkey;
 pub mod input;
 pub mod luaptr;
 pub mod memory;
 pub mod module;
 pub mod monster;
+pub mod network;
+pub mod patch_profile;
+pub mod player;
+pub mod quest;
 pub mod shared_state;
+pub mod singletons;
+pub mod spawn;
 pub mod string;
+pub mod struct_def;
+pub mod timer;
 
 pub struct SdkModule;
 
@@ -28,7 +44,22 @@ impl LuaModule for SdkModule {
         frida::FridaModule::register_library(lua, &sdk_table)?;
         ffi_call::FFICallModule::register_library(lua, &sdk_table)?;
         monster::MonsterModule::register_library(lua, &sdk_table)?;
+        player::PlayerModule::register_library(lua, &sdk_table)?;
+        quest::QuestModule::register_library(lua, &sdk_table)?;
+        chat::ChatModule::register_library(lua, &sdk_table)?;
         module::ModuleMod::register_library(lua, &sdk_table)?;
+        class_def::ClassDefModule::register_library(lua, &sdk_table)?;
+        struct_def::StructModule::register_library(lua, &sdk_table)?;
+        spawn::SpawnModule::register_library(lua, &sdk_table)?;
+        network::NetworkModule::register_library(lua, &sdk_table)?;
+        damage_number::DamageNumberModule::register_library(lua, &sdk_table)?;
+        code_writer::CodeWriterModule::register_library(lua, &sdk_table)?;
+        patch_profile::PatchProfileModule::register_library(lua, &sdk_table)?;
+        singletons::SingletonsModule::register_library(lua, &sdk_table)?;
+        game_object::GameObjectModule::register_library(lua, &sdk_table)?;
+        dti::DtiModule::register_library(lua, &sdk_table)?;
+        timer::TimerModule::register_library(lua, &sdk_table)?;
+        hotkey::HotkeyModule::register_library(lua, &sdk_table)?;
 
         // 获取单例
         sdk_table.set(
@@ -49,6 +80,19 @@ impl LuaModule for SdkModule {
             })?,
         )?;
 
+        // 所有已解析的单例，键为单例名
+        sdk_table.set(
+            "singletons",
+            lua.create_function(|lua, ()| {
+                let singletons = SingletonManager::instance().singletons();
+                let table = lua.create_table_with_capacity(0, singletons.len())?;
+                for (name, addr) in singletons {
+                    table.set(name, luaptr::LuaPtr::new(addr as u64))?;
+                }
+                Ok(table)
+            })?,
+        )?;
+
         registry.set("sdk", sdk_table)?;
         Ok(())
     }
@@ -0,0 +1,105 @@
+//! 从 Lua 传入的字节数据
+//!
+//! 接受二进制字符串或整数数组。字符串直接复制，适合大块数据，不受长度限制；
+//! 数组逐个元素转换，转换前检查长度，避免误传的超大数组在回调中长时间卡住游戏。
+
+use std::ops::Deref;
+
+use mlua::prelude::*;
+
+use crate::{config::Config, error::Error};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Buffer(Vec<u8>);
+
+impl Buffer {
+    /// 转换 Lua 值，`max_len` 为数组的最大长度，0 表示不限制
+    fn from_lua_with_limit(value: LuaValue, max_len: usize) -> LuaResult<Self> {
+        match value {
+            LuaValue::String(s) => Ok(Buffer(s.as_bytes().to_vec())),
+            LuaValue::Table(table) => {
+                let len = table.raw_len();
+                if max_len != 0 && len > max_len {
+                    return Err(Error::BytesTooLarge(len, max_len).into_lua_err());
+                }
+
+                let mut bytes = Vec::with_capacity(len);
+                for i in 1..=len {
+                    let value = table.raw_get::<LuaValue>(i)?;
+                    let byte = value
+                        .as_integer()
+                        .and_then(|v| u8::try_from(v).ok())
+                        .ok_or_else(|| {
+                            Error::InvalidValue(
+                                "byte (0-255)",
+                                format!("{:?} at index {}", value, i),
+                            )
+                            .into_lua_err()
+                        })?;
+                    bytes.push(byte);
+                }
+                Ok(Buffer(bytes))
+            }
+            other => Err(Error::InvalidValue(
+                "binary string or byte array",
+                format!("{:?}", other),
+            )
+            .into_lua_err()),
+        }
+    }
+}
+
+impl Deref for Buffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl FromLua for Buffer {
+    fn from_lua(value: LuaValue, _lua: &Lua) -> LuaResult<Self> {
+        Self::from_lua_with_limit(value, Config::global().scripts.max_bytes_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_from_string() {
+        let lua = Lua::new();
+        let value = LuaValue::String(lua.create_string(b"\x00\xffabc").unwrap());
+        let buffer = Buffer::from_lua_with_limit(value, 2).unwrap();
+        // 字符串不受数组长度限制
+        assert_eq!(&*buffer, b"\x00\xffabc");
+    }
+
+    #[test]
+    fn test_buffer_from_table() {
+        let lua = Lua::new();
+        let table = lua.create_sequence_from([0, 127, 255]).unwrap();
+        let buffer = Buffer::from_lua_with_limit(LuaValue::Table(table), 3).unwrap();
+        assert_eq!(&*buffer, &[0, 127, 255]);
+
+        let table = lua.create_sequence_from([1, 256]).unwrap();
+        let err = Buffer::from_lua_with_limit(LuaValue::Table(table), 0).unwrap_err();
+        assert!(err.to_string().contains("at index 2"));
+
+        let table = lua.create_sequence_from([1, -1]).unwrap();
+        assert!(Buffer::from_lua_with_limit(LuaValue::Table(table), 0).is_err());
+    }
+
+    #[test]
+    fn test_buffer_table_cap() {
+        let lua = Lua::new();
+        let table = lua.create_sequence_from([1, 2, 3, 4]).unwrap();
+        let err = Buffer::from_lua_with_limit(LuaValue::Table(table.clone()), 3).unwrap_err();
+        assert!(err.to_string().contains("exceeds the limit of 3"));
+
+        // 0 表示不限制
+        let buffer = Buffer::from_lua_with_limit(LuaValue::Table(table), 0).unwrap();
+        assert_eq!(buffer.len(), 4);
+    }
+}
@@ -0,0 +1,120 @@
+//! 聊天消息与聊天命令
+//!
+//! 命令处理函数保存在虚拟机的 `_chat_commands` 表中，命令元信息由 `CommandRegistry` 管理。
+
+use mlua::prelude::*;
+
+use crate::{
+    error::Error,
+    game::{
+        chat,
+        command::{ChatCommand, CommandRegistry},
+    },
+    luavm::{
+        capability::{self, UnsafeCapability},
+        library::LuaModule,
+        safety::SafetyPolicy,
+    },
+};
+
+const COMMANDS_KEY: &str = "_chat_commands";
+
+pub struct ChatModule;
+
+impl LuaModule for ChatModule {
+    fn register_library(lua: &mlua::Lua, registry: &mlua::Table) -> mlua::Result<()> {
+        let chat_table = lua.create_table()?;
+
+        // 以自己的身份发送聊天消息
+        chat_table.set(
+            "send",
+            lua.create_function(|lua, text: String| {
+                capability::ensure_capability(lua, UnsafeCapability::GameControl, "Chat.send")?;
+                SafetyPolicy::check_lua(lua, "Chat.send")?;
+                chat::send(&text).map_err(|e| e.into_lua_err())
+            })?,
+        )?;
+        // 显示只有自己可见的系统消息
+        chat_table.set(
+            "system_message",
+            lua.create_function(|_, text: String| {
+                chat::system_message(&text).map_err(|e| e.into_lua_err())
+            })?,
+        )?;
+        // 设置聊天消息回调，在下一帧调用，参数为 (消息, "sent"|"received", 发送者)
+        chat_table.set(
+            "on_message",
+            lua.create_function(|lua, fun: Option<LuaFunction>| {
+                lua.globals().set("_on_chat_message", fun)?;
+                Ok(())
+            })?,
+        )?;
+
+        // 注册聊天命令，处理函数参数为 (参数列表, 原始消息)，返回的字符串作为系统消息显示
+        chat_table.set(
+            "register_command",
+            lua.create_function(
+                |lua, (name, handler, options): (String, LuaFunction, Option<LuaTable>)| {
+                    if name.is_empty() || name.contains(char::is_whitespace) {
+                        return Err(Error::InvalidValue("command name without whitespace", name)
+                            .into_lua_err());
+                    }
+                    let (usage, help, swallow) = match options {
+                        Some(options) => (
+                            options.get::<Option<String>>("usage")?,
+                            options.get::<Option<String>>("help")?,
+                            options.get::<Option<bool>>("swallow")?.unwrap_or(true),
+                        ),
+                        None => (None, None, true),
+                    };
+                    commands_table(lua)?.set(name.as_str(), handler)?;
+                    CommandRegistry::instance().register(ChatCommand {
+                        script: lua.globals().get::<String>("_name")?,
+                        name,
+                        usage,
+                        help,
+                        swallow,
+                    });
+                    Ok(())
+                },
+            )?,
+        )?;
+        chat_table.set(
+            "unregister_command",
+            lua.create_function(|lua, name: String| {
+                commands_table(lua)?.set(name.as_str(), LuaNil)?;
+                let script = lua.globals().get::<String>("_name")?;
+                Ok(CommandRegistry::instance().unregister(&script, &name))
+            })?,
+        )?;
+
+        registry.set("Chat", chat_table)?;
+
+        Ok(())
+    }
+}
+
+impl ChatModule {
+    /// 调用命令处理函数，返回要显示的回复
+    pub fn dispatch_command(
+        lua: &Lua,
+        name: &str,
+        args: &[String],
+        raw: &str,
+    ) -> LuaResult<Option<String>> {
+        let Some(handler) = commands_table(lua)?.get::<Option<LuaFunction>>(name)? else {
+            return Ok(None);
+        };
+        handler.call::<Option<String>>((args.to_vec(), raw))
+    }
+}
+
+fn commands_table(lua: &Lua) -> LuaResult<LuaTable> {
+    let globals = lua.globals();
+    if let Some(commands) = globals.get::<Option<LuaTable>>(COMMANDS_KEY)? {
+        return Ok(commands);
+    }
+    let commands = lua.create_table()?;
+    globals.set(COMMANDS_KEY, &commands)?;
+    Ok(commands)
+}
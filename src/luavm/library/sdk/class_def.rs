@@ -0,0 +1,473 @@
+//! 游戏对象类定义模块
+//!
+//! 从定义文件（TOML/JSON）中读取类的字段、方法和单例信息，
+//! 并生成对应的 Lua userdata 对象，以数据更新的方式扩展游戏 API。
+
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, LazyLock},
+};
+
+use mlua::prelude::*;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    ffi_call::{self, Argument, ArgumentType},
+    luaptr::{self, LuaPtr},
+};
+use crate::{
+    address::AddressRepository,
+    error::{Error, Result},
+    game::{
+        mt_type::{EmptyGameObject, GameObject, GameObjectExt},
+        singleton::SingletonManager,
+    },
+    luavm::{
+        library::{LuaModule, fs::create_abs_path_in},
+        safety::SafetyPolicy,
+    },
+    memory::{MAX_C_STRING_LEN, MemoryUtils},
+};
+
+const CLASS_DEFS_DIR: &str = "lua_framework/defs";
+
+pub struct ClassDefModule;
+
+impl LuaModule for ClassDefModule {
+    fn register_library(lua: &mlua::Lua, registry: &mlua::Table) -> mlua::Result<()> {
+        let class_table = lua.create_table()?;
+        // 从定义目录加载定义文件，返回加载的类名列表
+        class_table.set(
+            "load",
+            lua.create_function(|_, path: String| {
+                let full_path = create_abs_path_in(CLASS_DEFS_DIR, &path)?;
+                ClassRegistry::instance()
+                    .load_file(&full_path)
+                    .map_err(|e| e.into_lua_err())
+            })?,
+        )?;
+        // 使用 Lua table 定义类
+        class_table.set(
+            "define",
+            lua.create_function(|lua, def: LuaValue| {
+                let def: ClassDef = lua.from_value(def)?;
+                let name = def.name.clone();
+                ClassRegistry::instance().register(def);
+                Ok(name)
+            })?,
+        )?;
+        // 列出所有已定义的类
+        class_table.set(
+            "list",
+            lua.create_function(|_, ()| Ok(ClassRegistry::instance().class_names()))?,
+        )?;
+        // 将指针包装为指定类的对象
+        class_table.set(
+            "new",
+            lua.create_function(|_, (name, ptr): (String, LuaPtr)| {
+                let def = ClassRegistry::instance()
+                    .get(&name)
+                    .ok_or(Error::ClassDefNotFound(name).into_lua_err())?;
+                Ok(GameClassObject::new(def, ptr.to_usize()))
+            })?,
+        )?;
+        // 获取类定义中声明的单例对象
+        class_table.set(
+            "singleton",
+            lua.create_function(|_, name: String| {
+                let def = ClassRegistry::instance()
+                    .get(&name)
+                    .ok_or(Error::ClassDefNotFound(name.clone()).into_lua_err())?;
+                let singleton_name = def.singleton.clone().unwrap_or(name);
+                let address = SingletonManager::instance()
+                    .get_address(&singleton_name)
+                    .ok_or(Error::SingletonNotFound(singleton_name).into_lua_err())?;
+                Ok(GameClassObject::new(def, address))
+            })?,
+        )?;
+
+        registry.set("ClassDef", class_table)?;
+
+        Ok(())
+    }
+}
+
+/// 定义文件结构
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClassDefFile {
+    #[serde(default)]
+    pub classes: Vec<ClassDef>,
+}
+
+/// 类定义
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassDef {
+    pub name: String,
+    /// 单例名称，为空时使用类名
+    #[serde(default)]
+    pub singleton: Option<String>,
+    #[serde(default)]
+    pub fields: Vec<FieldDef>,
+    #[serde(default)]
+    pub methods: Vec<MethodDef>,
+}
+
+impl ClassDef {
+    fn field(&self, name: &str) -> Option<&FieldDef> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    pub fn method(&self, name: &str) -> Option<&MethodDef> {
+        self.methods.iter().find(|m| m.name == name)
+    }
+}
+
+/// 字段定义
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldDef {
+    pub name: String,
+    pub offset: isize,
+    #[serde(rename = "type")]
+    pub ty: FieldType,
+    #[serde(default)]
+    pub readonly: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    Bool,
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    I64,
+    U64,
+    F32,
+    F64,
+    Pointer,
+    String,
+}
+
+impl FieldType {
+    pub(super) fn size(&self) -> u32 {
+        match self {
+            FieldType::Bool | FieldType::I8 | FieldType::U8 => 1,
+            FieldType::I16 | FieldType::U16 => 2,
+            FieldType::I32 | FieldType::U32 | FieldType::F32 => 4,
+            FieldType::I64 | FieldType::U64 | FieldType::F64 | FieldType::Pointer => 8,
+            FieldType::String => 0,
+        }
+    }
+}
+
+/// 方法定义
+///
+/// 函数地址由 `record`（AddressRepository 记录名）或 `vtable_index`（虚函数索引）给出。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MethodDef {
+    pub name: String,
+    #[serde(default)]
+    pub record: Option<String>,
+    #[serde(default)]
+    pub vtable_index: Option<usize>,
+    /// 参数类型名列表，与 `call_native_function` 相同
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub ret: Option<String>,
+    /// 是否将对象指针作为第一个参数传入
+    #[serde(default = "default_true")]
+    pub this_call: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 全局类定义表
+pub struct ClassRegistry {
+    classes: Mutex<HashMap<String, Arc<ClassDef>>>,
+}
+
+impl ClassRegistry {
+    pub fn instance() -> &'static ClassRegistry {
+        static INSTANCE: LazyLock<ClassRegistry> = LazyLock::new(ClassRegistry::new_with_defs_dir);
+        &INSTANCE
+    }
+
+    pub fn register(&self, def: ClassDef) {
+        self.classes.lock().insert(def.name.clone(), Arc::new(def));
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<ClassDef>> {
+        self.classes.lock().get(name).cloned()
+    }
+
+    pub fn class_names(&self) -> Vec<String> {
+        let mut names = self.classes.lock().keys().cloned().collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    /// 加载定义文件，返回加载的类名
+    pub fn load_file(&self, path: &Path) -> Result<Vec<String>> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            Error::IoWithContext(e, format!("Failed to read defs file '{}'", path.display()))
+        })?;
+        let file: ClassDefFile = if path.extension() == Some("json".as_ref()) {
+            serde_json::from_str(&content)
+                .map_err(|e| Error::InvalidValue("class defs json", e.to_string()))?
+        } else {
+            toml::from_str(&content)
+                .map_err(|e| Error::InvalidValue("class defs toml", e.to_string()))?
+        };
+
+        let names = file.classes.iter().map(|c| c.name.clone()).collect();
+        for def in file.classes {
+            self.register(def);
+        }
+        Ok(names)
+    }
+
+    fn new_with_defs_dir() -> Self {
+        let this = Self {
+            classes: Mutex::new(HashMap::new()),
+        };
+
+        let Ok(entries) = std::fs::read_dir(CLASS_DEFS_DIR) else {
+            return this;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_defs = path
+                .extension()
+                .is_some_and(|ext| ext == "toml" || ext == "json");
+            if !path.is_file() || !is_defs {
+                continue;
+            }
+            match this.load_file(&path) {
+                Ok(names) => log::debug!(
+                    "Loaded {} class defs from '{}'",
+                    names.len(),
+                    path.display()
+                ),
+                Err(e) => log::error!(
+                    "Failed to load class defs '{}': {}",
+                    path.display(),
+                    e.log()
+                ),
+            }
+        }
+
+        this
+    }
+}
+
+/// 绑定了类定义的游戏对象
+#[derive(Clone)]
+pub struct GameClassObject {
+    def: Arc<ClassDef>,
+    ptr: usize,
+}
+
+impl GameClassObject {
+    pub fn new(def: Arc<ClassDef>, ptr: usize) -> Self {
+        Self { def, ptr }
+    }
+
+    fn read_field(&self, lua: &Lua, field: &FieldDef) -> LuaResult<LuaValue> {
+        let address = (self.ptr as isize + field.offset) as usize;
+        read_value(lua, address, field.ty)
+    }
+
+    fn write_field(&self, lua: &Lua, field: &FieldDef, value: LuaValue) -> LuaResult<()> {
+        if field.readonly {
+            return Err(LuaError::external(format!(
+                "field '{}.{}' is readonly",
+                self.def.name, field.name
+            )));
+        }
+        if field.ty == FieldType::String {
+            return Err(LuaError::external(format!(
+                "string field '{}.{}' is not writable",
+                self.def.name, field.name
+            )));
+        }
+        let address = (self.ptr as isize + field.offset) as usize;
+        write_value(lua, address, field.ty, value)
+    }
+
+    fn resolve_method_address(&self, method: &MethodDef) -> LuaResult<u64> {
+        if let Some(record) = &method.record {
+            let address = AddressRepository::instance()
+                .get_address(record)
+                .map_err(|e| e.into_lua_err())?;
+            return Ok(address as u64);
+        }
+        if let Some(index) = method.vtable_index {
+            MemoryUtils::check_permission_read(self.ptr).map_err(|e| e.into_lua_err())?;
+            let obj = EmptyGameObject::from_address(self.ptr);
+            let fun = obj.get_virtual_function(index).ok_or_else(|| {
+                LuaError::external(format!(
+                    "virtual function {} of '{}' is null",
+                    index, self.def.name
+                ))
+            })?;
+            return Ok(fun as u64);
+        }
+
+        Err(LuaError::external(format!(
+            "method '{}.{}' has no address",
+            self.def.name, method.name
+        )))
+    }
+
+    fn call_method(
+        &self,
+        method: &MethodDef,
+        args: mlua::Variadic<LuaValue>,
+    ) -> LuaResult<LuaValue> {
+        if args.len() != method.args.len() {
+            return Err(LuaError::external(format!(
+                "method '{}.{}' expects {} arguments, got {}",
+                self.def.name,
+                method.name,
+                method.args.len(),
+                args.len()
+            )));
+        }
+
+        let fun = self.resolve_method_address(method)?;
+
+        let mut ffi_args = Vec::with_capacity(args.len() + 1);
+        if method.this_call {
+            ffi_args.push(Argument::Pointer(self.ptr));
+        }
+        for (type_name, value) in method.args.iter().zip(args.iter()) {
+            ffi_args.push(Argument::from_type_name_value(type_name, value)?);
+        }
+        let ret_type = method.ret.as_deref().and_then(ArgumentType::from_type_name);
+
+        ffi_call::call_native_function(fun, ffi_args, ret_type, false)
+    }
+}
+
+impl LuaUserData for GameClassObject {
+    fn add_fields<F: LuaUserDataFields<Self>>(fields: &mut F) {
+        fields.add_meta_field(LuaMetaMethod::Type, "GameClassObject");
+    }
+
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        methods.add_meta_method(LuaMetaMethod::ToString, |_, this, ()| {
+            Ok(format!("{}(0x{:016X})", this.def.name, this.ptr))
+        });
+        methods.add_meta_method(LuaMetaMethod::Index, |lua, this, key: String| {
+            match key.as_str() {
+                "_ptr" => return LuaPtr::new(this.ptr as u64).into_lua(lua),
+                "_class" => return this.def.name.as_str().into_lua(lua),
+                _ => {}
+            }
+
+            if let Some(field) = this.def.field(&key) {
+                return this.read_field(lua, field);
+            }
+            if this.def.method(&key).is_some() {
+                // 以 obj:method(...) 形式调用
+                let method_name = key.clone();
+                let fun = lua.create_function(
+                    move |lua,
+                          (obj, args): (
+                        LuaUserDataRef<GameClassObject>,
+                        mlua::Variadic<LuaValue>,
+                    )| {
+                        let method = obj.def.method(&method_name).ok_or_else(|| {
+                            Error::ClassMemberNotFound(obj.def.name.clone(), method_name.clone())
+                                .into_lua_err()
+                        })?;
+                        SafetyPolicy::check_lua(lua, "ClassDef method call")?;
+                        obj.call_method(method, args)
+                    },
+                )?;
+                return Ok(LuaValue::Function(fun));
+            }
+
+            Err(Error::ClassMemberNotFound(this.def.name.clone(), key).into_lua_err())
+        });
+        methods.add_meta_method(
+            LuaMetaMethod::NewIndex,
+            |lua, this, (key, value): (String, LuaValue)| {
+                let Some(field) = this.def.field(&key) else {
+                    return Err(
+                        Error::ClassMemberNotFound(this.def.name.clone(), key).into_lua_err()
+                    );
+                };
+                this.write_field(lua, field, value)
+            },
+        );
+    }
+}
+
+/// 按字段类型读取内存中的值
+pub(super) fn read_value(lua: &Lua, address: usize, ty: FieldType) -> LuaResult<LuaValue> {
+    if ty == FieldType::String {
+        let bytes =
+            MemoryUtils::read_c_string(address, MAX_C_STRING_LEN).map_err(|e| e.into_lua_err())?;
+        return Ok(LuaValue::String(lua.create_string(bytes)?));
+    }
+
+    let bytes = luaptr::quick_read_bytes(lua, address, ty.size()).into_lua_err()?;
+    let value = match ty {
+        FieldType::Bool => LuaValue::Boolean(bytes[0] != 0),
+        FieldType::I8 => LuaValue::Integer(bytes[0] as i8 as i64),
+        FieldType::U8 => LuaValue::Integer(bytes[0] as i64),
+        FieldType::I16 => LuaValue::Integer(i16::from_le_bytes([bytes[0], bytes[1]]) as i64),
+        FieldType::U16 => LuaValue::Integer(u16::from_le_bytes([bytes[0], bytes[1]]) as i64),
+        FieldType::I32 => {
+            LuaValue::Integer(i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as i64)
+        }
+        FieldType::U32 => {
+            LuaValue::Integer(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as i64)
+        }
+        FieldType::I64 | FieldType::U64 => LuaValue::Integer(i64::from_le_bytes(bytes)),
+        FieldType::F32 => {
+            LuaValue::Number(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64)
+        }
+        FieldType::F64 => LuaValue::Number(f64::from_le_bytes(bytes)),
+        FieldType::Pointer => LuaPtr::new(u64::from_le_bytes(bytes)).into_lua(lua)?,
+        FieldType::String => unreachable!(),
+    };
+
+    Ok(value)
+}
+
+/// 按字段类型写入值，不支持字符串类型
+pub(super) fn write_value(
+    lua: &Lua,
+    address: usize,
+    ty: FieldType,
+    value: LuaValue,
+) -> LuaResult<()> {
+    let bytes: Vec<u8> = match ty {
+        FieldType::Bool => vec![bool::from_lua(value, lua)? as u8],
+        FieldType::F32 => f32::from_lua(value, lua)?.to_le_bytes().to_vec(),
+        FieldType::F64 => f64::from_lua(value, lua)?.to_le_bytes().to_vec(),
+        FieldType::Pointer => LuaPtr::from_lua(value, lua)?
+            .to_u64()
+            .to_le_bytes()
+            .to_vec(),
+        FieldType::String => {
+            return Err(LuaError::external("string value is not writable"));
+        }
+        ty => {
+            let integer = i64::from_lua(value, lua)?;
+            integer.to_le_bytes()[..ty.size() as usize].to_vec()
+        }
+    };
+
+    luaptr::write_bytes(lua, address, &bytes).into_lua_err()
+}
@@ -0,0 +1,313 @@
+//! 基于 frida X86Writer/X86Relocator 的代码写入接口
+//!
+//! 相比 Memory.patch 直接写入字节数组，CodeWriter 会自动处理跳转编码、
+//! 指令重定位和指令缓存刷新，适合编写跳板和代码岛。
+
+use std::{collections::HashMap, sync::LazyLock};
+
+use frida_gum::instruction_writer::{InstructionWriter, X86InstructionWriter, X86Relocator};
+use mlua::prelude::*;
+use parking_lot::Mutex;
+
+use super::{buffer::Buffer, luaptr::LuaPtr, memory::MemoryPatchManager};
+use crate::{
+    error::{Error, Result},
+    luavm::{library::LuaModule, safety::SafetyPolicy},
+    memory::MemoryUtils,
+};
+
+/// 单条重定位指令的最大输出长度
+const MAX_RELOCATED_INSN_SIZE: usize = 32;
+/// 绝对跳转最大长度：jmp [rip+0]; dq address
+const MAX_JMP_SIZE: usize = 14;
+
+pub struct CodeWriterModule;
+
+impl LuaModule for CodeWriterModule {
+    fn register_library(lua: &mlua::Lua, registry: &mlua::Table) -> mlua::Result<()> {
+        let code_writer_table = lua.create_table()?;
+        // 分配可执行代码岛
+        code_writer_table.set(
+            "alloc",
+            lua.create_function(|lua, size: usize| {
+                let address = CodeIslandManager::instance()
+                    .alloc(size)
+                    .map_err(|e| e.into_lua_err())?;
+
+                let island_table = lua.globals().get::<LuaTable>("_code_islands")?;
+                island_table.push(LuaPtr::new(address as u64))?;
+
+                Ok(LuaPtr::new(address as u64))
+            })?,
+        )?;
+        // 释放当前脚本分配的代码岛
+        code_writer_table.set(
+            "free",
+            lua.create_function(|lua, ptr: LuaPtr| {
+                let address = ptr.to_usize();
+                let island_table = lua.globals().get::<LuaTable>("_code_islands")?;
+                let Some(index) = owned_island_index(&island_table, address)? else {
+                    return Err(not_owned_island(address));
+                };
+                island_table.raw_remove(index)?;
+
+                CodeIslandManager::instance()
+                    .free(address)
+                    .map_err(|e| e.into_lua_err())
+            })?,
+        )?;
+        // 向代码岛写入代码，返回写入的字节数
+        code_writer_table.set(
+            "write",
+            lua.create_function(|lua, (ptr, fun): (LuaPtr, LuaFunction)| {
+                SafetyPolicy::check_lua(lua, "CodeWriter.write")?;
+
+                let address = ptr.to_usize();
+                let island_table = lua.globals().get::<LuaTable>("_code_islands")?;
+                if owned_island_index(&island_table, address)?.is_none() {
+                    return Err(not_owned_island(address));
+                }
+                let Some(capacity) = CodeIslandManager::instance().capacity(address) else {
+                    return Err(not_owned_island(address));
+                };
+
+                let written = write_code(lua, address, capacity, &fun)?;
+                MemoryUtils::flush_instruction_cache(address, capacity)
+                    .map_err(|e| Error::from(e).into_lua_err())?;
+
+                Ok(written)
+            })?,
+        )?;
+        // 原地修改代码，剩余空间填充 nop，可通过 Memory.restore_patch 还原
+        code_writer_table.set(
+            "patch",
+            lua.create_function(|lua, (ptr, size, fun): (LuaPtr, usize, LuaFunction)| {
+                SafetyPolicy::check_lua(lua, "CodeWriter.patch")?;
+
+                let address = ptr.to_usize();
+                MemoryPatchManager::instance()
+                    .new_patch_code(address, size, || {
+                        let written = write_code(lua, address, size, &fun)?;
+                        unsafe {
+                            std::ptr::write_bytes(
+                                (address + written) as *mut u8,
+                                0x90,
+                                size - written,
+                            );
+                        }
+                        Ok(())
+                    })
+                    .map_err(|e| e.into_lua_err())?;
+
+                let patch_table = lua.globals().get::<LuaTable>("_patches")?;
+                patch_table.push(ptr)?;
+
+                Ok(ptr)
+            })?,
+        )?;
+
+        registry.set("CodeWriter", code_writer_table)?;
+
+        lua.globals().set("_code_islands", lua.create_table()?)?;
+
+        Ok(())
+    }
+}
+
+impl CodeWriterModule {
+    /// 释放虚拟机分配的所有代码岛
+    pub fn free_all_islands(lua: &Lua) -> Result<()> {
+        let island_table = lua.globals().get::<LuaTable>("_code_islands")?;
+
+        let manager = CodeIslandManager::instance();
+        for ptr in island_table.sequence_values() {
+            let ptr: LuaPtr = ptr?;
+            manager.free(ptr.to_usize())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// 查找当前虚拟机分配的代码岛在 `_code_islands` 中的序号
+fn owned_island_index(island_table: &LuaTable, address: usize) -> LuaResult<Option<usize>> {
+    for (i, ptr) in island_table.sequence_values::<LuaPtr>().enumerate() {
+        if ptr?.to_usize() == address {
+            return Ok(Some(i + 1));
+        }
+    }
+    Ok(None)
+}
+
+fn not_owned_island(address: usize) -> LuaError {
+    Error::InvalidValue(
+        "pointer returned by CodeWriter.alloc",
+        format!("0x{:x}", address),
+    )
+    .into_lua_err()
+}
+
+/// 在 `address` 处创建写入器并调用 Lua 回调，返回写入的字节数
+fn write_code(lua: &Lua, address: usize, capacity: usize, fun: &LuaFunction) -> Result<usize> {
+    let mut code_writer = LuaCodeWriter {
+        writer: X86InstructionWriter::new(address as u64),
+        capacity,
+    };
+
+    lua.scope(|scope| {
+        let writer_ud = scope.create_userdata_ref_mut(&mut code_writer)?;
+        fun.call::<()>(writer_ud)
+    })?;
+
+    code_writer.writer.flush();
+    let written = code_writer.offset();
+    if written > capacity {
+        // 不应发生，写入前已检查
+        log::error!(
+            "CodeWriter overflow at 0x{:x}: {} > {}",
+            address,
+            written,
+            capacity
+        );
+    }
+
+    Ok(written.min(capacity))
+}
+
+/// 传入 Lua 回调的写入器
+struct LuaCodeWriter {
+    writer: X86InstructionWriter,
+    capacity: usize,
+}
+
+unsafe impl Send for LuaCodeWriter {}
+
+impl LuaCodeWriter {
+    fn offset(&self) -> usize {
+        self.writer.offset() as usize
+    }
+
+    /// 检查剩余空间
+    fn reserve(&self, size: usize) -> LuaResult<()> {
+        if self.offset() + size > self.capacity {
+            return Err(Error::InvalidValue(
+                "enough space in code buffer",
+                format!(
+                    "{} bytes needed, {} bytes left",
+                    size,
+                    self.capacity - self.offset()
+                ),
+            )
+            .into_lua_err());
+        }
+        Ok(())
+    }
+
+    fn put_bytes(&mut self, bytes: &[u8]) -> LuaResult<()> {
+        self.reserve(bytes.len())?;
+        self.writer.put_bytes(bytes);
+        Ok(())
+    }
+
+    /// 重定位 `src` 处至少 `min_bytes` 字节的完整指令，返回读取的源字节数
+    fn relocate(&mut self, src: usize, min_bytes: usize) -> LuaResult<usize> {
+        MemoryUtils::check_permission_execute(src).map_err(|e| Error::from(e).into_lua_err())?;
+
+        let offset = self.offset();
+        let capacity = self.capacity;
+        let mut relocator = X86Relocator::new(src as u64, &mut self.writer);
+
+        let mut read = 0;
+        let mut insn_count = 0;
+        while read < min_bytes {
+            let (total, _) = relocator.read_one();
+            if total == 0 {
+                return Err(LuaError::external(format!(
+                    "failed to decode instruction at 0x{:x}",
+                    src + read
+                )));
+            }
+            read = total as usize;
+            insn_count += 1;
+            if relocator.eoi() {
+                break;
+            }
+        }
+
+        if offset + insn_count * MAX_RELOCATED_INSN_SIZE > capacity {
+            return Err(Error::InvalidValue(
+                "enough space in code buffer",
+                format!("{} instructions to relocate", insn_count),
+            )
+            .into_lua_err());
+        }
+        relocator.write_all();
+
+        Ok(read)
+    }
+}
+
+impl LuaUserData for LuaCodeWriter {
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        // 已写入的字节数
+        methods.add_method("offset", |_, this, ()| Ok(this.offset()));
+        // 当前写入位置对应的地址
+        methods.add_method("pc", |_, this, ()| Ok(LuaPtr::new(this.writer.pc())));
+        methods.add_method_mut("put_bytes", |_, this, bytes: Buffer| this.put_bytes(&bytes));
+        methods.add_method_mut("put_nop", |_, this, count: Option<usize>| {
+            this.put_bytes(&vec![0x90; count.unwrap_or(1)])
+        });
+        methods.add_method_mut("put_ret", |_, this, ()| this.put_bytes(&[0xC3]));
+        // 跳转到目标地址，距离较远时自动使用绝对跳转
+        methods.add_method_mut("put_jmp", |_, this, target: LuaPtr| {
+            this.reserve(MAX_JMP_SIZE)?;
+            this.writer.put_jmp_address(target.to_u64());
+            Ok(())
+        });
+        // 绝对调用：call [rip+2]; jmp +8; dq address
+        methods.add_method_mut("put_call", |_, this, target: LuaPtr| {
+            let mut code = vec![0xFF, 0x15, 0x02, 0x00, 0x00, 0x00, 0xEB, 0x08];
+            code.extend_from_slice(&target.to_u64().to_le_bytes());
+            this.put_bytes(&code)
+        });
+        methods.add_method_mut("relocate", |_, this, (src, min_bytes): (LuaPtr, usize)| {
+            this.relocate(src.to_usize(), min_bytes)
+        });
+    }
+}
+
+/// 可执行代码岛管理
+#[derive(Default)]
+struct CodeIslandManager {
+    /// 地址 -> 大小
+    islands: Mutex<HashMap<usize, usize>>,
+}
+
+impl CodeIslandManager {
+    fn instance() -> &'static Self {
+        static INSTANCE: LazyLock<CodeIslandManager> = LazyLock::new(CodeIslandManager::default);
+        &INSTANCE
+    }
+
+    fn alloc(&self, size: usize) -> Result<usize> {
+        let address = MemoryUtils::alloc_executable(size)?;
+        // 使用 int3 填充，避免执行到未写入的区域
+        unsafe {
+            std::ptr::write_bytes(address as *mut u8, 0xCC, size);
+        }
+        self.islands.lock().insert(address, size);
+        Ok(address)
+    }
+
+    fn free(&self, address: usize) -> Result<bool> {
+        if self.islands.lock().remove(&address).is_none() {
+            return Ok(false);
+        }
+        MemoryUtils::free_executable(address)?;
+        Ok(true)
+    }
+
+    fn capacity(&self, address: usize) -> Option<usize> {
+        self.islands.lock().get(&address).copied()
+    }
+}
@@ -0,0 +1,52 @@
+use mlua::prelude::*;
+
+use crate::{game::damage_number, luavm::library::LuaModule};
+
+pub struct DamageNumberModule;
+
+impl LuaModule for DamageNumberModule {
+    fn register_library(lua: &mlua::Lua, registry: &mlua::Table) -> mlua::Result<()> {
+        let damage_number_table = lua.create_table()?;
+
+        // 正在显示的伤害数字
+        damage_number_table.set(
+            "active",
+            lua.create_function(|lua, ()| {
+                let numbers = damage_number::active_numbers();
+                let list = lua.create_table_with_capacity(numbers.len(), 0)?;
+                for number in numbers {
+                    let entry = lua.create_table()?;
+                    entry.set("value", number.value)?;
+                    entry.set("critical", number.critical)?;
+                    entry.set("weak_point", number.weak_point)?;
+                    entry.set("elapsed", number.elapsed)?;
+                    entry.set("color", number.color)?;
+                    let position = lua.create_table()?;
+                    position.set("x", number.position.x)?;
+                    position.set("y", number.position.y)?;
+                    position.set("z", number.position.z)?;
+                    entry.set("position", position)?;
+                    list.push(entry)?;
+                }
+                Ok(list)
+            })?,
+        )?;
+        // 格式化数字，默认不保留小数，千位分隔符为 ","
+        damage_number_table.set(
+            "format",
+            lua.create_function(
+                |_, (value, decimals, separator): (f64, Option<usize>, Option<String>)| {
+                    Ok(damage_number::format_number(
+                        value,
+                        decimals.unwrap_or(0).min(9),
+                        separator.as_deref().unwrap_or(","),
+                    ))
+                },
+            )?,
+        )?;
+
+        registry.set("DamageNumber", damage_number_table)?;
+
+        Ok(())
+    }
+}
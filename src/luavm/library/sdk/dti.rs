@@ -0,0 +1,154 @@
+//! DTI 反射
+//!
+//! 脚本在运行时查询 MT Framework 的类型信息：枚举类、获取对象的类名和继承链、
+//! 通过 DTI 创建实例以及读取对象的属性。
+
+use mlua::prelude::*;
+
+use super::luaptr::LuaPtr;
+use crate::{
+    error::Error,
+    game::{
+        dti::DtiRegistry,
+        mt_type::{GameObject, MtDti},
+    },
+    luavm::{library::LuaModule, safety::SafetyPolicy},
+};
+
+/// 虚拟机通过 DTI.new 创建的实例，键为地址
+const INSTANCES_KEY: &str = "_dti_instances";
+
+pub struct DtiModule;
+
+impl LuaModule for DtiModule {
+    fn register_library(lua: &mlua::Lua, registry: &mlua::Table) -> mlua::Result<()> {
+        let dti_table = lua.create_table()?;
+        // 列出所有已注册的类名
+        dti_table.set(
+            "list",
+            lua.create_function(|_, ()| Ok(DtiRegistry::instance().class_names()))?,
+        )?;
+        // 按类名查找类型信息，未找到时返回 nil
+        dti_table.set(
+            "find",
+            lua.create_function(|lua, name: String| {
+                DtiRegistry::instance()
+                    .find(&name)
+                    .map(|dti| dti_to_table(lua, &dti))
+                    .transpose()
+            })?,
+        )?;
+        // 获取对象的类型信息，不是 MtObject 时返回 nil
+        dti_table.set(
+            "of",
+            lua.create_function(|lua, ptr: LuaPtr| {
+                DtiRegistry::instance()
+                    .of_object(ptr.to_usize())
+                    .map(|dti| dti_to_table(lua, &dti))
+                    .transpose()
+            })?,
+        )?;
+        // 类及其父类名列表，从自身开始
+        dti_table.set(
+            "parents",
+            lua.create_function(|_, name: String| {
+                let registry = DtiRegistry::instance();
+                let dti = registry
+                    .find(&name)
+                    .ok_or(Error::DtiNotFound(name).into_lua_err())?;
+                Ok(registry
+                    .ancestors(&dti)
+                    .iter()
+                    .filter_map(|class| class.name().map(|name| name.to_string()))
+                    .collect::<Vec<_>>())
+            })?,
+        )?;
+        // 对象是否为指定类或其子类的实例
+        dti_table.set(
+            "is_a",
+            lua.create_function(|_, (ptr, name): (LuaPtr, String)| {
+                Ok(DtiRegistry::instance().is_instance_of(ptr.to_usize(), &name))
+            })?,
+        )?;
+        // 通过 DTI 的分配器创建新实例
+        dti_table.set(
+            "new",
+            lua.create_function(|lua, name: String| {
+                SafetyPolicy::check_lua(lua, "DTI.new")?;
+                let instance = DtiRegistry::instance()
+                    .new_instance(&name)
+                    .map_err(|e| e.into_lua_err())?;
+                let instances = lua.globals().get::<LuaTable>(INSTANCES_KEY)?;
+                instances.set(instance as i64, true)?;
+                Ok(LuaPtr::new(instance as u64))
+            })?,
+        )?;
+        // 释放 DTI.new 创建的实例，只能释放当前虚拟机创建的实例
+        dti_table.set(
+            "destroy",
+            lua.create_function(|lua, ptr: LuaPtr| {
+                SafetyPolicy::check_lua(lua, "DTI.destroy")?;
+                let address = ptr.to_usize();
+                let instances = lua.globals().get::<LuaTable>(INSTANCES_KEY)?;
+                if !instances.contains_key(address as i64)? {
+                    return Err(Error::InvalidValue(
+                        "instance created by DTI.new",
+                        format!("0x{:x}", address),
+                    )
+                    .into_lua_err());
+                }
+                instances.set(address as i64, LuaNil)?;
+                unsafe { DtiRegistry::instance().destroy(address) }.map_err(|e| e.into_lua_err())
+            })?,
+        )?;
+        // 读取对象的属性列表
+        dti_table.set(
+            "properties",
+            lua.create_function(|lua, ptr: LuaPtr| {
+                let properties = DtiRegistry::instance()
+                    .properties(ptr.to_usize())
+                    .map_err(|e| e.into_lua_err())?;
+                lua.to_value(&properties)
+            })?,
+        )?;
+
+        registry.set("DTI", dti_table)?;
+
+        lua.globals().set(INSTANCES_KEY, lua.create_table()?)?;
+
+        Ok(())
+    }
+}
+
+impl DtiModule {
+    /// 释放虚拟机通过 DTI.new 创建的所有实例
+    pub fn destroy_all_instances(lua: &Lua) -> crate::error::Result<()> {
+        let Some(instances) = lua.globals().get::<Option<LuaTable>>(INSTANCES_KEY)? else {
+            return Ok(());
+        };
+
+        let registry = DtiRegistry::instance();
+        for pair in instances.pairs::<i64, bool>() {
+            let (address, _) = pair?;
+            if let Err(e) = unsafe { registry.destroy(address as usize) } {
+                log::warn!("Failed to destroy DTI instance: {}", e);
+            }
+        }
+        instances.clear()?;
+
+        Ok(())
+    }
+}
+
+fn dti_to_table(lua: &Lua, dti: &MtDti) -> LuaResult<LuaTable> {
+    let table = lua.create_table()?;
+    table.set("name", dti.name())?;
+    table.set("address", LuaPtr::new(dti.as_address() as u64))?;
+    table.set("size", dti.size())?;
+    table.set("id", dti.id())?;
+    let parent = dti.parent();
+    if parent.as_address() != 0 && parent.as_address() != dti.as_address() {
+        table.set("parent", parent.name())?;
+    }
+    Ok(table)
+}
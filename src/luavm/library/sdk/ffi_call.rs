@@ -1,25 +1,55 @@
 #![allow(unnecessary_transmutes, clippy::missing_transmute_annotations)]
 
-use std::ffi::c_void;
+use std::{ffi::c_void, sync::Arc};
 
 use mlua::prelude::*;
 
 use crate::{
     error::Error,
     extension::CoreAPI,
-    luavm::library::{LuaModule, utility::UtilityModule},
+    luavm::{
+        library::{LuaModule, utility::UtilityModule},
+        safety::SafetyPolicy,
+    },
     memory::MemoryUtils,
-    static_mut,
+    static_mut, static_ref,
 };
 
-use super::{luaptr::LuaPtr, string::ManagedString};
+use super::{
+    luaptr::LuaPtr,
+    string::{ManagedString, PinnedString},
+};
+
+mod binding;
+mod callback;
+mod structure;
+
+pub use structure::{StructLayout, StructType};
 
 pub struct FFICallModule;
 
 static mut CALL_NATIVE_FUNCTION: Option<CallNativeFunction> = None;
+static mut CALL_NATIVE_FUNCTION_EX: Option<CallNativeFunctionEx> = None;
+static mut NEW_STRUCT_TYPE: Option<NewStructType> = None;
+static mut FREE_STRUCT_TYPE: Option<FreeStructType> = None;
+static mut PREPARE_CIF: Option<PrepareCif> = None;
+static mut CALL_CIF: Option<CallCif> = None;
+static mut FREE_CIF: Option<FreeCif> = None;
+static mut CREATE_CLOSURE: Option<CreateClosure> = None;
+static mut FREE_CLOSURE: Option<FreeClosure> = None;
+
+impl FFICallModule {
+    /// 释放虚拟机创建的原生回调
+    pub fn free_all_callbacks(lua: &Lua) -> crate::error::Result<()> {
+        callback::free_all(lua)
+    }
+}
 
 impl LuaModule for FFICallModule {
     fn register_library(lua: &mlua::Lua, registry: &mlua::Table) -> mlua::Result<()> {
+        lua.globals()
+            .set(callback::CALLBACKS_KEY, lua.create_table()?)?;
+
         if !CoreAPI::instance().has_extension("luaf_libffi") {
             log::debug!("No luaf_libffi extension, skipping libffi module initialization");
             return Ok(());
@@ -31,6 +61,61 @@ impl LuaModule for FFICallModule {
             "call_native_function",
             lua.create_function(lua_call_native_function)?,
         )?;
+        registry.set(
+            "struct",
+            lua.create_function(|_, fields: LuaTable| {
+                Ok(StructType(Arc::new(StructLayout::from_fields(&fields)?)))
+            })?,
+        )?;
+        registry.set(
+            "bind_function",
+            lua.create_function(|lua, params: LuaTable| {
+                SafetyPolicy::check_lua(lua, "bind_function")?;
+                binding::NativeFunction::from_params(&params)
+            })?,
+        )?;
+        registry.set(
+            "new_native_callback",
+            lua.create_function(
+                |lua,
+                 (fun, arg_type_names, ret_type_name, use_system_abi): (
+                    LuaFunction,
+                    Option<Vec<String>>,
+                    Option<String>,
+                    Option<bool>,
+                )| {
+                    SafetyPolicy::check_lua(lua, "new_native_callback")?;
+                    let args = arg_type_names
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|name| parse_type_name(name))
+                        .collect::<LuaResult<Vec<_>>>()?;
+                    let ret = ret_type_name
+                        .map(|name| parse_type_name(&name))
+                        .transpose()?
+                        .filter(|ty| !matches!(ty, ArgumentType::Void));
+
+                    let code = callback::CallbackRegistry::instance().create(
+                        lua,
+                        fun,
+                        args,
+                        ret,
+                        use_system_abi.unwrap_or(false),
+                    )?;
+                    let ptr = LuaPtr::new(code as u64);
+
+                    // 记录回调，以便脚本卸载时释放
+                    let callback_table = lua.globals().get::<LuaTable>(callback::CALLBACKS_KEY)?;
+                    callback_table.set(code as i64, true)?;
+
+                    Ok(ptr)
+                },
+            )?,
+        )?;
+        registry.set(
+            "free_native_callback",
+            lua.create_function(|lua, ptr: LuaPtr| callback::free(lua, ptr.to_usize()))?,
+        )?;
 
         Ok(())
     }
@@ -45,49 +130,119 @@ fn acquire_ffi_functions() {
         {
             *fun = Some(std::mem::transmute(call_c_function));
         };
+
+        // 旧版本扩展没有 CIF 接口，绑定的函数退回逐次调用
+        let core_api = CoreAPI::instance();
+        if let Some(prepare_cif) = core_api.get_function("libffi::prepare_cif")
+            && let Some(call_cif) = core_api.get_function("libffi::call_cif")
+            && let Some(free_cif) = core_api.get_function("libffi::free_cif")
+        {
+            *static_mut!(PREPARE_CIF) = Some(std::mem::transmute(prepare_cif));
+            *static_mut!(CALL_CIF) = Some(std::mem::transmute(call_cif));
+            *static_mut!(FREE_CIF) = Some(std::mem::transmute(free_cif));
+        }
+        if let Some(call_c_function_ex) = core_api.get_function("libffi::call_c_function_ex")
+            && let Some(new_struct_type) = core_api.get_function("libffi::new_struct_type")
+            && let Some(free_struct_type) = core_api.get_function("libffi::free_struct_type")
+        {
+            *static_mut!(CALL_NATIVE_FUNCTION_EX) = Some(std::mem::transmute(call_c_function_ex));
+            *static_mut!(NEW_STRUCT_TYPE) = Some(std::mem::transmute(new_struct_type));
+            *static_mut!(FREE_STRUCT_TYPE) = Some(std::mem::transmute(free_struct_type));
+        }
+        if let Some(create_closure) = core_api.get_function("libffi::create_closure")
+            && let Some(free_closure) = core_api.get_function("libffi::free_closure")
+        {
+            *static_mut!(CREATE_CLOSURE) = Some(std::mem::transmute(create_closure));
+            *static_mut!(FREE_CLOSURE) = Some(std::mem::transmute(free_closure));
+        }
     }
 }
 
 fn lua_call_native_function(
-    _lua: &Lua,
-    (fun_arg, args, ret_type_name, use_system_abi): (
+    lua: &Lua,
+    (fun_arg, args, ret_type_arg, use_system_abi, retains_args): (
         LuaValue,
         Vec<Argument>,
-        Option<String>,
+        LuaValue,
+        Option<bool>,
         Option<bool>,
     ),
 ) -> LuaResult<LuaValue> {
+    SafetyPolicy::check_lua(lua, "call_native_function")?;
     // 读取长整型
     let fun = lua_parse_long_integer(&fun_arg)?;
-    // 判断权限
-    MemoryUtils::check_permission_execute(fun as usize).map_err(|e| e.into_lua_err())?;
-    // 解析 ABI
-    const FFI_DEFAULT_ABI: u32 = 2;
-    const FFI_WIN64_ABI: u32 = 1;
+    // 会保存指针的函数，临时字符串在调用结束后即被释放
+    if retains_args.unwrap_or(false) {
+        let transient = args
+            .iter()
+            .filter(|arg| matches!(arg, Argument::String(_)))
+            .count();
+        if transient != 0 {
+            log::warn!(
+                "Passing {} transient string(s) to function 0x{:x} which retains arguments, use String.pin instead",
+                transient,
+                fun
+            );
+        }
+    }
+    // 解析返回值类型，接受类型名或 StructType
+    let ret_type = match ret_type_arg {
+        LuaValue::String(name) => ArgumentType::from_type_name(&name.to_str()?),
+        LuaNil => None,
+        other => Some(ArgumentType::Struct(StructType::from_lua(other, lua)?.0)),
+    };
 
-    let use_system_abi = use_system_abi.unwrap_or(false);
-    let abi = if use_system_abi {
-        FFI_WIN64_ABI
-    } else {
-        FFI_DEFAULT_ABI
+    // 结构体需要扩展的聚合类型支持
+    if matches!(ret_type, Some(ArgumentType::Struct(_)))
+        || args.iter().any(|arg| matches!(arg, Argument::Struct(..)))
+    {
+        return structure::call_with_structs(
+            lua,
+            fun,
+            args,
+            ret_type,
+            use_system_abi.unwrap_or(false),
+        );
+    }
+
+    call_native_function(fun, args, ret_type, use_system_abi.unwrap_or(false))
+}
+
+/// libffi 扩展是否已加载
+pub fn is_ffi_available() -> bool {
+    unsafe { static_ref!(CALL_NATIVE_FUNCTION).is_some() }
+}
+
+/// 调用原生函数
+///
+/// ret_type 为 `None` 或 `Void` 时无返回值。不支持按值传递结构体。
+pub fn call_native_function(
+    fun: u64,
+    args: Vec<Argument>,
+    ret_type: Option<ArgumentType>,
+    use_system_abi: bool,
+) -> LuaResult<LuaValue> {
+    let Some(call_c_function) = (unsafe { *static_ref!(CALL_NATIVE_FUNCTION) }) else {
+        return Err(Error::FFIUnavailable.into_lua_err());
     };
+    if matches!(ret_type, Some(ArgumentType::Struct(_)))
+        || args.iter().any(|arg| matches!(arg, Argument::Struct(..)))
+    {
+        return Err(LuaError::external(
+            "Struct arguments are only supported by sdk.call_native_function",
+        ));
+    }
 
-    // 解析返回值类型
-    let ret_type = ret_type_name.and_then(|name| {
-        let type_name = ArgumentType::from_type_name(&name)?;
-        if let ArgumentType::Void = type_name {
-            None
-        } else {
-            Some(type_name)
-        }
-    });
+    // 判断权限
+    MemoryUtils::check_permission_execute(fun as usize).map_err(|e| e.into_lua_err())?;
+    let abi = ffi_abi(use_system_abi);
 
-    let call_c_function = unsafe { CALL_NATIVE_FUNCTION.unwrap() };
+    // 解析返回值类型
+    let ret_type = ret_type.filter(|ty| !matches!(ty, ArgumentType::Void));
 
     // 转换参数
     let mut ffi_args = args
-        .iter()
-        .cloned()
+        .into_iter()
         .map(FFIArg::from_argument)
         .collect::<Vec<FFIArg>>();
     let mut ffi_arg_types = ffi_args
@@ -140,8 +295,26 @@ fn lua_call_native_function(
         );
     }
 
+    Ok(decode_return_value(ffi_ret_type, ret_val))
+}
+
+/// 解析 ABI
+fn ffi_abi(use_system_abi: bool) -> u32 {
+    const FFI_DEFAULT_ABI: u32 = 2;
+    const FFI_WIN64_ABI: u32 = 1;
+
+    if use_system_abi {
+        FFI_WIN64_ABI
+    } else {
+        FFI_DEFAULT_ABI
+    }
+}
+
+/// 将返回值转换为 Lua 值
+fn decode_return_value(ffi_ret_type: FFIArgType, ret_val: *mut c_void) -> LuaValue {
     match ffi_ret_type {
-        FFIArgType::Void => Ok(LuaNil),
+        // 结构体返回值由 structure::call_with_structs 处理
+        FFIArgType::Void | FFIArgType::Struct => LuaNil,
         FFIArgType::UInt8
         | FFIArgType::SInt8
         | FFIArgType::UInt16
@@ -150,14 +323,14 @@ fn lua_call_native_function(
         | FFIArgType::SInt32
         | FFIArgType::UInt64
         | FFIArgType::SInt64
-        | FFIArgType::Pointer => Ok(LuaValue::Integer(ret_val as i64)),
+        | FFIArgType::Pointer => LuaValue::Integer(ret_val as i64),
         FFIArgType::Float => {
             let container: f32 = unsafe { std::mem::transmute(ret_val as i32) };
-            Ok(LuaValue::Number(container as f64))
+            LuaValue::Number(container as f64)
         }
         FFIArgType::Double => {
             let val: f64 = unsafe { std::mem::transmute(ret_val) };
-            Ok(LuaValue::Number(val))
+            LuaValue::Number(val)
         }
     }
 }
@@ -198,6 +371,54 @@ type CallNativeFunction = unsafe extern "C" fn(
     abi: u32,
 ) -> i32;
 
+type CallNativeFunctionEx = unsafe extern "C" fn(
+    ptr: *mut c_void,
+    arg_types: *const usize,
+    args: *mut *mut c_void,
+    args_len: usize,
+    ret_type: usize,
+    ret_val: *mut c_void,
+    abi: u32,
+) -> i32;
+
+type NewStructType = unsafe extern "C" fn(field_types: *const i32, len: usize) -> *mut c_void;
+
+type FreeStructType = unsafe extern "C" fn(handle: *mut c_void);
+
+type PrepareCif = unsafe extern "C" fn(
+    arg_types: *const i32,
+    arg_types_len: usize,
+    ret_type: i32,
+    abi: u32,
+    out: *mut *mut c_void,
+) -> i32;
+
+type CallCif = unsafe extern "C" fn(
+    cif: *mut c_void,
+    ptr: *mut c_void,
+    args: *mut AnyVar,
+    args_len: usize,
+    ret_val: *mut AnyVar,
+) -> i32;
+
+type FreeCif = unsafe extern "C" fn(cif: *mut c_void);
+
+type ClosureCallback =
+    unsafe extern "C" fn(user_data: *mut c_void, args: *mut *mut c_void, ret: *mut c_void);
+
+type CreateClosure = unsafe extern "C" fn(
+    arg_types: *const i32,
+    arg_types_len: usize,
+    ret_type: i32,
+    abi: u32,
+    callback: ClosureCallback,
+    user_data: *mut c_void,
+    out_code: *mut *mut c_void,
+    out_handle: *mut *mut c_void,
+) -> i32;
+
+type FreeClosure = unsafe extern "C" fn(handle: *mut c_void);
+
 struct FFIArg {
     ty: FFIArgType,
     value: FFIValue,
@@ -226,6 +447,7 @@ impl FFIArg {
             }
             Argument::Pointer(v) => FFIValue::Simple(v as *mut c_void),
             Argument::String(vec) => FFIValue::Complex(vec),
+            Argument::Struct(_, bytes) => FFIValue::Complex(bytes),
         };
 
         FFIArg { ty, value }
@@ -249,7 +471,7 @@ impl FFIValue {
 
 #[repr(i32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum FFIArgType {
+pub enum FFIArgType {
     Void = 0,
     UInt8 = 1,
     SInt8 = 2,
@@ -262,10 +484,12 @@ enum FFIArgType {
     Float = 9,
     Double = 10,
     Pointer = 11,
+    /// 结构体，类型句柄单独传递
+    Struct = 12,
 }
 
 #[derive(Debug, Clone)]
-enum Argument {
+pub enum Argument {
     Void,
     UInt8(u8),
     SInt8(i8),
@@ -279,10 +503,12 @@ enum Argument {
     Double(f64),
     Pointer(usize),
     String(Vec<u8>),
+    /// 按值传递的结构体数据
+    Struct(Arc<StructLayout>, Vec<u8>),
 }
 
 impl FromLua for Argument {
-    fn from_lua(value: LuaValue, _lua: &Lua) -> LuaResult<Self> {
+    fn from_lua(value: LuaValue, lua: &Lua) -> LuaResult<Self> {
         if !value.is_table() && !value.is_nil() {
             return Err(LuaError::external(format!(
                 "Invalid argument: {}",
@@ -294,37 +520,59 @@ impl FromLua for Argument {
             return Ok(Argument::Void);
         }
 
-        // 模式：{ "type": "pointer", "value": 123 }
+        // 模式：{ "type": "pointer", "value": 123 }，结构体的 type 为 StructType
         let arg_param = value.as_table().unwrap();
-        let arg_type_name = arg_param.get::<String>("type")?;
         let arg_value = arg_param.get::<LuaValue>("value")?;
+        match arg_param.get::<LuaValue>("type")? {
+            LuaValue::String(name) => Argument::from_type_name_value(&name.to_str()?, &arg_value),
+            other => {
+                let layout = StructType::from_lua(other, lua)?.0;
+                Argument::from_type_value(&ArgumentType::Struct(layout), &arg_value)
+            }
+        }
+    }
+}
+
+impl Argument {
+    /// 根据类型名和 Lua 值构造参数
+    pub fn from_type_name_value(type_name: &str, arg_value: &LuaValue) -> LuaResult<Self> {
+        let ty = parse_type_name(type_name)?;
+        Self::from_type_value(&ty, arg_value)
+    }
 
-        let argument = match arg_type_name.as_str() {
-            "void" => Argument::Void,
-            "uint8" | "u8" => Argument::UInt8(parse_value_to_integer(&arg_value)? as u8),
-            "int8" | "i8" => Argument::SInt8(parse_value_to_integer(&arg_value)? as i8),
-            "uint16" | "u16" => Argument::UInt16(parse_value_to_integer(&arg_value)? as u16),
-            "int16" | "i16" => Argument::SInt16(parse_value_to_integer(&arg_value)? as i16),
-            "uint32" | "u32" => Argument::UInt32(parse_value_to_integer(&arg_value)? as u32),
-            "int32" | "i32" => Argument::SInt32(parse_value_to_integer(&arg_value)? as i32),
-            "uint64" | "u64" => Argument::UInt64(parse_value_to_integer(&arg_value)? as u64),
-            "int64" | "i64" => Argument::SInt64(parse_value_to_integer(&arg_value)?),
-            "float" | "f32" => Argument::Float(parse_value_to_float(&arg_value)? as f32),
-            "double" | "f64" => Argument::Double(parse_value_to_float(&arg_value)?),
-            "pointer" => Argument::Pointer(parse_value_to_integer(&arg_value)? as usize),
-            "string" => {
+    /// 根据类型和 Lua 值构造参数
+    pub fn from_type_value(ty: &ArgumentType, arg_value: &LuaValue) -> LuaResult<Self> {
+        let argument = match ty {
+            ArgumentType::Void => Argument::Void,
+            ArgumentType::UInt8 => Argument::UInt8(parse_value_to_integer(arg_value)? as u8),
+            ArgumentType::SInt8 => Argument::SInt8(parse_value_to_integer(arg_value)? as i8),
+            ArgumentType::UInt16 => Argument::UInt16(parse_value_to_integer(arg_value)? as u16),
+            ArgumentType::SInt16 => Argument::SInt16(parse_value_to_integer(arg_value)? as i16),
+            ArgumentType::UInt32 => Argument::UInt32(parse_value_to_integer(arg_value)? as u32),
+            ArgumentType::SInt32 => Argument::SInt32(parse_value_to_integer(arg_value)? as i32),
+            ArgumentType::UInt64 => Argument::UInt64(parse_value_to_integer(arg_value)? as u64),
+            ArgumentType::SInt64 => Argument::SInt64(parse_value_to_integer(arg_value)?),
+            ArgumentType::Float => Argument::Float(parse_value_to_float(arg_value)? as f32),
+            ArgumentType::Double => Argument::Double(parse_value_to_float(arg_value)?),
+            ArgumentType::Pointer => Argument::Pointer(parse_value_to_pointer(arg_value)? as usize),
+            ArgumentType::String => {
                 let ud = arg_value
                     .as_userdata()
                     .ok_or(Error::InvalidValue(
-                        "ManagedString",
+                        "ManagedString or PinnedString",
                         format!("{:?}", arg_value),
                     ))
                     .into_lua_err()?;
-                let string = ud.borrow::<ManagedString>()?;
-                Argument::String(string.to_bytes_with_nul())
+                // 固定字符串直接传递地址
+                if let Ok(pinned) = ud.borrow::<PinnedString>() {
+                    Argument::Pointer(pinned.address())
+                } else {
+                    let string = ud.borrow::<ManagedString>()?;
+                    Argument::String(string.to_bytes_with_nul())
+                }
             }
-            _ => {
-                return Err(Error::InvalidValue("argument type name", arg_type_name).into_lua_err());
+            ArgumentType::Struct(layout) => {
+                Argument::Struct(layout.clone(), layout.encode(arg_value)?)
             }
         };
 
@@ -332,6 +580,50 @@ impl FromLua for Argument {
     }
 }
 
+/// 将 Lua 值转换为通用寄存器中的值，用于设置 Hook 的参数与返回值
+///
+/// 接受 nil、布尔值、整数、浮点数（按 double 转换）、LuaPtr、UInt64，
+/// 以及 `{ type = "float", value = 1.5 }` 形式的显式类型参数。
+/// 浮点数按位写入通用寄存器，与 call_native_function 的参数转换一致。
+pub fn coerce_register_value(lua: &Lua, value: LuaValue) -> LuaResult<u64> {
+    let argument = match value {
+        LuaNil => return Ok(0),
+        LuaValue::Boolean(v) => return Ok(v as u64),
+        LuaValue::Integer(v) => return Ok(v as u64),
+        LuaValue::Number(v) if v.fract() == 0.0 && v.abs() <= u32::MAX as f64 => {
+            return Ok(v as i64 as u64);
+        }
+        LuaValue::Number(v) => Argument::Double(v),
+        LuaValue::Table(ref tbl) if tbl.contains_key("type")? => {
+            let arg_type_name = tbl.get::<String>("type")?;
+            let arg_value = tbl.get::<LuaValue>("value")?;
+            Argument::from_type_name_value(&arg_type_name, &arg_value)?
+        }
+        other => return Ok(LuaPtr::from_lua(other, lua)?.to_u64()),
+    };
+
+    register_value(argument)
+}
+
+/// 参数在寄存器中的值，浮点数按位保存
+pub fn register_value(argument: Argument) -> LuaResult<u64> {
+    match FFIArg::from_argument(argument).value {
+        FFIValue::Simple(v) => Ok(v as u64),
+        FFIValue::Complex(_) => Err(Error::InvalidValue(
+            "value fits in a register",
+            "string".to_string(),
+        )
+        .into_lua_err()),
+    }
+}
+
+/// 解析类型名，未知类型名返回错误
+pub fn parse_type_name(type_name: &str) -> LuaResult<ArgumentType> {
+    ArgumentType::from_type_name(type_name)
+        .ok_or_else(|| Error::InvalidValue("argument type name", type_name.to_string()))
+        .into_lua_err()
+}
+
 fn parse_value_to_integer(value: &LuaValue) -> LuaResult<i64> {
     value
         .as_integer()
@@ -339,6 +631,14 @@ fn parse_value_to_integer(value: &LuaValue) -> LuaResult<i64> {
         .into_lua_err()
 }
 
+/// 指针参数额外接受 LuaPtr 和 UInt64
+fn parse_value_to_pointer(value: &LuaValue) -> LuaResult<u64> {
+    match value {
+        LuaValue::Integer(v) => Ok(*v as u64),
+        other => lua_parse_long_integer(other),
+    }
+}
+
 fn parse_value_to_float(value: &LuaValue) -> LuaResult<f64> {
     value
         .as_number()
@@ -362,13 +662,33 @@ impl Argument {
             Argument::Double(_) => FFIArgType::Double,
             Argument::Pointer(_) => FFIArgType::Pointer,
             Argument::String(_) => FFIArgType::Pointer,
+            Argument::Struct(..) => FFIArgType::Struct,
+        }
+    }
+
+    /// 参数类型，字符串参数按指针处理
+    pub fn argument_type(&self) -> ArgumentType {
+        match self {
+            Argument::Void => ArgumentType::Void,
+            Argument::UInt8(_) => ArgumentType::UInt8,
+            Argument::SInt8(_) => ArgumentType::SInt8,
+            Argument::UInt16(_) => ArgumentType::UInt16,
+            Argument::SInt16(_) => ArgumentType::SInt16,
+            Argument::UInt32(_) => ArgumentType::UInt32,
+            Argument::SInt32(_) => ArgumentType::SInt32,
+            Argument::UInt64(_) => ArgumentType::UInt64,
+            Argument::SInt64(_) => ArgumentType::SInt64,
+            Argument::Float(_) => ArgumentType::Float,
+            Argument::Double(_) => ArgumentType::Double,
+            Argument::Pointer(_) | Argument::String(_) => ArgumentType::Pointer,
+            Argument::Struct(layout, _) => ArgumentType::Struct(layout.clone()),
         }
     }
 }
 
 /// 无 payload 的 Argument，通常用于返回值类型定义
 #[derive(Debug, Clone)]
-enum ArgumentType {
+pub enum ArgumentType {
     Void,
     UInt8,
     SInt8,
@@ -382,6 +702,7 @@ enum ArgumentType {
     Double,
     Pointer,
     String,
+    Struct(Arc<StructLayout>),
 }
 
 impl ArgumentType {
@@ -422,6 +743,53 @@ impl ArgumentType {
             ArgumentType::Double => FFIArgType::Double,
             ArgumentType::Pointer => FFIArgType::Pointer,
             ArgumentType::String => FFIArgType::Pointer,
+            ArgumentType::Struct(_) => FFIArgType::Struct,
         }
     }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, ArgumentType::Float | ArgumentType::Double)
+    }
+
+    /// 将寄存器或栈上的原始值转换为参数
+    pub fn argument_from_register(&self, raw: u64) -> Argument {
+        match self {
+            ArgumentType::Void => Argument::Void,
+            ArgumentType::UInt8 => Argument::UInt8(raw as u8),
+            ArgumentType::SInt8 => Argument::SInt8(raw as i8),
+            ArgumentType::UInt16 => Argument::UInt16(raw as u16),
+            ArgumentType::SInt16 => Argument::SInt16(raw as i16),
+            ArgumentType::UInt32 => Argument::UInt32(raw as u32),
+            ArgumentType::SInt32 => Argument::SInt32(raw as i32),
+            ArgumentType::UInt64 => Argument::UInt64(raw),
+            ArgumentType::SInt64 => Argument::SInt64(raw as i64),
+            ArgumentType::Float => Argument::Float(f32::from_bits(raw as u32)),
+            ArgumentType::Double => Argument::Double(f64::from_bits(raw)),
+            ArgumentType::Pointer | ArgumentType::String => Argument::Pointer(raw as usize),
+            ArgumentType::Struct(layout) => {
+                Argument::Struct(layout.clone(), layout.bytes_from_register(raw))
+            }
+        }
+    }
+
+    /// 将寄存器或栈上的原始值解码为 Lua 值，指针解码为 LuaPtr
+    pub fn decode_register(&self, lua: &Lua, raw: u64) -> LuaResult<LuaValue> {
+        let value = match self.argument_from_register(raw) {
+            Argument::Void => LuaNil,
+            Argument::UInt8(v) => LuaValue::Integer(v as i64),
+            Argument::SInt8(v) => LuaValue::Integer(v as i64),
+            Argument::UInt16(v) => LuaValue::Integer(v as i64),
+            Argument::SInt16(v) => LuaValue::Integer(v as i64),
+            Argument::UInt32(v) => LuaValue::Integer(v as i64),
+            Argument::SInt32(v) => LuaValue::Integer(v as i64),
+            Argument::UInt64(v) => LuaValue::Integer(v as i64),
+            Argument::SInt64(v) => LuaValue::Integer(v),
+            Argument::Float(v) => LuaValue::Number(v as f64),
+            Argument::Double(v) => LuaValue::Number(v),
+            Argument::Pointer(v) => LuaPtr::new(v as u64).into_lua(lua)?,
+            Argument::String(_) => unreachable!(),
+            Argument::Struct(layout, bytes) => LuaValue::Table(layout.decode(lua, &bytes)?),
+        };
+        Ok(value)
+    }
 }
@@ -0,0 +1,193 @@
+//! 绑定的原生函数
+//!
+//! 绑定时解析签名并构建 CIF，调用时直接按签名转换参数，
+//! 无需每次构造 `{ type = ..., value = ... }` 参数表。
+
+use std::ffi::c_void;
+
+use mlua::prelude::*;
+
+use super::{
+    Argument, ArgumentType, CALL_CIF, FFIArg, FFIArgType, FREE_CIF, PREPARE_CIF,
+    call_native_function, decode_return_value, ffi_abi, lua_parse_long_integer, parse_type_name,
+};
+use crate::{
+    error::Error,
+    luavm::{library::sdk::luaptr::LuaPtr, safety::SafetyPolicy},
+    memory::MemoryUtils,
+    static_ref,
+};
+
+/// 扩展中构建的 CIF 句柄
+struct PreparedCif(*mut c_void);
+
+unsafe impl Send for PreparedCif {}
+unsafe impl Sync for PreparedCif {}
+
+impl PreparedCif {
+    /// 扩展不支持 CIF 时返回 `None`
+    fn prepare(
+        args: &[ArgumentType],
+        ret: Option<&ArgumentType>,
+        abi: u32,
+    ) -> LuaResult<Option<Self>> {
+        let Some(prepare_cif) = (unsafe { *static_ref!(PREPARE_CIF) }) else {
+            return Ok(None);
+        };
+
+        let arg_types = args
+            .iter()
+            .map(|ty| ty.as_ffi_type() as i32)
+            .collect::<Vec<_>>();
+        let ret_type = ret.map_or(FFIArgType::Void, |ty| ty.as_ffi_type());
+        let mut cif = std::ptr::null_mut::<c_void>();
+        let code = unsafe {
+            prepare_cif(
+                arg_types.as_ptr(),
+                arg_types.len(),
+                ret_type as i32,
+                abi,
+                &mut cif,
+            )
+        };
+        if code != 0 || cif.is_null() {
+            return Err(LuaError::external(format!(
+                "Failed to prepare native function interface: code {}",
+                code
+            )));
+        }
+
+        Ok(Some(PreparedCif(cif)))
+    }
+}
+
+impl Drop for PreparedCif {
+    fn drop(&mut self) {
+        if let Some(free_cif) = unsafe { *static_ref!(FREE_CIF) } {
+            unsafe { free_cif(self.0) };
+        }
+    }
+}
+
+/// `sdk.bind_function` 返回的可调用对象
+pub struct NativeFunction {
+    name: Option<String>,
+    address: u64,
+    args: Vec<ArgumentType>,
+    /// `None` 表示无返回值
+    ret: Option<ArgumentType>,
+    use_system_abi: bool,
+    cif: Option<PreparedCif>,
+}
+
+impl NativeFunction {
+    /// 从 `{ address = ptr, args = {...}, ret = "float", name = "...", system_abi = false }` 创建
+    pub fn from_params(params: &LuaTable) -> LuaResult<Self> {
+        let address = lua_parse_long_integer(&params.get::<LuaValue>("address")?)?;
+        MemoryUtils::check_permission_execute(address as usize).map_err(|e| e.into_lua_err())?;
+
+        let args = params
+            .get::<Option<Vec<String>>>("args")?
+            .unwrap_or_default()
+            .iter()
+            .map(|name| parse_type_name(name))
+            .collect::<LuaResult<Vec<_>>>()?;
+        let ret = params
+            .get::<Option<String>>("ret")?
+            .map(|name| parse_type_name(&name))
+            .transpose()?
+            .filter(|ty| !matches!(ty, ArgumentType::Void));
+        let use_system_abi = params.get::<Option<bool>>("system_abi")?.unwrap_or(false);
+
+        let cif = PreparedCif::prepare(&args, ret.as_ref(), ffi_abi(use_system_abi))?;
+
+        Ok(Self {
+            name: params.get::<Option<String>>("name")?,
+            address,
+            args,
+            ret,
+            use_system_abi,
+            cif,
+        })
+    }
+
+    fn call(&self, lua: &Lua, values: LuaMultiValue) -> LuaResult<LuaValue> {
+        SafetyPolicy::check_lua(lua, "call_native_function")?;
+
+        if values.len() != self.args.len() {
+            return Err(Error::InvalidValue(
+                "argument count matching the signature",
+                format!("{} for {}", values.len(), self.display_name()),
+            )
+            .into_lua_err());
+        }
+        let args = self
+            .args
+            .iter()
+            .zip(values.iter())
+            .map(|(ty, value)| Argument::from_type_value(ty, value))
+            .collect::<LuaResult<Vec<_>>>()?;
+
+        let (Some(cif), Some(call_cif)) = (&self.cif, unsafe { *static_ref!(CALL_CIF) }) else {
+            return call_native_function(self.address, args, self.ret.clone(), self.use_system_abi);
+        };
+
+        let mut ffi_args = args
+            .into_iter()
+            .map(FFIArg::from_argument)
+            .collect::<Vec<_>>();
+        let mut ffi_arg_values = ffi_args
+            .iter_mut()
+            .map(|arg| arg.value.as_ptr())
+            .collect::<Vec<_>>();
+        let mut ret_val = std::ptr::null_mut::<c_void>();
+        let code = unsafe {
+            call_cif(
+                cif.0,
+                self.address as *mut _,
+                ffi_arg_values.as_mut_ptr(),
+                ffi_arg_values.len(),
+                &mut ret_val as *mut *mut c_void,
+            )
+        };
+        if code != 0 {
+            return Err(LuaError::external(format!(
+                "Failed to call {}: code {}",
+                self.display_name(),
+                code
+            )));
+        }
+
+        let ret_type = self
+            .ret
+            .as_ref()
+            .map_or(FFIArgType::Void, |ty| ty.as_ffi_type());
+        Ok(decode_return_value(ret_type, ret_val))
+    }
+
+    fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("0x{:x}", self.address),
+        }
+    }
+}
+
+impl LuaUserData for NativeFunction {
+    fn add_fields<F: LuaUserDataFields<Self>>(fields: &mut F) {
+        fields.add_meta_field(LuaMetaMethod::Type, "NativeFunction");
+        fields.add_field("_type", "NativeFunction");
+
+        fields.add_field_method_get("name", |_, this| Ok(this.name.clone()));
+        fields.add_field_method_get("address", |_, this| Ok(LuaPtr::new(this.address)));
+    }
+
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        methods.add_meta_method(LuaMetaMethod::Call, |lua, this, args: LuaMultiValue| {
+            this.call(lua, args)
+        });
+        methods.add_meta_method(LuaMetaMethod::ToString, |_, this, ()| {
+            Ok(format!("NativeFunction({})", this.display_name()))
+        });
+    }
+}
@@ -0,0 +1,245 @@
+//! 原生回调
+//!
+//! 通过 libffi 闭包将 Lua 函数包装为原生函数指针，传给需要 C 回调的游戏接口。
+//! 回调在全局锁中执行，脚本卸载时释放闭包，此后不能再调用该函数指针。
+//! 脚本只能释放自己创建的回调，正在执行的回调推迟到执行结束后释放。
+
+use std::{
+    collections::HashMap,
+    ffi::c_void,
+    sync::{
+        Arc, LazyLock,
+        atomic::{AtomicU32, Ordering},
+    },
+};
+
+use mlua::prelude::*;
+use parking_lot::Mutex;
+
+use super::{Argument, ArgumentType, CREATE_CLOSURE, FREE_CLOSURE, ffi_abi, register_value};
+use crate::{
+    error::{Error, Result},
+    luavm::{LuaVMManager, WeakLuaVM},
+    static_ref,
+};
+
+pub(super) const CALLBACKS_KEY: &str = "_native_callbacks";
+
+struct CallbackData {
+    vm_ref: WeakLuaVM,
+    callback: LuaFunction,
+    args: Vec<ArgumentType>,
+    /// `None` 表示无返回值
+    ret: Option<ArgumentType>,
+    /// 正在执行的调用数
+    running: AtomicU32,
+}
+
+struct NativeCallback {
+    /// 扩展中的闭包句柄
+    handle: usize,
+    data: *mut CallbackData,
+}
+
+unsafe impl Send for NativeCallback {}
+
+impl NativeCallback {
+    fn is_running(&self) -> bool {
+        unsafe { &*self.data }.running.load(Ordering::Acquire) != 0
+    }
+}
+
+impl Drop for NativeCallback {
+    fn drop(&mut self) {
+        if let Some(free_closure) = unsafe { *static_ref!(FREE_CLOSURE) } {
+            unsafe { free_closure(self.handle as *mut c_void) };
+        }
+        drop(unsafe { Box::from_raw(self.data) });
+    }
+}
+
+#[derive(Default)]
+pub struct CallbackRegistry {
+    /// 函数指针 -> 回调
+    callbacks: Mutex<HashMap<usize, NativeCallback>>,
+    /// 释放时仍在执行的回调
+    retired: Mutex<Vec<NativeCallback>>,
+}
+
+impl CallbackRegistry {
+    pub fn instance() -> &'static CallbackRegistry {
+        static INSTANCE: LazyLock<CallbackRegistry> = LazyLock::new(CallbackRegistry::default);
+        &INSTANCE
+    }
+
+    /// 创建回调，返回函数指针
+    pub fn create(
+        &self,
+        lua: &Lua,
+        callback: LuaFunction,
+        args: Vec<ArgumentType>,
+        ret: Option<ArgumentType>,
+        use_system_abi: bool,
+    ) -> LuaResult<usize> {
+        let Some(create_closure) = (unsafe { *static_ref!(CREATE_CLOSURE) }) else {
+            return Err(Error::FFIUnavailable.into_lua_err());
+        };
+        let Some(luavm) = LuaVMManager::instance().get_vm_by_lua(lua) else {
+            return Err(LuaError::external("Internal: invalid lua vm"));
+        };
+        self.release_retired();
+
+        let arg_types = args
+            .iter()
+            .map(|ty| ty.as_ffi_type() as i32)
+            .collect::<Vec<_>>();
+        let ret_type = ret.as_ref().map_or(0, |ty| ty.as_ffi_type() as i32);
+        let data = Box::into_raw(Box::new(CallbackData {
+            vm_ref: Arc::downgrade(&luavm),
+            callback,
+            args,
+            ret,
+            running: AtomicU32::new(0),
+        }));
+
+        let mut code = std::ptr::null_mut::<c_void>();
+        let mut handle = std::ptr::null_mut::<c_void>();
+        let status = unsafe {
+            create_closure(
+                arg_types.as_ptr(),
+                arg_types.len(),
+                ret_type,
+                ffi_abi(use_system_abi),
+                callback_entry,
+                data as *mut c_void,
+                &mut code,
+                &mut handle,
+            )
+        };
+        if status != 0 || code.is_null() {
+            drop(unsafe { Box::from_raw(data) });
+            return Err(LuaError::external(format!(
+                "Failed to create native callback: code {}",
+                status
+            )));
+        }
+
+        self.callbacks.lock().insert(
+            code as usize,
+            NativeCallback {
+                handle: handle as usize,
+                data,
+            },
+        );
+        Ok(code as usize)
+    }
+
+    /// 释放回调，回调正在执行时推迟到执行结束后释放
+    pub fn free(&self, code: usize) -> bool {
+        self.release_retired();
+        let Some(callback) = self.callbacks.lock().remove(&code) else {
+            return false;
+        };
+        if callback.is_running() {
+            self.retired.lock().push(callback);
+        }
+        true
+    }
+
+    /// 释放已执行结束的推迟回调
+    fn release_retired(&self) {
+        self.retired.lock().retain(|callback| callback.is_running());
+    }
+}
+
+/// 释放虚拟机创建的回调，只能释放本虚拟机创建的
+pub fn free(lua: &Lua, code: usize) -> LuaResult<bool> {
+    let callback_table = lua.globals().get::<LuaTable>(CALLBACKS_KEY)?;
+    let key = code as i64;
+    if !callback_table.contains_key(key)? {
+        return Ok(false);
+    }
+    callback_table.set(key, LuaNil)?;
+    Ok(CallbackRegistry::instance().free(code))
+}
+
+/// 释放虚拟机创建的所有回调
+pub fn free_all(lua: &Lua) -> Result<()> {
+    let Some(callback_table) = lua.globals().get::<Option<LuaTable>>(CALLBACKS_KEY)? else {
+        return Ok(());
+    };
+
+    let registry = CallbackRegistry::instance();
+    for pair in callback_table.pairs::<i64, bool>() {
+        let (code, _) = pair?;
+        registry.free(code as usize);
+    }
+    callback_table.clear()?;
+
+    Ok(())
+}
+
+/// 闭包入口，参数为各参数值的指针
+unsafe extern "C" fn callback_entry(
+    user_data: *mut c_void,
+    args: *mut *mut c_void,
+    ret: *mut c_void,
+) {
+    let data = unsafe { &*(user_data as *const CallbackData) };
+
+    data.running.fetch_add(1, Ordering::AcqRel);
+    let result = invoke_callback(data, args);
+    data.running.fetch_sub(1, Ordering::AcqRel);
+    let value = result.unwrap_or_else(|e| {
+        let err_msg = format!("native callback error:\n{}", e);
+        crate::error::set_last_error(err_msg.clone());
+        log::error!("{}", err_msg);
+        0
+    });
+    if data.ret.is_some() {
+        unsafe { (ret as *mut u64).write(value) };
+    }
+}
+
+fn invoke_callback(data: &CallbackData, args: *mut *mut c_void) -> Result<u64> {
+    let Some(luavm) = data.vm_ref.upgrade() else {
+        return Err(Error::LuaVMNotFound);
+    };
+    let lua = luavm.lua();
+
+    let mut values = LuaMultiValue::with_capacity(data.args.len());
+    for (index, ty) in data.args.iter().enumerate() {
+        let raw = unsafe { read_raw(ty, *args.add(index)) };
+        values.push_back(ty.decode_register(lua, raw)?);
+    }
+
+    let mut ret = LuaNil;
+    // 全局锁中执行回调
+    LuaVMManager::instance().run_with_lock(|_| {
+        ret = data.callback.call::<LuaValue>(values)?;
+        Ok(())
+    })?;
+
+    let Some(ret_type) = &data.ret else {
+        return Ok(0);
+    };
+    if ret.is_nil() {
+        return Ok(0);
+    }
+    Ok(register_value(Argument::from_type_value(ret_type, &ret)?)?)
+}
+
+/// 按类型宽度读取参数值
+unsafe fn read_raw(ty: &ArgumentType, ptr: *const c_void) -> u64 {
+    unsafe {
+        match ty {
+            ArgumentType::Void => 0,
+            ArgumentType::UInt8 | ArgumentType::SInt8 => *(ptr as *const u8) as u64,
+            ArgumentType::UInt16 | ArgumentType::SInt16 => *(ptr as *const u16) as u64,
+            ArgumentType::UInt32 | ArgumentType::SInt32 | ArgumentType::Float => {
+                *(ptr as *const u32) as u64
+            }
+            _ => *(ptr as *const u64),
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! 按值传递的结构体
+//!
+//! `sdk.struct{ {"float", 4} }` 描述结构体的成员布局，成员按 C 规则自然对齐。
+//! 结构体值与 Lua 表互相转换，表中按顺序列出展开后的所有成员。
+
+use std::{ffi::c_void, sync::Arc};
+
+use mlua::prelude::*;
+
+use super::{
+    Argument, ArgumentType, CALL_NATIVE_FUNCTION_EX, FFIArg, FFIArgType, FREE_STRUCT_TYPE,
+    NEW_STRUCT_TYPE, decode_return_value, ffi_abi, parse_type_name, register_value,
+};
+use crate::{error::Error, memory::MemoryUtils, static_ref};
+
+/// 结构体布局
+#[derive(Debug)]
+pub struct StructLayout {
+    /// 展开后的成员类型与偏移
+    elements: Vec<(ArgumentType, usize)>,
+    size: usize,
+    align: usize,
+    /// 扩展中的类型句柄，扩展不支持时为 0
+    handle: usize,
+}
+
+impl StructLayout {
+    /// 从 `{ {"float", 4}, {"pointer"} }` 创建
+    pub fn from_fields(fields: &LuaTable) -> LuaResult<Self> {
+        let mut elements = Vec::new();
+        let mut size = 0;
+        let mut align = 1;
+        for field in fields.sequence_values::<LuaTable>() {
+            let field = field?;
+            let type_name = field.get::<String>(1)?;
+            let count = field.get::<Option<usize>>(2)?.unwrap_or(1);
+            let ty = parse_type_name(&type_name)?;
+            let Some(element_size) = element_size(&ty) else {
+                return Err(
+                    Error::InvalidValue("numeric or pointer field type", type_name).into_lua_err(),
+                );
+            };
+
+            align = align.max(element_size);
+            for _ in 0..count {
+                size = size.next_multiple_of(element_size);
+                elements.push((ty.clone(), size));
+                size += element_size;
+            }
+        }
+        if elements.is_empty() {
+            return Err(
+                Error::InvalidValue("at least one struct field", "empty".to_string())
+                    .into_lua_err(),
+            );
+        }
+
+        let handle = match unsafe { *static_ref!(NEW_STRUCT_TYPE) } {
+            Some(new_struct_type) => {
+                let field_types = elements
+                    .iter()
+                    .map(|(ty, _)| ty.as_ffi_type() as i32)
+                    .collect::<Vec<_>>();
+                unsafe { new_struct_type(field_types.as_ptr(), field_types.len()) as usize }
+            }
+            None => 0,
+        };
+
+        Ok(Self {
+            elements,
+            size: size.next_multiple_of(align),
+            align,
+            handle,
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// 将 Lua 表转换为结构体数据
+    pub fn encode(&self, value: &LuaValue) -> LuaResult<Vec<u8>> {
+        let table = value
+            .as_table()
+            .ok_or_else(|| Error::InvalidValue("table", format!("{:?}", value)))
+            .into_lua_err()?;
+
+        let mut bytes = vec![0u8; self.size];
+        for (index, (ty, offset)) in self.elements.iter().enumerate() {
+            let value = table.get::<LuaValue>(index + 1)?;
+            let raw = register_value(Argument::from_type_value(ty, &value)?)?;
+            let len = element_size(ty).unwrap_or(8);
+            bytes[*offset..*offset + len].copy_from_slice(&raw.to_le_bytes()[..len]);
+        }
+        Ok(bytes)
+    }
+
+    /// 将结构体数据转换为 Lua 表
+    pub fn decode(&self, lua: &Lua, bytes: &[u8]) -> LuaResult<LuaTable> {
+        let table = lua.create_table_with_capacity(self.elements.len(), 0)?;
+        for (ty, offset) in self.elements.iter() {
+            let len = element_size(ty).unwrap_or(8);
+            let mut raw = [0u8; 8];
+            raw[..len].copy_from_slice(&bytes[*offset..*offset + len]);
+            table.push(ty.decode_register(lua, u64::from_le_bytes(raw))?)?;
+        }
+        Ok(table)
+    }
+
+    /// 寄存器或栈上的原始值
+    ///
+    /// Windows x64 中大小为 1、2、4、8 字节的结构体直接传递，其他按指针传递。
+    pub fn bytes_from_register(&self, raw: u64) -> Vec<u8> {
+        if matches!(self.size, 1 | 2 | 4 | 8) {
+            raw.to_le_bytes()[..self.size].to_vec()
+        } else if MemoryUtils::check_permission_read(raw as usize).is_ok() {
+            unsafe { std::slice::from_raw_parts(raw as *const u8, self.size).to_vec() }
+        } else {
+            vec![0u8; self.size]
+        }
+    }
+}
+
+impl Drop for StructLayout {
+    fn drop(&mut self) {
+        if self.handle != 0
+            && let Some(free_struct_type) = unsafe { *static_ref!(FREE_STRUCT_TYPE) }
+        {
+            unsafe { free_struct_type(self.handle as *mut c_void) };
+        }
+    }
+}
+
+/// `sdk.struct` 返回的结构体类型
+#[derive(Debug, Clone)]
+pub struct StructType(pub Arc<StructLayout>);
+
+impl LuaUserData for StructType {
+    fn add_fields<F: LuaUserDataFields<Self>>(fields: &mut F) {
+        fields.add_meta_field(LuaMetaMethod::Type, "StructType");
+        fields.add_field("_type", "StructType");
+
+        fields.add_field_method_get("size", |_, this| Ok(this.0.size));
+        fields.add_field_method_get("align", |_, this| Ok(this.0.align));
+    }
+}
+
+impl FromLua for StructType {
+    fn from_lua(value: LuaValue, _lua: &Lua) -> LuaResult<Self> {
+        match value {
+            LuaValue::UserData(ud) => Ok(ud.borrow::<StructType>()?.clone()),
+            other => Err(Error::InvalidValue("StructType", format!("{:?}", other)).into_lua_err()),
+        }
+    }
+}
+
+/// 成员大小，不支持的类型返回 `None`
+fn element_size(ty: &ArgumentType) -> Option<usize> {
+    let size = match ty {
+        ArgumentType::UInt8 | ArgumentType::SInt8 => 1,
+        ArgumentType::UInt16 | ArgumentType::SInt16 => 2,
+        ArgumentType::UInt32 | ArgumentType::SInt32 | ArgumentType::Float => 4,
+        ArgumentType::UInt64
+        | ArgumentType::SInt64
+        | ArgumentType::Double
+        | ArgumentType::Pointer => 8,
+        ArgumentType::Void | ArgumentType::String | ArgumentType::Struct(_) => return None,
+    };
+    Some(size)
+}
+
+/// 调用参数或返回值包含结构体的函数
+pub fn call_with_structs(
+    lua: &Lua,
+    fun: u64,
+    args: Vec<Argument>,
+    ret_type: Option<ArgumentType>,
+    use_system_abi: bool,
+) -> LuaResult<LuaValue> {
+    let Some(call_c_function_ex) = (unsafe { *static_ref!(CALL_NATIVE_FUNCTION_EX) }) else {
+        return Err(LuaError::external(
+            "Struct arguments require a newer luaf_libffi extension",
+        ));
+    };
+    MemoryUtils::check_permission_execute(fun as usize).map_err(|e| e.into_lua_err())?;
+
+    // 类型句柄：标量为 FFIArgType，结构体为扩展中的类型句柄
+    let type_handle = |ty: &ArgumentType| match ty {
+        ArgumentType::Struct(layout) if layout.handle == 0 => Err(LuaError::external(
+            "Struct type is not supported by the loaded luaf_libffi extension",
+        )),
+        ArgumentType::Struct(layout) => Ok(layout.handle),
+        other => Ok(other.as_ffi_type() as usize),
+    };
+    let arg_types = args
+        .iter()
+        .map(|arg| type_handle(&arg.argument_type()))
+        .collect::<LuaResult<Vec<_>>>()?;
+    let ret_type = ret_type.filter(|ty| !matches!(ty, ArgumentType::Void));
+    let ret_handle = match &ret_type {
+        Some(ty) => type_handle(ty)?,
+        None => FFIArgType::Void as usize,
+    };
+
+    // 标量参数的值保存在槽中，结构体参数直接指向数据
+    let mut ffi_args = args
+        .into_iter()
+        .map(FFIArg::from_argument)
+        .collect::<Vec<_>>();
+    let mut slots = ffi_args
+        .iter_mut()
+        .map(|arg| arg.value.as_ptr())
+        .collect::<Vec<_>>();
+    let mut arg_values = ffi_args
+        .iter()
+        .zip(slots.iter_mut())
+        .map(|(arg, slot)| {
+            if arg.ty == FFIArgType::Struct {
+                *slot
+            } else {
+                slot as *mut *mut c_void as *mut c_void
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let ret_size = match &ret_type {
+        Some(ArgumentType::Struct(layout)) => layout.size,
+        _ => 0,
+    };
+    let mut ret_val = vec![0u8; ret_size.max(8)];
+    let code = unsafe {
+        call_c_function_ex(
+            fun as *mut _,
+            arg_types.as_ptr(),
+            arg_values.as_mut_ptr(),
+            arg_values.len(),
+            ret_handle,
+            ret_val.as_mut_ptr() as *mut c_void,
+            ffi_abi(use_system_abi),
+        )
+    };
+    if code != 0 {
+        return Err(LuaError::external(format!(
+            "Failed to call native function 0x{:x}: code {}",
+            fun, code
+        )));
+    }
+
+    match ret_type {
+        None => Ok(LuaNil),
+        Some(ArgumentType::Struct(layout)) => Ok(LuaValue::Table(layout.decode(lua, &ret_val)?)),
+        Some(ty) => {
+            let raw = u64::from_le_bytes(ret_val[..8].try_into().unwrap());
+            Ok(decode_return_value(ty.as_ffi_type(), raw as *mut c_void))
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, ffi::c_void, sync::LazyLock};
+use std::{collections::HashMap, ffi::c_void, sync::LazyLock, time::Instant};
 
 use frida_gum::{
     Gum, NativePointer,
@@ -10,20 +10,33 @@ use mlua::prelude::*;
 use parking_lot::Mutex;
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
+use vtable::VtableSlot;
 
-use super::luaptr::LuaPtr;
+use super::{
+    class_def::{ClassRegistry, GameClassObject},
+    ffi_call::coerce_register_value,
+    luaptr::LuaPtr,
+};
 use crate::{
     error::{Error, Result},
-    luavm::library::LuaModule,
+    game::{dti::DtiRegistry, object_tracker::ObjectTracker},
+    luavm::{library::LuaModule, safety::SafetyPolicy},
     memory::MemoryUtils,
+    profiler::Profiler,
 };
 
 mod inline;
 mod mid;
+mod replace;
+mod signature;
+mod vtable;
 
 static GUM: LazyLock<Gum> = LazyLock::new(Gum::obtain);
 static INTERCEPTOR: LazyLock<Mutex<InterceptorSend>> =
     LazyLock::new(|| Mutex::new(InterceptorSend(Interceptor::obtain(&GUM))));
+/// 已监听的析构函数，常驻不卸载
+static DESTRUCTOR_LISTENERS: LazyLock<Mutex<HashMap<usize, ListenerGuard>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
 
 pub struct FridaModule;
 
@@ -34,41 +47,55 @@ impl LuaModule for FridaModule {
         interceptor_table.set(
             "attach",
             lua.create_function(|lua, (ptr, params): (LuaPtr, LuaTable)| {
-                // 安全检查
-                MemoryUtils::check_page_commit(ptr.to_usize()).map_err(|e| e.into_lua_err())?;
-
-                let interceptor = InlineInterceptor::new_with_params(lua, ptr.to_usize(), &params)?;
-                let handle = interceptor.handle();
-                InterceptorDispatcher::instance()
-                    .lock()
-                    .add_inline(interceptor)
-                    .map_err(LuaError::external)?;
-
-                // 记录句柄，以便后续移除
-                let handle_table = lua.globals().get::<LuaTable>("_interceptor_handles")?;
-                handle_table.push(handle)?;
-
-                Ok(handle)
+                attach_inline(lua, ptr.to_usize(), &params)
             })?,
         )?;
         interceptor_table.set(
             "detach",
             lua.create_function(|_lua, handle: InterceptorHandle| {
+                if let InterceptorHandle::Replace(id) = handle {
+                    return Ok(replace::revert(id));
+                }
                 let ok = InterceptorDispatcher::instance().lock().remove_hook(handle);
                 Ok(ok)
             })?,
         )?;
+        // 替换函数，回调接收 (original, ...) 并返回函数返回值
+        interceptor_table.set(
+            "replace",
+            lua.create_function(
+                |lua, (ptr, signature, callback): (LuaPtr, LuaTable, LuaFunction)| {
+                    SafetyPolicy::check_lua(lua, "Interceptor.replace")?;
+                    let signature = replace::ReplaceSignature::from_table(&signature)?;
+                    let handle = replace::replace(lua, ptr.to_usize(), signature, callback)?;
+
+                    // 记录句柄，以便后续移除
+                    let handle_table = lua.globals().get::<LuaTable>("_interceptor_handles")?;
+                    handle_table.push(handle)?;
+
+                    Ok(handle)
+                },
+            )?,
+        )?;
+        // 替换对象的虚表项，只 Hook 使用该虚表的对象
+        interceptor_table.set(
+            "attach_vtable",
+            lua.create_function(|lua, (object, index, params): (LuaPtr, usize, LuaTable)| {
+                attach_vtable(lua, object.to_usize(), index, &params)
+            })?,
+        )?;
         interceptor_table.set(
             "attach_instruction",
             lua.create_function(|lua, (ptr, params): (LuaPtr, LuaTable)| {
+                SafetyPolicy::check_lua(lua, "Interceptor.attach_instruction")?;
                 // 安全检查
                 MemoryUtils::check_page_commit(ptr.to_usize()).map_err(|e| e.into_lua_err())?;
 
+                let persistent_key = persistent_key(lua, ptr.to_usize(), &params)?;
                 let interceptor = MidInterceptor::new_with_params(lua, ptr.to_usize(), &params)?;
-                let handle = interceptor.handle();
-                InterceptorDispatcher::instance()
+                let handle = InterceptorDispatcher::instance()
                     .lock()
-                    .add_mid(interceptor)
+                    .add(LuaInterceptor::Mid(interceptor), persistent_key)
                     .map_err(LuaError::external)?;
 
                 // 记录句柄，以便后续移除
@@ -81,6 +108,19 @@ impl LuaModule for FridaModule {
 
         registry.set("Interceptor", interceptor_table)?;
 
+        // Hook
+        let hook_table = lua.create_table()?;
+        // Hook DTI 类的虚函数，回调参数为 (this, args)
+        hook_table.set(
+            "on_method",
+            lua.create_function(
+                |lua, (class_name, method, callback): (String, LuaValue, LuaFunction)| {
+                    hook_method(lua, class_name, method, callback)
+                },
+            )?,
+        )?;
+        registry.set("Hook", hook_table)?;
+
         lua.globals()
             .set("_interceptor_handles", lua.create_table()?)?;
         Ok(())
@@ -94,11 +134,183 @@ impl FridaModule {
         let mut dispatcher = InterceptorDispatcher::instance().lock();
         for handle in handles.sequence_values() {
             let handle: InterceptorHandle = handle?;
-            dispatcher.remove_hook(handle);
+            if let InterceptorHandle::Replace(id) = handle {
+                replace::revert(id);
+                continue;
+            }
+            // 持久化 Hook 仅解除绑定，等待重载后的虚拟机重新绑定
+            if !dispatcher.orphan_hook(handle) {
+                dispatcher.remove_hook(handle);
+            }
         }
 
         Ok(())
     }
+
+    /// 列出虚拟机设置的 Hook
+    pub fn list_hooks(lua: &Lua) -> Result<Vec<HookInfo>> {
+        let handles = lua.globals().get::<LuaTable>("_interceptor_handles")?;
+
+        let dispatcher = InterceptorDispatcher::instance().lock();
+        let mut hooks = Vec::new();
+        for handle in handles.sequence_values() {
+            let handle: InterceptorHandle = handle?;
+            if let InterceptorHandle::Replace(id) = handle {
+                if let Some(address) = replace::target(id) {
+                    hooks.push(HookInfo {
+                        kind: "replace",
+                        address,
+                        persistent_key: None,
+                    });
+                }
+                continue;
+            }
+            let Some(interceptor) = dispatcher.interceptors.get(&handle) else {
+                continue;
+            };
+            hooks.push(HookInfo {
+                kind: match interceptor {
+                    _ if dispatcher.vtable_handles.contains_key(&handle) => "vtable",
+                    LuaInterceptor::Inline(_) => "inline",
+                    LuaInterceptor::Mid(_) => "mid",
+                },
+                address: interceptor.hook_ptr(),
+                persistent_key: dispatcher.persistent.get(&handle).cloned(),
+            });
+        }
+
+        Ok(hooks)
+    }
+
+    /// 移除重载后未被重新绑定的持久化 Hook
+    pub fn release_orphaned_hooks() {
+        InterceptorDispatcher::instance().lock().release_orphans();
+    }
+}
+
+/// Hook 信息，用于状态导出
+#[derive(Debug, Clone, Serialize)]
+pub struct HookInfo {
+    pub kind: &'static str,
+    pub address: usize,
+    pub persistent_key: Option<String>,
+}
+
+/// Interceptor.attach 实现
+fn attach_inline(lua: &Lua, ptr: usize, params: &LuaTable) -> LuaResult<InterceptorHandle> {
+    SafetyPolicy::check_lua(lua, "Interceptor.attach")?;
+    // 安全检查
+    MemoryUtils::check_page_commit(ptr).map_err(|e| e.into_lua_err())?;
+
+    let persistent_key = persistent_key(lua, ptr, params)?;
+    let interceptor = InlineInterceptor::new_with_params(lua, ptr, params)?;
+    let handle = InterceptorDispatcher::instance()
+        .lock()
+        .add(LuaInterceptor::Inline(interceptor), persistent_key)
+        .map_err(LuaError::external)?;
+
+    // 记录句柄，以便后续移除
+    let handle_table = lua.globals().get::<LuaTable>("_interceptor_handles")?;
+    handle_table.push(handle)?;
+
+    Ok(handle)
+}
+
+/// Interceptor.attach_vtable 实现
+fn attach_vtable(
+    lua: &Lua,
+    object: usize,
+    index: usize,
+    params: &LuaTable,
+) -> LuaResult<InterceptorHandle> {
+    SafetyPolicy::check_lua(lua, "Interceptor.attach_vtable")?;
+    let slot = VtableSlot::slot_address(object, index).map_err(|e| e.into_lua_err())?;
+
+    let mut dispatcher = InterceptorDispatcher::instance().lock();
+    let stub = dispatcher.install_vtable_slot(slot).into_lua_err()?;
+    let interceptor = InlineInterceptor::new_with_params(lua, stub, params)?;
+    let result = dispatcher.add(LuaInterceptor::Inline(interceptor), None);
+    let handle = match result {
+        Ok(handle) => handle,
+        Err(e) => {
+            dispatcher.release_vtable_slot(slot);
+            return Err(e.into_lua_err());
+        }
+    };
+    dispatcher.vtable_handles.insert(handle, slot);
+    drop(dispatcher);
+
+    // 记录句柄，以便后续移除
+    let handle_table = lua.globals().get::<LuaTable>("_interceptor_handles")?;
+    handle_table.push(handle)?;
+
+    Ok(handle)
+}
+
+/// Hook.on_method 实现
+///
+/// `method` 为虚函数索引或 ClassDef 中定义的方法名。
+/// 若该类存在 ClassDef 定义，this 会包装为对应的类对象，否则为 LuaPtr。
+fn hook_method(
+    lua: &Lua,
+    class_name: String,
+    method: LuaValue,
+    callback: LuaFunction,
+) -> LuaResult<InterceptorHandle> {
+    let class_def = ClassRegistry::instance().get(&class_name);
+
+    let vtable_index = match &method {
+        LuaValue::Integer(index) if *index >= 0 => *index as usize,
+        LuaValue::String(name) => {
+            let name = name.to_str()?;
+            class_def
+                .as_ref()
+                .and_then(|def| def.method(&name))
+                .and_then(|method| method.vtable_index)
+                .ok_or_else(|| {
+                    Error::ClassMemberNotFound(class_name.clone(), name.to_string()).into_lua_err()
+                })?
+        }
+        other => {
+            return Err(
+                Error::InvalidValue("vtable index or method name", format!("{:?}", other))
+                    .into_lua_err(),
+            );
+        }
+    };
+
+    let target = DtiRegistry::instance()
+        .virtual_function(&class_name, vtable_index)
+        .map_err(|e| e.into_lua_err())?;
+
+    let on_enter = lua.create_function(move |lua, args: LuaAnyUserData| {
+        let this_ptr = args.get::<LuaPtr>(0)?;
+        let this = match &class_def {
+            Some(def) => GameClassObject::new(def.clone(), this_ptr.to_usize()).into_lua(lua)?,
+            None => this_ptr.into_lua(lua)?,
+        };
+        callback.call::<()>((this, args))
+    })?;
+
+    let params = lua.create_table()?;
+    params.set("on_enter", on_enter)?;
+    attach_inline(lua, target, &params)
+}
+
+/// 解析持久化参数，返回 Hook 的持久化标识
+///
+/// 标识由脚本名称和 `key` 参数组成，未指定 `key` 时使用 Hook 地址。
+fn persistent_key(lua: &Lua, hook_ptr: usize, params: &LuaTable) -> LuaResult<Option<String>> {
+    let persistent = params.get::<Option<bool>>("persistent")?.unwrap_or(false);
+    if !persistent {
+        return Ok(None);
+    }
+
+    let script_name = lua.globals().get::<String>("_name")?;
+    let key = params
+        .get::<Option<String>>("key")?
+        .unwrap_or_else(|| format!("{:x}", hook_ptr));
+    Ok(Some(format!("{}:{}", script_name, key)))
 }
 
 /// Interceptor 句柄，用于获取原始信息。
@@ -109,6 +321,7 @@ impl FridaModule {
 enum InterceptorHandle {
     Inline(u32),
     Mid(u32),
+    Replace(u32),
 }
 
 impl IntoLua for InterceptorHandle {
@@ -132,10 +345,15 @@ impl InterceptorHandle {
         InterceptorHandle::Mid(rand::rng().next_u32())
     }
 
+    fn new_replace() -> Self {
+        InterceptorHandle::Replace(rand::rng().next_u32())
+    }
+
     fn id(&self) -> u32 {
         match self {
             InterceptorHandle::Inline(id) => *id,
             InterceptorHandle::Mid(id) => *id,
+            InterceptorHandle::Replace(id) => *id,
         }
     }
 }
@@ -186,12 +404,33 @@ enum LuaInterceptor {
 }
 
 impl LuaInterceptor {
+    fn handle(&self) -> InterceptorHandle {
+        match self {
+            LuaInterceptor::Inline(interceptor) => interceptor.handle(),
+            LuaInterceptor::Mid(interceptor) => interceptor.handle(),
+        }
+    }
+
     fn hook_ptr(&self) -> usize {
         match self {
             LuaInterceptor::Inline(interceptor) => interceptor.hook_ptr(),
             LuaInterceptor::Mid(interceptor) => interceptor.hook_ptr(),
         }
     }
+
+    fn set_handle(&mut self, handle: InterceptorHandle) {
+        match self {
+            LuaInterceptor::Inline(interceptor) => interceptor.set_handle(handle),
+            LuaInterceptor::Mid(interceptor) => interceptor.set_handle(handle),
+        }
+    }
+
+    fn unbind(&mut self) {
+        match self {
+            LuaInterceptor::Inline(interceptor) => interceptor.unbind(),
+            LuaInterceptor::Mid(interceptor) => interceptor.unbind(),
+        }
+    }
 }
 
 /// 管理全局 Interceptor 上下文，分发 Hook 事件
@@ -203,6 +442,14 @@ struct InterceptorDispatcher {
     interceptors: HashMap<InterceptorHandle, LuaInterceptor>,
     /// hook_ptr -> []handle
     hook_handles: HashMap<usize, Vec<InterceptorHandle>>,
+    /// 持久化 Hook：handle -> 持久化标识
+    persistent: HashMap<InterceptorHandle, String>,
+    /// 已解除绑定、等待重新绑定的持久化 Hook：持久化标识 -> handle
+    orphans: HashMap<String, InterceptorHandle>,
+    /// 已替换的虚表项：虚表项地址 -> (虚表项, 引用数)
+    vtable_slots: HashMap<usize, (VtableSlot, usize)>,
+    /// 虚表 Hook：handle -> 虚表项地址
+    vtable_handles: HashMap<InterceptorHandle, usize>,
 }
 
 impl InterceptorDispatcher {
@@ -212,6 +459,45 @@ impl InterceptorDispatcher {
         &INSTANCE
     }
 
+    /// 添加 Interceptor，持久化 Hook 优先复用重载前的同名 Hook
+    fn add(
+        &mut self,
+        mut interceptor: LuaInterceptor,
+        persistent_key: Option<String>,
+    ) -> Result<InterceptorHandle> {
+        let Some(key) = persistent_key else {
+            return self.add_interceptor(interceptor);
+        };
+
+        if let Some(old_handle) = self.orphans.remove(&key) {
+            let reusable = self.interceptors.get(&old_handle).is_some_and(|old| {
+                old.hook_ptr() == interceptor.hook_ptr()
+                    && std::mem::discriminant(&old.handle())
+                        == std::mem::discriminant(&interceptor.handle())
+            });
+            if reusable {
+                // 重新绑定回调，Hook 本身保持不变
+                interceptor.set_handle(old_handle);
+                self.interceptors.insert(old_handle, interceptor);
+                self.persistent.insert(old_handle, key);
+                return Ok(old_handle);
+            }
+            // 地址或类型已变化，移除旧 Hook
+            self.remove_hook(old_handle);
+        }
+
+        let handle = self.add_interceptor(interceptor)?;
+        self.persistent.insert(handle, key);
+        Ok(handle)
+    }
+
+    fn add_interceptor(&mut self, interceptor: LuaInterceptor) -> Result<InterceptorHandle> {
+        match interceptor {
+            LuaInterceptor::Inline(interceptor) => self.add_inline(interceptor),
+            LuaInterceptor::Mid(interceptor) => self.add_mid(interceptor),
+        }
+    }
+
     fn add_inline(&mut self, interceptor: InlineInterceptor) -> Result<InterceptorHandle> {
         let hook_ptr = interceptor.hook_ptr();
         let hook_handle = interceptor.handle();
@@ -280,10 +566,61 @@ impl InterceptorDispatcher {
         Ok(hook_handle)
     }
 
+    /// 解除持久化 Hook 与虚拟机的绑定，非持久化 Hook 返回 false
+    fn orphan_hook(&mut self, hook_handle: InterceptorHandle) -> bool {
+        let Some(key) = self.persistent.get(&hook_handle).cloned() else {
+            return false;
+        };
+        let Some(interceptor) = self.interceptors.get_mut(&hook_handle) else {
+            return false;
+        };
+
+        interceptor.unbind();
+        self.orphans.insert(key, hook_handle);
+        true
+    }
+
+    fn release_orphans(&mut self) {
+        let orphans = std::mem::take(&mut self.orphans);
+        for (key, handle) in orphans {
+            log::debug!("Releasing persistent hook '{}' which is not rebound", key);
+            self.remove_hook(handle);
+        }
+    }
+
+    /// 替换虚表项并增加引用，返回跳板地址
+    fn install_vtable_slot(&mut self, slot: usize) -> Result<usize> {
+        if let Some((vtable_slot, count)) = self.vtable_slots.get_mut(&slot) {
+            *count += 1;
+            return Ok(vtable_slot.stub);
+        }
+        let vtable_slot = VtableSlot::install(slot)?;
+        let stub = vtable_slot.stub;
+        self.vtable_slots.insert(slot, (vtable_slot, 1));
+        Ok(stub)
+    }
+
+    /// 减少虚表项引用，没有 Hook 时还原
+    fn release_vtable_slot(&mut self, slot: usize) {
+        let Some((_, count)) = self.vtable_slots.get_mut(&slot) else {
+            return;
+        };
+        *count -= 1;
+        if *count > 0 {
+            return;
+        }
+        if let Some((vtable_slot, _)) = self.vtable_slots.remove(&slot)
+            && let Err(e) = vtable_slot.restore()
+        {
+            log::error!("Failed to restore vtable slot 0x{:x}: {}", slot, e);
+        }
+    }
+
     fn remove_hook(&mut self, hook_handle: InterceptorHandle) -> bool {
         let Some(interceptor) = self.interceptors.remove(&hook_handle) else {
             return false;
         };
+        self.persistent.remove(&hook_handle);
 
         let hook_ptr = interceptor.hook_ptr();
 
@@ -300,6 +637,10 @@ impl InterceptorDispatcher {
             // 释放 hook
             self.listeners.remove(&hook_ptr);
         }
+        // 虚表 Hook 在 listener 释放后还原虚表项
+        if let Some(slot) = self.vtable_handles.remove(&hook_handle) {
+            self.release_vtable_slot(slot);
+        }
 
         true
     }
@@ -318,7 +659,17 @@ impl InterceptorDispatcher {
             let LuaInterceptor::Inline(interceptor) = interceptor else {
                 continue;
             };
-            if let Err(e) = interceptor.invoke_callback(context) {
+            let profiler = Profiler::instance();
+            profiler.add_hook_dispatch();
+            let start = Instant::now();
+            let result = interceptor.invoke_callback(context);
+            match interceptor.luavm() {
+                Some(luavm) => {
+                    profiler.record_callback(luavm.name(), "hook", start.elapsed(), result.is_err())
+                }
+                None => profiler.add_lua_time(start.elapsed()),
+            }
+            if let Err(e) = result {
                 log::error!("invoke inline callback error ({:x}): {}", handle.id(), e);
             };
         }
@@ -338,7 +689,17 @@ impl InterceptorDispatcher {
             let LuaInterceptor::Mid(interceptor) = interceptor else {
                 continue;
             };
-            if let Err(e) = interceptor.invoke_callback(context) {
+            let profiler = Profiler::instance();
+            profiler.add_hook_dispatch();
+            let start = Instant::now();
+            let result = interceptor.invoke_callback(context);
+            match interceptor.luavm() {
+                Some(luavm) => {
+                    profiler.record_callback(luavm.name(), "hook", start.elapsed(), result.is_err())
+                }
+                None => profiler.add_lua_time(start.elapsed()),
+            }
+            if let Err(e) = result {
                 log::error!("invoke mid callback error ({:x}): {}", handle.id(), e);
             };
         }
@@ -365,6 +726,37 @@ impl InvocationListener for InlineListener {
     }
 }
 
+/// 对象析构时使弱引用失效
+struct DestructorListener;
+
+impl InvocationListener for DestructorListener {
+    fn on_enter(&mut self, context: frida_gum::interceptor::InvocationContext) {
+        ObjectTracker::instance().invalidate(context.arg(0));
+    }
+
+    fn on_leave(&mut self, _context: frida_gum::interceptor::InvocationContext) {}
+}
+
+/// 监听析构函数，被追踪的对象析构时失效
+pub fn watch_destructor(destructor: usize) -> Result<()> {
+    let mut listeners = DESTRUCTOR_LISTENERS.lock();
+    if listeners.contains_key(&destructor) {
+        return Ok(());
+    }
+    MemoryUtils::check_permission_execute(destructor)?;
+
+    let listener = INTERCEPTOR
+        .lock()
+        .attach(
+            NativePointer(destructor as *mut c_void),
+            &mut DestructorListener,
+        )
+        .map_err(|e| Error::Frida(e.to_string()))?;
+    listeners.insert(destructor, ListenerGuard::new(listener));
+
+    Ok(())
+}
+
 struct MidListener;
 
 impl ProbeListener for MidListener {
@@ -452,8 +844,7 @@ impl LuaUserData for CpuContextArgs<'_> {
             LuaMetaMethod::NewIndex,
             |lua, this, (key, value): (LuaValue, LuaValue)| {
                 let index_key: IndexKey = key.into();
-                let ptr = LuaPtr::from_lua(value, lua)?;
-                let ptr_val = ptr.to_u64();
+                let ptr_val = coerce_register_value(lua, value)?;
 
                 match index_key {
                     IndexKey::Str(key) => match key.as_str() {
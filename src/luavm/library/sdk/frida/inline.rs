@@ -6,9 +6,10 @@ use frida_gum::interceptor::{InvocationContext, PointCut};
 use mlua::prelude::*;
 
 use crate::error::{Error, Result};
-use crate::luavm::library::sdk::luaptr::LuaPtr;
-use crate::luavm::{LuaVMManager, WeakLuaVM};
+use crate::luavm::library::sdk::ffi_call::coerce_register_value;
+use crate::luavm::{LuaVMManager, SharedLuaVM, WeakLuaVM};
 
+use super::signature::FunctionSignature;
 use super::{IndexKey, InterceptorHandle};
 
 /// Interceptor.attach Lua 接口封装
@@ -18,6 +19,10 @@ pub struct InlineInterceptor {
     vm_ref: WeakLuaVM,
     on_enter: Option<LuaFunction>,
     on_leave: Option<LuaFunction>,
+    /// 仅在游戏主线程触发回调
+    game_thread_only: bool,
+    /// 函数签名，用于按名称访问参数
+    signature: Option<FunctionSignature>,
 }
 
 impl InlineInterceptor {
@@ -28,6 +33,8 @@ impl InlineInterceptor {
             vm_ref: weak,
             on_enter: None,
             on_leave: None,
+            game_thread_only: false,
+            signature: None,
         }
     }
 
@@ -45,6 +52,12 @@ impl InlineInterceptor {
         if let Ok(on_leave) = params.get::<LuaFunction>("on_leave") {
             interceptor.set_on_leave(on_leave);
         }
+        interceptor.game_thread_only = params
+            .get::<Option<bool>>("game_thread_only")?
+            .unwrap_or(false);
+        if let Some(signature) = params.get::<Option<String>>("signature")? {
+            interceptor.signature = Some(FunctionSignature::parse(&signature)?);
+        }
 
         Ok(interceptor)
     }
@@ -65,26 +78,48 @@ impl InlineInterceptor {
         self.on_leave = Some(func);
     }
 
-    pub fn invoke_callback(&self, context: &InvocationContext) -> Result<()> {
-        let Some(luavm) = self.vm_ref.upgrade() else {
-            return Err(Error::LuaVMNotFound);
-        };
+    pub(super) fn set_handle(&mut self, handle: InterceptorHandle) {
+        self.handle = handle;
+    }
 
+    /// 解除与虚拟机的绑定，保留 Hook 但不再调用回调
+    pub(super) fn unbind(&mut self) {
+        self.vm_ref = WeakLuaVM::new();
+        self.on_enter = None;
+        self.on_leave = None;
+    }
+
+    /// 注册 Hook 的虚拟机，已卸载时返回 None
+    pub fn luavm(&self) -> Option<SharedLuaVM> {
+        self.vm_ref.upgrade()
+    }
+
+    pub fn invoke_callback(&self, context: &InvocationContext) -> Result<()> {
         let lua_callback = match context.point_cut() {
             PointCut::Enter => &self.on_enter,
             PointCut::Leave => &self.on_leave,
         };
+        if lua_callback.is_none() {
+            return Ok(());
+        }
+        if self.game_thread_only && !crate::game::thread::is_game_thread() {
+            return Ok(());
+        }
+
+        let Some(luavm) = self.vm_ref.upgrade() else {
+            return Err(Error::LuaVMNotFound);
+        };
 
         if let Some(lua_callback) = lua_callback {
             let lua = luavm.lua();
             lua.scope(|scope| {
                 let args_ud = match context.point_cut() {
                     PointCut::Enter => {
-                        let args = InlineEnterArgs::new(context);
+                        let args = InlineEnterArgs::new(context, self.signature.as_ref());
                         scope.create_userdata(args)?
                     }
                     PointCut::Leave => {
-                        let args = InlineLeaveArgs::new(context);
+                        let args = InlineLeaveArgs::new(context, self.signature.as_ref());
                         scope.create_userdata(args)?
                     }
                 };
@@ -106,6 +141,7 @@ thread_local! {
 struct InlineEnterArgs<'a> {
     /// 原始上下文
     context: &'a InvocationContext<'a>,
+    signature: Option<&'a FunctionSignature>,
 }
 
 unsafe impl Send for InlineEnterArgs<'_> {}
@@ -113,7 +149,7 @@ unsafe impl Sync for InlineEnterArgs<'_> {}
 
 impl LuaUserData for InlineEnterArgs<'_> {
     fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
-        methods.add_meta_method(LuaMetaMethod::Index, |_lua, this, key: LuaValue| {
+        methods.add_meta_method(LuaMetaMethod::Index, |lua, this, key: LuaValue| {
             let index_key: IndexKey = key.into();
 
             match index_key {
@@ -122,6 +158,15 @@ impl LuaUserData for InlineEnterArgs<'_> {
                     Ok(LuaValue::Integer(this.context.arg(key) as i64))
                 }
                 IndexKey::Str(key) => {
+                    // 签名中的命名参数
+                    if let Some((index, ty)) = this.signature.and_then(|sig| sig.param(&key)) {
+                        let raw = this.context.arg(index) as u64;
+                        let xmm = FunctionSignature::xmm_register(index, ty).and_then(read_xmm);
+                        return this
+                            .signature
+                            .unwrap()
+                            .decode_param(lua, index, ty, raw, xmm);
+                    }
                     // 内部保留关键字key
                     match key.as_ref() {
                         "cpu_context" => {
@@ -154,11 +199,17 @@ impl LuaUserData for InlineEnterArgs<'_> {
                 match index_key {
                     IndexKey::Int(key) => {
                         // 设置参数值
-                        let ptr = LuaPtr::from_lua(value, lua)?;
-                        this.context.set_arg(key, ptr.to_usize());
+                        let value = coerce_register_value(lua, value)?;
+                        this.context.set_arg(key, value as usize);
                         Ok(())
                     }
                     IndexKey::Str(key) => {
+                        // 签名中的命名参数
+                        if let Some((index, _)) = this.signature.and_then(|sig| sig.param(&key)) {
+                            let value = coerce_register_value(lua, value)?;
+                            this.context.set_arg(index, value as usize);
+                            return Ok(());
+                        }
                         // 内部保留关键字key
                         match key.as_ref() {
                             "retval" => {
@@ -184,8 +235,8 @@ impl LuaUserData for InlineEnterArgs<'_> {
 }
 
 impl<'a> InlineEnterArgs<'a> {
-    fn new(context: &'a InvocationContext) -> Self {
-        Self { context }
+    fn new(context: &'a InvocationContext, signature: Option<&'a FunctionSignature>) -> Self {
+        Self { context, signature }
     }
 }
 
@@ -193,6 +244,7 @@ impl<'a> InlineEnterArgs<'a> {
 struct InlineLeaveArgs<'a> {
     /// 原始上下文
     context: &'a InvocationContext<'a>,
+    signature: Option<&'a FunctionSignature>,
 }
 
 unsafe impl Send for InlineLeaveArgs<'_> {}
@@ -200,7 +252,7 @@ unsafe impl Sync for InlineLeaveArgs<'_> {}
 
 impl LuaUserData for InlineLeaveArgs<'_> {
     fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
-        methods.add_meta_method(LuaMetaMethod::Index, |_lua, this, key: LuaValue| {
+        methods.add_meta_method(LuaMetaMethod::Index, |lua, this, key: LuaValue| {
             let index_key: IndexKey = key.into();
 
             match index_key {
@@ -213,7 +265,11 @@ impl LuaUserData for InlineLeaveArgs<'_> {
                     match key.as_ref() {
                         "retval" => {
                             // 获取返回值
-                            Ok(LuaValue::Integer(this.context.return_value() as i64))
+                            let raw = this.context.return_value() as u64;
+                            match this.signature {
+                                Some(signature) => signature.decode_ret(lua, raw, read_xmm(0)),
+                                None => Ok(LuaValue::Integer(raw as i64)),
+                            }
                         }
                         other => {
                             let value =
@@ -249,8 +305,8 @@ impl LuaUserData for InlineLeaveArgs<'_> {
                         // 内部保留关键字key
                         match key.as_ref() {
                             "retval" => {
-                                let ptr = LuaPtr::from_lua(value, lua)?;
-                                this.context.set_return_value(ptr.to_usize());
+                                let value = coerce_register_value(lua, value)?;
+                                this.context.set_return_value(value as usize);
                             }
                             _ => {
                                 // 设置用户局部变量处理
@@ -272,9 +328,30 @@ impl LuaUserData for InlineLeaveArgs<'_> {
 }
 
 impl<'a> InlineLeaveArgs<'a> {
-    fn new(context: &'a InvocationContext) -> Self {
-        Self { context }
+    fn new(context: &'a InvocationContext, signature: Option<&'a FunctionSignature>) -> Self {
+        Self { context, signature }
+    }
+}
+
+/// frida 在调用监听器前以 fxsave 保存扩展寄存器，
+/// 保存区位于 GumCpuContext 按 16 字节向下对齐后再向下 512 字节处
+const FXSAVE_SIZE: usize = 512;
+/// fxsave 保存区中 XMM0 的偏移，每个寄存器占 16 字节
+const FXSAVE_XMM0_OFFSET: usize = 0xA0;
+
+/// 读取当前调用进入 Hook 时 XMM 寄存器的低 64 位
+fn read_xmm(index: usize) -> Option<u64> {
+    let invocation = unsafe { frida_gum_sys::gum_interceptor_get_current_invocation() };
+    if invocation.is_null() {
+        return None;
+    }
+    let cpu_context = unsafe { (*invocation).cpu_context } as usize;
+    if cpu_context == 0 {
+        return None;
     }
+    let fxsave = (cpu_context & !0xF) - FXSAVE_SIZE;
+    let slot = fxsave + FXSAVE_XMM0_OFFSET + index * 16;
+    Some(unsafe { *(slot as *const u64) })
 }
 
 fn get_thread_local_var_key(value: &LuaValue) -> LuaResult<String> {
@@ -4,7 +4,7 @@ use frida_gum::interceptor::InvocationContext;
 use mlua::prelude::*;
 
 use crate::error::{Error, Result};
-use crate::luavm::{LuaVMManager, WeakLuaVM};
+use crate::luavm::{LuaVMManager, SharedLuaVM, WeakLuaVM};
 
 use super::{CpuContextArgs, InterceptorHandle};
 
@@ -14,6 +14,8 @@ pub struct MidInterceptor {
     hook_ptr: usize,
     vm_ref: WeakLuaVM,
     on_hit: Option<LuaFunction>,
+    /// 仅在游戏主线程触发回调
+    game_thread_only: bool,
 }
 
 impl MidInterceptor {
@@ -23,6 +25,7 @@ impl MidInterceptor {
             hook_ptr,
             vm_ref: weak,
             on_hit: None,
+            game_thread_only: false,
         }
     }
 
@@ -37,6 +40,9 @@ impl MidInterceptor {
         if let Ok(on_hit) = params.get::<LuaFunction>("on_hit") {
             interceptor.set_on_hit(on_hit);
         }
+        interceptor.game_thread_only = params
+            .get::<Option<bool>>("game_thread_only")?
+            .unwrap_or(false);
 
         Ok(interceptor)
     }
@@ -53,7 +59,29 @@ impl MidInterceptor {
         self.on_hit = Some(on_hit);
     }
 
+    pub(super) fn set_handle(&mut self, handle: InterceptorHandle) {
+        self.handle = handle;
+    }
+
+    /// 解除与虚拟机的绑定，保留 Hook 但不再调用回调
+    pub(super) fn unbind(&mut self) {
+        self.vm_ref = WeakLuaVM::new();
+        self.on_hit = None;
+    }
+
+    /// 注册 Hook 的虚拟机，已卸载时返回 None
+    pub fn luavm(&self) -> Option<SharedLuaVM> {
+        self.vm_ref.upgrade()
+    }
+
     pub fn invoke_callback(&self, context: &InvocationContext) -> Result<()> {
+        if self.on_hit.is_none() {
+            return Ok(());
+        }
+        if self.game_thread_only && !crate::game::thread::is_game_thread() {
+            return Ok(());
+        }
+
         let Some(luavm) = self.vm_ref.upgrade() else {
             return Err(Error::LuaVMNotFound);
         };
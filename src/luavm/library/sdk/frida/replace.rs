@@ -0,0 +1,315 @@
+//! 函数替换
+//!
+//! 通过 frida 的 replace 将函数替换为通用入口，入口保存参数寄存器后分发到 Lua 回调。
+//! 回调按签名接收解码后的参数，返回值作为函数返回值，可通过 `original(...)` 调用原函数。
+//! 调用原函数使用 libffi 扩展。
+
+use std::{
+    arch::naked_asm,
+    collections::HashMap,
+    ffi::c_void,
+    sync::{Arc, LazyLock},
+};
+
+use frida_gum::{NativePointer, interceptor::Interceptor};
+use mlua::prelude::*;
+use parking_lot::Mutex;
+
+use super::{INTERCEPTOR, InterceptorHandle};
+use crate::{
+    error::{Error, Result},
+    luavm::{
+        LuaVMManager, WeakLuaVM,
+        library::sdk::ffi_call::{self, Argument, ArgumentType},
+    },
+    memory::MemoryUtils,
+};
+
+/// 通过寄存器传递的参数个数（Windows x64）
+const REGISTER_ARG_COUNT: usize = 4;
+
+/// 替换函数签名，类型名与 call_native_function 相同
+#[derive(Debug, Clone)]
+pub struct ReplaceSignature {
+    ret_name: String,
+    ret: ArgumentType,
+    arg_names: Vec<String>,
+    args: Vec<ArgumentType>,
+}
+
+impl ReplaceSignature {
+    /// 解析 `{ ret = "int32", args = { "pointer", "float" } }`
+    pub fn from_table(table: &LuaTable) -> LuaResult<Self> {
+        let ret_name = table
+            .get::<Option<String>>("ret")?
+            .unwrap_or_else(|| "void".to_string());
+        let ret = ffi_call::parse_type_name(&ret_name)?;
+        let arg_names = table
+            .get::<Option<Vec<String>>>("args")?
+            .unwrap_or_default();
+        let args = arg_names
+            .iter()
+            .map(|name| ffi_call::parse_type_name(name))
+            .collect::<LuaResult<Vec<_>>>()?;
+
+        Ok(Self {
+            ret_name,
+            ret,
+            arg_names,
+            args,
+        })
+    }
+
+    /// 将 Lua 返回值转换为寄存器值
+    fn encode_ret(&self, value: &LuaValue) -> LuaResult<u64> {
+        if matches!(self.ret, ArgumentType::Void) || value.is_nil() {
+            return Ok(0);
+        }
+        ffi_call::register_value(Argument::from_type_name_value(&self.ret_name, value)?)
+    }
+}
+
+struct ReplaceHook {
+    target: usize,
+    /// 原函数的跳板
+    original: usize,
+    signature: ReplaceSignature,
+    vm_ref: WeakLuaVM,
+    callback: LuaFunction,
+    /// 传给回调的 `original` 函数
+    original_fn: LuaFunction,
+}
+
+#[derive(Default)]
+struct ReplaceManager {
+    /// id -> 替换
+    hooks: HashMap<u32, Arc<ReplaceHook>>,
+    /// 目标函数 -> id
+    targets: HashMap<usize, u32>,
+}
+
+impl ReplaceManager {
+    fn instance() -> &'static Mutex<Self> {
+        static INSTANCE: LazyLock<Mutex<ReplaceManager>> =
+            LazyLock::new(|| Mutex::new(ReplaceManager::default()));
+        &INSTANCE
+    }
+}
+
+/// 替换函数，同一函数只能替换一次
+pub fn replace(
+    lua: &Lua,
+    target: usize,
+    signature: ReplaceSignature,
+    callback: LuaFunction,
+) -> LuaResult<InterceptorHandle> {
+    if !ffi_call::is_ffi_available() {
+        return Err(Error::FFIUnavailable.into_lua_err());
+    }
+    // 安全检查
+    MemoryUtils::check_page_commit(target).map_err(|e| e.into_lua_err())?;
+    let Some(luavm) = LuaVMManager::instance().get_vm_by_lua(lua) else {
+        return Err(LuaError::external("Internal: invalid lua vm"));
+    };
+
+    let mut manager = ReplaceManager::instance().lock();
+    if manager.targets.contains_key(&target) {
+        return Err(
+            Error::Frida(format!("function 0x{:x} is already replaced", target)).into_lua_err(),
+        );
+    }
+
+    let handle = InterceptorHandle::new_replace();
+    let id = handle.id();
+    let original = INTERCEPTOR
+        .lock()
+        .replace(
+            NativePointer(target as *mut c_void),
+            NativePointer(replacement_entry as *mut c_void),
+            NativePointer(id as usize as *mut c_void),
+        )
+        .map_err(|e| Error::Frida(e.to_string()).into_lua_err())?
+        .0 as usize;
+
+    let original_fn = {
+        let signature = signature.clone();
+        lua.create_function(move |_, args: LuaMultiValue| {
+            call_original(original, &signature, |index, name| {
+                let value = args.get(index).cloned().unwrap_or(LuaNil);
+                Argument::from_type_name_value(name, &value)
+            })
+        })?
+    };
+
+    manager.targets.insert(target, id);
+    manager.hooks.insert(
+        id,
+        Arc::new(ReplaceHook {
+            target,
+            original,
+            signature,
+            vm_ref: Arc::downgrade(&luavm),
+            callback,
+            original_fn,
+        }),
+    );
+
+    Ok(handle)
+}
+
+/// 还原被替换的函数
+pub fn revert(id: u32) -> bool {
+    let Some(hook) = ({
+        let mut manager = ReplaceManager::instance().lock();
+        let hook = manager.hooks.remove(&id);
+        if let Some(hook) = &hook {
+            manager.targets.remove(&hook.target);
+        }
+        hook
+    }) else {
+        return false;
+    };
+
+    INTERCEPTOR
+        .lock()
+        .revert(NativePointer(hook.target as *mut c_void));
+    true
+}
+
+/// 被替换函数的地址
+pub fn target(id: u32) -> Option<usize> {
+    ReplaceManager::instance()
+        .lock()
+        .hooks
+        .get(&id)
+        .map(|hook| hook.target)
+}
+
+fn call_original(
+    original: usize,
+    signature: &ReplaceSignature,
+    argument: impl Fn(usize, &str) -> LuaResult<Argument>,
+) -> LuaResult<LuaValue> {
+    let args = signature
+        .arg_names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| argument(index, name))
+        .collect::<LuaResult<Vec<_>>>()?;
+    ffi_call::call_native_function(original as u64, args, Some(signature.ret.clone()), true)
+}
+
+/// 入口保存的参数寄存器，返回时从中读取返回值
+#[repr(C)]
+struct RegisterArgs {
+    gpr: [u64; REGISTER_ARG_COUNT],
+    xmm: [u64; REGISTER_ARG_COUNT],
+}
+
+/// 通用替换入口
+///
+/// 保存 rcx、rdx、r8、r9 和 xmm0~xmm3，调用 [`replace_dispatch`]，
+/// 返回时将结果同时写入 rax 和 xmm0。
+#[unsafe(naked)]
+unsafe extern "system" fn replacement_entry() {
+    naked_asm!(
+        // 入口处 rsp 为 16n+8，分配后对齐：影子空间 0x20 + RegisterArgs 0x40 + 填充 8
+        "sub rsp, 0x68",
+        "mov [rsp + 0x20], rcx",
+        "mov [rsp + 0x28], rdx",
+        "mov [rsp + 0x30], r8",
+        "mov [rsp + 0x38], r9",
+        "movq [rsp + 0x40], xmm0",
+        "movq [rsp + 0x48], xmm1",
+        "movq [rsp + 0x50], xmm2",
+        "movq [rsp + 0x58], xmm3",
+        "lea rcx, [rsp + 0x20]",
+        // 第 5 个参数：返回地址 8 + 调用者影子空间 0x20
+        "lea rdx, [rsp + 0x90]",
+        "call {dispatch}",
+        "mov rax, [rsp + 0x20]",
+        "movq xmm0, [rsp + 0x40]",
+        "add rsp, 0x68",
+        "ret",
+        dispatch = sym replace_dispatch,
+    )
+}
+
+unsafe extern "system" fn replace_dispatch(regs: *mut RegisterArgs, stack_args: *const u64) {
+    let regs = unsafe { &mut *regs };
+    let id = Interceptor::current_invocation()
+        .replacement_data()
+        .map(|data| data.0 as usize as u32);
+    let hook = id.and_then(|id| ReplaceManager::instance().lock().hooks.get(&id).cloned());
+    let Some(hook) = hook else {
+        log::error!("Replaced function called without a registered replacement");
+        regs.gpr[0] = 0;
+        regs.xmm[0] = 0;
+        return;
+    };
+
+    // 按签名读取原始参数
+    let raw_args = hook
+        .signature
+        .args
+        .iter()
+        .enumerate()
+        .map(|(index, ty)| {
+            if index >= REGISTER_ARG_COUNT {
+                unsafe { *stack_args.add(index - REGISTER_ARG_COUNT) }
+            } else if ty.is_float() {
+                regs.xmm[index]
+            } else {
+                regs.gpr[index]
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let ret = match invoke_callback(&hook, &raw_args) {
+        Ok(ret) => ret,
+        Err(e) => {
+            let err_msg = format!(
+                "replacement of 0x{:x} error, calling original:\n{}",
+                hook.target, e
+            );
+            crate::error::set_last_error(err_msg.clone());
+            log::error!("{}", err_msg);
+            call_original(hook.original, &hook.signature, |index, _| {
+                Ok(hook.signature.args[index].argument_from_register(raw_args[index]))
+            })
+            .and_then(|value| hook.signature.encode_ret(&value))
+            .unwrap_or_else(|e| {
+                log::error!(
+                    "Failed to call original function 0x{:x}: {}",
+                    hook.target,
+                    e
+                );
+                0
+            })
+        }
+    };
+    regs.gpr[0] = ret;
+    regs.xmm[0] = ret;
+}
+
+fn invoke_callback(hook: &ReplaceHook, raw_args: &[u64]) -> Result<u64> {
+    let Some(luavm) = hook.vm_ref.upgrade() else {
+        return Err(Error::LuaVMNotFound);
+    };
+    let lua = luavm.lua();
+
+    let mut args = LuaMultiValue::with_capacity(raw_args.len() + 1);
+    args.push_back(LuaValue::Function(hook.original_fn.clone()));
+    for (ty, raw) in hook.signature.args.iter().zip(raw_args) {
+        args.push_back(ty.decode_register(lua, *raw)?);
+    }
+
+    let mut ret = LuaNil;
+    // 全局锁中执行回调
+    LuaVMManager::instance().run_with_lock(|_| {
+        ret = hook.callback.call::<LuaValue>(args)?;
+        Ok(())
+    })?;
+
+    Ok(hook.signature.encode_ret(&ret)?)
+}
@@ -0,0 +1,289 @@
+//! Hook 函数签名解析
+//!
+//! 解析形如 `void(cPlayer* this, float dmg, int part)` 的签名，
+//! 使 on_enter 中可以通过参数名访问参数，并按类型自动解码。
+
+use mlua::prelude::*;
+
+use crate::error::Error;
+use crate::luavm::library::sdk::luaptr::LuaPtr;
+
+/// 通过通用寄存器传递的参数个数（Windows x64）
+const REGISTER_ARG_COUNT: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    Void,
+    Bool,
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Int64,
+    UInt64,
+    Float,
+    Double,
+    Pointer,
+}
+
+impl ValueType {
+    fn from_type_name(type_name: &str) -> Option<Self> {
+        if type_name.ends_with('*') || type_name.ends_with('&') {
+            return Some(Self::Pointer);
+        }
+
+        let ty = match type_name {
+            "void" => Self::Void,
+            "bool" => Self::Bool,
+            "char" | "int8" | "int8_t" | "i8" => Self::Int8,
+            "unsigned char" | "uint8" | "uint8_t" | "u8" | "byte" => Self::UInt8,
+            "short" | "int16" | "int16_t" | "i16" => Self::Int16,
+            "unsigned short" | "uint16" | "uint16_t" | "u16" => Self::UInt16,
+            "int" | "long" | "int32" | "int32_t" | "i32" => Self::Int32,
+            "unsigned" | "unsigned int" | "unsigned long" | "uint" | "uint32" | "uint32_t"
+            | "u32" => Self::UInt32,
+            "long long" | "__int64" | "int64" | "int64_t" | "i64" | "intptr_t" => Self::Int64,
+            "unsigned long long" | "unsigned __int64" | "uint64" | "uint64_t" | "u64"
+            | "size_t" | "uintptr_t" => Self::UInt64,
+            "float" | "f32" => Self::Float,
+            "double" | "f64" => Self::Double,
+            "pointer" | "ptr" => Self::Pointer,
+            _ => return None,
+        };
+        Some(ty)
+    }
+
+    fn is_float(self) -> bool {
+        matches!(self, Self::Float | Self::Double)
+    }
+
+    /// 将寄存器或栈上的原始值解码为 Lua 值
+    pub fn decode(self, lua: &Lua, raw: u64) -> LuaResult<LuaValue> {
+        let value = match self {
+            Self::Void => LuaNil,
+            Self::Bool => LuaValue::Boolean(raw as u8 != 0),
+            Self::Int8 => LuaValue::Integer(raw as i8 as i64),
+            Self::UInt8 => LuaValue::Integer(raw as u8 as i64),
+            Self::Int16 => LuaValue::Integer(raw as i16 as i64),
+            Self::UInt16 => LuaValue::Integer(raw as u16 as i64),
+            Self::Int32 => LuaValue::Integer(raw as i32 as i64),
+            Self::UInt32 => LuaValue::Integer(raw as u32 as i64),
+            Self::Int64 | Self::UInt64 => LuaValue::Integer(raw as i64),
+            Self::Float => LuaValue::Number(f32::from_bits(raw as u32) as f64),
+            Self::Double => LuaValue::Number(f64::from_bits(raw)),
+            Self::Pointer => LuaPtr::new(raw).into_lua(lua)?,
+        };
+        Ok(value)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    pub name: String,
+    pub ty: ValueType,
+}
+
+/// Hook 函数签名
+#[derive(Debug, Clone)]
+pub struct FunctionSignature {
+    pub ret: ValueType,
+    pub params: Vec<Parameter>,
+}
+
+impl FunctionSignature {
+    /// 解析签名字符串
+    pub fn parse(signature: &str) -> LuaResult<Self> {
+        let invalid =
+            || Error::InvalidValue("function signature", signature.to_string()).into_lua_err();
+
+        let (ret, rest) = signature.split_once('(').ok_or_else(invalid)?;
+        let params_str = rest.trim_end().strip_suffix(')').ok_or_else(invalid)?;
+        let ret = ValueType::from_type_name(&normalize_type(ret)).ok_or_else(invalid)?;
+
+        let mut params = Vec::new();
+        for (index, param) in params_str.split(',').enumerate() {
+            let param = param.trim();
+            if param.is_empty() || (index == 0 && param == "void") {
+                continue;
+            }
+            // 最后一个标识符为参数名，其余部分为类型
+            let split_at = param
+                .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .map(|i| i + 1)
+                .ok_or_else(invalid)?;
+            let (type_name, name) = param.split_at(split_at);
+            if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            let ty = ValueType::from_type_name(&normalize_type(type_name)).ok_or_else(invalid)?;
+            if ty == ValueType::Void {
+                return Err(invalid());
+            }
+
+            params.push(Parameter {
+                name: name.to_string(),
+                ty,
+            });
+        }
+
+        Ok(Self { ret, params })
+    }
+
+    /// 根据参数名查找参数位置与类型
+    pub fn param(&self, name: &str) -> Option<(u32, ValueType)> {
+        self.params
+            .iter()
+            .position(|p| p.name == name)
+            .map(|index| (index as u32, self.params[index].ty))
+    }
+
+    /// 参数是否通过 XMM 寄存器传递，返回寄存器序号
+    pub fn xmm_register(index: u32, ty: ValueType) -> Option<usize> {
+        (ty.is_float() && (index as usize) < REGISTER_ARG_COUNT).then_some(index as usize)
+    }
+
+    /// 解码参数值
+    ///
+    /// `raw` 为通用寄存器或栈上的值，`xmm` 为对应 XMM 寄存器的低 64 位，
+    /// 前 4 个浮点参数从 `xmm` 解码，无法读取时返回 nil。
+    pub fn decode_param(
+        &self,
+        lua: &Lua,
+        index: u32,
+        ty: ValueType,
+        raw: u64,
+        xmm: Option<u64>,
+    ) -> LuaResult<LuaValue> {
+        if Self::xmm_register(index, ty).is_some() {
+            return match xmm {
+                Some(bits) => ty.decode(lua, bits),
+                None => Ok(LuaNil),
+            };
+        }
+        ty.decode(lua, raw)
+    }
+
+    /// 解码返回值，浮点返回值位于 XMM0，无法读取时保持原始整数
+    pub fn decode_ret(&self, lua: &Lua, raw: u64, xmm0: Option<u64>) -> LuaResult<LuaValue> {
+        if self.ret.is_float() {
+            return match xmm0 {
+                Some(bits) => self.ret.decode(lua, bits),
+                None => Ok(LuaValue::Integer(raw as i64)),
+            };
+        }
+        self.ret.decode(lua, raw)
+    }
+}
+
+/// 规范化类型名：去除 const/struct/class 修饰并合并空白
+fn normalize_type(type_name: &str) -> String {
+    let words = type_name
+        .split_whitespace()
+        .filter(|w| !matches!(*w, "const" | "struct" | "class" | "volatile"))
+        .collect::<Vec<_>>();
+    let joined = words.join(" ");
+    // 指针符号前的空白，如 "cPlayer *"
+    joined.replace(" *", "*").replace(" &", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_signature() {
+        let sig =
+            FunctionSignature::parse("void(const cPlayer * this, float dmg, unsigned int part)")
+                .unwrap();
+        assert_eq!(sig.ret, ValueType::Void);
+        assert_eq!(sig.params.len(), 3);
+        assert_eq!(sig.param("this"), Some((0, ValueType::Pointer)));
+        assert_eq!(sig.param("dmg"), Some((1, ValueType::Float)));
+        assert_eq!(sig.param("part"), Some((2, ValueType::UInt32)));
+        assert_eq!(sig.param("missing"), None);
+
+        let sig = FunctionSignature::parse("double(void)").unwrap();
+        assert_eq!(sig.ret, ValueType::Double);
+        assert!(sig.params.is_empty());
+    }
+
+    #[test]
+    fn test_parse_invalid_signature() {
+        for signature in [
+            "void",
+            "void(int",
+            "unknown(int a)",
+            "void(int)",
+            "void(int 1a)",
+            "void(void a)",
+            "void(foo a)",
+        ] {
+            assert!(
+                FunctionSignature::parse(signature).is_err(),
+                "{} should be rejected",
+                signature
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_value() {
+        let lua = Lua::new();
+        let decode = |ty: ValueType, raw: u64| ty.decode(&lua, raw).unwrap();
+
+        assert_eq!(decode(ValueType::Bool, 0x100), LuaValue::Boolean(false));
+        assert_eq!(decode(ValueType::Int8, 0xFF), LuaValue::Integer(-1));
+        assert_eq!(decode(ValueType::UInt8, 0x1FF), LuaValue::Integer(0xFF));
+        assert_eq!(decode(ValueType::Int32, 0xFFFF_FFFE), LuaValue::Integer(-2));
+        assert_eq!(
+            decode(ValueType::UInt32, 0xFFFF_FFFF_FFFF_FFFF),
+            LuaValue::Integer(0xFFFF_FFFF)
+        );
+        assert_eq!(
+            decode(ValueType::Float, 1.5f32.to_bits() as u64),
+            LuaValue::Number(1.5)
+        );
+        assert_eq!(
+            decode(ValueType::Double, 2.25f64.to_bits()),
+            LuaValue::Number(2.25)
+        );
+    }
+
+    #[test]
+    fn test_decode_xmm_param() {
+        let lua = Lua::new();
+        let sig =
+            FunctionSignature::parse("float(void* a, float b, int c, int d, float e)").unwrap();
+        let bits = 3.5f32.to_bits() as u64;
+
+        // 前 4 个浮点参数从 XMM 寄存器读取
+        assert_eq!(
+            FunctionSignature::xmm_register(1, ValueType::Float),
+            Some(1)
+        );
+        assert_eq!(
+            sig.decode_param(&lua, 1, ValueType::Float, 0, Some(bits))
+                .unwrap(),
+            LuaValue::Number(3.5)
+        );
+        assert_eq!(
+            sig.decode_param(&lua, 1, ValueType::Float, bits, None)
+                .unwrap(),
+            LuaNil
+        );
+        // 第 5 个参数位于栈上
+        assert_eq!(FunctionSignature::xmm_register(4, ValueType::Float), None);
+        assert_eq!(
+            sig.decode_param(&lua, 4, ValueType::Float, bits, None)
+                .unwrap(),
+            LuaValue::Number(3.5)
+        );
+        // 浮点返回值位于 XMM0
+        assert_eq!(
+            sig.decode_ret(&lua, 0, Some(bits)).unwrap(),
+            LuaValue::Number(3.5)
+        );
+    }
+}
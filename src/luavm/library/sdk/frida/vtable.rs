@@ -0,0 +1,98 @@
+//! 虚表 Hook
+//!
+//! 将虚表项替换为跳转到原函数的跳板，再在跳板上设置 inline Hook。
+//! 只有使用该虚表的对象会触发回调，共享实现的其他类不受影响。
+//!
+//! 跳板常驻不释放，其他线程可能仍在执行；同一虚表项重新 Hook 时复用。
+
+use std::{
+    collections::HashMap,
+    sync::{
+        LazyLock,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use parking_lot::Mutex;
+
+use crate::{error::Result, memory::MemoryUtils};
+
+const STUB_SIZE: usize = 32;
+
+/// 虚表项地址 -> 跳板地址
+static STUBS: LazyLock<Mutex<HashMap<usize, usize>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 已替换的虚表项
+#[derive(Debug)]
+pub struct VtableSlot {
+    pub slot: usize,
+    pub original: usize,
+    pub stub: usize,
+}
+
+impl VtableSlot {
+    /// 计算对象虚函数的虚表项地址
+    pub fn slot_address(object: usize, index: usize) -> Result<usize> {
+        MemoryUtils::check_permission_read(object)?;
+        let vtable = unsafe { *(object as *const usize) };
+        let slot = vtable + index * size_of::<usize>();
+        MemoryUtils::check_permission_read(slot)?;
+        Ok(slot)
+    }
+
+    /// 将虚表项替换为跳板
+    pub fn install(slot: usize) -> Result<Self> {
+        let original = unsafe { *(slot as *const usize) };
+        MemoryUtils::check_permission_execute(original)?;
+
+        let stub = Self::stub_for(slot, original)?;
+        Self::write_slot(slot, stub)?;
+
+        Ok(Self {
+            slot,
+            original,
+            stub,
+        })
+    }
+
+    /// 还原虚表项，虚表项已被其他工具修改时保持不变
+    pub fn restore(&self) -> Result<()> {
+        let current = unsafe { *(self.slot as *const usize) };
+        if current != self.stub {
+            log::warn!(
+                "Vtable slot 0x{:x} was modified by others, not restoring",
+                self.slot
+            );
+            return Ok(());
+        }
+        Self::write_slot(self.slot, self.original)
+    }
+
+    /// 获取或创建跳板：jmp [rip+0]; dq original
+    fn stub_for(slot: usize, original: usize) -> Result<usize> {
+        let mut stubs = STUBS.lock();
+        let stub = match stubs.get(&slot) {
+            Some(stub) => *stub,
+            None => {
+                let stub = MemoryUtils::alloc_executable(STUB_SIZE)?;
+                stubs.insert(slot, stub);
+                stub
+            }
+        };
+
+        let mut code = vec![0xFF, 0x25, 0x00, 0x00, 0x00, 0x00];
+        code.extend_from_slice(&(original as u64).to_le_bytes());
+        code.resize(STUB_SIZE, 0xCC);
+        MemoryUtils::patch(stub, &code)?;
+        MemoryUtils::flush_instruction_cache(stub, STUB_SIZE)?;
+
+        Ok(stub)
+    }
+
+    fn write_slot(slot: usize, value: usize) -> Result<()> {
+        let _guard = MemoryUtils::unprotect(slot, size_of::<usize>())?;
+        // 虚表项按指针对齐，原子写入避免其他线程读到一半
+        unsafe { (*(slot as *const AtomicUsize)).store(value, Ordering::SeqCst) };
+        Ok(())
+    }
+}
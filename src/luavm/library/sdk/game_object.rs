@@ -0,0 +1,123 @@
+//! 游戏对象弱引用
+//!
+//! 直接保存的指针在对象析构后仍可访问，导致崩溃。
+//! `GameObject.track` 返回的弱引用在对象析构时失效，失效后访问返回 nil 或报错。
+
+use mlua::prelude::*;
+
+use super::{
+    class_def::{ClassRegistry, GameClassObject},
+    frida,
+    luaptr::LuaPtr,
+};
+use crate::{
+    error::Error,
+    game::{
+        mt_type::{EmptyGameObject, GameObject, GameObjectExt},
+        object_tracker::ObjectTracker,
+    },
+    luavm::library::LuaModule,
+    memory::MemoryUtils,
+};
+
+/// MtObject 虚函数：析构函数
+const VFN_DESTRUCTOR: usize = 0;
+
+pub struct GameObjectModule;
+
+impl LuaModule for GameObjectModule {
+    fn register_library(lua: &mlua::Lua, registry: &mlua::Table) -> mlua::Result<()> {
+        let game_object_table = lua.create_table()?;
+        // 追踪对象，返回弱引用
+        game_object_table.set(
+            "track",
+            lua.create_function(|_, ptr: LuaPtr| WeakGameObject::track(ptr.to_usize()))?,
+        )?;
+
+        registry.set("GameObject", game_object_table)?;
+
+        Ok(())
+    }
+}
+
+/// 游戏对象弱引用
+#[derive(Debug, Clone)]
+pub struct WeakGameObject {
+    address: usize,
+    generation: u64,
+    class_name: Option<String>,
+}
+
+impl WeakGameObject {
+    fn track(address: usize) -> LuaResult<Self> {
+        MemoryUtils::check_permission_read(address).map_err(|e| e.into_lua_err())?;
+        let object = EmptyGameObject::from_address(address);
+
+        // 监听对象自身虚函数表中的析构函数
+        let destructor = object.get_virtual_function(VFN_DESTRUCTOR).ok_or_else(|| {
+            Error::InvalidValue("game object with vtable", format!("0x{:x}", address))
+                .into_lua_err()
+        })?;
+        frida::watch_destructor(destructor as usize).map_err(|e| e.into_lua_err())?;
+
+        let class_name = object
+            .get_dti()
+            .and_then(|dti| dti.name().map(|name| name.to_string()));
+
+        Ok(Self {
+            address,
+            generation: ObjectTracker::instance().track(address),
+            class_name,
+        })
+    }
+
+    fn is_valid(&self) -> bool {
+        ObjectTracker::instance().is_valid(self.address, self.generation)
+    }
+
+    fn ensure_valid(&self) -> LuaResult<()> {
+        if !self.is_valid() {
+            return Err(Error::InvalidValue(
+                "alive game object",
+                format!("destroyed object 0x{:x}", self.address),
+            )
+            .into_lua_err());
+        }
+        Ok(())
+    }
+}
+
+impl LuaUserData for WeakGameObject {
+    fn add_fields<F: LuaUserDataFields<Self>>(fields: &mut F) {
+        fields.add_meta_field(LuaMetaMethod::Type, "GameObject");
+    }
+
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        methods.add_meta_method(LuaMetaMethod::ToString, |_, this, ()| {
+            let state = if this.is_valid() { "" } else { ", destroyed" };
+            Ok(format!(
+                "GameObject({}, 0x{:016X}{})",
+                this.class_name.as_deref().unwrap_or("?"),
+                this.address,
+                state
+            ))
+        });
+        methods.add_method("is_valid", |_, this, ()| Ok(this.is_valid()));
+        // 对象指针，失效后返回 nil
+        methods.add_method("ptr", |_, this, ()| {
+            Ok(this.is_valid().then(|| LuaPtr::new(this.address as u64)))
+        });
+        methods.add_method("class_name", |_, this, ()| Ok(this.class_name.clone()));
+        // 转换为 ClassDef 类对象，失效后报错
+        methods.add_method("as_class", |_, this, class_name: Option<String>| {
+            this.ensure_valid()?;
+            let Some(name) = class_name.or_else(|| this.class_name.clone()) else {
+                return Err(Error::ClassDefNotFound("?".to_string()).into_lua_err());
+            };
+            let def = ClassRegistry::instance()
+                .get(&name)
+                .ok_or_else(|| Error::ClassDefNotFound(name).into_lua_err())?;
+            Ok(GameClassObject::new(def, this.address))
+        });
+    }
+}
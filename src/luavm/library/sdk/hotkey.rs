@@ -0,0 +1,90 @@
+//! 脚本快捷键
+//!
+//! 绑定由 `HotkeyManager` 统一管理，回调保存在虚拟机的 `_hotkeys` 表中，
+//! 虚拟机销毁时移除该脚本的所有绑定。
+
+use mlua::prelude::*;
+
+use super::input::parse_key;
+use crate::{
+    input::{KeyCode, hotkey::HotkeyManager},
+    luavm::library::LuaModule,
+};
+
+const HOTKEYS_KEY: &str = "_hotkeys";
+
+pub struct HotkeyModule;
+
+impl LuaModule for HotkeyModule {
+    fn register_library(lua: &mlua::Lua, registry: &mlua::Table) -> mlua::Result<()> {
+        let hotkey_table = lua.create_table()?;
+        // 注册快捷键，返回实际生效的按键（用户可能已修改绑定）
+        hotkey_table.set(
+            "register",
+            lua.create_function(
+                |lua, (name, keys, callback): (String, Vec<LuaValue>, LuaFunction)| {
+                    let keys = keys
+                        .into_iter()
+                        .map(|key| parse_key(lua, key))
+                        .collect::<LuaResult<Vec<_>>>()?;
+                    let script = script_name(lua)?;
+                    let keys = HotkeyManager::instance().register(&script, &name, keys);
+                    hotkeys_table(lua)?.set(name, callback)?;
+                    Ok(key_names(&keys))
+                },
+            )?,
+        )?;
+        hotkey_table.set(
+            "unregister",
+            lua.create_function(|lua, name: String| {
+                let script = script_name(lua)?;
+                hotkeys_table(lua)?.set(name.as_str(), LuaNil)?;
+                Ok(HotkeyManager::instance().unregister(&script, &name))
+            })?,
+        )?;
+        // 获取当前绑定的按键
+        hotkey_table.set(
+            "get",
+            lua.create_function(|lua, name: String| {
+                let script = script_name(lua)?;
+                Ok(HotkeyManager::instance()
+                    .keys(&script, &name)
+                    .map(|keys| key_names(&keys)))
+            })?,
+        )?;
+
+        registry.set("Hotkey", hotkey_table)?;
+        Ok(())
+    }
+}
+
+impl HotkeyModule {
+    /// 调用快捷键回调
+    pub fn dispatch(lua: &Lua, name: &str) -> LuaResult<()> {
+        let Some(hotkeys) = lua.globals().get::<Option<LuaTable>>(HOTKEYS_KEY)? else {
+            return Ok(());
+        };
+        if let Some(callback) = hotkeys.get::<Option<LuaFunction>>(name)? {
+            callback.call::<()>(name)?;
+        }
+        Ok(())
+    }
+}
+
+fn script_name(lua: &Lua) -> LuaResult<String> {
+    lua.globals().get::<String>("_name")
+}
+
+fn key_names(keys: &[KeyCode]) -> Vec<&'static str> {
+    keys.iter().map(|key| <&'static str>::from(*key)).collect()
+}
+
+fn hotkeys_table(lua: &Lua) -> LuaResult<LuaTable> {
+    let globals = lua.globals();
+    if let Some(hotkeys) = globals.get::<Option<LuaTable>>(HOTKEYS_KEY)? {
+        return Ok(hotkeys);
+    }
+    let hotkeys = lua.create_table()?;
+    globals.set(HOTKEYS_KEY, &hotkeys)?;
+    Ok(hotkeys)
+}
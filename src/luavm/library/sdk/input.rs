@@ -1,8 +1,15 @@
 use mlua::prelude::*;
 
 use crate::{
+    config::Config,
     error::Error,
-    input::{ControllerButton, Input, KeyCode},
+    input::{
+        ControllerButton, Input, KeyCode,
+        axis::{self, Side},
+        layout,
+        mouse::MouseButton,
+        remap,
+    },
     luavm::library::LuaModule,
 };
 
@@ -30,6 +37,44 @@ impl LuaModule for InputModule {
                 Ok(Input::instance().keyboard().is_down(key_code))
             })?,
         )?;
+        // 键盘按键是否被点击，或按住时重复触发
+        key_table.set(
+            "is_repeated",
+            lua.create_function(|lua, key: LuaValue| {
+                let key_code = parse_key(lua, key)?;
+                Ok(Input::instance().is_repeated(key_code))
+            })?,
+        )?;
+        // 获取按键在当前键盘布局下的显示名称
+        key_table.set(
+            "display_name",
+            lua.create_function(|lua, key: LuaValue| {
+                let key_code = parse_key(lua, key)?;
+                Ok(layout::display_name(key_code))
+            })?,
+        )?;
+        // 通过显示名称查找按键
+        key_table.set(
+            "find_by_display_name",
+            lua.create_function(|_, name: String| {
+                Ok(layout::find_by_display_name(&name).map(<&'static str>::from))
+            })?,
+        )?;
+        // 获取按键在当前键盘布局下的虚拟键码
+        key_table.set(
+            "to_virtual_key",
+            lua.create_function(|lua, key: LuaValue| {
+                let key_code = parse_key(lua, key)?;
+                Ok(layout::to_virtual_key(key_code))
+            })?,
+        )?;
+        // 通过虚拟键码获取按键
+        key_table.set(
+            "from_virtual_key",
+            lua.create_function(|_, vk: u32| {
+                Ok(layout::from_virtual_key(vk).map(<&'static str>::from))
+            })?,
+        )?;
         input_table.set("keyboard", key_table)?;
 
         let controller_table = lua.create_table()?;
@@ -37,27 +82,184 @@ impl LuaModule for InputModule {
         controller_table.set(
             "is_pressed",
             lua.create_function(|lua, key: LuaValue| {
-                let key_code = parse_controller(lua, key)?;
-                Ok(Input::instance().controller().is_pressed(key_code))
+                let buttons = parse_controller_buttons(lua, key)?;
+                let controller = Input::instance().controller();
+                Ok(buttons.into_iter().any(|b| controller.is_pressed(b)))
             })?,
         )?;
         // 手柄按键是否被按下
         controller_table.set(
             "is_down",
             lua.create_function(|lua, key: LuaValue| {
-                let key_code = parse_controller(lua, key)?;
-                Ok(Input::instance().controller().is_down(key_code))
+                let buttons = parse_controller_buttons(lua, key)?;
+                let controller = Input::instance().controller();
+                Ok(buttons.into_iter().any(|b| controller.is_down(b)))
+            })?,
+        )?;
+        // 摇杆位置，已应用死区，手柄未连接时返回 nil
+        controller_table.set(
+            "stick",
+            lua.create_function(|_, (side, index): (String, Option<u32>)| {
+                let side = parse_side(&side)?;
+                Ok(axis::stick(index.unwrap_or(0), side)
+                    .map_or((None, None), |(x, y)| (Some(x), Some(y))))
+            })?,
+        )?;
+        // 扳机位置，已应用死区，手柄未连接时返回 nil
+        controller_table.set(
+            "trigger",
+            lua.create_function(|_, (side, index): (String, Option<u32>)| {
+                let side = parse_side(&side)?;
+                Ok(axis::trigger(index.unwrap_or(0), side))
+            })?,
+        )?;
+        // 获取逻辑按键对应的物理按键
+        controller_table.set(
+            "mapping",
+            lua.create_function(|_, name: String| {
+                Ok(remap::resolve(&name).map(|buttons| {
+                    buttons
+                        .into_iter()
+                        .map(<&'static str>::from)
+                        .collect::<Vec<_>>()
+                }))
+            })?,
+        )?;
+        // 获取所有逻辑按键名称
+        controller_table.set(
+            "logical_names",
+            lua.create_function(|_, ()| {
+                Ok(remap::mappings()
+                    .into_iter()
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>())
             })?,
         )?;
         input_table.set("controller", controller_table)?;
 
+        let mouse_table = lua.create_table()?;
+        // 光标在游戏窗口客户区中的位置
+        mouse_table.set(
+            "position",
+            lua.create_function(|_, ()| Ok(Input::instance().mouse().position()))?,
+        )?;
+        // 鼠标按键是否被点击
+        mouse_table.set(
+            "is_pressed",
+            lua.create_function(|lua, button: LuaValue| {
+                let button = parse_mouse_button(lua, button)?;
+                Ok(Input::instance().mouse().is_pressed(button))
+            })?,
+        )?;
+        // 鼠标按键是否被按下
+        mouse_table.set(
+            "is_down",
+            lua.create_function(|lua, button: LuaValue| {
+                let button = parse_mouse_button(lua, button)?;
+                Ok(Input::instance().mouse().is_down(button))
+            })?,
+        )?;
+        // 鼠标按键是否被松开
+        mouse_table.set(
+            "is_released",
+            lua.create_function(|lua, button: LuaValue| {
+                let button = parse_mouse_button(lua, button)?;
+                Ok(Input::instance().mouse().is_released(button))
+            })?,
+        )?;
+        // 上一帧的滚轮滚动格数，向上为正
+        mouse_table.set(
+            "wheel",
+            lua.create_function(|_, ()| Ok(Input::instance().mouse().wheel_delta()))?,
+        )?;
+        // 拖动时返回相对按下位置的偏移，未拖动时返回 nil
+        mouse_table.set(
+            "drag_delta",
+            lua.create_function(|lua, button: LuaValue| {
+                let button = parse_mouse_button(lua, button)?;
+                Ok(Input::instance()
+                    .mouse()
+                    .drag_delta(button)
+                    .map_or((None, None), |(dx, dy)| (Some(dx), Some(dy))))
+            })?,
+        )?;
+        input_table.set("mouse", mouse_table)?;
+
+        // 设置键盘按键状态变化回调
+        input_table.set(
+            "on_key",
+            lua.create_function(|lua, fun: LuaFunction| {
+                lua.globals().set("_on_key", fun)?;
+                Ok(())
+            })?,
+        )?;
+        // 设置按住按键时的重复触发回调
+        input_table.set(
+            "on_key_repeat",
+            lua.create_function(|lua, fun: LuaFunction| {
+                lua.globals().set("_on_key_repeat", fun)?;
+                Ok(())
+            })?,
+        )?;
+        // 设置手柄按键状态变化回调
+        input_table.set(
+            "on_button",
+            lua.create_function(|lua, fun: LuaFunction| {
+                lua.globals().set("_on_button", fun)?;
+                Ok(())
+            })?,
+        )?;
+
+        // 向游戏键盘写入按键状态，需要在设置中允许输入注入
+        input_table.set(
+            "send_key",
+            lua.create_function(|lua, (key, down): (LuaValue, bool)| {
+                let key_code = parse_key(lua, key)?;
+                Input::instance()
+                    .send_key(key_code, down, &script_name(lua)?)
+                    .into_lua_err()
+            })?,
+        )?;
+        // 按当前键盘布局输入文本，返回加入队列的字符数
+        input_table.set(
+            "send_text",
+            lua.create_function(|lua, text: String| {
+                Input::instance()
+                    .send_text(&text, &script_name(lua)?)
+                    .into_lua_err()
+            })?,
+        )?;
+
+        // 获取死区和按键重复设置
+        input_table.set(
+            "settings",
+            lua.create_function(|lua, ()| {
+                let config = Config::global();
+                let table = lua.create_table()?;
+                table.set("stick_dead_zone", config.input.stick_dead_zone)?;
+                table.set("trigger_dead_zone", config.input.trigger_dead_zone)?;
+                table.set("repeat_delay_ms", config.input.repeat_delay_ms)?;
+                table.set("repeat_interval_ms", config.input.repeat_interval_ms)?;
+                Ok(table)
+            })?,
+        )?;
+
         registry.set("Input", input_table)?;
 
         Ok(())
     }
 }
 
-fn parse_key(lua: &Lua, key: LuaValue) -> LuaResult<KeyCode> {
+fn script_name(lua: &Lua) -> LuaResult<String> {
+    lua.globals().get::<String>("_name")
+}
+
+fn parse_side(side: &str) -> LuaResult<Side> {
+    side.parse::<Side>()
+        .map_err(|_| Error::InvalidValue("\"left\" or \"right\"", side.to_string()).into_lua_err())
+}
+
+pub(super) fn parse_key(lua: &Lua, key: LuaValue) -> LuaResult<KeyCode> {
     // 支持格式：字符串枚举值，数字枚举值
     if key.is_string() {
         let val: KeyCode = lua.from_value(key)?;
@@ -77,6 +279,16 @@ fn parse_key(lua: &Lua, key: LuaValue) -> LuaResult<KeyCode> {
     }
 }
 
+/// 解析手柄按键，字符串优先按逻辑按键名称查找映射
+fn parse_controller_buttons(lua: &Lua, key: LuaValue) -> LuaResult<Vec<ControllerButton>> {
+    if let Some(name) = key.as_string()
+        && let Some(buttons) = remap::resolve(&name.to_string_lossy())
+    {
+        return Ok(buttons);
+    }
+    Ok(vec![parse_controller(lua, key)?])
+}
+
 fn parse_controller(lua: &Lua, key: LuaValue) -> LuaResult<ControllerButton> {
     // 支持格式：字符串枚举值，数字枚举值
     if key.is_string() {
@@ -96,3 +308,21 @@ fn parse_controller(lua: &Lua, key: LuaValue) -> LuaResult<ControllerButton> {
         .into_lua_err())
     }
 }
+
+fn parse_mouse_button(lua: &Lua, button: LuaValue) -> LuaResult<MouseButton> {
+    // 支持格式：字符串枚举值，数字枚举值
+    if button.is_string() {
+        let val: MouseButton = lua.from_value(button)?;
+        Ok(val)
+    } else if let Some(button_int) = button.as_integer() {
+        MouseButton::from_repr(button_int as u32).ok_or(LuaError::external(format!(
+            "{button_int} is not a valid MouseButton."
+        )))
+    } else {
+        Err(Error::InvalidValue(
+            "integer or string expected for MouseButton",
+            format!("{:?}", button),
+        )
+        .into_lua_err())
+    }
+}
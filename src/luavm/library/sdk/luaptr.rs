@@ -2,9 +2,17 @@ use mlua::prelude::*;
 
 use crate::error::{Error, Result};
 
+use super::{
+    buffer::Buffer,
+    string::{Encoding, ManagedString},
+};
 use crate::luavm::library::LuaModule;
 use crate::{
-    luavm::library::{runtime::RuntimeModule, utility::UtilityModule},
+    luavm::{
+        capability::{self, UnsafeCapability},
+        library::utility::UtilityModule,
+        safety::SafetyPolicy,
+    },
     memory::MemoryUtils,
 };
 
@@ -70,6 +78,10 @@ impl LuaUserData for LuaPtr {
 
             Ok(value)
         });
+        // 读取有符号整数，进行符号扩展
+        methods.add_method("read_signed", |lua, this, size: u32| {
+            read_integer(lua, this.to_usize(), size, true).into_lua_err()
+        });
         methods.add_method("read_bytes", |lua, this, size: u32| {
             if size == 0 {
                 return Ok(vec![]);
@@ -93,7 +105,7 @@ impl LuaUserData for LuaPtr {
         });
         methods.add_method(
             "write_bytes",
-            |lua, this, (buf, size): (Vec<u8>, Option<u32>)| {
+            |lua, this, (buf, size): (Buffer, Option<u32>)| {
                 let size = size.unwrap_or(buf.len() as u32);
                 if size == 0 || size > buf.len() as u32 {
                     return Err(
@@ -111,12 +123,10 @@ impl LuaUserData for LuaPtr {
         );
 
         // register read_i32, read_i64, write_i32, write_i64, and so on
+        // 读取时不进行符号扩展，有符号整数使用 read_signed
         INTEGER_TYPE_SIZE_MAP.iter().for_each(|(name, size)| {
             methods.add_method(format!("read_{}", name), |lua, this, ()| {
-                let ptr = this.to_usize();
-                let bytes = quick_read_bytes(lua, ptr, *size).into_lua_err()?;
-                let value = i64::from_le_bytes(bytes);
-                Ok(value)
+                read_integer(lua, this.to_usize(), *size, false).into_lua_err()
             });
             methods.add_method(format!("write_{}", name), |lua, this, integer: i64| {
                 let ptr = this.to_usize();
@@ -163,8 +173,22 @@ impl LuaUserData for LuaPtr {
             Ok(luaptr)
         });
 
-        // 进阶内存读写方法
-        // TODO: 各种字符串读写
+        // 读取以 `\0` 结尾的字符串，默认最多读取 1024 字节
+        methods.add_method(
+            "read_string",
+            |lua, this, (max_len, encoding): (Option<usize>, Option<LuaValue>)| {
+                let encoding = parse_encoding(lua, encoding)?;
+                read_string(lua, this.to_usize(), max_len, encoding).into_lua_err()
+            },
+        );
+        // 写入字符串，包含结尾的 `\0`
+        methods.add_method(
+            "write_string",
+            |lua, this, (value, encoding): (String, Option<LuaValue>)| {
+                let encoding = parse_encoding(lua, encoding)?;
+                write_string(lua, this.to_usize(), &value, encoding).into_lua_err()
+            },
+        );
 
         // 指针运算便捷方法
         // 多级指针偏移等
@@ -318,7 +342,7 @@ impl FromLua for LuaPtr {
     }
 }
 
-const INTEGER_TYPE_SIZE_MAP: &[(&str, u32)] = &[
+pub(super) const INTEGER_TYPE_SIZE_MAP: &[(&str, u32)] = &[
     ("i8", 1),
     ("u8", 1),
     ("i16", 2),
@@ -329,22 +353,106 @@ const INTEGER_TYPE_SIZE_MAP: &[(&str, u32)] = &[
     ("u64", 8),
 ];
 
-fn read_bytes(lua: &Lua, address: usize, size: u32) -> Result<Vec<u8>> {
-    let is_unsafe = RuntimeModule::is_unsafe_mode(lua);
+/// 字符串默认最多读取的字节数
+const DEFAULT_STRING_MAX_LEN: usize = 1024;
+const PAGE_SIZE: usize = 0x1000;
+
+/// 读取整数，signed 为 true 时进行符号扩展
+pub(super) fn read_integer(lua: &Lua, address: usize, size: u32, signed: bool) -> Result<i64> {
+    if size == 0 || size > 8 {
+        return Err(Error::InvalidValue("0 < size <= 8", size.to_string()));
+    }
+    let bytes = quick_read_bytes(lua, address, size)?;
+    let value = i64::from_le_bytes(bytes);
+    if signed && size < 8 {
+        let shift = 64 - size * 8;
+        return Ok((value << shift) >> shift);
+    }
+    Ok(value)
+}
+
+/// 读取以 `\0` 结尾的字符串
+///
+/// 按页读取并逐页检查权限，字符串跨越不可读页面时返回错误。
+pub(super) fn read_string(
+    lua: &Lua,
+    address: usize,
+    max_len: Option<usize>,
+    encoding: Encoding,
+) -> Result<String> {
+    let max_len = max_len.unwrap_or(DEFAULT_STRING_MAX_LEN);
+    let unit = match encoding {
+        Encoding::Utf8 => 1,
+        Encoding::Utf16 => 2,
+    };
+
+    let mut bytes = Vec::new();
+    // 已检查的完整字符长度
+    let mut checked = 0;
+    'read: while bytes.len() < max_len {
+        let cursor = address + bytes.len();
+        let page_end = (cursor / PAGE_SIZE + 1) * PAGE_SIZE;
+        let chunk_len = (page_end - cursor).min(max_len - bytes.len());
+        bytes.extend(read_bytes(lua, cursor, chunk_len as u32)?);
+
+        while checked + unit <= bytes.len() {
+            if bytes[checked..checked + unit].iter().all(|b| *b == 0) {
+                break 'read;
+            }
+            checked += unit;
+        }
+    }
+    bytes.truncate(checked);
+
+    let string = match encoding {
+        Encoding::Utf8 => String::from_utf8_lossy(&bytes).to_string(),
+        Encoding::Utf16 => {
+            let units = bytes
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .collect::<Vec<_>>();
+            String::from_utf16_lossy(&units)
+        }
+    };
+    Ok(string)
+}
+
+/// 写入字符串，包含结尾的 `\0`
+pub(super) fn write_string(
+    lua: &Lua,
+    address: usize,
+    value: &str,
+    encoding: Encoding,
+) -> Result<()> {
+    let bytes = ManagedString::new(value, encoding).to_bytes_with_nul();
+    write_bytes(lua, address, &bytes)
+}
+
+/// 解析字符串编码，默认 UTF-8
+pub(super) fn parse_encoding(lua: &Lua, encoding: Option<LuaValue>) -> LuaResult<Encoding> {
+    match encoding {
+        Some(encoding) => lua.from_value(encoding),
+        None => Ok(Encoding::Utf8),
+    }
+}
+
+pub(super) fn read_bytes(lua: &Lua, address: usize, size: u32) -> Result<Vec<u8>> {
+    let is_unsafe = capability::has_capability(lua, UnsafeCapability::MemoryRead);
     let bytes = MemoryUtils::read(address, size as usize, !is_unsafe)?;
 
     Ok(bytes)
 }
 
-fn quick_read_bytes(lua: &Lua, address: usize, size: u32) -> Result<[u8; 8]> {
-    let is_unsafe = RuntimeModule::is_unsafe_mode(lua);
+pub(super) fn quick_read_bytes(lua: &Lua, address: usize, size: u32) -> Result<[u8; 8]> {
+    let is_unsafe = capability::has_capability(lua, UnsafeCapability::MemoryRead);
     let bytes = MemoryUtils::quick_read(address, size, !is_unsafe)?;
 
     Ok(bytes)
 }
 
-fn write_bytes(lua: &Lua, address: usize, bytes: &[u8]) -> Result<()> {
-    let is_unsafe = RuntimeModule::is_unsafe_mode(lua);
+pub(super) fn write_bytes(lua: &Lua, address: usize, bytes: &[u8]) -> Result<()> {
+    SafetyPolicy::check_lua(lua, "memory write")?;
+    let is_unsafe = capability::has_capability(lua, UnsafeCapability::MemoryWrite);
     MemoryUtils::write(address, bytes, !is_unsafe)?;
 
     Ok(())
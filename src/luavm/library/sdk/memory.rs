@@ -1,15 +1,40 @@
-use std::{collections::HashMap, sync::LazyLock};
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock},
+};
 
+use frida_gum::instruction_writer::{InstructionWriter, X86InstructionWriter, X86Relocator};
 use mlua::prelude::*;
 use parking_lot::Mutex;
 
 use crate::{
-    address::AddressRecord,
+    address::{AddressRecord, AddressStatus, AddressValidator},
     error::{Error, Result},
-    memory::MemoryUtils,
+    luavm::{LuaVMManager, safety::SafetyPolicy},
+    memory::{DecodedInstruction, InstructionFlow, MemoryUtils},
+};
+
+use super::{
+    LuaModule,
+    buffer::Buffer,
+    luaptr::{self, INTEGER_TYPE_SIZE_MAP, LuaPtr},
+};
+use crate::luavm::library::utility::{
+    hash,
+    task::{self, TaskOutput},
+    time,
 };
 
-use super::{LuaModule, luaptr::LuaPtr};
+const WATCHES_KEY: &str = "_memory_watches";
+/// 虚拟机分配的内存，键为地址
+const ALLOCATIONS_KEY: &str = "_allocations";
+const WATCH_NEXT_ID_KEY: &str = "_memory_watch_next_id";
+/// 反汇编默认指令数
+const DEFAULT_DISASM_COUNT: usize = 10;
+/// 查找指令时默认最多解码的指令数
+const DEFAULT_FIND_MAX_COUNT: usize = 64;
+/// 内存监视默认检查间隔
+const DEFAULT_WATCH_INTERVAL_MS: f64 = 500.0;
 
 pub struct MemoryModule;
 
@@ -58,30 +83,199 @@ impl LuaModule for MemoryModule {
                 },
             )?,
         )?;
+        // 列出所有已加载的模块，第一个为主模块
+        memory.set(
+            "modules",
+            lua.create_function(|lua, ()| {
+                let modules = MemoryUtils::modules().map_err(|e| Error::from(e).into_lua_err())?;
+                let list = lua.create_table_with_capacity(modules.len(), 0)?;
+                for module in modules {
+                    let entry = lua.create_table()?;
+                    entry.set("name", module.name)?;
+                    entry.set("base", LuaPtr::new(module.base as u64))?;
+                    entry.set("size", module.size)?;
+                    list.push(entry)?;
+                }
+                Ok(list)
+            })?,
+        )?;
+        // 在指定模块中扫描，查找匹配的第一个地址
+        memory.set(
+            "scan_module",
+            lua.create_function(
+                |_, (module, pattern, offset): (String, String, Option<i32>)| {
+                    let result = MemoryUtils::scan_module_first(&module, &pattern)
+                        .map_err(|e| Error::from(e).into_lua_err())?;
+                    let result = (result as isize + offset.unwrap_or(0) as isize) as usize;
+                    Ok(LuaPtr::new(result as u64))
+                },
+            )?,
+        )?;
+        // 反汇编指定地址开始的 count 条指令，遇到无效指令时提前停止
+        memory.set(
+            "disasm",
+            lua.create_function(|lua, (ptr, count): (LuaPtr, Option<usize>)| {
+                let instructions =
+                    MemoryUtils::disassemble(ptr.to_usize(), count.unwrap_or(DEFAULT_DISASM_COUNT))
+                        .map_err(|e| Error::from(e).into_lua_err())?;
+                let list = lua.create_table_with_capacity(instructions.len(), 0)?;
+                for instruction in &instructions {
+                    list.push(instruction_to_table(lua, instruction)?)?;
+                }
+                Ok(list)
+            })?,
+        )?;
+        // 查找下一条 call 指令
+        memory.set(
+            "find_next_call",
+            lua.create_function(|lua, (ptr, max_count): (LuaPtr, Option<usize>)| {
+                find_next_instruction(lua, ptr, max_count, |ins| ins.flow == InstructionFlow::Call)
+            })?,
+        )?;
+        // 查找下一条跳转指令（包括条件跳转）
+        memory.set(
+            "find_next_jump",
+            lua.create_function(|lua, (ptr, max_count): (LuaPtr, Option<usize>)| {
+                find_next_instruction(lua, ptr, max_count, |ins| {
+                    matches!(
+                        ins.flow,
+                        InstructionFlow::Jump | InstructionFlow::ConditionalJump
+                    )
+                })
+            })?,
+        )?;
+        // 查找下一条指定助记符的指令，不区分大小写
+        memory.set(
+            "find_next_instruction",
+            lua.create_function(
+                |lua, (ptr, mnemonic, max_count): (LuaPtr, String, Option<usize>)| {
+                    find_next_instruction(lua, ptr, max_count, |ins| {
+                        ins.mnemonic.eq_ignore_ascii_case(&mnemonic)
+                    })
+                },
+            )?,
+        )?;
+        // 在后台线程扫描内存，返回任务句柄，完成后以地址列表调用 callback
+        memory.set(
+            "scan_all_async",
+            lua.create_function(
+                |lua,
+                 (ptr, size, pattern, callback, offset): (
+                    LuaPtr,
+                    usize,
+                    String,
+                    LuaFunction,
+                    Option<i32>,
+                )| {
+                    let address_usize = ptr.to_usize();
+                    task::spawn(lua, callback, move |token| {
+                        let results = MemoryUtils::scan_all_cancellable(
+                            address_usize,
+                            size,
+                            &pattern,
+                            || token.is_cancelled(),
+                        )
+                        .map_err(|e| e.to_string())?;
+
+                        let output: TaskOutput = Box::new(move |lua| {
+                            let offset = offset.unwrap_or(0) as isize;
+                            results
+                                .into_iter()
+                                .map(|ptr| LuaPtr::new((ptr as isize + offset) as u64))
+                                .collect::<Vec<_>>()
+                                .into_lua_multi(lua)
+                        });
+                        Ok(output)
+                    })
+                },
+            )?,
+        )?;
         // 分配一段填充为0的内存，并返回起始指针
         memory.set(
             "malloc",
-            lua.create_function(|_, size: usize| {
+            lua.create_function(|lua, size: usize| {
                 let address = MemoryAllocManager::instance()
                     .malloc(size)
                     .map_err(|e| e.into_lua_err())?;
-                Ok(LuaPtr::new(address as u64))
+                track_allocation(lua, address)
             })?,
         )?;
-        // 释放分配的内存
+        // 分配页内存，protection 为 "rw"（默认）或 "rwx"
+        memory.set(
+            "alloc",
+            lua.create_function(|lua, (size, protection): (usize, Option<String>)| {
+                let executable = parse_protection(protection.as_deref(), false)?;
+                if executable {
+                    SafetyPolicy::check_lua(lua, "Memory.alloc")?;
+                }
+                let address = MemoryAllocManager::instance()
+                    .alloc(size, executable)
+                    .map_err(|e| e.into_lua_err())?;
+                track_allocation(lua, address)
+            })?,
+        )?;
+        // 在地址前后 ±2GB 范围内分配页内存，用于 rel32 跳转可达的跳板，默认可执行
+        memory.set(
+            "alloc_near",
+            lua.create_function(
+                |lua, (ptr, size, protection): (LuaPtr, usize, Option<String>)| {
+                    let executable = parse_protection(protection.as_deref(), true)?;
+                    if executable {
+                        SafetyPolicy::check_lua(lua, "Memory.alloc_near")?;
+                    }
+                    let address = MemoryAllocManager::instance()
+                        .alloc_near(ptr.to_usize(), size, executable)
+                        .map_err(|e| e.into_lua_err())?;
+                    track_allocation(lua, address)
+                },
+            )?,
+        )?;
+        // 释放当前脚本分配的内存
         memory.set(
             "free",
-            lua.create_function(|_, ptr: LuaPtr| {
-                let ok = MemoryAllocManager::instance().free(ptr.to_usize());
-                Ok(ok)
+            lua.create_function(|lua, ptr: LuaPtr| {
+                let allocations = lua.globals().get::<LuaTable>(ALLOCATIONS_KEY)?;
+                let key = ptr.to_usize() as i64;
+                if !allocations.contains_key(key)? {
+                    return Ok(false);
+                }
+                allocations.set(key, LuaNil)?;
+                Ok(MemoryAllocManager::instance().free(ptr.to_usize()))
             })?,
         )?;
-        // 修改内存
+        // 修改内存，可指定适用的游戏版本，版本不匹配时跳过并返回 nil
         memory.set(
             "patch",
-            lua.create_function(|lua, (ptr, bytes): (LuaPtr, Vec<u8>)| {
+            lua.create_function(
+                |lua, (ptr, bytes, revisions): (LuaPtr, Buffer, Option<Vec<u32>>)| {
+                    SafetyPolicy::check_lua(lua, "Memory.patch")?;
+                    if let Some(revisions) = revisions
+                        && let Err(e) = crate::utility::check_game_revision(
+                            &format!("patch at 0x{:x}", ptr.to_u64()),
+                            &revisions,
+                        )
+                    {
+                        log::warn!("Memory patch skipped: {}", e.log());
+                        return Ok(None);
+                    }
+                    MemoryPatchManager::instance()
+                        .new_patch(ptr.to_usize(), &bytes)
+                        .map_err(|e| e.into_lua_err())?;
+
+                    let patch_table = lua.globals().get::<LuaTable>("_patches")?;
+                    patch_table.push(ptr)?;
+
+                    Ok(Some(ptr))
+                },
+            )?,
+        )?;
+        // 使用 0x90 填充内存
+        memory.set(
+            "patch_nop",
+            lua.create_function(|lua, (ptr, size): (LuaPtr, usize)| {
+                SafetyPolicy::check_lua(lua, "Memory.patch_nop")?;
                 MemoryPatchManager::instance()
-                    .new_patch(ptr.to_usize(), &bytes)
+                    .new_patch_nop(ptr.to_usize(), size)
                     .map_err(|e| e.into_lua_err())?;
 
                 let patch_table = lua.globals().get::<LuaTable>("_patches")?;
@@ -90,10 +284,13 @@ impl LuaModule for MemoryModule {
                 Ok(ptr)
             })?,
         )?;
-        // 使用 0x90 填充内存
+        // 以完整指令为单位填充 0x90，返回填充的字节数
         memory.set(
-            "patch_nop",
-            lua.create_function(|lua, (ptr, size): (LuaPtr, usize)| {
+            "nop_instructions",
+            lua.create_function(|lua, (ptr, count): (LuaPtr, usize)| {
+                SafetyPolicy::check_lua(lua, "Memory.nop_instructions")?;
+                let size =
+                    instructions_size(ptr.to_usize(), count).map_err(|e| e.into_lua_err())?;
                 MemoryPatchManager::instance()
                     .new_patch_nop(ptr.to_usize(), size)
                     .map_err(|e| e.into_lua_err())?;
@@ -101,7 +298,163 @@ impl LuaModule for MemoryModule {
                 let patch_table = lua.globals().get::<LuaTable>("_patches")?;
                 patch_table.push(ptr)?;
 
-                Ok(ptr)
+                Ok(size)
+            })?,
+        )?;
+        // 解析多级指针，失败时返回 nil
+        memory.set(
+            "resolve_chain",
+            lua.create_function(|_, (base, offsets): (LuaPtr, Vec<isize>)| {
+                Ok(MemoryUtils::resolve_chain(base.to_usize(), &offsets)
+                    .map(|ptr| LuaPtr::new(ptr as u64)))
+            })?,
+        )?;
+        // 按 CheatEngine 算法解析多级指针，先对基址取值
+        memory.set(
+            "resolve_chain_ce",
+            lua.create_function(|_, (base, offsets): (LuaPtr, Vec<isize>)| {
+                Ok(MemoryUtils::resolve_chain_ce(base.to_usize(), &offsets)
+                    .map(|ptr| LuaPtr::new(ptr as u64)))
+            })?,
+        )?;
+        // 类型化读写，与 LuaPtr 的同名方法一致，读取时不进行符号扩展
+        for &(name, size) in INTEGER_TYPE_SIZE_MAP {
+            memory.set(
+                format!("read_{}", name),
+                lua.create_function(move |lua, ptr: LuaPtr| {
+                    luaptr::read_integer(lua, ptr.to_usize(), size, false).into_lua_err()
+                })?,
+            )?;
+            memory.set(
+                format!("write_{}", name),
+                lua.create_function(move |lua, (ptr, value): (LuaPtr, i64)| {
+                    let bytes = value.to_le_bytes();
+                    luaptr::write_bytes(lua, ptr.to_usize(), &bytes[..size as usize]).into_lua_err()
+                })?,
+            )?;
+        }
+        memory.set(
+            "read_signed",
+            lua.create_function(|lua, (ptr, size): (LuaPtr, u32)| {
+                luaptr::read_integer(lua, ptr.to_usize(), size, true).into_lua_err()
+            })?,
+        )?;
+        memory.set(
+            "read_f32",
+            lua.create_function(|lua, ptr: LuaPtr| {
+                let bytes = luaptr::quick_read_bytes(lua, ptr.to_usize(), 4).into_lua_err()?;
+                Ok(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            })?,
+        )?;
+        memory.set(
+            "read_f64",
+            lua.create_function(|lua, ptr: LuaPtr| {
+                let bytes = luaptr::quick_read_bytes(lua, ptr.to_usize(), 8).into_lua_err()?;
+                Ok(f64::from_le_bytes(bytes))
+            })?,
+        )?;
+        memory.set(
+            "write_f32",
+            lua.create_function(|lua, (ptr, value): (LuaPtr, f32)| {
+                luaptr::write_bytes(lua, ptr.to_usize(), &value.to_le_bytes()).into_lua_err()
+            })?,
+        )?;
+        memory.set(
+            "write_f64",
+            lua.create_function(|lua, (ptr, value): (LuaPtr, f64)| {
+                luaptr::write_bytes(lua, ptr.to_usize(), &value.to_le_bytes()).into_lua_err()
+            })?,
+        )?;
+        memory.set(
+            "read_ptr",
+            lua.create_function(|lua, ptr: LuaPtr| {
+                let bytes = luaptr::quick_read_bytes(lua, ptr.to_usize(), 8).into_lua_err()?;
+                Ok(LuaPtr::new(u64::from_le_bytes(bytes)))
+            })?,
+        )?;
+        memory.set(
+            "read_bytes",
+            lua.create_function(|lua, (ptr, size): (LuaPtr, u32)| {
+                if size == 0 {
+                    return Ok(vec![]);
+                }
+                luaptr::read_bytes(lua, ptr.to_usize(), size).into_lua_err()
+            })?,
+        )?;
+        memory.set(
+            "write_bytes",
+            lua.create_function(|lua, (ptr, bytes): (LuaPtr, Buffer)| {
+                luaptr::write_bytes(lua, ptr.to_usize(), &bytes).into_lua_err()
+            })?,
+        )?;
+        memory.set(
+            "read_string",
+            lua.create_function(
+                |lua, (ptr, max_len, encoding): (LuaPtr, Option<usize>, Option<LuaValue>)| {
+                    let encoding = luaptr::parse_encoding(lua, encoding)?;
+                    luaptr::read_string(lua, ptr.to_usize(), max_len, encoding).into_lua_err()
+                },
+            )?,
+        )?;
+        memory.set(
+            "write_string",
+            lua.create_function(
+                |lua, (ptr, value, encoding): (LuaPtr, String, Option<LuaValue>)| {
+                    let encoding = luaptr::parse_encoding(lua, encoding)?;
+                    luaptr::write_string(lua, ptr.to_usize(), &value, encoding).into_lua_err()
+                },
+            )?,
+        )?;
+        // 比较两段内存，返回是否相同以及第一个不同字节的偏移
+        memory.set(
+            "compare",
+            lua.create_function(|_, (a, b, len): (LuaPtr, LuaPtr, usize)| {
+                let a = read_region(a.to_usize(), len).map_err(|e| e.into_lua_err())?;
+                let b = read_region(b.to_usize(), len).map_err(|e| e.into_lua_err())?;
+                let diff = a.iter().zip(b.iter()).position(|(a, b)| a != b);
+                Ok((diff.is_none(), diff))
+            })?,
+        )?;
+        // 计算内存区域的 CRC-32
+        memory.set(
+            "crc32",
+            lua.create_function(|_, (ptr, len): (LuaPtr, usize)| {
+                let data = read_region(ptr.to_usize(), len).map_err(|e| e.into_lua_err())?;
+                Ok(hash::crc32(&data))
+            })?,
+        )?;
+        // 定期检查内存区域，内容变化时调用回调，返回监视 ID
+        memory.set(
+            "watch",
+            lua.create_function(
+                |lua, (ptr, len, callback, interval_ms): (LuaPtr, usize, LuaFunction, Option<f64>)| {
+                    let data = read_region(ptr.to_usize(), len).map_err(|e| e.into_lua_err())?;
+                    let interval = interval_ms.unwrap_or(DEFAULT_WATCH_INTERVAL_MS).max(0.0);
+
+                    let globals = lua.globals();
+                    let id = globals.get::<Option<i64>>(WATCH_NEXT_ID_KEY)?.unwrap_or(1);
+                    globals.set(WATCH_NEXT_ID_KEY, id + 1)?;
+
+                    let watch = lua.create_table()?;
+                    watch.set("ptr", ptr)?;
+                    watch.set("len", len)?;
+                    watch.set("crc", hash::crc32(&data))?;
+                    watch.set("interval", interval)?;
+                    watch.set("next", now_millis() + interval)?;
+                    watch.set("fn", callback)?;
+                    globals.get::<LuaTable>(WATCHES_KEY)?.set(id, watch)?;
+
+                    Ok(id)
+                },
+            )?,
+        )?;
+        memory.set(
+            "unwatch",
+            lua.create_function(|lua, id: i64| {
+                let watches = lua.globals().get::<LuaTable>(WATCHES_KEY)?;
+                let exists = watches.contains_key(id)?;
+                watches.set(id, LuaNil)?;
+                Ok(exists)
             })?,
         )?;
         // 还原 patch 的内存
@@ -133,6 +486,8 @@ impl LuaModule for MemoryModule {
         registry.set("Memory", memory)?;
 
         lua.globals().set("_patches", lua.create_table()?)?;
+        lua.globals().set(ALLOCATIONS_KEY, lua.create_table()?)?;
+        lua.globals().set(WATCHES_KEY, lua.create_table()?)?;
 
         // AddressRepository
         let repo_table = lua.create_table()?;
@@ -158,6 +513,55 @@ impl LuaModule for MemoryModule {
                 }
             })?,
         )?;
+        // 获取地址，解析失败时返回 nil，下次获取时重试
+        repo_table.set(
+            "get_deferred",
+            lua.create_function(|_, name: String| {
+                let repo = crate::address::AddressRepository::instance();
+                Ok(repo
+                    .get_address_deferred(&name)
+                    .map(|addr| LuaPtr::new(addr as u64)))
+            })?,
+        )?;
+        // 列出所有地址记录及解析状态
+        repo_table.set(
+            "list",
+            lua.create_function(|lua, ()| {
+                let repo = crate::address::AddressRepository::instance();
+                let list = lua.create_table()?;
+                for item in repo.list() {
+                    let entry = lua.create_table()?;
+                    entry.set("name", item.name)?;
+                    if let Some(record) = item.record {
+                        entry.set("pattern", record.pattern)?;
+                        entry.set("offset", record.offset)?;
+                        entry.set("policy", lua.to_value(&record.policy)?)?;
+                    }
+                    match item.status {
+                        AddressStatus::Resolved(addr) => {
+                            entry.set("status", "resolved")?;
+                            entry.set("address", LuaPtr::new(addr as u64))?;
+                        }
+                        AddressStatus::Failed(error) => {
+                            entry.set("status", "failed")?;
+                            entry.set("error", error)?;
+                        }
+                        AddressStatus::Unresolved => entry.set("status", "unresolved")?,
+                    }
+                    list.push(entry)?;
+                }
+                Ok(list)
+            })?,
+        )?;
+        // 丢弃缓存的地址并重新扫描
+        repo_table.set(
+            "rescan",
+            lua.create_function(|_, name: String| {
+                let repo = crate::address::AddressRepository::instance();
+                let addr = repo.rescan(&name).map_err(|e| e.into_lua_err())?;
+                Ok(LuaPtr::new(addr as u64))
+            })?,
+        )?;
         // 向地址记录添加记录项
         repo_table.set(
             "set_record",
@@ -207,6 +611,53 @@ impl LuaModule for MemoryModule {
 }
 
 impl MemoryModule {
+    /// 检查到期的内存监视，内容变化时调用回调
+    pub fn tick_watches(lua: &Lua) -> LuaResult<()> {
+        let Ok(watches) = lua.globals().get::<LuaTable>(WATCHES_KEY) else {
+            return Ok(());
+        };
+        let now = now_millis();
+
+        let mut due_watches = Vec::new();
+        for pair in watches.pairs::<i64, LuaTable>() {
+            let (id, watch) = pair?;
+            if watch.get::<f64>("next")? <= now {
+                due_watches.push((id, watch));
+            }
+        }
+
+        for (id, watch) in due_watches {
+            watch.set("next", now + watch.get::<f64>("interval")?)?;
+
+            let ptr = watch.get::<LuaPtr>("ptr")?;
+            let old_crc = watch.get::<u32>("crc")?;
+            let new_crc = match read_region(ptr.to_usize(), watch.get::<usize>("len")?) {
+                Ok(data) => Some(hash::crc32(&data)),
+                // 内存已不可读，通知后移除
+                Err(_) => {
+                    watches.set(id, LuaNil)?;
+                    None
+                }
+            };
+            if new_crc == Some(old_crc) {
+                continue;
+            }
+            if let Some(new_crc) = new_crc {
+                watch.set("crc", new_crc)?;
+            }
+
+            let callback = watch.get::<LuaFunction>("fn")?;
+            if let Err(e) = callback.call::<()>((ptr, old_crc, new_crc)) {
+                let name = lua.globals().get::<String>("_name").unwrap_or_default();
+                let err_msg = format!("memory watch callback in LuaVM({}) error:\n{}", name, e);
+                crate::error::set_last_error(err_msg.clone());
+                log::error!("{}", err_msg);
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn restore_all_patches(lua: &Lua) -> Result<()> {
         let patcher = MemoryPatchManager::instance();
 
@@ -219,10 +670,80 @@ impl MemoryModule {
         Ok(())
     }
 
-    pub fn free_all_allocations() {
+    /// 释放虚拟机分配的所有内存
+    pub fn free_all_allocations(lua: &Lua) -> Result<()> {
+        let Some(allocations) = lua.globals().get::<Option<LuaTable>>(ALLOCATIONS_KEY)? else {
+            return Ok(());
+        };
+
         let alloc_manager = MemoryAllocManager::instance();
-        let mut allocs = alloc_manager.allocs.lock();
-        allocs.clear();
+        for pair in allocations.pairs::<i64, bool>() {
+            let (address, _) = pair?;
+            alloc_manager.free(address as usize);
+        }
+
+        Ok(())
+    }
+}
+
+/// 记录虚拟机分配的内存，脚本卸载时释放
+fn track_allocation(lua: &Lua, address: usize) -> LuaResult<LuaPtr> {
+    let allocations = lua.globals().get::<LuaTable>(ALLOCATIONS_KEY)?;
+    allocations.set(address as i64, true)?;
+    Ok(LuaPtr::new(address as u64))
+}
+
+fn instruction_to_table(lua: &Lua, instruction: &DecodedInstruction) -> LuaResult<LuaTable> {
+    let table = lua.create_table()?;
+    table.set("address", LuaPtr::new(instruction.address as u64))?;
+    table.set("size", instruction.len())?;
+    table.set("bytes", instruction.bytes.clone())?;
+    table.set("mnemonic", instruction.mnemonic.as_str())?;
+    table.set("operands", instruction.operands.as_str())?;
+    table.set("text", instruction.text())?;
+    table.set("flow", instruction.flow.as_ref())?;
+    table.set(
+        "target",
+        instruction
+            .branch_target
+            .map(|target| LuaPtr::new(target as u64)),
+    )?;
+    table.set(
+        "memory_target",
+        instruction
+            .memory_target
+            .map(|target| LuaPtr::new(target as u64)),
+    )?;
+    Ok(table)
+}
+
+/// 从地址开始查找满足条件的指令，未找到时返回 nil
+fn find_next_instruction(
+    lua: &Lua,
+    ptr: LuaPtr,
+    max_count: Option<usize>,
+    predicate: impl Fn(&DecodedInstruction) -> bool,
+) -> LuaResult<Option<LuaTable>> {
+    let instruction = MemoryUtils::find_next_instruction(
+        ptr.to_usize(),
+        max_count.unwrap_or(DEFAULT_FIND_MAX_COUNT),
+        predicate,
+    )
+    .map_err(|e| Error::from(e).into_lua_err())?;
+    instruction
+        .map(|instruction| instruction_to_table(lua, &instruction))
+        .transpose()
+}
+
+/// 解析内存保护属性，返回是否可执行
+fn parse_protection(protection: Option<&str>, default_executable: bool) -> LuaResult<bool> {
+    match protection {
+        None => Ok(default_executable),
+        Some("rw") => Ok(false),
+        Some("rwx") => Ok(true),
+        Some(other) => {
+            Err(Error::InvalidValue("\"rw\" or \"rwx\"", other.to_string()).into_lua_err())
+        }
     }
 }
 
@@ -234,9 +755,73 @@ fn pattern_scan_first(address: usize, size: usize, pattern: &str) -> Result<usiz
     Ok(MemoryUtils::scan_first(address, size, pattern)?)
 }
 
+/// 读取内存区域，检查首尾两端的读权限
+fn read_region(address: usize, len: usize) -> Result<Vec<u8>> {
+    if len > 1 {
+        MemoryUtils::check_permission_read(address + len - 1)?;
+    }
+    Ok(MemoryUtils::read(address, len, true)?)
+}
+
+fn now_millis() -> f64 {
+    time::monotonic() * 1000.0
+}
+
+/// 计算从 `address` 开始 `count` 条完整指令的总长度
+fn instructions_size(address: usize, count: usize) -> Result<usize> {
+    if count == 0 {
+        return Err(Error::InvalidValue(
+            "instruction count greater than 0",
+            count.to_string(),
+        ));
+    }
+    MemoryUtils::check_permission_execute(address)?;
+
+    // 仅用于解码，不会写入
+    let mut scratch = [0u8; 64];
+    let mut writer = X86InstructionWriter::new(scratch.as_mut_ptr() as u64);
+    let mut relocator = X86Relocator::new(address as u64, &mut writer);
+
+    let mut size = 0;
+    for index in 0..count {
+        let (total, _) = relocator.read_one();
+        if total == 0 {
+            return Err(Error::InvalidValue(
+                "decodable instruction",
+                format!("0x{:x} (instruction #{})", address + size, index + 1),
+            ));
+        }
+        size = total as usize;
+        // 跳转或返回指令之后的字节不一定是指令
+        if relocator.eoi() && index + 1 < count {
+            return Err(Error::InvalidValue(
+                "instructions within one basic block",
+                format!(
+                    "block ends at 0x{:x} after {} instructions",
+                    address + size,
+                    index + 1
+                ),
+            ));
+        }
+    }
+
+    Ok(size)
+}
+
 fn parse_record_args(lua: &Lua, args: mlua::Variadic<LuaValue>) -> Result<AddressRecord> {
     if args.len() == 1 {
-        Ok(lua.from_value::<AddressRecord>(args.into_iter().next().unwrap())?)
+        let value = args.into_iter().next().unwrap();
+        // 校验函数不能反序列化，单独读取
+        let validator = match &value {
+            LuaValue::Table(table) => table.get::<Option<LuaFunction>>("validator")?,
+            _ => None,
+        };
+        let options = LuaDeserializeOptions::new().deny_unsupported_types(false);
+        let mut record = lua.from_value_with::<AddressRecord>(value, options)?;
+        if let Some(validator) = validator {
+            record.validator = Some(lua_validator(lua, validator)?);
+        }
+        Ok(record)
     } else if args.len() >= 2 {
         let mut iter = args.into_iter();
         let name = iter
@@ -258,6 +843,7 @@ fn parse_record_args(lua: &Lua, args: mlua::Variadic<LuaValue>) -> Result<Addres
             name,
             pattern,
             offset,
+            ..Default::default()
         })
     } else {
         Err(Error::InvalidValue(
@@ -267,8 +853,37 @@ fn parse_record_args(lua: &Lua, args: mlua::Variadic<LuaValue>) -> Result<Addres
     }
 }
 
+/// 以 Lua 函数作为地址校验回调，脚本卸载后拒绝所有地址
+fn lua_validator(lua: &Lua, validator: LuaFunction) -> Result<AddressValidator> {
+    let Some(luavm) = LuaVMManager::instance().get_vm_by_lua(lua) else {
+        return Err(Error::LuaVMNotFound);
+    };
+    let vm_ref = Arc::downgrade(&luavm);
+
+    Ok(AddressValidator(Arc::new(move |address| {
+        // 校验期间保持虚拟机存活
+        let Some(_luavm) = vm_ref.upgrade() else {
+            log::debug!(
+                "Address 0x{:x} rejected, the validating script is unloaded",
+                address
+            );
+            return false;
+        };
+        let mut valid = false;
+        let result = LuaVMManager::instance().run_with_lock(|_| {
+            valid = validator.call::<bool>(LuaPtr::new(address as u64))?;
+            Ok(())
+        });
+        if let Err(e) = result {
+            log::error!("Address validator error: {}", e);
+            return false;
+        }
+        valid
+    })))
+}
+
 #[derive(Default)]
-struct MemoryPatchManager {
+pub(super) struct MemoryPatchManager {
     patches: Mutex<HashMap<usize, MemoryPatch>>,
 }
 
@@ -315,6 +930,39 @@ impl MemoryPatchManager {
         Ok(())
     }
 
+    /// 在可写状态下调用 `f` 修改代码，失败时还原
+    pub fn new_patch_code<F>(&self, address: usize, size: usize, f: F) -> Result<()>
+    where
+        F: FnOnce() -> Result<()>,
+    {
+        if self.is_patch_exists(address, size) {
+            return Err(Error::PatchAlreadyExists(address));
+        }
+
+        let backup = MemoryUtils::read(address, size, true)?;
+        {
+            let _guard = MemoryUtils::unprotect(address, size)?;
+            if let Err(e) = f() {
+                unsafe {
+                    std::ptr::copy_nonoverlapping(backup.as_ptr(), address as *mut u8, size);
+                }
+                return Err(e);
+            }
+        }
+        MemoryUtils::flush_instruction_cache(address, size)?;
+
+        self.patches.lock().insert(
+            address,
+            MemoryPatch {
+                address,
+                size,
+                backup,
+            },
+        );
+
+        Ok(())
+    }
+
     pub fn restore_patch(&self, address: usize) -> Result<bool> {
         if let Some(patch) = self.patches.lock().remove(&address) {
             MemoryUtils::patch(patch.address, &patch.backup)?;
@@ -349,9 +997,26 @@ struct MemoryPatch {
     backup: Vec<u8>,
 }
 
+enum Allocation {
+    /// 堆内存
+    Heap(#[allow(dead_code)] Vec<u8>),
+    /// VirtualAlloc 分配的页内存
+    Virtual(usize),
+}
+
+impl Drop for Allocation {
+    fn drop(&mut self) {
+        if let Allocation::Virtual(address) = self
+            && let Err(e) = MemoryUtils::free_executable(*address)
+        {
+            log::error!("Failed to free memory at 0x{:x}: {}", address, e);
+        }
+    }
+}
+
 #[derive(Default)]
 struct MemoryAllocManager {
-    allocs: Mutex<HashMap<usize, Vec<u8>>>,
+    allocs: Mutex<HashMap<usize, Allocation>>,
 }
 
 impl MemoryAllocManager {
@@ -368,7 +1033,19 @@ impl MemoryAllocManager {
 
         let mut buffer = vec![0u8; size];
         let ptr = buffer.as_mut_ptr() as usize;
-        self.allocs.lock().insert(ptr, buffer);
+        self.allocs.lock().insert(ptr, Allocation::Heap(buffer));
+        Ok(ptr)
+    }
+
+    pub fn alloc(&self, size: usize, executable: bool) -> Result<usize> {
+        let ptr = MemoryUtils::alloc(size, executable)?;
+        self.allocs.lock().insert(ptr, Allocation::Virtual(ptr));
+        Ok(ptr)
+    }
+
+    pub fn alloc_near(&self, address: usize, size: usize, executable: bool) -> Result<usize> {
+        let ptr = MemoryUtils::alloc_near(address, size, executable)?;
+        self.allocs.lock().insert(ptr, Allocation::Virtual(ptr));
         Ok(ptr)
     }
 
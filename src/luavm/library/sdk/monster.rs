@@ -1,9 +1,13 @@
 use std::ffi::c_void;
 
+use mlua::prelude::*;
 use mlua::{Lua, Table};
 
+use crate::game::monster;
+use crate::luavm::capability::{self, UnsafeCapability};
 use crate::luavm::library::LuaModule;
 use crate::luavm::library::sdk::luaptr::LuaPtr;
+use crate::luavm::safety::SafetyPolicy;
 
 pub struct MonsterModule;
 
@@ -15,6 +19,31 @@ impl LuaModule for MonsterModule {
             "list",
             lua.create_function(|_, ()| Ok(crate::game::monster::get_monsters()))?,
         )?;
+        // 所有已生成怪物的状态
+        monster_table.set(
+            "all",
+            lua.create_function(|lua, ()| {
+                let infos = monster::get_monster_infos();
+                let list = lua.create_table_with_capacity(infos.len(), 0)?;
+                for info in infos {
+                    let entry = lua.create_table()?;
+                    entry.set("ptr", LuaPtr::new(info.entry.address as u64))?;
+                    entry.set("id", info.entry.id)?;
+                    entry.set("species", info.entry.type_id)?;
+                    entry.set("sub_id", info.entry.type_sub_id)?;
+                    entry.set("hp", info.health)?;
+                    entry.set("max_hp", info.health_max)?;
+                    entry.set("rage", info.rage)?;
+                    let position = lua.create_table()?;
+                    position.set("x", info.position.x)?;
+                    position.set("y", info.position.y)?;
+                    position.set("z", info.position.z)?;
+                    entry.set("position", position)?;
+                    list.push(entry)?;
+                }
+                Ok(list)
+            })?,
+        )?;
         monster_table.set(
             "contains",
             lua.create_function(|_, monster: LuaPtr| {
@@ -24,6 +53,59 @@ impl LuaModule for MonsterModule {
             })?,
         )?;
 
+        // 以下接口会修改怪物状态，需要启用不安全模式
+        monster_table.set(
+            "set_rage",
+            lua.create_function(|lua, (monster, value): (LuaPtr, f32)| {
+                capability::ensure_capability(
+                    lua,
+                    UnsafeCapability::GameControl,
+                    "Monster.set_rage",
+                )?;
+                SafetyPolicy::check_lua(lua, "Monster.set_rage")?;
+                monster::set_rage(monster.to_usize() as *const c_void, value).into_lua_err()
+            })?,
+        )?;
+        monster_table.set(
+            "set_stamina",
+            lua.create_function(|lua, (monster, value): (LuaPtr, f32)| {
+                capability::ensure_capability(
+                    lua,
+                    UnsafeCapability::GameControl,
+                    "Monster.set_stamina",
+                )?;
+                SafetyPolicy::check_lua(lua, "Monster.set_stamina")?;
+                monster::set_stamina(monster.to_usize() as *const c_void, value).into_lua_err()
+            })?,
+        )?;
+        monster_table.set(
+            "set_target",
+            lua.create_function(|lua, (monster, target): (LuaPtr, Option<LuaPtr>)| {
+                capability::ensure_capability(
+                    lua,
+                    UnsafeCapability::GameControl,
+                    "Monster.set_target",
+                )?;
+                SafetyPolicy::check_lua(lua, "Monster.set_target")?;
+                let target = target.map(|t| t.to_usize()).unwrap_or(0);
+                monster::set_target(monster.to_usize() as *const c_void, target as *const c_void)
+                    .into_lua_err()
+            })?,
+        )?;
+        monster_table.set(
+            "enqueue_action",
+            lua.create_function(|lua, (monster, action_id): (LuaPtr, i32)| {
+                capability::ensure_capability(
+                    lua,
+                    UnsafeCapability::GameControl,
+                    "Monster.enqueue_action",
+                )?;
+                SafetyPolicy::check_lua(lua, "Monster.enqueue_action")?;
+                monster::enqueue_action(monster.to_usize() as *const c_void, action_id)
+                    .into_lua_err()
+            })?,
+        )?;
+
         registry.set("Monster", monster_table)?;
 
         Ok(())
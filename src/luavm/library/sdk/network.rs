@@ -0,0 +1,28 @@
+use mlua::prelude::*;
+
+use crate::{game::network, luavm::library::LuaModule};
+
+pub struct NetworkModule;
+
+impl LuaModule for NetworkModule {
+    fn register_library(lua: &mlua::Lua, registry: &mlua::Table) -> mlua::Result<()> {
+        let network_table = lua.create_table()?;
+
+        // 获取当前会话信息，未进入会话时返回 nil
+        network_table.set(
+            "info",
+            lua.create_function(|lua, ()| match network::get_session_info() {
+                Some(info) => lua.to_value(&info),
+                None => Ok(LuaValue::Nil),
+            })?,
+        )?;
+        network_table.set(
+            "is_online",
+            lua.create_function(|_, ()| Ok(network::is_online()))?,
+        )?;
+
+        registry.set("Network", network_table)?;
+
+        Ok(())
+    }
+}
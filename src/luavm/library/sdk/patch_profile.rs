@@ -0,0 +1,274 @@
+//! 补丁方案
+//!
+//! 补丁方案是一组保存在配置中的内存补丁，可在界面或脚本中整体启用和还原，
+//! 常见的二进制修改（如解除帧率限制）无需单独编写脚本。
+
+use std::{collections::HashMap, sync::LazyLock};
+
+use mlua::prelude::*;
+use parking_lot::Mutex;
+
+use super::memory::MemoryPatchManager;
+use crate::{
+    address::{AddressRecord, AddressRepository},
+    config::{Config, PatchEntry, PatchProfile},
+    error::{Error, Result},
+    luavm::{library::LuaModule, safety::SafetyPolicy},
+    utility,
+};
+
+pub struct PatchProfileModule;
+
+impl LuaModule for PatchProfileModule {
+    fn register_library(lua: &mlua::Lua, registry: &mlua::Table) -> mlua::Result<()> {
+        let profile_table = lua.create_table()?;
+        // 定义补丁方案并保存到配置中
+        profile_table.set(
+            "define",
+            lua.create_function(
+                |lua, (name, patches, revisions): (String, LuaValue, Option<Vec<u32>>)| {
+                    let patches: Vec<PatchEntry> = lua.from_value(patches)?;
+                    PatchProfileManager::instance()
+                        .define(&name, patches, revisions.unwrap_or_default())
+                        .map_err(|e| e.into_lua_err())
+                },
+            )?,
+        )?;
+        profile_table.set(
+            "enable",
+            lua.create_function(|lua, name: String| {
+                SafetyPolicy::check_lua(lua, "PatchProfile.enable")?;
+                PatchProfileManager::instance()
+                    .set_enabled(&name, true)
+                    .map_err(|e| e.into_lua_err())
+            })?,
+        )?;
+        profile_table.set(
+            "disable",
+            lua.create_function(|_, name: String| {
+                PatchProfileManager::instance()
+                    .set_enabled(&name, false)
+                    .map_err(|e| e.into_lua_err())
+            })?,
+        )?;
+        profile_table.set(
+            "is_applied",
+            lua.create_function(|_, name: String| {
+                Ok(PatchProfileManager::instance().is_applied(&name))
+            })?,
+        )?;
+        profile_table.set(
+            "list",
+            lua.create_function(|_, ()| {
+                Ok(PatchProfileManager::instance()
+                    .profiles()
+                    .into_iter()
+                    .map(|profile| profile.name)
+                    .collect::<Vec<_>>())
+            })?,
+        )?;
+
+        registry.set("PatchProfile", profile_table)?;
+
+        Ok(())
+    }
+}
+
+/// 补丁方案状态
+pub struct PatchProfileInfo {
+    pub name: String,
+    pub enabled: bool,
+    pub applied: bool,
+    /// 当前游戏版本不匹配时的错误信息
+    pub mismatch: Option<String>,
+}
+
+#[derive(Default)]
+pub struct PatchProfileManager {
+    /// 方案名 -> 已应用的补丁地址
+    applied: Mutex<HashMap<String, Vec<usize>>>,
+}
+
+impl PatchProfileManager {
+    pub fn instance() -> &'static Self {
+        static INSTANCE: LazyLock<PatchProfileManager> =
+            LazyLock::new(PatchProfileManager::default);
+        &INSTANCE
+    }
+
+    /// 应用所有已启用的方案
+    pub fn apply_enabled_profiles(&self) {
+        let names = Config::global()
+            .patch_profiles
+            .iter()
+            .filter(|p| p.enabled)
+            .map(|p| p.name.clone())
+            .collect::<Vec<_>>();
+        for name in names {
+            match self.apply(&name) {
+                Ok(()) => {}
+                Err(e @ Error::GameRevisionMismatch(..)) => {
+                    log::warn!("Patch profile '{}' skipped: {}", name, e.log())
+                }
+                Err(e) => log::error!("Failed to apply patch profile '{}': {}", name, e.log()),
+            }
+        }
+    }
+
+    /// 所有方案的状态
+    pub fn profiles(&self) -> Vec<PatchProfileInfo> {
+        let applied = self.applied.lock();
+        Config::global()
+            .patch_profiles
+            .iter()
+            .map(|p| PatchProfileInfo {
+                name: p.name.clone(),
+                enabled: p.enabled,
+                applied: applied.contains_key(&p.name),
+                mismatch: utility::check_game_revision(&p.name, &p.revisions)
+                    .err()
+                    .map(|e| e.to_string()),
+            })
+            .collect()
+    }
+
+    pub fn is_applied(&self, name: &str) -> bool {
+        self.applied.lock().contains_key(name)
+    }
+
+    /// 定义方案，已存在时替换补丁内容并保留启用状态
+    pub fn define(&self, name: &str, patches: Vec<PatchEntry>, revisions: Vec<u32>) -> Result<()> {
+        // 先校验字节格式
+        for entry in patches.iter() {
+            parse_hex_bytes(&entry.bytes)?;
+        }
+
+        let was_applied = self.restore(name)?;
+        {
+            let mut config = Config::global_mut();
+            match config.patch_profiles.iter_mut().find(|p| p.name == name) {
+                Some(profile) => {
+                    profile.patches = patches;
+                    profile.revisions = revisions;
+                }
+                None => config.patch_profiles.push(PatchProfile {
+                    name: name.to_string(),
+                    enabled: false,
+                    revisions,
+                    patches,
+                }),
+            }
+        }
+        if was_applied {
+            self.apply(name)?;
+        }
+
+        Ok(())
+    }
+
+    /// 启用或禁用方案，并保存到配置
+    pub fn set_enabled(&self, name: &str, enabled: bool) -> Result<()> {
+        if enabled {
+            self.apply(name)?;
+        } else {
+            self.restore(name)?;
+        }
+
+        let mut config = Config::global_mut();
+        if let Some(profile) = config.patch_profiles.iter_mut().find(|p| p.name == name) {
+            profile.enabled = enabled;
+        }
+        Ok(())
+    }
+
+    /// 应用方案中的所有补丁，任一补丁失败时还原已应用的部分
+    pub fn apply(&self, name: &str) -> Result<()> {
+        if self.is_applied(name) {
+            return Ok(());
+        }
+
+        let profile = Config::global()
+            .patch_profiles
+            .iter()
+            .find(|p| p.name == name)
+            .cloned()
+            .ok_or_else(|| Error::PatchProfileNotFound(name.to_string()))?;
+        // 游戏更新后旧补丁可能破坏代码，版本不匹配时跳过
+        utility::check_game_revision(name, &profile.revisions)?;
+
+        // 先解析全部地址，避免部分应用
+        let mut resolved = Vec::with_capacity(profile.patches.len());
+        for (index, entry) in profile.patches.iter().enumerate() {
+            let address = resolve_address(name, index, entry)?;
+            let bytes = parse_hex_bytes(&entry.bytes)?;
+            resolved.push((address, bytes));
+        }
+
+        let manager = MemoryPatchManager::instance();
+        let mut applied = Vec::with_capacity(resolved.len());
+        for (address, bytes) in resolved {
+            if let Err(e) = manager.new_patch(address, &bytes) {
+                for address in applied {
+                    let _ = manager.restore_patch(address);
+                }
+                return Err(e);
+            }
+            applied.push(address);
+        }
+
+        log::info!("Patch profile '{}' applied", name);
+        self.applied.lock().insert(name.to_string(), applied);
+        Ok(())
+    }
+
+    /// 还原方案，返回方案此前是否已应用
+    pub fn restore(&self, name: &str) -> Result<bool> {
+        let Some(addresses) = self.applied.lock().remove(name) else {
+            return Ok(false);
+        };
+
+        let manager = MemoryPatchManager::instance();
+        for address in addresses {
+            manager.restore_patch(address)?;
+        }
+
+        log::info!("Patch profile '{}' restored", name);
+        Ok(true)
+    }
+}
+
+fn resolve_address(profile_name: &str, index: usize, entry: &PatchEntry) -> Result<usize> {
+    let repository = AddressRepository::instance();
+    if let Some(record) = &entry.record {
+        let address = repository.get_address(record)?;
+        return Ok((address as isize + entry.offset) as usize);
+    }
+    if let Some(pattern) = &entry.pattern {
+        let record_name = format!("patch_profile:{}:{}", profile_name, index);
+        repository.set_record(AddressRecord {
+            name: record_name.clone(),
+            pattern: pattern.clone(),
+            offset: entry.offset,
+            ..Default::default()
+        });
+        return repository.get_address(&record_name);
+    }
+
+    Err(Error::InvalidValue(
+        "patch entry with record or pattern",
+        format!("{}[{}]", profile_name, index),
+    ))
+}
+
+/// 解析十六进制字节字符串，如 "90 90 EB"
+fn parse_hex_bytes(s: &str) -> Result<Vec<u8>> {
+    let bytes = s
+        .split_whitespace()
+        .map(|b| u8::from_str_radix(b, 16))
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|_| Error::InvalidValue("hex bytes", s.to_string()))?;
+    if bytes.is_empty() {
+        return Err(Error::InvalidValue("hex bytes", s.to_string()));
+    }
+    Ok(bytes)
+}
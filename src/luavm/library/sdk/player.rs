@@ -0,0 +1,108 @@
+use mlua::prelude::*;
+
+use crate::{
+    game::{mt_type::GameObject, player},
+    luavm::{
+        capability::{self, UnsafeCapability},
+        library::LuaModule,
+        safety::SafetyPolicy,
+    },
+};
+
+use super::{luaptr::LuaPtr, spawn::parse_position};
+
+pub struct PlayerModule;
+
+impl LuaModule for PlayerModule {
+    fn register_library(lua: &mlua::Lua, registry: &mlua::Table) -> mlua::Result<()> {
+        let player_table = lua.create_table()?;
+
+        // 以下接口在玩家不存在时返回 nil
+        player_table.set(
+            "get",
+            lua.create_function(|_, ()| {
+                Ok(player::get_player().map(|player| LuaPtr::new(player.as_address() as u64)))
+            })?,
+        )?;
+        player_table.set(
+            "position",
+            lua.create_function(|lua, ()| {
+                let Some(position) = player::position() else {
+                    return Ok(None);
+                };
+                let table = lua.create_table()?;
+                table.set("x", position.x)?;
+                table.set("y", position.y)?;
+                table.set("z", position.z)?;
+                Ok(Some(table))
+            })?,
+        )?;
+        // 返回当前值和最大值
+        player_table.set(
+            "health",
+            lua.create_function(|_, ()| Ok(player::health().unzip()))?,
+        )?;
+        player_table.set(
+            "stamina",
+            lua.create_function(|_, ()| Ok(player::stamina().unzip()))?,
+        )?;
+        player_table.set(
+            "weapon",
+            lua.create_function(|lua, ()| {
+                let Some(weapon) = player::weapon() else {
+                    return Ok(None);
+                };
+                let table = lua.create_table()?;
+                table.set("type", weapon.weapon_type)?;
+                table.set("id", weapon.weapon_id)?;
+                Ok(Some(table))
+            })?,
+        )?;
+        player_table.set(
+            "action_id",
+            lua.create_function(|_, ()| Ok(player::action_id()))?,
+        )?;
+
+        // 以下接口会修改玩家状态，需要启用不安全模式
+        player_table.set(
+            "set_position",
+            lua.create_function(|lua, position: LuaTable| {
+                capability::ensure_capability(
+                    lua,
+                    UnsafeCapability::GameControl,
+                    "Player.set_position",
+                )?;
+                SafetyPolicy::check_lua(lua, "Player.set_position")?;
+                player::set_position(parse_position(&position)?).into_lua_err()
+            })?,
+        )?;
+        player_table.set(
+            "set_health",
+            lua.create_function(|lua, value: f32| {
+                capability::ensure_capability(
+                    lua,
+                    UnsafeCapability::GameControl,
+                    "Player.set_health",
+                )?;
+                SafetyPolicy::check_lua(lua, "Player.set_health")?;
+                player::set_health(value).into_lua_err()
+            })?,
+        )?;
+        player_table.set(
+            "set_stamina",
+            lua.create_function(|lua, value: f32| {
+                capability::ensure_capability(
+                    lua,
+                    UnsafeCapability::GameControl,
+                    "Player.set_stamina",
+                )?;
+                SafetyPolicy::check_lua(lua, "Player.set_stamina")?;
+                player::set_stamina(value).into_lua_err()
+            })?,
+        )?;
+
+        registry.set("Player", player_table)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use mlua::prelude::*;
+
+use crate::{game::quest, luavm::library::LuaModule};
+
+pub struct QuestModule;
+
+impl LuaModule for QuestModule {
+    fn register_library(lua: &mlua::Lua, registry: &mlua::Table) -> mlua::Result<()> {
+        let quest_table = lua.create_table()?;
+
+        // 当前任务 ID，不在任务中时返回 nil
+        quest_table.set("id", lua.create_function(|_, ()| Ok(quest::quest_id()))?)?;
+        quest_table.set(
+            "state",
+            lua.create_function(|_, ()| Ok(quest::state().as_ref().to_string()))?,
+        )?;
+        // 剩余时间（秒），不在任务中时返回 nil
+        quest_table.set(
+            "remaining_time",
+            lua.create_function(|_, ()| Ok(quest::remaining_time()))?,
+        )?;
+
+        registry.set("Quest", quest_table)?;
+
+        Ok(())
+    }
+}
@@ -5,7 +5,10 @@ use std::{collections::HashMap, sync::LazyLock};
 use mlua::prelude::*;
 use parking_lot::Mutex;
 
-use crate::luavm::library::LuaModule;
+use crate::luavm::library::{
+    LuaModule,
+    userdata_serde::{decode_userdata, encode_userdata},
+};
 
 pub struct ShardStateModule;
 
@@ -98,14 +101,15 @@ impl FromLua for LuaValueStateless {
             LuaValue::Integer(v) => Ok(LuaValueStateless::Integer(v)),
             LuaValue::Number(v) => Ok(LuaValueStateless::Number(v)),
             LuaValue::String(v) => Ok(LuaValueStateless::String(v.to_string_lossy().to_string())),
-            LuaValue::Table(t) => {
-                let val: serde_json::Value = lua.from_value(LuaValue::Table(t))?;
+            // userdata 以带类型标记的 table 形式保存
+            value @ (LuaValue::Table(_) | LuaValue::UserData(_)) => {
+                let val: serde_json::Value = lua.from_value(encode_userdata(lua, value)?)?;
                 Ok(LuaValueStateless::Table(val))
             },
             other => Err(LuaError::FromLuaConversionError {
                 from: other.type_name(),
                 to: "LuaValueStateless".to_string(),
-                message: Some("Cannot convert LuaValue to LuaValueStateless. Only Nil, Boolean, Integer, Number, String, and Table are supported.".to_string()),
+                message: Some("Cannot convert LuaValue to LuaValueStateless. Only Nil, Boolean, Integer, Number, String, Table, LuaPtr and ManagedString are supported.".to_string()),
             }),
         }
     }
@@ -119,7 +123,7 @@ impl IntoLua for LuaValueStateless {
             LuaValueStateless::Integer(v) => Ok(LuaValue::Integer(v)),
             LuaValueStateless::Number(v) => Ok(LuaValue::Number(v)),
             LuaValueStateless::String(v) => Ok(LuaValue::String(lua.create_string(&v)?)),
-            LuaValueStateless::Table(v) => Ok(lua.to_value(&v)?),
+            LuaValueStateless::Table(v) => decode_userdata(lua, lua.to_value(&v)?),
         }
     }
 }
@@ -0,0 +1,100 @@
+//! 等待单例
+//!
+//! 单例在 mhMain 构造后解析，之后新构造的单例在下一帧解析。
+//! 等待中的回调保存在虚拟机的 `_singleton_waits` 表中，单例解析后调用并移除。
+//! 查询已解析的单例使用 `sdk.get_singleton` 和 `sdk.singletons`。
+
+use mlua::prelude::*;
+
+use super::luaptr::LuaPtr;
+use crate::{game::singleton::SingletonManager, luavm::library::LuaModule};
+
+const WAITS_KEY: &str = "_singleton_waits";
+
+pub struct SingletonsModule;
+
+impl LuaModule for SingletonsModule {
+    fn register_library(lua: &mlua::Lua, registry: &mlua::Table) -> mlua::Result<()> {
+        let singletons_table = lua.create_table()?;
+        // 单例可用时调用回调，已可用时立即调用。返回是否已立即调用
+        singletons_table.set(
+            "wait",
+            lua.create_function(|lua, (name, callback): (String, LuaFunction)| {
+                if let Some(addr) = SingletonManager::instance().get_address(&name) {
+                    callback.call::<()>(LuaPtr::new(addr as u64))?;
+                    return Ok(true);
+                }
+
+                let waits = waits_table(lua)?;
+                let callbacks = match waits.get::<Option<LuaTable>>(name.as_str())? {
+                    Some(callbacks) => callbacks,
+                    None => {
+                        let callbacks = lua.create_table()?;
+                        waits.set(name.as_str(), &callbacks)?;
+                        callbacks
+                    }
+                };
+                callbacks.push(callback)?;
+                Ok(false)
+            })?,
+        )?;
+        // 设置新单例解析回调
+        singletons_table.set(
+            "on_registered",
+            lua.create_function(|lua, fun: LuaFunction| {
+                lua.globals().set("_on_singleton_registered", fun)?;
+                Ok(())
+            })?,
+        )?;
+
+        registry.set("Singletons", singletons_table)?;
+
+        Ok(())
+    }
+}
+
+impl SingletonsModule {
+    /// 单例解析后调用等待中的回调和 on_registered 回调
+    ///
+    /// 单个回调出错不影响其他回调，返回第一个错误，其余错误直接记录到日志。
+    pub fn dispatch_registered(lua: &Lua, name: &str, address: usize) -> LuaResult<()> {
+        let ptr = LuaPtr::new(address as u64);
+        let globals = lua.globals();
+        let mut first_error = None;
+        let mut record = |result: LuaResult<()>| {
+            let Err(e) = result else {
+                return;
+            };
+            if first_error.is_none() {
+                first_error = Some(e);
+            } else {
+                log::error!("Singleton '{}' callback error: {}", name, e);
+            }
+        };
+
+        if let Some(waits) = globals.get::<Option<LuaTable>>(WAITS_KEY)?
+            && let Some(callbacks) = waits.get::<Option<LuaTable>>(name)?
+        {
+            waits.set(name, LuaNil)?;
+            for callback in callbacks.sequence_values::<LuaFunction>() {
+                record(callback.and_then(|callback| callback.call::<()>(ptr)));
+            }
+        }
+
+        if let Ok(fun) = globals.get::<LuaFunction>("_on_singleton_registered") {
+            record(fun.call::<()>((name, ptr)));
+        }
+
+        first_error.map_or(Ok(()), Err)
+    }
+}
+
+fn waits_table(lua: &Lua) -> LuaResult<LuaTable> {
+    let globals = lua.globals();
+    if let Some(waits) = globals.get::<Option<LuaTable>>(WAITS_KEY)? {
+        return Ok(waits);
+    }
+    let waits = lua.create_table()?;
+    globals.set(WAITS_KEY, &waits)?;
+    Ok(waits)
+}
@@ -0,0 +1,71 @@
+use mlua::prelude::*;
+
+use crate::{
+    game::{mt_type::MtVector3, spawn},
+    luavm::{
+        capability::{self, UnsafeCapability},
+        library::LuaModule,
+        safety::SafetyPolicy,
+    },
+};
+
+use super::luaptr::LuaPtr;
+
+pub struct SpawnModule;
+
+impl LuaModule for SpawnModule {
+    fn register_library(lua: &mlua::Lua, registry: &mlua::Table) -> mlua::Result<()> {
+        let spawn_table = lua.create_table()?;
+
+        // 掉落物品
+        spawn_table.set(
+            "item",
+            lua.create_function(
+                |lua, (item_id, position, count): (i32, LuaTable, Option<i32>)| {
+                    capability::ensure_capability(
+                        lua,
+                        UnsafeCapability::GameControl,
+                        "Spawn.item",
+                    )?;
+                    SafetyPolicy::check_lua(lua, "Spawn.item")?;
+                    let position = parse_position(&position)?;
+                    let object =
+                        spawn::spawn_item(item_id, count.unwrap_or(1), position).into_lua_err()?;
+                    Ok(LuaPtr::new(object as u64))
+                },
+            )?,
+        )?;
+        // 生成环境生物
+        spawn_table.set(
+            "endemic_life",
+            lua.create_function(|lua, (em_id, position): (i32, LuaTable)| {
+                capability::ensure_capability(
+                    lua,
+                    UnsafeCapability::GameControl,
+                    "Spawn.endemic_life",
+                )?;
+                SafetyPolicy::check_lua(lua, "Spawn.endemic_life")?;
+                let position = parse_position(&position)?;
+                let object = spawn::spawn_endemic_life(em_id, position).into_lua_err()?;
+                Ok(LuaPtr::new(object as u64))
+            })?,
+        )?;
+
+        registry.set("Spawn", spawn_table)?;
+
+        Ok(())
+    }
+}
+
+/// 解析坐标，支持 `{x=, y=, z=}` 和 `{x, y, z}` 两种形式
+pub(super) fn parse_position(table: &LuaTable) -> LuaResult<MtVector3> {
+    if table.contains_key("x")? {
+        return Ok(MtVector3::new(
+            table.get("x")?,
+            table.get("y")?,
+            table.get("z")?,
+        ));
+    }
+
+    Ok(MtVector3::new(table.get(1)?, table.get(2)?, table.get(3)?))
+}
@@ -1,15 +1,16 @@
 //! 字符串模块，用于FFI调用。
 
-use std::ffi::CStr;
+use std::{collections::HashMap, ffi::CStr, sync::LazyLock};
 
 use mlua::prelude::*;
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 
 use crate::error::{Error, Result};
 use crate::luavm::library::LuaModule;
 use crate::memory::MemoryUtils;
 
-use super::luaptr::LuaPtr;
+use super::{buffer::Buffer, luaptr::LuaPtr};
 
 pub struct StringModule;
 
@@ -45,17 +46,146 @@ impl LuaModule for StringModule {
         )?;
         string_table.set(
             "from_utf8_bytes",
-            lua.create_function(|_lua, bytes: Vec<u8>| {
+            lua.create_function(|_lua, bytes: Buffer| {
                 let s = String::from_utf8_lossy(&bytes).to_string();
                 Ok(s)
             })?,
         )?;
 
+        // 固定字符串，地址在释放前保持有效，用于会保存指针的原生函数
+        string_table.set(
+            "pin",
+            lua.create_function(|lua, (value, encoding): (LuaValue, Option<LuaValue>)| {
+                let mut s = parse_lua_value_to_string(&value).into_lua_err()?;
+                if let Some(encoding) = encoding {
+                    s.encoding = lua.from_value(encoding)?;
+                }
+                let address = StringPool::instance().pin(&s);
+                let pinned_table = lua.globals().get::<LuaTable>(PINNED_KEY)?;
+                pinned_table.set(address as i64, true)?;
+
+                Ok(PinnedString {
+                    address,
+                    encoding: s.encoding,
+                })
+            })?,
+        )?;
+        string_table.set(
+            "release",
+            lua.create_function(|lua, value: LuaValue| {
+                let address = match &value {
+                    LuaValue::UserData(ud) if ud.is::<PinnedString>() => {
+                        ud.borrow::<PinnedString>()?.address
+                    }
+                    other => LuaPtr::from_lua(other.clone(), lua)?.to_usize(),
+                };
+                StringModule::release_pinned(lua, address)
+            })?,
+        )?;
+
         registry.set("String", string_table)?;
+        lua.globals().set(PINNED_KEY, lua.create_table()?)?;
+        Ok(())
+    }
+}
+
+impl StringModule {
+    /// 释放固定字符串，地址不属于当前虚拟机时返回 false
+    fn release_pinned(lua: &Lua, address: usize) -> LuaResult<bool> {
+        // 地址作为整数键，LuaPtr 作为键时按 userdata 身份比较
+        let pinned_table = lua.globals().get::<LuaTable>(PINNED_KEY)?;
+        let key = address as i64;
+        if !pinned_table.contains_key(key)? {
+            return Ok(false);
+        }
+        pinned_table.set(key, LuaNil)?;
+        Ok(StringPool::instance().release(address))
+    }
+
+    /// 释放虚拟机固定的所有字符串
+    pub fn release_all_pinned(lua: &Lua) -> Result<()> {
+        let pinned_table = lua.globals().get::<LuaTable>(PINNED_KEY)?;
+
+        let pool = StringPool::instance();
+        for pair in pinned_table.pairs::<i64, bool>() {
+            let (address, _) = pair?;
+            pool.release(address as usize);
+        }
+        pinned_table.clear()?;
+
         Ok(())
     }
 }
 
+const PINNED_KEY: &str = "_pinned_strings";
+
+/// 固定字符串池
+///
+/// FFI 调用的字符串参数只在调用期间有效，会保存指针的原生函数之后读取到的是已释放的内存。
+/// 池中的字符串在显式释放或虚拟机移除前保持有效。
+pub struct StringPool {
+    strings: Mutex<HashMap<usize, Box<[u8]>>>,
+}
+
+impl StringPool {
+    pub fn instance() -> &'static StringPool {
+        static INSTANCE: LazyLock<StringPool> = LazyLock::new(|| StringPool {
+            strings: Mutex::new(HashMap::new()),
+        });
+        &INSTANCE
+    }
+
+    /// 固定字符串，返回以 `\0` 结尾的数据地址
+    pub fn pin(&self, string: &ManagedString) -> usize {
+        let data = string.to_bytes_with_nul().into_boxed_slice();
+        let address = data.as_ptr() as usize;
+        self.strings.lock().insert(address, data);
+        address
+    }
+
+    pub fn release(&self, address: usize) -> bool {
+        self.strings.lock().remove(&address).is_some()
+    }
+
+    pub fn contains(&self, address: usize) -> bool {
+        self.strings.lock().contains_key(&address)
+    }
+}
+
+/// 固定字符串句柄
+///
+/// 句柄被回收不会释放字符串，需调用 `release` 或等待虚拟机移除。
+#[derive(Debug, Clone, Copy)]
+pub struct PinnedString {
+    address: usize,
+    encoding: Encoding,
+}
+
+impl PinnedString {
+    pub fn address(&self) -> usize {
+        self.address
+    }
+}
+
+impl LuaUserData for PinnedString {
+    fn add_fields<F: LuaUserDataFields<Self>>(fields: &mut F) {
+        fields.add_meta_field(LuaMetaMethod::Type, "PinnedString");
+        fields.add_field("_type", "PinnedString");
+
+        fields.add_field_method_get("encoding", |lua, this| lua.to_value(&this.encoding));
+        fields.add_field_method_get("released", |_, this| {
+            Ok(!StringPool::instance().contains(this.address))
+        });
+    }
+
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        methods.add_method("as_ptr", |_, this, ()| Ok(LuaPtr::new(this.address as u64)));
+        methods.add_method("release", |lua, this, ()| {
+            StringModule::release_pinned(lua, this.address)
+        });
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Encoding {
@@ -0,0 +1,434 @@
+//! 结构体布局定义
+//!
+//! 脚本声明内存布局后，将指针包装为带类型的访问对象，支持嵌套结构体、结构体指针和数组。
+//! 字段偏移在定义时检查是否超出结构体大小，数组访问检查索引，读写统一经过页面权限检查。
+//!
+//! 结构体定义属于定义它的虚拟机，不同脚本中的同名结构体互不影响，脚本卸载时一并释放。
+
+use std::{collections::HashMap, sync::Arc};
+
+use mlua::prelude::*;
+use parking_lot::Mutex;
+
+use super::{
+    class_def::{self, FieldType},
+    luaptr::LuaPtr,
+};
+use crate::{error::Error, luavm::library::LuaModule};
+
+const POINTER_SIZE: usize = 8;
+
+pub struct StructModule;
+
+impl LuaModule for StructModule {
+    fn register_library(lua: &mlua::Lua, registry: &mlua::Table) -> mlua::Result<()> {
+        let struct_table = lua.create_table()?;
+        // 定义结构体，字段格式为 name = {offset, type, count?}，返回结构体大小
+        struct_table.set(
+            "define",
+            lua.create_function(
+                |lua, (name, fields, size): (String, LuaTable, Option<usize>)| {
+                    let def = StructDef::from_lua_table(lua, &name, fields, size)?;
+                    let size = def.size;
+                    StructRegistry::of(lua)?.register(def);
+                    Ok(size)
+                },
+            )?,
+        )?;
+        // 将指针包装为指定结构体的访问对象
+        struct_table.set(
+            "wrap",
+            lua.create_function(|lua, (name, ptr): (String, LuaPtr)| {
+                let def = StructRegistry::of(lua)?.get_or_err(&name)?;
+                Ok(StructObject::new(def, ptr.to_usize()))
+            })?,
+        )?;
+        struct_table.set(
+            "sizeof",
+            lua.create_function(|lua, name: String| {
+                Ok(StructRegistry::of(lua)?.get_or_err(&name)?.size)
+            })?,
+        )?;
+        struct_table.set(
+            "list",
+            lua.create_function(|lua, ()| Ok(StructRegistry::of(lua)?.names()))?,
+        )?;
+
+        registry.set("Struct", struct_table)?;
+        lua.set_app_data(StructRegistry::default());
+
+        Ok(())
+    }
+}
+
+/// 字段的元素类型
+#[derive(Debug, Clone)]
+enum ElementType {
+    Value(FieldType),
+    /// 内嵌的结构体
+    Struct(Arc<StructDef>),
+    /// 指向结构体的指针，按名称延迟查找，允许引用尚未定义或自身的结构体
+    Pointer(String),
+}
+
+impl ElementType {
+    /// 解析类型名：基础类型、已定义的结构体名，或 `*结构体名` 表示结构体指针
+    fn parse(lua: &Lua, name: &str) -> LuaResult<Self> {
+        if let Some(target) = name.strip_prefix('*') {
+            return Ok(ElementType::Pointer(target.to_string()));
+        }
+        if let Ok(ty) = lua.from_value::<FieldType>(name.into_lua(lua)?) {
+            return Ok(ElementType::Value(ty));
+        }
+        let def = StructRegistry::of(lua)?.get_or_err(name)?;
+        Ok(ElementType::Struct(def))
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            ElementType::Value(ty) => ty.size() as usize,
+            ElementType::Struct(def) => def.size,
+            ElementType::Pointer(_) => POINTER_SIZE,
+        }
+    }
+
+    fn read(&self, lua: &Lua, address: usize) -> LuaResult<LuaValue> {
+        match self {
+            ElementType::Value(ty) => class_def::read_value(lua, address, *ty),
+            ElementType::Struct(def) => StructObject::new(def.clone(), address).into_lua(lua),
+            ElementType::Pointer(name) => {
+                let value = class_def::read_value(lua, address, FieldType::Pointer)?;
+                let ptr = LuaPtr::from_lua(value, lua)?.to_usize();
+                if ptr == 0 {
+                    return Ok(LuaNil);
+                }
+                let def = StructRegistry::of(lua)?.get_or_err(name)?;
+                StructObject::new(def, ptr).into_lua(lua)
+            }
+        }
+    }
+
+    fn write(&self, lua: &Lua, address: usize, value: LuaValue) -> LuaResult<()> {
+        match self {
+            ElementType::Value(ty) => class_def::write_value(lua, address, *ty, value),
+            ElementType::Pointer(_) => {
+                // 允许直接赋值结构体对象
+                let value = match value {
+                    LuaValue::UserData(ud) if ud.is::<StructObject>() => {
+                        LuaPtr::new(ud.borrow::<StructObject>()?.ptr as u64).into_lua(lua)?
+                    }
+                    other => other,
+                };
+                class_def::write_value(lua, address, FieldType::Pointer, value)
+            }
+            ElementType::Struct(def) => Err(LuaError::external(format!(
+                "embedded struct '{}' is not assignable",
+                def.name
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct StructField {
+    offset: usize,
+    ty: ElementType,
+    /// 数组长度，`None` 表示单个元素
+    count: Option<usize>,
+}
+
+impl StructField {
+    fn from_lua_table(lua: &Lua, spec: LuaTable) -> LuaResult<Self> {
+        let offset: usize = spec.get(1)?;
+        let type_name: String = spec.get(2)?;
+        let count: Option<usize> = spec.get(3)?;
+
+        let ty = ElementType::parse(lua, &type_name)?;
+        if count.is_some() && matches!(ty, ElementType::Value(FieldType::String)) {
+            return Err(Error::InvalidValue("non-string array element", type_name).into_lua_err());
+        }
+
+        let field = Self { offset, ty, count };
+        if field.end().is_none() {
+            return Err(Error::InvalidValue(
+                "field size within address space",
+                format!("offset 0x{:x}, count {}", offset, count.unwrap_or(1)),
+            )
+            .into_lua_err());
+        }
+        Ok(field)
+    }
+
+    /// 字段的结束偏移，溢出时返回 None
+    fn end(&self) -> Option<usize> {
+        self.ty
+            .size()
+            .checked_mul(self.count.unwrap_or(1))?
+            .checked_add(self.offset)
+    }
+}
+
+#[derive(Debug)]
+pub struct StructDef {
+    name: String,
+    size: usize,
+    fields: HashMap<String, StructField>,
+}
+
+impl StructDef {
+    /// 从 Lua table 构造，未指定大小时取字段的最大结束偏移
+    fn from_lua_table(
+        lua: &Lua,
+        name: &str,
+        fields: LuaTable,
+        size: Option<usize>,
+    ) -> LuaResult<Self> {
+        let mut parsed = HashMap::new();
+        for pair in fields.pairs::<String, LuaTable>() {
+            let (field_name, spec) = pair?;
+            let field = StructField::from_lua_table(lua, spec)
+                .map_err(|e| e.context(format!("invalid field '{}.{}'", name, field_name)))?;
+            parsed.insert(field_name, field);
+        }
+
+        // 字段构造时已检查结束偏移不会溢出
+        let end_of = |field: &StructField| field.end().unwrap_or(usize::MAX);
+        let end = parsed.values().map(end_of).max().unwrap_or(0);
+        let size = size.unwrap_or(end);
+        if let Some((field_name, _)) = parsed.iter().find(|(_, field)| end_of(field) > size) {
+            return Err(Error::InvalidValue(
+                "field within struct size",
+                format!("{}.{} (size 0x{:x})", name, field_name, size),
+            )
+            .into_lua_err());
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            size,
+            fields: parsed,
+        })
+    }
+}
+
+/// 虚拟机的结构体定义表
+#[derive(Default)]
+pub struct StructRegistry {
+    structs: Mutex<HashMap<String, Arc<StructDef>>>,
+}
+
+impl StructRegistry {
+    /// 获取虚拟机的定义表
+    fn of(lua: &Lua) -> LuaResult<mlua::AppDataRef<'_, StructRegistry>> {
+        lua.app_data_ref::<StructRegistry>()
+            .ok_or_else(|| LuaError::external("Internal: struct registry is not initialized"))
+    }
+
+    fn register(&self, def: StructDef) {
+        self.structs.lock().insert(def.name.clone(), Arc::new(def));
+    }
+
+    fn get_or_err(&self, name: &str) -> LuaResult<Arc<StructDef>> {
+        self.structs
+            .lock()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::StructNotFound(name.to_string()).into_lua_err())
+    }
+
+    fn names(&self) -> Vec<String> {
+        let mut names = self.structs.lock().keys().cloned().collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+}
+
+/// 绑定了结构体定义的指针
+#[derive(Clone)]
+pub struct StructObject {
+    def: Arc<StructDef>,
+    ptr: usize,
+}
+
+impl StructObject {
+    fn new(def: Arc<StructDef>, ptr: usize) -> Self {
+        Self { def, ptr }
+    }
+
+    fn field_address(&self, field: &StructField) -> LuaResult<usize> {
+        self.ptr.checked_add(field.offset).ok_or_else(|| {
+            Error::InvalidValue("address within address space", format!("0x{:x}", self.ptr))
+                .into_lua_err()
+        })
+    }
+
+    fn field(&self, name: &str) -> LuaResult<&StructField> {
+        self.def.fields.get(name).ok_or_else(|| {
+            Error::ClassMemberNotFound(self.def.name.clone(), name.to_string()).into_lua_err()
+        })
+    }
+}
+
+impl LuaUserData for StructObject {
+    fn add_fields<F: LuaUserDataFields<Self>>(fields: &mut F) {
+        fields.add_meta_field(LuaMetaMethod::Type, "StructObject");
+    }
+
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        methods.add_meta_method(LuaMetaMethod::ToString, |_, this, ()| {
+            Ok(format!("{}(0x{:016X})", this.def.name, this.ptr))
+        });
+        methods.add_meta_method(LuaMetaMethod::Index, |lua, this, key: String| {
+            match key.as_str() {
+                "_ptr" => return LuaPtr::new(this.ptr as u64).into_lua(lua),
+                "_struct" => return this.def.name.as_str().into_lua(lua),
+                "_size" => return this.def.size.into_lua(lua),
+                _ => {}
+            }
+
+            let field = this.field(&key)?;
+            let address = this.field_address(field)?;
+            match field.count {
+                Some(len) => StructArray {
+                    ty: field.ty.clone(),
+                    ptr: address,
+                    len,
+                }
+                .into_lua(lua),
+                None => field.ty.read(lua, address),
+            }
+        });
+        methods.add_meta_method(
+            LuaMetaMethod::NewIndex,
+            |lua, this, (key, value): (String, LuaValue)| {
+                let field = this.field(&key)?;
+                if field.count.is_some() {
+                    return Err(LuaError::external(format!(
+                        "array field '{}.{}' is not assignable, assign its elements instead",
+                        this.def.name, key
+                    )));
+                }
+                field.ty.write(lua, this.field_address(field)?, value)
+            },
+        );
+    }
+}
+
+/// 结构体中的定长数组，索引从 1 开始
+#[derive(Clone)]
+pub struct StructArray {
+    ty: ElementType,
+    ptr: usize,
+    len: usize,
+}
+
+impl StructArray {
+    fn element_address(&self, index: usize) -> LuaResult<usize> {
+        if index == 0 || index > self.len {
+            return Err(Error::IndexOutOfBounds(index, self.len).into_lua_err());
+        }
+        // 字段定义时已检查数组大小不会溢出
+        self.ptr
+            .checked_add((index - 1) * self.ty.size())
+            .ok_or_else(|| {
+                Error::InvalidValue("address within address space", format!("0x{:x}", self.ptr))
+                    .into_lua_err()
+            })
+    }
+}
+
+impl LuaUserData for StructArray {
+    fn add_fields<F: LuaUserDataFields<Self>>(fields: &mut F) {
+        fields.add_meta_field(LuaMetaMethod::Type, "StructArray");
+    }
+
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        methods.add_meta_method(LuaMetaMethod::Len, |_, this, ()| Ok(this.len));
+        methods.add_meta_method(LuaMetaMethod::Index, |lua, this, key: LuaValue| match key {
+            LuaValue::String(s) if s.to_str()? == "_ptr" => {
+                LuaPtr::new(this.ptr as u64).into_lua(lua)
+            }
+            key => {
+                let index = usize::from_lua(key, lua)?;
+                this.ty.read(lua, this.element_address(index)?)
+            }
+        });
+        methods.add_meta_method(
+            LuaMetaMethod::NewIndex,
+            |lua, this, (index, value): (usize, LuaValue)| {
+                this.ty.write(lua, this.element_address(index)?, value)
+            },
+        );
+        // 读取所有元素为 Lua table
+        methods.add_method("to_table", |lua, this, ()| {
+            let table = lua.create_table_with_capacity(this.len, 0)?;
+            for index in 1..=this.len {
+                table.push(this.ty.read(lua, this.element_address(index)?)?)?;
+            }
+            Ok(table)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::luavm::LuaVM;
+
+    #[test]
+    fn test_struct_read_write() {
+        let vm = LuaVM::new_with_libs("virtual:test_struct_read_write.lua").unwrap();
+
+        let mut data = [0u8; 16];
+        data[0] = 7;
+        vm.lua()
+            .globals()
+            .set("ptr", super::LuaPtr::new(data.as_mut_ptr() as u64))
+            .unwrap();
+
+        let script = r#"
+            local Struct = sdk.Struct
+            assert(Struct.define("Pair", { a = { 0, "i32" }, b = { 4, "i32", 2 } }) == 12)
+            assert(Struct.define("Outer", { pair = { 0, "Pair" }, tail = { 12, "u8" } }, 16) == 16)
+
+            local outer = Struct.wrap("Outer", ptr)
+            assert(outer.pair.a == 7)
+            outer.pair.b[2] = 3
+            assert(outer.pair.b[2] == 3 and #outer.pair.b == 2)
+            assert(not pcall(function() return outer.pair.b[3] end))
+        "#;
+        vm.load_script(script).unwrap();
+        assert_eq!(data[8], 3);
+    }
+
+    #[test]
+    fn test_struct_define_checks_size() {
+        let vm = LuaVM::new_with_libs("virtual:test_struct_checks.lua").unwrap();
+
+        let script = r#"
+            local Struct = sdk.Struct
+            assert(not pcall(Struct.define, "TooSmall", { a = { 4, "i32" } }, 4))
+            assert(not pcall(Struct.define, "Overflow", { a = { 0, "i64", 0x2000000000000000 } }))
+            assert(not pcall(Struct.define, "OffsetOverflow", { a = { 0xFFFFFFFFFFFFF000, "i64", 0x1000 } }))
+        "#;
+        vm.load_script(script).unwrap();
+    }
+
+    #[test]
+    fn test_struct_registry_per_vm() {
+        let vm_a = LuaVM::new_with_libs("virtual:test_struct_a.lua").unwrap();
+        let vm_b = LuaVM::new_with_libs("virtual:test_struct_b.lua").unwrap();
+
+        vm_a.load_script(r#"sdk.Struct.define("Shared", { a = { 0, "i32" } })"#)
+            .unwrap();
+        vm_b.load_script(
+            r#"
+            assert(not pcall(sdk.Struct.sizeof, "Shared"))
+            assert(#sdk.Struct.list() == 0)
+            sdk.Struct.define("Shared", { a = { 0, "i64" } })
+            "#,
+        )
+        .unwrap();
+        vm_a.load_script(r#"assert(sdk.Struct.sizeof("Shared") == 4)"#)
+            .unwrap();
+    }
+}
@@ -0,0 +1,51 @@
+//! 延迟与重复任务
+//!
+//! 由每帧更新驱动，脚本无需在 on_update 中自行计时。任务保存在虚拟机中，
+//! 脚本卸载时自动清理。
+
+use mlua::prelude::*;
+
+use crate::{
+    error::Error,
+    luavm::library::{LuaModule, utility::timer},
+};
+
+pub struct TimerModule;
+
+impl LuaModule for TimerModule {
+    fn register_library(lua: &mlua::Lua, registry: &mlua::Table) -> mlua::Result<()> {
+        let timer_table = lua.create_table()?;
+        // seconds 秒后执行一次，返回任务句柄
+        timer_table.set(
+            "after",
+            lua.create_function(
+                |lua, (seconds, callback, args): (f64, LuaFunction, LuaMultiValue)| {
+                    timer::schedule(lua, seconds * 1000.0, callback, args)
+                },
+            )?,
+        )?;
+        // 每 seconds 秒执行一次，直到取消
+        timer_table.set(
+            "every",
+            lua.create_function(
+                |lua, (seconds, callback, args): (f64, LuaFunction, LuaMultiValue)| {
+                    if seconds.is_nan() || seconds <= 0.0 {
+                        return Err(
+                            Error::InvalidValue("positive interval", seconds.to_string())
+                                .into_lua_err(),
+                        );
+                    }
+                    timer::schedule_repeating(lua, seconds * 1000.0, callback, args)
+                },
+            )?,
+        )?;
+        // 取消任务，返回任务是否存在
+        timer_table.set(
+            "cancel",
+            lua.create_function(|lua, handle: i64| timer::cancel(lua, handle))?,
+        )?;
+
+        registry.set("Timer", timer_table)?;
+        Ok(())
+    }
+}
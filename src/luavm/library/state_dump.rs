@@ -0,0 +1,194 @@
+//! 脚本状态导出
+//!
+//! `core.dump_state(path)` 将脚本列表、全局变量快照、Hook 与补丁信息
+//! 写入 data 目录下的 JSON 文件，便于用户提交问题报告。
+
+use mlua::prelude::*;
+use serde_json::{Map, Value, json};
+
+use super::{fs, sdk::frida::FridaModule, sdk::luaptr::LuaPtr};
+use crate::{config::Config, error::Error, luavm::LuaVMManager};
+
+const DUMP_DIR: &str = "dumps";
+/// 导出的全局变量个数上限
+const MAX_GLOBALS: usize = 256;
+/// 单个 table 导出的条目上限
+const MAX_TABLE_ENTRIES: usize = 64;
+/// table 导出的最大深度
+const MAX_DEPTH: usize = 3;
+/// 字符串导出的最大长度
+const MAX_STRING_LEN: usize = 256;
+
+/// Lua 标准库与框架库，不导出
+const SKIPPED_GLOBALS: &[&str] = &[
+    "_G",
+    "_VERSION",
+    "string",
+    "table",
+    "math",
+    "os",
+    "io",
+    "coroutine",
+    "debug",
+    "bit",
+    "jit",
+    "package",
+    "core",
+    "sdk",
+    "utils",
+    "log",
+    "imgui",
+    "draw",
+    "fs",
+    "json",
+    "toml",
+];
+
+/// 导出当前虚拟机状态，返回写入的文件路径
+pub fn dump_state(lua: &Lua, path: Option<String>) -> LuaResult<String> {
+    let globals = lua.globals();
+    let script_name = globals
+        .get::<String>("_name")
+        .unwrap_or_else(|_| "Script".to_string());
+
+    let path = path.unwrap_or_else(|| {
+        let stem = script_name.trim_end_matches(".lua");
+        let time = chrono::Local::now().format("%Y%m%d_%H%M%S");
+        format!("{}/{}_{}.json", DUMP_DIR, stem, time)
+    });
+    let full_path = fs::create_abs_path(&path)?;
+
+    let bundle = json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "process": crate::utility::process::process_info().ok(),
+        "time": chrono::Local::now().to_rfc3339(),
+        "script": script_name,
+        "scripts": script_list()?,
+        "globals": globals_snapshot(lua)?,
+        "hooks": hook_list(lua)?,
+        "patches": address_list(lua, "_patches")?,
+        "code_islands": address_list(lua, "_code_islands")?,
+        "last_error": crate::error::get_last_error().map(|e| e.error),
+    });
+
+    fs::create_dirs(&full_path)?;
+    let content = serde_json::to_string_pretty(&bundle).map_err(|e| e.into_lua_err())?;
+    std::fs::write(&full_path, content).map_err(|e| {
+        Error::IoWithContext(e, format!("write state dump '{}'", path)).into_lua_err()
+    })?;
+
+    log::info!(
+        "[{}] State dumped to '{}'",
+        script_name,
+        full_path.display()
+    );
+    Ok(full_path.to_string_lossy().replace('\\', "/"))
+}
+
+fn script_list() -> LuaResult<Value> {
+    let mut loaded = Vec::new();
+    LuaVMManager::instance().run_with_lock(|inner| {
+        loaded = inner
+            .iter_vms()
+            .filter(|(_, vm)| !vm.is_virtual())
+            .map(|(_, vm)| vm.name().to_string())
+            .collect();
+        Ok(())
+    })?;
+    loaded.sort();
+
+    Ok(json!({
+        "loaded": loaded,
+        "disabled": Config::global().scripts.disabled_scripts.clone(),
+    }))
+}
+
+fn globals_snapshot(lua: &Lua) -> LuaResult<Value> {
+    let mut snapshot = Map::new();
+    for pair in lua.globals().pairs::<LuaValue, LuaValue>() {
+        let (key, value) = pair?;
+        let Some(key) = key.as_string().map(|s| s.to_string_lossy()) else {
+            continue;
+        };
+        // 跳过内部变量和库
+        if key.starts_with('_') || SKIPPED_GLOBALS.contains(&key.as_str()) {
+            continue;
+        }
+        if snapshot.len() >= MAX_GLOBALS {
+            snapshot.insert("...".to_string(), json!("truncated"));
+            break;
+        }
+        snapshot.insert(key, value_to_json(&value, 0)?);
+    }
+
+    Ok(Value::Object(snapshot))
+}
+
+fn value_to_json(value: &LuaValue, depth: usize) -> LuaResult<Value> {
+    let json_value = match value {
+        LuaNil => Value::Null,
+        LuaValue::Boolean(v) => json!(v),
+        LuaValue::Integer(v) => json!(v),
+        LuaValue::Number(v) => json!(v),
+        LuaValue::String(v) => {
+            let string = v.to_string_lossy();
+            if string.chars().count() > MAX_STRING_LEN {
+                let truncated = string.chars().take(MAX_STRING_LEN).collect::<String>();
+                json!(format!("{}...", truncated))
+            } else {
+                json!(string)
+            }
+        }
+        LuaValue::Table(table) => {
+            if depth >= MAX_DEPTH {
+                return Ok(json!("<table>"));
+            }
+            let mut map = Map::new();
+            for pair in table.pairs::<LuaValue, LuaValue>() {
+                let (key, value) = pair?;
+                if map.len() >= MAX_TABLE_ENTRIES {
+                    map.insert("...".to_string(), json!("truncated"));
+                    break;
+                }
+                let key = match &key {
+                    LuaValue::String(s) => s.to_string_lossy(),
+                    other => other.to_string()?,
+                };
+                map.insert(key, value_to_json(&value, depth + 1)?);
+            }
+            Value::Object(map)
+        }
+        LuaValue::UserData(ud) if ud.is::<LuaPtr>() => {
+            json!(format!("0x{:x}", ud.borrow::<LuaPtr>()?.to_u64()))
+        }
+        other => json!(format!("<{}>", other.type_name())),
+    };
+
+    Ok(json_value)
+}
+
+fn hook_list(lua: &Lua) -> LuaResult<Value> {
+    let hooks = FridaModule::list_hooks(lua).map_err(|e| e.into_lua_err())?;
+    let hooks = hooks
+        .into_iter()
+        .map(|hook| {
+            json!({
+                "kind": hook.kind,
+                "address": format!("0x{:x}", hook.address),
+                "persistent_key": hook.persistent_key,
+            })
+        })
+        .collect::<Vec<_>>();
+    Ok(Value::Array(hooks))
+}
+
+fn address_list(lua: &Lua, key: &str) -> LuaResult<Value> {
+    let Ok(table) = lua.globals().get::<LuaTable>(key) else {
+        return Ok(Value::Array(Vec::new()));
+    };
+    let addresses = table
+        .sequence_values::<LuaPtr>()
+        .map(|ptr| ptr.map(|ptr| json!(format!("0x{:x}", ptr.to_u64()))))
+        .collect::<LuaResult<Vec<_>>>()?;
+    Ok(Value::Array(addresses))
+}
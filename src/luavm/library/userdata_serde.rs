@@ -0,0 +1,94 @@
+//! 框架 userdata 的序列化支持
+//!
+//! 序列化前将 LuaPtr、ManagedString 等 userdata 转换为带 `__type` 标记的 table，
+//! 反序列化后再还原为对应的 userdata。
+
+use mlua::prelude::*;
+
+use super::sdk::{
+    luaptr::LuaPtr,
+    string::{Encoding, ManagedString},
+};
+
+const TYPE_TAG: &str = "__type";
+const MAX_DEPTH: usize = 128;
+
+/// 将值中的框架 userdata 替换为带类型标记的 table
+pub fn encode_userdata(lua: &Lua, value: LuaValue) -> LuaResult<LuaValue> {
+    encode_value(lua, value, 0)
+}
+
+/// 将值中带类型标记的 table 还原为框架 userdata
+pub fn decode_userdata(lua: &Lua, value: LuaValue) -> LuaResult<LuaValue> {
+    decode_value(lua, value, 0)
+}
+
+fn encode_value(lua: &Lua, value: LuaValue, depth: usize) -> LuaResult<LuaValue> {
+    if depth > MAX_DEPTH {
+        return Err(LuaError::external(
+            "value is nested too deeply or contains cycles",
+        ));
+    }
+
+    match value {
+        LuaValue::UserData(ud) => {
+            let tagged = lua.create_table()?;
+            if let Ok(ptr) = ud.borrow::<LuaPtr>() {
+                tagged.set(TYPE_TAG, "LuaPtr")?;
+                tagged.set("value", format!("0x{:X}", ptr.to_u64()))?;
+            } else if let Ok(s) = ud.borrow::<ManagedString>() {
+                tagged.set(TYPE_TAG, "ManagedString")?;
+                tagged.set("encoding", lua.to_value(&s.encoding())?)?;
+                tagged.set("value", s.data())?;
+            } else {
+                return Err(LuaError::SerializeError(
+                    "only LuaPtr and ManagedString userdata can be serialized".to_string(),
+                ));
+            }
+            Ok(LuaValue::Table(tagged))
+        }
+        LuaValue::Table(table) => {
+            let encoded = lua.create_table()?;
+            for pair in table.pairs::<LuaValue, LuaValue>() {
+                let (key, value) = pair?;
+                encoded.raw_set(key, encode_value(lua, value, depth + 1)?)?;
+            }
+            // 保留 array 等元表标记
+            encoded.set_metatable(table.metatable())?;
+            Ok(LuaValue::Table(encoded))
+        }
+        other => Ok(other),
+    }
+}
+
+fn decode_value(lua: &Lua, value: LuaValue, depth: usize) -> LuaResult<LuaValue> {
+    if depth > MAX_DEPTH {
+        return Err(LuaError::external("value is nested too deeply"));
+    }
+    let LuaValue::Table(table) = value else {
+        return Ok(value);
+    };
+
+    match table.raw_get::<Option<String>>(TYPE_TAG)?.as_deref() {
+        Some("LuaPtr") => {
+            let value = table.raw_get::<String>("value")?;
+            let address = u64::from_str_radix(value.trim_start_matches("0x"), 16)
+                .map_err(LuaError::external)?;
+            LuaPtr::new(address).into_lua(lua)
+        }
+        Some("ManagedString") => {
+            let encoding: Encoding = lua.from_value(table.raw_get("encoding")?)?;
+            let value = table.raw_get::<String>("value")?;
+            ManagedString::new(&value, encoding).into_lua(lua)
+        }
+        _ => {
+            for pair in table.pairs::<LuaValue, LuaValue>() {
+                let (key, value) = pair?;
+                if value.is_table() {
+                    table.raw_set(key, decode_value(lua, value, depth + 1)?)?;
+                }
+            }
+            Ok(LuaValue::Table(table))
+        }
+    }
+}
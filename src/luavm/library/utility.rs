@@ -6,6 +6,13 @@ use crate::error::{Error, Result};
 
 use super::LuaModule;
 
+pub mod hash;
+mod random;
+mod table;
+pub mod task;
+pub mod time;
+pub mod timer;
+
 pub struct UtilityModule;
 
 impl LuaModule for UtilityModule {
@@ -43,18 +50,126 @@ impl LuaModule for UtilityModule {
             })?,
         )?;
 
+        // 深拷贝，保留元表与循环引用
+        utils_table.set(
+            "deep_copy",
+            lua.create_function(|lua, value: LuaValue| table::deep_copy(lua, value))?,
+        )?;
+        // 将 src 合并到 dst 中，返回 dst
+        utils_table.set(
+            "table_merge",
+            lua.create_function(|_, (dst, src, deep): (LuaTable, LuaTable, Option<bool>)| {
+                table::merge(&dst, &src, deep.unwrap_or(false))?;
+                Ok(dst)
+            })?,
+        )?;
+        // 比较两个 table，返回值不同的字段
+        utils_table.set(
+            "table_diff",
+            lua.create_function(|lua, (a, b): (LuaTable, LuaTable)| table::diff(lua, &a, &b))?,
+        )?;
+
+        // UUID v4
+        utils_table.set("uuid", lua.create_function(|_, ()| Ok(random::uuid_v4()))?)?;
+        // xxHash32
+        utils_table.set(
+            "hash32",
+            lua.create_function(|_, (data, seed): (LuaString, Option<u32>)| {
+                Ok(hash::xxh32(&data.as_bytes(), seed.unwrap_or(0)))
+            })?,
+        )?;
+        // xxHash64，结果按 i64 返回
+        utils_table.set(
+            "hash64",
+            lua.create_function(|_, (data, seed): (LuaString, Option<i64>)| {
+                Ok(hash::xxh64(&data.as_bytes(), seed.unwrap_or(0) as u64) as i64)
+            })?,
+        )?;
+        utils_table.set(
+            "crc32",
+            lua.create_function(|_, data: LuaString| Ok(hash::crc32(&data.as_bytes())))?,
+        )?;
+        utils_table.set(
+            "md5",
+            lua.create_function(|_, data: LuaString| Ok(hash::md5_hex(&data.as_bytes())))?,
+        )?;
+        utils_table.set(
+            "sha256",
+            lua.create_function(|_, data: LuaString| Ok(hash::sha256_hex(&data.as_bytes())))?,
+        )?;
+
+        // Random
+        let random_table = lua.create_table()?;
+        random_table.set(
+            "new",
+            lua.create_function(|_, seed: Option<i64>| {
+                Ok(random::LuaRandom::new(seed.map(|s| s as u64)))
+            })?,
+        )?;
+        utils_table.set("Random", random_table)?;
+
+        utils_table.set("time", time::create_time_table(lua)?)?;
+
+        // 防抖，连续调用时只在最后一次调用 ms 毫秒后执行
+        utils_table.set(
+            "debounce",
+            lua.create_function(|lua, (fun, ms): (LuaFunction, f64)| {
+                timer::debounce(lua, fun, ms)
+            })?,
+        )?;
+        // 节流，每 ms 毫秒最多执行一次
+        utils_table.set(
+            "throttle",
+            lua.create_function(|lua, (fun, ms): (LuaFunction, f64)| {
+                timer::throttle(lua, fun, ms)
+            })?,
+        )?;
+
+        // 断言当前处于游戏主线程
+        utils_table.set(
+            "assert_game_thread",
+            lua.create_function(|_, message: Option<String>| {
+                if crate::game::thread::is_game_thread() {
+                    return Ok(());
+                }
+                let thread_id = crate::game::thread::current_thread_id();
+                Err(match message {
+                    Some(message) => LuaError::external(format!(
+                        "{} (thread {} is not the game thread)",
+                        message, thread_id
+                    )),
+                    None => Error::NotGameThread(thread_id).into_lua_err(),
+                })
+            })?,
+        )?;
+
         // Instant
         let instant_table = lua.create_table()?;
         instant_table.set("now", lua.create_function(|_, ()| Ok(LuaInstant::now()))?)?;
 
         utils_table.set("Instant", instant_table)?;
 
+        // 进程模块信息与版本指纹
+        utils_table.set(
+            "process_info",
+            lua.create_function(|lua, ()| {
+                let info = crate::utility::process::process_info().into_lua_err()?;
+                lua.to_value(&info)
+            })?,
+        )?;
+
         registry.set("utils", utils_table)?;
         Ok(())
     }
 }
 
 impl UtilityModule {
+    /// 执行虚拟机中到期的定时任务，调用已完成的后台任务回调
+    pub fn tick_timers(lua: &Lua) -> LuaResult<()> {
+        timer::tick(lua)?;
+        task::tick(lua)
+    }
+
     /// 将两个 u32 表示的高低位合并为一个 u64 (LE)
     pub fn merge_to_u64(high: u32, low: u32) -> u64 {
         ((high as u64) << 32) | (low as u64)
@@ -0,0 +1,56 @@
+//! 哈希函数
+
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+pub fn xxh32(data: &[u8], seed: u32) -> u32 {
+    xxhash_rust::xxh32::xxh32(data, seed)
+}
+
+pub fn xxh64(data: &[u8], seed: u64) -> u64 {
+    xxhash_rust::xxh64::xxh64(data, seed)
+}
+
+/// 计算 MD5，返回小写十六进制字符串
+pub fn md5_hex(data: &[u8]) -> String {
+    to_hex(&Md5::digest(data))
+}
+
+/// 计算 SHA-256，返回小写十六进制字符串
+pub fn sha256_hex(data: &[u8]) -> String {
+    to_hex(&Sha256::digest(data))
+}
+
+/// CRC-32 (IEEE) 查找表
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// 计算 CRC-32 (IEEE)
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc = CRC32_TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,70 @@
+//! UUID 与随机数生成器
+
+use mlua::prelude::*;
+use rand::{Rng, RngCore, SeedableRng, rngs::StdRng};
+
+/// 生成 UUID v4 字符串
+pub fn uuid_v4() -> String {
+    let mut bytes = [0u8; 16];
+    rand::rng().fill_bytes(&mut bytes);
+    // 版本号与变体标记
+    bytes[6] = (bytes[6] & 0x0F) | 0x40;
+    bytes[8] = (bytes[8] & 0x3F) | 0x80;
+
+    let hex = bytes
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// 可指定种子的随机数生成器
+pub struct LuaRandom(StdRng);
+
+impl LuaRandom {
+    pub fn new(seed: Option<u64>) -> Self {
+        match seed {
+            Some(seed) => Self(StdRng::seed_from_u64(seed)),
+            None => Self(StdRng::from_os_rng()),
+        }
+    }
+}
+
+impl LuaUserData for LuaRandom {
+    fn add_fields<F: LuaUserDataFields<Self>>(fields: &mut F) {
+        fields.add_meta_field(LuaMetaMethod::Type, "Random");
+    }
+
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        // 生成 [min, max] 范围内的整数
+        methods.add_method_mut("integer", |_, this, (min, max): (i64, i64)| {
+            if min > max {
+                return Err(LuaError::external(format!(
+                    "invalid range: min {} > max {}",
+                    min, max
+                )));
+            }
+            Ok(this.0.random_range(min..=max))
+        });
+        // 生成 [0, 1) 范围内的浮点数
+        methods.add_method_mut("number", |_, this, ()| Ok(this.0.random::<f64>()));
+        // 以概率 p 返回 true，默认 0.5
+        methods.add_method_mut("bool", |_, this, p: Option<f64>| {
+            let p = p.unwrap_or(0.5);
+            if !(0.0..=1.0).contains(&p) {
+                return Err(LuaError::external(format!(
+                    "probability {} is out of range [0, 1]",
+                    p
+                )));
+            }
+            Ok(this.0.random_bool(p))
+        });
+    }
+}
@@ -0,0 +1,153 @@
+//! table 工具函数
+//!
+//! 函数、userdata 等引用类型按引用复制，循环引用会被保留。
+//! 递归深度限制为 [`MAX_DEPTH`]，超出时返回错误，避免嵌套过深的 table 耗尽原生栈。
+
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::c_void,
+};
+
+use mlua::prelude::*;
+
+use crate::error::Error;
+
+/// 最大嵌套层数
+pub const MAX_DEPTH: usize = 200;
+
+fn check_depth(depth: usize) -> LuaResult<()> {
+    if depth > MAX_DEPTH {
+        return Err(Error::TableTooDeep(MAX_DEPTH).into_lua_err());
+    }
+    Ok(())
+}
+
+/// 深拷贝，保留元表与循环引用
+pub fn deep_copy(lua: &Lua, value: LuaValue) -> LuaResult<LuaValue> {
+    let mut copied = HashMap::new();
+    deep_copy_value(lua, value, &mut copied, 0)
+}
+
+fn deep_copy_value(
+    lua: &Lua,
+    value: LuaValue,
+    copied: &mut HashMap<*const c_void, LuaTable>,
+    depth: usize,
+) -> LuaResult<LuaValue> {
+    let LuaValue::Table(table) = value else {
+        return Ok(value);
+    };
+    if let Some(copy) = copied.get(&table.to_pointer()) {
+        return Ok(LuaValue::Table(copy.clone()));
+    }
+    check_depth(depth)?;
+
+    let copy = lua.create_table()?;
+    copied.insert(table.to_pointer(), copy.clone());
+    for pair in table.pairs::<LuaValue, LuaValue>() {
+        let (key, value) = pair?;
+        let key = deep_copy_value(lua, key, copied, depth + 1)?;
+        let value = deep_copy_value(lua, value, copied, depth + 1)?;
+        copy.raw_set(key, value)?;
+    }
+    copy.set_metatable(table.metatable())?;
+
+    Ok(LuaValue::Table(copy))
+}
+
+/// 将 `src` 合并到 `dst` 中
+///
+/// `deep` 为 true 时，两侧同名字段均为 table 则递归合并，否则直接覆盖。
+pub fn merge(dst: &LuaTable, src: &LuaTable, deep: bool) -> LuaResult<()> {
+    let mut visited = HashSet::new();
+    merge_inner(dst, src, deep, &mut visited, 0)
+}
+
+fn merge_inner(
+    dst: &LuaTable,
+    src: &LuaTable,
+    deep: bool,
+    visited: &mut HashSet<(*const c_void, *const c_void)>,
+    depth: usize,
+) -> LuaResult<()> {
+    if !visited.insert((dst.to_pointer(), src.to_pointer())) {
+        return Ok(());
+    }
+    check_depth(depth)?;
+
+    for pair in src.pairs::<LuaValue, LuaValue>() {
+        let (key, value) = pair?;
+        if deep
+            && let LuaValue::Table(src_child) = &value
+            && let LuaValue::Table(dst_child) = dst.raw_get::<LuaValue>(key.clone())?
+        {
+            merge_inner(&dst_child, src_child, deep, visited, depth + 1)?;
+            continue;
+        }
+        dst.raw_set(key, value)?;
+    }
+
+    Ok(())
+}
+
+/// 比较两个 table，返回值不同的字段
+///
+/// 返回格式：`{ [key] = { old = a[key], new = b[key] } }`，嵌套 table 按值比较。
+pub fn diff(lua: &Lua, a: &LuaTable, b: &LuaTable) -> LuaResult<LuaTable> {
+    let result = lua.create_table()?;
+
+    let mut push_diff = |key: LuaValue, old: LuaValue, new: LuaValue| -> LuaResult<()> {
+        let entry = lua.create_table()?;
+        entry.raw_set("old", old)?;
+        entry.raw_set("new", new)?;
+        result.raw_set(key, entry)
+    };
+
+    for pair in a.pairs::<LuaValue, LuaValue>() {
+        let (key, old) = pair?;
+        let new = b.raw_get::<LuaValue>(key.clone())?;
+        if !deep_equal(&old, &new, &mut HashSet::new(), 0)? {
+            push_diff(key, old, new)?;
+        }
+    }
+    // b 中新增的字段
+    for pair in b.pairs::<LuaValue, LuaValue>() {
+        let (key, new) = pair?;
+        if a.raw_get::<LuaValue>(key.clone())?.is_nil() {
+            push_diff(key, LuaValue::Nil, new)?;
+        }
+    }
+
+    Ok(result)
+}
+
+fn deep_equal(
+    a: &LuaValue,
+    b: &LuaValue,
+    visited: &mut HashSet<(*const c_void, *const c_void)>,
+    depth: usize,
+) -> LuaResult<bool> {
+    let (LuaValue::Table(ta), LuaValue::Table(tb)) = (a, b) else {
+        return Ok(a == b);
+    };
+    if ta == tb || !visited.insert((ta.to_pointer(), tb.to_pointer())) {
+        return Ok(true);
+    }
+    check_depth(depth)?;
+
+    for pair in ta.pairs::<LuaValue, LuaValue>() {
+        let (key, va) = pair?;
+        let vb = tb.raw_get::<LuaValue>(key)?;
+        if !deep_equal(&va, &vb, visited, depth + 1)? {
+            return Ok(false);
+        }
+    }
+    for pair in tb.pairs::<LuaValue, LuaValue>() {
+        let (key, _) = pair?;
+        if ta.raw_get::<LuaValue>(key)?.is_nil() {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
@@ -0,0 +1,201 @@
+//! 后台任务
+//!
+//! 耗时操作在后台线程执行，返回的任务句柄可通过 `:cancel()` 取消、`:status()` 查询状态。
+//! 任务保存在虚拟机的 `_tasks` 表中，每帧检查完成的任务并在游戏主线程调用回调。
+//! 虚拟机卸载时取消其所有任务，后台线程在下一次检查取消标记时停止。
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use mlua::prelude::*;
+use parking_lot::Mutex;
+
+const TASKS_KEY: &str = "_tasks";
+const NEXT_ID_KEY: &str = "_task_next_id";
+
+/// 任务完成后在游戏主线程转换为回调参数
+pub type TaskOutput = Box<dyn FnOnce(&Lua) -> LuaResult<LuaMultiValue> + Send>;
+
+/// 取消标记，后台任务应定期检查并尽快返回
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum TaskStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+struct TaskState {
+    status: TaskStatus,
+    /// 尚未交给回调的结果
+    output: Option<Result<TaskOutput, String>>,
+}
+
+/// 任务句柄
+#[derive(Clone)]
+pub struct TaskHandle {
+    id: i64,
+    token: CancellationToken,
+    state: Arc<Mutex<TaskState>>,
+}
+
+impl TaskHandle {
+    pub fn status(&self) -> TaskStatus {
+        self.state.lock().status
+    }
+
+    /// 取消任务，任务已结束时返回 false
+    pub fn cancel(&self) -> bool {
+        let mut state = self.state.lock();
+        if state.status != TaskStatus::Running {
+            return false;
+        }
+        self.token.cancel();
+        state.status = TaskStatus::Cancelled;
+        state.output = None;
+        true
+    }
+
+    fn finish(&self, output: Result<TaskOutput, String>) {
+        let mut state = self.state.lock();
+        // 已取消的任务丢弃结果
+        if state.status != TaskStatus::Running || self.token.is_cancelled() {
+            return;
+        }
+        state.status = match output {
+            Ok(_) => TaskStatus::Completed,
+            Err(_) => TaskStatus::Failed,
+        };
+        state.output = Some(output);
+    }
+}
+
+impl LuaUserData for TaskHandle {
+    fn add_fields<F: LuaUserDataFields<Self>>(fields: &mut F) {
+        fields.add_field_method_get("id", |_, this| Ok(this.id));
+    }
+
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        methods.add_method("cancel", |_, this, ()| Ok(this.cancel()));
+        methods.add_method("status", |_, this, ()| {
+            let status: &'static str = this.status().into();
+            Ok(status)
+        });
+    }
+}
+
+fn tasks_table(lua: &Lua) -> LuaResult<LuaTable> {
+    let globals = lua.globals();
+    if let Ok(tasks) = globals.get::<LuaTable>(TASKS_KEY) {
+        return Ok(tasks);
+    }
+    let tasks = lua.create_table()?;
+    globals.set(TASKS_KEY, &tasks)?;
+    Ok(tasks)
+}
+
+/// 在后台线程执行任务
+///
+/// 成功时以 `work` 的结果调用 `callback`，失败时以 `(nil, err)` 调用，取消后不调用。
+pub fn spawn<F>(lua: &Lua, callback: LuaFunction, work: F) -> LuaResult<TaskHandle>
+where
+    F: FnOnce(&CancellationToken) -> Result<TaskOutput, String> + Send + 'static,
+{
+    let globals = lua.globals();
+    let id = globals.get::<Option<i64>>(NEXT_ID_KEY)?.unwrap_or(1);
+    globals.set(NEXT_ID_KEY, id + 1)?;
+
+    let handle = TaskHandle {
+        id,
+        token: CancellationToken::default(),
+        state: Arc::new(Mutex::new(TaskState {
+            status: TaskStatus::Running,
+            output: None,
+        })),
+    };
+
+    let task = lua.create_table()?;
+    task.set("handle", handle.clone())?;
+    task.set("fn", callback)?;
+    tasks_table(lua)?.set(id, task)?;
+
+    let worker = handle.clone();
+    std::thread::Builder::new()
+        .name(format!("luaf-task-{}", id))
+        .spawn(move || {
+            let output = work(&worker.token);
+            worker.finish(output);
+        })
+        .into_lua_err()?;
+
+    Ok(handle)
+}
+
+/// 调用已完成任务的回调
+pub fn tick(lua: &Lua) -> LuaResult<()> {
+    let Ok(tasks) = lua.globals().get::<LuaTable>(TASKS_KEY) else {
+        return Ok(());
+    };
+
+    let mut finished = Vec::new();
+    for pair in tasks.pairs::<i64, LuaTable>() {
+        let (id, task) = pair?;
+        let handle = task.get::<LuaUserDataRef<TaskHandle>>("handle")?;
+        let mut state = handle.state.lock();
+        match state.status {
+            TaskStatus::Running => {}
+            TaskStatus::Cancelled => finished.push((id, task.clone(), None)),
+            _ => finished.push((id, task.clone(), state.output.take())),
+        }
+    }
+
+    for (id, task, output) in finished {
+        tasks.set(id, LuaNil)?;
+        let Some(output) = output else {
+            continue;
+        };
+
+        let callback = task.get::<LuaFunction>("fn")?;
+        let result = match output {
+            Ok(output) => output(lua).and_then(|args| callback.call::<()>(args)),
+            Err(e) => callback.call::<()>((LuaNil, e)),
+        };
+        if let Err(e) = result {
+            let name = lua.globals().get::<String>("_name").unwrap_or_default();
+            let err_msg = format!("task callback in LuaVM({}) error:\n{}", name, e);
+            crate::error::set_last_error(err_msg.clone());
+            log::error!("{}", err_msg);
+        }
+    }
+
+    Ok(())
+}
+
+/// 取消虚拟机中的所有任务
+pub fn cancel_all(lua: &Lua) -> LuaResult<()> {
+    let Ok(tasks) = lua.globals().get::<LuaTable>(TASKS_KEY) else {
+        return Ok(());
+    };
+    for pair in tasks.pairs::<i64, LuaTable>() {
+        let (_, task) = pair?;
+        task.get::<LuaUserDataRef<TaskHandle>>("handle")?.cancel();
+    }
+    tasks.clear()?;
+    Ok(())
+}
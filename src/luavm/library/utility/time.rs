@@ -0,0 +1,131 @@
+//! 时间与日期工具
+
+use std::{
+    fmt::Write,
+    sync::LazyLock,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{DateTime, Datelike, Local, TimeZone, Timelike, Utc};
+use mlua::prelude::*;
+
+use crate::game::on_update;
+
+const DEFAULT_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// 进程内单调时钟起点
+static START: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+/// 单调时钟（秒）
+pub fn monotonic() -> f64 {
+    START.elapsed().as_secs_f64()
+}
+
+pub fn create_time_table(lua: &Lua) -> LuaResult<LuaTable> {
+    let time_table = lua.create_table()?;
+
+    // Unix 时间戳（秒，含小数部分）
+    time_table.set(
+        "now",
+        lua.create_function(|_, ()| {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(LuaError::external)?;
+            Ok(now.as_secs_f64())
+        })?,
+    )?;
+    // Unix 时间戳（毫秒）
+    time_table.set(
+        "now_millis",
+        lua.create_function(|_, ()| {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(LuaError::external)?;
+            Ok(now.as_millis() as i64)
+        })?,
+    )?;
+    // 高精度单调时钟（秒），适用于计时
+    time_table.set("monotonic", lua.create_function(|_, ()| Ok(monotonic()))?)?;
+    // 格式化时间戳，默认为当前本地时间
+    time_table.set(
+        "format",
+        lua.create_function(
+            |_, (timestamp, format, utc): (Option<f64>, Option<String>, Option<bool>)| {
+                let time = to_datetime(timestamp)?;
+                let format = format.as_deref().unwrap_or(DEFAULT_FORMAT);
+                if utc.unwrap_or(false) {
+                    format_time(&time, format)
+                } else {
+                    format_time(&time.with_timezone(&Local), format)
+                }
+            },
+        )?,
+    )?;
+    // 获取本地时间的各个字段
+    time_table.set(
+        "local_time",
+        lua.create_function(|lua, timestamp: Option<f64>| {
+            let time = to_datetime(timestamp)?.with_timezone(&Local);
+
+            let table = lua.create_table()?;
+            table.set("year", time.year())?;
+            table.set("month", time.month())?;
+            table.set("day", time.day())?;
+            table.set("hour", time.hour())?;
+            table.set("minute", time.minute())?;
+            table.set("second", time.second())?;
+            table.set("millis", time.timestamp_subsec_millis())?;
+            table.set("weekday", time.weekday().num_days_from_monday() + 1)?;
+            table.set("utc_offset", time.offset().local_minus_utc())?;
+            Ok(table)
+        })?,
+    )?;
+
+    // 游戏内地图时钟
+    time_table.set(
+        "game_time",
+        lua.create_function(|_, ()| Ok(on_update::map_clock().game_time))?,
+    )?;
+    time_table.set(
+        "game_time_scale",
+        lua.create_function(|_, ()| Ok(on_update::map_clock().scale))?,
+    )?;
+    // 现实时间（秒）转换为游戏时间
+    time_table.set(
+        "real_to_game",
+        lua.create_function(|_, seconds: f64| Ok(seconds * on_update::map_clock().scale))?,
+    )?;
+    // 游戏时间转换为现实时间（秒），时钟未运行时返回 nil
+    time_table.set(
+        "game_to_real",
+        lua.create_function(|_, game_time: f64| {
+            let scale = on_update::map_clock().scale;
+            Ok((scale > 0.0).then(|| game_time / scale))
+        })?,
+    )?;
+
+    Ok(time_table)
+}
+
+/// 格式化时间，格式串无效时返回错误而不是 panic
+fn format_time<Tz>(time: &DateTime<Tz>, format: &str) -> LuaResult<String>
+where
+    Tz: TimeZone,
+    Tz::Offset: std::fmt::Display,
+{
+    let mut output = String::new();
+    write!(output, "{}", time.format(format))
+        .map_err(|_| LuaError::external(format!("invalid time format: {}", format)))?;
+    Ok(output)
+}
+
+fn to_datetime(timestamp: Option<f64>) -> LuaResult<DateTime<Utc>> {
+    let Some(timestamp) = timestamp else {
+        return Ok(Utc::now());
+    };
+
+    let secs = timestamp.floor();
+    let nanos = ((timestamp - secs) * 1e9) as u32;
+    DateTime::from_timestamp(secs as i64, nanos)
+        .ok_or_else(|| LuaError::external(format!("invalid timestamp: {}", timestamp)))
+}
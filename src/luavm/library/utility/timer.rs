@@ -0,0 +1,199 @@
+//! 脚本定时器
+//!
+//! 定时任务保存在虚拟机的 `_timers` 表中，每帧 on_update 之前检查并执行到期任务。
+//! 虚拟机销毁时任务随之释放。
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicI64, Ordering},
+};
+
+use mlua::prelude::*;
+use parking_lot::Mutex;
+
+use super::time;
+
+const TIMERS_KEY: &str = "_timers";
+const NEXT_ID_KEY: &str = "_timer_next_id";
+
+fn now_millis() -> f64 {
+    time::monotonic() * 1000.0
+}
+
+fn timers_table(lua: &Lua) -> LuaResult<LuaTable> {
+    let globals = lua.globals();
+    if let Ok(timers) = globals.get::<LuaTable>(TIMERS_KEY) {
+        return Ok(timers);
+    }
+    let timers = lua.create_table()?;
+    globals.set(TIMERS_KEY, &timers)?;
+    Ok(timers)
+}
+
+/// 添加定时任务，`delay_ms` 毫秒后执行，返回任务 ID
+pub fn schedule(
+    lua: &Lua,
+    delay_ms: f64,
+    callback: LuaFunction,
+    args: LuaMultiValue,
+) -> LuaResult<i64> {
+    schedule_with_interval(lua, delay_ms, None, callback, args)
+}
+
+/// 添加重复任务，每 `interval_ms` 毫秒执行一次，直到取消
+pub fn schedule_repeating(
+    lua: &Lua,
+    interval_ms: f64,
+    callback: LuaFunction,
+    args: LuaMultiValue,
+) -> LuaResult<i64> {
+    schedule_with_interval(lua, interval_ms, Some(interval_ms), callback, args)
+}
+
+fn schedule_with_interval(
+    lua: &Lua,
+    delay_ms: f64,
+    interval_ms: Option<f64>,
+    callback: LuaFunction,
+    args: LuaMultiValue,
+) -> LuaResult<i64> {
+    let globals = lua.globals();
+    let id = globals.get::<Option<i64>>(NEXT_ID_KEY)?.unwrap_or(1);
+    globals.set(NEXT_ID_KEY, id + 1)?;
+
+    let timer = lua.create_table()?;
+    timer.set("due", now_millis() + delay_ms.max(0.0))?;
+    timer.set("interval", interval_ms)?;
+    timer.set("fn", callback)?;
+    timer.set("n", args.len())?;
+    timer.set("args", lua.create_sequence_from(args)?)?;
+    timers_table(lua)?.set(id, timer)?;
+
+    Ok(id)
+}
+
+/// 取消定时任务
+pub fn cancel(lua: &Lua, id: i64) -> LuaResult<bool> {
+    let timers = timers_table(lua)?;
+    let exists = timers.contains_key(id)?;
+    timers.set(id, LuaNil)?;
+    Ok(exists)
+}
+
+/// 执行到期的定时任务
+pub fn tick(lua: &Lua) -> LuaResult<()> {
+    let Ok(timers) = lua.globals().get::<LuaTable>(TIMERS_KEY) else {
+        return Ok(());
+    };
+    let now = now_millis();
+
+    let mut due_timers = Vec::new();
+    for pair in timers.pairs::<i64, LuaTable>() {
+        let (id, timer) = pair?;
+        let due = timer.get::<f64>("due")?;
+        if due <= now {
+            due_timers.push((due, id, timer));
+        }
+    }
+    due_timers.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+
+    for (_, id, timer) in due_timers {
+        // 已被本帧之前的回调取消
+        if !timers.contains_key(id)? {
+            continue;
+        }
+        // 重复任务在回调前重新排期，回调中可以取消自身
+        match timer.get::<Option<f64>>("interval")? {
+            Some(interval) => timer.set("due", now + interval)?,
+            None => timers.set(id, LuaNil)?,
+        }
+
+        let callback = timer.get::<LuaFunction>("fn")?;
+        let n = timer.get::<usize>("n")?;
+        let args_table = timer.get::<LuaTable>("args")?;
+        let mut args = LuaMultiValue::with_capacity(n);
+        for i in 1..=n {
+            args.push_back(args_table.raw_get::<LuaValue>(i)?);
+        }
+
+        if let Err(e) = callback.call::<()>(args) {
+            let name = lua.globals().get::<String>("_name").unwrap_or_default();
+            let err_msg = format!("timer callback in LuaVM({}) error:\n{}", name, e);
+            crate::error::set_last_error(err_msg.clone());
+            log::error!("{}", err_msg);
+        }
+    }
+
+    Ok(())
+}
+
+/// 防抖：连续调用时只在最后一次调用 `ms` 毫秒后执行一次
+pub fn debounce(lua: &Lua, fun: LuaFunction, ms: f64) -> LuaResult<LuaFunction> {
+    // 0 表示没有等待中的任务
+    let pending = AtomicI64::new(0);
+
+    lua.create_function(move |lua, args: LuaMultiValue| {
+        let last = pending.swap(0, Ordering::Relaxed);
+        if last != 0 {
+            cancel(lua, last)?;
+        }
+        let id = schedule(lua, ms, fun.clone(), args)?;
+        pending.store(id, Ordering::Relaxed);
+        Ok(())
+    })
+}
+
+#[derive(Default)]
+struct ThrottleState {
+    last_call: Option<f64>,
+    trailing_id: Option<i64>,
+    pending_args: Option<LuaMultiValue>,
+}
+
+/// 节流：每 `ms` 毫秒最多执行一次，期间的最后一次调用会在间隔结束后补发
+pub fn throttle(lua: &Lua, fun: LuaFunction, ms: f64) -> LuaResult<LuaFunction> {
+    let state = Arc::new(Mutex::new(ThrottleState::default()));
+
+    let trailing = {
+        let state = state.clone();
+        let fun = fun.clone();
+        lua.create_function(move |_, ()| {
+            let mut st = state.lock();
+            st.trailing_id = None;
+            let Some(args) = st.pending_args.take() else {
+                return Ok(());
+            };
+            st.last_call = Some(now_millis());
+            drop(st);
+
+            fun.call::<()>(args)
+        })?
+    };
+
+    lua.create_function(move |lua, args: LuaMultiValue| {
+        let now = now_millis();
+        let mut st = state.lock();
+
+        let last_call = match st.last_call {
+            Some(last_call) if now - last_call < ms => last_call,
+            _ => {
+                st.last_call = Some(now);
+                st.pending_args = None;
+                drop(st);
+                return fun.call::<()>(args);
+            }
+        };
+
+        st.pending_args = Some(args);
+        if st.trailing_id.is_none() {
+            let delay = last_call + ms - now;
+            st.trailing_id = Some(schedule(
+                lua,
+                delay,
+                trailing.clone(),
+                LuaMultiValue::new(),
+            )?);
+        }
+        Ok(())
+    })
+}
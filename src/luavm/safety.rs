@@ -0,0 +1,105 @@
+//! 联机会话安全策略
+//!
+//! 处于多人联机会话时，阻止脚本修改内存、打补丁和调用原生函数。
+//! 用户可在界面中为单个脚本放行。
+
+use std::{collections::HashSet, sync::LazyLock};
+
+use mlua::prelude::*;
+use parking_lot::Mutex;
+
+use crate::{
+    config::Config,
+    error::{Error, Result},
+    game::network,
+    luavm::ScriptIdentity,
+};
+
+#[derive(Default)]
+pub struct SafetyPolicy {
+    /// 被策略拦截过的脚本，等待用户处理
+    blocked_scripts: Mutex<HashSet<String>>,
+}
+
+impl SafetyPolicy {
+    pub fn instance() -> &'static SafetyPolicy {
+        static INSTANCE: LazyLock<SafetyPolicy> = LazyLock::new(SafetyPolicy::default);
+        &INSTANCE
+    }
+
+    /// 当前是否处于受限状态
+    pub fn is_restricted(&self) -> bool {
+        Config::global().scripts.disable_unsafe_online && network::is_online()
+    }
+
+    /// 检查脚本是否允许调用受限接口
+    pub fn check(&self, script_name: &str, api_name: &'static str) -> Result<()> {
+        if !self.is_restricted() {
+            return Ok(());
+        }
+        let allowed = Config::global()
+            .safety
+            .allowed_scripts
+            .iter()
+            .any(|name| name == script_name);
+        if allowed {
+            return Ok(());
+        }
+
+        let is_new = self.blocked_scripts.lock().insert(script_name.to_string());
+        if is_new {
+            log::warn!(
+                "[{}] '{}' is blocked in online session by safety policy",
+                script_name,
+                api_name
+            );
+        }
+
+        Err(Error::BlockedByOnlinePolicy(api_name))
+    }
+
+    /// 从 Lua 虚拟机中获取脚本名称并检查
+    ///
+    /// 脚本名取自 [`ScriptIdentity`]，而不是脚本可修改的全局变量 `_name`。
+    pub fn check_lua(lua: &Lua, api_name: &'static str) -> LuaResult<()> {
+        let script_name = ScriptIdentity::name_of(lua);
+        Self::instance()
+            .check(&script_name, api_name)
+            .map_err(|e| e.into_lua_err())
+    }
+
+    /// 获取被拦截的脚本列表
+    pub fn blocked_scripts(&self) -> Vec<String> {
+        let mut scripts = self
+            .blocked_scripts
+            .lock()
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        scripts.sort();
+        scripts
+    }
+
+    /// 允许脚本在联机会话中调用受限接口
+    pub fn allow_script(&self, script_name: &str) {
+        self.blocked_scripts.lock().remove(script_name);
+
+        let mut config = Config::global_mut();
+        if !config
+            .safety
+            .allowed_scripts
+            .iter()
+            .any(|name| name == script_name)
+        {
+            config.safety.allowed_scripts.push(script_name.to_string());
+        }
+    }
+
+    /// 撤销脚本的放行
+    pub fn revoke_script(&self, script_name: &str) {
+        Config::global_mut()
+            .safety
+            .allowed_scripts
+            .retain(|name| name != script_name);
+    }
+}
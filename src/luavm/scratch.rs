@@ -0,0 +1,168 @@
+//! 可复用的临时虚拟机
+//!
+//! 创建虚拟机需要注册全部库并通知扩展，交互式执行和测试中频繁创建的开销较大。
+//! 临时虚拟机使用完毕后清空状态放回池中，下次取出时直接复用。
+//!
+//! 清空状态时释放 Hook、补丁等资源，将全局变量还原为创建时的快照，
+//! 并清空框架内部的状态表（以 `_` 开头的表）和授权状态。库表本身被脚本修改的内容不会还原。
+
+use std::{
+    ops::Deref,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use mlua::prelude::*;
+
+use super::{LuaVM, LuaVMManager, SharedLuaVM, capability};
+use crate::error::Result;
+
+/// 全局变量快照在注册表中的键
+const BASELINE_KEY: &str = "_scratch_baseline";
+/// 已加载模块快照在注册表中的键
+const LOADED_BASELINE_KEY: &str = "_scratch_loaded_baseline";
+/// 池中保留的虚拟机数量
+const POOL_CAPACITY: usize = 2;
+
+static NEXT_SCRATCH_ID: AtomicU32 = AtomicU32::new(1);
+
+/// 从池中取出的临时虚拟机，释放时清空状态并放回池中
+pub struct ScratchVM {
+    luavm: Option<SharedLuaVM>,
+    manager: &'static LuaVMManager,
+}
+
+impl Deref for ScratchVM {
+    type Target = LuaVM;
+
+    fn deref(&self) -> &Self::Target {
+        self.luavm.as_ref().unwrap()
+    }
+}
+
+impl Drop for ScratchVM {
+    fn drop(&mut self) {
+        if let Some(luavm) = self.luavm.take() {
+            self.manager.release_scratch_vm(luavm);
+        }
+    }
+}
+
+impl LuaVMManager {
+    /// 取出一个状态为空的临时虚拟机，池中没有时新建
+    ///
+    /// 使用期间虚拟机与其他虚拟机一样接收回调。
+    #[cfg_attr(not(feature = "embed"), allow(dead_code))]
+    pub fn acquire_scratch_vm(&'static self) -> Result<ScratchVM> {
+        let pooled = self.scratch_pool.lock().pop();
+        let luavm = match pooled {
+            Some(luavm) => luavm,
+            None => {
+                let id = NEXT_SCRATCH_ID.fetch_add(1, Ordering::Relaxed);
+                let luavm = LuaVM::new_with_libs(&format!("virtual:scratch:{}", id))?;
+                take_snapshot(luavm.lua())?;
+                std::sync::Arc::new(luavm)
+            }
+        };
+
+        {
+            let inner = self.inner.lock();
+            inner
+                .borrow_mut()
+                .add_vm(luavm.id(), luavm.name(), luavm.clone());
+        }
+
+        Ok(ScratchVM {
+            luavm: Some(luavm),
+            manager: self,
+        })
+    }
+
+    fn release_scratch_vm(&self, luavm: SharedLuaVM) {
+        {
+            let inner = self.inner.lock();
+            inner.borrow_mut().remove_vm_by_name(luavm.name());
+        }
+
+        // 仍有其他引用时无法安全复用，交由最后一个引用释放
+        if std::sync::Arc::strong_count(&luavm) != 1 {
+            return;
+        }
+        if let Err(e) = reset(&luavm) {
+            log::error!("Failed to reset LuaVM({}): {}", luavm.name(), e);
+            return;
+        }
+
+        let mut pool = self.scratch_pool.lock();
+        if pool.len() < POOL_CAPACITY {
+            pool.push(luavm);
+        }
+    }
+}
+
+/// 记录全局变量和已加载模块的快照
+fn take_snapshot(lua: &Lua) -> LuaResult<()> {
+    lua.set_named_registry_value(BASELINE_KEY, shallow_copy(lua, &lua.globals())?)?;
+    let loaded = lua
+        .globals()
+        .get::<LuaTable>("package")?
+        .get::<LuaTable>("loaded")?;
+    lua.set_named_registry_value(LOADED_BASELINE_KEY, shallow_copy(lua, &loaded)?)?;
+    Ok(())
+}
+
+/// 释放资源并还原到快照
+fn reset(luavm: &LuaVM) -> LuaResult<()> {
+    luavm.release_resources();
+
+    let lua = luavm.lua();
+    capability::reset(lua);
+    let baseline = lua.named_registry_value::<LuaTable>(BASELINE_KEY)?;
+    restore(&lua.globals(), &baseline)?;
+    // 清空内部状态表，_G 指向全局表本身
+    for pair in baseline.pairs::<LuaValue, LuaValue>() {
+        let (key, value) = pair?;
+        if let (Some(name), LuaValue::Table(table)) = (key.as_string(), value)
+            && name.to_string_lossy().starts_with('_')
+            && name.to_string_lossy() != "_G"
+        {
+            table.clear()?;
+        }
+    }
+
+    let loaded = lua
+        .globals()
+        .get::<LuaTable>("package")?
+        .get::<LuaTable>("loaded")?;
+    let loaded_baseline = lua.named_registry_value::<LuaTable>(LOADED_BASELINE_KEY)?;
+    restore(&loaded, &loaded_baseline)?;
+
+    lua.gc_collect()?;
+    Ok(())
+}
+
+fn shallow_copy(lua: &Lua, table: &LuaTable) -> LuaResult<LuaTable> {
+    let copy = lua.create_table()?;
+    for pair in table.pairs::<LuaValue, LuaValue>() {
+        let (key, value) = pair?;
+        copy.raw_set(key, value)?;
+    }
+    Ok(copy)
+}
+
+/// 移除快照中不存在的键，并还原快照中的值
+fn restore(table: &LuaTable, baseline: &LuaTable) -> LuaResult<()> {
+    let keys = table
+        .pairs::<LuaValue, LuaValue>()
+        .map(|pair| pair.map(|(key, _)| key))
+        .collect::<LuaResult<Vec<_>>>()?;
+    for key in keys {
+        if !baseline.contains_key(key.clone())? {
+            table.raw_set(key, LuaNil)?;
+        }
+    }
+    for pair in baseline.pairs::<LuaValue, LuaValue>() {
+        let (key, value) = pair?;
+        table.raw_set(key, value)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,158 @@
+//! 脚本文件监视
+//!
+//! 后台线程定期检查脚本目录中 `.lua` 文件的修改时间，发现新建、修改或删除时记录变更，
+//! 由游戏主线程在下一帧只重载受影响的虚拟机。
+//!
+//! 编辑器保存时可能连续写入多次，文件在 [DEBOUNCE] 内没有新的变化后才提交变更。
+
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        LazyLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use parking_lot::Mutex;
+
+use super::LuaVMManager;
+use crate::config::Config;
+
+/// 检查间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// 文件停止变化多久后提交变更
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChangeKind {
+    Created,
+    Modified,
+    Removed,
+}
+
+/// 脚本文件变更
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptChange {
+    Created(PathBuf),
+    Modified(PathBuf),
+    Removed(PathBuf),
+}
+
+pub struct ScriptWatcher {
+    running: AtomicBool,
+    changes: Mutex<Vec<ScriptChange>>,
+}
+
+impl ScriptWatcher {
+    pub fn instance() -> &'static ScriptWatcher {
+        static INSTANCE: LazyLock<ScriptWatcher> = LazyLock::new(|| ScriptWatcher {
+            running: AtomicBool::new(false),
+            changes: Mutex::new(Vec::new()),
+        });
+        &INSTANCE
+    }
+
+    /// 启动监视线程，重复调用无效
+    ///
+    /// 线程持续运行，设置中关闭热重载时只暂停检查。
+    pub fn start(&'static self) {
+        if self.running.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let result = std::thread::Builder::new()
+            .name("luaf-script-watcher".to_string())
+            .spawn(move || self.run());
+        if let Err(e) = result {
+            log::error!("Failed to start script watcher: {}", e);
+            self.running.store(false, Ordering::Release);
+        }
+    }
+
+    /// 取出已提交的变更
+    pub fn take_changes(&self) -> Vec<ScriptChange> {
+        std::mem::take(&mut *self.changes.lock())
+    }
+
+    fn run(&self) {
+        // None 表示需要重新建立快照，避免启用前的修改触发重载
+        let mut known: Option<HashMap<PathBuf, SystemTime>> = None;
+        let mut pending: HashMap<PathBuf, (ChangeKind, Instant)> = HashMap::new();
+
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            if !Config::global().scripts.hot_reload {
+                known = None;
+                pending.clear();
+                continue;
+            }
+
+            let current = scan_scripts();
+            let Some(known) = known.replace(current.clone()) else {
+                continue;
+            };
+
+            let now = Instant::now();
+            for (path, mtime) in current.iter() {
+                let kind = match known.get(path) {
+                    None => ChangeKind::Created,
+                    Some(old) if old != mtime => ChangeKind::Modified,
+                    Some(_) => continue,
+                };
+                let kind = match pending.get(path) {
+                    // 新建后继续写入仍视为新建
+                    Some((ChangeKind::Created, _)) => ChangeKind::Created,
+                    // 删除后重新创建（部分编辑器的保存方式）视为修改
+                    Some((ChangeKind::Removed, _)) => ChangeKind::Modified,
+                    _ => kind,
+                };
+                pending.insert(path.clone(), (kind, now));
+            }
+            for path in known.keys() {
+                if !current.contains_key(path) {
+                    pending.insert(path.clone(), (ChangeKind::Removed, now));
+                }
+            }
+
+            let ready = pending
+                .iter()
+                .filter(|(_, (_, time))| time.elapsed() >= DEBOUNCE)
+                .map(|(path, (kind, _))| (path.clone(), *kind))
+                .collect::<Vec<_>>();
+            if ready.is_empty() {
+                continue;
+            }
+            let mut changes = self.changes.lock();
+            for (path, kind) in ready {
+                pending.remove(&path);
+                log::debug!("Script file {:?}: {}", kind, path.display());
+                changes.push(match kind {
+                    ChangeKind::Created => ScriptChange::Created(path),
+                    ChangeKind::Modified => ScriptChange::Modified(path),
+                    ChangeKind::Removed => ScriptChange::Removed(path),
+                });
+            }
+        }
+    }
+}
+
+/// 所有脚本目录中的脚本文件及修改时间
+fn scan_scripts() -> HashMap<PathBuf, SystemTime> {
+    let mut files = HashMap::new();
+    for dir in LuaVMManager::script_dirs() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension() != Some("lua".as_ref()) {
+                continue;
+            }
+            if let Ok(mtime) = entry.metadata().and_then(|m| m.modified()) {
+                files.insert(path, mtime);
+            }
+        }
+    }
+    files
+}
@@ -0,0 +1,225 @@
+//! 反汇编
+//!
+//! 基于 iced-x86 解码 x64 指令，供脚本根据扫描到的地址计算 Hook 点，而不是硬编码偏移。
+
+use iced_x86::{
+    Decoder, DecoderOptions, FlowControl, Formatter, Instruction, IntelFormatter, OpKind,
+};
+
+use super::{MemoryError, MemoryUtils};
+
+/// x64 指令的最大长度
+const MAX_INSTRUCTION_LEN: usize = 15;
+/// 单次最多解码的指令数
+pub const MAX_DECODE_COUNT: usize = 4096;
+const PAGE_SIZE: usize = 0x1000;
+
+/// 指令的控制流类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::AsRefStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum InstructionFlow {
+    Next,
+    Call,
+    Jump,
+    ConditionalJump,
+    Return,
+    Other,
+}
+
+#[derive(Debug, Clone)]
+pub struct DecodedInstruction {
+    pub address: usize,
+    pub bytes: Vec<u8>,
+    pub mnemonic: String,
+    pub operands: String,
+    pub flow: InstructionFlow,
+    /// 直接调用或跳转的目标地址
+    pub branch_target: Option<usize>,
+    /// RIP 相对寻址的内存操作数地址
+    pub memory_target: Option<usize>,
+}
+
+impl DecodedInstruction {
+    fn new(instruction: &Instruction, bytes: &[u8], formatter: &mut IntelFormatter) -> Self {
+        let mut mnemonic = String::new();
+        formatter.format_mnemonic(instruction, &mut mnemonic);
+        let mut operands = String::new();
+        formatter.format_all_operands(instruction, &mut operands);
+
+        let flow = match instruction.flow_control() {
+            FlowControl::Next => InstructionFlow::Next,
+            FlowControl::Call | FlowControl::IndirectCall => InstructionFlow::Call,
+            FlowControl::UnconditionalBranch | FlowControl::IndirectBranch => InstructionFlow::Jump,
+            FlowControl::ConditionalBranch => InstructionFlow::ConditionalJump,
+            FlowControl::Return => InstructionFlow::Return,
+            _ => InstructionFlow::Other,
+        };
+        let is_near_branch = (0..instruction.op_count()).any(|i| {
+            matches!(
+                instruction.op_kind(i),
+                OpKind::NearBranch16 | OpKind::NearBranch32 | OpKind::NearBranch64
+            )
+        });
+        let branch_target = is_near_branch.then(|| instruction.near_branch_target() as usize);
+        let memory_target = instruction
+            .is_ip_rel_memory_operand()
+            .then(|| instruction.ip_rel_memory_address() as usize);
+
+        Self {
+            address: instruction.ip() as usize,
+            bytes: bytes.to_vec(),
+            mnemonic,
+            operands,
+            flow,
+            branch_target,
+            memory_target,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// 完整的指令文本
+    pub fn text(&self) -> String {
+        if self.operands.is_empty() {
+            self.mnemonic.clone()
+        } else {
+            format!("{} {}", self.mnemonic, self.operands)
+        }
+    }
+}
+
+/// 从 `address` 开始解码最多 `count` 条指令，`count` 不超过 [`MAX_DECODE_COUNT`]
+///
+/// 遇到无效指令或不可读的内存时停止，返回已解码的指令。
+pub(super) fn disassemble(
+    address: usize,
+    count: usize,
+) -> Result<Vec<DecodedInstruction>, MemoryError> {
+    let mut instructions = Vec::new();
+    decode(address, count, |instruction| {
+        instructions.push(instruction);
+        true
+    })?;
+    Ok(instructions)
+}
+
+/// 从 `address` 开始，在 `max_count` 条指令内查找第一条满足条件的指令
+pub(super) fn find_next_instruction(
+    address: usize,
+    max_count: usize,
+    predicate: impl Fn(&DecodedInstruction) -> bool,
+) -> Result<Option<DecodedInstruction>, MemoryError> {
+    let mut found = None;
+    decode(address, max_count, |instruction| {
+        if predicate(&instruction) {
+            found = Some(instruction);
+            return false;
+        }
+        true
+    })?;
+    Ok(found)
+}
+
+/// 解码指令并依次传给 `f`，`f` 返回 false 时停止
+fn decode(
+    address: usize,
+    count: usize,
+    f: impl FnMut(DecodedInstruction) -> bool,
+) -> Result<(), MemoryError> {
+    let count = count.min(MAX_DECODE_COUNT);
+    let len = readable_len(address, count * MAX_INSTRUCTION_LEN)?;
+    let code = unsafe { std::slice::from_raw_parts(address as *const u8, len) };
+
+    decode_slice(code, address as u64, count, f);
+    Ok(())
+}
+
+/// 解码 `code` 中最多 `count` 条指令，`ip` 为 `code` 起始处的地址
+fn decode_slice(code: &[u8], ip: u64, count: usize, mut f: impl FnMut(DecodedInstruction) -> bool) {
+    let mut decoder = Decoder::with_ip(64, code, ip, DecoderOptions::NONE);
+    let mut formatter = IntelFormatter::new();
+    let mut instruction = Instruction::default();
+    for _ in 0..count {
+        if !decoder.can_decode() {
+            break;
+        }
+        let offset = decoder.position();
+        decoder.decode_out(&mut instruction);
+        if instruction.is_invalid() {
+            break;
+        }
+        let bytes = &code[offset..offset + instruction.len()];
+        if !f(DecodedInstruction::new(&instruction, bytes, &mut formatter)) {
+            break;
+        }
+    }
+}
+
+/// 从 `address` 开始最多 `max_len` 字节中连续可读的长度，起始地址不可读时返回错误
+fn readable_len(address: usize, max_len: usize) -> Result<usize, MemoryError> {
+    MemoryUtils::check_permission_read(address)?;
+
+    let end = address.saturating_add(max_len);
+    let mut page = (address / PAGE_SIZE + 1) * PAGE_SIZE;
+    while page < end {
+        if MemoryUtils::check_permission_read(page).is_err() {
+            return Ok(page - address);
+        }
+        page += PAGE_SIZE;
+    }
+    Ok(max_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IP: u64 = 0x1000;
+
+    fn decode_all(code: &[u8], count: usize) -> Vec<DecodedInstruction> {
+        let mut instructions = Vec::new();
+        decode_slice(code, IP, count, |instruction| {
+            instructions.push(instruction);
+            true
+        });
+        instructions
+    }
+
+    #[test]
+    fn test_decode_prologue() {
+        // push rbx; sub rsp, 0x20; mov rax, [rip+0x10]; call +5; ret
+        let code = [
+            0x53, 0x48, 0x83, 0xEC, 0x20, 0x48, 0x8B, 0x05, 0x10, 0x00, 0x00, 0x00, 0xE8, 0x00,
+            0x00, 0x00, 0x00, 0xC3,
+        ];
+        let instructions = decode_all(&code, 10);
+        assert_eq!(instructions.len(), 5);
+
+        assert_eq!(instructions[0].text(), "push rbx");
+        assert_eq!(instructions[1].address, IP as usize + 1);
+        assert_eq!(instructions[1].len(), 4);
+
+        let mov = &instructions[2];
+        assert_eq!(mov.mnemonic, "mov");
+        assert_eq!(mov.memory_target, Some(IP as usize + 12 + 0x10));
+        assert_eq!(mov.branch_target, None);
+
+        let call = &instructions[3];
+        assert_eq!(call.flow, InstructionFlow::Call);
+        assert_eq!(call.branch_target, Some(IP as usize + 17));
+
+        assert_eq!(instructions[4].flow, InstructionFlow::Return);
+    }
+
+    #[test]
+    fn test_decode_stops_at_count_and_invalid() {
+        let nops = [0x90; 8];
+        assert_eq!(decode_all(&nops, 3).len(), 3);
+
+        // nop; 未完成的 mov
+        let truncated = [0x90, 0x48, 0x8B];
+        assert_eq!(decode_all(&truncated, 10).len(), 1);
+    }
+}
@@ -1,13 +1,24 @@
-use std::{io::Cursor, slice};
+use std::{
+    io::{self, Cursor, Read},
+    ops::Range,
+    slice,
+    str::FromStr,
+};
 
 use super::{
-    MemoryError, pattern_scan,
+    DecodedInstruction, MemoryError, ModuleInfo, disasm,
+    pattern_scan::{self, Pattern},
     windows_util::{self, VirtualProtectGuard},
 };
 
 use windows::Win32::System::Memory::PAGE_EXECUTE_READWRITE;
 pub use windows_util::MemoryState;
 
+const PAGE_SIZE: usize = 0x1000;
+
+/// C 字符串默认读取上限
+pub const MAX_C_STRING_LEN: usize = 0x1000;
+
 pub struct MemoryUtils;
 
 impl MemoryUtils {
@@ -15,22 +26,18 @@ impl MemoryUtils {
     pub fn scan_first(base: usize, size: usize, pattern: &str) -> Result<usize, MemoryError> {
         let memory_slice = unsafe { slice::from_raw_parts(base as *const u8, size) };
 
-        let matches = pattern_scan::scan_first_match(Cursor::new(memory_slice), pattern)
-            .map_err(MemoryError::PatternScan)?;
-        if let Some(matches) = matches {
-            let real_ptr = base + matches;
-            return Ok(real_ptr);
+        match Pattern::from_str(pattern)?.scan_slice_first(memory_slice) {
+            Some(offset) => Ok(base + offset),
+            None => Err(MemoryError::NotFound(pattern.to_string())),
         }
-
-        Err(MemoryError::NotFound(pattern.to_string()))
     }
 
     /// 扫描内存，查找匹配的所有地址
     pub fn scan_all(base: usize, size: usize, pattern: &str) -> Result<Vec<usize>, MemoryError> {
         let memory_slice = unsafe { slice::from_raw_parts(base as *const u8, size) };
 
-        let result = pattern_scan::scan(Cursor::new(memory_slice), pattern)
-            .map_err(MemoryError::PatternScan)?
+        let result = Pattern::from_str(pattern)?
+            .scan_slice(memory_slice)
             .into_iter()
             .map(|v| v + base)
             .collect::<Vec<_>>();
@@ -42,6 +49,93 @@ impl MemoryUtils {
         }
     }
 
+    /// 在主模块中一次扫描多个特征码，按顺序返回各特征码匹配的第一个地址
+    pub fn scan_batch(patterns: &[Pattern]) -> Result<Vec<Option<usize>>, MemoryError> {
+        let (base, size) = unsafe { windows_util::get_base_module_space() }?;
+        let memory_slice = unsafe { slice::from_raw_parts(base as *const u8, size) };
+
+        Ok(pattern_scan::scan_slice_batch(memory_slice, patterns)
+            .into_iter()
+            .map(|offset| offset.map(|v| v + base))
+            .collect())
+    }
+
+    /// 扫描内存，查找匹配的所有地址，未找到时返回空列表
+    ///
+    /// 每读取一块数据检查一次 `is_cancelled`，返回 true 时立即停止扫描并返回错误。
+    pub fn scan_all_cancellable(
+        base: usize,
+        size: usize,
+        pattern: &str,
+        is_cancelled: impl Fn() -> bool,
+    ) -> Result<Vec<usize>, MemoryError> {
+        let memory_slice = unsafe { slice::from_raw_parts(base as *const u8, size) };
+        let reader = CancellableReader {
+            inner: Cursor::new(memory_slice),
+            is_cancelled,
+        };
+
+        let result = pattern_scan::scan(reader, pattern)
+            .map_err(MemoryError::PatternScan)?
+            .into_iter()
+            .map(|v| v + base)
+            .collect::<Vec<_>>();
+        Ok(result)
+    }
+
+    /// 获取主模块的基地址和大小
+    pub fn base_module_space() -> Result<(usize, usize), MemoryError> {
+        Ok(unsafe { windows_util::get_base_module_space() }?)
+    }
+
+    /// 枚举所有已加载的模块，第一个为主模块
+    pub fn modules() -> Result<Vec<ModuleInfo>, MemoryError> {
+        Ok(unsafe { windows_util::enumerate_modules() }?)
+    }
+
+    /// 按名称查找已加载的模块，不区分大小写
+    pub fn find_module(name: &str) -> Result<ModuleInfo, MemoryError> {
+        Self::modules()?
+            .into_iter()
+            .find(|module| module.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| MemoryError::ModuleNotFound(name.to_string()))
+    }
+
+    /// 在指定模块中扫描，查找匹配的第一个地址
+    ///
+    /// 模块中可能存在不可访问的页面（如加壳或受保护的模块），只扫描已提交且可读的区域。
+    pub fn scan_module_first(module: &str, pattern: &str) -> Result<usize, MemoryError> {
+        let module = Self::find_module(module)?;
+        let pattern_value = Pattern::from_str(pattern)?;
+
+        let regions = unsafe { windows_util::readable_regions(module.base, module.size) };
+        for region in regions {
+            let memory_slice =
+                unsafe { slice::from_raw_parts(region.start as *const u8, region.len()) };
+            if let Some(offset) = pattern_value.scan_slice_first(memory_slice) {
+                return Ok(region.start + offset);
+            }
+        }
+        Err(MemoryError::NotFound(pattern.to_string()))
+    }
+
+    /// 从指定地址开始反汇编最多 `count` 条指令
+    pub fn disassemble(
+        address: usize,
+        count: usize,
+    ) -> Result<Vec<DecodedInstruction>, MemoryError> {
+        disasm::disassemble(address, count)
+    }
+
+    /// 从指定地址开始，在 `max_count` 条指令内查找第一条满足条件的指令
+    pub fn find_next_instruction(
+        address: usize,
+        max_count: usize,
+        predicate: impl Fn(&DecodedInstruction) -> bool,
+    ) -> Result<Option<DecodedInstruction>, MemoryError> {
+        disasm::find_next_instruction(address, max_count, predicate)
+    }
+
     /// 自动获取主模块地址，并扫描内存，查找匹配的第一个地址
     pub fn auto_scan_first(pattern: &str) -> Result<usize, MemoryError> {
         let (base, size) = unsafe { windows_util::get_base_module_space() }?;
@@ -56,6 +150,57 @@ impl MemoryUtils {
         Self::scan_all(base, size, pattern)
     }
 
+    /// 自动获取主模块地址并扫描，查找匹配的第一个地址
+    ///
+    /// 匹配时忽略前 `skip_bytes` 个字节和 `hooked` 范围内的字节，
+    /// `relax_call_sites` 为 true 时同时忽略相对调用/跳转指令，
+    /// 用于容忍被其他工具修改过的函数头和调用点。
+    pub fn auto_scan_first_masked(
+        pattern: &str,
+        skip_bytes: usize,
+        hooked: Range<usize>,
+        relax_call_sites: bool,
+    ) -> Result<usize, MemoryError> {
+        let masked = Self::masked_pattern_with(pattern, skip_bytes, hooked, relax_call_sites)?;
+
+        let (base, size) = unsafe { windows_util::get_base_module_space() }?;
+        let memory_slice = unsafe { slice::from_raw_parts(base as *const u8, size) };
+        match masked.scan_slice_first(memory_slice) {
+            Some(offset) => Ok(base + offset),
+            None => Err(MemoryError::NotFound(masked.to_string())),
+        }
+    }
+
+    /// 解析特征码并忽略前 `skip_bytes` 个字节，参数含义同 [`Self::auto_scan_first_masked`]
+    pub fn masked_pattern(
+        pattern: &str,
+        skip_bytes: usize,
+        relax_call_sites: bool,
+    ) -> Result<Pattern, MemoryError> {
+        Self::masked_pattern_with(pattern, skip_bytes, 0..0, relax_call_sites)
+    }
+
+    fn masked_pattern_with(
+        pattern: &str,
+        skip_bytes: usize,
+        hooked: Range<usize>,
+        relax_call_sites: bool,
+    ) -> Result<Pattern, MemoryError> {
+        let mut masked = Pattern::from_str(pattern)?;
+        masked.mask_prefix(skip_bytes);
+        masked.mask_range(hooked);
+        if relax_call_sites {
+            masked.relax_call_sites();
+        }
+        if masked.is_all_wildcard() {
+            return Err(MemoryError::PatternScan(pattern_scan::Error::new(format!(
+                "pattern has no fixed byte after masking: {}",
+                pattern
+            ))));
+        }
+        Ok(masked)
+    }
+
     // /// 扫描内存，查找匹配的地址，如果有且仅有一个，则返回地址，否则返回错误
     // pub fn safe_scan(pattern: &[u8]) -> Result<u64, MemoryError> {
     //     let mut result = Vec::new();
@@ -114,6 +259,34 @@ impl MemoryUtils {
         Ok(result)
     }
 
+    /// 读取以 NUL 结尾的字符串，最多读取 `max_len` 字节（不含结尾 NUL）
+    ///
+    /// 跨页时逐页检查读权限，遇到不可读页面或达到上限时截断返回。
+    pub fn read_c_string(address: usize, max_len: usize) -> Result<Vec<u8>, MemoryError> {
+        Self::check_permission_read(address)?;
+
+        let mut result = Vec::new();
+        let mut cursor = address;
+        while result.len() < max_len {
+            let Some(page_end) = (cursor | (PAGE_SIZE - 1)).checked_add(1) else {
+                break;
+            };
+            let chunk = (page_end - cursor).min(max_len - result.len());
+            let bytes = unsafe { slice::from_raw_parts(cursor as *const u8, chunk) };
+            if let Some(pos) = bytes.iter().position(|&b| b == 0) {
+                result.extend_from_slice(&bytes[..pos]);
+                return Ok(result);
+            }
+            result.extend_from_slice(bytes);
+            cursor = page_end;
+            if result.len() < max_len && Self::check_permission_read(cursor).is_err() {
+                break;
+            }
+        }
+
+        Ok(result)
+    }
+
     /// 写入内存数据
     pub fn write(address: usize, buf: &[u8], safe: bool) -> Result<(), MemoryError> {
         if buf.is_empty() {
@@ -222,6 +395,39 @@ impl MemoryUtils {
         }
     }
 
+    /// 多级指针偏移，与 [`Self::offset_ptr`] 相同，每次取值前检查读权限
+    ///
+    /// 取值失败或取到空指针时返回 `None`。
+    pub fn resolve_chain(base: usize, offsets: &[isize]) -> Option<usize> {
+        let mut addr = base;
+        for (idx, &offset) in offsets.iter().enumerate() {
+            addr = addr.wrapping_add_signed(offset);
+            if idx == offsets.len() - 1 {
+                break;
+            }
+            addr = Self::read_ptr_checked(addr)?;
+        }
+        Some(addr)
+    }
+
+    /// 多级指针偏移，与 [`Self::offset_ptr_ce`] 相同，每次取值前检查读权限
+    pub fn resolve_chain_ce(base: usize, offsets: &[isize]) -> Option<usize> {
+        if base == 0 {
+            return None;
+        }
+        let mut addr = base;
+        for &offset in offsets {
+            addr = Self::read_ptr_checked(addr)?.wrapping_add_signed(offset);
+        }
+        Some(addr)
+    }
+
+    fn read_ptr_checked(address: usize) -> Option<usize> {
+        Self::check_permission_read(address).ok()?;
+        let value = unsafe { (address as *const usize).read_unaligned() };
+        (value != 0).then_some(value)
+    }
+
     pub fn patch(address: usize, data: &[u8]) -> Result<Vec<u8>, MemoryError> {
         // 检查页面是否已提交
         MemoryUtils::check_page_commit(address)?;
@@ -262,6 +468,47 @@ impl MemoryUtils {
         Ok(backup)
     }
 
+    /// 临时将内存设置为可读写执行，守卫释放时恢复原有保护
+    pub fn unprotect(address: usize, size: usize) -> Result<VirtualProtectGuard, MemoryError> {
+        MemoryUtils::check_page_commit(address)?;
+        VirtualProtectGuard::new(address as *const _, size, PAGE_EXECUTE_READWRITE)
+    }
+
+    /// 修改代码后刷新指令缓存
+    pub fn flush_instruction_cache(address: usize, size: usize) -> Result<(), MemoryError> {
+        unsafe { Ok(windows_util::flush_instruction_cache(address, size)?) }
+    }
+
+    /// 分配可执行内存
+    pub fn alloc_executable(size: usize) -> Result<usize, MemoryError> {
+        if size == 0 {
+            return Err(MemoryError::InvalidSize(size));
+        }
+        unsafe { Ok(windows_util::alloc_executable(size)?) }
+    }
+
+    /// 分配可读写的内存，`executable` 为 true 时同时可执行
+    pub fn alloc(size: usize, executable: bool) -> Result<usize, MemoryError> {
+        if size == 0 {
+            return Err(MemoryError::InvalidSize(size));
+        }
+        unsafe { Ok(windows_util::virtual_alloc(None, size, executable)?) }
+    }
+
+    /// 在 `address` 前后 ±2GB 范围内分配内存，可通过 rel32 跳转到达，用于跳板
+    pub fn alloc_near(address: usize, size: usize, executable: bool) -> Result<usize, MemoryError> {
+        if size == 0 {
+            return Err(MemoryError::InvalidSize(size));
+        }
+        unsafe { windows_util::alloc_near(address, size, executable) }?
+            .ok_or(MemoryError::NoMemoryNear(address))
+    }
+
+    /// 释放 [`MemoryUtils::alloc_executable`] 分配的内存
+    pub fn free_executable(address: usize) -> Result<(), MemoryError> {
+        unsafe { Ok(windows_util::free_executable(address)?) }
+    }
+
     /// 通过特征码扫描获取静态变量的调用点，并通过相对地址计算绝对地址。
     pub fn scan_relative_static(pattern: &str, offset: isize) -> Result<usize, MemoryError> {
         let scan_result = MemoryUtils::auto_scan_first(pattern)?;
@@ -279,3 +526,75 @@ impl MemoryUtils {
         (0..=0x10000).contains(&address) || address > i64::MAX as usize
     }
 }
+
+/// 读取前检查取消标记的 Reader
+struct CancellableReader<R, F> {
+    inner: R,
+    is_cancelled: F,
+}
+
+impl<R: Read, F: Fn() -> bool> Read for CancellableReader<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if (self.is_cancelled)() {
+            return Err(io::Error::other("scan cancelled"));
+        }
+        self.inner.read(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_c_string_stops_at_nul() {
+        let buf = b"hello\0world\0";
+        let s = MemoryUtils::read_c_string(buf.as_ptr() as usize, MAX_C_STRING_LEN).unwrap();
+        assert_eq!(s, b"hello");
+    }
+
+    #[test]
+    fn test_read_c_string_truncates_at_limit() {
+        let buf = [b'a'; 64];
+        let s = MemoryUtils::read_c_string(buf.as_ptr() as usize, 16).unwrap();
+        assert_eq!(s.len(), 16);
+        assert!(s.iter().all(|&b| b == b'a'));
+    }
+
+    #[test]
+    fn test_read_c_string_empty() {
+        let buf = [0u8; 4];
+        let s = MemoryUtils::read_c_string(buf.as_ptr() as usize, MAX_C_STRING_LEN).unwrap();
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn test_readable_regions_skip_noaccess() {
+        use windows::Win32::System::Memory::{
+            PAGE_NOACCESS, PAGE_PROTECTION_FLAGS, VirtualProtect,
+        };
+
+        unsafe {
+            let base = windows_util::virtual_alloc(None, PAGE_SIZE * 3, false).unwrap();
+            let mut old = PAGE_PROTECTION_FLAGS::default();
+            VirtualProtect(
+                (base + PAGE_SIZE) as *const _,
+                PAGE_SIZE,
+                PAGE_NOACCESS,
+                &mut old,
+            )
+            .unwrap();
+
+            let regions = windows_util::readable_regions(base, PAGE_SIZE * 3);
+            assert_eq!(
+                regions,
+                vec![
+                    base..base + PAGE_SIZE,
+                    base + PAGE_SIZE * 2..base + PAGE_SIZE * 3
+                ]
+            );
+
+            windows_util::free_executable(base).unwrap();
+        }
+    }
+}
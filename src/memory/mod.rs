@@ -1,13 +1,21 @@
+mod disasm;
 mod memory_util;
 mod pattern_scan;
 mod windows_util;
 
-pub use memory_util::MemoryUtils;
+pub use disasm::{DecodedInstruction, InstructionFlow};
+pub use memory_util::{MAX_C_STRING_LEN, MemoryUtils};
+pub use pattern_scan::Pattern;
+pub use windows_util::{ModuleInfo, VirtualProtectGuard};
 
 #[derive(Debug, thiserror::Error)]
 pub enum MemoryError {
     #[error("pattern not found: {0}")]
     NotFound(String),
+    #[error("No free memory within ±2GB of 0x{0:x}")]
+    NoMemoryNear(usize),
+    #[error("module not found: {0}")]
+    ModuleNotFound(String),
     #[error("more than one pattern found, expected exactly one")]
     MultipleMatchesFound,
     #[error("Invalid size: {0}")]
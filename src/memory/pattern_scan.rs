@@ -4,8 +4,11 @@
 
 use std::fmt::{self, Display};
 use std::io::Read;
+use std::ops::Range;
 use std::str::FromStr;
 
+mod simd;
+
 /// Size of chunks to be read from `reader` when looking for patterns.
 ///
 /// In [`Matches`] (which in turn is used in [`scan`] and [`scan_first_match`]), bytes are read
@@ -13,6 +16,17 @@ use std::str::FromStr;
 /// given by `CHUNK_SIZE`.
 pub const CHUNK_SIZE: usize = 0x4096;
 
+/// Find the first match of each pattern in `data`, scanning it only once.
+///
+/// Returns the offset of the first match for each pattern, in the same order as `patterns`.
+pub fn scan_slice_batch(data: &[u8], patterns: &[Pattern]) -> Vec<Option<usize>> {
+    let compiled = patterns
+        .iter()
+        .map(simd::CompiledPattern::new)
+        .collect::<Vec<_>>();
+    simd::find_first_batch(data, &compiled)
+}
+
 /// Scan for any instances of `pattern` in the bytes read by `reader`.
 ///
 /// Returns a [`Result`] containing a vector of indices of the start of each match within the
@@ -36,6 +50,7 @@ pub fn scan(reader: impl Read, pattern: &str) -> Result<Vec<usize>, Error> {
 /// was not found. Returns an [`Error`] if an error was encountered while scanning, which could
 /// occur if the pattern is invalid (i.e: contains something other than 8-bit hex values and
 /// wildcards), or if the reader encounters an error.
+#[allow(dead_code)]
 pub fn scan_first_match(reader: impl Read, pattern: &str) -> Result<Option<usize>, Error> {
     let mut matches = Matches::from_pattern_str(reader, pattern)?;
     matches.next().transpose()
@@ -71,11 +86,21 @@ impl Display for Error {
 
 impl std::error::Error for Error {}
 
+/// Size of a relative call or jump instruction (`E8`/`E9` + rel32).
+const REL_BRANCH_SIZE: usize = 5;
+
 /// Represents a single byte in a search pattern.
 #[derive(PartialEq, Eq)]
 pub enum PatternByte {
     Byte(u8),
     Any,
+    /// Matches when `byte & mask == value & mask`.
+    ///
+    /// Written as a half wildcard such as `4?` / `?8`, or explicitly as `value&mask` like `E8&FE`.
+    Masked {
+        value: u8,
+        mask: u8,
+    },
 }
 
 impl FromStr for PatternByte {
@@ -83,12 +108,36 @@ impl FromStr for PatternByte {
 
     /// Create an instance of [`PatternByte`] from a string.
     ///
-    /// This string should either be a hexadecimal byte, or a "?". Will return an error if the
-    /// string is not a "?", or it cannot be converted into an 8-bit integer when interpreted as
-    /// hexadecimal.
+    /// This string should either be a hexadecimal byte, a "?", a half wildcard like "4?", or a
+    /// masked byte like "E8&FE". Will return an error if the string is none of these, or it
+    /// cannot be converted into an 8-bit integer when interpreted as hexadecimal.
     fn from_str(s: &str) -> Result<Self, Error> {
         if ["?", "??", "*", "**"].contains(&s) {
             Ok(Self::Any)
+        } else if let Some((value, mask)) = s.split_once('&') {
+            let parse = |v: &str| {
+                u8::from_str_radix(v, 16)
+                    .map_err(|e| Error::new(format!("invalid masked byte '{}': {}", s, e)))
+            };
+            Ok(Self::Masked {
+                value: parse(value)?,
+                mask: parse(mask)?,
+            })
+        } else if s.len() == 2 && s.contains('?') {
+            let nibble = |c: char| match c {
+                '?' => Ok((0, 0)),
+                c => c
+                    .to_digit(16)
+                    .map(|d| (d as u8, 0xF))
+                    .ok_or_else(|| Error::new(format!("invalid half wildcard '{}'", s))),
+            };
+            let mut chars = s.chars();
+            let (high, high_mask) = nibble(chars.next().unwrap())?;
+            let (low, low_mask) = nibble(chars.next().unwrap())?;
+            Ok(Self::Masked {
+                value: (high << 4) | low,
+                mask: (high_mask << 4) | low_mask,
+            })
         } else {
             let n = match u8::from_str_radix(s, 16) {
                 Ok(n) => Ok(n),
@@ -105,6 +154,17 @@ impl PartialEq<u8> for PatternByte {
         match self {
             PatternByte::Any => true,
             PatternByte::Byte(b) => b == other,
+            PatternByte::Masked { value, mask } => value & mask == other & mask,
+        }
+    }
+}
+
+impl Display for PatternByte {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternByte::Byte(b) => write!(f, "{:02X}", b),
+            PatternByte::Any => write!(f, "??"),
+            PatternByte::Masked { value, mask } => write!(f, "{:02X}&{:02X}", value, mask),
         }
     }
 }
@@ -124,11 +184,63 @@ impl Pattern {
         self.bytes.len()
     }
 
+    /// Treat the first `count` bytes as wildcards.
+    ///
+    /// Useful when the start of the pattern may have been overwritten by a detour of another tool.
+    pub fn mask_prefix(&mut self, count: usize) {
+        self.mask_range(0..count);
+    }
+
+    /// Treat the bytes in `range` as wildcards, the part beyond the pattern is ignored.
+    pub fn mask_range(&mut self, range: Range<usize>) {
+        let end = range.end.min(self.bytes.len());
+        let start = range.start.min(end);
+        for byte in self.bytes[start..end].iter_mut() {
+            *byte = PatternByte::Any;
+        }
+    }
+
+    /// Treat relative call / jump sites (`E8`/`E9` followed by a wildcarded rel32) as wildcards,
+    /// including the opcode, so that call sites redirected by other tools still match.
+    pub fn relax_call_sites(&mut self) {
+        let mut i = 0;
+        while i + REL_BRANCH_SIZE <= self.bytes.len() {
+            let is_branch = matches!(self.bytes[i], PatternByte::Byte(0xE8 | 0xE9))
+                && self.bytes[i + 1..i + REL_BRANCH_SIZE]
+                    .iter()
+                    .all(|b| *b == PatternByte::Any);
+            if is_branch {
+                self.bytes[i] = PatternByte::Any;
+                i += REL_BRANCH_SIZE;
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    /// Whether every byte in the pattern is a wildcard.
+    pub fn is_all_wildcard(&self) -> bool {
+        self.bytes.iter().all(|b| *b == PatternByte::Any)
+    }
+
+    /// Find all matches in an in-memory byte string, using the parallel SIMD scanner.
+    pub fn scan_slice(&self, data: &[u8]) -> Vec<usize> {
+        simd::find(data, &simd::CompiledPattern::new(self), false)
+    }
+
+    /// Find the first match in an in-memory byte string, using the parallel SIMD scanner.
+    pub fn scan_slice_first(&self, data: &[u8]) -> Option<usize> {
+        simd::find(data, &simd::CompiledPattern::new(self), true)
+            .first()
+            .copied()
+    }
+
     pub fn scan(self, reader: impl Read) -> Result<Vec<usize>, Error> {
         let matches = Matches::from_pattern(reader, self)?;
         matches.collect()
     }
 
+    #[allow(dead_code)]
     pub fn scan_first_match(self, reader: impl Read) -> Result<Option<usize>, Error> {
         let mut matches = Matches::from_pattern(reader, self)?;
         matches.next().transpose()
@@ -149,6 +261,18 @@ impl FromStr for Pattern {
     }
 }
 
+impl Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, byte) in self.bytes.iter().enumerate() {
+            if i != 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", byte)?;
+        }
+        Ok(())
+    }
+}
+
 impl PartialEq<[u8]> for Pattern {
     fn eq(&self, other: &[u8]) -> bool {
         Iterator::zip(self.bytes.iter(), other.iter()).all(|(pb, b)| pb == b)
@@ -392,6 +516,58 @@ mod tests {
         assert!(scan(Cursor::new(bytes), pattern).is_err());
     }
 
+    #[test]
+    fn scan_half_wildcard() {
+        let bytes = [0x10, 0x4a, 0x30, 0x10, 0x5a, 0x30];
+        let pattern = "10 4? 30";
+
+        assert_eq!(scan(Cursor::new(bytes), pattern).unwrap(), vec![0]);
+    }
+
+    #[test]
+    fn scan_explicit_mask() {
+        let bytes = [0xe8, 0x00, 0xe9, 0x00];
+        let pattern = "e8&fe 00";
+
+        assert_eq!(scan(Cursor::new(bytes), pattern).unwrap(), vec![0, 2]);
+    }
+
+    #[test]
+    fn scan_mask_prefix() {
+        let bytes = [0xe9, 0x11, 0x22, 0x33, 0x44, 0x90];
+        let mut pattern = Pattern::from_str("48 89 5c 24 08 90").unwrap();
+        pattern.mask_prefix(5);
+
+        assert_eq!(pattern.scan(Cursor::new(bytes)).unwrap(), vec![0]);
+    }
+
+    #[test]
+    fn scan_mask_range() {
+        let bytes = [0x10, 0x20, 0xe9, 0x11, 0x22, 0x33, 0x44, 0x90];
+        let mut pattern = Pattern::from_str("10 20 48 89 5c 24 08 90").unwrap();
+        pattern.mask_range(2..7);
+
+        assert_eq!(pattern.to_string(), "10 20 ?? ?? ?? ?? ?? 90");
+        assert_eq!(pattern.scan(Cursor::new(bytes)).unwrap(), vec![0]);
+
+        // Bytes beyond the pattern are ignored
+        let mut pattern = Pattern::from_str("10 20 30").unwrap();
+        pattern.mask_range(2..7);
+        assert_eq!(pattern.to_string(), "10 20 ??");
+        pattern.mask_range(5..9);
+        assert_eq!(pattern.to_string(), "10 20 ??");
+    }
+
+    #[test]
+    fn scan_relaxed_call_site() {
+        let bytes = [0x10, 0xe9, 0x11, 0x22, 0x33, 0x44, 0x20];
+        let mut pattern = Pattern::from_str("10 e8 ? ? ? ? 20").unwrap();
+        pattern.relax_call_sites();
+
+        assert_eq!(pattern.to_string(), "10 ?? ?? ?? ?? ?? 20");
+        assert_eq!(pattern.scan(Cursor::new(bytes)).unwrap(), vec![0]);
+    }
+
     #[test]
     fn scan_first_match_simple_start() {
         let bytes = [0x10, 0x20, 0x30, 0x40, 0x50];
@@ -0,0 +1,298 @@
+//! Parallel in-memory scanner.
+//!
+//! Candidate positions are found by comparing a single "anchor" byte of the pattern against 32
+//! (AVX2) or 16 (SSE2) bytes at a time; the full pattern is only checked at those candidates.
+//! Large inputs are split into chunks which are scanned on separate threads, and several patterns
+//! can be resolved in one pass by scanning them block by block while the block is still cached.
+
+use std::{arch::x86_64::*, ops::Range, thread};
+
+use super::{Pattern, PatternByte};
+
+/// Inputs smaller than this are scanned on the calling thread.
+const PARALLEL_THRESHOLD: usize = 0x100000;
+/// Block size used when scanning several patterns in one pass.
+const BATCH_BLOCK_SIZE: usize = 0x40000;
+/// Bytes that are very common in x86-64 code and make poor anchors.
+const COMMON_BYTES: &[u8] = &[
+    0x00, 0xFF, 0xCC, 0x90, 0x48, 0x4C, 0x8B, 0x89, 0x0F, 0x24, 0x01, 0xE8,
+];
+
+/// A pattern prepared for scanning: byte values, masks and the anchor byte.
+pub(super) struct CompiledPattern {
+    values: Vec<u8>,
+    masks: Vec<u8>,
+    /// Index of the anchor byte, `None` if no byte of the pattern is fully fixed.
+    anchor: Option<usize>,
+}
+
+impl CompiledPattern {
+    pub(super) fn new(pattern: &Pattern) -> Self {
+        let (values, masks): (Vec<u8>, Vec<u8>) = pattern
+            .bytes
+            .iter()
+            .map(|byte| match byte {
+                PatternByte::Byte(b) => (*b, 0xFF),
+                PatternByte::Any => (0, 0),
+                PatternByte::Masked { value, mask } => (value & mask, *mask),
+            })
+            .unzip();
+        let anchor = (0..values.len())
+            .filter(|&i| masks[i] == 0xFF)
+            .min_by_key(|&i| COMMON_BYTES.contains(&values[i]));
+
+        Self {
+            values,
+            masks,
+            anchor,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn matches_at(&self, data: &[u8], start: usize) -> bool {
+        let Some(window) = data.get(start..start + self.len()) else {
+            return false;
+        };
+        window
+            .iter()
+            .zip(self.values.iter().zip(&self.masks))
+            .all(|(byte, (value, mask))| byte & mask == *value)
+    }
+
+    /// Find matches starting within `starts`, appending their offsets to `out` in ascending order.
+    fn find_in(&self, data: &[u8], starts: Range<usize>, first_only: bool, out: &mut Vec<usize>) {
+        if self.len() == 0 {
+            return;
+        }
+
+        let Some(anchor) = self.anchor else {
+            for start in starts {
+                if self.matches_at(data, start) {
+                    out.push(start);
+                    if first_only {
+                        return;
+                    }
+                }
+            }
+            return;
+        };
+
+        let lo = starts.start + anchor;
+        let hi = (starts.end + anchor).min(data.len());
+        if lo >= hi {
+            return;
+        }
+        find_byte(&data[lo..hi], self.values[anchor], |pos| {
+            let start = starts.start + pos;
+            if self.matches_at(data, start) {
+                out.push(start);
+                return !first_only;
+            }
+            true
+        });
+    }
+}
+
+/// Find matches of `pattern` in `data`, in ascending order.
+pub(super) fn find(data: &[u8], pattern: &CompiledPattern, first_only: bool) -> Vec<usize> {
+    let ranges = split_ranges(data.len());
+    let results = thread::scope(|s| {
+        let handles = ranges
+            .into_iter()
+            .map(|range| {
+                s.spawn(move || {
+                    let mut out = Vec::new();
+                    pattern.find_in(data, range, first_only, &mut out);
+                    out
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|h| h.join().unwrap_or_default())
+            .collect::<Vec<_>>()
+    });
+
+    let mut matches = results.into_iter().flatten();
+    if first_only {
+        matches.next().into_iter().collect()
+    } else {
+        matches.collect()
+    }
+}
+
+/// Find the first match of each pattern in a single pass over `data`.
+pub(super) fn find_first_batch(data: &[u8], patterns: &[CompiledPattern]) -> Vec<Option<usize>> {
+    let ranges = split_ranges(data.len());
+    let results = thread::scope(|s| {
+        let handles = ranges
+            .into_iter()
+            .map(|range| s.spawn(move || first_in_range(data, range, patterns)))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|h| h.join().unwrap_or_else(|_| vec![None; patterns.len()]))
+            .collect::<Vec<_>>()
+    });
+
+    // chunks are in ascending order, so the first chunk with a match has the first match
+    (0..patterns.len())
+        .map(|i| results.iter().find_map(|r| r[i]))
+        .collect()
+}
+
+fn first_in_range(
+    data: &[u8],
+    range: Range<usize>,
+    patterns: &[CompiledPattern],
+) -> Vec<Option<usize>> {
+    let mut found = vec![None; patterns.len()];
+    let mut out = Vec::new();
+    let mut block_start = range.start;
+    while block_start < range.end && found.iter().any(Option::is_none) {
+        let block_end = (block_start + BATCH_BLOCK_SIZE).min(range.end);
+        for (pattern, slot) in patterns.iter().zip(found.iter_mut()) {
+            if slot.is_some() {
+                continue;
+            }
+            out.clear();
+            pattern.find_in(data, block_start..block_end, true, &mut out);
+            *slot = out.first().copied();
+        }
+        block_start = block_end;
+    }
+    found
+}
+
+/// Split the candidate start positions of `len` bytes into one range per thread.
+///
+/// Matches starting near the end of a range may extend into the next one, so each thread reads
+/// past its range end as needed.
+fn split_ranges(len: usize) -> Vec<Range<usize>> {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    if len < PARALLEL_THRESHOLD || threads == 1 {
+        return vec![0..len];
+    }
+    let chunk = len.div_ceil(threads);
+    (0..len)
+        .step_by(chunk)
+        .map(|start| start..(start + chunk).min(len))
+        .collect()
+}
+
+/// Call `f` with the offset of every occurrence of `byte` in `data`, stopping when it returns false.
+fn find_byte(data: &[u8], byte: u8, mut f: impl FnMut(usize) -> bool) {
+    let scanned = if is_x86_feature_detected!("avx2") {
+        unsafe { find_byte_avx2(data, byte, &mut f) }
+    } else {
+        unsafe { find_byte_sse2(data, byte, &mut f) }
+    };
+    let Some(scanned) = scanned else {
+        return;
+    };
+
+    for (i, b) in data[scanned..].iter().enumerate() {
+        if *b == byte && !f(scanned + i) {
+            return;
+        }
+    }
+}
+
+/// Returns the number of bytes scanned, or `None` if `f` stopped the scan.
+#[target_feature(enable = "avx2")]
+unsafe fn find_byte_avx2(
+    data: &[u8],
+    byte: u8,
+    f: &mut impl FnMut(usize) -> bool,
+) -> Option<usize> {
+    let mut i = 0;
+    unsafe {
+        let needle = _mm256_set1_epi8(byte as i8);
+        while i + 32 <= data.len() {
+            let chunk = _mm256_loadu_si256(data.as_ptr().add(i) as *const __m256i);
+            let mut mask = _mm256_movemask_epi8(_mm256_cmpeq_epi8(chunk, needle)) as u32;
+            while mask != 0 {
+                if !f(i + mask.trailing_zeros() as usize) {
+                    return None;
+                }
+                mask &= mask - 1;
+            }
+            i += 32;
+        }
+    }
+    Some(i)
+}
+
+/// Returns the number of bytes scanned, or `None` if `f` stopped the scan.
+#[target_feature(enable = "sse2")]
+unsafe fn find_byte_sse2(
+    data: &[u8],
+    byte: u8,
+    f: &mut impl FnMut(usize) -> bool,
+) -> Option<usize> {
+    let mut i = 0;
+    unsafe {
+        let needle = _mm_set1_epi8(byte as i8);
+        while i + 16 <= data.len() {
+            let chunk = _mm_loadu_si128(data.as_ptr().add(i) as *const __m128i);
+            let mut mask = _mm_movemask_epi8(_mm_cmpeq_epi8(chunk, needle)) as u32;
+            while mask != 0 {
+                if !f(i + mask.trailing_zeros() as usize) {
+                    return None;
+                }
+                mask &= mask - 1;
+            }
+            i += 16;
+        }
+    }
+    Some(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn compile(pattern: &str) -> CompiledPattern {
+        CompiledPattern::new(&Pattern::from_str(pattern).unwrap())
+    }
+
+    #[test]
+    fn test_find_matches_across_simd_lanes() {
+        let mut data = vec![0u8; 100];
+        data[3..6].copy_from_slice(&[0x11, 0x22, 0x33]);
+        data[30..33].copy_from_slice(&[0x11, 0x22, 0x33]);
+        data[97..100].copy_from_slice(&[0x11, 0x22, 0x33]);
+        let pattern = compile("11 ?? 33");
+        assert_eq!(find(&data, &pattern, false), vec![3, 30, 97]);
+        assert_eq!(find(&data, &pattern, true), vec![3]);
+    }
+
+    #[test]
+    fn test_find_across_thread_chunks() {
+        let mut data = vec![0u8; PARALLEL_THRESHOLD * 2];
+        let ranges = split_ranges(data.len());
+        // a match straddling the boundary between two chunks
+        let boundary = ranges[0].end.min(data.len() - 4) - 2;
+        data[boundary..boundary + 4].copy_from_slice(&[0xDE, 0xAD, 0xBE, 0xEF]);
+        let pattern = compile("DE AD BE EF");
+        assert_eq!(find(&data, &pattern, false), vec![boundary]);
+    }
+
+    #[test]
+    fn test_find_first_batch() {
+        let mut data = vec![0u8; 0x1000];
+        data[0x800..0x803].copy_from_slice(&[0xAA, 0xBB, 0xCC]);
+        data[0x100..0x103].copy_from_slice(&[0xAA, 0xBB, 0xCC]);
+        data[0x200..0x202].copy_from_slice(&[0x12, 0x34]);
+        let patterns = [compile("AA BB CC"), compile("12 3?"), compile("56 78")];
+        assert_eq!(
+            find_first_batch(&data, &patterns),
+            vec![Some(0x100), Some(0x200), None]
+        );
+    }
+}
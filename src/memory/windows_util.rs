@@ -1,21 +1,23 @@
-use std::ffi::c_void;
+use std::{ffi::c_void, ops::Range};
 
 use bitflags::bitflags;
 use windows::Win32::{
-    Foundation::HMODULE,
+    Foundation::{HANDLE, HMODULE},
     System::{
+        Diagnostics::Debug::FlushInstructionCache,
         Memory::{
-            MEM_COMMIT, MEMORY_BASIC_INFORMATION, PAGE_PROTECTION_FLAGS, VirtualProtect,
+            MEM_COMMIT, MEM_FREE, MEM_RELEASE, MEM_RESERVE, MEMORY_BASIC_INFORMATION,
+            PAGE_PROTECTION_FLAGS, VirtualAlloc, VirtualFree, VirtualProtect, VirtualQuery,
             VirtualQueryEx,
         },
-        ProcessStatus::{EnumProcessModules, GetModuleInformation, MODULEINFO},
+        ProcessStatus::{EnumProcessModules, GetModuleBaseNameW, GetModuleInformation, MODULEINFO},
         Threading::GetCurrentProcess,
     },
 };
 
 use windows::Win32::System::Memory::{
-    PAGE_EXECUTE, PAGE_EXECUTE_READ, PAGE_EXECUTE_READWRITE, PAGE_EXECUTE_WRITECOPY, PAGE_NOACCESS,
-    PAGE_READONLY, PAGE_READWRITE, PAGE_WRITECOPY,
+    PAGE_EXECUTE, PAGE_EXECUTE_READ, PAGE_EXECUTE_READWRITE, PAGE_EXECUTE_WRITECOPY, PAGE_GUARD,
+    PAGE_NOACCESS, PAGE_READONLY, PAGE_READWRITE, PAGE_WRITECOPY,
 };
 
 use super::MemoryError;
@@ -30,6 +32,14 @@ bitflags! {
     }
 }
 
+/// 已加载模块的信息
+#[derive(Debug, Clone)]
+pub struct ModuleInfo {
+    pub name: String,
+    pub base: usize,
+    pub size: usize,
+}
+
 /// 获取基模块的空间信息，基地址和大小
 ///
 /// # Safety
@@ -38,37 +48,86 @@ bitflags! {
 pub unsafe fn get_base_module_space() -> Result<(usize, usize), windows::core::Error> {
     unsafe {
         let hprocess = GetCurrentProcess();
-        let mut modules: [HMODULE; 1024] = [HMODULE::default(); 1024];
-        let mut cb_needed: u32 = 0;
+        let Some(hmodule) = enum_module_handles()?.first().copied() else {
+            return Ok((0, 0));
+        };
+        let module_info = get_module_info(hprocess, hmodule)?;
 
-        EnumProcessModules(
-            hprocess,
-            modules.as_mut_ptr(),
-            (modules.len() * std::mem::size_of::<HMODULE>()) as u32,
-            &mut cb_needed,
-        )?;
-
-        let module_count = cb_needed / std::mem::size_of::<HMODULE>() as u32;
-        if module_count > 0 {
-            let hmodule = modules[0];
-            let mut module_info = MODULEINFO::default();
-            GetModuleInformation(
+        Ok((
+            module_info.lpBaseOfDll as usize,
+            module_info.SizeOfImage as usize,
+        ))
+    }
+}
+
+/// 枚举所有已加载的模块，第一个为主模块
+///
+/// # Safety
+///
+/// 调用 Windows API
+pub unsafe fn enumerate_modules() -> Result<Vec<ModuleInfo>, windows::core::Error> {
+    unsafe {
+        let hprocess = GetCurrentProcess();
+        let mut modules = Vec::new();
+        for hmodule in enum_module_handles()? {
+            // 枚举期间被卸载的模块
+            let Ok(module_info) = get_module_info(hprocess, hmodule) else {
+                continue;
+            };
+            let mut name_buf = [0u16; 260];
+            let len = GetModuleBaseNameW(hprocess, Some(hmodule), &mut name_buf) as usize;
+            modules.push(ModuleInfo {
+                name: String::from_utf16_lossy(&name_buf[..len]),
+                base: module_info.lpBaseOfDll as usize,
+                size: module_info.SizeOfImage as usize,
+            });
+        }
+
+        Ok(modules)
+    }
+}
+
+unsafe fn enum_module_handles() -> Result<Vec<HMODULE>, windows::core::Error> {
+    unsafe {
+        let hprocess = GetCurrentProcess();
+        let mut modules = vec![HMODULE::default(); 1024];
+        loop {
+            let mut cb_needed: u32 = 0;
+            EnumProcessModules(
                 hprocess,
-                hmodule,
-                &mut module_info,
-                std::mem::size_of::<MODULEINFO>() as u32,
+                modules.as_mut_ptr(),
+                (modules.len() * std::mem::size_of::<HMODULE>()) as u32,
+                &mut cb_needed,
             )?;
 
-            return Ok((
-                module_info.lpBaseOfDll as usize,
-                module_info.SizeOfImage as usize,
-            ));
+            let module_count = cb_needed as usize / std::mem::size_of::<HMODULE>();
+            // 缓冲区不足时扩大后重新枚举
+            if module_count > modules.len() {
+                modules.resize(module_count, HMODULE::default());
+                continue;
+            }
+            modules.truncate(module_count);
+            return Ok(modules);
         }
-
-        Ok((0, 0))
     }
 }
 
+unsafe fn get_module_info(
+    hprocess: HANDLE,
+    hmodule: HMODULE,
+) -> Result<MODULEINFO, windows::core::Error> {
+    let mut module_info = MODULEINFO::default();
+    unsafe {
+        GetModuleInformation(
+            hprocess,
+            hmodule,
+            &mut module_info,
+            std::mem::size_of::<MODULEINFO>() as u32,
+        )?
+    };
+    Ok(module_info)
+}
+
 /// 获取内存的权限
 pub unsafe fn get_memory_state(address: usize) -> Result<MemoryState, windows::core::Error> {
     let hprocess = unsafe { GetCurrentProcess() };
@@ -113,6 +172,177 @@ pub unsafe fn get_memory_state(address: usize) -> Result<MemoryState, windows::c
     Ok(permissions)
 }
 
+/// 遍历 `base` 起 `size` 字节的范围，返回其中已提交且可读的区域，相邻的区域合并
+///
+/// # Safety
+///
+/// 调用 Windows API
+pub unsafe fn readable_regions(base: usize, size: usize) -> Vec<Range<usize>> {
+    const READABLE: [PAGE_PROTECTION_FLAGS; 6] = [
+        PAGE_READONLY,
+        PAGE_READWRITE,
+        PAGE_WRITECOPY,
+        PAGE_EXECUTE_READ,
+        PAGE_EXECUTE_READWRITE,
+        PAGE_EXECUTE_WRITECOPY,
+    ];
+
+    let end = base.saturating_add(size);
+    let mut regions: Vec<Range<usize>> = Vec::new();
+    let mut cursor = base;
+    while cursor < end {
+        let mut mbi = MEMORY_BASIC_INFORMATION::default();
+        let len = unsafe {
+            VirtualQuery(
+                Some(cursor as *const c_void),
+                &mut mbi,
+                std::mem::size_of::<MEMORY_BASIC_INFORMATION>(),
+            )
+        };
+        if len == 0 || mbi.RegionSize == 0 {
+            break;
+        }
+        let region_end = (mbi.BaseAddress as usize + mbi.RegionSize).min(end);
+        let readable = mbi.State == MEM_COMMIT
+            && mbi.Protect.0 & PAGE_GUARD.0 == 0
+            && READABLE.iter().any(|flag| mbi.Protect.0 & 0xFF == flag.0);
+        if readable {
+            match regions.last_mut() {
+                Some(last) if last.end == cursor => last.end = region_end,
+                _ => regions.push(cursor..region_end),
+            }
+        }
+        cursor = region_end;
+    }
+    regions
+}
+
+/// 刷新指令缓存
+///
+/// # Safety
+///
+/// 调用 Windows API
+pub unsafe fn flush_instruction_cache(address: usize, size: usize) -> windows::core::Result<()> {
+    unsafe { FlushInstructionCache(GetCurrentProcess(), Some(address as *const c_void), size) }
+}
+
+/// 分配可读写执行的内存
+///
+/// # Safety
+///
+/// 调用 Windows API
+pub unsafe fn alloc_executable(size: usize) -> windows::core::Result<usize> {
+    unsafe { virtual_alloc(None, size, true) }
+}
+
+/// 分配可读写的内存，`executable` 为 true 时同时可执行，`address` 为 None 时由系统选择地址
+///
+/// # Safety
+///
+/// 调用 Windows API
+pub unsafe fn virtual_alloc(
+    address: Option<usize>,
+    size: usize,
+    executable: bool,
+) -> windows::core::Result<usize> {
+    let protect = if executable {
+        PAGE_EXECUTE_READWRITE
+    } else {
+        PAGE_READWRITE
+    };
+    let ptr = unsafe {
+        VirtualAlloc(
+            address.map(|address| address as *const c_void),
+            size,
+            MEM_COMMIT | MEM_RESERVE,
+            protect,
+        )
+    };
+    if ptr.is_null() {
+        return Err(windows::core::Error::from_win32());
+    }
+    Ok(ptr as usize)
+}
+
+/// 在 `address` 前后 ±2GB 范围内分配内存，优先选择距离最近的空闲区域
+///
+/// 范围内没有足够的空闲区域时返回 None。
+///
+/// # Safety
+///
+/// 调用 Windows API
+pub unsafe fn alloc_near(
+    address: usize,
+    size: usize,
+    executable: bool,
+) -> windows::core::Result<Option<usize>> {
+    let lo = address
+        .saturating_sub(NEAR_RANGE)
+        .max(ALLOCATION_GRANULARITY);
+    let hi = address.saturating_add(NEAR_RANGE);
+
+    // 收集范围内空闲区域中最靠近 address 的候选地址
+    let mut candidates = Vec::new();
+    let mut cursor = lo;
+    while cursor < hi {
+        let mut mbi = MEMORY_BASIC_INFORMATION::default();
+        let len = unsafe {
+            VirtualQuery(
+                Some(cursor as *const c_void),
+                &mut mbi,
+                std::mem::size_of::<MEMORY_BASIC_INFORMATION>(),
+            )
+        };
+        if len == 0 || mbi.RegionSize == 0 {
+            break;
+        }
+        let region_start = mbi.BaseAddress as usize;
+        let region_end = region_start + mbi.RegionSize;
+        if mbi.State == MEM_FREE {
+            let first = align_up(region_start.max(lo), ALLOCATION_GRANULARITY);
+            if first + size <= region_end.min(hi) {
+                candidates.push(first);
+                let last = align_down(region_end.min(hi) - size, ALLOCATION_GRANULARITY);
+                if last > first {
+                    candidates.push(last);
+                }
+            }
+        }
+        cursor = region_end;
+    }
+    candidates.sort_by_key(|candidate| candidate.abs_diff(address));
+
+    for candidate in candidates {
+        // 其他线程可能已占用该区域，继续尝试下一个
+        if let Ok(ptr) = unsafe { virtual_alloc(Some(candidate), size, executable) } {
+            return Ok(Some(ptr));
+        }
+    }
+    Ok(None)
+}
+
+/// rel32 可达的范围，留出分配大小的余量
+const NEAR_RANGE: usize = 0x7FF0_0000;
+/// VirtualAlloc 的地址粒度
+const ALLOCATION_GRANULARITY: usize = 0x10000;
+
+fn align_up(value: usize, align: usize) -> usize {
+    value.div_ceil(align) * align
+}
+
+fn align_down(value: usize, align: usize) -> usize {
+    value / align * align
+}
+
+/// 释放 [`alloc_executable`] 分配的内存
+///
+/// # Safety
+///
+/// 调用 Windows API
+pub unsafe fn free_executable(address: usize) -> windows::core::Result<()> {
+    unsafe { VirtualFree(address as *mut c_void, 0, MEM_RELEASE) }
+}
+
 /// VirtualProtect RAII object
 pub struct VirtualProtectGuard {
     old_protect: PAGE_PROTECTION_FLAGS,
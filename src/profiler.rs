@@ -0,0 +1,222 @@
+//! 性能统计计数器
+//!
+//! 记录每帧耗时、Lua 回调耗时、Hook 分发次数和绘制数据大小，
+//! 供统计浮窗使用。
+//!
+//! 同时按脚本和回调名记录调用次数、耗时和错误数，供 `core.stats()` 查询。
+//! 单次回调耗时超出预算时记录事件，在下一帧以 `script_over_budget` 事件发布给脚本。
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        LazyLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+
+/// 单帧统计数据
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameStats {
+    /// 帧间隔（毫秒）
+    pub frame_time_ms: f32,
+    /// Lua 回调总耗时（毫秒）
+    pub lua_time_ms: f32,
+    /// Hook 分发次数
+    pub hook_dispatches: u32,
+    /// 顶点数
+    pub vertices: u32,
+    /// 索引数
+    pub indices: u32,
+    /// 绘制列表数
+    pub draw_lists: u32,
+}
+
+/// 绘制数据大小
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DrawStats {
+    pub vertices: u32,
+    pub indices: u32,
+    pub draw_lists: u32,
+}
+
+/// 单个回调的统计数据
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CallbackStats {
+    pub calls: u64,
+    pub errors: u64,
+    /// 超出预算的次数
+    pub over_budget: u64,
+    /// 平均耗时（微秒）
+    pub avg_us: f64,
+    /// 最大耗时（微秒）
+    pub max_us: u64,
+    #[serde(skip)]
+    total_us: u64,
+    /// 上次发布超出预算事件的时间，用于限制事件频率
+    #[serde(skip)]
+    last_budget_event: Option<Instant>,
+}
+
+/// 回调超出预算事件
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetExceeded {
+    pub script: String,
+    pub callback: String,
+    pub elapsed_us: u64,
+    pub budget_us: u64,
+}
+
+pub struct Profiler {
+    hook_dispatches: AtomicU64,
+    lua_nanos: AtomicU64,
+    /// 单次回调耗时预算（微秒），0 表示不检查
+    budget_us: AtomicU64,
+    inner: Mutex<ProfilerInner>,
+    /// 脚本名 -> 回调名 -> 统计数据
+    scripts: Mutex<HashMap<String, HashMap<String, CallbackStats>>>,
+    budget_events: Mutex<Vec<BudgetExceeded>>,
+}
+
+struct ProfilerInner {
+    last_frame: Option<Instant>,
+    history: VecDeque<FrameStats>,
+}
+
+impl Profiler {
+    /// 保留的历史帧数
+    pub const HISTORY_SIZE: usize = 240;
+    /// 默认的单次回调耗时预算
+    pub const DEFAULT_CALLBACK_BUDGET_MS: f32 = 5.0;
+    /// 同一回调两次超出预算事件的最小间隔
+    const BUDGET_EVENT_INTERVAL: Duration = Duration::from_secs(1);
+
+    pub fn instance() -> &'static Profiler {
+        static INSTANCE: LazyLock<Profiler> = LazyLock::new(|| Profiler {
+            hook_dispatches: AtomicU64::new(0),
+            lua_nanos: AtomicU64::new(0),
+            budget_us: AtomicU64::new(budget_to_us(Profiler::DEFAULT_CALLBACK_BUDGET_MS)),
+            inner: Mutex::new(ProfilerInner {
+                last_frame: None,
+                history: VecDeque::with_capacity(Profiler::HISTORY_SIZE),
+            }),
+            scripts: Mutex::new(HashMap::new()),
+            budget_events: Mutex::new(Vec::new()),
+        });
+        &INSTANCE
+    }
+
+    /// 记录一次 Hook 分发
+    pub fn add_hook_dispatch(&self) {
+        self.hook_dispatches.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 累加 Lua 回调耗时
+    pub fn add_lua_time(&self, elapsed: Duration) {
+        self.lua_nanos
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// 设置单次回调耗时预算，0 表示不检查
+    pub fn set_callback_budget(&self, budget_ms: f32) {
+        self.budget_us
+            .store(budget_to_us(budget_ms), Ordering::Relaxed);
+    }
+
+    /// 记录一次脚本回调，同时累加 Lua 回调耗时
+    pub fn record_callback(&self, script: &str, callback: &str, elapsed: Duration, failed: bool) {
+        self.add_lua_time(elapsed);
+
+        let elapsed_us = elapsed.as_micros() as u64;
+        let budget_us = self.budget_us.load(Ordering::Relaxed);
+
+        let mut scripts = self.scripts.lock();
+        if !scripts.contains_key(script) {
+            scripts.insert(script.to_string(), HashMap::new());
+        }
+        let callbacks = scripts.get_mut(script).unwrap();
+        if !callbacks.contains_key(callback) {
+            callbacks.insert(callback.to_string(), CallbackStats::default());
+        }
+        let stats = callbacks.get_mut(callback).unwrap();
+
+        stats.calls += 1;
+        stats.total_us += elapsed_us;
+        stats.avg_us = stats.total_us as f64 / stats.calls as f64;
+        stats.max_us = stats.max_us.max(elapsed_us);
+        if failed {
+            stats.errors += 1;
+        }
+
+        if budget_us == 0 || elapsed_us <= budget_us {
+            return;
+        }
+        stats.over_budget += 1;
+        if stats
+            .last_budget_event
+            .is_some_and(|last| last.elapsed() < Self::BUDGET_EVENT_INTERVAL)
+        {
+            return;
+        }
+        stats.last_budget_event = Some(Instant::now());
+        self.budget_events.lock().push(BudgetExceeded {
+            script: script.to_string(),
+            callback: callback.to_string(),
+            elapsed_us,
+            budget_us,
+        });
+    }
+
+    /// 所有脚本的回调统计数据
+    pub fn script_stats(&self) -> HashMap<String, HashMap<String, CallbackStats>> {
+        self.scripts.lock().clone()
+    }
+
+    /// 移除脚本的统计数据
+    pub fn remove_script(&self, script: &str) {
+        self.scripts.lock().remove(script);
+    }
+
+    /// 取出待发布的超出预算事件
+    pub fn take_budget_events(&self) -> Vec<BudgetExceeded> {
+        std::mem::take(&mut *self.budget_events.lock())
+    }
+
+    /// 结束当前帧，汇总计数器并重置
+    pub fn end_frame(&self, draw: DrawStats) {
+        let hook_dispatches = self.hook_dispatches.swap(0, Ordering::Relaxed);
+        let lua_nanos = self.lua_nanos.swap(0, Ordering::Relaxed);
+
+        let mut inner = self.inner.lock();
+        let now = Instant::now();
+        let frame_time = inner
+            .last_frame
+            .replace(now)
+            .map(|last| now - last)
+            .unwrap_or_default();
+
+        if inner.history.len() >= Self::HISTORY_SIZE {
+            inner.history.pop_front();
+        }
+        inner.history.push_back(FrameStats {
+            frame_time_ms: frame_time.as_secs_f32() * 1000.0,
+            lua_time_ms: lua_nanos as f32 / 1_000_000.0,
+            hook_dispatches: hook_dispatches as u32,
+            vertices: draw.vertices,
+            indices: draw.indices,
+            draw_lists: draw.draw_lists,
+        });
+    }
+
+    /// 获取历史帧数据，从旧到新
+    pub fn history(&self) -> Vec<FrameStats> {
+        self.inner.lock().history.iter().copied().collect()
+    }
+}
+
+fn budget_to_us(budget_ms: f32) -> u64 {
+    (budget_ms.max(0.0) * 1000.0) as u64
+}
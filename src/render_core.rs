@@ -1,26 +1,93 @@
 use std::cell::OnceCell;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::ffi::c_void;
+use std::io::Read as _;
+use std::path::{Path, PathBuf};
 
 use anyhow::Context as _;
 use cimgui::{Context, DrawData, WindowFocusedFlags, WindowHoveredFlags};
 use cimgui::{FontConfig, FontGlyphRanges, FontId, FontSource, Io, sys as imgui_sys};
 use log::{debug, error};
-use luaf_include::KeyCode;
+use luaf_include::{KeyCode, RenderCallback};
+use parking_lot::Mutex;
 
 use crate::config::Config;
 use crate::extension::CoreAPI;
+use crate::game::scene;
 use crate::input::Input;
 use crate::luavm::LuaVMManager;
+use crate::profiler::{DrawStats, Profiler};
 use crate::{static_mut, static_ref};
 
+pub mod camera;
+mod context;
 mod draw;
+pub mod layer;
+pub mod splash;
+mod stats;
+pub mod texture;
+
+use context::ContextGuard;
 
 static mut IMGUI_CONTEXT: Option<Context> = None;
 
+/// 后备字体的字形范围：基本多文种平面
+const FALLBACK_GLYPH_RANGES: &[u32] = &[0x0020, 0xFFFF, 0];
+
 type InvalidateDeviceFn = extern "C" fn();
 static mut INVALIDATE_DEVICE_FN: OnceCell<Option<InvalidateDeviceFn>> = OnceCell::new();
 
+static EXT_RENDER_CALLBACKS: Mutex<ExtRenderCallbacks> = Mutex::new(ExtRenderCallbacks::new());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RenderStage {
+    /// 菜单显示时调用
+    Imgui,
+    /// 每帧调用
+    Draw,
+}
+
+#[derive(Clone, Copy)]
+struct RenderCallbackEntry {
+    handle: u64,
+    stage: RenderStage,
+    callback: RenderCallback,
+    user_data: usize,
+}
+
+/// 扩展注册的渲染回调
+struct ExtRenderCallbacks {
+    entries: Vec<RenderCallbackEntry>,
+    next_handle: u64,
+}
+
+impl ExtRenderCallbacks {
+    const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            next_handle: 1,
+        }
+    }
+
+    fn add(&mut self, stage: RenderStage, callback: RenderCallback, user_data: usize) -> u64 {
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        self.entries.push(RenderCallbackEntry {
+            handle,
+            stage,
+            callback,
+            user_data,
+        });
+        handle
+    }
+
+    fn remove(&mut self, handle: u64) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|entry| entry.handle != handle);
+        self.entries.len() != len
+    }
+}
+
 pub struct RenderManager {
     /// 是否为DX12
     is_d3d12: bool,
@@ -41,6 +108,7 @@ pub struct RenderManager {
 
 impl RenderManager {
     const DEFAULT_FONT_NAME: &'static str = "SourceHanSansCN-Regular";
+    const FALLBACK_FONT_DIR: &'static str = "lua_framework/fonts/fallback";
     const STD_FONT_SIZE: f32 = 20.0;
     const STD_VIEWPORT_SIZE: f32 = 1080.0;
 
@@ -62,9 +130,25 @@ impl RenderManager {
         core_api.register_function("Render::core_imgui_initialize", imgui_core_initialize as _);
         core_api.register_function("Render::core_imgui_render", imgui_core_render as _);
         core_api.register_function("Render::core_imgui_pre_render", imgui_core_pre_render as _);
+        // 扩展渲染回调
+        core_api.register_function("Render::add_on_imgui_render", add_on_imgui_render as _);
+        core_api.register_function("Render::add_on_draw_render", add_on_draw_render as _);
+        core_api.register_function(
+            "Render::remove_render_callback",
+            remove_render_callback as _,
+        );
     }
 
     pub fn get_mut() -> &'static mut RenderManager {
+        unsafe { &mut *Self::instance_ptr() }
+    }
+
+    /// 只读访问，只需查询状态时使用，避免创建可变引用
+    pub fn get() -> &'static RenderManager {
+        unsafe { &*Self::instance_ptr() }
+    }
+
+    fn instance_ptr() -> *mut RenderManager {
         static mut INSTANCE: Option<RenderManager> = None;
 
         let this = unsafe { static_mut!(INSTANCE) };
@@ -88,9 +172,32 @@ impl RenderManager {
         LuaVMManager::instance().invoke_fn("on_imgui");
     }
 
-    pub fn render_draw(&self, _ctx_raw: *mut imgui_sys::ImGuiContext) {
-        // Lua回调函数 on_draw
-        LuaVMManager::instance().invoke_fn("on_draw");
+    pub fn render_menu_bar(&self) {
+        // Lua回调函数 on_menu
+        LuaVMManager::instance().render_menu_bar();
+    }
+
+    pub fn render_draw(&self, ctx_raw: *mut imgui_sys::ImGuiContext) {
+        // Lua回调函数 on_draw，按绘制层顺序调用
+        LuaVMManager::instance().render_draw_layers();
+        // 扩展回调
+        Self::invoke_ext_callbacks(RenderStage::Draw, ctx_raw);
+    }
+
+    fn invoke_ext_callbacks(stage: RenderStage, ctx_raw: *mut imgui_sys::ImGuiContext) {
+        // 复制一份，允许回调中注销自身
+        let entries = EXT_RENDER_CALLBACKS
+            .lock()
+            .entries
+            .iter()
+            .filter(|entry| entry.stage == stage)
+            .copied()
+            .collect::<Vec<_>>();
+        for entry in entries {
+            unsafe {
+                (entry.callback)(ctx_raw as *mut c_void, entry.user_data as *mut c_void);
+            }
+        }
     }
 
     pub fn fonts_mut(&mut self) -> &mut HashMap<String, FontRegisterSource> {
@@ -101,6 +208,62 @@ impl RenderManager {
         self.fonts.get(name).and_then(|f| f.id)
     }
 
+    /// 注册脚本字体，同名字体被替换，在下一帧重新加载字体后可用
+    ///
+    /// 名称、路径和大小都与已注册字体相同时不做任何操作，避免每次调用都重新加载所有字体。
+    pub fn add_font(
+        &mut self,
+        name: &str,
+        path: PathBuf,
+        size: f32,
+        glyph_ranges: FontGlyphRanges,
+    ) -> Result<(), crate::error::Error> {
+        if name == Self::DEFAULT_FONT_NAME {
+            return Err(crate::error::Error::InvalidValue(
+                "font name other than the default font",
+                name.to_string(),
+            ));
+        }
+        if !size.is_finite() || size <= 0.0 {
+            return Err(crate::error::Error::InvalidValue(
+                "positive font size",
+                size.to_string(),
+            ));
+        }
+        std::fs::metadata(&path).map_err(|e| {
+            crate::error::Error::IoWithContext(e, format!("open font file {}", path.display()))
+        })?;
+        let registered = self.fonts.get(name).is_some_and(|source| {
+            matches!(
+                source.entries.as_slice(),
+                [entry] if entry.data_source == path
+                    && entry.config.as_ref().map(|c| c.size_pixels) == Some(size)
+            )
+        });
+        if registered {
+            return Ok(());
+        }
+
+        self.fonts.insert(
+            name.to_string(),
+            FontRegisterSource {
+                name: name.to_string(),
+                entries: vec![FontRegisterEntry {
+                    data_source: path,
+                    config: Some(FontConfig {
+                        size_pixels: size,
+                        glyph_ranges,
+                        name: Some(name.to_string()),
+                        ..FontConfig::default()
+                    }),
+                }],
+                id: None,
+            },
+        );
+        self.reload_fonts();
+        Ok(())
+    }
+
     /// 重新加载字体
     ///
     /// 此操作仅登记请求，操作会在下一帧生效
@@ -152,26 +315,84 @@ impl RenderManager {
     }
 
     /// 注册默认字体
+    ///
+    /// 后备字体目录中的字体合并到默认字体中，补充默认字体缺少的字形（如日文汉字、特殊符号），
+    /// 避免显示游戏文本时出现方块。
     fn register_default_fonts(&mut self) -> anyhow::Result<()> {
         let font_size = self.get_font_size();
 
+        let mut entries = vec![FontRegisterEntry {
+            data_source: PathBuf::from("lua_framework/fonts/SourceHanSansCN-Regular.otf"),
+            config: Some(FontConfig {
+                size_pixels: font_size,
+                glyph_ranges: FontGlyphRanges::chinese_full(),
+                name: Some(Self::DEFAULT_FONT_NAME.to_string()),
+                ..FontConfig::default()
+            }),
+        }];
+        entries.extend(
+            Self::fallback_font_paths()
+                .into_iter()
+                .map(|path| FontRegisterEntry {
+                    data_source: path,
+                    config: Some(FontConfig {
+                        size_pixels: font_size,
+                        glyph_ranges: FontGlyphRanges::from_slice(FALLBACK_GLYPH_RANGES),
+                        ..FontConfig::default()
+                    }),
+                }),
+        );
+
         self.register_font(FontRegisterSource {
             name: Self::DEFAULT_FONT_NAME.to_string(),
-            entries: vec![FontRegisterEntry {
-                data_source: PathBuf::from("lua_framework/fonts/SourceHanSansCN-Regular.otf"),
-                config: Some(FontConfig {
-                    size_pixels: font_size,
-                    glyph_ranges: FontGlyphRanges::chinese_full(),
-                    name: Some(Self::DEFAULT_FONT_NAME.to_string()),
-                    ..FontConfig::default()
-                }),
-            }],
+            entries,
             id: None,
         })?;
 
         Ok(())
     }
 
+    /// 后备字体目录中的字体文件，按文件名排序
+    fn fallback_font_paths() -> Vec<PathBuf> {
+        let Ok(dir) = std::fs::read_dir(Self::FALLBACK_FONT_DIR) else {
+            return Vec::new();
+        };
+        let mut paths = dir
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| {
+                        ["ttf", "otf", "ttc"]
+                            .iter()
+                            .any(|e| ext.eq_ignore_ascii_case(e))
+                    })
+            })
+            .filter(|path| match Self::check_font_file(path) {
+                Ok(()) => true,
+                Err(e) => {
+                    log::warn!("Skipping fallback font '{}': {}", path.display(), e);
+                    false
+                }
+            })
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths
+    }
+
+    /// 检查字体文件可读且文件头为 TrueType/OpenType 格式，避免损坏的后备字体导致默认字体加载失败
+    fn check_font_file(path: &Path) -> std::io::Result<()> {
+        let mut header = [0u8; 4];
+        std::fs::File::open(path)?.read_exact(&mut header)?;
+        match &header {
+            [0x00, 0x01, 0x00, 0x00] | b"OTTO" | b"true" | b"ttcf" => Ok(()),
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "not a TrueType/OpenType font",
+            )),
+        }
+    }
+
     /// 读取字体大小，如果没有配置则使用默认字体大小
     fn get_font_size(&self) -> f32 {
         let mut font_size = Config::global().ui.font_size;
@@ -208,6 +429,8 @@ pub struct UIContext {
     pub need_reload_fonts: bool,
     pub need_invalidate_devices: bool,
     pub change_menu_key: bool,
+    /// 正在录制按键的快捷键：(脚本名, 绑定名称)
+    pub capture_hotkey: Option<(String, String)>,
 }
 
 pub unsafe extern "C" fn imgui_core_initialize(
@@ -216,11 +439,14 @@ pub unsafe extern "C" fn imgui_core_initialize(
     d3d12: bool,
 ) -> *mut imgui_sys::ImGuiContext {
     // 创建 Context
-    // let context = static_mut!(IMGUI_CONTEXT);
     let context = unsafe { static_mut!(IMGUI_CONTEXT) };
     if context.is_none() {
+        // 其他工具的 Context 为当前 Context 时无法创建新 Context，先暂时切换到空 Context
+        let _guard = ContextGuard::enter();
         *context = Some(Context::create());
+        context::set_own_context(unsafe { imgui_sys::igGetCurrentContext() });
     }
+    let _guard = ContextGuard::enter();
 
     let render_manager = RenderManager::get_mut();
 
@@ -248,10 +474,11 @@ pub unsafe extern "C" fn imgui_core_initialize(
         render_manager.viewport_size, render_manager.window_size, d3d12,
     );
 
-    unsafe { imgui_sys::igGetCurrentContext() }
+    context::own_context()
 }
 
 pub unsafe extern "C" fn imgui_core_pre_render() {
+    let _guard = ContextGuard::enter();
     let render_manager = RenderManager::get_mut();
     let ui_context = render_manager.ui_context_mut();
 
@@ -266,6 +493,9 @@ pub unsafe extern "C" fn imgui_core_pre_render() {
         debug!("Fonts reloaded");
     };
 
+    // 上传脚本加载的纹理
+    texture::TextureManager::instance().process_pending();
+
     if ui_context.need_invalidate_devices {
         ui_context.need_invalidate_devices = false;
         if let Some(invalidate_device) = get_invalidate_device_fn() {
@@ -276,6 +506,8 @@ pub unsafe extern "C" fn imgui_core_pre_render() {
 }
 
 pub unsafe extern "C" fn imgui_core_render() -> *mut imgui_sys::ImDrawData {
+    let _guard = ContextGuard::enter();
+
     unsafe {
         let render_manager = RenderManager::get_mut();
 
@@ -301,7 +533,11 @@ pub unsafe extern "C" fn imgui_core_render() -> *mut imgui_sys::ImDrawData {
             io.mouse_draw_cursor = any_focusing || any_hovering;
         }
 
-        if render_manager.show {
+        // 过场动画和加载时自动隐藏
+        let auto_hidden =
+            Config::global().ui.hide_in_cutscene && (scene::is_cutscene() || scene::is_loading());
+
+        if render_manager.show && !auto_hidden {
             // 设置默认字体
             let mut has_default_font = false;
             if let Some(font_id) = render_manager.get_font(RenderManager::DEFAULT_FONT_NAME) {
@@ -315,6 +551,15 @@ pub unsafe extern "C" fn imgui_core_render() -> *mut imgui_sys::ImDrawData {
                 render_manager.render_imgui();
             });
 
+            // 脚本注册的主菜单栏
+            render_manager.render_menu_bar();
+
+            // 扩展回调
+            RenderManager::invoke_ext_callbacks(
+                RenderStage::Imgui,
+                imgui_sys::igGetCurrentContext(),
+            );
+
             if has_default_font {
                 imgui_sys::igPopFont();
             }
@@ -324,13 +569,49 @@ pub unsafe extern "C" fn imgui_core_render() -> *mut imgui_sys::ImDrawData {
         let ctx_ptr = imgui_sys::igGetCurrentContext();
         render_manager.render_draw(ctx_ptr);
 
+        // 性能统计浮窗
+        if Config::global().ui.show_stats_overlay && !auto_hidden {
+            stats::draw_stats_overlay(ui);
+        }
+
+        // 启动状态浮窗，不受自动隐藏影响以便加载期间可见
+        if Config::global().ui.show_startup_status {
+            splash::draw_startup_overlay(ui);
+        }
+
         ui.end_frame_early();
 
         // 渲染并返回绘制数据
-        ctx.render() as *const DrawData as *mut imgui_sys::ImDrawData
+        let draw_data = ctx.render();
+        Profiler::instance().end_frame(DrawStats {
+            vertices: draw_data.total_vtx_count as u32,
+            indices: draw_data.total_idx_count as u32,
+            draw_lists: draw_data.draw_lists_count() as u32,
+        });
+
+        draw_data as *const DrawData as *mut imgui_sys::ImDrawData
     }
 }
 
+/// 注册菜单显示时调用的渲染回调，返回用于注销的句柄
+extern "C" fn add_on_imgui_render(callback: RenderCallback, user_data: *mut c_void) -> u64 {
+    EXT_RENDER_CALLBACKS
+        .lock()
+        .add(RenderStage::Imgui, callback, user_data as usize)
+}
+
+/// 注册每帧调用的渲染回调，返回用于注销的句柄
+extern "C" fn add_on_draw_render(callback: RenderCallback, user_data: *mut c_void) -> u64 {
+    EXT_RENDER_CALLBACKS
+        .lock()
+        .add(RenderStage::Draw, callback, user_data as usize)
+}
+
+/// 注销渲染回调
+extern "C" fn remove_render_callback(handle: u64) -> bool {
+    EXT_RENDER_CALLBACKS.lock().remove(handle)
+}
+
 fn get_invalidate_device_fn() -> Option<InvalidateDeviceFn> {
     unsafe {
         let fun = static_ref!(INVALIDATE_DEVICE_FN).get_or_init(|| {
@@ -0,0 +1,114 @@
+//! 游戏相机投影
+//!
+//! 读取游戏相机的观察矩阵和投影矩阵，将世界坐标投影到屏幕坐标，用于在世界中的位置绘制标记。
+//! 矩阵为行主序，坐标以行向量右乘矩阵，与 D3D 一致。
+
+use crate::{address::AddressRepository, game::mt_type::MtVector3, memory::MemoryUtils};
+
+type Matrix = [[f32; 4]; 4];
+
+/// 相机对象成员偏移
+mod offsets {
+    /// 观察矩阵
+    pub const VIEW: usize = 0x180;
+    /// 投影矩阵
+    pub const PROJECTION: usize = 0x1C0;
+}
+
+/// `mov reg, [rip+disp32]` 指令长度和 disp32 的偏移
+const LOAD_INSTRUCTION_LEN: usize = 7;
+const LOAD_DISPLACEMENT_OFFSET: usize = 3;
+
+/// 将世界坐标投影到屏幕坐标，位于相机后方或相机不可用时返回 None
+pub fn world_to_screen(position: MtVector3, display_size: [f32; 2]) -> Option<[f32; 2]> {
+    let camera = get_camera()?;
+    let view = read_matrix(camera + offsets::VIEW)?;
+    let projection = read_matrix(camera + offsets::PROJECTION)?;
+
+    project(&multiply(&view, &projection), position, display_size)
+}
+
+/// 相机对象地址，由加载相机全局指针的指令解析
+fn get_camera() -> Option<usize> {
+    let instruction =
+        AddressRepository::instance().get_address_deferred(AddressRepository::CAMERA_INSTANCE)?;
+    let displacement = MemoryUtils::read(instruction + LOAD_DISPLACEMENT_OFFSET, 4, true).ok()?;
+    let displacement = i32::from_le_bytes(displacement.try_into().ok()?);
+    let global = (instruction + LOAD_INSTRUCTION_LEN).wrapping_add_signed(displacement as isize);
+
+    let camera = MemoryUtils::read(global, 8, true).ok()?;
+    let camera = usize::from_le_bytes(camera.try_into().ok()?);
+    (camera != 0).then_some(camera)
+}
+
+fn read_matrix(address: usize) -> Option<Matrix> {
+    let bytes = MemoryUtils::read(address, size_of::<Matrix>(), true).ok()?;
+    let mut matrix = [[0.0; 4]; 4];
+    for (i, value) in bytes.chunks_exact(4).enumerate() {
+        matrix[i / 4][i % 4] = f32::from_le_bytes(value.try_into().ok()?);
+    }
+    Some(matrix)
+}
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut result = [[0.0; 4]; 4];
+    for (i, row) in result.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..4).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    result
+}
+
+fn project(
+    view_projection: &Matrix,
+    position: MtVector3,
+    display_size: [f32; 2],
+) -> Option<[f32; 2]> {
+    let input = [position.x, position.y, position.z, 1.0];
+    let mut clip = [0.0f32; 4];
+    for (j, value) in clip.iter_mut().enumerate() {
+        *value = (0..4).map(|i| input[i] * view_projection[i][j]).sum();
+    }
+    // w 为到相机平面的距离，不大于 0 时位于相机后方
+    if clip[3] <= f32::EPSILON {
+        return None;
+    }
+
+    let ndc_x = clip[0] / clip[3];
+    let ndc_y = clip[1] / clip[3];
+    Some([
+        (ndc_x + 1.0) * 0.5 * display_size[0],
+        (1.0 - ndc_y) * 0.5 * display_size[1],
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project() {
+        // 透视投影：w = z
+        let matrix = [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 1.0],
+            [0.0, 0.0, 0.0, 0.0],
+        ];
+        let size = [1920.0, 1080.0];
+
+        assert_eq!(
+            project(&matrix, MtVector3::new(0.0, 0.0, 10.0), size),
+            Some([960.0, 540.0])
+        );
+        assert_eq!(
+            project(&matrix, MtVector3::new(5.0, 5.0, 10.0), size),
+            Some([1440.0, 270.0])
+        );
+        assert_eq!(
+            project(&matrix, MtVector3::new(0.0, 0.0, -10.0), size),
+            None
+        );
+    }
+}
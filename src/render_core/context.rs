@@ -0,0 +1,67 @@
+//! ImGui Context 切换保护
+//!
+//! 其他覆盖层工具可能创建自己的 ImGui Context 并设为当前 Context。
+//! 框架的渲染入口在进入时切换到自身的 Context，离开时恢复原 Context。
+
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+use cimgui::sys as imgui_sys;
+use log::warn;
+
+/// 框架自身的 Context
+static OWN_CONTEXT: AtomicPtr<imgui_sys::ImGuiContext> = AtomicPtr::new(std::ptr::null_mut());
+/// 最近一次检测到的外部 Context，用于避免重复警告
+static LAST_FOREIGN_CONTEXT: AtomicPtr<imgui_sys::ImGuiContext> =
+    AtomicPtr::new(std::ptr::null_mut());
+
+pub fn set_own_context(ctx: *mut imgui_sys::ImGuiContext) {
+    OWN_CONTEXT.store(ctx, Ordering::Release);
+}
+
+pub fn own_context() -> *mut imgui_sys::ImGuiContext {
+    OWN_CONTEXT.load(Ordering::Acquire)
+}
+
+/// 切换到框架 Context，离开作用域时恢复原 Context
+pub struct ContextGuard {
+    previous: *mut imgui_sys::ImGuiContext,
+}
+
+impl ContextGuard {
+    /// 切换到框架 Context
+    ///
+    /// 框架 Context 尚未创建时，切换到空 Context。
+    pub fn enter() -> Self {
+        let own = own_context();
+        let previous = unsafe { imgui_sys::igGetCurrentContext() };
+
+        if previous != own {
+            if !previous.is_null() {
+                check_foreign_context(previous);
+            }
+            unsafe { imgui_sys::igSetCurrentContext(own) };
+        }
+
+        Self { previous }
+    }
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        // 仅恢复外部 Context
+        if !self.previous.is_null() && self.previous != own_context() {
+            unsafe { imgui_sys::igSetCurrentContext(self.previous) };
+        }
+    }
+}
+
+fn check_foreign_context(ctx: *mut imgui_sys::ImGuiContext) {
+    let last = LAST_FOREIGN_CONTEXT.swap(ctx, Ordering::AcqRel);
+    if last != ctx {
+        warn!(
+            "Foreign ImGui context {:p} is active, switching to LuaFramework context {:p} during rendering.",
+            ctx,
+            own_context()
+        );
+    }
+}
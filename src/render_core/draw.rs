@@ -1,12 +1,18 @@
 use std::collections::HashMap;
 
-use cimgui::TreeNodeFlags;
+use cimgui::{Condition, MouseButton, TreeNodeFlags};
+use luaf_include::LogLevel;
 use strum::IntoEnumIterator;
 
 use super::RenderManager;
-use crate::config::Config;
-use crate::input::Input;
-use crate::luavm::LuaVMManager;
+use super::layer::{self, DrawLayer};
+use crate::config::{Config, WindowLayout};
+use crate::error::Language;
+use crate::input::{self, Input};
+use crate::luavm::PatchProfileManager;
+use crate::luavm::capability::{self, UnsafeCapability};
+use crate::luavm::safety::SafetyPolicy;
+use crate::luavm::{LuaVMManager, ReloadRequest};
 
 pub fn draw_basic_window<F>(ui: &cimgui::Ui, script_ui_draw: F)
 where
@@ -14,23 +20,71 @@ where
 {
     let render_manager = RenderManager::get_mut();
 
-    ui.window("Lua Framework")
+    let saved_layout = Config::global().ui.layout.clone();
+    let mut layout = saved_layout.clone();
+
+    let mut window = ui
+        .window("Lua Framework")
         .focus_on_appearing(false)
         .opened(&mut render_manager.show)
-        .build(|| {
-            ui.text(concat!("Lua Framework v", env!("CARGO_PKG_VERSION")));
-            ui.text("Default menu key: F7");
+        .collapsed(saved_layout.collapsed, Condition::FirstUseEver);
+    if let Some(position) = saved_layout.position {
+        window = window.position(position, Condition::FirstUseEver);
+    }
+    if let Some(size) = saved_layout.size {
+        window = window.size(size, Condition::FirstUseEver);
+    }
 
-            draw_options_tab(ui);
+    let built = window.build(|| {
+        layout.position = Some(ui.window_pos());
+        layout.size = Some(ui.window_size());
 
-            draw_script_manager_tab(ui);
+        ui.text(concat!("Lua Framework v", env!("CARGO_PKG_VERSION")));
+        ui.text("Default menu key: F7");
+        if SafetyPolicy::instance().is_restricted() {
+            ui.text_colored(
+                [1.0, 0.6, 0.0, 1.0],
+                "Online session: memory writes and native calls are blocked",
+            ); // orange
+        }
 
-            draw_script_generated_tab(ui, script_ui_draw);
-        });
+        draw_options_tab(ui, &mut layout);
+
+        draw_script_manager_tab(ui, &mut layout);
+
+        draw_hotkeys_tab(ui, &mut layout);
+
+        draw_script_generated_tab(ui, &mut layout, script_ui_draw);
+    });
+    layout.collapsed = built.is_none();
+
+    // 拖动或缩放结束后再保存布局
+    if layout != saved_layout && !ui.is_mouse_down(MouseButton::Left) {
+        Config::global_mut().ui.layout = layout;
+    }
+}
+
+/// 折叠标题，展开状态记录到布局中
+fn collapsing_header(ui: &cimgui::Ui, label: &str, layout: &mut WindowLayout) -> bool {
+    let was_open = layout.open_headers.iter().any(|h| h == label);
+    let flags = if was_open {
+        TreeNodeFlags::DEFAULT_OPEN
+    } else {
+        TreeNodeFlags::empty()
+    };
+
+    let open = ui.collapsing_header(label, flags);
+    if open && !was_open {
+        layout.open_headers.push(label.to_string());
+    } else if !open && was_open {
+        layout.open_headers.retain(|h| h != label);
+    }
+
+    open
 }
 
-pub fn draw_options_tab(ui: &cimgui::Ui) {
-    if !ui.collapsing_header("Options", TreeNodeFlags::empty()) {
+pub fn draw_options_tab(ui: &cimgui::Ui, layout: &mut WindowLayout) {
+    if !collapsing_header(ui, "Options", layout) {
         return;
     };
 
@@ -68,16 +122,23 @@ pub fn draw_options_tab(ui: &cimgui::Ui) {
     let render_manager = RenderManager::get_mut();
     let menu_key = render_manager.menu_key;
     let button_label = if render_manager.ui_context_mut().change_menu_key {
-        "Press any key..."
+        "Press any key...".to_string()
     } else {
-        menu_key.into()
+        // 显示当前键盘布局下的按键名称
+        let key_name: &'static str = menu_key.into();
+        let display_name = input::layout::display_name(menu_key);
+        if display_name.eq_ignore_ascii_case(key_name) {
+            display_name
+        } else {
+            format!("{} ({})", display_name, key_name)
+        }
     };
 
     ui.text("Menu Key");
     ui.same_line_with_spacing(0.0, 5.0);
     {
         let _width_guard = ui.push_item_width(font_size * 3.0);
-        if ui.button(button_label) {
+        if ui.button(format!("{}##menu_key", button_label)) {
             let change_signal = render_manager.ui_context_mut().change_menu_key;
             render_manager.ui_context_mut().change_menu_key = !change_signal;
         }
@@ -93,17 +154,357 @@ pub fn draw_options_tab(ui: &cimgui::Ui) {
             }
         }
     }
+
+    // 性能统计浮窗
+    let mut show_stats_overlay = Config::global().ui.show_stats_overlay;
+    if ui.checkbox("Show stats overlay", &mut show_stats_overlay) {
+        Config::global_mut().ui.show_stats_overlay = show_stats_overlay;
+    }
+
+    // 手柄确认/取消键
+    let confirm_buttons = input::remap::resolve(input::remap::CONFIRM).unwrap_or_default();
+    let confirm_label = confirm_buttons
+        .iter()
+        .map(|b| <&'static str>::from(*b))
+        .collect::<Vec<_>>()
+        .join("/");
+    ui.text(format!("Controller confirm: {}", confirm_label));
+    ui.same_line_with_spacing(0.0, 5.0);
+    if ui.button("Swap confirm/cancel") {
+        input::remap::swap_confirm_cancel();
+    }
+
+    // 过场动画和加载时自动隐藏
+    let mut hide_in_cutscene = Config::global().ui.hide_in_cutscene;
+    if ui.checkbox("Hide during cutscenes and loading", &mut hide_in_cutscene) {
+        Config::global_mut().ui.hide_in_cutscene = hide_in_cutscene;
+    }
+
+    // 启动状态浮窗
+    let mut show_startup_status = Config::global().ui.show_startup_status;
+    if ui.checkbox("Show startup status", &mut show_startup_status) {
+        Config::global_mut().ui.show_startup_status = show_startup_status;
+    }
+
+    // 自动保存间隔
+    let mut autosave_interval = Config::global().autosave.interval_minutes;
+    if ui.slider("Autosave interval (min)", 0, 60, &mut autosave_interval) {
+        Config::global_mut().autosave.interval_minutes = autosave_interval;
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text("0 disables periodic autosave");
+    }
+
+    // 日志语言，与界面语言无关
+    let languages = Language::iter().collect::<Vec<_>>();
+    let current_language = Config::global().log.language;
+    let mut language_index = languages
+        .iter()
+        .position(|l| *l == current_language)
+        .unwrap_or_default();
+    if ui.combo("Log language", &mut language_index, &languages, |l| {
+        l.display_name().into()
+    }) {
+        let language = languages[language_index];
+        crate::error::set_log_language(language);
+        Config::global_mut().log.language = language;
+    }
+
+    draw_log_settings(ui);
+
+    // 脚本文件变化时自动重载
+    let mut hot_reload = Config::global().scripts.hot_reload;
+    if ui.checkbox("Reload scripts on file change", &mut hot_reload) {
+        Config::global_mut().scripts.hot_reload = hot_reload;
+    }
+
+    // 联机时禁用不安全模式
+    let mut disable_unsafe_online = Config::global().scripts.disable_unsafe_online;
+    if ui.checkbox(
+        "Disable unsafe mode, memory writes and native calls in online sessions",
+        &mut disable_unsafe_online,
+    ) {
+        Config::global_mut().scripts.disable_unsafe_online = disable_unsafe_online;
+    }
+
+    // 允许脚本注入键盘输入
+    let mut allow_injection = Config::global().input.allow_injection;
+    if ui.checkbox(
+        "Allow scripts to inject keyboard input",
+        &mut allow_injection,
+    ) {
+        Config::global_mut().input.allow_injection = allow_injection;
+    }
+
+    draw_input_settings(ui);
+
+    draw_layers(ui);
+
+    draw_safety_policy(ui);
+
+    draw_log_channels(ui);
+
+    draw_patch_profiles(ui);
+}
+
+/// 手柄死区与按键重复设置
+fn draw_input_settings(ui: &cimgui::Ui) {
+    ui.text("Input");
+    let mut stick_dead_zone = Config::global().input.stick_dead_zone;
+    if ui.slider("Stick dead zone", 0.0, 0.9, &mut stick_dead_zone) {
+        Config::global_mut().input.stick_dead_zone = stick_dead_zone;
+    }
+    let mut trigger_dead_zone = Config::global().input.trigger_dead_zone;
+    if ui.slider("Trigger dead zone", 0.0, 0.9, &mut trigger_dead_zone) {
+        Config::global_mut().input.trigger_dead_zone = trigger_dead_zone;
+    }
+    let mut repeat_delay = Config::global().input.repeat_delay_ms;
+    if ui.slider("Key repeat delay (ms)", 100, 1000, &mut repeat_delay) {
+        Config::global_mut().input.repeat_delay_ms = repeat_delay;
+    }
+    let mut repeat_interval = Config::global().input.repeat_interval_ms;
+    if ui.slider("Key repeat interval (ms)", 0, 500, &mut repeat_interval) {
+        Config::global_mut().input.repeat_interval_ms = repeat_interval;
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text("0 disables key repeat");
+    }
 }
 
-fn draw_script_manager_tab(ui: &cimgui::Ui) {
-    if !ui.collapsing_header("Script Manager", TreeNodeFlags::empty()) {
+/// 日志等级与输出设置，立即生效
+fn draw_log_settings(ui: &cimgui::Ui) {
+    const LEVELS: [LogLevel; 5] = [
+        LogLevel::Trace,
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+    ];
+
+    let settings = crate::logger::settings();
+    let mut level_index = LEVELS
+        .iter()
+        .position(|l| *l == settings.level)
+        .unwrap_or_default();
+    if ui.combo("Log level", &mut level_index, &LEVELS, |l| {
+        format!("{:?}", l).into()
+    }) {
+        crate::logger::set_level(LEVELS[level_index]);
+    }
+
+    let mut log_to_console = settings.log_to_console;
+    if ui.checkbox("Log to console", &mut log_to_console) {
+        crate::logger::set_log_to_console(log_to_console);
+    }
+    let mut log_to_file = settings.log_to_file;
+    if ui.checkbox("Log to file", &mut log_to_file)
+        && let Err(e) = crate::logger::set_log_to_file(log_to_file)
+    {
+        log::error!("Failed to open log file: {}", e);
+    }
+
+    let mut log_file_path = settings.log_file_path;
+    if ui
+        .input_text("Log file", &mut log_file_path)
+        .enter_returns_true(true)
+        .build()
+        && let Err(e) = crate::logger::set_log_file_path(&log_file_path)
+    {
+        log::error!("Failed to open log file '{}': {}", log_file_path, e);
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text("Press Enter to apply");
+    }
+}
+
+/// 绘制层开关
+fn draw_layers(ui: &cimgui::Ui) {
+    ui.text("Draw Layers");
+    for layer in DrawLayer::iter() {
+        let name: &'static str = layer.into();
+        let mut enabled = layer::is_enabled(layer);
+        if ui.checkbox(format!("{}##draw_layer", name), &mut enabled) {
+            layer::set_enabled(layer, enabled);
+        }
+    }
+}
+
+fn draw_patch_profiles(ui: &cimgui::Ui) {
+    let manager = PatchProfileManager::instance();
+    let profiles = manager.profiles();
+    if profiles.is_empty() {
+        return;
+    }
+
+    ui.text("Patch Profiles");
+    for profile in profiles {
+        let name = profile.name;
+        let mut checked = profile.applied;
+        if ui.checkbox(format!("{}##patch_profile_{}", name, name), &mut checked)
+            && let Err(e) = manager.set_enabled(&name, checked)
+        {
+            log::error!("Failed to toggle patch profile '{}': {}", name, e.log());
+        }
+        if let Some(mismatch) = profile.mismatch {
+            ui.same_line();
+            ui.text_disabled("(revision mismatch)");
+            if ui.is_item_hovered() {
+                ui.tooltip_text(mismatch);
+            }
+        }
+    }
+}
+
+fn draw_log_channels(ui: &cimgui::Ui) {
+    let channels = crate::logger::channels();
+    if channels.is_empty() {
+        return;
+    }
+
+    ui.text("Log Channels");
+    for channel in channels {
+        let mut enabled = channel.enabled;
+        if ui.checkbox(
+            format!("{}##channel_{}", channel.name, channel.name),
+            &mut enabled,
+        ) {
+            crate::logger::set_channel_enabled(&channel.name, enabled);
+        }
+        ui.same_line_with_spacing(0.0, 10.0);
+        let mut to_file = channel.to_file;
+        if ui.checkbox(
+            format!("Write to file##channel_file_{}", channel.name),
+            &mut to_file,
+        ) {
+            crate::logger::set_channel_to_file(&channel.name, to_file);
+        }
+    }
+}
+
+fn draw_safety_policy(ui: &cimgui::Ui) {
+    // 与 "Disable unsafe mode in online sessions" 共用同一开关
+    if !Config::global().scripts.disable_unsafe_online {
+        return;
+    }
+
+    // 被拦截的脚本，等待用户放行
+    for name in SafetyPolicy::instance().blocked_scripts() {
+        ui.text_colored([1.0, 0.6, 0.0, 1.0], format!("Blocked: {}", name));
+        ui.same_line_with_spacing(0.0, 5.0);
+        if ui.button(format!("Allow##allow_{}", name)) {
+            SafetyPolicy::instance().allow_script(&name);
+        }
+    }
+    // 已放行的脚本
+    let allowed_scripts = Config::global().safety.allowed_scripts.clone();
+    for name in allowed_scripts {
+        ui.text(format!("Allowed online: {}", name));
+        ui.same_line_with_spacing(0.0, 5.0);
+        if ui.button(format!("Revoke##revoke_{}", name)) {
+            SafetyPolicy::instance().revoke_script(&name);
+        }
+    }
+}
+
+fn hotkey_label(keys: &[input::KeyCode]) -> String {
+    if keys.is_empty() {
+        return "None".to_string();
+    }
+    keys.iter()
+        .map(|key| input::layout::display_name(*key))
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+fn draw_hotkeys_tab(ui: &cimgui::Ui, layout: &mut WindowLayout) {
+    if !collapsing_header(ui, "Hotkeys", layout) {
+        return;
+    };
+
+    let manager = input::hotkey::HotkeyManager::instance();
+    let render_manager = RenderManager::get_mut();
+    let mut bindings = manager.bindings();
+    // 录制中的绑定已被脚本移除
+    let ui_context = render_manager.ui_context_mut();
+    if let Some((script, name)) = &ui_context.capture_hotkey
+        && !bindings
+            .iter()
+            .any(|b| &b.script == script && &b.name == name)
+    {
+        ui_context.capture_hotkey = None;
+        manager.set_capturing(false);
+    }
+    if bindings.is_empty() {
+        ui.text_disabled("No hotkeys registered");
+        return;
+    }
+    bindings.sort_by(|a, b| (&a.script, &a.name).cmp(&(&b.script, &b.name)));
+
+    for binding in bindings {
+        let id = (binding.script.clone(), binding.name.clone());
+        let capturing = render_manager.ui_context_mut().capture_hotkey.as_ref() == Some(&id);
+
+        ui.text(format!("{}: {}", binding.script, binding.name));
+        ui.same_line_with_spacing(0.0, 5.0);
+        let button_label = if capturing {
+            "Press keys...".to_string()
+        } else {
+            hotkey_label(&binding.keys)
+        };
+        if ui.button(format!("{}##hotkey_{}_{}", button_label, id.0, id.1)) {
+            let capture = if capturing { None } else { Some(id.clone()) };
+            manager.set_capturing(capture.is_some());
+            render_manager.ui_context_mut().capture_hotkey = capture;
+        }
+        if binding.keys != binding.default_keys {
+            ui.same_line_with_spacing(0.0, 5.0);
+            if ui.button(format!("Reset##hotkey_reset_{}_{}", id.0, id.1)) {
+                manager.rebind(&id.0, &id.1, binding.default_keys.clone());
+            }
+        }
+
+        let conflicts = manager.conflicts(&id.0, &id.1);
+        if !conflicts.is_empty() {
+            let names = conflicts
+                .iter()
+                .map(|(script, name)| format!("{}: {}", script, name))
+                .collect::<Vec<_>>()
+                .join(", ");
+            ui.text_colored([1.0, 0.0, 0.0, 1.0], format!("Conflicts with {}", names)); // red
+        }
+    }
+
+    // 录制组合键：按下非修饰键时结束，Esc 取消
+    if let Some((script, name)) = render_manager.ui_context_mut().capture_hotkey.clone() {
+        let keyboard = Input::instance().keyboard();
+        if keyboard.is_pressed(input::KeyCode::Escape) {
+            render_manager.ui_context_mut().capture_hotkey = None;
+            manager.set_capturing(false);
+            return;
+        }
+        let pressed = input::KeyCode::iter()
+            .find(|key| !input::hotkey::is_modifier(*key) && keyboard.is_pressed(*key));
+        if let Some(key) = pressed {
+            let mut keys = input::KeyCode::iter()
+                .filter(|key| input::hotkey::is_modifier(*key) && keyboard.is_down(*key))
+                .collect::<Vec<_>>();
+            keys.push(key);
+            manager.rebind(&script, &name, keys);
+            render_manager.ui_context_mut().capture_hotkey = None;
+            manager.set_capturing(false);
+        }
+    }
+}
+
+fn draw_script_manager_tab(ui: &cimgui::Ui, layout: &mut WindowLayout) {
+    if !collapsing_header(ui, "Script Manager", layout) {
         return;
     };
 
     if ui.button("Reload All")
         && let Err(e) = LuaVMManager::instance().reload_physical_vms()
     {
-        log::error!("Failed to reload all scripts: {}", e);
+        log::error!("Failed to reload all scripts: {}", e.log());
     }
 
     ui.same_line_with_spacing(0.0, 5.0);
@@ -117,8 +518,13 @@ fn draw_script_manager_tab(ui: &cimgui::Ui) {
         }
     }
     ui.same_line_with_spacing(0.0, 5.0);
-    if ui.button("Spawn Console") {
-        crate::logger::spawn_logger_console();
+    let console_label = if crate::logger::is_logger_console_visible() {
+        "Hide Console"
+    } else {
+        "Show Console"
+    };
+    if ui.button(console_label) {
+        crate::logger::toggle_logger_console();
     }
 
     // 显示最后错误信息
@@ -139,24 +545,25 @@ fn draw_script_manager_tab(ui: &cimgui::Ui) {
 
     let mut changed = false;
     let _ = LuaVMManager::instance().run_with_lock_mut(|inner| {
-        // Name -> Checked
+        // Name -> (Checked, Requested capabilities)
         let mut all_vms = HashMap::new();
 
         inner
             .iter_vms()
             .filter(|(_, vm)| !vm.is_virtual())
             .for_each(|(_, vm)| {
-                all_vms.insert(vm.name().to_string(), true);
+                let requested = capability::requested_capabilities(vm.lua());
+                all_vms.insert(vm.name().to_string(), (true, requested));
             });
         inner.disabled_vms().for_each(|name| {
-            all_vms.insert(name.to_string(), false);
+            all_vms.insert(name.to_string(), (false, Vec::new()));
         });
         // 排序
         let mut sorted_vms = all_vms.into_iter().collect::<Vec<_>>();
         sorted_vms.sort_by(|(name, _), (name2, _)| name.cmp(name2));
 
         // 显示checkbox
-        sorted_vms.iter().for_each(|(name, checked)| {
+        sorted_vms.iter().for_each(|(name, (checked, requested))| {
             let mut checked = *checked;
             if ui.checkbox(name, &mut checked) {
                 if checked {
@@ -166,21 +573,52 @@ fn draw_script_manager_tab(ui: &cimgui::Ui) {
                 }
                 changed = true;
             }
+            if *checked {
+                ui.same_line_with_spacing(0.0, 5.0);
+                if ui.button(format!("Reload##reload_{}", name)) {
+                    LuaVMManager::instance().request_reload(ReloadRequest::Script(name.clone()));
+                }
+            }
+            draw_script_capabilities(ui, name, requested);
         });
 
         Ok(())
     });
 
     if changed && let Err(e) = LuaVMManager::instance().reload_physical_vms() {
-        log::error!("Failed to reload all scripts: {}", e);
+        log::error!("Failed to reload all scripts: {}", e.log());
+    }
+}
+
+/// 显示脚本的不安全权限开关
+fn draw_script_capabilities(ui: &cimgui::Ui, script_name: &str, requested: &[UnsafeCapability]) {
+    let Some(_node) = ui.tree_node(format!("Permissions##{}", script_name)) else {
+        return;
+    };
+
+    for capability in UnsafeCapability::iter() {
+        let mut allowed = capability::is_allowed(script_name, capability);
+        let label = format!(
+            "{}##{}_{}",
+            capability.name(),
+            script_name,
+            capability.name()
+        );
+        if ui.checkbox(label, &mut allowed) {
+            capability::set_allowed(script_name, capability, allowed);
+        }
+        if requested.contains(&capability) {
+            ui.same_line_with_spacing(0.0, 10.0);
+            ui.text_colored([1.0, 0.8, 0.0, 1.0], "(requested)");
+        }
     }
 }
 
-fn draw_script_generated_tab<F>(ui: &cimgui::Ui, script_ui_draw: F)
+fn draw_script_generated_tab<F>(ui: &cimgui::Ui, layout: &mut WindowLayout, script_ui_draw: F)
 where
     F: FnOnce(&cimgui::Ui),
 {
-    if !ui.collapsing_header("Script Generated UI", TreeNodeFlags::empty()) {
+    if !collapsing_header(ui, "Script Generated UI", layout) {
         return;
     };
 
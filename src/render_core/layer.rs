@@ -0,0 +1,53 @@
+//! 绘制层
+//!
+//! 多个脚本的 on_draw 回调按绘制层和层内 z 值依次调用，后绘制的内容显示在上方，
+//! 避免多个叠加显示相互遮挡的顺序取决于脚本加载顺序。
+
+use serde::{Deserialize, Serialize};
+use strum::{EnumIter, EnumString, IntoStaticStr};
+
+use crate::config::Config;
+
+/// 绘制层，按声明顺序从下到上绘制
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    EnumIter,
+    EnumString,
+    IntoStaticStr,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum DrawLayer {
+    /// 背景，位于所有内容下方
+    Background,
+    /// 世界空间标记，如怪物血条
+    World,
+    /// 界面叠加显示，`core.on_draw` 注册的回调位于此层
+    Hud,
+    /// 调试信息，位于最上方
+    Debug,
+}
+
+/// 绘制层是否启用
+pub fn is_enabled(layer: DrawLayer) -> bool {
+    !Config::global().ui.disabled_draw_layers.contains(&layer)
+}
+
+/// 启用或禁用绘制层，保存到配置
+pub fn set_enabled(layer: DrawLayer, enabled: bool) {
+    let mut config = Config::global_mut();
+    let disabled = &mut config.ui.disabled_draw_layers;
+    disabled.retain(|l| *l != layer);
+    if !enabled {
+        disabled.push(layer);
+    }
+}
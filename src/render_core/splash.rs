@@ -0,0 +1,112 @@
+//! 启动状态浮窗
+//!
+//! 初始化期间及完成后的数秒内在游戏窗口角落显示加载状态，
+//! 无需打开控制台即可确认注入是否成功。
+
+use std::{
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+use cimgui::{Condition, WindowFlags};
+use parking_lot::Mutex;
+
+/// 初始化完成后的显示时长
+const DISPLAY_DURATION: Duration = Duration::from_secs(8);
+/// 存在错误时的显示时长
+const ERROR_DISPLAY_DURATION: Duration = Duration::from_secs(20);
+
+const COLOR_ERROR: [f32; 4] = [1.0, 0.3, 0.3, 1.0];
+
+#[derive(Debug, Default)]
+pub struct StartupStatus {
+    /// 初始化开始时间，用于判断错误是否发生在初始化期间
+    started_at: Option<Instant>,
+    finished_at: Option<Instant>,
+    scripts: usize,
+    extensions: usize,
+    failed_extensions: usize,
+    /// 初始化失败或加载过程中的最后错误
+    error: Option<String>,
+}
+
+static STARTUP_STATUS: LazyLock<Mutex<StartupStatus>> =
+    LazyLock::new(|| Mutex::new(StartupStatus::default()));
+
+/// 标记初始化开始
+pub fn mark_started() {
+    STARTUP_STATUS.lock().started_at = Some(Instant::now());
+}
+
+pub fn set_extensions(total: usize, success: usize) {
+    let mut status = STARTUP_STATUS.lock();
+    status.extensions = success;
+    status.failed_extensions = total - success;
+}
+
+pub fn set_scripts(count: usize) {
+    STARTUP_STATUS.lock().scripts = count;
+}
+
+/// 标记初始化结束，`error` 为初始化失败的原因
+pub fn mark_finished(error: Option<String>) {
+    let mut status = STARTUP_STATUS.lock();
+    // 脚本加载失败不会中断初始化，从最后错误中获取
+    let load_error = crate::error::get_last_error()
+        .filter(|e| status.started_at.is_some_and(|started| e.time >= started))
+        .map(|e| e.error);
+    status.error = error.or(load_error);
+    status.finished_at = Some(Instant::now());
+}
+
+/// 绘制启动状态浮窗，显示时长结束后不再绘制
+pub fn draw_startup_overlay(ui: &cimgui::Ui) {
+    let status = STARTUP_STATUS.lock();
+    let has_error = status.error.is_some() || status.failed_extensions > 0;
+    if let Some(finished_at) = status.finished_at {
+        let duration = if has_error {
+            ERROR_DISPLAY_DURATION
+        } else {
+            DISPLAY_DURATION
+        };
+        if finished_at.elapsed() > duration {
+            return;
+        }
+    }
+
+    let flags = WindowFlags::NO_DECORATION
+        | WindowFlags::ALWAYS_AUTO_RESIZE
+        | WindowFlags::NO_SAVED_SETTINGS
+        | WindowFlags::NO_FOCUS_ON_APPEARING
+        | WindowFlags::NO_NAV
+        | WindowFlags::NO_INPUTS;
+
+    let display_size = ui.io().display_size;
+    ui.window("##lua_framework_startup")
+        .flags(flags)
+        .position([10.0, display_size[1] - 10.0], Condition::Always)
+        .position_pivot([0.0, 1.0])
+        .bg_alpha(0.6)
+        .build(|| {
+            let version = env!("CARGO_PKG_VERSION");
+            if status.finished_at.is_none() {
+                ui.text(format!("LuaFramework v{} loading...", version));
+                return;
+            }
+
+            ui.text(format!(
+                "LuaFramework v{} loaded: {} scripts, {} extensions",
+                version, status.scripts, status.extensions
+            ));
+            if status.failed_extensions > 0 {
+                ui.text_colored(
+                    COLOR_ERROR,
+                    format!("{} extensions failed to load", status.failed_extensions),
+                );
+            }
+            if let Some(error) = &status.error {
+                ui.text_colored(COLOR_ERROR, error);
+                ui.text_disabled("See the console or log file for details.");
+            }
+        });
+}
@@ -0,0 +1,66 @@
+use cimgui::{Condition, WindowFlags};
+
+use crate::profiler::{FrameStats, Profiler};
+
+/// 绘制性能统计浮窗，独立于主窗口显示，不接收输入
+pub fn draw_stats_overlay(ui: &cimgui::Ui) {
+    let history = Profiler::instance().history();
+    let Some(last) = history.last().copied() else {
+        return;
+    };
+
+    let frame_times = history.iter().map(|s| s.frame_time_ms).collect::<Vec<_>>();
+    let lua_times = history.iter().map(|s| s.lua_time_ms).collect::<Vec<_>>();
+    let avg_frame_time = average(&frame_times);
+    let max_frame_time = frame_times.iter().copied().fold(0.0, f32::max);
+    let avg_lua_time = average(&lua_times);
+
+    let flags = WindowFlags::NO_DECORATION
+        | WindowFlags::ALWAYS_AUTO_RESIZE
+        | WindowFlags::NO_SAVED_SETTINGS
+        | WindowFlags::NO_FOCUS_ON_APPEARING
+        | WindowFlags::NO_NAV
+        | WindowFlags::NO_INPUTS;
+
+    ui.window("##lua_framework_stats")
+        .flags(flags)
+        .position([10.0, 10.0], Condition::FirstUseEver)
+        .bg_alpha(0.6)
+        .build(|| {
+            ui.text(format!(
+                "Frame: {:.2} ms (avg {:.2}, max {:.2})",
+                last.frame_time_ms, avg_frame_time, max_frame_time
+            ));
+            ui.plot_lines("##frame_time", &frame_times)
+                .graph_size([240.0, 50.0])
+                .scale_min(0.0)
+                .scale_max(max_frame_time.max(1.0))
+                .build();
+
+            ui.text(format!(
+                "Lua: {:.2} ms (avg {:.2})",
+                last.lua_time_ms, avg_lua_time
+            ));
+            ui.plot_lines("##lua_time", &lua_times)
+                .graph_size([240.0, 30.0])
+                .scale_min(0.0)
+                .build();
+
+            draw_frame_counters(ui, &last);
+        });
+}
+
+fn draw_frame_counters(ui: &cimgui::Ui, stats: &FrameStats) {
+    ui.text(format!("Hook dispatches: {}", stats.hook_dispatches));
+    ui.text(format!(
+        "Draw data: {} lists, {} vtx, {} idx",
+        stats.draw_lists, stats.vertices, stats.indices
+    ));
+}
+
+fn average(values: &[f32]) -> f32 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f32>() / values.len() as f32
+}
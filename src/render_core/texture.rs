@@ -0,0 +1,176 @@
+//! 纹理
+//!
+//! 图片在调用线程解码为 RGBA8，在下一帧渲染前由渲染后端扩展上传到 GPU，
+//! 上传完成前绘制该纹理不显示任何内容。释放也在渲染前进行，避免释放正在使用的纹理。
+
+use std::{collections::HashMap, ffi::c_void, path::Path, sync::LazyLock};
+
+use image::ImageReader;
+use parking_lot::Mutex;
+
+use crate::{error::Error, extension::CoreAPI};
+
+/// 渲染后端创建纹理，返回 ImTextureID，失败时返回 0
+type CreateTextureFn = extern "C" fn(data: *const u8, width: u32, height: u32) -> u64;
+type DestroyTextureFn = extern "C" fn(texture_id: u64);
+
+const CREATE_TEXTURE_FN: &str = "RenderCore::CreateTexture";
+const DESTROY_TEXTURE_FN: &str = "RenderCore::DestroyTexture";
+
+/// 纹理的最大边长
+const MAX_TEXTURE_SIZE: u32 = 4096;
+
+#[derive(Debug, Clone, Copy)]
+pub struct TextureInfo {
+    pub handle: u64,
+    pub width: u32,
+    pub height: u32,
+}
+
+enum TextureState {
+    /// 等待上传的像素数据
+    Pending(Vec<u8>),
+    Uploaded(u64),
+    Failed,
+}
+
+struct TextureEntry {
+    width: u32,
+    height: u32,
+    state: TextureState,
+}
+
+#[derive(Default)]
+struct TextureManagerInner {
+    textures: HashMap<u64, TextureEntry>,
+    /// 等待释放的 ImTextureID
+    pending_release: Vec<u64>,
+    next_handle: u64,
+}
+
+pub struct TextureManager {
+    inner: Mutex<TextureManagerInner>,
+}
+
+impl TextureManager {
+    pub fn instance() -> &'static TextureManager {
+        static INSTANCE: LazyLock<TextureManager> = LazyLock::new(|| TextureManager {
+            inner: Mutex::new(TextureManagerInner {
+                next_handle: 1,
+                ..Default::default()
+            }),
+        });
+        &INSTANCE
+    }
+
+    /// 渲染后端是否支持上传纹理
+    pub fn is_available() -> bool {
+        create_texture_fn().is_some() && destroy_texture_fn().is_some()
+    }
+
+    /// 加载 PNG 或 DDS 图片，返回的纹理在下一帧渲染前上传
+    pub fn load(&self, path: &Path) -> Result<TextureInfo, Error> {
+        if !Self::is_available() {
+            return Err(Error::TextureUnavailable);
+        }
+
+        let decode_error = |e: &dyn std::fmt::Display| {
+            Error::ImageDecode(path.display().to_string(), e.to_string())
+        };
+        let open = || {
+            ImageReader::open(path)
+                .map_err(|e| decode_error(&e))?
+                .with_guessed_format()
+                .map_err(|e| decode_error(&e))
+        };
+
+        // 解码前检查尺寸，避免为超大图片分配内存
+        let (width, height) = open()?.into_dimensions().map_err(|e| decode_error(&e))?;
+        if width == 0 || height == 0 || width > MAX_TEXTURE_SIZE || height > MAX_TEXTURE_SIZE {
+            return Err(Error::InvalidValue(
+                "image size up to 4096x4096",
+                format!("{}x{}", width, height),
+            ));
+        }
+        let image = open()?.decode().map_err(|e| decode_error(&e))?.to_rgba8();
+        let (width, height) = image.dimensions();
+
+        let mut inner = self.inner.lock();
+        let handle = inner.next_handle;
+        inner.next_handle += 1;
+        inner.textures.insert(
+            handle,
+            TextureEntry {
+                width,
+                height,
+                state: TextureState::Pending(image.into_raw()),
+            },
+        );
+
+        Ok(TextureInfo {
+            handle,
+            width,
+            height,
+        })
+    }
+
+    /// 释放纹理，已上传的纹理在下一帧渲染前释放
+    pub fn release(&self, handle: u64) {
+        let mut inner = self.inner.lock();
+        if let Some(TextureEntry {
+            state: TextureState::Uploaded(id),
+            ..
+        }) = inner.textures.remove(&handle)
+        {
+            inner.pending_release.push(id);
+        }
+    }
+
+    /// 已上传纹理的 ImTextureID
+    pub fn texture_id(&self, handle: u64) -> Option<u64> {
+        match self.inner.lock().textures.get(&handle)?.state {
+            TextureState::Uploaded(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// 上传和释放纹理，在渲染线程中调用
+    pub(super) fn process_pending(&self) {
+        let mut inner = self.inner.lock();
+        if !inner.pending_release.is_empty() {
+            if let Some(destroy_texture) = destroy_texture_fn() {
+                for id in inner.pending_release.drain(..) {
+                    destroy_texture(id);
+                }
+            }
+        }
+
+        let Some(create_texture) = create_texture_fn() else {
+            return;
+        };
+        for (handle, entry) in inner.textures.iter_mut() {
+            let TextureState::Pending(data) = &entry.state else {
+                continue;
+            };
+            let id = create_texture(data.as_ptr(), entry.width, entry.height);
+            entry.state = if id != 0 {
+                TextureState::Uploaded(id)
+            } else {
+                log::error!("Failed to upload texture {}", handle);
+                TextureState::Failed
+            };
+        }
+    }
+}
+
+fn create_texture_fn() -> Option<CreateTextureFn> {
+    CoreAPI::instance()
+        .get_function(CREATE_TEXTURE_FN)
+        .map(|f| unsafe { std::mem::transmute::<*const c_void, CreateTextureFn>(f) })
+}
+
+fn destroy_texture_fn() -> Option<DestroyTextureFn> {
+    CoreAPI::instance()
+        .get_function(DESTROY_TEXTURE_FN)
+        .map(|f| unsafe { std::mem::transmute::<*const c_void, DestroyTextureFn>(f) })
+}
@@ -1,5 +1,6 @@
 use crate::error::Error;
-use std::ffi::CStr;
+use crate::memory::MemoryUtils;
+use std::sync::OnceLock;
 use windows::Win32::Foundation::HWND;
 use windows::Win32::UI::WindowsAndMessaging::{
     FindWindowW, GetForegroundWindow, SetForegroundWindow,
@@ -9,6 +10,8 @@ use windows::{
     core::PCWSTR,
 };
 
+pub mod process;
+
 /// 将字符串转换为 UTF16-LE 字节数组，有 \0 结尾
 pub fn to_wstring_bytes_with_nul(s: &str) -> Vec<u16> {
     s.encode_utf16().chain(Some(0)).collect()
@@ -42,7 +45,7 @@ pub fn set_game_foreground() -> bool {
         let game_hwnd = match get_game_window_handle() {
             Ok(hwnd) => hwnd,
             Err(e) => {
-                log::error!("Failed to get game window handle: {}", e);
+                log::error!("Failed to get game window handle: {}", e.log());
                 return false;
             }
         };
@@ -50,13 +53,44 @@ pub fn set_game_foreground() -> bool {
     }
 }
 
-/// 获取游戏版本号
+/// 获取游戏版本号，只在首次调用时读取
 pub fn get_game_revision() -> Option<u32> {
-    let singleton_manager = crate::game::singleton::SingletonManager::instance();
-    let revision_ptr = singleton_manager.get_ptr("static:GameRevisionStr")?;
-    let revision_str = unsafe { CStr::from_ptr(revision_ptr as *const _) };
+    /// 版本号字符串最多读取的字节数
+    const MAX_REVISION_LEN: usize = 32;
+    static REVISION: OnceLock<Option<u32>> = OnceLock::new();
+
+    *REVISION.get_or_init(|| {
+        let singleton_manager = crate::game::singleton::SingletonManager::instance();
+        let revision_ptr = singleton_manager.get_address("static:GameRevisionStr")?;
+        let bytes = MemoryUtils::read_c_string(revision_ptr, MAX_REVISION_LEN).ok()?;
+        let revision = std::str::from_utf8(&bytes).ok()?.parse::<u32>().ok();
+        if revision.is_none() {
+            log::warn!("Failed to parse game revision: {:?}", bytes);
+        }
+        revision
+    })
+}
+
+/// 检查当前游戏版本是否在 `revisions` 中，`revisions` 为空时不限制
+pub fn check_game_revision(target: &str, revisions: &[u32]) -> Result<(), Error> {
+    if revisions.is_empty() {
+        return Ok(());
+    }
+
+    let current = get_game_revision();
+    if current.is_some_and(|current| revisions.contains(&current)) {
+        return Ok(());
+    }
 
-    revision_str.to_str().ok()?.parse::<u32>().ok()
+    Err(Error::GameRevisionMismatch(
+        target.to_string(),
+        revisions
+            .iter()
+            .map(|r| r.to_string())
+            .collect::<Vec<_>>()
+            .join("/"),
+        current.map_or_else(|| "unknown".to_string(), |r| r.to_string()),
+    ))
 }
 
 /// 获取游戏窗口标题名
@@ -65,7 +99,7 @@ fn get_game_window_title() -> Option<String> {
 }
 
 /// 获取游戏窗口句柄
-fn get_game_window_handle() -> Result<HWND, Error> {
+pub fn get_game_window_handle() -> Result<HWND, Error> {
     const CLASS_NAME: &str = "MT FRAMEWORK";
     let title = get_game_window_title().ok_or(Error::GameWindowNotFound)?;
 
@@ -0,0 +1,173 @@
+//! 进程模块信息与版本指纹
+//!
+//! 指纹由游戏版本号、PE 时间戳和映像大小组成，同一版本号的不同构建（如不同平台的补丁）
+//! 也能区分，供脚本按构建启用功能，并写入诊断报告。
+
+use std::{collections::BTreeMap, ffi::c_void, sync::OnceLock};
+
+use serde::Serialize;
+use windows::{
+    Win32::{
+        Storage::FileSystem::{GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW},
+        System::LibraryLoader::GetModuleFileNameW,
+    },
+    core::PCWSTR,
+};
+
+use super::{get_game_revision, to_wstring_bytes_with_nul};
+use crate::{error::Result, memory::MemoryUtils};
+
+/// 读取的版本资源字符串
+const VERSION_STRINGS: &[(&str, &str)] = &[
+    ("FileVersion", "file_version"),
+    ("ProductVersion", "product_version"),
+    ("ProductName", "product_name"),
+    ("CompanyName", "company_name"),
+    ("FileDescription", "file_description"),
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessInfo {
+    pub exe_path: String,
+    pub base_address: usize,
+    pub image_size: usize,
+    /// PE 文件头中的链接时间戳
+    pub timestamp: u32,
+    pub revision: Option<u32>,
+    /// 版本资源字符串，键为 snake_case 名称
+    pub version: BTreeMap<&'static str, String>,
+    pub fingerprint: String,
+}
+
+static PROCESS_INFO: OnceLock<ProcessInfo> = OnceLock::new();
+
+/// 获取进程信息
+///
+/// 游戏版本号需要在单例解析后才能获取，获取到版本号后缓存结果。
+pub fn process_info() -> Result<ProcessInfo> {
+    if let Some(info) = PROCESS_INFO.get() {
+        return Ok(info.clone());
+    }
+    let info = read_process_info()?;
+    if info.revision.is_some() {
+        let _ = PROCESS_INFO.set(info.clone());
+    }
+    Ok(info)
+}
+
+/// 已缓存的版本指纹，用于崩溃处理等不应读取内存的场合
+pub fn cached_fingerprint() -> Option<&'static str> {
+    PROCESS_INFO.get().map(|info| info.fingerprint.as_str())
+}
+
+fn read_process_info() -> Result<ProcessInfo> {
+    let (base_address, image_size) = MemoryUtils::base_module_space()?;
+    let timestamp = unsafe { read_pe_timestamp(base_address) };
+    let exe_path = exe_path();
+    let revision = get_game_revision();
+    let fingerprint = format!(
+        "{}-{:08X}-{:X}",
+        revision.map_or_else(|| "unknown".to_string(), |r| r.to_string()),
+        timestamp,
+        image_size
+    );
+
+    Ok(ProcessInfo {
+        version: read_version_strings(&exe_path),
+        exe_path,
+        base_address,
+        image_size,
+        timestamp,
+        revision,
+        fingerprint,
+    })
+}
+
+/// 读取已加载映像的 IMAGE_FILE_HEADER.TimeDateStamp
+pub(crate) unsafe fn read_pe_timestamp(base: usize) -> u32 {
+    unsafe {
+        // IMAGE_DOS_HEADER.e_lfanew
+        let nt_offset = *((base + 0x3C) as *const u32) as usize;
+        // Signature (4) + Machine (2) + NumberOfSections (2)
+        *((base + nt_offset + 8) as *const u32)
+    }
+}
+
+fn exe_path() -> String {
+    let mut buf = vec![0u16; 1024];
+    let len = unsafe { GetModuleFileNameW(None, &mut buf) } as usize;
+    String::from_utf16_lossy(&buf[..len])
+}
+
+fn read_version_strings(exe_path: &str) -> BTreeMap<&'static str, String> {
+    let path_w = to_wstring_bytes_with_nul(exe_path);
+    let size = unsafe { GetFileVersionInfoSizeW(PCWSTR(path_w.as_ptr()), None) };
+    if size == 0 {
+        return BTreeMap::new();
+    }
+    let mut data = vec![0u8; size as usize];
+    if unsafe {
+        GetFileVersionInfoW(
+            PCWSTR(path_w.as_ptr()),
+            None,
+            size,
+            data.as_mut_ptr() as *mut c_void,
+        )
+    }
+    .is_err()
+    {
+        return BTreeMap::new();
+    }
+
+    // 使用第一个语言和代码页
+    let Some(translation) = query_value(&data, "\\VarFileInfo\\Translation") else {
+        return BTreeMap::new();
+    };
+    if translation.len() < 4 {
+        return BTreeMap::new();
+    }
+    let language = u16::from_le_bytes([translation[0], translation[1]]);
+    let codepage = u16::from_le_bytes([translation[2], translation[3]]);
+
+    VERSION_STRINGS
+        .iter()
+        .filter_map(|(name, key)| {
+            let sub_block = format!(
+                "\\StringFileInfo\\{:04x}{:04x}\\{}",
+                language, codepage, name
+            );
+            let value = query_value(&data, &sub_block)?;
+            let units = value
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .take_while(|&c| c != 0)
+                .collect::<Vec<_>>();
+            Some((*key, String::from_utf16_lossy(&units)))
+        })
+        .collect()
+}
+
+/// 查询版本资源，字符串值的长度以字符计，按字节返回
+fn query_value<'a>(data: &'a [u8], sub_block: &str) -> Option<&'a [u8]> {
+    let sub_block_w = to_wstring_bytes_with_nul(sub_block);
+    let mut ptr: *mut c_void = std::ptr::null_mut();
+    let mut len = 0u32;
+    let ok = unsafe {
+        VerQueryValueW(
+            data.as_ptr() as *const c_void,
+            PCWSTR(sub_block_w.as_ptr()),
+            &mut ptr,
+            &mut len,
+        )
+    };
+    if !ok.as_bool() || ptr.is_null() {
+        return None;
+    }
+
+    let byte_len = if sub_block.starts_with("\\StringFileInfo") {
+        len as usize * 2
+    } else {
+        len as usize
+    };
+    Some(unsafe { std::slice::from_raw_parts(ptr as *const u8, byte_len) })
+}